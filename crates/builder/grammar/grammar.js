@@ -43,6 +43,7 @@ module.exports = grammar({
     ),
     module_field: $ => choice(
       $.type,
+      $.enum,
       $.module_method,
       $.interceptor
     ),
@@ -77,6 +78,14 @@ module.exports = grammar({
       field("body", $.type_body)
     ),
     type_body: $ => seq("{", repeat(field("field", $.field)), "}"),
+    enum: $ => seq(
+      "enum",
+      field("name", $.term),
+      "{",
+      optional(seq(commaSep(field("value", $.enum_value)), optional(","))),
+      "}"
+    ),
+    enum_value: $ => field("name", $.term),
     annotation: $ => seq(
       "@",
       field("name", $.term),
@@ -92,7 +101,11 @@ module.exports = grammar({
     ),
     annotation_multiple_params: $ => commaSep(field("exprs", $.expression)),
     annotation_map_params: $ => commaSep(field("param", $.annotation_map_param)),
-    annotation_map_param: $ => seq(field("name", $.term), "=", field("expr", $.expression)),
+    annotation_map_param: $ => seq(
+      field("name", $.term),
+      "=",
+      field("expr", choice($.expression, $.literal_str_list))
+    ),
     argument: $ => seq(
       repeat(field("annotation", $.annotation)),
       field("name", $.term),
@@ -227,6 +240,7 @@ module.exports = grammar({
     str: $ => /(?:[^"\\]|\\.)*/, // string with escaped quotes
     number: $ => /\d+/,
     literal_str: $ => seq("\"", field("value", $.str), "\""),
+    literal_str_list: $ => seq("[", commaSep(field("value", $.literal_str)), "]"), // ["title", "body"]
     literal_boolean: $ => choice("true", "false"),
     literal_number: $ => field("value", $.number),
     literal_null: $ => "null",
@@ -21,9 +21,9 @@ use tree_sitter_c2rust::{Node, Tree, TreeCursor};
 
 use super::{sitter_ffi, span_from_node};
 use crate::ast::ast_types::{
-    AstAnnotation, AstAnnotationParams, AstArgument, AstExpr, AstField, AstFieldDefault,
-    AstFieldDefaultKind, AstFieldType, AstInterceptor, AstMethod, AstModel, AstModelKind,
-    AstModule, AstSystem, FieldSelection, LogicalOp, RelationalOp, Untyped,
+    AstAnnotation, AstAnnotationParams, AstArgument, AstEnum, AstEnumValue, AstExpr, AstField,
+    AstFieldDefault, AstFieldDefaultKind, AstFieldType, AstInterceptor, AstMethod, AstModel,
+    AstModelKind, AstModule, AstSystem, FieldSelection, LogicalOp, RelationalOp, Untyped,
 };
 use crate::error::ParserError;
 
@@ -245,6 +245,9 @@ fn convert_module(
         interceptors: matching_nodes(node, &mut node.walk(), "interceptor")
             .map(|n| convert_interceptor(n, source, source_span))
             .collect(),
+        enums: matching_nodes(node, &mut node.walk(), "enum")
+            .map(|n| convert_enum(n, source, source_span))
+            .collect(),
         annotations,
         base_exofile: filepath.into(),
         span: span_from_node(source_span, node),
@@ -297,6 +300,22 @@ fn convert_interceptor(node: Node, source: &[u8], source_span: Span) -> AstInter
     }
 }
 
+fn convert_enum(node: Node, source: &[u8], source_span: Span) -> AstEnum {
+    let mut cursor = node.walk();
+
+    AstEnum {
+        name: text_child(node, source, "name"),
+        values: node
+            .children_by_field_name("value", &mut cursor)
+            .map(|c| AstEnumValue {
+                name: text_child(c, source, "name"),
+                span: span_from_node(source_span, c),
+            })
+            .collect(),
+        span: span_from_node(source_span, node),
+    }
+}
+
 fn convert_fields(node: Node, source: &[u8], source_span: Span) -> Vec<AstField<Untyped>> {
     let mut cursor = node.walk();
     node.children_by_field_name("field", &mut cursor)
@@ -474,14 +493,15 @@ fn convert_annotation_params(
             let exprs = params
                 .iter()
                 .map(|(name, p)| {
-                    (
-                        name.clone(),
-                        convert_expression(
-                            p.child_by_field_name("expr").unwrap(),
-                            source,
-                            source_span,
-                        ),
-                    )
+                    let expr = p.child_by_field_name("expr").unwrap();
+
+                    let expr = if expr.kind() == "literal_str_list" {
+                        convert_str_list(expr, source, source_span)
+                    } else {
+                        convert_expression(expr, source, source_span)
+                    };
+
+                    (name.clone(), expr)
                 })
                 .collect();
 
@@ -503,6 +523,22 @@ fn convert_annotation_params(
     }
 }
 
+fn convert_str_list(node: Node, source: &[u8], source_span: Span) -> AstExpr<Untyped> {
+    let mut cursor = node.walk();
+
+    let (strings, spans) = node
+        .children_by_field_name("value", &mut cursor)
+        .map(|value| {
+            (
+                text_child(value, source, "value"),
+                span_from_node(source_span, value.child_by_field_name("value").unwrap()),
+            )
+        })
+        .unzip();
+
+    AstExpr::StringList(strings, spans)
+}
+
 fn convert_literal(node: Node, source: &[u8], source_span: Span) -> AstExpr<Untyped> {
     let first_child = node.child(0).unwrap();
 
@@ -11,6 +11,7 @@ use std::collections::HashMap;
 
 use codemap_diagnostic::{Diagnostic, Level, SpanLabel, SpanStyle};
 use core_model::mapped_arena::MappedArena;
+use core_model::primitive_type::PrimitiveType;
 use core_model_builder::typechecker::annotation::{AnnotationSpec, AnnotationTarget};
 use core_model_builder::typechecker::annotation_map::AnnotationMap;
 use core_model_builder::typechecker::Typed;
@@ -83,8 +84,36 @@ impl TypecheckFrom<AstField<Untyped>> for AstField<Typed> {
                 }
             };
 
+            // A string literal is the only valid default value for an enum field
+            let enum_type = match type_env.get_by_key(&type_name) {
+                Some(Type::Primitive(PrimitiveType::Enum(enum_type))) => Some(enum_type),
+                _ => None,
+            };
+
             match *expr {
-                AstExpr::StringLiteral(_, _) => assert_type(&["String"]),
+                AstExpr::StringLiteral(ref value, _) => match enum_type {
+                    Some(enum_type) => {
+                        if !enum_type.values.contains(value) {
+                            errors.push(Diagnostic {
+                                level: Level::Error,
+                                message: format!(
+                                    "Default value `{value}` is not a value of the enum `{}`",
+                                    enum_type.name
+                                ),
+                                code: Some("C000".to_string()),
+                                spans: vec![SpanLabel {
+                                    span: expr.span(),
+                                    style: SpanStyle::Primary,
+                                    label: Some(format!(
+                                        "should be one of {}",
+                                        enum_type.values.join(", ")
+                                    )),
+                                }],
+                            });
+                        }
+                    }
+                    None => assert_type(&["String"]),
+                },
                 AstExpr::BooleanLiteral(_, _) => assert_type(&["Boolean"]),
                 AstExpr::NumberLiteral(_, _) => assert_type(&["Int", "Float"]),
                 AstExpr::FieldSelection(_) => {
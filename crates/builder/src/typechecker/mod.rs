@@ -11,7 +11,10 @@ use std::{collections::HashMap, vec};
 
 use codemap::Span;
 use codemap_diagnostic::{Diagnostic, Level, SpanLabel, SpanStyle};
-use core_model::{mapped_arena::MappedArena, primitive_type::PrimitiveType};
+use core_model::{
+    mapped_arena::MappedArena,
+    primitive_type::{EnumType, PrimitiveType},
+};
 use core_model_builder::{
     ast::ast_types::{AstModel, AstModelKind, AstModule, AstSystem, Untyped},
     typechecker::{
@@ -130,6 +133,28 @@ fn populate_annotation_env(
                 ]),
             },
         ),
+        (
+            "cache",
+            AnnotationSpec {
+                targets: &[
+                    AnnotationTarget::Type,
+                    AnnotationTarget::Field,
+                    AnnotationTarget::Method,
+                ],
+                no_params: false,
+                single_params: true,
+                mapped_params: Some(&[
+                    MappedAnnotationParamSpec {
+                        name: "maxAge",
+                        optional: false,
+                    },
+                    MappedAnnotationParamSpec {
+                        name: "scope",
+                        optional: true,
+                    },
+                ]),
+            },
+        ),
         (
             "cookie",
             AnnotationSpec {
@@ -261,6 +286,43 @@ pub fn build(
         validate_module(module)?;
     }
 
+    // Enums are primitive types (their values are stored and exchanged as strings), so register
+    // them along with the built-in primitives before typechecking any fields that refer to them
+    for module in ast_system.modules.iter() {
+        for ast_enum in module.enums.iter() {
+            let clashes_with_type = types_arena.get_id(&ast_enum.name).is_some()
+                || ast_module_types
+                    .iter()
+                    .chain(ast_system.types.iter())
+                    .any(|ast_type| ast_type.name == ast_enum.name);
+
+            if clashes_with_type {
+                return Err(ParserError::Diagnosis(vec![Diagnostic {
+                    level: Level::Error,
+                    message: format!("Duplicate type: {}", ast_enum.name),
+                    code: Some("C000".to_string()),
+                    spans: vec![SpanLabel {
+                        span: ast_enum.span,
+                        style: SpanStyle::Primary,
+                        label: Some("enum with the same name as another type".to_string()),
+                    }],
+                }]));
+            }
+
+            types_arena.add(
+                &ast_enum.name,
+                Type::Primitive(PrimitiveType::Enum(EnumType {
+                    name: ast_enum.name.clone(),
+                    values: ast_enum
+                        .values
+                        .iter()
+                        .map(|value| value.name.clone())
+                        .collect(),
+                })),
+            );
+        }
+    }
+
     let ast_types_iter = ast_system.types.iter().chain(ast_module_types.iter());
     let ast_root_types = &ast_system.types;
 
@@ -383,6 +445,35 @@ fn validate_module(module: &AstModule<Untyped>) -> Result<(), ParserError> {
         "model/type",
     ))?;
 
+    process_err(validate_no_duplicates(
+        &module.enums,
+        |ast_enum| &ast_enum.name,
+        |ast_enum| ast_enum.span,
+        "enum",
+    ))?;
+
+    for ast_enum in module.enums.iter() {
+        if ast_enum.values.is_empty() {
+            process_err(Err(ParserError::Diagnosis(vec![Diagnostic {
+                level: Level::Error,
+                message: format!("Enum `{}` must have at least one value", ast_enum.name),
+                code: Some("C000".to_string()),
+                spans: vec![SpanLabel {
+                    span: ast_enum.span,
+                    style: SpanStyle::Primary,
+                    label: None,
+                }],
+            }])))?;
+        }
+
+        process_err(validate_no_duplicates(
+            &ast_enum.values,
+            |value| &value.name,
+            |value| value.span,
+            "enum value",
+        ))?;
+    }
+
     // iterate over module.types and validate that all fields in each type is unique
     for model in module.types.iter() {
         process_err(validate_no_duplicates(
@@ -926,6 +1017,80 @@ mod tests {
         assert_err(model);
     }
 
+    #[multiplatform_test]
+    fn enum_field() {
+        let model = r#"
+        @postgres
+        module BlogModule {
+            enum Status {
+                DRAFT,
+                PUBLISHED
+            }
+
+            type Post {
+                @pk id: Int = autoIncrement()
+                status: Status = "DRAFT"
+            }
+        }
+        "#;
+
+        assert!(build(model).is_ok());
+    }
+
+    #[multiplatform_test]
+    fn enum_with_duplicate_values() {
+        let model = r#"
+        @postgres
+        module BlogModule {
+            enum Status {
+                DRAFT,
+                DRAFT
+            }
+        }
+        "#;
+
+        assert_err(model);
+    }
+
+    #[multiplatform_test]
+    fn enum_and_type_with_same_name() {
+        let model = r#"
+        @postgres
+        module BlogModule {
+            enum Status {
+                DRAFT,
+                PUBLISHED
+            }
+
+            type Status {
+                @pk id: Int = autoIncrement()
+            }
+        }
+        "#;
+
+        assert_err(model);
+    }
+
+    #[multiplatform_test]
+    fn enum_with_invalid_default_value() {
+        let model = r#"
+        @postgres
+        module BlogModule {
+            enum Status {
+                DRAFT,
+                PUBLISHED
+            }
+
+            type Post {
+                @pk id: Int = autoIncrement()
+                status: Status = "ARCHIVED"
+            }
+        }
+        "#;
+
+        assert_err(model);
+    }
+
     fn assert_err(src: &str) {
         assert!(build(src).is_err());
     }
@@ -36,6 +36,7 @@ impl TypecheckFrom<AstModule<Untyped>> for AstModule<Typed> {
             types: typed(&untyped.types),
             methods: typed(&untyped.methods),
             interceptors: typed(&untyped.interceptors),
+            enums: untyped.enums.clone(),
             annotations: annotation_map,
             base_exofile: untyped.base_exofile.clone(),
             span: untyped.span,
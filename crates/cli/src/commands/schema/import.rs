@@ -12,8 +12,10 @@ use async_trait::async_trait;
 use clap::Command;
 use exo_sql::schema::column_spec::{ColumnSpec, ColumnTypeSpec};
 use exo_sql::schema::database_spec::DatabaseSpec;
+use exo_sql::schema::enum_spec::EnumSpec;
 use exo_sql::schema::issue::WithIssues;
 use exo_sql::schema::table_spec::TableSpec;
+use exo_sql::TableKind;
 use std::fmt::Write;
 use std::path::PathBuf;
 
@@ -82,7 +84,11 @@ impl ToModel for DatabaseSpec {
     /// Converts the schema specification to a exograph file.
     fn to_model(&self) -> WithIssues<String> {
         let mut issues = Vec::new();
-        let stmt = self.tables.iter().fold(String::new(), |mut acc, table| {
+        let enum_stmts = self.enums.iter().fold(String::new(), |mut acc, enum_spec| {
+            let _ = write!(acc, "{}\n\n", enum_spec.to_model().value);
+            acc
+        });
+        let stmt = self.tables.iter().fold(enum_stmts, |mut acc, table| {
             let mut model = table.to_model();
             issues.append(&mut model.issues);
             let _ = write!(acc, "{}\n\n", model.value);
@@ -96,22 +102,110 @@ impl ToModel for DatabaseSpec {
     }
 }
 
+impl ToModel for EnumSpec {
+    /// Converts the enum specification to a exograph enum.
+    fn to_model(&self) -> WithIssues<String> {
+        let values = self
+            .values
+            .iter()
+            .map(|value| format!("  {value}"))
+            .collect::<Vec<_>>()
+            .join(",\n");
+
+        WithIssues {
+            value: format!("enum {} {{\n{}\n}}", to_model_name(&self.name), values),
+            issues: Vec::new(),
+        }
+    }
+}
+
 impl ToModel for TableSpec {
     /// Converts the table specification to a exograph model.
     fn to_model(&self) -> WithIssues<String> {
         let mut issues = Vec::new();
 
+        let annot_name = match self.kind {
+            TableKind::Table => "table",
+            TableKind::View => "view",
+            TableKind::MaterializedView => "materializedView",
+        };
         let table_annot = match &self.name.schema {
-            Some(schema) => format!("@table(name=\"{}\", schema=\"{}\")", self.name.name, schema),
-            None => format!("@table(\"{}\")", self.name.name),
+            Some(schema) => format!(
+                "@{annot_name}(name=\"{}\", schema=\"{}\")",
+                self.name.name, schema
+            ),
+            None => format!("@{annot_name}(\"{}\")", self.name.name),
         };
         let column_stmts = self.columns.iter().fold(String::new(), |mut acc, c| {
-            let mut model = c.to_model();
+            let mut model = match &c.typ {
+                // A foreign key over a composite primary key is a single field, so emit it once for
+                // the first column of the group
+                ColumnTypeSpec::ColumnReference {
+                    group_name: Some(group_name),
+                    foreign_pk_type,
+                    ..
+                } => {
+                    let group_columns = self.foreign_key_group_columns(group_name);
+                    let is_first_column = group_columns.first() == Some(&c.name);
+
+                    match composite_foreign_key_field_name(self, group_name) {
+                        Some(name) if is_first_column => ColumnSpec { name, ..c.clone() }.to_model(),
+                        Some(_) => return acc,
+                        // The model can't map these columns (for example, `tenant_id` and
+                        // `venue_id`), so import them as plain fields
+                        None => {
+                            if is_first_column {
+                                issues.push(Issue::Warning(format!(
+                                    "foreign key `{group_name}` of `{}` imported as plain fields, since its columns ({}) aren't named `<field>_<referenced column>`",
+                                    self.name.fully_qualified_name(),
+                                    group_columns
+                                        .iter()
+                                        .map(|column| format!("`{column}`"))
+                                        .collect::<Vec<_>>()
+                                        .join(", ")
+                                )));
+                            }
+                            ColumnSpec {
+                                typ: (**foreign_pk_type).clone(),
+                                ..c.clone()
+                            }
+                            .to_model()
+                        }
+                    }
+                }
+                // Represented by the model's `@fullTextSearch` annotation
+                ColumnTypeSpec::TextSearchVector { .. } => return acc,
+                _ => c.to_model(),
+            };
             issues.append(&mut model.issues);
             let _ = writeln!(acc, "  {}", model.value);
             acc
         });
 
+        let full_text_search_annot = self
+            .columns
+            .iter()
+            .find_map(|c| match &c.typ {
+                ColumnTypeSpec::TextSearchVector { language, columns } => Some(format!(
+                    "\n@fullTextSearch(fields=[{}], language=\"{language}\")",
+                    columns
+                        .iter()
+                        .map(|column| format!("\"{column}\""))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )),
+                _ => None,
+            })
+            .unwrap_or_default();
+
+        // Views have no primary key constraint to import
+        if self.kind.is_view() && !self.columns.iter().any(|c| c.is_pk) {
+            issues.push(Issue::Hint(format!(
+                "view `{}` should have a field marked with `@pk`",
+                self.name.fully_qualified_name()
+            )));
+        }
+
         // not a robust check
         if self.name.name.ends_with('s') {
             issues.push(Issue::Hint(format!(
@@ -122,8 +216,9 @@ impl ToModel for TableSpec {
 
         WithIssues {
             value: format!(
-                "{}\nmodel {} {{\n{}}}",
+                "{}{}\nmodel {} {{\n{}}}",
                 table_annot,
+                full_text_search_annot,
                 to_model_name(&self.name.name),
                 column_stmts
             ),
@@ -132,6 +227,36 @@ impl ToModel for TableSpec {
     }
 }
 
+/// The name of the field for a foreign key over a composite primary key (for example, `venue`
+/// for `venue_tenant_id` and `venue_id` referencing `tenant_id` and `id`).
+///
+/// The model derives the columns of such a field as `<field>_<referenced column>`, so this is
+/// `None` if the columns don't follow that convention.
+fn composite_foreign_key_field_name(table: &TableSpec, group_name: &str) -> Option<String> {
+    let references = table
+        .columns
+        .iter()
+        .filter_map(|c| match &c.typ {
+            ColumnTypeSpec::ColumnReference {
+                group_name: Some(name),
+                foreign_pk_column_name,
+                ..
+            } if name == group_name => Some((&c.name, foreign_pk_column_name)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let (column_name, foreign_pk_column_name) = references.first()?;
+    let field_name = column_name.strip_suffix(&format!("_{foreign_pk_column_name}"))?;
+
+    references
+        .iter()
+        .all(|(column_name, foreign_pk_column_name)| {
+            **column_name == format!("{field_name}_{foreign_pk_column_name}")
+        })
+        .then(|| field_name.to_string())
+}
+
 impl ToModel for ColumnSpec {
     /// Converts the column specification to a exograph model.
     fn to_model(&self) -> WithIssues<String> {
@@ -156,6 +281,9 @@ impl ToModel for ColumnSpec {
                 foreign_table_name.fully_qualified_name(), to_model_name(&self.name),
             )));
         }
+        if let Some(enum_name) = self.typ.enum_name() {
+            data_type = data_type.replace(enum_name, &to_model_name(enum_name));
+        }
 
         if self.is_nullable {
             data_type += "?"
@@ -170,3 +298,135 @@ impl ToModel for ColumnSpec {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use exo_sql::{IntBits, PhysicalTableName};
+
+    use super::*;
+
+    #[test]
+    fn composite_foreign_key() {
+        let venue_reference = |pk_column_name: &str| ColumnTypeSpec::ColumnReference {
+            foreign_table_name: PhysicalTableName::new("venues", None),
+            foreign_pk_column_name: pk_column_name.to_string(),
+            foreign_pk_type: Box::new(ColumnTypeSpec::Int { bits: IntBits::_32 }),
+            group_name: Some("concerts_venue_fk".to_string()),
+        };
+        let concerts_model = |tenant_column_name: &str, venue_column_name: &str| {
+            let model = TableSpec::new(
+                PhysicalTableName::new("concert", None),
+                vec![
+                    column("id", ColumnTypeSpec::Int { bits: IntBits::_32 }, true),
+                    column(tenant_column_name, venue_reference("tenant_id"), false),
+                    column(venue_column_name, venue_reference("id"), false),
+                ],
+                vec![],
+                vec![],
+            )
+            .to_model();
+
+            (
+                model.value,
+                model
+                    .issues
+                    .iter()
+                    .map(|issue| issue.to_string())
+                    .collect::<Vec<_>>(),
+            )
+        };
+
+        // A single field for both columns (whose names the model derives from the field's name)
+        assert_eq!(
+            concerts_model("venue_tenant_id", "venue_id"),
+            (
+                "@table(\"concert\")\nmodel Concert {\n  id: Int @pk\n  venue: Venues\n}"
+                    .to_string(),
+                vec![]
+            )
+        );
+        // Columns that the model can't derive from a field name
+        assert_eq!(
+            concerts_model("tenant_id", "venue_id"),
+            (
+                "@table(\"concert\")\nmodel Concert {\n  id: Int @pk\n  tenant_id: Int\n  venue_id: Int\n}"
+                    .to_string(),
+                vec!["warning: foreign key `concerts_venue_fk` of `concert` imported as plain fields, since its columns (`tenant_id`, `venue_id`) aren't named `<field>_<referenced column>`".to_string()]
+            )
+        );
+        assert_eq!(
+            concerts_model("venue_tenant", "venue_id").1,
+            vec!["warning: foreign key `concerts_venue_fk` of `concert` imported as plain fields, since its columns (`venue_tenant`, `venue_id`) aren't named `<field>_<referenced column>`".to_string()]
+        );
+    }
+
+    #[test]
+    fn views() {
+        let view = |name: PhysicalTableName, kind: TableKind, is_pk: bool| {
+            let model = TableSpec {
+                kind,
+                ..TableSpec::new(
+                    name,
+                    vec![
+                        column("id", ColumnTypeSpec::Int { bits: IntBits::_32 }, is_pk),
+                        column("name", ColumnTypeSpec::String { max_length: None }, false),
+                    ],
+                    vec![],
+                    vec![],
+                )
+            }
+            .to_model();
+
+            (
+                model.value,
+                model
+                    .issues
+                    .iter()
+                    .map(|issue| issue.to_string())
+                    .collect::<Vec<_>>(),
+            )
+        };
+
+        // Views have no primary key constraint, so the user needs to mark one
+        assert_eq!(
+            view(
+                PhysicalTableName::new("venue_summaries", None),
+                TableKind::View,
+                false
+            ),
+            (
+                "@view(\"venue_summaries\")\nmodel VenueSummaries {\n  id: Int\n  name: String\n}"
+                    .to_string(),
+                vec![
+                    "hint: view `venue_summaries` should have a field marked with `@pk`"
+                        .to_string(),
+                    "hint: model name `VenueSummaries` should be changed to singular".to_string()
+                ]
+            )
+        );
+        assert_eq!(
+            view(
+                PhysicalTableName::new("venue_stat", Some("reporting")),
+                TableKind::MaterializedView,
+                true
+            ),
+            (
+                "@materializedView(name=\"venue_stat\", schema=\"reporting\")\nmodel VenueStat {\n  id: Int @pk\n  name: String\n}"
+                    .to_string(),
+                vec![]
+            )
+        );
+    }
+
+    fn column(name: &str, typ: ColumnTypeSpec, is_pk: bool) -> ColumnSpec {
+        ColumnSpec {
+            name: name.to_string(),
+            typ,
+            is_pk,
+            is_auto_increment: false,
+            is_nullable: false,
+            unique_constraints: vec![],
+            default_value: None,
+        }
+    }
+}
@@ -17,6 +17,13 @@ use std::{collections::HashMap, pin::Pin};
 pub trait RequestPayload {
     fn get_head(&self) -> &(dyn RequestHead + Send + Sync);
     fn take_body(&mut self) -> Value;
+
+    /// An owned copy of the request head for processing that outlives the request (for example,
+    /// resolving deferred fields while streaming the response). Returns `None` if the underlying
+    /// request cannot be copied, in which case such processing falls back to the request's lifetime.
+    fn clone_head(&self) -> Option<Box<dyn RequestHead + Send + Sync>> {
+        None
+    }
 }
 
 type PinnedStream<E> = Pin<Box<dyn Stream<Item = Result<Bytes, E>>>>;
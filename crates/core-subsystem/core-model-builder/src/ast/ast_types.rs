@@ -89,6 +89,7 @@ pub struct AstModule<T: NodeTypedness> {
     pub types: Vec<AstModel<T>>,
     pub methods: Vec<AstMethod<T>>,
     pub interceptors: Vec<AstInterceptor<T>>,
+    pub enums: Vec<AstEnum>,
     pub base_exofile: PathBuf, // The exo file in which this module is defined. Used to resolve relative imports and js/ts/wasm sources
     #[serde(skip_serializing)]
     #[serde(skip_deserializing)]
@@ -138,6 +139,27 @@ pub struct AstInterceptor<T: NodeTypedness> {
     pub span: Span,
 }
 
+/// An enum declaration such as `enum Status { DRAFT, PUBLISHED }`. Since an enum has no fields or
+/// annotations to typecheck, it is the same for both untyped and typed ASTs.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AstEnum {
+    pub name: String,
+    pub values: Vec<AstEnumValue>,
+    #[serde(skip_serializing)]
+    #[serde(skip_deserializing)]
+    #[serde(default = "default_span")]
+    pub span: Span,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AstEnumValue {
+    pub name: String,
+    #[serde(skip_serializing)]
+    #[serde(skip_deserializing)]
+    #[serde(default = "default_span")]
+    pub span: Span,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub enum AstModelKind {
     Type,    // a type in a module (with semantics assigned by each module plugin)
@@ -0,0 +1,91 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use codemap::Span;
+use codemap_diagnostic::{Diagnostic, Level, SpanLabel, SpanStyle};
+use core_model::cache_policy::{CachePolicy, CacheScope};
+
+use crate::{
+    ast::ast_types::{AstAnnotationParams, AstExpr},
+    typechecker::Typed,
+};
+
+/// Build the cache policy from the `@cache` annotation, which may be specified either as
+/// `@cache(<max-age>)` or `@cache(maxAge=<max-age>, scope="public"|"private")` (the scope
+/// defaults to "public").
+pub fn build_cache_policy(
+    annotation: Option<&AstAnnotationParams<Typed>>,
+    errors: &mut Vec<Diagnostic>,
+) -> Option<CachePolicy> {
+    let (max_age, scope) = match annotation? {
+        AstAnnotationParams::Single(max_age, _) => (max_age, None),
+        AstAnnotationParams::Map(params, _) => (params.get("maxAge")?, params.get("scope")),
+        AstAnnotationParams::None => return None,
+    };
+
+    let max_age = match max_age {
+        AstExpr::NumberLiteral(max_age, span) => match u32::try_from(*max_age) {
+            Ok(max_age) => Some(max_age),
+            Err(_) => {
+                errors.push(cache_diagnostic(
+                    "The maxAge of @cache must be a non-negative number of seconds",
+                    *span,
+                ));
+                None
+            }
+        },
+        expr => {
+            errors.push(cache_diagnostic(
+                "The maxAge of @cache must be a number",
+                expr.span(),
+            ));
+            None
+        }
+    };
+
+    let scope = match scope {
+        None => Some(CacheScope::Public),
+        Some(AstExpr::StringLiteral(scope, span)) => match scope.as_str() {
+            "public" => Some(CacheScope::Public),
+            "private" => Some(CacheScope::Private),
+            _ => {
+                errors.push(cache_diagnostic(
+                    "The scope of @cache must be either \"public\" or \"private\"",
+                    *span,
+                ));
+                None
+            }
+        },
+        Some(expr) => {
+            errors.push(cache_diagnostic(
+                "The scope of @cache must be either \"public\" or \"private\"",
+                expr.span(),
+            ));
+            None
+        }
+    };
+
+    Some(CachePolicy {
+        max_age: max_age?,
+        scope: scope?,
+    })
+}
+
+fn cache_diagnostic(message: &str, span: Span) -> Diagnostic {
+    Diagnostic {
+        level: Level::Error,
+        message: message.to_string(),
+        code: Some("C000".to_string()),
+        spans: vec![SpanLabel {
+            span,
+            style: SpanStyle::Primary,
+            label: None,
+        }],
+    }
+}
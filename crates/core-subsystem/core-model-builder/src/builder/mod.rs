@@ -7,6 +7,7 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+pub mod cache_policy_builder;
 pub mod resolved_builder;
 pub mod system_builder;
 
@@ -0,0 +1,110 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use async_graphql_parser::{types::ConstDirective, Positioned};
+use async_graphql_value::{ConstValue, Name, Number};
+use serde::{Deserialize, Serialize};
+
+use crate::type_normalization::{default_positioned, default_positioned_name};
+
+/// The name of the schema directive through which subsystems expose the cache policy of types
+/// and fields (specified through the `@cache` annotation) to the core resolver
+pub const CACHE_CONTROL_DIRECTIVE: &str = "cacheControl";
+
+/// Who may cache a response
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheScope {
+    /// Any cache (including shared ones such as CDNs)
+    Public,
+    /// Only the client's cache (since the response may depend on the user)
+    Private,
+}
+
+impl CacheScope {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CacheScope::Public => "public",
+            CacheScope::Private => "private",
+        }
+    }
+}
+
+/// How long and by whom the value of a type or field may be cached
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CachePolicy {
+    /// In seconds
+    pub max_age: u32,
+    pub scope: CacheScope,
+}
+
+impl CachePolicy {
+    /// The policy that satisfies both policies (the shorter max age and the narrower scope)
+    pub fn restrict(self, other: CachePolicy) -> CachePolicy {
+        CachePolicy {
+            max_age: self.max_age.min(other.max_age),
+            scope: if self.scope == CacheScope::Private || other.scope == CacheScope::Private {
+                CacheScope::Private
+            } else {
+                CacheScope::Public
+            },
+        }
+    }
+
+    /// The value of the `Cache-Control` HTTP header for this policy
+    pub fn header_value(&self) -> String {
+        format!("max-age={}, {}", self.max_age, self.scope.name())
+    }
+
+    pub fn directive(&self) -> Positioned<ConstDirective> {
+        default_positioned(ConstDirective {
+            name: default_positioned_name(CACHE_CONTROL_DIRECTIVE),
+            arguments: vec![
+                (
+                    default_positioned_name("maxAge"),
+                    default_positioned(ConstValue::Number(Number::from(self.max_age))),
+                ),
+                (
+                    default_positioned_name("scope"),
+                    default_positioned(ConstValue::Enum(Name::new(
+                        self.scope.name().to_uppercase(),
+                    ))),
+                ),
+            ],
+        })
+    }
+
+    /// The policy specified through the `@cacheControl` directive (if any)
+    pub fn from_directives(directives: &[Positioned<ConstDirective>]) -> Option<CachePolicy> {
+        let directive = directives
+            .iter()
+            .find(|directive| directive.node.name.node == CACHE_CONTROL_DIRECTIVE)?;
+
+        let max_age = match directive.node.get_argument("maxAge").map(|arg| &arg.node) {
+            Some(ConstValue::Number(max_age)) => max_age.as_u64()?.try_into().ok()?,
+            _ => return None,
+        };
+
+        let scope = match directive.node.get_argument("scope").map(|arg| &arg.node) {
+            Some(ConstValue::Enum(scope)) if scope.as_str() == "PRIVATE" => CacheScope::Private,
+            _ => CacheScope::Public,
+        };
+
+        Some(CachePolicy { max_age, scope })
+    }
+}
+
+/// The directives to add to the schema definition of a type or field with the given policy
+pub fn cache_policy_directives(
+    cache_policy: &Option<CachePolicy>,
+) -> Vec<Positioned<ConstDirective>> {
+    cache_policy
+        .iter()
+        .map(|cache_policy| cache_policy.directive())
+        .collect()
+}
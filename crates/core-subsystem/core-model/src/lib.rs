@@ -8,6 +8,7 @@
 // by the Apache License, Version 2.0.
 
 pub mod access;
+pub mod cache_policy;
 pub mod context_type;
 pub mod function_defn;
 pub mod mapped_arena;
@@ -31,6 +31,8 @@ pub enum PrimitiveType {
     // TODO: This should not be a primitive type, but a type with modifier or some variation of it
     /// An array version of a primitive type.
     Array(Box<PrimitiveType>),
+    /// A user-declared enum such as `enum Status { DRAFT, PUBLISHED }`.
+    Enum(EnumType),
 
     // TODO: These should not be a primitive types... perhaps another enum `InjectedType`?
     /// Available as an injected dependency to Deno queries and mutations so that the implementation
@@ -63,10 +65,17 @@ impl PrimitiveType {
             PrimitiveType::ExographPriv => "ExographPriv".to_owned(),
             PrimitiveType::Interception(name) => name.to_owned(),
             PrimitiveType::Array(pt) => format!("[{}]", pt.name()),
+            PrimitiveType::Enum(enum_type) => enum_type.name.to_owned(),
         }
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnumType {
+    pub name: String,
+    pub values: Vec<String>,
+}
+
 impl Display for PrimitiveType {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.write_str(&self.name())
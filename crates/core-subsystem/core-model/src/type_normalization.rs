@@ -16,6 +16,7 @@ use async_graphql_parser::{
 };
 use async_graphql_value::Name;
 
+use crate::cache_policy::{cache_policy_directives, CachePolicy};
 use crate::primitive_type::vector_introspection_type;
 
 pub trait FieldDefinitionProvider<S> {
@@ -182,6 +183,10 @@ pub trait Operation {
     fn name(&self) -> &String;
     fn parameters(&self) -> Vec<&dyn Parameter>;
     fn return_type(&self) -> Type;
+    /// The cache policy of the operation's result (if specified)
+    fn cache_policy(&self) -> Option<CachePolicy> {
+        None
+    }
 }
 
 // Field definition for the query such as `venue(id: Int!): Venue`, combining such fields will form
@@ -198,7 +203,7 @@ impl<T: Operation, S> FieldDefinitionProvider<S> for T {
             description: None,
             name: default_positioned_name(self.name()),
             arguments: fields,
-            directives: vec![],
+            directives: cache_policy_directives(&self.cache_policy()),
             ty: default_positioned(self.return_type()),
         }
     }
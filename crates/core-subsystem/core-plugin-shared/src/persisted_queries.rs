@@ -0,0 +1,102 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::sync::{Arc, Mutex};
+
+use indexmap::IndexMap;
+
+/// Documents registered by clients through the [automatic persisted
+/// queries](https://www.apollographql.com/docs/apollo-server/performance/apq/) (APQ) protocol.
+///
+/// A client first sends only the hash of a document. If we don't know the document, we respond
+/// with the `PersistedQueryNotFound` error, and the client retries with both the document and
+/// its hash, at which point we register the document. To keep the memory usage bounded, we
+/// keep only the `capacity` most recently used documents.
+#[derive(Debug)]
+pub struct PersistedQueries {
+    capacity: usize,
+    // Ordered from the least to the most recently used
+    documents: Mutex<IndexMap<String, Arc<str>>>,
+}
+
+impl PersistedQueries {
+    /// Create a store that keeps up to `capacity` documents (a zero capacity disables storage,
+    /// so clients will have to send the document every time)
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            documents: Mutex::new(IndexMap::new()),
+        }
+    }
+
+    pub fn get(&self, hash: &str) -> Option<Arc<str>> {
+        let mut documents = self.documents.lock().unwrap();
+
+        let index = documents.get_index_of(hash)?;
+        let last = documents.len() - 1;
+        documents.move_index(index, last);
+
+        documents
+            .get_index(last)
+            .map(|(_, document)| document.clone())
+    }
+
+    pub fn insert(&self, hash: &str, document: &str) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut documents = self.documents.lock().unwrap();
+
+        match documents.get_index_of(hash) {
+            Some(index) => {
+                let last = documents.len() - 1;
+                documents.move_index(index, last);
+            }
+            None => {
+                if documents.len() == self.capacity {
+                    documents.shift_remove_index(0);
+                }
+                documents.insert(hash.to_string(), document.into());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use multiplatform_test::multiplatform_test;
+
+    #[multiplatform_test]
+    fn evicts_least_recently_used() {
+        let persisted_queries = PersistedQueries::new(2);
+
+        persisted_queries.insert("hash1", "query1");
+        persisted_queries.insert("hash2", "query2");
+
+        // Makes "hash1" the most recently used, so "hash2" will be evicted next
+        assert_eq!(persisted_queries.get("hash1").as_deref(), Some("query1"));
+
+        persisted_queries.insert("hash3", "query3");
+
+        assert_eq!(persisted_queries.get("hash1").as_deref(), Some("query1"));
+        assert_eq!(persisted_queries.get("hash2"), None);
+        assert_eq!(persisted_queries.get("hash3").as_deref(), Some("query3"));
+    }
+
+    #[multiplatform_test]
+    fn zero_capacity() {
+        let persisted_queries = PersistedQueries::new(0);
+
+        persisted_queries.insert("hash1", "query1");
+
+        assert_eq!(persisted_queries.get("hash1"), None);
+    }
+}
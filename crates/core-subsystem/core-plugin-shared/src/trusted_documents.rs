@@ -1,9 +1,12 @@
 use serde::{Deserialize, Serialize};
 use sha2::Digest;
+use std::borrow::Cow;
 use std::collections::HashMap;
 use thiserror::Error;
 use tracing::warn;
 
+use crate::persisted_queries::PersistedQueries;
+
 #[derive(Serialize, Deserialize, Debug)]
 pub enum TrustedDocuments {
     /// Allow all documents and use the map to get the document by its hash if query isn't present and query_hash is
@@ -12,7 +15,7 @@ pub enum TrustedDocuments {
     MatchingOnly(HashMap<String, String>),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum TrustedDocumentEnforcement {
     Enforce,
     DoNotEnforce,
@@ -31,25 +34,31 @@ impl TrustedDocuments {
         }
     }
 
+    /// Resolve the document to execute.
+    ///
+    /// When untrusted documents are allowed, clients may also use the automatic persisted queries
+    /// protocol: a document sent along with its hash is registered in `persisted_queries`, so later
+    /// requests may send only the hash.
     pub fn resolve<'a>(
         &'a self,
         query: Option<&'a str>,
         query_hash: Option<&str>,
         enforcement: TrustedDocumentEnforcement,
-    ) -> Result<&str, TrustedDocumentResolutionError> {
+        persisted_queries: &PersistedQueries,
+    ) -> Result<Cow<'a, str>, TrustedDocumentResolutionError> {
         let allow_untrusted = matches!(self, TrustedDocuments::All(_))
             || matches!(enforcement, TrustedDocumentEnforcement::DoNotEnforce);
 
         match (query, query_hash) {
             (Some(query), None) => {
                 if allow_untrusted {
-                    Ok(query)
+                    Ok(Cow::Borrowed(query))
                 } else {
                     let query_hash = Self::sha256(query);
                     match self.get(&query_hash) {
                         Some(document) => {
                             warn!("Query sent when sending only the query hash would be efficient");
-                            Ok(document)
+                            Ok(Cow::Borrowed(document))
                         }
                         None => Err(TrustedDocumentResolutionError::NotTrusted {
                             hash: None, // the client didn't send the hash
@@ -58,15 +67,30 @@ impl TrustedDocuments {
                     }
                 }
             }
-            (None, Some(query_hash)) => self.get(query_hash).ok_or(if allow_untrusted {
-                TrustedDocumentResolutionError::NotFound
-            } else {
-                TrustedDocumentResolutionError::NotTrusted {
+            (None, Some(query_hash)) => match self.get(query_hash) {
+                Some(document) => Ok(Cow::Borrowed(document)),
+                None if allow_untrusted => persisted_queries
+                    .get(query_hash)
+                    .map(|document| Cow::Owned(document.to_string()))
+                    .ok_or(TrustedDocumentResolutionError::NotFound),
+                None => Err(TrustedDocumentResolutionError::NotTrusted {
                     hash: Some(query_hash.to_string()),
                     query: None,
+                }),
+            },
+            (Some(query), Some(query_hash)) => {
+                if !allow_untrusted {
+                    return Err(TrustedDocumentResolutionError::BothPresent);
+                }
+
+                // The client is registering the document (after we reported `NotFound` for the hash)
+                if Self::sha256(query) != query_hash {
+                    return Err(TrustedDocumentResolutionError::HashMismatch);
                 }
-            }),
-            (Some(_), Some(_)) => Err(TrustedDocumentResolutionError::BothPresent),
+                persisted_queries.insert(query_hash, query);
+
+                Ok(Cow::Borrowed(query))
+            }
             (None, None) => Err(TrustedDocumentResolutionError::NonePresent),
         }
     }
@@ -93,9 +117,12 @@ pub enum TrustedDocumentResolutionError {
         query: Option<String>,
     },
 
-    #[error("The hash present in the query does not match any trusted document")]
+    #[error("The hash present in the query does not match any trusted or persisted document")]
     NotFound,
 
+    #[error("The hash present in the query does not match the query")]
+    HashMismatch,
+
     #[error("Both query and query_hash present in the payload, only one should be")]
     BothPresent,
 
@@ -120,6 +147,7 @@ mod tests {
 
         let trusted_documents_matching_only = TrustedDocuments::from_map(map.clone(), false);
         let trusted_documents_all = TrustedDocuments::from_map(map, true);
+        let persisted_queries = PersistedQueries::new(10);
 
         for i in 0..=2 {
             let query = format!("query{i}");
@@ -132,17 +160,19 @@ mod tests {
                     trusted_documents.resolve(
                         Some(&query),
                         None,
-                        TrustedDocumentEnforcement::Enforce
+                        TrustedDocumentEnforcement::Enforce,
+                        &persisted_queries
                     ),
-                    Ok(query.as_str())
+                    Ok(Cow::Borrowed(query.as_str()))
                 );
                 assert_eq!(
                     trusted_documents.resolve(
                         None,
                         Some(&hash),
-                        TrustedDocumentEnforcement::Enforce
+                        TrustedDocumentEnforcement::Enforce,
+                        &persisted_queries
                     ),
-                    Ok(query.as_str())
+                    Ok(Cow::Borrowed(query.as_str()))
                 );
 
                 // Should be able to resolve both by query and hash even in the unenforced case
@@ -150,35 +180,30 @@ mod tests {
                     trusted_documents.resolve(
                         Some(&query),
                         None,
-                        TrustedDocumentEnforcement::DoNotEnforce
+                        TrustedDocumentEnforcement::DoNotEnforce,
+                        &persisted_queries
                     ),
-                    Ok(query.as_str())
+                    Ok(Cow::Borrowed(query.as_str()))
                 );
                 assert_eq!(
                     trusted_documents.resolve(
                         None,
                         Some(&hash),
-                        TrustedDocumentEnforcement::DoNotEnforce
+                        TrustedDocumentEnforcement::DoNotEnforce,
+                        &persisted_queries
                     ),
-                    Ok(query.as_str())
+                    Ok(Cow::Borrowed(query.as_str()))
                 );
 
-                // Should not be able to resolve both by query and hash
+                // Should be able to send both query and hash in the unenforced case (to register the query)
                 assert_eq!(
                     trusted_documents.resolve(
                         Some(&query),
                         Some(&hash),
-                        TrustedDocumentEnforcement::Enforce
+                        TrustedDocumentEnforcement::DoNotEnforce,
+                        &persisted_queries
                     ),
-                    Err(TrustedDocumentResolutionError::BothPresent)
-                );
-                assert_eq!(
-                    trusted_documents.resolve(
-                        Some(&query),
-                        Some(&hash),
-                        TrustedDocumentEnforcement::DoNotEnforce
-                    ),
-                    Err(TrustedDocumentResolutionError::BothPresent)
+                    Ok(Cow::Borrowed(query.as_str()))
                 );
 
                 // In unenforced mode, should be able to resolve by query, but not by hash (there would be no mapping for it)
@@ -186,26 +211,38 @@ mod tests {
                     trusted_documents.resolve(
                         Some("query3"),
                         None,
-                        TrustedDocumentEnforcement::DoNotEnforce
+                        TrustedDocumentEnforcement::DoNotEnforce,
+                        &persisted_queries
                     ),
-                    Ok("query3")
+                    Ok(Cow::Borrowed("query3"))
                 );
                 assert_eq!(
                     trusted_documents.resolve(
                         None,
                         Some("hash3"),
-                        TrustedDocumentEnforcement::DoNotEnforce
+                        TrustedDocumentEnforcement::DoNotEnforce,
+                        &persisted_queries
                     ),
                     Err(TrustedDocumentResolutionError::NotFound)
                 );
 
                 // At least one of query or hash should be present
                 assert_eq!(
-                    trusted_documents.resolve(None, None, TrustedDocumentEnforcement::Enforce),
+                    trusted_documents.resolve(
+                        None,
+                        None,
+                        TrustedDocumentEnforcement::Enforce,
+                        &persisted_queries
+                    ),
                     Err(TrustedDocumentResolutionError::NonePresent)
                 );
                 assert_eq!(
-                    trusted_documents.resolve(None, None, TrustedDocumentEnforcement::DoNotEnforce),
+                    trusted_documents.resolve(
+                        None,
+                        None,
+                        TrustedDocumentEnforcement::DoNotEnforce,
+                        &persisted_queries
+                    ),
                     Err(TrustedDocumentResolutionError::NonePresent)
                 );
             }
@@ -216,7 +253,8 @@ mod tests {
             trusted_documents_matching_only.resolve(
                 Some("query3"),
                 None,
-                TrustedDocumentEnforcement::Enforce
+                TrustedDocumentEnforcement::Enforce,
+                &persisted_queries
             ),
             Err(TrustedDocumentResolutionError::NotTrusted {
                 hash: None,
@@ -227,7 +265,8 @@ mod tests {
             trusted_documents_matching_only.resolve(
                 None,
                 Some("hash3"),
-                TrustedDocumentEnforcement::Enforce
+                TrustedDocumentEnforcement::Enforce,
+                &persisted_queries
             ),
             Err(TrustedDocumentResolutionError::NotTrusted {
                 hash: Some("hash3".to_string()),
@@ -235,18 +274,88 @@ mod tests {
             })
         );
 
+        // In enforced mode with matching only, should not be able to send both query and hash
+        assert_eq!(
+            trusted_documents_matching_only.resolve(
+                Some("query0"),
+                Some(&TrustedDocuments::sha256("query0")),
+                TrustedDocumentEnforcement::Enforce,
+                &persisted_queries
+            ),
+            Err(TrustedDocumentResolutionError::BothPresent)
+        );
+
         // In enforced mode with all, should be able to resolve by query, but not by hash (there would be no mapping for it)
         assert_eq!(
             trusted_documents_all.resolve(
                 Some("query3"),
                 None,
-                TrustedDocumentEnforcement::Enforce
+                TrustedDocumentEnforcement::Enforce,
+                &persisted_queries
+            ),
+            Ok(Cow::Borrowed("query3"))
+        );
+        assert_eq!(
+            trusted_documents_all.resolve(
+                None,
+                Some("hash3"),
+                TrustedDocumentEnforcement::Enforce,
+                &persisted_queries
             ),
-            Ok("query3")
+            Err(TrustedDocumentResolutionError::NotFound)
         );
+    }
+
+    #[multiplatform_test]
+    fn automatic_persisted_queries() {
+        let trusted_documents = TrustedDocuments::all();
+        let persisted_queries = PersistedQueries::new(10);
+
+        let query = "query { concerts { id } }";
+        let hash = TrustedDocuments::sha256(query);
+
+        // The hash alone isn't enough until the client registers the query
         assert_eq!(
-            trusted_documents_all.resolve(None, Some("hash3"), TrustedDocumentEnforcement::Enforce),
+            trusted_documents.resolve(
+                None,
+                Some(&hash),
+                TrustedDocumentEnforcement::Enforce,
+                &persisted_queries
+            ),
             Err(TrustedDocumentResolutionError::NotFound)
         );
+
+        // The query must match the hash
+        assert_eq!(
+            trusted_documents.resolve(
+                Some("query { venues { id } }"),
+                Some(&hash),
+                TrustedDocumentEnforcement::Enforce,
+                &persisted_queries
+            ),
+            Err(TrustedDocumentResolutionError::HashMismatch)
+        );
+
+        // Register the query
+        assert_eq!(
+            trusted_documents.resolve(
+                Some(query),
+                Some(&hash),
+                TrustedDocumentEnforcement::Enforce,
+                &persisted_queries
+            ),
+            Ok(Cow::Borrowed(query))
+        );
+
+        // And now the hash alone is enough
+        assert_eq!(
+            trusted_documents.resolve(
+                None,
+                Some(&hash),
+                TrustedDocumentEnforcement::Enforce,
+                &persisted_queries
+            ),
+            Ok(Cow::Borrowed(query))
+        );
     }
 }
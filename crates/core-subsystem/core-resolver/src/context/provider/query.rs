@@ -58,6 +58,10 @@ impl ContextExtractor for QueryExtractor<'_> {
 
         match matching_result {
             Some((_, matching_result)) => {
+                let matching_result = matching_result
+                    .as_ref()
+                    .map_err(|e| ContextExtractionError::Generic(e.to_string()))?;
+
                 let json_result = matching_result.body.to_json().map_err(|_| {
                     ContextExtractionError::Generic(
                         "Could not convert query result into JSON during @query context processing"
@@ -88,6 +88,17 @@ impl<'a> RequestContext<'a> {
         }
     }
 
+    /// The values of the given context fields for the original request (see
+    /// [UserRequestContext::context_field_values])
+    pub async fn context_field_values(
+        &'a self,
+        fields: &[(String, String)],
+    ) -> Result<Vec<Option<Val>>, ContextExtractionError> {
+        self.get_base_context()
+            .context_field_values(fields, self)
+            .await
+    }
+
     #[async_recursion]
     pub async fn ensure_transaction(&self) {
         match self {
@@ -7,7 +7,10 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 use tokio::sync::Mutex;
 
 use elsa::sync::FrozenMap;
@@ -34,6 +37,12 @@ pub struct UserRequestContext<'a> {
     request_head: &'a (dyn RequestHead + Send + Sync),
     // cache of context values so that we compute them only once per request
     context_cache: FrozenMap<(String, String), Box<Option<Val>>>,
+    // the context fields accessed so far (a response that depends on them can be cached only for
+    // requests with the same values)
+    accessed_context_fields: std::sync::Mutex<Vec<(String, String)>>,
+    // the types that the mutations in the transaction may modify (`None` if unknown), whose cached
+    // responses are evicted once the transaction commits
+    mutated_types: std::sync::Mutex<Vec<Option<HashSet<String>>>>,
 }
 
 impl<'a> UserRequestContext<'a> {
@@ -64,9 +73,15 @@ impl<'a> UserRequestContext<'a> {
             transaction_holder: Arc::new(Mutex::new(TransactionHolder::default())),
             request_head,
             context_cache: FrozenMap::new(),
+            accessed_context_fields: std::sync::Mutex::new(vec![]),
+            mutated_types: std::sync::Mutex::new(vec![]),
         }
     }
 
+    pub fn request_head(&self) -> &'a (dyn RequestHead + Send + Sync) {
+        self.request_head
+    }
+
     pub async fn extract_context_field(
         &'a self,
         annotation: &str,
@@ -87,6 +102,11 @@ impl<'a> UserRequestContext<'a> {
         let value: &'a Option<Val> = match cached_value {
             Some(value) => value,
             None => {
+                self.accessed_context_fields
+                    .lock()
+                    .unwrap()
+                    .push(cache_key.clone());
+
                 let raw_field_value = self
                     .extract_context_field_from_source(annotation, key, request_context)
                     .await;
@@ -102,6 +122,38 @@ impl<'a> UserRequestContext<'a> {
         Ok(value.as_ref())
     }
 
+    /// The context fields (as `(annotation, key)` pairs) accessed so far
+    pub fn accessed_context_fields(&self) -> Vec<(String, String)> {
+        self.accessed_context_fields.lock().unwrap().clone()
+    }
+
+    /// The values of the given context fields (without coercion, and without caching them, so that
+    /// they don't count as accessed unless the operation accesses them)
+    pub async fn context_field_values(
+        &'a self,
+        fields: &[(String, String)],
+        request_context: &'a RequestContext<'a>,
+    ) -> Result<Vec<Option<Val>>, ContextExtractionError> {
+        let mut values = Vec::with_capacity(fields.len());
+        for (annotation, key) in fields {
+            values.push(
+                self.extract_context_field_from_source(annotation, key, request_context)
+                    .await?,
+            );
+        }
+        Ok(values)
+    }
+
+    /// Record the types that a mutation may modify (see [crate::response_cache::operation_types])
+    pub fn record_mutated_types(&self, types: Option<HashSet<String>>) {
+        self.mutated_types.lock().unwrap().push(types);
+    }
+
+    /// Take the types recorded through [UserRequestContext::record_mutated_types]
+    pub fn take_mutated_types(&self) -> Vec<Option<HashSet<String>>> {
+        std::mem::take(&mut *self.mutated_types.lock().unwrap())
+    }
+
     // Given an annotation name and its value,
     // extract a context field from the request context
     async fn extract_context_field_from_source(
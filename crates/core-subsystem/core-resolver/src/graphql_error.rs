@@ -0,0 +1,147 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use async_graphql_parser::Pos;
+use serde::Serialize;
+
+use crate::system_resolver::SystemResolutionError;
+
+/// A machine-readable classification of an error (reported as `extensions.code`), so that clients
+/// may branch on it instead of parsing the message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    /// The operation could not be parsed
+    GraphqlParseFailed,
+    /// The operation is not valid against the schema (unknown field, wrong argument type, etc.)
+    ValidationFailed,
+    /// The input (such as an argument value) is not acceptable
+    BadUserInput,
+    /// The user is not authorized to perform the operation
+    Unauthorized,
+    /// The requested operation does not exist in the document
+    NotFound,
+    /// The operation is not a trusted document
+    OperationNotAllowed,
+    /// The hash sent by an automatic persisted queries client doesn't match any known document (the
+    /// client is expected to retry with the document)
+    PersistedQueryNotFound,
+    /// The operation type is not supported over the transport used (such as a subscription over HTTP)
+    OperationNotSupported,
+    /// The operation conflicts with a concurrent change (such as an update with a stale version)
+    Conflict,
+    /// The operation failed while executing (such as a database constraint violation or an error thrown by a module)
+    OperationFailed,
+    /// Any other error (details are hidden from the user)
+    InternalServerError,
+}
+
+/// A segment of the path to the response field associated with an error
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum PathSegment {
+    Field(String),
+    Index(usize),
+}
+
+/// An error in the form specified by the [GraphQL spec](https://spec.graphql.org/October2021/#sec-Errors)
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphQLError {
+    pub message: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub locations: Vec<Pos>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub path: Vec<PathSegment>,
+    pub extensions: GraphQLErrorExtensions,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphQLErrorExtensions {
+    pub code: ErrorCode,
+}
+
+impl GraphQLError {
+    /// An error not associated with any location or path
+    pub fn new(message: String, code: ErrorCode) -> Self {
+        GraphQLError {
+            message,
+            locations: vec![],
+            path: vec![],
+            extensions: GraphQLErrorExtensions { code },
+        }
+    }
+}
+
+impl From<&SystemResolutionError> for GraphQLError {
+    fn from(error: &SystemResolutionError) -> Self {
+        GraphQLError {
+            message: error.user_error_message(),
+            locations: error.locations(),
+            path: error.path(),
+            extensions: GraphQLErrorExtensions { code: error.code() },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{plugin::SubsystemResolutionError, validation::validation_error::ValidationError};
+
+    #[test]
+    fn validation_error() {
+        let error = SystemResolutionError::Validation(ValidationError::InvalidField(
+            "foo".to_string(),
+            "Query".to_string(),
+            Pos { line: 2, column: 3 },
+        ));
+
+        assert_eq!(
+            serde_json::to_value(GraphQLError::from(&error)).unwrap(),
+            serde_json::json!({
+                "message": "Field 'foo' is not valid for type 'Query'",
+                "locations": [{"line": 2, "column": 3}],
+                "extensions": {"code": "VALIDATION_FAILED"}
+            })
+        );
+    }
+
+    #[test]
+    fn field_error() {
+        let error = SystemResolutionError::SubsystemResolutionError(
+            SubsystemResolutionError::Authorization,
+        )
+        .at_field("concerts".to_string());
+
+        assert_eq!(
+            serde_json::to_value(GraphQLError::from(&error)).unwrap(),
+            serde_json::json!({
+                "message": "Not authorized",
+                "path": ["concerts"],
+                "extensions": {"code": "UNAUTHORIZED"}
+            })
+        );
+    }
+
+    #[test]
+    fn internal_error() {
+        let error = SystemResolutionError::Generic("connection refused".to_string())
+            .at_field("concerts".to_string());
+
+        assert_eq!(
+            serde_json::to_value(GraphQLError::from(&error)).unwrap(),
+            serde_json::json!({
+                "message": "Internal server error",
+                "path": ["concerts"],
+                "extensions": {"code": "INTERNAL_SERVER_ERROR"}
+            })
+        );
+    }
+}
@@ -11,12 +11,13 @@ use std::collections::{HashMap, HashSet};
 
 use async_graphql_parser::{
     types::{
-        BaseType, FieldDefinition, InputValueDefinition, ObjectType, Type, TypeDefinition, TypeKind,
+        BaseType, DirectiveDefinition, DirectiveLocation, FieldDefinition, InputValueDefinition,
+        ObjectType, Type, TypeDefinition, TypeKind,
     },
     Positioned,
 };
 
-use async_graphql_value::Name;
+use async_graphql_value::{ConstValue, Name};
 use core_model::type_normalization::{
     default_positioned, default_positioned_name, TypeDefinitionIntrospection,
 };
@@ -26,6 +27,8 @@ use crate::{plugin::SubsystemResolver, validation::underlying_type};
 #[derive(Debug, Clone)]
 pub struct Schema {
     pub type_definitions: Vec<TypeDefinition>,
+    /// The directives that may be used in operations (such as `@skip`)
+    pub directive_definitions: Vec<DirectiveDefinition>,
     pub(crate) schema_field_definition: FieldDefinition,
     pub(crate) type_field_definition: FieldDefinition,
 }
@@ -77,13 +80,27 @@ impl Schema {
             mutations
         };
 
-        Self::new(type_definitions, queries, mutations)
+        let subscriptions = {
+            let mut subscriptions = subsystem_resolvers
+                .iter()
+                .fold(vec![], |mut acc, resolver| {
+                    acc.extend(resolver.schema_subscriptions());
+                    acc
+                });
+
+            // ensure introspection outputs subscriptions in a stable order
+            subscriptions.sort_by_key(|s| s.name.clone());
+            subscriptions
+        };
+
+        Self::new(type_definitions, queries, mutations, subscriptions)
     }
 
     pub fn new(
         type_definitions: Vec<TypeDefinition>,
         queries: Vec<FieldDefinition>,
         mutations: Vec<FieldDefinition>,
+        subscriptions: Vec<FieldDefinition>,
     ) -> Schema {
         let mut type_definitions = type_definitions;
 
@@ -122,6 +139,19 @@ impl Schema {
             });
         };
 
+        if !subscriptions.is_empty() {
+            type_definitions.push(TypeDefinition {
+                extend: false,
+                description: None,
+                name: default_positioned_name(SUBSCRIPTION_ROOT_TYPENAME),
+                directives: vec![],
+                kind: TypeKind::Object(ObjectType {
+                    implements: vec![],
+                    fields: subscriptions.into_iter().map(default_positioned).collect(),
+                }),
+            });
+        };
+
         type_definitions.push(Self::create_schema_type_definition());
         type_definitions.push(Self::create_type_definition());
         type_definitions.push(Self::create_field_definition());
@@ -130,10 +160,11 @@ impl Schema {
         type_definitions.push(Self::create_input_value_definition());
 
         // We may have unused scalars (an artifact of how our typechecking starts with supplying all scalars)
-        // So here we remove unused ones
+        // So here we remove unused ones. We treat enums the same way, since every subsystem exposes
+        // all declared enums (whether it uses them or not).
         let (scalars, mut type_definitions): (Vec<_>, Vec<_>) = type_definitions
             .into_iter()
-            .partition(|td| matches!(td.kind, TypeKind::Scalar));
+            .partition(|td| matches!(td.kind, TypeKind::Scalar | TypeKind::Enum(_)));
 
         // Start with assuming no scalars are used
         let mut unused_scalars_names: HashSet<String> = scalars
@@ -174,7 +205,7 @@ impl Schema {
         let used_scalars = scalars
             .into_iter()
             .filter(|td| !unused_scalars_names.contains(td.name.node.as_str()));
-        // Create a unique list of scalars (each subsystem may expose the same scalar)
+        // Create a unique list of scalars (each subsystem may expose the same scalar or enum)
         let unique_scalars: HashMap<_, _> = used_scalars
             .into_iter()
             .map(|td| (td.name.node.as_str().to_string(), td))
@@ -184,6 +215,7 @@ impl Schema {
 
         Schema {
             type_definitions,
+            directive_definitions: Self::create_builtin_directive_definitions(),
             schema_field_definition: Self::create_field(
                 "__schema",
                 false,
@@ -218,6 +250,89 @@ impl Schema {
             .find(|td| td.name().as_str() == type_name)
     }
 
+    /// The directives required by the [spec](https://spec.graphql.org/October2021/#sec-Type-System.Directives.Built-in-Directives)
+    /// that apply to operations (the `@skip` and `@include` directives are evaluated during validation)
+    /// along with the `@defer` directive of the [incremental delivery](https://github.com/graphql/graphql-spec/pull/742)
+    /// proposal (honored only on the root selections of a query).
+    ///
+    /// We leave out `@stream`: we honor it only on root list fields, and even then resolve the
+    /// whole list before delivering its first items.
+    fn create_builtin_directive_definitions() -> Vec<DirectiveDefinition> {
+        let argument = |name: &str,
+                        description: &str,
+                        type_name: &str,
+                        nullable: bool,
+                        default_value: Option<ConstValue>| {
+            default_positioned(InputValueDefinition {
+                description: Some(default_positioned(description.to_string())),
+                name: default_positioned_name(name),
+                directives: vec![],
+                default_value: default_value.map(default_positioned),
+                ty: default_positioned(Type {
+                    base: BaseType::Named(Name::new(type_name)),
+                    nullable,
+                }),
+            })
+        };
+
+        let directive =
+            |name: &str,
+             description: &str,
+             arguments: Vec<Positioned<InputValueDefinition>>,
+             locations: &[DirectiveLocation]| DirectiveDefinition {
+                description: Some(default_positioned(description.to_string())),
+                name: default_positioned_name(name),
+                arguments,
+                is_repeatable: false,
+                locations: locations.iter().cloned().map(default_positioned).collect(),
+            };
+
+        let conditional_locations = [
+            DirectiveLocation::Field,
+            DirectiveLocation::FragmentSpread,
+            DirectiveLocation::InlineFragment,
+        ];
+
+        vec![
+            directive(
+                "include",
+                "Directs the executor to include this field or fragment only when the `if` argument is true.",
+                vec![argument("if", "Included when true.", "Boolean", false, None)],
+                &conditional_locations,
+            ),
+            directive(
+                "skip",
+                "Directs the executor to skip this field or fragment when the `if` argument is true.",
+                vec![argument("if", "Skipped when true.", "Boolean", false, None)],
+                &conditional_locations,
+            ),
+            directive(
+                "defer",
+                "Directs the executor to deliver this fragment after the rest of the response.",
+                vec![
+                    argument(
+                        "if",
+                        "Deferred when true.",
+                        "Boolean",
+                        false,
+                        Some(ConstValue::Boolean(true)),
+                    ),
+                    argument(
+                        "label",
+                        "A unique label to identify the deferred payload.",
+                        "String",
+                        true,
+                        None,
+                    ),
+                ],
+                &[
+                    DirectiveLocation::FragmentSpread,
+                    DirectiveLocation::InlineFragment,
+                ],
+            ),
+        ]
+    }
+
     fn create_schema_type_definition() -> TypeDefinition {
         let directives_field = Self::create_list_field(
             "directives",
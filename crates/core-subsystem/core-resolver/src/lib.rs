@@ -14,16 +14,19 @@ pub mod introspection;
 pub mod number_cmp;
 pub mod operation_resolver;
 pub mod plugin;
+pub mod response_cache;
 pub mod system_resolver;
 pub mod validation;
 pub mod value;
 
 mod field_resolver;
+mod graphql_error;
 mod interception;
 mod operation_payload;
 mod query_response;
 
 pub use field_resolver::FieldResolver;
+pub use graphql_error::{ErrorCode, GraphQLError, GraphQLErrorExtensions, PathSegment};
 pub use interception::InterceptedOperation;
-pub use operation_payload::OperationsPayload;
+pub use operation_payload::{BatchedOperationsPayload, OperationsPayload};
 pub use query_response::{QueryResponse, QueryResponseBody};
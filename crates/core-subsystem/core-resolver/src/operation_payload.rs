@@ -10,7 +10,7 @@
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct OperationsPayload {
     pub operation_name: Option<String>,
     pub query: Option<String>,
@@ -51,6 +51,30 @@ impl OperationsPayload {
         })
     }
 
+    /// Create a payload from the query parameters of a GET request. Following the
+    /// [GraphQL over HTTP](https://graphql.github.io/graphql-over-http/draft/#sec-GET) spec,
+    /// `variables` and `extensions` (if present) are JSON-encoded.
+    pub fn from_query_params(params: Value) -> Result<Self, serde_json::Error> {
+        let mut params = match params {
+            Value::Object(params) => params,
+            Value::Null => Map::new(),
+            _ => return Self::from_json(params),
+        };
+
+        for key in ["variables", "extensions"] {
+            if let Some(Value::String(encoded)) = params.get(key) {
+                let decoded = if encoded.is_empty() {
+                    Value::Null
+                } else {
+                    serde_json::from_str(encoded)?
+                };
+                params.insert(key.to_string(), decoded);
+            }
+        }
+
+        Self::from_json(Value::Object(params))
+    }
+
     pub fn to_json(&self) -> Result<Value, serde_json::Error> {
         let raw_payload = RawOperationsPayload {
             operation_name: self.operation_name.clone(),
@@ -68,3 +92,89 @@ impl OperationsPayload {
         serde_json::to_value(&raw_payload)
     }
 }
+
+/// The operations in the body of a POST request: either a single operation or a batch of them
+/// (sent as a JSON array)
+#[derive(Debug)]
+pub enum BatchedOperationsPayload {
+    Single(OperationsPayload),
+    Batch(Vec<OperationsPayload>),
+}
+
+impl BatchedOperationsPayload {
+    pub fn from_json(json: Value) -> Result<Self, serde_json::Error> {
+        match json {
+            Value::Array(payloads) => {
+                if payloads.is_empty() {
+                    return Err(serde::de::Error::custom("Empty batch of operations"));
+                }
+
+                payloads
+                    .into_iter()
+                    .map(OperationsPayload::from_json)
+                    .collect::<Result<Vec<_>, _>>()
+                    .map(BatchedOperationsPayload::Batch)
+            }
+            _ => OperationsPayload::from_json(json).map(BatchedOperationsPayload::Single),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    #[test]
+    fn query_params() {
+        let payload = OperationsPayload::from_query_params(json!({
+            "query": "query($id: Int!) { concert(id: $id) { id } }",
+            "operationName": "",
+            "variables": r#"{"id": 1}"#,
+            "extensions": r#"{"persistedQuery": {"version": 1, "sha256Hash": "abc"}}"#,
+        }))
+        .unwrap();
+
+        assert_eq!(
+            payload.query.as_deref(),
+            Some("query($id: Int!) { concert(id: $id) { id } }")
+        );
+        assert_eq!(payload.variables, json!({"id": 1}).as_object().cloned());
+        assert_eq!(payload.query_hash.as_deref(), Some("abc"));
+    }
+
+    #[test]
+    fn query_params_without_variables() {
+        let payload =
+            OperationsPayload::from_query_params(json!({"query": "{ concerts { id } }"})).unwrap();
+
+        assert_eq!(payload.variables, None);
+        assert_eq!(payload.query_hash, None);
+    }
+
+    #[test]
+    fn query_params_with_malformed_variables() {
+        assert!(OperationsPayload::from_query_params(json!({
+            "query": "{ concerts { id } }",
+            "variables": "{id: 1}"
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn batched_payload() {
+        let single = BatchedOperationsPayload::from_json(json!({"query": "{ concerts { id } }"}));
+        assert!(matches!(single, Ok(BatchedOperationsPayload::Single(_))));
+
+        let batch = BatchedOperationsPayload::from_json(json!([
+            {"query": "{ concerts { id } }"},
+            {"query": "{ venues { id } }"}
+        ]));
+        assert!(
+            matches!(batch, Ok(BatchedOperationsPayload::Batch(payloads)) if payloads.len() == 2)
+        );
+
+        assert!(BatchedOperationsPayload::from_json(json!([])).is_err());
+    }
+}
@@ -45,8 +45,10 @@ impl FieldResolver<QueryResponse, SystemResolutionError, SystemResolver> for Val
         let intercepted_operation =
             InterceptedOperation::new(Some(interception_tree), self.typ, field, system_resolver);
 
-        let QueryResponse { body, headers } =
-            intercepted_operation.resolve(request_context).await?;
+        let QueryResponse { body, headers } = intercepted_operation
+            .resolve(request_context)
+            .await
+            .map_err(|e| e.at_field(field.output_name()))?;
 
         // A proceed call in an around interceptor or a module call may have returned more fields
         // that necessary, so we need to filter out the fields that are not needed.
@@ -55,7 +57,8 @@ impl FieldResolver<QueryResponse, SystemResolutionError, SystemResolver> for Val
             QueryResponseBody::Json(value @ serde_json::Value::Object(_)) => {
                 let resolved_set = value
                     .resolve_fields(&field.subfields, &(), request_context)
-                    .await?;
+                    .await
+                    .map_err(|e| e.at_field(field.output_name()))?;
                 QueryResponseBody::Json(serde_json::Value::Object(
                     resolved_set.into_iter().collect(),
                 ))
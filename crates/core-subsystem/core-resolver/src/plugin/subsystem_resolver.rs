@@ -9,14 +9,22 @@
 
 use crate::{
     context::RequestContext, system_resolver::SystemResolver, validation::field::ValidatedField,
-    InterceptedOperation, QueryResponse,
+    ErrorCode, InterceptedOperation, QueryResponse,
 };
 use async_graphql_parser::types::{FieldDefinition, OperationType, TypeDefinition};
 use async_trait::async_trait;
 use core_plugin_shared::interception::InterceptorIndex;
+use futures::stream::BoxStream;
+use serde_json::Value;
 use thiserror::Error;
 use tokio::runtime::Handle;
 
+/// A stream of raw events produced by a subscription (see [SubsystemResolver::subscribe]).
+///
+/// Each event is opaque to everyone but the subsystem that produced it, which turns it into a
+/// response through [SubsystemResolver::resolve_subscription_event].
+pub type SubscriptionEventStream = BoxStream<'static, Value>;
+
 /// Provides resolution of operations and interceptor methods for a subsystem.
 ///
 /// When using a [SubsystemResolver] that has been loaded as a `cdylib`, it is
@@ -92,6 +100,34 @@ pub trait SubsystemResolver: Sync {
         system_resolver: &'a SystemResolver,
     ) -> Result<Option<QueryResponse>, SubsystemResolutionError>;
 
+    /// Subscribe to an individual subscription operation
+    ///
+    /// Returns `None` if the operation is not handled by this subsystem. Subsystems that don't
+    /// support subscriptions may rely on the default implementation.
+    async fn subscribe<'a>(
+        &'a self,
+        _operation: &'a ValidatedField,
+        _request_context: &'a RequestContext<'a>,
+    ) -> Result<Option<SubscriptionEventStream>, SubsystemResolutionError> {
+        Ok(None)
+    }
+
+    /// Resolve an event produced by the stream returned from `subscribe`
+    ///
+    /// The request context is created afresh for each event, so that access rules are evaluated
+    /// against the state at the time of the event. Returns `None` if the event should not be
+    /// delivered to the subscriber (for example, if it doesn't match the operation's filter or
+    /// the subscriber has no access to it).
+    async fn resolve_subscription_event<'a>(
+        &'a self,
+        _operation: &'a ValidatedField,
+        _event: &'a Value,
+        _request_context: &'a RequestContext<'a>,
+        _system_resolver: &'a SystemResolver,
+    ) -> Result<Option<QueryResponse>, SubsystemResolutionError> {
+        Ok(None)
+    }
+
     // Support for schema creation (and in turn, validation)
 
     /// Queries supported by this subsystem
@@ -99,6 +135,10 @@ pub trait SubsystemResolver: Sync {
     /// Mutations supported by this subsystem
 
     fn schema_mutations(&self) -> Vec<FieldDefinition>;
+    /// Subscriptions supported by this subsystem
+    fn schema_subscriptions(&self) -> Vec<FieldDefinition> {
+        vec![]
+    }
     /// Types supported by this subsystem. This includes types explicitly defined by user types as
     /// well as types derived from user types (such as for predicates)
     fn schema_types(&self) -> Vec<TypeDefinition>;
@@ -113,7 +153,7 @@ pub enum SubsystemResolutionError {
     Authorization,
 
     #[error("{0}")]
-    UserDisplayError(String), // Error message to be displayed to the user (subsystems should hide internal errors through this)
+    UserDisplayError(String, ErrorCode), // Error message to be displayed to the user (subsystems should hide internal errors through this)
 
     #[error("No interceptor found")]
     NoInterceptorFound, // Almost certainly a programming error (we asked a wrong subsystem)
@@ -126,8 +166,17 @@ impl SubsystemResolutionError {
                 Some(format!("Invalid field {field_name} for {container_type}"))
             }
             SubsystemResolutionError::Authorization => Some("Not authorized".to_string()),
-            SubsystemResolutionError::UserDisplayError(message) => Some(message.to_string()),
+            SubsystemResolutionError::UserDisplayError(message, _) => Some(message.to_string()),
             SubsystemResolutionError::NoInterceptorFound => None,
         }
     }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            SubsystemResolutionError::InvalidField(_, _) => ErrorCode::ValidationFailed,
+            SubsystemResolutionError::Authorization => ErrorCode::Unauthorized,
+            SubsystemResolutionError::UserDisplayError(_, code) => *code,
+            SubsystemResolutionError::NoInterceptorFound => ErrorCode::InternalServerError,
+        }
+    }
 }
@@ -0,0 +1,519 @@
+use std::{
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use async_graphql_parser::types::{OperationType, TypeDefinition, TypeKind};
+use core_model::{
+    cache_policy::{CachePolicy, CacheScope},
+    type_normalization::TypeDefinitionIntrospection,
+};
+use exo_sql::PhysicalTableName;
+use indexmap::IndexMap;
+use serde_json::json;
+
+use common::http::RequestHead;
+
+use crate::{
+    introspection::definition::schema::{
+        Schema, MUTATION_ROOT_TYPENAME, QUERY_ROOT_TYPENAME, SUBSCRIPTION_ROOT_TYPENAME,
+    },
+    validation::{field::ValidatedField, operation::ValidatedOperation, underlying_type},
+    value::Val,
+    OperationsPayload,
+};
+
+/// A serialized response kept in the [ResponseCache]
+#[derive(Debug)]
+pub struct CachedResponse {
+    pub body: Vec<u8>,
+    /// The response headers (other than `cache-control`; see [CachedResponse::cache_control])
+    pub headers: Vec<(String, String)>,
+    dependencies: CacheDependencies,
+    scope: CacheScope,
+    expires_at: Instant,
+}
+
+impl CachedResponse {
+    /// The `cache-control` header value for the response (with the max age reduced by the time
+    /// the response has spent in the cache)
+    pub fn cache_control(&self) -> String {
+        let remaining = self.expires_at.saturating_duration_since(Instant::now());
+
+        CachePolicy {
+            max_age: remaining.as_secs().try_into().unwrap_or(u32::MAX),
+            scope: self.scope,
+        }
+        .header_value()
+    }
+}
+
+/// The data a cached response is computed from (a change to any of these evicts the response)
+#[derive(Debug, Default)]
+pub struct CacheDependencies {
+    /// The types included in the response
+    pub types: HashSet<String>,
+    /// The tables read to compute the response
+    pub tables: HashSet<PhysicalTableName>,
+}
+
+/// In-process cache of query responses (for queries with a cache policy specified through the
+/// `@cache` annotation).
+///
+/// Each response is kept until its max age elapses or until a committed transaction modifies its
+/// data (see [ResponseCache::invalidate] and [ResponseCache::invalidate_tables]). To keep the memory
+/// usage bounded, we keep only the `capacity` most recently used responses.
+///
+/// The response to an operation may depend on the request's context (for example, on the
+/// authenticated user through an access rule), so we remember the context fields that its
+/// responses used and include their values in the key (see [ResponseCache::context_fields]).
+#[derive(Debug)]
+pub struct ResponseCache {
+    capacity: usize,
+    // Ordered from the least to the most recently used
+    responses: Mutex<IndexMap<String, Arc<CachedResponse>>>,
+    // The context fields (as `(annotation, key)` pairs) used by the responses to each operation
+    // (ordered from the least to the most recently recorded)
+    context_fields: Mutex<IndexMap<String, Vec<(String, String)>>>,
+    // Incremented on each invalidation (see [ResponseCache::invalidation_count])
+    invalidation_count: AtomicU64,
+}
+
+impl ResponseCache {
+    /// Create a cache that keeps up to `capacity` responses (a zero capacity disables caching)
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            responses: Mutex::new(IndexMap::new()),
+            context_fields: Mutex::new(IndexMap::new()),
+            invalidation_count: AtomicU64::new(0),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    pub fn get(&self, key: &str) -> Option<Arc<CachedResponse>> {
+        let mut responses = self.responses.lock().unwrap();
+
+        let index = responses.get_index_of(key)?;
+
+        if responses[index].expires_at <= Instant::now() {
+            responses.shift_remove_index(index);
+            return None;
+        }
+
+        let last = responses.len() - 1;
+        responses.move_index(index, last);
+
+        responses
+            .get_index(last)
+            .map(|(_, response)| response.clone())
+    }
+
+    /// Store a response computed after the invalidation count was `invalidation_count` (see
+    /// [ResponseCache::invalidation_count]).
+    ///
+    /// If an invalidation happened in the meantime, the response may already be stale (for
+    /// example, if it read a table before a concurrent mutation committed), so we skip it.
+    pub fn insert(
+        &self,
+        key: String,
+        body: Vec<u8>,
+        headers: Vec<(String, String)>,
+        dependencies: CacheDependencies,
+        policy: CachePolicy,
+        invalidation_count: u64,
+    ) {
+        if self.capacity == 0 || policy.max_age == 0 {
+            return;
+        }
+
+        let response = Arc::new(CachedResponse {
+            body,
+            headers,
+            dependencies,
+            scope: policy.scope,
+            expires_at: Instant::now() + Duration::from_secs(policy.max_age.into()),
+        });
+
+        let mut responses = self.responses.lock().unwrap();
+
+        // Checked while holding the lock, since invalidations take it, too
+        if self.invalidation_count() != invalidation_count {
+            return;
+        }
+
+        if !responses.contains_key(&key) && responses.len() == self.capacity {
+            responses.shift_remove_index(0);
+        }
+        // Inserting an existing key keeps its position, so move it to the end explicitly
+        let (index, _) = responses.insert_full(key, response);
+        let last = responses.len() - 1;
+        responses.move_index(index, last);
+    }
+
+    /// The number of invalidations so far (to be passed to [ResponseCache::insert])
+    pub fn invalidation_count(&self) -> u64 {
+        self.invalidation_count.load(Ordering::SeqCst)
+    }
+
+    /// Evict the responses that include any of the types (or all responses, if the types are not known)
+    pub fn invalidate(&self, types: Option<&HashSet<String>>) {
+        self.evict(|response| match types {
+            Some(types) => !response.dependencies.types.is_disjoint(types),
+            None => true,
+        })
+    }
+
+    /// Evict the responses that read any of the tables
+    pub fn invalidate_tables(&self, tables: &HashSet<PhysicalTableName>) {
+        if tables.is_empty() {
+            return;
+        }
+
+        self.evict(|response| !response.dependencies.tables.is_disjoint(tables))
+    }
+
+    fn evict(&self, predicate: impl Fn(&CachedResponse) -> bool) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut responses = self.responses.lock().unwrap();
+        self.invalidation_count.fetch_add(1, Ordering::SeqCst);
+        responses.retain(|_, response| !predicate(response));
+    }
+
+    /// The context fields used so far by the responses to the operation with the given key (see
+    /// [response_cache_key])
+    pub fn context_fields(&self, operation_key: &str) -> Vec<(String, String)> {
+        self.context_fields
+            .lock()
+            .unwrap()
+            .get(operation_key)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Record the context fields used by a response to the operation with the given key. Returns
+    /// all the context fields recorded for the operation so far.
+    pub fn record_context_fields(
+        &self,
+        operation_key: &str,
+        fields: Vec<(String, String)>,
+    ) -> Vec<(String, String)> {
+        let mut context_fields = self.context_fields.lock().unwrap();
+
+        let mut recorded = context_fields
+            .shift_remove(operation_key)
+            .unwrap_or_default();
+        for field in fields {
+            if !recorded.contains(&field) {
+                recorded.push(field);
+            }
+        }
+
+        if context_fields.len() == self.capacity {
+            context_fields.shift_remove_index(0);
+        }
+        context_fields.insert(operation_key.to_string(), recorded.clone());
+
+        recorded
+    }
+}
+
+/// The key for the operation in the payload (see [context_cache_key] for the key for its response).
+///
+/// The response to a query with the private scope depends on the user, so its key includes the
+/// request headers that identify the user.
+pub fn response_cache_key(
+    payload: &OperationsPayload,
+    scope: CacheScope,
+    request_head: &(dyn RequestHead + Send + Sync),
+) -> String {
+    let user = match scope {
+        CacheScope::Public => json!(null),
+        CacheScope::Private => json!([
+            request_head.get_headers("authorization"),
+            request_head.get_headers("cookie"),
+        ]),
+    };
+
+    json!([
+        payload.query_hash,
+        payload.query,
+        payload.operation_name,
+        payload.variables,
+        user,
+    ])
+    .to_string()
+}
+
+/// The key for a response given the key for its operation (see [response_cache_key]) and the values
+/// of the context fields the operation's responses used (see [ResponseCache::context_fields])
+pub fn context_cache_key(operation_key: &str, context_values: &[Option<Val>]) -> String {
+    json!([operation_key, context_values]).to_string()
+}
+
+/// The types whose data may be included in the response to the operation (for a query) or may be
+/// affected by the operation (for a mutation).
+///
+/// For a query, these are the types of the selected fields. For a mutation, we can't tell which
+/// types it modifies beyond those it returns, so we include all types reachable from the return
+/// types of its root fields. Returns `None` if the affected types cannot be determined (for
+/// example, for a mutation that returns a scalar).
+pub fn operation_types(schema: &Schema, operation: &ValidatedOperation) -> Option<HashSet<String>> {
+    let mut types = HashSet::new();
+
+    match operation.typ {
+        OperationType::Mutation => {
+            for field in &operation.fields {
+                let field_type = root_field_type(schema, operation.typ, field)?;
+                collect_reachable_types(schema, field_type, &mut types);
+            }
+        }
+        _ => {
+            for field in &operation.fields {
+                if let Some(field_type) = root_field_type(schema, operation.typ, field) {
+                    collect_selected_types(schema, field_type, &field.subfields, &mut types);
+                }
+            }
+        }
+    }
+
+    (!types.is_empty()).then_some(types)
+}
+
+fn root_field_type<'a>(
+    schema: &'a Schema,
+    operation_type: OperationType,
+    field: &ValidatedField,
+) -> Option<&'a TypeDefinition> {
+    let root_type_name = match operation_type {
+        OperationType::Query => QUERY_ROOT_TYPENAME,
+        OperationType::Mutation => MUTATION_ROOT_TYPENAME,
+        OperationType::Subscription => SUBSCRIPTION_ROOT_TYPENAME,
+    };
+
+    field_type(
+        schema,
+        schema.get_type_definition(root_type_name)?,
+        &field.name,
+    )
+    .filter(|typ| matches!(typ.kind, TypeKind::Object(_)))
+}
+
+fn field_type<'a>(
+    schema: &'a Schema,
+    container_type: &TypeDefinition,
+    field_name: &str,
+) -> Option<&'a TypeDefinition> {
+    let field_definition = container_type
+        .fields()?
+        .iter()
+        .find(|f| f.node.name.node == field_name)?;
+
+    schema.get_type_definition(underlying_type(&field_definition.node.ty.node))
+}
+
+fn collect_selected_types(
+    schema: &Schema,
+    typ: &TypeDefinition,
+    fields: &[ValidatedField],
+    types: &mut HashSet<String>,
+) {
+    types.insert(typ.name.node.to_string());
+
+    for field in fields {
+        if let Some(field_type) = field_type(schema, typ, &field.name) {
+            if matches!(field_type.kind, TypeKind::Object(_)) {
+                collect_selected_types(schema, field_type, &field.subfields, types);
+            }
+        }
+    }
+}
+
+fn collect_reachable_types(schema: &Schema, typ: &TypeDefinition, types: &mut HashSet<String>) {
+    if !types.insert(typ.name.node.to_string()) {
+        return;
+    }
+
+    for field in typ.fields().into_iter().flatten() {
+        if let Some(field_type) = field_type(schema, typ, &field.node.name.node) {
+            if matches!(field_type.kind, TypeKind::Object(_)) {
+                collect_reachable_types(schema, field_type, types);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn public(max_age: u32) -> CachePolicy {
+        CachePolicy {
+            max_age,
+            scope: CacheScope::Public,
+        }
+    }
+
+    fn table(name: &str) -> PhysicalTableName {
+        PhysicalTableName::new(name, None)
+    }
+
+    fn insert(cache: &ResponseCache, key: &str, types: &[&str]) {
+        insert_with_tables(cache, key, types, &[]);
+    }
+
+    fn insert_with_tables(cache: &ResponseCache, key: &str, types: &[&str], tables: &[&str]) {
+        cache.insert(
+            key.to_string(),
+            key.as_bytes().to_vec(),
+            vec![],
+            CacheDependencies {
+                types: types.iter().map(|t| t.to_string()).collect(),
+                tables: tables.iter().map(|t| table(t)).collect(),
+            },
+            public(60),
+            cache.invalidation_count(),
+        );
+    }
+
+    fn cached_body(cache: &ResponseCache, key: &str) -> Option<Vec<u8>> {
+        cache.get(key).map(|response| response.body.clone())
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let cache = ResponseCache::new(2);
+
+        insert(&cache, "key1", &["Concert"]);
+        insert(&cache, "key2", &["Concert"]);
+
+        // Makes "key1" the most recently used, so "key2" will be evicted next
+        assert!(cache.get("key1").is_some());
+
+        insert(&cache, "key3", &["Concert"]);
+
+        assert_eq!(cached_body(&cache, "key1"), Some(b"key1".to_vec()));
+        assert!(cache.get("key2").is_none());
+        assert_eq!(cached_body(&cache, "key3"), Some(b"key3".to_vec()));
+    }
+
+    #[test]
+    fn invalidates_by_type() {
+        let cache = ResponseCache::new(10);
+
+        insert(&cache, "concerts", &["Concert", "Venue"]);
+        insert(&cache, "artists", &["Artist"]);
+
+        cache.invalidate(Some(&HashSet::from(["Venue".to_string()])));
+
+        assert!(cache.get("concerts").is_none());
+        assert!(cache.get("artists").is_some());
+
+        cache.invalidate(None);
+
+        assert!(cache.get("artists").is_none());
+    }
+
+    #[test]
+    fn invalidates_by_table() {
+        let cache = ResponseCache::new(10);
+
+        insert_with_tables(&cache, "concerts", &["Concert"], &["concerts", "venues"]);
+        insert_with_tables(&cache, "artists", &["Artist"], &["artists"]);
+
+        cache.invalidate_tables(&HashSet::new());
+        assert!(cache.get("concerts").is_some());
+
+        cache.invalidate_tables(&HashSet::from([table("venues")]));
+
+        assert!(cache.get("concerts").is_none());
+        assert!(cache.get("artists").is_some());
+    }
+
+    #[test]
+    fn skips_responses_computed_before_invalidation() {
+        let cache = ResponseCache::new(10);
+
+        let invalidation_count = cache.invalidation_count();
+        cache.invalidate_tables(&HashSet::from([table("concerts")]));
+
+        cache.insert(
+            "concerts".to_string(),
+            vec![],
+            vec![],
+            CacheDependencies::default(),
+            public(60),
+            invalidation_count,
+        );
+        assert!(cache.get("concerts").is_none());
+    }
+
+    #[test]
+    fn skips_uncacheable_responses() {
+        let cache = ResponseCache::new(0);
+        insert(&cache, "key1", &["Concert"]);
+        assert!(cache.get("key1").is_none());
+
+        let cache = ResponseCache::new(10);
+        cache.insert(
+            "key1".to_string(),
+            vec![],
+            vec![],
+            CacheDependencies::default(),
+            public(0),
+            cache.invalidation_count(),
+        );
+        assert!(cache.get("key1").is_none());
+    }
+
+    #[test]
+    fn reports_remaining_max_age() {
+        let cache = ResponseCache::new(10);
+
+        insert(&cache, "concerts", &["Concert"]);
+
+        // Computed from the expiry (not the original max age), so it may have been rounded down
+        let cache_control = cache.get("concerts").unwrap().cache_control();
+        assert!(
+            ["max-age=59, public", "max-age=60, public"].contains(&cache_control.as_str()),
+            "{cache_control}"
+        );
+    }
+
+    #[test]
+    fn records_context_fields() {
+        let cache = ResponseCache::new(10);
+
+        let field = |annotation: &str, key: &str| (annotation.to_string(), key.to_string());
+
+        assert!(cache.context_fields("concerts").is_empty());
+
+        cache.record_context_fields("concerts", vec![field("jwt", "sub")]);
+        cache.record_context_fields("concerts", vec![field("header", "x-tenant")]);
+
+        assert_eq!(
+            cache.record_context_fields("concerts", vec![field("jwt", "sub")]),
+            vec![field("jwt", "sub"), field("header", "x-tenant")]
+        );
+        assert_eq!(
+            cache.context_fields("concerts"),
+            vec![field("jwt", "sub"), field("header", "x-tenant")]
+        );
+        assert!(cache.context_fields("artists").is_empty());
+
+        // Users with different context values get different keys
+        let user1 = context_cache_key("concerts", &[Some(Val::Number(1.into())), None]);
+        let user2 = context_cache_key("concerts", &[Some(Val::Number(2.into())), None]);
+        assert_ne!(user1, user2);
+    }
+}
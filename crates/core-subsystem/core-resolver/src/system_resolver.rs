@@ -7,6 +7,7 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use std::collections::HashSet;
 use std::sync::Arc;
 
 use async_graphql_parser::{
@@ -15,8 +16,10 @@ use async_graphql_parser::{
 };
 #[cfg(not(target_family = "wasm"))]
 use common::env_const::{get_enforce_trusted_documents, is_production};
+use common::http::RequestHead;
 use core_plugin_shared::{
     interception::{InterceptionMap, InterceptionTree, InterceptorIndexWithSubsystemIndex},
+    persisted_queries::PersistedQueries,
     trusted_documents::{
         TrustedDocumentEnforcement, TrustedDocumentResolutionError, TrustedDocuments,
     },
@@ -34,12 +37,16 @@ use crate::{context::provider::jwt::JwtAuthenticator, OperationsPayload};
 use crate::{
     context::RequestContext,
     introspection::definition::schema::Schema,
-    plugin::{subsystem_resolver::SubsystemResolver, SubsystemResolutionError},
+    plugin::{
+        subsystem_resolver::SubsystemResolver, SubscriptionEventStream, SubsystemResolutionError,
+    },
+    response_cache::{operation_types, ResponseCache},
     validation::{
         document_validator::DocumentValidator, field::ValidatedField,
-        operation::ValidatedOperation, validation_error::ValidationError,
+        operation::ValidatedOperation, query_cost::QueryCostModel,
+        validation_error::ValidationError,
     },
-    FieldResolver, InterceptedOperation, QueryResponse,
+    ErrorCode, FieldResolver, InterceptedOperation, PathSegment, QueryResponse,
 };
 
 pub type ExographExecuteQueryFn<'a> = dyn Fn(
@@ -52,6 +59,17 @@ pub type ExographExecuteQueryFn<'a> = dyn Fn(
     + Send
     + Sync;
 
+/// The response to each root field of an operation (see [SystemResolver::resolve_operations])
+pub type FieldResponses = Vec<(String, Result<QueryResponse, SystemResolutionError>)>;
+
+/// A subscription obtained through [SystemResolver::subscribe].
+pub struct Subscription {
+    /// The (only) root field of the subscription operation
+    pub field: ValidatedField,
+    /// The subsystem that handles the subscription
+    subsystem_index: usize,
+}
+
 /// The top-level system resolver.
 ///
 /// Delegates to subsystem resolvers to resolve individual operations.
@@ -60,11 +78,19 @@ pub struct SystemResolver {
     query_interception_map: InterceptionMap,
     mutation_interception_map: InterceptionMap,
     trusted_documents: TrustedDocuments,
+    persisted_queries: PersistedQueries,
     schema: Schema,
     pub jwt_authenticator: Arc<Option<JwtAuthenticator>>,
     pub env: Arc<dyn Environment>,
     normal_query_depth_limit: usize,
     introspection_query_depth_limit: usize,
+    query_cost_model: QueryCostModel,
+    /// The maximum number of root fields of a query to resolve concurrently (1 to resolve them one
+    /// after another in a single transaction)
+    max_concurrent_root_fields: usize,
+    /// The maximum number of operations in a batched request
+    max_batch_size: usize,
+    response_cache: ResponseCache,
 }
 
 impl SystemResolver {
@@ -74,11 +100,16 @@ impl SystemResolver {
         query_interception_map: InterceptionMap,
         mutation_interception_map: InterceptionMap,
         trusted_documents: TrustedDocuments,
+        persisted_queries: PersistedQueries,
         schema: Schema,
         jwt_authenticator: Arc<Option<JwtAuthenticator>>,
         env: Arc<dyn Environment>,
         normal_query_depth_limit: usize,
         introspection_query_depth_limit: usize,
+        query_cost_model: QueryCostModel,
+        max_concurrent_root_fields: usize,
+        max_batch_size: usize,
+        response_cache: ResponseCache,
     ) -> Self {
         #[cfg(not(target_family = "wasm"))]
         let trusted_documents =
@@ -97,16 +128,28 @@ impl SystemResolver {
             query_interception_map,
             mutation_interception_map,
             trusted_documents,
+            persisted_queries,
             schema,
             jwt_authenticator,
             env: env.clone(),
             normal_query_depth_limit,
             introspection_query_depth_limit,
+            query_cost_model,
+            max_concurrent_root_fields,
+            max_batch_size,
+            response_cache,
         }
     }
 
     /// Resolve the provided top-level operation (which may contain multiple queries, mutations, or subscription).
     ///
+    /// Returns an error if the operation as a whole failed (for example, if it is invalid).
+    /// Otherwise, returns the response to each root field. The root fields of a query are resolved
+    /// independently, so a failure of one field (such as an access-denied one) is reported for
+    /// that field alone, and the other fields are still returned. Mutations, on the other hand,
+    /// are all-or-nothing (they share a transaction), so a failure of any field fails the whole
+    /// operation.
+    ///
     /// Goes through the FieldResolver for ValidatedOperation (and thus get free support for `resolve_fields`)
     /// so that we can support fragments in top-level queries in such as:
     ///
@@ -136,60 +179,260 @@ impl SystemResolver {
         operations_payload: OperationsPayload,
         request_context: &RequestContext<'a>,
         trusted_document_enforcement: TrustedDocumentEnforcement,
-    ) -> Result<Vec<(String, QueryResponse)>, SystemResolutionError> {
-        let query = self.trusted_documents.resolve(
-            operations_payload.query.as_deref(),
-            operations_payload.query_hash.as_deref(),
-            trusted_document_enforcement,
-        );
+    ) -> Result<FieldResponses, SystemResolutionError> {
+        let operation =
+            self.validate_operations_payload(operations_payload, trusted_document_enforcement)?;
 
-        let operation = match query {
-            Ok(query) => self.validate_operation(
-                query,
-                operations_payload.operation_name,
-                operations_payload.variables,
-            )?,
-            // Special handing on introspection queries made by tools to be implicitly trusted
-            // Introspection queries made by the playground and tools such as graphql-codegen send queries as a string
-            // and have top-level field `__schema` (but we also allow `__type` and `__typename` to be more widely useful).
-            Err(TrustedDocumentResolutionError::NotTrusted {
-                hash: None,
-                query: Some(query),
-            }) => {
-                let operation = self.validate_operation(
-                    &query,
-                    operations_payload.operation_name,
-                    operations_payload.variables,
-                )?;
+        self.resolve_validated_operation(operation, request_context)
+            .await
+    }
 
-                for field in &operation.fields {
-                    if field.name == "__schema"
-                        || field.name == "__type"
-                        || field.name == "__typename"
-                    {
-                        continue;
-                    }
-                    return Err(TrustedDocumentResolutionError::NotTrusted {
-                        hash: None,
-                        query: Some(query),
-                    }
-                    .into());
+    /// Resolve an operation already validated through [SystemResolver::validate_operations_payload].
+    ///
+    /// Useful when the caller needs to inspect the operation before resolving it (for example, to
+    /// reject mutations sent over HTTP GET).
+    pub async fn resolve_validated_operation<'a>(
+        &self,
+        operation: ValidatedOperation,
+        request_context: &RequestContext<'a>,
+    ) -> Result<FieldResponses, SystemResolutionError> {
+        if self.can_resolve_concurrently(&operation, request_context) {
+            return Ok(self.resolve_concurrently(&operation, request_context).await);
+        }
+
+        // If multiple operations are present, we need to ensure that we have a transaction
+        let shared_transaction = operation.fields.len() > 1;
+        if shared_transaction {
+            request_context.ensure_transaction().await;
+        }
+
+        match operation.typ {
+            OperationType::Query => Ok(futures::stream::iter(operation.fields.iter())
+                .then(|field| async {
+                    let response = if shared_transaction {
+                        self.resolve_field_in_savepoint(&operation, field, request_context)
+                            .await
+                    } else {
+                        operation.resolve_field(field, self, request_context).await
+                    };
+                    (field.output_name(), response)
+                })
+                .collect()
+                .await),
+            OperationType::Mutation => {
+                // Cached responses may include data the mutation modifies (evicted once the
+                // transaction commits; see `finalize_transaction`)
+                if self.response_cache.is_enabled() {
+                    request_context
+                        .get_base_context()
+                        .record_mutated_types(operation_types(&self.schema, &operation));
                 }
 
-                operation
+                let response = operation
+                    .resolve_fields(&operation.fields, self, request_context)
+                    .await;
+
+                Ok(response?
+                    .into_iter()
+                    .map(|(name, response)| (name, Ok(response)))
+                    .collect())
             }
-            Err(e) => {
-                return Err(e.into());
+            OperationType::Subscription => {
+                // Subscriptions need a long-lived connection (see `subscribe`)
+                Err(SystemResolutionError::UnsupportedOperationType(
+                    operation.typ,
+                ))
             }
-        };
+        }
+    }
 
-        // If multiple operations are present, we need to ensure that we have a transaction
-        if operation.fields.len() > 1 {
-            request_context.ensure_transaction().await;
+    /// Can the root fields be resolved concurrently (each with its own request context, and thus its
+    /// own database connection)?
+    ///
+    /// Only queries qualify, since their fields are independent and read-only (unlike mutations,
+    /// which must execute in order in a single transaction). We also exclude fields with interceptors
+    /// (which need a shared transaction), requests with overridden context (which are nested in
+    /// an outer operation that may have its own transaction), and queries whose response may be
+    /// cached (which need to track the context fields and tables used by all fields).
+    fn can_resolve_concurrently(
+        &self,
+        operation: &ValidatedOperation,
+        request_context: &RequestContext<'_>,
+    ) -> bool {
+        self.max_concurrent_root_fields > 1
+            && operation.typ == OperationType::Query
+            && operation.fields.len() > 1
+            && matches!(request_context, RequestContext::User(_))
+            && !(self.response_cache.is_enabled() && operation.cache_policy.is_some())
+            && operation.fields.iter().all(|field| {
+                matches!(
+                    self.applicable_interception_tree(&field.name, operation.typ),
+                    None | Some(InterceptionTree::Operation)
+                )
+            })
+    }
+
+    // Returning a boxed future (rather than using `async fn`) avoids a "higher-ranked lifetime error"
+    // when this is called from an `async_trait` method (such as when resolving a `@query` context)
+    fn resolve_concurrently<'a>(
+        &'a self,
+        operation: &'a ValidatedOperation,
+        request_context: &'a RequestContext<'a>,
+    ) -> BoxFuture<'a, FieldResponses> {
+        let request_head = request_context.get_base_context().request_head();
+
+        let field_responses: Vec<BoxFuture<'a, _>> = operation
+            .fields
+            .iter()
+            .map(|field| {
+                Box::pin(self.resolve_independent_field(operation, field, request_head))
+                    as BoxFuture<'a, _>
+            })
+            .collect();
+
+        Box::pin(
+            futures::stream::iter(field_responses)
+                .buffered(self.max_concurrent_root_fields)
+                .collect(),
+        )
+    }
+
+    /// Resolve a root field with its own request context (and thus its own transaction)
+    async fn resolve_independent_field<'a>(
+        &'a self,
+        operation: &'a ValidatedOperation,
+        field: &'a ValidatedField,
+        request_head: &'a (dyn RequestHead + Send + Sync),
+    ) -> (String, Result<QueryResponse, SystemResolutionError>) {
+        let request_context = RequestContext::new(request_head, vec![], self);
+
+        let response = operation.resolve_field(field, self, &request_context).await;
+
+        let response = self
+            .finalize_transaction(&request_context, response.is_ok())
+            .await
+            .and(response);
+
+        (field.output_name(), response)
+    }
+
+    /// Finalize the transaction of a request context created to resolve an operation (committing
+    /// it if `commit` is true).
+    ///
+    /// Once the transaction commits, evicts the cached responses that may include the data it
+    /// modified: those that read a table it wrote to and those that include a type its mutations
+    /// may modify (which covers mutations that don't write to the database).
+    pub async fn finalize_transaction(
+        &self,
+        request_context: &RequestContext<'_>,
+        commit: bool,
+    ) -> Result<(), SystemResolutionError> {
+        let ctx = request_context.get_base_context();
+        let mut tx_holder = ctx.transaction_holder.lock().await;
+
+        tx_holder.finalize(commit).await.map_err(|e| {
+            SystemResolutionError::Generic(format!("Error while finalizing transaction: {e}"))
+        })?;
+
+        if commit && self.response_cache.is_enabled() {
+            self.response_cache
+                .invalidate_tables(&tx_holder.table_usage().written);
+
+            for types in ctx.take_mutated_types() {
+                self.response_cache.invalidate(types.as_ref());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Resolve a root field in a savepoint of the shared transaction, so that a failure (such as a
+    /// database error, which would otherwise abort the transaction) doesn't affect the later fields
+    async fn resolve_field_in_savepoint<'a>(
+        &'a self,
+        operation: &'a ValidatedOperation,
+        field: &'a ValidatedField,
+        request_context: &'a RequestContext<'a>,
+    ) -> Result<QueryResponse, SystemResolutionError> {
+        let transaction_holder = &request_context.get_base_context().transaction_holder;
+
+        transaction_holder.lock().await.begin_savepoint();
+
+        let response = operation.resolve_field(field, self, request_context).await;
+
+        transaction_holder
+            .lock()
+            .await
+            .end_savepoint(response.is_err())
+            .await
+            .map_err(|e| {
+                SystemResolutionError::Generic(format!("Error while ending savepoint: {e}"))
+            })
+            .and(response)
+    }
+
+    /// Subscribe to the provided subscription operation.
+    ///
+    /// Returns the validated subscription along with a stream of raw events. The caller is expected
+    /// to pass each event to [SystemResolver::resolve_subscription_event] (with a fresh request context) to
+    /// obtain the response to be pushed to the subscriber.
+    #[instrument(
+        name = "SystemResolver::subscribe"
+        skip_all
+        )]
+    pub async fn subscribe<'a>(
+        &self,
+        operations_payload: OperationsPayload,
+        request_context: &RequestContext<'a>,
+        trusted_document_enforcement: TrustedDocumentEnforcement,
+    ) -> Result<(Subscription, SubscriptionEventStream), SystemResolutionError> {
+        let mut operation =
+            self.validate_operations_payload(operations_payload, trusted_document_enforcement)?;
+
+        if operation.typ != OperationType::Subscription {
+            return Err(SystemResolutionError::UnsupportedOperationType(
+                operation.typ,
+            ));
+        }
+
+        // The validator ensures that a subscription has exactly one root field
+        let field = operation.fields.swap_remove(0);
+
+        for (subsystem_index, resolver) in self.subsystem_resolvers.iter().enumerate() {
+            let events = resolver
+                .subscribe(&field, request_context)
+                .await
+                .map_err(|e| SystemResolutionError::from(e).at_field(field.output_name()))?;
+
+            if let Some(events) = events {
+                return Ok((
+                    Subscription {
+                        field,
+                        subsystem_index,
+                    },
+                    events,
+                ));
+            }
         }
-        operation
-            .resolve_fields(&operation.fields, self, request_context)
+
+        Err(SystemResolutionError::NoResolverFound)
+    }
+
+    /// Resolve an event produced by the stream obtained through [SystemResolver::subscribe].
+    ///
+    /// Returns `None` if the event should not be delivered to the subscriber.
+    pub async fn resolve_subscription_event<'a>(
+        &self,
+        subscription: &Subscription,
+        event: &Value,
+        request_context: &RequestContext<'a>,
+    ) -> Result<Option<QueryResponse>, SystemResolutionError> {
+        let resolver = &self.subsystem_resolvers[subscription.subsystem_index];
+
+        resolver
+            .resolve_subscription_event(&subscription.field, event, request_context, self)
             .await
+            .map_err(|e| SystemResolutionError::from(e).at_field(subscription.field.output_name()))
     }
 
     /// Obtain the interception tree associated with the given operation
@@ -267,6 +510,79 @@ impl SystemResolver {
             .map_err(|e| e.into())
     }
 
+    pub fn query_cost_model(&self) -> &QueryCostModel {
+        &self.query_cost_model
+    }
+
+    pub fn response_cache(&self) -> &ResponseCache {
+        &self.response_cache
+    }
+
+    pub fn max_batch_size(&self) -> usize {
+        self.max_batch_size
+    }
+
+    /// The types whose data may be included in the response to the operation (see [operation_types])
+    pub fn operation_types(&self, operation: &ValidatedOperation) -> Option<HashSet<String>> {
+        operation_types(&self.schema, operation)
+    }
+
+    /// Validate the operation in the payload (after resolving it through trusted documents)
+    pub fn validate_operations_payload(
+        &self,
+        operations_payload: OperationsPayload,
+        trusted_document_enforcement: TrustedDocumentEnforcement,
+    ) -> Result<ValidatedOperation, SystemResolutionError> {
+        let query = self.trusted_documents.resolve(
+            operations_payload.query.as_deref(),
+            operations_payload.query_hash.as_deref(),
+            trusted_document_enforcement,
+            &self.persisted_queries,
+        );
+
+        let operation = match query {
+            Ok(query) => self.validate_operation(
+                &query,
+                operations_payload.operation_name,
+                operations_payload.variables,
+            )?,
+            // Special handing on introspection queries made by tools to be implicitly trusted
+            // Introspection queries made by the playground and tools such as graphql-codegen send queries as a string
+            // and have top-level field `__schema` (but we also allow `__type` and `__typename` to be more widely useful).
+            Err(TrustedDocumentResolutionError::NotTrusted {
+                hash: None,
+                query: Some(query),
+            }) => {
+                let operation = self.validate_operation(
+                    &query,
+                    operations_payload.operation_name,
+                    operations_payload.variables,
+                )?;
+
+                for field in &operation.fields {
+                    if field.name == "__schema"
+                        || field.name == "__type"
+                        || field.name == "__typename"
+                    {
+                        continue;
+                    }
+                    return Err(TrustedDocumentResolutionError::NotTrusted {
+                        hash: None,
+                        query: Some(query),
+                    }
+                    .into());
+                }
+
+                operation
+            }
+            Err(e) => {
+                return Err(e.into());
+            }
+        };
+
+        Ok(operation)
+    }
+
     #[instrument(skip_all)]
     fn validate_operation(
         &self,
@@ -282,6 +598,7 @@ impl SystemResolver {
             variables,
             self.normal_query_depth_limit,
             self.introspection_query_depth_limit,
+            Some(&self.query_cost_model),
         );
 
         document_validator.validate(document)
@@ -295,7 +612,9 @@ macro_rules! exograph_execute_query {
                variables: Option<serde_json::Map<String, serde_json::Value>>,
                enforce_trusted_documents: TrustedDocumentEnforcement,
                context_override: serde_json::Value| {
-            use core_plugin_interface::core_resolver::{OperationsPayload, QueryResponseBody};
+            use core_plugin_interface::core_resolver::{
+                system_resolver::SystemResolutionError, OperationsPayload, QueryResponseBody,
+            };
             use futures::FutureExt;
 
             let new_request_context = $request_context.with_override(context_override);
@@ -312,7 +631,10 @@ macro_rules! exograph_execute_query {
                         &new_request_context,
                         enforce_trusted_documents,
                     )
-                    .await?;
+                    .await?
+                    .into_iter()
+                    .map(|(name, response)| Ok((name, response?)))
+                    .collect::<Result<Vec<_>, SystemResolutionError>>()?;
 
                 // collate result into a single QueryResponse
 
@@ -348,11 +670,7 @@ fn parse_query(query: &str) -> Result<ExecutableDocument, ValidationError> {
                 message,
                 start,
                 end,
-            } => {
-                // Error::Syntax's message is formatted with newlines, escape them properly
-                let message = message.escape_debug();
-                (format!("Syntax error:\\n{message}"), start, end)
-            }
+            } => (format!("Syntax error:\n{message}"), start, end),
             async_graphql_parser::Error::MultipleRoots { root, schema, pos } => {
                 (format!("Multiple roots of {root} type"), schema, Some(pos))
             }
@@ -395,6 +713,9 @@ fn parse_query(query: &str) -> Result<ExecutableDocument, ValidationError> {
 pub enum RequestError {
     #[error("Invalid body JSON {0}")]
     InvalidBodyJson(serde_json::Error),
+
+    #[error("The batch of {0} operations exceeds the maximum batch size of {1}")]
+    BatchTooLarge(usize, usize),
 }
 
 #[derive(Error, Debug)]
@@ -427,6 +748,16 @@ pub enum SystemResolutionError {
 
     #[error("Invalid request {0}")]
     RequestError(#[from] RequestError),
+
+    #[error("Operation type '{0}' is not supported over this transport")]
+    UnsupportedOperationType(OperationType),
+
+    #[error("{source}")]
+    Field {
+        // The output name (alias or name) of the root field whose resolution failed
+        output_name: String,
+        source: Box<SystemResolutionError>,
+    },
 }
 
 impl SystemResolutionError {
@@ -442,14 +773,387 @@ impl SystemResolutionError {
         match self {
             SystemResolutionError::Validation(error) => Some(error.to_string()),
             SystemResolutionError::SubsystemResolutionError(error) => error.user_error_message(),
-            SystemResolutionError::TrustedDocumentResolution(e) => {
-                warn!("Error executing: {e}");
-                Some("Operation not allowed".to_string())
+            SystemResolutionError::UnsupportedOperationType(_) => Some(self.to_string()),
+            SystemResolutionError::RequestError(error @ RequestError::BatchTooLarge(..)) => {
+                Some(error.to_string())
             }
+            SystemResolutionError::TrustedDocumentResolution(e) => match e {
+                // The exact messages expected by clients of the automatic persisted queries protocol
+                TrustedDocumentResolutionError::NotFound => {
+                    Some("PersistedQueryNotFound".to_string())
+                }
+                TrustedDocumentResolutionError::HashMismatch => {
+                    Some("provided sha does not match query".to_string())
+                }
+                _ => {
+                    warn!("Error executing: {e}");
+                    Some("Operation not allowed".to_string())
+                }
+            },
             SystemResolutionError::Delegate(error) => error
                 .downcast_ref::<SystemResolutionError>()
                 .map(|error| error.user_error_message()),
+            SystemResolutionError::Field { source, .. } => source.explicit_message(),
             _ => None,
         }
     }
+
+    /// Associate this error with the root field whose resolution failed
+    pub fn at_field(self, output_name: String) -> Self {
+        SystemResolutionError::Field {
+            output_name,
+            source: Box::new(self),
+        }
+    }
+
+    /// The code to report as `extensions.code`. Consistent with [Self::user_error_message], so an
+    /// error whose details are hidden is reported as [ErrorCode::InternalServerError].
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            SystemResolutionError::Validation(error) => error.code(),
+            SystemResolutionError::SubsystemResolutionError(error) => error.code(),
+            SystemResolutionError::UnsupportedOperationType(_) => ErrorCode::OperationNotSupported,
+            SystemResolutionError::TrustedDocumentResolution(e) => match e {
+                TrustedDocumentResolutionError::NotFound => ErrorCode::PersistedQueryNotFound,
+                TrustedDocumentResolutionError::HashMismatch => ErrorCode::BadUserInput,
+                _ => ErrorCode::OperationNotAllowed,
+            },
+            SystemResolutionError::RequestError(_) => ErrorCode::BadUserInput,
+            SystemResolutionError::Delegate(error) => error
+                .downcast_ref::<SystemResolutionError>()
+                .map(|error| error.code())
+                .unwrap_or(ErrorCode::InternalServerError),
+            SystemResolutionError::Field { source, .. } => source.code(),
+            _ => ErrorCode::InternalServerError,
+        }
+    }
+
+    /// The path to the response field associated with this error (empty for errors that
+    /// prevented execution altogether, such as validation errors)
+    pub fn path(&self) -> Vec<PathSegment> {
+        match self {
+            SystemResolutionError::Field { output_name, .. } => {
+                vec![PathSegment::Field(output_name.clone())]
+            }
+            _ => vec![],
+        }
+    }
+
+    /// The locations in the operation document associated with this error
+    pub fn locations(&self) -> Vec<Pos> {
+        match self {
+            SystemResolutionError::Validation(error) => error.positions(),
+            SystemResolutionError::Field { source, .. } => source.locations(),
+            _ => vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use async_graphql_parser::types::{FieldDefinition, TypeDefinition};
+    use async_graphql_value::{indexmap::IndexMap, Name};
+    use async_trait::async_trait;
+    use core_plugin_shared::interception::InterceptorIndex;
+    use exo_env::MapEnvironment;
+    use serde_json::json;
+
+    use crate::{plugin::SubsystemResolutionError, QueryResponseBody};
+
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn concurrent_partitioning() {
+        let query = operation(OperationType::Query, &["first", "second"]);
+
+        let (system_resolver, _) = test_system_resolver(2, HashMap::new());
+        let request_context = RequestContext::new(&REQUEST, vec![], &system_resolver);
+
+        assert!(system_resolver.can_resolve_concurrently(&query, &request_context));
+
+        // A single field has nothing to resolve concurrently with
+        let single_field_query = operation(OperationType::Query, &["first"]);
+        assert!(!system_resolver.can_resolve_concurrently(&single_field_query, &request_context));
+
+        // Mutations must execute in order in a single transaction
+        let mutation = operation(OperationType::Mutation, &["first", "second"]);
+        assert!(!system_resolver.can_resolve_concurrently(&mutation, &request_context));
+
+        // A nested operation may be part of an outer transaction
+        let overridden_context = request_context.with_override(json!({}));
+        assert!(!system_resolver.can_resolve_concurrently(&query, &overridden_context));
+    }
+
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn serial_partitioning() {
+        let query = operation(OperationType::Query, &["first", "second"]);
+
+        // The default (see `EXO_MAX_CONCURRENT_ROOT_FIELDS`)
+        let (system_resolver, _) = test_system_resolver(1, HashMap::new());
+        let request_context = RequestContext::new(&REQUEST, vec![], &system_resolver);
+        assert!(!system_resolver.can_resolve_concurrently(&query, &request_context));
+
+        // A field with interceptors (other than the bare operation) needs a shared transaction
+        let (system_resolver, _) = test_system_resolver(
+            2,
+            HashMap::from([(
+                "second".to_string(),
+                InterceptionTree::Around {
+                    core: Box::new(InterceptionTree::Operation),
+                    interceptor: InterceptorIndexWithSubsystemIndex::new(0, InterceptorIndex(0)),
+                },
+            )]),
+        );
+        let request_context = RequestContext::new(&REQUEST, vec![], &system_resolver);
+        assert!(!system_resolver.can_resolve_concurrently(&query, &request_context));
+
+        let (system_resolver, _) = test_system_resolver(
+            2,
+            HashMap::from([("second".to_string(), InterceptionTree::Operation)]),
+        );
+        let request_context = RequestContext::new(&REQUEST, vec![], &system_resolver);
+        assert!(system_resolver.can_resolve_concurrently(&query, &request_context));
+    }
+
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn concurrent_resolution() {
+        for (max_concurrent_root_fields, expected_max_in_flight) in [(1, 1), (2, 2), (5, 3)] {
+            let query = operation(OperationType::Query, &["first", "second", "third"]);
+            let (system_resolver, max_in_flight) =
+                test_system_resolver(max_concurrent_root_fields, HashMap::new());
+            let request_context = RequestContext::new(&REQUEST, vec![], &system_resolver);
+
+            let responses = system_resolver
+                .resolve_validated_operation(query, &request_context)
+                .await
+                .unwrap();
+
+            assert_eq!(
+                response_values(responses),
+                vec![
+                    ("first".to_string(), Ok(json!("first"))),
+                    ("second".to_string(), Ok(json!("second"))),
+                    ("third".to_string(), Ok(json!("third"))),
+                ]
+            );
+            assert_eq!(
+                max_in_flight.load(Ordering::SeqCst),
+                expected_max_in_flight,
+                "max_concurrent_root_fields: {max_concurrent_root_fields}"
+            );
+        }
+    }
+
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn error_isolation() {
+        // Both when resolving concurrently and serially, a failing field doesn't affect the others
+        for max_concurrent_root_fields in [1, 3] {
+            let query = operation(OperationType::Query, &["first", "failing", "third"]);
+            let (system_resolver, _) =
+                test_system_resolver(max_concurrent_root_fields, HashMap::new());
+            let request_context = RequestContext::new(&REQUEST, vec![], &system_resolver);
+
+            let responses = system_resolver
+                .resolve_validated_operation(query, &request_context)
+                .await
+                .unwrap();
+
+            assert_eq!(
+                response_values(responses),
+                vec![
+                    ("first".to_string(), Ok(json!("first"))),
+                    (
+                        "failing".to_string(),
+                        Err((
+                            ErrorCode::Unauthorized,
+                            vec![PathSegment::Field("failing".into())]
+                        ))
+                    ),
+                    ("third".to_string(), Ok(json!("third"))),
+                ],
+                "max_concurrent_root_fields: {max_concurrent_root_fields}"
+            );
+        }
+    }
+
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn independent_field() {
+        let query = operation(OperationType::Query, &["first", "failing"]);
+        let (system_resolver, _) = test_system_resolver(2, HashMap::new());
+
+        let (name, response) = system_resolver
+            .resolve_independent_field(&query, &query.fields[0], &REQUEST)
+            .await;
+        assert_eq!(name, "first");
+        assert_eq!(response.unwrap().body.to_json().unwrap(), json!("first"));
+
+        let (name, response) = system_resolver
+            .resolve_independent_field(&query, &query.fields[1], &REQUEST)
+            .await;
+        assert_eq!(name, "failing");
+        assert_eq!(response.unwrap_err().code(), ErrorCode::Unauthorized);
+    }
+
+    type ResponseValue = Result<Value, (ErrorCode, Vec<PathSegment>)>;
+
+    fn response_values(responses: FieldResponses) -> Vec<(String, ResponseValue)> {
+        responses
+            .into_iter()
+            .map(|(name, response)| {
+                let value = response
+                    .map(|response| response.body.to_json().unwrap())
+                    .map_err(|error| (error.code(), error.path()));
+                (name, value)
+            })
+            .collect()
+    }
+
+    fn operation(typ: OperationType, field_names: &[&str]) -> ValidatedOperation {
+        ValidatedOperation {
+            name: None,
+            typ,
+            fields: field_names
+                .iter()
+                .map(|field_name| ValidatedField {
+                    alias: None,
+                    name: Name::new(field_name),
+                    arguments: IndexMap::new(),
+                    subfields: vec![],
+                })
+                .collect(),
+            deferred: vec![],
+            streamed: vec![],
+            cost: 0,
+            cache_policy: None,
+        }
+    }
+
+    /// A system resolver with a subsystem that resolves each field to its name (or fails for the
+    /// "failing" field), along with the maximum number of fields observed to be resolved at once
+    fn test_system_resolver(
+        max_concurrent_root_fields: usize,
+        query_interceptions: HashMap<String, InterceptionTree>,
+    ) -> (SystemResolver, Arc<AtomicUsize>) {
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+
+        let subsystem_resolver = TestSubsystemResolver {
+            in_flight: AtomicUsize::new(0),
+            max_in_flight: max_in_flight.clone(),
+        };
+
+        let system_resolver = SystemResolver::new(
+            vec![Box::new(subsystem_resolver)],
+            InterceptionMap {
+                map: query_interceptions,
+            },
+            InterceptionMap {
+                map: HashMap::new(),
+            },
+            TrustedDocuments::all(),
+            PersistedQueries::new(0),
+            Schema::new(vec![], vec![], vec![], vec![]),
+            None.into(),
+            Arc::new(MapEnvironment::from(HashMap::new())),
+            10,
+            10,
+            QueryCostModel::default(),
+            max_concurrent_root_fields,
+            10,
+            ResponseCache::new(0),
+        );
+
+        (system_resolver, max_in_flight)
+    }
+
+    struct TestSubsystemResolver {
+        in_flight: AtomicUsize,
+        max_in_flight: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl SubsystemResolver for TestSubsystemResolver {
+        fn id(&self) -> &'static str {
+            "test"
+        }
+
+        async fn resolve<'a>(
+            &'a self,
+            operation: &'a ValidatedField,
+            _operation_type: OperationType,
+            _request_context: &'a RequestContext,
+            _system_resolver: &'a SystemResolver,
+        ) -> Result<Option<QueryResponse>, SubsystemResolutionError> {
+            let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+
+            // Let any other field being resolved concurrently start before this one completes
+            tokio::task::yield_now().await;
+
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+            match operation.name.as_str() {
+                "failing" => Err(SubsystemResolutionError::Authorization),
+                name => Ok(Some(QueryResponse {
+                    body: QueryResponseBody::Json(json!(name)),
+                    headers: vec![],
+                })),
+            }
+        }
+
+        async fn invoke_interceptor<'a>(
+            &'a self,
+            _interceptor_index: InterceptorIndex,
+            _intercepted_operation: &'a InterceptedOperation,
+            _request_context: &'a RequestContext<'a>,
+            _system_resolver: &'a SystemResolver,
+        ) -> Result<Option<QueryResponse>, SubsystemResolutionError> {
+            Err(SubsystemResolutionError::NoInterceptorFound)
+        }
+
+        fn schema_queries(&self) -> Vec<FieldDefinition> {
+            vec![]
+        }
+
+        fn schema_mutations(&self) -> Vec<FieldDefinition> {
+            vec![]
+        }
+
+        fn schema_types(&self) -> Vec<TypeDefinition> {
+            vec![]
+        }
+    }
+
+    struct TestRequest {}
+
+    impl RequestHead for TestRequest {
+        fn get_headers(&self, _key: &str) -> Vec<String> {
+            vec![]
+        }
+
+        fn get_ip(&self) -> Option<std::net::IpAddr> {
+            None
+        }
+
+        fn get_method(&self) -> &http::Method {
+            &http::Method::POST
+        }
+
+        fn get_path(&self) -> &str {
+            ""
+        }
+
+        fn get_query(&self) -> serde_json::Value {
+            Default::default()
+        }
+    }
+
+    const REQUEST: TestRequest = TestRequest {};
 }
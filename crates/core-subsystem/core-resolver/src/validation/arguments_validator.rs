@@ -157,7 +157,9 @@ impl<'a> ArgumentValidator<'a> {
                 Value::Binary(binary) => {
                     Some(self.validate_binary_argument(argument_definition, binary, value.pos))
                 }
-                Value::Enum(e) => Some(Ok(Val::Enum(e.to_string()))),
+                Value::Enum(e) => {
+                    Some(self.validate_enum_argument(argument_definition, e.as_str(), value.pos))
+                }
                 Value::List(elems) => {
                     Some(self.validate_list_argument(argument_definition, elems, value.pos))
                 }
@@ -233,6 +235,11 @@ impl<'a> ArgumentValidator<'a> {
         string: &str,
         pos: Pos,
     ) -> Result<Val, ValidationError> {
+        // Enum values may also be supplied as strings (for example, through variables)
+        if self.enum_values(argument_definition).is_some() {
+            return self.validate_enum_argument(argument_definition, string, pos);
+        }
+
         // TODO: Use the types from PrimitiveType (but that is currently in the builder crate, which we don't want to depend on)
         self.validate_scalar_argument(
             "String",
@@ -269,6 +276,53 @@ impl<'a> ArgumentValidator<'a> {
         )
     }
 
+    /// Validate an enum argument
+    /// Check if the expected type is an enum and the value is one of its values
+    fn validate_enum_argument(
+        &self,
+        argument_definition: &InputValueDefinition,
+        value: &str,
+        pos: Pos,
+    ) -> Result<Val, ValidationError> {
+        let underlying = underlying_type(&argument_definition.ty.node);
+
+        match self.enum_values(argument_definition) {
+            Some(mut values) if values.any(|enum_value| enum_value == value) => {
+                Ok(Val::Enum(value.to_string()))
+            }
+            Some(_) => Err(ValidationError::InvalidEnumValue {
+                argument_name: argument_definition.name.node.to_string(),
+                enum_name: underlying.to_string(),
+                value: value.to_string(),
+                pos,
+            }),
+            None => Err(ValidationError::InvalidArgumentType {
+                argument_name: argument_definition.name.node.to_string(),
+                expected_type: underlying.to_string(),
+                actual_type: "Enum".to_string(),
+                pos,
+            }),
+        }
+    }
+
+    /// The values of the expected type if it is an enum
+    fn enum_values(
+        &self,
+        argument_definition: &InputValueDefinition,
+    ) -> Option<impl Iterator<Item = &str>> {
+        let underlying = underlying_type(&argument_definition.ty.node);
+
+        match &self.schema.get_type_definition(underlying.as_str())?.kind {
+            TypeKind::Enum(enum_type) => Some(
+                enum_type
+                    .values
+                    .iter()
+                    .map(|value| value.node.value.node.as_str()),
+            ),
+            _ => None,
+        }
+    }
+
     /// Validate a scalar argument
     /// Check if the argument is compatible with one the expected destination types
     fn validate_scalar_argument<const N: usize>(
@@ -0,0 +1,74 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use async_graphql_parser::types::TypeDefinition;
+use core_model::{cache_policy::CachePolicy, type_normalization::TypeDefinitionIntrospection};
+
+use crate::introspection::definition::schema::Schema;
+
+use super::{field::ValidatedField, underlying_type};
+
+/// Compute the cache policy of a query's response (subsystems express the policy of types and
+/// fields through the `@cacheControl` directive).
+///
+/// The policy of a field is the one specified on the field, or else the one specified on its
+/// type, or else the one inherited from its parent field. A root field without a policy makes the
+/// response uncacheable. The response's policy is the most restrictive one among all the fields
+/// (the shortest max age and, if any field is private, the private scope).
+pub(super) fn operation_cache_policy(
+    schema: &Schema,
+    root_type: &TypeDefinition,
+    fields: &[ValidatedField],
+) -> Option<CachePolicy> {
+    let mut policies = vec![];
+    collect_field_policies(schema, root_type, fields, None, &mut policies);
+
+    // An operation that selects only introspection fields isn't worth caching
+    let policies = policies.into_iter().collect::<Option<Vec<_>>>()?;
+
+    policies.into_iter().reduce(CachePolicy::restrict)
+}
+
+fn collect_field_policies(
+    schema: &Schema,
+    container_type: &TypeDefinition,
+    fields: &[ValidatedField],
+    inherited: Option<CachePolicy>,
+    policies: &mut Vec<Option<CachePolicy>>,
+) {
+    for field in fields {
+        // Introspection fields (including `__typename`) are computed from the (static) schema
+        if field.name.starts_with("__") {
+            continue;
+        }
+
+        let field_definition = container_type
+            .fields()
+            .and_then(|fields| fields.iter().find(|f| f.node.name.node == field.name))
+            .map(|f| &f.node);
+
+        // The validator has already rejected unknown fields, so this is only a precaution
+        let Some(field_definition) = field_definition else {
+            policies.push(None);
+            continue;
+        };
+
+        let field_type = schema.get_type_definition(underlying_type(&field_definition.ty.node));
+
+        let policy = CachePolicy::from_directives(&field_definition.directives)
+            .or_else(|| field_type.and_then(|typ| CachePolicy::from_directives(&typ.directives)))
+            .or(inherited);
+
+        policies.push(policy);
+
+        if let Some(field_type) = field_type {
+            collect_field_policies(schema, field_type, &field.subfields, policy, policies);
+        }
+    }
+}
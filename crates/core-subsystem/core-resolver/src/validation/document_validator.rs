@@ -15,7 +15,10 @@ use tracing::instrument;
 
 use crate::introspection::definition::schema::Schema;
 
-use super::{operation::ValidatedOperation, operation_validator::OperationValidator};
+use super::{
+    operation::ValidatedOperation, operation_validator::OperationValidator,
+    query_cost::QueryCostModel,
+};
 
 /// Context for validating a document.
 pub struct DocumentValidator<'a> {
@@ -24,6 +27,7 @@ pub struct DocumentValidator<'a> {
     variables: Option<Map<String, Value>>,
     normal_query_depth_limit: usize,
     introspection_query_depth_limit: usize,
+    query_cost_model: Option<&'a QueryCostModel>,
 }
 
 impl<'a> DocumentValidator<'a> {
@@ -33,6 +37,7 @@ impl<'a> DocumentValidator<'a> {
         variables: Option<Map<String, Value>>,
         normal_query_depth_limit: usize,
         introspection_query_depth_limit: usize,
+        query_cost_model: Option<&'a QueryCostModel>,
     ) -> Self {
         Self {
             schema,
@@ -40,6 +45,7 @@ impl<'a> DocumentValidator<'a> {
             variables,
             normal_query_depth_limit,
             introspection_query_depth_limit,
+            query_cost_model,
         }
     }
 
@@ -100,6 +106,7 @@ impl<'a> DocumentValidator<'a> {
             document.fragments,
             self.normal_query_depth_limit,
             self.introspection_query_depth_limit,
+            self.query_cost_model,
         );
 
         operation_validator.validate(raw_operation)
@@ -110,7 +117,10 @@ impl<'a> DocumentValidator<'a> {
 mod tests {
     use super::*;
 
+    use std::collections::HashMap;
+
     use async_graphql_parser::parse_query;
+    use core_model::cache_policy::{CachePolicy, CacheScope};
     use exo_env::MapEnvironment;
     use exo_sql::DatabaseClientManager;
 
@@ -140,7 +150,7 @@ mod tests {
     async fn argument_valid() {
         let schema = create_test_schema().await;
 
-        let validator = DocumentValidator::new(&schema, None, None, 10, 10);
+        let validator = DocumentValidator::new(&schema, None, None, 10, 10, None);
 
         let query = r#"
             query {
@@ -166,7 +176,7 @@ mod tests {
     async fn with_operation_name_valid() {
         let schema = create_test_schema().await;
 
-        let validator = DocumentValidator::new(&schema, None, None, 10, 10);
+        let validator = DocumentValidator::new(&schema, None, None, 10, 10, None);
 
         let query = r#"
             query ConcertById {
@@ -192,7 +202,7 @@ mod tests {
     async fn stray_argument_invalid() {
         let schema = create_test_schema().await;
 
-        let validator = DocumentValidator::new(&schema, None, None, 10, 10);
+        let validator = DocumentValidator::new(&schema, None, None, 10, 10, None);
 
         let query = r#"
             query {
@@ -218,7 +228,7 @@ mod tests {
     async fn unspecified_required_argument_invalid() {
         let schema = create_test_schema().await;
 
-        let validator = DocumentValidator::new(&schema, None, None, 10, 10);
+        let validator = DocumentValidator::new(&schema, None, None, 10, 10, None);
 
         let query = r#"
             query {
@@ -252,7 +262,7 @@ mod tests {
             }"#,
         );
 
-        let validator = DocumentValidator::new(&schema, None, Some(variables), 10, 10);
+        let validator = DocumentValidator::new(&schema, None, Some(variables), 10, 10, None);
 
         let query = r#"
             query($concert_id: Int!, $venue_id: Int!) {
@@ -279,7 +289,7 @@ mod tests {
         let schema = create_test_schema().await;
 
         let variables = create_variables(r#"{ "concert_id": 2 }"#);
-        let validator = DocumentValidator::new(&schema, None, Some(variables), 10, 10);
+        let validator = DocumentValidator::new(&schema, None, Some(variables), 10, 10, None);
 
         let query = r#"
             query($concert_id: Int!, $venue_id: Int!) { # venue_id is not a specified in variables
@@ -305,7 +315,7 @@ mod tests {
     async fn invalid_subfield() {
         let schema = create_test_schema().await;
 
-        let validator = DocumentValidator::new(&schema, None, None, 10, 10);
+        let validator = DocumentValidator::new(&schema, None, None, 10, 10, None);
 
         let query = r#"
             query {
@@ -328,7 +338,7 @@ mod tests {
     async fn aliases_valid() {
         let schema = create_test_schema().await;
 
-        let validator = DocumentValidator::new(&schema, None, None, 10, 10);
+        let validator = DocumentValidator::new(&schema, None, None, 10, 10, None);
 
         let query = r#"
             query {
@@ -350,7 +360,7 @@ mod tests {
     async fn mergeable_leaf_fields() {
         let schema = create_test_schema().await;
 
-        let validator = DocumentValidator::new(&schema, None, None, 10, 10);
+        let validator = DocumentValidator::new(&schema, None, None, 10, 10, None);
 
         let query = r#"
             query {
@@ -375,7 +385,7 @@ mod tests {
     async fn mergeable_leaf_fields_with_alias() {
         let schema = create_test_schema().await;
 
-        let validator = DocumentValidator::new(&schema, None, None, 10, 10);
+        let validator = DocumentValidator::new(&schema, None, None, 10, 10, None);
 
         let query = r#"
             query {
@@ -400,7 +410,7 @@ mod tests {
     async fn unmergeable_leaf_fields_all_aliases() {
         let schema = create_test_schema().await;
 
-        let validator = DocumentValidator::new(&schema, None, None, 10, 10);
+        let validator = DocumentValidator::new(&schema, None, None, 10, 10, None);
 
         let query = r#"
             query {
@@ -423,7 +433,7 @@ mod tests {
     async fn unmergeable_leaf_fields_mixed_aliases() {
         let schema = create_test_schema().await;
 
-        let validator = DocumentValidator::new(&schema, None, None, 10, 10);
+        let validator = DocumentValidator::new(&schema, None, None, 10, 10, None);
 
         let query = r#"
             query {
@@ -446,7 +456,7 @@ mod tests {
     async fn mergeable_non_leaf_fields() {
         let schema = create_test_schema().await;
 
-        let validator = DocumentValidator::new(&schema, None, None, 10, 10);
+        let validator = DocumentValidator::new(&schema, None, None, 10, 10, None);
 
         let query = r#"
             query {
@@ -479,7 +489,7 @@ mod tests {
     async fn unmergeable_non_leaf_fields() {
         let schema = create_test_schema().await;
 
-        let validator = DocumentValidator::new(&schema, None, None, 10, 10);
+        let validator = DocumentValidator::new(&schema, None, None, 10, 10, None);
 
         let query = r#"
             query {
@@ -508,7 +518,7 @@ mod tests {
     async fn mergeable_non_leaf_fields_with_alias() {
         let schema = create_test_schema().await;
 
-        let validator = DocumentValidator::new(&schema, None, None, 10, 10);
+        let validator = DocumentValidator::new(&schema, None, None, 10, 10, None);
 
         let query = r#"
             query {
@@ -557,14 +567,16 @@ mod tests {
             }
         "#;
 
-        let validator = DocumentValidator::new(&schema, Some("concert1".to_string()), None, 10, 10);
+        let validator =
+            DocumentValidator::new(&schema, Some("concert1".to_string()), None, 10, 10, None);
 
         assert_debug!(
             validator.validate(create_query_document(query)),
             "multi_operations_valid"
         );
 
-        let validator = DocumentValidator::new(&schema, Some("concert2".to_string()), None, 10, 10);
+        let validator =
+            DocumentValidator::new(&schema, Some("concert2".to_string()), None, 10, 10, None);
 
         assert_debug!(
             validator.validate(create_query_document(query)),
@@ -593,7 +605,7 @@ mod tests {
             }
         "#;
 
-        let validator = DocumentValidator::new(&schema, None, None, 10, 10);
+        let validator = DocumentValidator::new(&schema, None, None, 10, 10, None);
 
         assert_debug!(
             validator.validate(create_query_document(query)),
@@ -622,7 +634,8 @@ mod tests {
             }
         "#;
 
-        let validator = DocumentValidator::new(&schema, Some("foo".to_string()), None, 10, 10);
+        let validator =
+            DocumentValidator::new(&schema, Some("foo".to_string()), None, 10, 10, None);
 
         assert_debug!(
             validator.validate(create_query_document(query)),
@@ -647,7 +660,7 @@ mod tests {
             }
         "#;
 
-        let validator = DocumentValidator::new(&schema, None, None, 10, 10);
+        let validator = DocumentValidator::new(&schema, None, None, 10, 10, None);
 
         assert_debug!(
             validator.validate(create_query_document(query)),
@@ -676,7 +689,7 @@ mod tests {
             }
         "#;
 
-        let validator = DocumentValidator::new(&schema, None, None, 10, 10);
+        let validator = DocumentValidator::new(&schema, None, None, 10, 10, None);
 
         assert_debug!(
             validator.validate(create_query_document(query)),
@@ -707,14 +720,14 @@ mod tests {
         "#;
 
         // valid
-        let validator = DocumentValidator::new(&schema, None, None, 6, usize::MAX);
+        let validator = DocumentValidator::new(&schema, None, None, 6, usize::MAX, None);
         assert_debug!(
             validator.validate(create_query_document(query)),
             "query_depth_limit_direct"
         );
 
         // invalid: one level too deep
-        let validator = DocumentValidator::new(&schema, None, None, 5, usize::MAX);
+        let validator = DocumentValidator::new(&schema, None, None, 5, usize::MAX, None);
         assert_debug!(
             validator.validate(create_query_document(query)),
             "query_depth_limit_direct-2"
@@ -746,14 +759,14 @@ mod tests {
         "#;
 
         // valid
-        let validator = DocumentValidator::new(&schema, None, None, 6, usize::MAX);
+        let validator = DocumentValidator::new(&schema, None, None, 6, usize::MAX, None);
         assert_debug!(
             validator.validate(create_query_document(query)),
             "query_depth_limit_through_fragment"
         );
 
         // invalid: one level too deep
-        let validator = DocumentValidator::new(&schema, None, None, 5, usize::MAX);
+        let validator = DocumentValidator::new(&schema, None, None, 5, usize::MAX, None);
         assert_debug!(
             validator.validate(create_query_document(query)),
             "query_depth_limit_through_fragment-2"
@@ -777,20 +790,494 @@ mod tests {
         "#;
 
         // valid
-        let validator = DocumentValidator::new(&schema, None, None, usize::MAX, 3);
+        let validator = DocumentValidator::new(&schema, None, None, usize::MAX, 3, None);
         assert_debug!(
             validator.validate(create_query_document(query)),
             "introspection_query_depth_limit_direct"
         );
 
         // invalid: one level too deep
-        let validator = DocumentValidator::new(&schema, None, None, usize::MAX, 2);
+        let validator = DocumentValidator::new(&schema, None, None, usize::MAX, 2, None);
         assert_debug!(
             validator.validate(create_query_document(query)),
             "introspection_query_depth_limit_direct-2"
         );
     }
 
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn subscription_valid() {
+        let schema = create_test_schema().await;
+
+        let validator = DocumentValidator::new(&schema, None, None, 10, 10, None);
+
+        let query = r#"
+            subscription {
+                onConcertCreated(where: { title: { eq: "Rock" } }) {
+                    id
+                    title
+                }
+            }
+        "#;
+
+        assert_debug!(
+            validator.validate(create_query_document(query)),
+            "subscription_valid"
+        );
+    }
+
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn subscription_multiple_root_fields_invalid() {
+        let schema = create_test_schema().await;
+
+        let validator = DocumentValidator::new(&schema, None, None, 10, 10, None);
+
+        let query = r#"
+            subscription {
+                onConcertCreated {
+                    id
+                }
+                onConcertDeleted {
+                    id
+                }
+            }
+        "#;
+
+        assert_debug!(
+            validator.validate(create_query_document(query)),
+            "subscription_multiple_root_fields_invalid"
+        );
+    }
+
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn skip_include_fields() {
+        let schema = create_test_schema().await;
+
+        let variables = create_variables(r#"{ "withTitle": false, "withVenue": true }"#);
+        let validator = DocumentValidator::new(&schema, None, Some(variables), 10, 10, None);
+
+        let query = r#"
+            query($withTitle: Boolean!, $withVenue: Boolean!) {
+                concert(id: 1) {
+                    id @skip(if: false)
+                    title @include(if: $withTitle)
+                    venue @include(if: $withVenue) {
+                        id
+                        name @skip(if: $withVenue)
+                    }
+                }
+                concerts @skip(if: true) {
+                    id
+                }
+            }
+        "#;
+
+        assert_debug!(
+            validator.validate(create_query_document(query)),
+            "skip_include_fields"
+        );
+    }
+
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn skip_include_fragments() {
+        let schema = create_test_schema().await;
+
+        let variables = create_variables(r#"{ "details": true }"#);
+        let validator = DocumentValidator::new(&schema, None, Some(variables), 10, 10, None);
+
+        let query = r#"
+            query($details: Boolean!) {
+                concert(id: 1) {
+                    id
+                    ...ConcertTitle @skip(if: $details)
+                    ... @include(if: $details) {
+                        venue {
+                            name
+                        }
+                    }
+                    ... on Concert {
+                        title
+                    }
+                }
+            }
+
+            fragment ConcertTitle on Concert {
+                title
+                venue {
+                    id
+                }
+            }
+        "#;
+
+        assert_debug!(
+            validator.validate(create_query_document(query)),
+            "skip_include_fragments"
+        );
+    }
+
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn skip_include_invalid_condition() {
+        let schema = create_test_schema().await;
+
+        let variables = create_variables(r#"{ "withTitle": "yes" }"#);
+        let validator = DocumentValidator::new(&schema, None, Some(variables), 10, 10, None);
+
+        let query = r#"
+            query($withTitle: Boolean!) {
+                concert(id: 1) {
+                    id
+                    title @include(if: $withTitle)
+                }
+            }
+        "#;
+
+        assert_debug!(
+            validator.validate(create_query_document(query)),
+            "skip_include_invalid_condition"
+        );
+    }
+
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn defer_stream_root_fields() {
+        let schema = create_test_schema().await;
+
+        let variables = create_variables(r#"{ "deferVenues": false }"#);
+        let validator = DocumentValidator::new(&schema, None, Some(variables), 10, 10, None);
+
+        let query = r#"
+            query($deferVenues: Boolean!) {
+                concert(id: 1) {
+                    id
+                }
+                concerts @stream(initialCount: 2, label: "concerts") {
+                    id
+                    title
+                }
+                ... @defer(label: "aggregates") {
+                    concertsAgg {
+                        id {
+                            count
+                        }
+                    }
+                    concert(id: 1) {
+                        id
+                    }
+                }
+                ...Venues @defer(if: $deferVenues)
+                ...Concert @defer
+            }
+
+            fragment Venues on Query {
+                venues {
+                    id
+                }
+            }
+
+            fragment Concert on Query {
+                concert(id: 1) {
+                    id
+                }
+            }
+        "#;
+
+        let operation = validator.validate(create_query_document(query)).unwrap();
+
+        assert_debug!(
+            (operation.deferred, operation.streamed),
+            "defer_stream_root_fields"
+        );
+    }
+
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn stream_on_non_list_field() {
+        let schema = create_test_schema().await;
+
+        let validator = DocumentValidator::new(&schema, None, None, 10, 10, None);
+
+        let query = r#"
+            query {
+                concert(id: 1) @stream {
+                    id
+                }
+            }
+        "#;
+
+        assert_debug!(
+            validator.validate(create_query_document(query)),
+            "stream_on_non_list_field"
+        );
+    }
+
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn ignored_defer_stream() {
+        let schema = create_test_schema().await;
+
+        let validate = |query: &str| {
+            DocumentValidator::new(&schema, None, None, 10, 10, None)
+                .validate(create_query_document(query))
+                .unwrap()
+        };
+
+        // Delivered along with their parent (the deferred root fragment, if any)
+        let documents = [
+            // On a subfield
+            (r#"query { venues { concerts @stream { id } } }"#, vec![]),
+            // On a fragment in a subfield
+            (r#"query { venues { ... @defer { id } } }"#, vec![]),
+            // Nested in a deferred root fragment
+            (
+                r#"query { ... @defer { ... @defer { venues { id } } } }"#,
+                vec!["venues"],
+            ),
+            // On a field of a root fragment
+            (
+                r#"query { ...Venues } fragment Venues on Query { venues @stream { id } }"#,
+                vec![],
+            ),
+            // In a mutation
+            (
+                r#"mutation { ... @defer { deleteConcert(id: 1) { id } } }"#,
+                vec![],
+            ),
+        ];
+
+        for (query, deferred_names) in documents {
+            let operation = validate(query);
+
+            assert_eq!(operation.fields.len(), 1, "Unexpected fields for '{query}'");
+            assert_eq!(
+                operation
+                    .deferred
+                    .iter()
+                    .flat_map(|deferred_fields| deferred_fields.output_names.clone())
+                    .collect::<Vec<_>>(),
+                deferred_names,
+                "Unexpected deferred fields for '{query}'"
+            );
+            assert!(
+                operation.streamed.is_empty(),
+                "Unexpected streamed fields for '{query}'"
+            );
+        }
+    }
+
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn misplaced_defer_stream() {
+        let schema = create_test_schema().await;
+
+        let validate = |query: &str| {
+            DocumentValidator::new(&schema, None, None, 10, 10, None)
+                .validate(create_query_document(query))
+        };
+
+        let documents = [
+            ("defer", r#"query { venues @defer { id } }"#),
+            ("stream", r#"query { ... @stream { venues { id } } }"#),
+        ];
+
+        for (directive_name, query) in documents {
+            let result = validate(query);
+
+            assert!(
+                matches!(
+                    &result,
+                    Err(ValidationError::MisplacedIncrementalDirective(name, _, _)) if name == directive_name
+                ),
+                "Unexpected result for '{query}': {result:?}"
+            );
+        }
+
+        // Disabled directives are ignored
+        let query = r#"query { venues @defer(if: false) { id } }"#;
+        assert!(validate(query).is_ok());
+    }
+
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn query_cost() {
+        let schema = create_test_schema().await;
+
+        let query_cost_model = QueryCostModel {
+            max_cost: None,
+            default_list_size: 10,
+            field_costs: HashMap::from([("Venue.name".to_string(), 2)]),
+            report_cost: false,
+        };
+        let validator =
+            DocumentValidator::new(&schema, None, None, 10, 10, Some(&query_cost_model));
+
+        // concert: 1 + (venue: 1 + name: 2)
+        // concerts: 1 + 5 * (id: 0 + venue: 1 + (name: 2))
+        // venues: 1 + 10 * (concerts: 1 + 10 * (id: 0))
+        let query = r#"
+            query {
+                concert(id: 1) {
+                    venue {
+                        name
+                    }
+                }
+                concerts(limit: 5) {
+                    id
+                    venue {
+                        name
+                    }
+                }
+                venues {
+                    concerts {
+                        id
+                    }
+                }
+                __typename
+            }
+        "#;
+
+        let operation = validator.validate(create_query_document(query)).unwrap();
+        assert_eq!(operation.cost, 4 + 16 + 11);
+    }
+
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn connection_query_cost() {
+        let schema = create_test_schema().await;
+
+        let query_cost_model = QueryCostModel {
+            max_cost: None,
+            default_list_size: 10,
+            field_costs: HashMap::from([("Venue.name".to_string(), 2)]),
+            report_cost: false,
+        };
+        let cost = |page_arguments: &str| {
+            let query = format!(
+                r#"
+                query {{
+                    concertsConnection{page_arguments} {{
+                        edges {{
+                            cursor
+                            node {{
+                                id
+                                venue {{
+                                    name
+                                }}
+                            }}
+                        }}
+                        pageInfo {{
+                            hasNextPage
+                        }}
+                    }}
+                }}
+            "#
+            );
+
+            DocumentValidator::new(&schema, None, None, 10, 10, Some(&query_cost_model))
+                .validate(create_query_document(&query))
+                .unwrap()
+                .cost
+        };
+
+        // concertsConnection: 1 + (edges: 1 + <page size> * (node: 1 + (venue: 1 + (name: 2))) + pageInfo: 1)
+        assert_eq!(cost("(first: 5)"), 1 + (1 + 5 * 4) + 1);
+        assert_eq!(cost("(last: 3)"), 1 + (1 + 3 * 4) + 1);
+        assert_eq!(cost(""), 1 + (1 + 10 * 4) + 1);
+    }
+
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn query_cost_exceeded() {
+        let schema = create_test_schema().await;
+
+        let query_cost_model = QueryCostModel {
+            max_cost: Some(100),
+            ..QueryCostModel::default()
+        };
+        let validator =
+            DocumentValidator::new(&schema, None, None, 10, 10, Some(&query_cost_model));
+
+        let query = r#"
+            query {
+                venues {
+                    concerts {
+                        id
+                    }
+                }
+            }
+        "#;
+
+        assert_debug!(
+            validator.validate(create_query_document(query)),
+            "query_cost_exceeded"
+        );
+    }
+
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn cache_policy() {
+        let test_exo = r#"
+            @postgres
+            module ConcertModule {
+                @cache(60)
+                type Concert {
+                    @pk id: Int = autoIncrement()
+                    title: String
+                    @cache(maxAge=30, scope="private") notes: String
+                    venue: Venue
+                }
+
+                @cache(maxAge=120)
+                type Venue {
+                    @pk id: Int = autoIncrement()
+                    name: String
+                    concerts: Set<Concert>
+                }
+
+                type Artist {
+                    @pk id: Int = autoIncrement()
+                    name: String
+                }
+            }
+        "#;
+        let schema = create_schema_from_str(test_exo).await;
+
+        let cache_policy = |query: &str| {
+            DocumentValidator::new(&schema, None, None, 10, 10, None)
+                .validate(create_query_document(query))
+                .unwrap()
+                .cache_policy
+        };
+        let public = |max_age| {
+            Some(CachePolicy {
+                max_age,
+                scope: CacheScope::Public,
+            })
+        };
+
+        assert_eq!(cache_policy("{ concerts { id title } }"), public(60));
+        assert_eq!(cache_policy("{ venues { name } }"), public(120));
+        // The more restrictive policy of the nested type wins
+        assert_eq!(
+            cache_policy("{ venues { name concerts { title } } }"),
+            public(60)
+        );
+        // A field's policy overrides its type's policy
+        assert_eq!(
+            cache_policy("{ concerts { title notes } }"),
+            Some(CachePolicy {
+                max_age: 30,
+                scope: CacheScope::Private,
+            })
+        );
+        // A root field without a policy makes the response uncacheable
+        assert_eq!(cache_policy("{ concerts { id } artists { id } }"), None);
+        assert_eq!(cache_policy("{ __typename }"), None);
+    }
+
     fn create_variables(variables: &str) -> Map<String, Value> {
         serde_json::from_str(variables).unwrap()
     }
@@ -799,6 +1286,7 @@ mod tests {
         let test_exo = r#"
             @postgres
             module LogModule {
+                @subscribable
                 type Concert {
                     @pk id: Int = autoIncrement()
                     title: String
@@ -813,6 +1301,11 @@ mod tests {
                 }
             }
         "#;
+
+        create_schema_from_str(test_exo).await
+    }
+
+    async fn create_schema_from_str(test_exo: &str) -> Schema {
         let postgres_subsystem =
             create_postgres_system_from_str(test_exo, "test.exo".to_string()).await;
 
@@ -820,6 +1313,7 @@ mod tests {
             postgres_subsystem.schema_types(),
             postgres_subsystem.schema_queries(),
             postgres_subsystem.schema_mutations(),
+            postgres_subsystem.schema_subscriptions(),
         )
     }
 
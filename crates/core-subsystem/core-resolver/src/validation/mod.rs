@@ -19,7 +19,9 @@ pub mod operation;
 pub mod document_validator;
 
 mod arguments_validator;
+mod cache_policy;
 mod operation_validator;
+pub mod query_cost;
 mod selection_set_validator;
 
 pub fn underlying_type(typ: &Type) -> &Name {
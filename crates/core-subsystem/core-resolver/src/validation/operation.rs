@@ -8,6 +8,7 @@
 // by the Apache License, Version 2.0.
 
 use async_graphql_parser::types::OperationType;
+use core_model::cache_policy::CachePolicy;
 
 use crate::validation::field::ValidatedField;
 
@@ -19,4 +20,30 @@ pub struct ValidatedOperation {
     pub typ: OperationType,
     /// The operation's fields (individual queries or mutations).
     pub fields: Vec<ValidatedField>,
+    /// Groups of root fields selected only through fragments marked with `@defer` (these fields
+    /// are also in `fields`, so a transport that doesn't support incremental delivery may ignore this).
+    pub deferred: Vec<DeferredFields>,
+    /// Root fields marked with `@stream`
+    pub streamed: Vec<StreamedField>,
+    /// The estimated cost of executing the operation (see [QueryCostModel](super::query_cost::QueryCostModel))
+    pub cost: usize,
+    /// How long the response to the operation may be cached (`None` if it must not be cached)
+    pub cache_policy: Option<CachePolicy>,
+}
+
+/// Root fields of a fragment marked with `@defer`, which may be delivered after the rest of the response
+#[derive(Debug)]
+pub struct DeferredFields {
+    pub label: Option<String>,
+    /// The output names of the deferred fields
+    pub output_names: Vec<String>,
+}
+
+/// A root list field marked with `@stream`, whose elements beyond `initial_count` may be delivered
+/// after the rest of the response
+#[derive(Debug)]
+pub struct StreamedField {
+    pub label: Option<String>,
+    pub output_name: String,
+    pub initial_count: usize,
 }
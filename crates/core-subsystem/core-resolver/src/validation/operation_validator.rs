@@ -17,11 +17,16 @@ use async_graphql_value::{ConstValue, Name};
 use serde_json::{Map, Value};
 
 use crate::{
-    introspection::definition::schema::{Schema, MUTATION_ROOT_TYPENAME, QUERY_ROOT_TYPENAME},
+    introspection::definition::schema::{
+        Schema, MUTATION_ROOT_TYPENAME, QUERY_ROOT_TYPENAME, SUBSCRIPTION_ROOT_TYPENAME,
+    },
     validation::validation_error::ValidationError,
 };
 
-use super::{operation::ValidatedOperation, selection_set_validator::SelectionSetValidator};
+use super::{
+    cache_policy::operation_cache_policy, operation::ValidatedOperation,
+    query_cost::QueryCostModel, selection_set_validator::SelectionSetValidator,
+};
 
 /// Context for validating an operation.
 pub struct OperationValidator<'a> {
@@ -31,6 +36,7 @@ pub struct OperationValidator<'a> {
     fragment_definitions: HashMap<Name, Positioned<FragmentDefinition>>,
     normal_query_depth_limit: usize,
     introspection_query_depth_limit: usize,
+    query_cost_model: Option<&'a QueryCostModel>,
 }
 
 impl<'a> OperationValidator<'a> {
@@ -42,6 +48,7 @@ impl<'a> OperationValidator<'a> {
         fragment_definitions: HashMap<Name, Positioned<FragmentDefinition>>,
         normal_query_depth_limit: usize,
         introspection_query_depth_limit: usize,
+        query_cost_model: Option<&'a QueryCostModel>,
     ) -> Self {
         Self {
             schema,
@@ -50,6 +57,7 @@ impl<'a> OperationValidator<'a> {
             fragment_definitions,
             normal_query_depth_limit,
             introspection_query_depth_limit,
+            query_cost_model,
         }
     }
 
@@ -94,6 +102,11 @@ impl<'a> OperationValidator<'a> {
     /// - Each variables in [OperationDefinition.variable_definitions] is
     ///   available (see [`validate_variables`] for details)
     /// - The selected fields are valid (see [SelectionSetValidator] for details)])
+    /// - A subscription selects exactly one top-level field
+    /// - The `@defer` and `@stream` directives (supported only on the root selections of a query) are valid
+    /// - The cost of the operation is within the limit (see [QueryCostModel] for details)
+    ///
+    /// Also computes the cache policy of a query's response (see [operation_cache_policy] for details)
     ///
     /// # Returns
     ///   A validated operation with all variables and fields resolved and normalized.
@@ -104,7 +117,7 @@ impl<'a> OperationValidator<'a> {
         let operation_type_name = match operation.node.ty {
             OperationType::Query => QUERY_ROOT_TYPENAME,
             OperationType::Mutation => MUTATION_ROOT_TYPENAME,
-            OperationType::Subscription => todo!(),
+            OperationType::Subscription => SUBSCRIPTION_ROOT_TYPENAME,
         };
 
         let container_type = match self.schema.get_type_definition(operation_type_name) {
@@ -132,10 +145,57 @@ impl<'a> OperationValidator<'a> {
             &self.selection_depth_check(),
         )?;
 
+        // Per the spec, a subscription must have exactly one root field (each pushed event
+        // corresponds to a change in that field)
+        if operation.node.ty == OperationType::Subscription && fields.len() != 1 {
+            return Err(ValidationError::SubscriptionMultipleRootFields(
+                operation.node.selection_set.pos,
+            ));
+        }
+
+        // Incremental delivery makes sense only for queries (mutations are executed in a single
+        // transaction and subscriptions push their only field as each event occurs), so other
+        // operations ignore `@defer` and `@stream` and deliver everything in a single response
+        let (deferred, streamed) = if operation.node.ty == OperationType::Query {
+            selection_set_validator.validate_incremental_delivery(
+                &operation.node.selection_set,
+                0,
+                &self.selection_depth_check(),
+            )?
+        } else {
+            (vec![], vec![])
+        };
+
+        let cost = match self.query_cost_model {
+            Some(query_cost_model) => query_cost_model.operation_cost(
+                self.schema,
+                container_type,
+                &fields,
+                operation.pos,
+            )?,
+            None => QueryCostModel::default().operation_cost(
+                self.schema,
+                container_type,
+                &fields,
+                operation.pos,
+            )?,
+        };
+
+        // Only query responses may be cached
+        let cache_policy = if operation.node.ty == OperationType::Query {
+            operation_cache_policy(self.schema, container_type, &fields)
+        } else {
+            None
+        };
+
         Ok(ValidatedOperation {
             name: self.operation_name,
             typ: operation.node.ty,
             fields,
+            deferred,
+            streamed,
+            cost,
+            cache_policy,
         })
     }
 
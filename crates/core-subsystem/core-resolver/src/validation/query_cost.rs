@@ -0,0 +1,160 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::HashMap;
+
+use async_graphql_parser::{
+    types::{BaseType, TypeDefinition},
+    Pos,
+};
+use core_model::type_normalization::TypeDefinitionIntrospection;
+use serde_json::json;
+
+use crate::{introspection::definition::schema::Schema, value::val::Val};
+
+use super::{field::ValidatedField, underlying_type, validation_error::ValidationError};
+
+/// The number of elements assumed for a list field that doesn't specify its size
+pub const DEFAULT_LIST_SIZE: usize = 100;
+
+/// Cost model to reject operations that would be too expensive to execute (beyond what the
+/// selection depth limit can catch, such as a shallow query that selects thousands of rows).
+///
+/// The cost of a field is its own cost plus the cost of its subfields, multiplied by the number of
+/// elements if the field is a list. A field's own cost is 1 if it selects subfields and 0 otherwise,
+/// unless overridden through `field_costs`. The number of elements of a list is taken from the
+/// `limit` (or `first`/`last`) argument, falling back to `default_list_size`. For a connection
+/// (a non-list field with the `first`/`last` argument), that argument sizes its list subfields (such
+/// as `edges`) instead.
+#[derive(Debug, Clone)]
+pub struct QueryCostModel {
+    /// The maximum allowed cost of an operation (`None` for no limit)
+    pub max_cost: Option<usize>,
+    /// The number of elements assumed for a list field without a size argument
+    pub default_list_size: usize,
+    /// The cost of specific fields keyed by "<type name>.<field name>" (for example, "Query.concerts")
+    pub field_costs: HashMap<String, usize>,
+    /// Whether to report the cost of each operation in the response extensions
+    pub report_cost: bool,
+}
+
+impl Default for QueryCostModel {
+    fn default() -> Self {
+        Self {
+            max_cost: None,
+            default_list_size: DEFAULT_LIST_SIZE,
+            field_costs: HashMap::new(),
+            report_cost: false,
+        }
+    }
+}
+
+impl QueryCostModel {
+    /// Compute the cost of the operation's root fields, failing if it exceeds the maximum cost
+    pub(super) fn operation_cost(
+        &self,
+        schema: &Schema,
+        root_type: &TypeDefinition,
+        fields: &[ValidatedField],
+        pos: Pos,
+    ) -> Result<usize, ValidationError> {
+        let cost = self.selection_cost(schema, root_type, fields, None);
+
+        match self.max_cost {
+            Some(max_cost) if cost > max_cost => {
+                Err(ValidationError::QueryCostExceeded(cost, max_cost, pos))
+            }
+            _ => Ok(cost),
+        }
+    }
+
+    /// The response extensions reporting the cost of an operation (if enabled)
+    pub fn cost_extensions(&self, cost: usize) -> Option<serde_json::Value> {
+        self.report_cost.then(|| match self.max_cost {
+            Some(max_cost) => json!({ "cost": { "requested": cost, "maximum": max_cost } }),
+            None => json!({ "cost": { "requested": cost } }),
+        })
+    }
+
+    /// The cost of the fields, where `page_size` is the size of the enclosing connection (if any)
+    fn selection_cost(
+        &self,
+        schema: &Schema,
+        container_type: &TypeDefinition,
+        fields: &[ValidatedField],
+        page_size: Option<usize>,
+    ) -> usize {
+        fields
+            .iter()
+            .map(|field| self.field_cost(schema, container_type, field, page_size))
+            .fold(0, usize::saturating_add)
+    }
+
+    fn field_cost(
+        &self,
+        schema: &Schema,
+        container_type: &TypeDefinition,
+        field: &ValidatedField,
+        page_size: Option<usize>,
+    ) -> usize {
+        // Introspection fields (including `__typename`) don't touch the data
+        if field.name.starts_with("__") {
+            return 0;
+        }
+
+        let field_definition = container_type
+            .fields()
+            .and_then(|fields| fields.iter().find(|f| f.node.name.node == field.name))
+            .map(|f| &f.node);
+
+        // The validator has already rejected unknown fields, so this is only a precaution
+        let Some(field_definition) = field_definition else {
+            return 0;
+        };
+
+        let own_cost = self
+            .field_costs
+            .get(&format!("{}.{}", container_type.name.node, field.name))
+            .copied()
+            .unwrap_or(if field.subfields.is_empty() { 0 } else { 1 });
+
+        let (multiplier, subfields_page_size) = match field_definition.ty.node.base {
+            BaseType::List(_) => (page_size.unwrap_or_else(|| self.list_size(field)), None),
+            BaseType::Named(_) => (1, Self::page_size(field)),
+        };
+
+        let subfields_cost = schema
+            .get_type_definition(underlying_type(&field_definition.ty.node))
+            .map(|field_type| {
+                self.selection_cost(schema, field_type, &field.subfields, subfields_page_size)
+            })
+            .unwrap_or(0);
+
+        own_cost.saturating_add(multiplier.saturating_mul(subfields_cost))
+    }
+
+    fn list_size(&self, field: &ValidatedField) -> usize {
+        Self::size_argument(field, &["limit", "first", "last"]).unwrap_or(self.default_list_size)
+    }
+
+    /// The page size of a connection field (without one, its list subfields are sized as usual)
+    fn page_size(field: &ValidatedField) -> Option<usize> {
+        Self::size_argument(field, &["first", "last"])
+    }
+
+    fn size_argument(field: &ValidatedField, argument_names: &[&str]) -> Option<usize> {
+        argument_names
+            .iter()
+            .find_map(|argument_name| match field.arguments.get(*argument_name) {
+                Some(Val::Number(size)) => size.as_u64(),
+                _ => None,
+            })
+            .map(|size| size as usize)
+    }
+}
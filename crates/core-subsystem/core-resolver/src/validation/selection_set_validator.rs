@@ -11,14 +11,17 @@ use std::collections::{HashMap, HashSet};
 
 use async_graphql_parser::{
     types::{
-        Field, FieldDefinition, FragmentDefinition, FragmentSpread, Selection, SelectionSet, Type,
-        TypeDefinition,
+        BaseType, Directive, Field, FieldDefinition, FragmentDefinition, FragmentSpread,
+        InlineFragment, Selection, SelectionSet, Type, TypeDefinition,
     },
     Pos, Positioned,
 };
-use async_graphql_value::{indexmap::IndexMap, ConstValue, Name};
+use async_graphql_value::{indexmap::IndexMap, ConstValue, Name, Value};
 
-use crate::validation::field::ValidatedField;
+use crate::validation::{
+    field::ValidatedField,
+    operation::{DeferredFields, StreamedField},
+};
 use core_model::type_normalization::TypeDefinitionIntrospection;
 
 use crate::{
@@ -63,6 +66,9 @@ impl<'a> SelectionSetValidator<'a> {
     /// - Each field is defined in the `container_type`
     /// - Each fragment referred is defined
     /// - Arguments to each field are valid (see [validate_arguments] for more details)
+    /// - The `if` argument of each `@skip` and `@include` directive is a boolean
+    ///
+    /// Selections excluded by the `@skip` and `@include` directives are dropped.
     ///
     /// # Returns
     ///   A vector of validated fields (any fragment is resolved and inlined, thus normalizing the fields)
@@ -171,6 +177,12 @@ impl<'a> SelectionSetValidator<'a> {
         selection_depth: usize,
         selection_depth_check: &impl Fn(usize, Option<bool>, Pos) -> Result<bool, ValidationError>,
     ) -> Result<Vec<(Pos, ValidatedField)>, ValidationError> {
+        let directives = selection_directives(selection);
+
+        if !self.is_included(directives)? {
+            return Ok(vec![]);
+        }
+
         match &selection.node {
             Selection::Field(field) => self
                 .validate_field(field, selection_depth, selection_depth_check)
@@ -197,12 +209,291 @@ impl<'a> SelectionSetValidator<'a> {
                         )
                     })
             }
-            Selection::InlineFragment(inline_fragment) => Err(
-                ValidationError::InlineFragmentNotSupported(inline_fragment.pos),
+            Selection::InlineFragment(inline_fragment) => self.validate_inline_fragment(
+                inline_fragment,
+                fragment_trail,
+                selection_depth,
+                selection_depth_check,
             ),
         }
     }
 
+    /// Validate an inline fragment. Since we don't support interfaces or unions, the only
+    /// meaningful inline fragments are those without a type condition or with the type condition
+    /// matching the container type (typically used to apply `@skip` or `@include` to a group of fields).
+    fn validate_inline_fragment(
+        &self,
+        inline_fragment: &Positioned<InlineFragment>,
+        fragment_trail: HashSet<String>,
+        selection_depth: usize,
+        selection_depth_check: &impl Fn(usize, Option<bool>, Pos) -> Result<bool, ValidationError>,
+    ) -> Result<Vec<(Pos, ValidatedField)>, ValidationError> {
+        let matches_container_type = match &inline_fragment.node.type_condition {
+            Some(type_condition) => type_condition.node.on.node == self.container_type.name.node,
+            None => true,
+        };
+
+        if !matches_container_type {
+            return Err(ValidationError::InlineFragmentNotSupported(
+                inline_fragment.pos,
+            ));
+        }
+
+        self.validate_selection_set(
+            &inline_fragment.node.selection_set,
+            fragment_trail,
+            selection_depth,
+            selection_depth_check,
+        )
+    }
+
+    /// Evaluate the [`@skip`](https://spec.graphql.org/October2021/#sec--skip) and
+    /// [`@include`](https://spec.graphql.org/October2021/#sec--include) directives of a selection.
+    /// Other directives are ignored.
+    fn is_included(&self, directives: &[Positioned<Directive>]) -> Result<bool, ValidationError> {
+        for directive in directives {
+            // The value of the `if` argument that includes the selection
+            let included_if = match directive.node.name.node.as_str() {
+                "skip" => false,
+                "include" => true,
+                _ => continue,
+            };
+
+            if self.directive_condition(directive)? != included_if {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    fn directive_condition(
+        &self,
+        directive: &Positioned<Directive>,
+    ) -> Result<bool, ValidationError> {
+        match self.directive_argument(directive, "if")? {
+            Some((ConstValue::Boolean(condition), _)) => Ok(condition),
+            Some((_, pos)) => Err(Self::invalid_directive_argument(
+                directive,
+                "if",
+                "a boolean",
+                pos,
+            )),
+            None => Err(ValidationError::RequiredArgumentNotFound(
+                "if".to_string(),
+                directive.pos,
+            )),
+        }
+    }
+
+    /// The value of a directive's argument (after resolving any variable) along with its position
+    fn directive_argument(
+        &self,
+        directive: &Positioned<Directive>,
+        argument_name: &str,
+    ) -> Result<Option<(ConstValue, Pos)>, ValidationError> {
+        let Some(argument) = directive.node.get_argument(argument_name) else {
+            return Ok(None);
+        };
+
+        let value =
+            match &argument.node {
+                Value::Variable(name) => self.variables.get(name).cloned().ok_or_else(|| {
+                    ValidationError::VariableNotFound(name.to_string(), argument.pos)
+                })?,
+                value => value.clone().into_const().unwrap_or(ConstValue::Null),
+            };
+
+        Ok(Some((value, argument.pos)))
+    }
+
+    fn invalid_directive_argument(
+        directive: &Positioned<Directive>,
+        argument_name: &str,
+        expected: &str,
+        pos: Pos,
+    ) -> ValidationError {
+        ValidationError::InvalidDirectiveArgument {
+            directive_name: directive.node.name.node.to_string(),
+            argument_name: argument_name.to_string(),
+            expected: expected.to_string(),
+            pos,
+        }
+    }
+
+    /// Determine the root fields to be delivered incrementally (through `@defer` on fragments and
+    /// `@stream` on list fields) in the root selection set.
+    ///
+    /// We support these directives only on the selections of the root selection set (where each
+    /// field is resolved independently). Elsewhere (including inside the root fragments), we ignore
+    /// them and deliver the selections along with their parent (as the spec allows).
+    pub(super) fn validate_incremental_delivery(
+        &self,
+        selection_set: &Positioned<SelectionSet>,
+        selection_depth: usize,
+        selection_depth_check: &impl Fn(usize, Option<bool>, Pos) -> Result<bool, ValidationError>,
+    ) -> Result<(Vec<DeferredFields>, Vec<StreamedField>), ValidationError> {
+        let mut immediate_names = HashSet::new();
+        let mut deferred: Vec<DeferredFields> = vec![];
+        let mut streamed = vec![];
+
+        for selection in &selection_set.node.items {
+            let output_names: Vec<String> = self
+                .validate_selection(
+                    selection,
+                    HashSet::new(),
+                    selection_depth,
+                    selection_depth_check,
+                )?
+                .into_iter()
+                .map(|(_, field)| field.output_name())
+                .collect();
+
+            // Skipped through `@skip` or `@include`
+            if output_names.is_empty() {
+                continue;
+            }
+
+            match &selection.node {
+                Selection::Field(field) => {
+                    // `@defer` applies only to fragments
+                    if let Some(directive) =
+                        self.incremental_directive(&field.node.directives, "defer")?
+                    {
+                        return Err(ValidationError::MisplacedIncrementalDirective(
+                            "defer".to_string(),
+                            "fragments".to_string(),
+                            directive.pos,
+                        ));
+                    }
+
+                    immediate_names.extend(output_names);
+
+                    if let Some(directive) =
+                        self.incremental_directive(&field.node.directives, "stream")?
+                    {
+                        streamed.push(self.streamed_field(field, directive)?);
+                    }
+                }
+                Selection::FragmentSpread(Positioned {
+                    node: FragmentSpread { directives, .. },
+                    ..
+                })
+                | Selection::InlineFragment(Positioned {
+                    node: InlineFragment { directives, .. },
+                    ..
+                }) => {
+                    // `@stream` applies only to fields
+                    if let Some(directive) = self.incremental_directive(directives, "stream")? {
+                        return Err(ValidationError::MisplacedIncrementalDirective(
+                            "stream".to_string(),
+                            "fields".to_string(),
+                            directive.pos,
+                        ));
+                    }
+
+                    match self.incremental_directive(directives, "defer")? {
+                        Some(directive) => deferred.push(DeferredFields {
+                            label: self.directive_label(directive)?,
+                            output_names,
+                        }),
+                        None => immediate_names.extend(output_names),
+                    }
+                }
+            }
+        }
+
+        // A field selected without deferral (or in an earlier deferred fragment) is delivered
+        // with it, so remove it from the (later) deferred fragments
+        let mut delivered_names = immediate_names;
+        for deferred_fields in deferred.iter_mut() {
+            deferred_fields
+                .output_names
+                .retain(|name| delivered_names.insert(name.clone()));
+        }
+        deferred.retain(|deferred_fields| !deferred_fields.output_names.is_empty());
+
+        Ok((deferred, streamed))
+    }
+
+    /// The `@defer` or `@stream` directive (if present and enabled through its optional `if` argument)
+    fn incremental_directive<'d>(
+        &self,
+        directives: &'d [Positioned<Directive>],
+        directive_name: &str,
+    ) -> Result<Option<&'d Positioned<Directive>>, ValidationError> {
+        let Some(directive) = directives
+            .iter()
+            .find(|directive| directive.node.name.node == directive_name)
+        else {
+            return Ok(None);
+        };
+
+        match self.directive_argument(directive, "if")? {
+            None | Some((ConstValue::Boolean(true), _)) => Ok(Some(directive)),
+            Some((ConstValue::Boolean(false), _)) => Ok(None),
+            Some((_, pos)) => Err(Self::invalid_directive_argument(
+                directive,
+                "if",
+                "a boolean",
+                pos,
+            )),
+        }
+    }
+
+    fn directive_label(
+        &self,
+        directive: &Positioned<Directive>,
+    ) -> Result<Option<String>, ValidationError> {
+        match self.directive_argument(directive, "label")? {
+            None => Ok(None),
+            Some((ConstValue::String(label), _)) => Ok(Some(label)),
+            Some((_, pos)) => Err(Self::invalid_directive_argument(
+                directive, "label", "a string", pos,
+            )),
+        }
+    }
+
+    fn streamed_field(
+        &self,
+        field: &Positioned<Field>,
+        directive: &Positioned<Directive>,
+    ) -> Result<StreamedField, ValidationError> {
+        let field_definition = self.get_field_definition(field)?;
+        if !matches!(field_definition.ty.node.base, BaseType::List(_)) {
+            return Err(ValidationError::StreamOnNonListField(
+                field.node.name.node.to_string(),
+                directive.pos,
+            ));
+        }
+
+        let initial_count = match self.directive_argument(directive, "initialCount")? {
+            None => 0,
+            Some((ConstValue::Number(count), pos)) => count.as_u64().ok_or_else(|| {
+                Self::invalid_directive_argument(
+                    directive,
+                    "initialCount",
+                    "a non-negative integer",
+                    pos,
+                )
+            })? as usize,
+            Some((_, pos)) => {
+                return Err(Self::invalid_directive_argument(
+                    directive,
+                    "initialCount",
+                    "a non-negative integer",
+                    pos,
+                ))
+            }
+        };
+
+        Ok(StreamedField {
+            label: self.directive_label(directive)?,
+            output_name: field.node.response_key().node.to_string(),
+            initial_count,
+        })
+    }
+
     fn validate_field(
         &self,
         field: &Positioned<Field>,
@@ -414,3 +705,11 @@ fn merge_subfields(
         Err(invalid_fields)
     }
 }
+
+fn selection_directives(selection: &Positioned<Selection>) -> &[Positioned<Directive>] {
+    match &selection.node {
+        Selection::Field(field) => &field.node.directives,
+        Selection::FragmentSpread(fragment_spread) => &fragment_spread.node.directives,
+        Selection::InlineFragment(inline_fragment) => &inline_fragment.node.directives,
+    }
+}
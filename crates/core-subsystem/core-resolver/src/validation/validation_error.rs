@@ -10,6 +10,8 @@
 use async_graphql_parser::Pos;
 use thiserror::Error;
 
+use crate::ErrorCode;
+
 #[derive(Error, Debug)]
 pub enum ValidationError {
     #[error("{0}")]
@@ -24,7 +26,9 @@ pub enum ValidationError {
     #[error("Fragment definition '{0}' not found")]
     FragmentDefinitionNotFound(String, Pos),
 
-    #[error("Inline fragments are not supported")]
+    #[error(
+        "Inline fragments with a type condition other than the enclosing type are not supported"
+    )]
     InlineFragmentNotSupported(Pos),
 
     #[error("No such operation '{0}'")]
@@ -55,6 +59,14 @@ pub enum ValidationError {
         pos: Pos,
     },
 
+    #[error("Argument '{argument_name}' has the value '{value}', which is not a value of the enum '{enum_name}'")]
+    InvalidEnumValue {
+        argument_name: String,
+        enum_name: String,
+        value: String,
+        pos: Pos,
+    },
+
     #[error(
         "Failed to merge field defined multiple times with different selection or arguments: '{0}'"
     )]
@@ -74,6 +86,28 @@ pub enum ValidationError {
 
     #[error("Selection set too deep")]
     SelectionSetTooDeep(Pos),
+
+    #[error("Subscription operations must select exactly one top-level field")]
+    SubscriptionMultipleRootFields(Pos),
+
+    #[error(
+        "The '{argument_name}' argument of the '@{directive_name}' directive must be {expected}"
+    )]
+    InvalidDirectiveArgument {
+        directive_name: String,
+        argument_name: String,
+        expected: String,
+        pos: Pos,
+    },
+
+    #[error("The '@stream' directive may only be applied to list fields, but '{0}' is not a list")]
+    StreamOnNonListField(String, Pos),
+
+    #[error("The '@{0}' directive may only be applied to {1}")]
+    MisplacedIncrementalDirective(String, String, Pos),
+
+    #[error("The operation's cost of {0} exceeds the maximum allowed cost of {1}")]
+    QueryCostExceeded(usize, usize, Pos),
 }
 
 impl ValidationError {
@@ -97,8 +131,23 @@ impl ValidationError {
             ValidationError::MultipleOperationsNoOperationName => vec![],
             ValidationError::MultipleOperationsUnmatchedOperationName(_) => vec![],
             ValidationError::InvalidArgumentType { pos, .. } => vec![*pos],
+            ValidationError::InvalidEnumValue { pos, .. } => vec![*pos],
             ValidationError::FragmentCycle(_, pos) => vec![*pos],
             ValidationError::SelectionSetTooDeep(pos) => vec![*pos],
+            ValidationError::SubscriptionMultipleRootFields(pos) => vec![*pos],
+            ValidationError::InvalidDirectiveArgument { pos, .. } => vec![*pos],
+            ValidationError::StreamOnNonListField(_, pos) => vec![*pos],
+            ValidationError::MisplacedIncrementalDirective(_, _, pos) => vec![*pos],
+            ValidationError::QueryCostExceeded(_, _, pos) => vec![*pos],
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ValidationError::QueryParsingFailed(..) => ErrorCode::GraphqlParseFailed,
+            ValidationError::OperationNotFound(..)
+            | ValidationError::MultipleOperationsUnmatchedOperationName(_) => ErrorCode::NotFound,
+            _ => ErrorCode::ValidationFailed,
         }
     }
 }
@@ -15,7 +15,7 @@ use std::{fs::File, path::Path};
 use core_plugin_interface::core_model::context_type::{ContextFieldType, ContextType};
 use core_plugin_interface::core_model_builder::builder::system_builder::BaseModelSystem;
 use core_plugin_interface::core_model_builder::{
-    ast::ast_types::{AstArgument, AstFieldType, AstModel, AstModule},
+    ast::ast_types::{AstArgument, AstEnum, AstFieldType, AstModel, AstModule},
     error::ModelBuildingError,
     typechecker::Typed,
 };
@@ -262,6 +262,7 @@ fn import_types_from_models(
         .iter()
         .filter(|model| selection(model))
         .map(|model| model.name.clone())
+        .chain(module.enums.iter().map(|enum_| enum_.name.clone()))
         .collect::<Vec<_>>();
 
     types_used.dedup();
@@ -354,6 +355,9 @@ fn generate_module_definitions(module: &AstModule<Typed>) -> Result<(), ModelBui
     for module_type in module.types.iter() {
         generate_type_skeleton(module_type, &mut file)?;
     }
+    for module_enum in module.enums.iter() {
+        generate_enum_skeleton(module_enum, &mut file)?;
+    }
 
     Ok(())
 }
@@ -370,6 +374,23 @@ fn generate_type_skeleton(model: &dyn Type, out_file: &mut File) -> Result<(), M
     Ok(())
 }
 
+/// Generate a string union type for an enum (for example, `export type Status = "DRAFT" | "PUBLISHED";`)
+fn generate_enum_skeleton(
+    module_enum: &AstEnum,
+    out_file: &mut File,
+) -> Result<(), ModelBuildingError> {
+    let values = module_enum
+        .values
+        .iter()
+        .map(|value| format!("\"{}\"", value.name))
+        .collect::<Vec<_>>()
+        .join(" | ");
+
+    out_file.write_all(format!("export type {} = {values};\n\n", module_enum.name).as_bytes())?;
+
+    Ok(())
+}
+
 fn generate_field(name: &str, tpe: &dyn TypeScriptType, is_typescript: bool) -> String {
     if is_typescript {
         format!("{}: {}", name, tpe.typescript_type())
@@ -597,6 +618,7 @@ mod tests {
             annotations: Default::default(),
             base_exofile: PathBuf::new(),
             interceptors: vec![],
+            enums: vec![],
             methods: vec![],
             span,
         }
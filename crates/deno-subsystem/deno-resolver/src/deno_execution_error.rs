@@ -9,7 +9,7 @@
 
 use core_plugin_interface::core_resolver::{
     access_solver::AccessSolverError, context::ContextExtractionError,
-    plugin::SubsystemResolutionError, system_resolver::SystemResolutionError,
+    plugin::SubsystemResolutionError, system_resolver::SystemResolutionError, ErrorCode,
 };
 use thiserror::Error;
 
@@ -42,17 +42,30 @@ impl DenoExecutionError {
         }
     }
 
-    pub fn explicit_message(&self) -> Option<String> {
-        fn root_error<'a>(
-            error: &'a (dyn std::error::Error + 'static),
-        ) -> &'a (dyn std::error::Error + 'static) {
-            match error.source() {
-                Some(source) => root_error(source),
-                None => error,
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            DenoExecutionError::Authorization => ErrorCode::Unauthorized,
+            DenoExecutionError::Deno(DenoError::Explicit(_)) => ErrorCode::OperationFailed,
+            _ => {
+                let root_error = self.root_error();
+
+                // Mirror `explicit_message`, so that the code of a nested error is preserved
+                match root_error.downcast_ref::<DenoError>() {
+                    Some(DenoError::Explicit(_)) => ErrorCode::OperationFailed,
+                    _ => match root_error.downcast_ref::<SubsystemResolutionError>() {
+                        Some(error) => error.code(),
+                        _ => root_error
+                            .downcast_ref::<SystemResolutionError>()
+                            .map(|error| error.code())
+                            .unwrap_or(ErrorCode::InternalServerError),
+                    },
+                }
             }
         }
+    }
 
-        let root_error = root_error(self);
+    pub fn explicit_message(&self) -> Option<String> {
+        let root_error = self.root_error();
 
         // To deal with nested exceptions, we need to check if it is an explicit error (direct
         // invocation, so DenoError::Explicit suffices), or did it invoke it in nested fashion
@@ -68,6 +81,19 @@ impl DenoExecutionError {
             },
         }
     }
+
+    fn root_error(&self) -> &(dyn std::error::Error + 'static) {
+        fn root_error<'a>(
+            error: &'a (dyn std::error::Error + 'static),
+        ) -> &'a (dyn std::error::Error + 'static) {
+            match error.source() {
+                Some(source) => root_error(source),
+                None => error,
+            }
+        }
+
+        root_error(self)
+    }
 }
 
 impl From<AccessSolverError> for DenoExecutionError {
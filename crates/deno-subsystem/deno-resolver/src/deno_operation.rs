@@ -65,7 +65,7 @@ impl<'a> DenoOperation<'a> {
                 let return_type = return_type.typ(&subsystem.module_types);
 
                 let type_level_access = match &return_type.kind {
-                    ModuleTypeKind::Primitive => true,
+                    ModuleTypeKind::Primitive | ModuleTypeKind::Enum(_) => true,
                     ModuleTypeKind::Composite(ModuleCompositeType { access, .. }) => subsystem
                         .solve(self.request_context, None, &access.value)
                         .await?
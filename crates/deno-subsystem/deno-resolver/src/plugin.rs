@@ -18,7 +18,7 @@ use core_plugin_interface::{
         plugin::{SubsystemResolutionError, SubsystemResolver},
         system_resolver::SystemResolver,
         validation::field::ValidatedField,
-        InterceptedOperation, QueryResponse, QueryResponseBody,
+        ErrorCode, InterceptedOperation, QueryResponse, QueryResponseBody,
     },
     interception::InterceptorIndex,
     interface::{SubsystemLoader, SubsystemLoadingError},
@@ -121,9 +121,8 @@ impl SubsystemResolver for DenoSubsystemResolver {
                     )
                 })
             }
-            OperationType::Subscription => Some(Err(DenoExecutionError::Generic(
-                "Subscriptions are not supported".to_string(),
-            ))),
+            // This subsystem doesn't offer any subscriptions
+            OperationType::Subscription => None,
         };
 
         match deno_operation {
@@ -201,10 +200,13 @@ impl From<DenoExecutionError> for SubsystemResolutionError {
     fn from(e: DenoExecutionError) -> Self {
         match e {
             DenoExecutionError::Authorization => SubsystemResolutionError::Authorization,
-            _ => SubsystemResolutionError::UserDisplayError(
-                e.user_error_message()
-                    .unwrap_or_else(|| "Internal server error".to_string()),
-            ),
+            _ => match e.user_error_message() {
+                Some(message) => SubsystemResolutionError::UserDisplayError(message, e.code()),
+                None => SubsystemResolutionError::UserDisplayError(
+                    "Internal server error".to_string(),
+                    ErrorCode::InternalServerError,
+                ),
+            },
         }
     }
 }
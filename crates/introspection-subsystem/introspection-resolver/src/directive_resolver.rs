@@ -7,7 +7,7 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use async_graphql_parser::types::Directive;
+use async_graphql_parser::types::{DirectiveDefinition, DirectiveLocation};
 use async_trait::async_trait;
 use core_resolver::{
     context::RequestContext, introspection::definition::schema::Schema,
@@ -19,20 +19,35 @@ use core_resolver::validation::field::ValidatedField;
 
 use crate::field_resolver::FieldResolver;
 
+use super::resolver_support::Resolver;
+
 #[async_trait]
-impl FieldResolver<Value, SubsystemResolutionError> for Directive {
+impl FieldResolver<Value, SubsystemResolutionError> for DirectiveDefinition {
     async fn resolve_field<'e>(
         &'e self,
         field: &ValidatedField,
-        _schema: &Schema,
-        _request_context: &'e RequestContext<'e>,
+        schema: &Schema,
+        request_context: &'e RequestContext<'e>,
     ) -> Result<Value, SubsystemResolutionError> {
         match field.name.as_str() {
             "name" => Ok(Value::String(self.name.node.as_str().to_owned())),
-            "description" => Ok(Value::Null),
-            "isRepeatable" => Ok(Value::Bool(false)), // TODO
-            "locations" => Ok(Value::Array(vec![])),  // TODO
-            "args" => Ok(Value::Array(vec![])),       // TODO
+            "description" => Ok(self
+                .description
+                .clone()
+                .map(|v| Value::String(v.node))
+                .unwrap_or(Value::Null)),
+            "isRepeatable" => Ok(Value::Bool(self.is_repeatable)),
+            "locations" => Ok(Value::Array(
+                self.locations
+                    .iter()
+                    .map(|location| Value::String(location_name(&location.node).to_string()))
+                    .collect(),
+            )),
+            "args" => {
+                self.arguments
+                    .resolve_value(&field.subfields, schema, request_context)
+                    .await
+            }
             "__typename" => Ok(Value::String("__Directive".to_string())),
             field_name => Err(SubsystemResolutionError::InvalidField(
                 field_name.to_owned(),
@@ -41,3 +56,27 @@ impl FieldResolver<Value, SubsystemResolutionError> for Directive {
         }
     }
 }
+
+fn location_name(location: &DirectiveLocation) -> &'static str {
+    match location {
+        DirectiveLocation::Query => "QUERY",
+        DirectiveLocation::Mutation => "MUTATION",
+        DirectiveLocation::Subscription => "SUBSCRIPTION",
+        DirectiveLocation::Field => "FIELD",
+        DirectiveLocation::FragmentDefinition => "FRAGMENT_DEFINITION",
+        DirectiveLocation::FragmentSpread => "FRAGMENT_SPREAD",
+        DirectiveLocation::InlineFragment => "INLINE_FRAGMENT",
+        DirectiveLocation::Schema => "SCHEMA",
+        DirectiveLocation::Scalar => "SCALAR",
+        DirectiveLocation::Object => "OBJECT",
+        DirectiveLocation::FieldDefinition => "FIELD_DEFINITION",
+        DirectiveLocation::ArgumentDefinition => "ARGUMENT_DEFINITION",
+        DirectiveLocation::Interface => "INTERFACE",
+        DirectiveLocation::Union => "UNION",
+        DirectiveLocation::Enum => "ENUM",
+        DirectiveLocation::EnumValue => "ENUM_VALUE",
+        DirectiveLocation::InputObject => "INPUT_OBJECT",
+        DirectiveLocation::InputFieldDefinition => "INPUT_FIELD_DEFINITION",
+        DirectiveLocation::VariableDefinition => "VARIABLE_DEFINITION",
+    }
+}
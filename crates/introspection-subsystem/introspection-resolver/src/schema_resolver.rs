@@ -49,7 +49,11 @@ impl FieldResolver<Value, SubsystemResolutionError> for Schema {
                     .resolve_value(&field.subfields, schema, request_context)
                     .await
             }
-            "directives" => Ok(Value::Array(vec![])), // TODO
+            "directives" => {
+                self.directive_definitions
+                    .resolve_value(&field.subfields, schema, request_context)
+                    .await
+            }
             "description" => Ok(Value::String("Top-level schema".to_string())),
             "__typename" => Ok(Value::String("__Schema".to_string())),
             field_name => Err(SubsystemResolutionError::InvalidField(
@@ -46,9 +46,10 @@ fn create_shallow_type(resolved_type: &ResolvedType, building: &mut SystemContex
     let aggregate_type_name = aggregate_type_name(&resolved_type.name());
 
     let typ = match resolved_type {
-        ResolvedType::Composite(_) => AggregateType {
+        ResolvedType::Composite(composite) => AggregateType {
             name: aggregate_type_name.clone(),
             fields: vec![],
+            cache_policy: composite.cache_policy,
         },
         ResolvedType::Primitive(_) => {
             let supported_kinds = AGG_MAP
@@ -83,6 +84,7 @@ fn create_shallow_type(resolved_type: &ResolvedType, building: &mut SystemContex
             AggregateType {
                 name: aggregate_type_name.clone(),
                 fields,
+                cache_policy: None,
             }
         }
     };
@@ -42,7 +42,9 @@ impl Builder for CreateMutationBuilder {
         resolved_composite_type: &ResolvedCompositeType,
         types: &MappedArena<ResolvedType>,
     ) -> Vec<String> {
-        if !resolved_composite_type.access.creation_allowed() {
+        if !resolved_composite_type.access.creation_allowed()
+            || resolved_composite_type.table_kind.is_view()
+        {
             return vec![];
         }
         let mut field_types = self.data_param_field_type_names(resolved_composite_type, types);
@@ -54,15 +56,17 @@ impl Builder for CreateMutationBuilder {
         &self,
         building: &mut SystemContextBuilding,
     ) -> Result<(), ModelBuildingError> {
-        let creation_access_is_false = |entity_type: &EntityType| -> bool {
-            matches!(
-                building.input_access_expressions.borrow()[entity_type.access.creation],
-                AccessPredicateExpression::BooleanLiteral(false)
-            )
+        // Views are read-only
+        let creation_not_allowed = |entity_type: &EntityType| -> bool {
+            entity_type.is_view(&building.database)
+                || matches!(
+                    building.input_access_expressions.borrow()[entity_type.access.creation],
+                    AccessPredicateExpression::BooleanLiteral(false)
+                )
         };
 
         for (_, entity_type) in building.entity_types.iter() {
-            if !creation_access_is_false(entity_type) {
+            if !creation_not_allowed(entity_type) {
                 for (existing_id, expanded_type) in
                     self.expanded_data_type(entity_type, building, Some(entity_type), None, false)?
                 {
@@ -72,7 +76,7 @@ impl Builder for CreateMutationBuilder {
         }
 
         for (_, entity_type) in building.entity_types.iter() {
-            if !creation_access_is_false(entity_type) {
+            if !creation_not_allowed(entity_type) {
                 let entity_type_id = building
                     .entity_types
                     .get_id(entity_type.name.as_str())
@@ -131,6 +135,10 @@ impl DataParamBuilder<DataParameter> for CreateMutationBuilder {
         entity_type_name.creation_type()
     }
 
+    fn many_to_many_data_type_name(field_type_name: &str) -> String {
+        field_type_name.reference_type()
+    }
+
     fn data_param_role() -> DataParamRole {
         DataParamRole::Create
     }
@@ -8,7 +8,8 @@
 // by the Apache License, Version 2.0.
 
 //! Build mutation input types associated with deletion (`<Type>DeletionInput`) and
-//! the create mutations (`delete<Type>`, and `delete<Type>s`)
+//! the create mutations (`delete<Type>`, and `delete<Type>s`) along with the restore mutations
+//! (`restore<Type>`, and `restore<Type>s`) for types with the `@softDelete` annotation
 
 use core_plugin_interface::{
     core_model::{
@@ -49,6 +50,10 @@ impl Builder for DeleteMutationBuilder {
     ) -> Result<(), ModelBuildingError> {
         // Since there are no special input types for deletion, no expansion is needed
         for (entity_type_id, entity_type) in building.entity_types.iter() {
+            // Views are read-only
+            if entity_type.is_view(&building.database) {
+                continue;
+            }
             if let AccessPredicateExpression::BooleanLiteral(false) =
                 building.database_access_expressions.borrow()[entity_type.access.delete]
             {
@@ -57,6 +62,15 @@ impl Builder for DeleteMutationBuilder {
             for mutation in self.build_mutations(entity_type_id, entity_type, building) {
                 building.mutations.add(&mutation.name.to_owned(), mutation);
             }
+            if entity_type.soft_delete.is_some() {
+                for mutation in (RestoreMutationBuilder {}).build_mutations(
+                    entity_type_id,
+                    entity_type,
+                    building,
+                ) {
+                    building.mutations.add(&mutation.name.to_owned(), mutation);
+                }
+            }
         }
 
         Ok(())
@@ -72,7 +86,7 @@ impl MutationBuilder for DeleteMutationBuilder {
         entity_type: &EntityType,
         building: &SystemContextBuilding,
     ) -> PostgresMutationParameters {
-        PostgresMutationParameters::Delete(query_builder::pk_predicate_param(
+        PostgresMutationParameters::Delete(query_builder::pk_predicate_params(
             entity_type,
             &building.predicate_types,
             &building.database,
@@ -94,9 +108,51 @@ impl MutationBuilder for DeleteMutationBuilder {
         entity_type: &EntityType,
         building: &SystemContextBuilding,
     ) -> PostgresMutationParameters {
-        PostgresMutationParameters::Delete(query_builder::collection_predicate_param(
+        PostgresMutationParameters::Delete(vec![query_builder::collection_predicate_param(
             entity_type,
             &building.predicate_types,
+        )])
+    }
+}
+
+/// Builds mutations to restore soft-deleted entities. Since restoring undoes a deletion, these
+/// mutations share the delete access rule of the type.
+struct RestoreMutationBuilder;
+
+impl MutationBuilder for RestoreMutationBuilder {
+    fn single_mutation_name(entity_type: &EntityType) -> String {
+        entity_type.pk_restore()
+    }
+
+    fn single_mutation_parameters(
+        entity_type: &EntityType,
+        building: &SystemContextBuilding,
+    ) -> PostgresMutationParameters {
+        PostgresMutationParameters::Restore(query_builder::pk_predicate_params(
+            entity_type,
+            &building.predicate_types,
+            &building.database,
         ))
     }
+
+    fn single_mutation_modified_type(
+        base_type: BaseOperationReturnType<EntityType>,
+    ) -> OperationReturnType<EntityType> {
+        // We return null if the specified id doesn't exist (or isn't deleted)
+        OperationReturnType::Optional(Box::new(OperationReturnType::Plain(base_type)))
+    }
+
+    fn multi_mutation_name(entity_type: &EntityType) -> String {
+        entity_type.collection_restore()
+    }
+
+    fn multi_mutation_parameters(
+        entity_type: &EntityType,
+        building: &SystemContextBuilding,
+    ) -> PostgresMutationParameters {
+        PostgresMutationParameters::Restore(vec![query_builder::collection_predicate_param(
+            entity_type,
+            &building.predicate_types,
+        )])
+    }
 }
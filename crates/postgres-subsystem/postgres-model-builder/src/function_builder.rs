@@ -0,0 +1,185 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Build queries and mutations backed by Postgres functions (through the `@function` annotation).
+//!
+//! Such operations return entities, so this builder doesn't create any types of its own.
+
+use codemap::Span;
+use codemap_diagnostic::{Diagnostic, Level, SpanLabel, SpanStyle};
+
+use core_plugin_interface::{
+    core_model::types::{BaseOperationReturnType, FieldType, OperationReturnType},
+    core_model_builder::{ast::ast_types::AstMethodType, error::ModelBuildingError},
+};
+use exo_sql::PhysicalColumnType;
+use postgres_model::function::{
+    FunctionArgument, FunctionArgumentType, FunctionOperation, FunctionParameters,
+};
+
+use super::{
+    resolved_builder::{ResolvedFunction, ResolvedFunctionArgument, ResolvedType},
+    system_builder::SystemContextBuilding,
+    type_builder::{compute_input_access_expr, default_column_type, ResolvedTypeEnv},
+};
+
+pub fn build_expanded(
+    resolved_functions: &[ResolvedFunction],
+    resolved_env: &ResolvedTypeEnv,
+    building: &mut SystemContextBuilding,
+) -> Result<(), ModelBuildingError> {
+    let mut errors = vec![];
+
+    for resolved_function in resolved_functions {
+        let name_taken = match resolved_function.typ {
+            AstMethodType::Query => is_query_name_taken(&resolved_function.name, building),
+            AstMethodType::Mutation => is_mutation_name_taken(&resolved_function.name, building),
+        };
+
+        if name_taken {
+            errors.push(error(
+                format!(
+                    "Operation '{}' conflicts with an operation of the same name",
+                    resolved_function.name
+                ),
+                resolved_function.span,
+            ));
+            continue;
+        }
+
+        match build_function(resolved_function, resolved_env, building)? {
+            Some(function) => {
+                let functions = match resolved_function.typ {
+                    AstMethodType::Query => &mut building.function_queries,
+                    AstMethodType::Mutation => &mut building.function_mutations,
+                };
+                functions.add(&function.name.to_owned(), function);
+            }
+            None => errors.push(error(
+                format!(
+                    "Operation '{}' must return a type in a @postgres module",
+                    resolved_function.name
+                ),
+                resolved_function.span,
+            )),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(ModelBuildingError::Diagnosis(errors))
+    }
+}
+
+/// Build the operation (or `None` if the return type isn't an entity type)
+fn build_function(
+    resolved_function: &ResolvedFunction,
+    resolved_env: &ResolvedTypeEnv,
+    building: &SystemContextBuilding,
+) -> Result<Option<FunctionOperation>, ModelBuildingError> {
+    let return_type_name = &resolved_function.return_type.innermost().type_name;
+    let Some(entity_type_id) = building.get_entity_type_id(return_type_name) else {
+        return Ok(None);
+    };
+
+    let base_return_type = OperationReturnType::Plain(BaseOperationReturnType {
+        associated_type_id: entity_type_id,
+        type_name: return_type_name.clone(),
+    });
+
+    // The resolved builder ensures that the return type is either a list or an optional
+    let return_type = match &resolved_function.return_type {
+        FieldType::List(_) => OperationReturnType::List(Box::new(base_return_type)),
+        _ => OperationReturnType::Optional(Box::new(base_return_type)),
+    };
+
+    let access = compute_input_access_expr(
+        &[&resolved_function.access],
+        entity_type_id,
+        resolved_env,
+        building,
+    )?;
+
+    Ok(Some(FunctionOperation {
+        name: resolved_function.name.clone(),
+        parameters: FunctionParameters {
+            function_name: resolved_function.function_name.clone(),
+            arguments: resolved_function
+                .arguments
+                .iter()
+                .map(|argument| build_argument(argument, resolved_env, building))
+                .collect(),
+            access,
+        },
+        return_type,
+    }))
+}
+
+fn build_argument(
+    argument: &ResolvedFunctionArgument,
+    resolved_env: &ResolvedTypeEnv,
+    building: &SystemContextBuilding,
+) -> FunctionArgument {
+    let type_name = &argument.typ.innermost().type_name;
+
+    let type_id = building.primitive_types.get_id(type_name).unwrap();
+
+    let ResolvedType::Primitive(pt) = resolved_env.get_by_key(type_name).unwrap() else {
+        panic!("Function argument type '{type_name}' is not a primitive type")
+    };
+
+    // A list argument (such as `ids: Array<Int>`) is passed as an array
+    fn column_type(typ: &FieldType<impl Sized>, base: PhysicalColumnType) -> PhysicalColumnType {
+        match typ {
+            FieldType::Plain(_) => base,
+            FieldType::Optional(underlying) => column_type(underlying, base),
+            FieldType::List(underlying) => PhysicalColumnType::Array {
+                typ: Box::new(column_type(underlying, base)),
+            },
+        }
+    }
+
+    FunctionArgument {
+        name: argument.name.clone(),
+        typ: argument.typ.wrap(FunctionArgumentType {
+            type_name: type_name.clone(),
+            type_id,
+        }),
+        column_type: column_type(&argument.typ, default_column_type(pt)),
+    }
+}
+
+fn is_query_name_taken(name: &str, building: &SystemContextBuilding) -> bool {
+    building.pk_queries.get_id(name).is_some()
+        || building.collection_queries.get_id(name).is_some()
+        || building.connection_queries.get_id(name).is_some()
+        || building.aggregate_queries.get_id(name).is_some()
+        || building.group_by_queries.get_id(name).is_some()
+        || building.unique_queries.get_id(name).is_some()
+        || building.history_queries.get_id(name).is_some()
+        || building.function_queries.get_id(name).is_some()
+}
+
+fn is_mutation_name_taken(name: &str, building: &SystemContextBuilding) -> bool {
+    building.mutations.get_id(name).is_some() || building.function_mutations.get_id(name).is_some()
+}
+
+fn error(message: String, span: Span) -> Diagnostic {
+    Diagnostic {
+        level: Level::Error,
+        message,
+        code: Some("C000".to_string()),
+        spans: vec![SpanLabel {
+            span,
+            style: SpanStyle::Primary,
+            label: None,
+        }],
+    }
+}
@@ -15,6 +15,7 @@ mod aggregate_type_builder;
 mod builder;
 mod create_mutation_builder;
 mod delete_mutation_builder;
+mod function_builder;
 mod mutation_builder;
 mod naming;
 mod order_by_type_builder;
@@ -22,11 +23,14 @@ mod plugin;
 mod predicate_builder;
 mod query_builder;
 mod reference_input_type_builder;
+mod refresh_mutation_builder;
 mod resolved_builder;
 mod shallow;
+mod subscription_builder;
 mod system_builder;
 mod type_builder;
 mod update_mutation_builder;
+mod upsert_mutation_builder;
 mod utils;
 
 #[cfg(test)]
@@ -8,7 +8,8 @@
 // by the Apache License, Version 2.0.
 
 //! Build mutation input types (`<Type>CreationInput`, `<Type>UpdateInput`, `<Type>ReferenceInput`) and
-//! mutations (`create<Type>`, `update<Type>`, and `delete<Type>` as well as their plural versions)
+//! mutations (`create<Type>`, `update<Type>`, `delete<Type>`, and `upsert<Type>` as well as their plural versions,
+//! and `refresh<Type>s` for materialized views)
 
 use core_plugin_interface::{
     core_model::{
@@ -42,9 +43,12 @@ use super::{
     delete_mutation_builder::DeleteMutationBuilder,
     naming::ToPostgresTypeNames,
     reference_input_type_builder::ReferenceInputTypeBuilder,
+    refresh_mutation_builder,
     resolved_builder::{ResolvedCompositeType, ResolvedType},
     system_builder::SystemContextBuilding,
+    type_builder::ResolvedTypeEnv,
     update_mutation_builder::UpdateMutationBuilder,
+    upsert_mutation_builder,
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -69,12 +73,17 @@ pub fn build_shallow(
 }
 
 /// Expand the mutation input types as well as build the mutation
-pub fn build_expanded(building: &mut SystemContextBuilding) -> Result<(), ModelBuildingError> {
+pub fn build_expanded(
+    resolved_env: &ResolvedTypeEnv,
+    building: &mut SystemContextBuilding,
+) -> Result<(), ModelBuildingError> {
     ReferenceInputTypeBuilder {}.build_expanded(building)?; // Used by many...
 
     CreateMutationBuilder {}.build_expanded(building)?;
     UpdateMutationBuilder {}.build_expanded(building)?;
     DeleteMutationBuilder {}.build_expanded(building)?;
+    upsert_mutation_builder::build_expanded(resolved_env, building);
+    refresh_mutation_builder::build_expanded(building);
 
     Ok(())
 }
@@ -164,6 +173,11 @@ pub trait DataParamBuilder<D> {
     /// since the "create", "update" and "delete" operations themselves are of the list type.
     fn use_list_for_nested_one_to_many() -> bool;
 
+    /// The name of the type for a many-to-many field that refers to the given type. In case of
+    /// creation, it is the reference type (used as a list to connect the referred objects), and for
+    /// updates, it is a type that allows connecting and disconnecting the referred objects.
+    fn many_to_many_data_type_name(field_type_name: &str) -> String;
+
     fn data_param_field_type_names(
         &self,
         resolved_composite_type: &ResolvedCompositeType,
@@ -173,6 +187,12 @@ pub trait DataParamBuilder<D> {
             .fields
             .iter()
             .flat_map(|field| {
+                // A many-to-many field refers to existing objects (through the linking table), so
+                // doesn't need a nested input data type
+                if field.many_to_many_table.is_some() {
+                    return vec![];
+                }
+
                 // Create a nested input data type only if it refers to a many side
                 // So for Venue <-> [Concert] case, create only ConcertCreationInputFromVenue
 
@@ -184,9 +204,17 @@ pub trait DataParamBuilder<D> {
                 };
 
                 // If the type is a list or a reference, we need to create a nested input type (one-to-many or one-to-zero-or-one)
-                if let Some(ResolvedType::Composite(ResolvedCompositeType { name, .. })) =
-                    typ.deref_subsystem_type(resolved_types)
+                // (except for views, which are read-only)
+                if let Some(ResolvedType::Composite(ResolvedCompositeType {
+                    name,
+                    table_kind,
+                    ..
+                })) = typ.deref_subsystem_type(resolved_types)
                 {
+                    if table_kind.is_view() {
+                        return vec![];
+                    }
+
                     if let FieldType::List(_) = field.typ {
                         // If it is a list, we need to create a nested input type (one-to-many)
                         Self::data_param_field_one_to_many_type_names(name, resolved_composite_type)
@@ -253,6 +281,7 @@ pub trait DataParamBuilder<D> {
                         access: field.access.clone(),
                         dynamic_default_value: field.dynamic_default_value.clone(),
                         readonly: field.readonly,
+                        cache_policy: None,
                     })
                 } else {
                     // Make the decision to include the pk column based on the default value for the
@@ -281,6 +310,7 @@ pub trait DataParamBuilder<D> {
                             has_default_value: field.has_default_value,
                             dynamic_default_value: field.dynamic_default_value.clone(),
                             readonly: field.readonly,
+                            cache_policy: None,
                         })
                     }
                 }
@@ -297,10 +327,14 @@ pub trait DataParamBuilder<D> {
                 has_default_value: field.has_default_value,
                 dynamic_default_value: field.dynamic_default_value.clone(),
                 readonly: field.readonly,
+                cache_policy: None,
             }),
             PostgresRelation::OneToMany { .. } => {
                 self.compute_one_to_many_data_field(field, container_type, building)
             }
+            PostgresRelation::ManyToMany { .. } => {
+                self.compute_many_to_many_data_field(field, building)
+            }
             PostgresRelation::ManyToOne { .. } => {
                 let field_type_name = field.typ.name().reference_type();
                 let field_type_id = building.mutation_types.get_id(&field_type_name).unwrap();
@@ -330,6 +364,7 @@ pub trait DataParamBuilder<D> {
                         has_default_value: field.has_default_value,
                         dynamic_default_value: field.dynamic_default_value.clone(),
                         readonly: field.readonly,
+                        cache_policy: None,
                     }),
                 }
             }
@@ -374,11 +409,46 @@ pub trait DataParamBuilder<D> {
                         has_default_value: field.has_default_value,
                         dynamic_default_value: field.dynamic_default_value.clone(),
                         readonly: field.readonly,
+                        cache_policy: None,
                     }),
                 }
             })
     }
 
+    fn compute_many_to_many_data_field(
+        &self,
+        field: &PostgresField<EntityType>,
+        building: &SystemContextBuilding,
+    ) -> Option<PostgresField<MutationType>> {
+        let field_type_name = Self::many_to_many_data_type_name(field.typ.name());
+
+        building
+            .mutation_types
+            .get_id(&field_type_name)
+            .map(|field_type_id| {
+                let field_plain_type = FieldType::Plain(PostgresFieldType {
+                    type_name: field_type_name,
+                    type_id: TypeIndex::Composite(field_type_id),
+                });
+                let field_type = if Self::use_list_for_nested_one_to_many() {
+                    FieldType::List(Box::new(field_plain_type))
+                } else {
+                    field_plain_type
+                };
+
+                PostgresField {
+                    name: field.name.clone(),
+                    typ: field_type.optional(),
+                    access: field.access.clone(),
+                    relation: field.relation.clone(),
+                    has_default_value: field.has_default_value,
+                    dynamic_default_value: None,
+                    readonly: field.readonly,
+                    cache_policy: None,
+                }
+            })
+    }
+
     fn expanded_data_type(
         &self,
         entity_type: &EntityType,
@@ -398,7 +468,8 @@ pub trait DataParamBuilder<D> {
             if let (PostgresType::Composite(field_type), PostgresRelation::OneToMany { .. }) =
                 (&field_type, &field.relation)
             {
-                if !expanding_one_to_many {
+                // Views are read-only, so don't have nested input types
+                if !expanding_one_to_many && !field_type.is_view(&building.database) {
                     let expanded = self.expand_one_to_many(
                         entity_type,
                         field,
@@ -524,7 +595,7 @@ fn get_matching_field<'a>(
         let matching_fields: Vec<_> = field_typ
             .fields
             .iter()
-            .filter(|f| field.column_name == f.column_name)
+            .filter(|f| f.many_to_many_table.is_none() && field.column_name == f.column_name)
             .collect();
 
         match &matching_fields[..] {
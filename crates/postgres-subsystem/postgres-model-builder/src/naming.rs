@@ -46,6 +46,12 @@ pub(super) trait ToPostgresQueryName {
     fn collection_query(&self) -> String;
     /// Aggregate query name (e.g. `concertAgg`)
     fn aggregate_query(&self) -> String;
+    /// Connection query name (e.g. `concertsConnection`)
+    fn connection_query(&self) -> String;
+    /// Group-by query name (e.g. `concertsGroupBy`)
+    fn group_by_query(&self) -> String;
+    /// History query name (e.g. `concertHistory`)
+    fn history_query(&self) -> String;
 
     /// Unique query name (e.g. `concertByTitle`)
     /// `constraint_name` is the name of the unique constraint in the database (possibly in snake case or camel case)
@@ -69,6 +75,18 @@ impl<T: ToPlural> ToPostgresQueryName for T {
         format!("{}Agg", self.collection_query())
     }
 
+    fn connection_query(&self) -> String {
+        format!("{}Connection", self.collection_query())
+    }
+
+    fn group_by_query(&self) -> String {
+        format!("{}GroupBy", self.collection_query())
+    }
+
+    fn history_query(&self) -> String {
+        format!("{}History", self.pk_query())
+    }
+
     fn unique_query(&self, constraint_name: &str) -> String {
         format!(
             "{}By{}",
@@ -90,6 +108,18 @@ fn to_update(name: &str) -> String {
     format!("update{name}")
 }
 
+fn to_upsert(name: &str) -> String {
+    format!("upsert{name}")
+}
+
+fn to_restore(name: &str) -> String {
+    format!("restore{name}")
+}
+
+fn to_refresh(name: &str) -> String {
+    format!("refresh{name}")
+}
+
 /// A type that can generate GraphQL mutation names.
 pub(crate) trait ToPostgresMutationNames {
     /// Single create name (e.g. `createConcert`)
@@ -104,6 +134,20 @@ pub(crate) trait ToPostgresMutationNames {
     fn collection_delete(&self) -> String;
     /// Plural update name (e.g. `updateConcerts`)
     fn collection_update(&self) -> String;
+    /// Single upsert name (e.g. `upsertConcert`)
+    fn pk_upsert(&self) -> String;
+    /// Plural upsert name (e.g. `upsertConcerts`)
+    fn collection_upsert(&self) -> String;
+    /// Single upsert name for a unique constraint (e.g. `upsertConcertByTitle`)
+    fn unique_upsert(&self, constraint_name: &str) -> String;
+    /// Plural upsert name for a unique constraint (e.g. `upsertConcertsByTitle`)
+    fn collection_unique_upsert(&self, constraint_name: &str) -> String;
+    /// Single restore name for a soft-deleted entity (e.g. `restoreConcert`)
+    fn pk_restore(&self) -> String;
+    /// Plural restore name for soft-deleted entities (e.g. `restoreConcerts`)
+    fn collection_restore(&self) -> String;
+    /// Refresh name for a materialized view (e.g. `refreshConcertSummaries`)
+    fn collection_refresh(&self) -> String;
 }
 
 impl<T: ToPlural> ToPostgresMutationNames for T {
@@ -130,6 +174,66 @@ impl<T: ToPlural> ToPostgresMutationNames for T {
     fn collection_update(&self) -> String {
         to_update(&self.to_plural())
     }
+
+    fn pk_upsert(&self) -> String {
+        to_upsert(&self.to_singular())
+    }
+
+    fn collection_upsert(&self) -> String {
+        to_upsert(&self.to_plural())
+    }
+
+    fn unique_upsert(&self, constraint_name: &str) -> String {
+        format!(
+            "{}By{}",
+            self.pk_upsert(),
+            constraint_name.to_upper_camel_case()
+        )
+    }
+
+    fn collection_unique_upsert(&self, constraint_name: &str) -> String {
+        format!(
+            "{}By{}",
+            self.collection_upsert(),
+            constraint_name.to_upper_camel_case()
+        )
+    }
+
+    fn pk_restore(&self) -> String {
+        to_restore(&self.to_singular())
+    }
+
+    fn collection_restore(&self) -> String {
+        to_restore(&self.to_plural())
+    }
+
+    fn collection_refresh(&self) -> String {
+        to_refresh(&self.to_plural())
+    }
+}
+
+/// A type that can generate GraphQL subscription names.
+pub(crate) trait ToPostgresSubscriptionNames {
+    /// Created subscription name (e.g. `onConcertCreated`)
+    fn created_subscription(&self) -> String;
+    /// Updated subscription name (e.g. `onConcertUpdated`)
+    fn updated_subscription(&self) -> String;
+    /// Deleted subscription name (e.g. `onConcertDeleted`)
+    fn deleted_subscription(&self) -> String;
+}
+
+impl<T: ToPlural> ToPostgresSubscriptionNames for T {
+    fn created_subscription(&self) -> String {
+        format!("on{}Created", self.to_singular())
+    }
+
+    fn updated_subscription(&self) -> String {
+        format!("on{}Updated", self.to_singular())
+    }
+
+    fn deleted_subscription(&self) -> String {
+        format!("on{}Deleted", self.to_singular())
+    }
 }
 
 fn to_creation_type(name: &str) -> String {
@@ -144,6 +248,26 @@ fn to_reference_type(name: &str) -> String {
     format!("{name}ReferenceInput")
 }
 
+fn to_many_to_many_update_type(name: &str) -> String {
+    format!("{name}ManyToManyUpdateInput")
+}
+
+fn to_connection_type(name: &str) -> String {
+    format!("{name}Connection")
+}
+
+fn to_edge_type(name: &str) -> String {
+    format!("{name}Edge")
+}
+
+fn to_group_type(name: &str) -> String {
+    format!("{name}Group")
+}
+
+fn to_history_type(name: &str) -> String {
+    format!("{name}History")
+}
+
 /// A type that can generate GraphQL type names.
 pub(crate) trait ToPostgresTypeNames {
     /// Creation type name (e.g. `ConcertCreationInput`)
@@ -152,6 +276,16 @@ pub(crate) trait ToPostgresTypeNames {
     fn update_type(&self) -> String;
     /// Reference type name (e.g. `ConcertReferenceInput`)
     fn reference_type(&self) -> String;
+    /// Type name to connect and disconnect through a many-to-many relation (e.g. `ArtistManyToManyUpdateInput`)
+    fn many_to_many_update_type(&self) -> String;
+    /// Connection type name (e.g. `ConcertConnection`)
+    fn connection_type(&self) -> String;
+    /// Edge type name (e.g. `ConcertEdge`)
+    fn edge_type(&self) -> String;
+    /// Group type name (e.g. `ConcertGroup`)
+    fn group_type(&self) -> String;
+    /// History type name (e.g. `ConcertHistory`)
+    fn history_type(&self) -> String;
 }
 
 impl ToPostgresTypeNames for str {
@@ -166,6 +300,26 @@ impl ToPostgresTypeNames for str {
     fn reference_type(&self) -> String {
         to_reference_type(self)
     }
+
+    fn many_to_many_update_type(&self) -> String {
+        to_many_to_many_update_type(self)
+    }
+
+    fn connection_type(&self) -> String {
+        to_connection_type(self)
+    }
+
+    fn edge_type(&self) -> String {
+        to_edge_type(self)
+    }
+
+    fn group_type(&self) -> String {
+        to_group_type(self)
+    }
+
+    fn history_type(&self) -> String {
+        to_history_type(self)
+    }
 }
 
 impl<T: ToPlural> ToPostgresTypeNames for T {
@@ -180,6 +334,26 @@ impl<T: ToPlural> ToPostgresTypeNames for T {
     fn reference_type(&self) -> String {
         to_reference_type(&self.to_singular())
     }
+
+    fn many_to_many_update_type(&self) -> String {
+        to_many_to_many_update_type(&self.to_singular())
+    }
+
+    fn connection_type(&self) -> String {
+        to_connection_type(&self.to_singular())
+    }
+
+    fn edge_type(&self) -> String {
+        to_edge_type(&self.to_singular())
+    }
+
+    fn group_type(&self) -> String {
+        to_group_type(&self.to_singular())
+    }
+
+    fn history_type(&self) -> String {
+        to_history_type(&self.to_singular())
+    }
 }
 
 pub(crate) trait ToTableName {
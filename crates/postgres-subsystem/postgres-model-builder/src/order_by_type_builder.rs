@@ -15,13 +15,19 @@ use core_plugin_interface::core_model::{
 use exo_sql::{ColumnPathLink, Database};
 use postgres_model::{
     access::Access,
+    aggregate::AggregateType,
     order::OrderByParameter,
-    order::{OrderByParameterType, OrderByParameterTypeKind, OrderByParameterTypeWrapper},
+    order::{
+        aggregate_ordering_type_name, OrderByParameterType, OrderByParameterTypeKind,
+        OrderByParameterTypeWrapper,
+    },
+    relation::PostgresRelation,
     types::{EntityType, PostgresField, PostgresPrimitiveType, PostgresType},
 };
 
 use crate::{
-    resolved_builder::{ResolvedField, ResolvedTypeHint},
+    aggregate_type_builder::aggregate_type_name,
+    resolved_builder::{ResolvedField, ResolvedTypeHint, SEARCH_VECTOR_COLUMN_NAME},
     shallow::Shallow,
 };
 
@@ -52,6 +58,8 @@ impl Shallow for OrderByParameterTypeWrapper {
     }
 }
 
+const TEXT_SEARCH_ORDERING_TYPE_NAME: &str = "TextSearchOrdering";
+
 pub fn build_shallow(resolved_env: &ResolvedTypeEnv, building: &mut SystemContextBuilding) {
     let type_name = "Ordering".to_string();
     let primitive_type = OrderByParameterType {
@@ -71,6 +79,20 @@ pub fn build_shallow(resolved_env: &ResolvedTypeEnv, building: &mut SystemContex
         .order_by_types
         .add(&vector_ordering_type_name, vector_ordering_type);
 
+    let has_full_text_search = resolved_env
+        .resolved_types
+        .iter()
+        .any(|(_, typ)| matches!(typ, ResolvedType::Composite(c) if c.full_text_search.is_some()));
+    if has_full_text_search {
+        building.order_by_types.add(
+            TEXT_SEARCH_ORDERING_TYPE_NAME,
+            OrderByParameterType {
+                name: TEXT_SEARCH_ORDERING_TYPE_NAME.to_string(),
+                kind: OrderByParameterTypeKind::TextSearch,
+            },
+        );
+    }
+
     for (_, typ) in resolved_env.resolved_types.iter() {
         if let ResolvedType::Composite(ResolvedCompositeType { .. }) = typ {
             let shallow_type = create_shallow_type(typ);
@@ -81,6 +103,10 @@ pub fn build_shallow(resolved_env: &ResolvedTypeEnv, building: &mut SystemContex
 }
 
 pub fn build_expanded(resolved_env: &ResolvedTypeEnv, building: &mut SystemContextBuilding) {
+    // The aggregate ordering types must exist before expanding the entity ordering types, which
+    // refer to them for one-to-many relations
+    build_aggregate_types(building);
+
     for (_, entity_type) in building.entity_types.iter() {
         let param_type_name = get_parameter_type_name(&entity_type.name, false);
         let existing_param_id = building.order_by_types.get_id(&param_type_name);
@@ -97,6 +123,133 @@ pub fn build_expanded(resolved_env: &ResolvedTypeEnv, building: &mut SystemConte
     }
 }
 
+/// Build the types to order by aggregates over one-to-many relations: one for each entity type
+/// (such as `ConcertAggOrdering`) and one for each scalar type with aggregated fields (such as
+/// `IntAggOrdering`).
+fn build_aggregate_types(building: &mut SystemContextBuilding) {
+    let entity_aggregate_types: Vec<_> = building
+        .entity_types
+        .iter()
+        .map(|(entity_type_id, entity_type)| {
+            let parameters = entity_type
+                .fields
+                .iter()
+                .filter_map(|field| {
+                    aggregate_field_param(
+                        field,
+                        &building.aggregate_types,
+                        &mut building.order_by_types,
+                    )
+                })
+                .collect();
+
+            let type_name = aggregate_ordering_type_name(&aggregate_type_name(&entity_type.name));
+
+            OrderByParameterType {
+                name: type_name,
+                kind: OrderByParameterTypeKind::Aggregate {
+                    entity_type_id,
+                    parameters,
+                },
+            }
+        })
+        .collect();
+
+    for aggregate_type in entity_aggregate_types {
+        let type_name = aggregate_type.name.clone();
+        building.order_by_types.add(&type_name, aggregate_type);
+    }
+}
+
+/// The parameter to order by aggregates of a scalar field such as `price: IntAggOrdering`
+fn aggregate_field_param(
+    field: &PostgresField<EntityType>,
+    aggregate_types: &MappedArena<AggregateType>,
+    order_by_types: &mut MappedArena<OrderByParameterType>,
+) -> Option<OrderByParameter> {
+    let column_id = match &field.relation {
+        PostgresRelation::Pk { column_id } | PostgresRelation::Scalar { column_id } => *column_id,
+        _ => return None,
+    };
+
+    // Leave out arrays and vectors (aggregates of which can't be meaningfully ordered)
+    let FieldType::Plain(field_type) = field.typ.base_type() else {
+        return None;
+    };
+    if field_type.type_name == "Vector" {
+        return None;
+    }
+
+    let scalar_aggregate_type =
+        aggregate_types.get_by_key(&aggregate_type_name(&field_type.type_name))?;
+    let type_name = aggregate_ordering_type_name(&scalar_aggregate_type.name);
+
+    let type_id = match order_by_types.get_id(&type_name) {
+        Some(type_id) => type_id,
+        None => order_by_types.add(
+            &type_name,
+            OrderByParameterType {
+                name: type_name.clone(),
+                kind: OrderByParameterTypeKind::ScalarAggregate {
+                    functions: scalar_aggregate_type
+                        .fields
+                        .iter()
+                        .map(|field| field.name.clone())
+                        .collect(),
+                },
+            },
+        ),
+    };
+
+    Some(OrderByParameter {
+        name: field.name.clone(),
+        typ: FieldType::Optional(Box::new(FieldType::Plain(OrderByParameterTypeWrapper {
+            name: type_name,
+            type_id,
+        }))),
+        column_path_link: Some(ColumnPathLink::Leaf(column_id)),
+        access: Some(field.access.clone()),
+        vector_distance_function: None,
+    })
+}
+
+/// The parameter to order by aggregates over a one-to-many relation such as `concertsAgg:
+/// ConcertAggOrdering` (named after the aggregate field such as `concertsAgg { price { max } }`).
+///
+/// We leave out self-referencing relations (such as `Employee.reports`), since the aggregate
+/// subselect can't tell apart the rows of the same table.
+fn aggregate_relation_param(
+    entity_field: &PostgresField<EntityType>,
+    order_by_types: &MappedArena<OrderByParameterType>,
+    database: &Database,
+) -> Option<OrderByParameter> {
+    let PostgresRelation::OneToMany(relation) = &entity_field.relation else {
+        return None;
+    };
+
+    let column_path_link = relation.column_path_link(database);
+    match &column_path_link {
+        ColumnPathLink::Relation(link) if link.self_table_id() != link.linked_table_id() => {}
+        _ => return None,
+    }
+
+    let type_name = aggregate_ordering_type_name(&aggregate_type_name(
+        &entity_field.typ.innermost().type_name,
+    ));
+    let type_id = order_by_types.get_id(&type_name)?;
+
+    Some(OrderByParameter {
+        name: format!("{}Agg", entity_field.name),
+        typ: FieldType::Optional(Box::new(FieldType::Plain(OrderByParameterTypeWrapper {
+            name: type_name,
+            type_id,
+        }))),
+        column_path_link: Some(column_path_link),
+        access: Some(entity_field.access.clone()),
+        vector_distance_function: None,
+    })
+}
+
 fn get_parameter_type_name(entity_type_name: &str, is_primitive: bool) -> String {
     if entity_type_name == "Vector" {
         "VectorOrdering".to_string()
@@ -141,11 +294,45 @@ fn expand_type(
                 &building.database,
             )
         })
+        .chain(search_rank_param(
+            resolved_type.as_composite(),
+            entity_type,
+            building,
+        ))
         .collect();
 
     OrderByParameterTypeKind::Composite { parameters }
 }
 
+/// The `searchRank` parameter (such as `{searchRank: {query: "rust async"}}`) for a type with the
+/// `@fullTextSearch` annotation, which orders by how well the type's search vector column matches
+/// the query.
+fn search_rank_param(
+    resolved_type: &ResolvedCompositeType,
+    entity_type: &EntityType,
+    building: &SystemContextBuilding,
+) -> Option<OrderByParameter> {
+    resolved_type.full_text_search.as_ref()?;
+
+    let column_id = building
+        .database
+        .get_column_id(entity_type.table_id, SEARCH_VECTOR_COLUMN_NAME)?;
+
+    Some(OrderByParameter {
+        name: "searchRank".to_string(),
+        typ: FieldType::Optional(Box::new(FieldType::Plain(OrderByParameterTypeWrapper {
+            name: TEXT_SEARCH_ORDERING_TYPE_NAME.to_string(),
+            type_id: building
+                .order_by_types
+                .get_id(TEXT_SEARCH_ORDERING_TYPE_NAME)
+                .unwrap(),
+        }))),
+        column_path_link: Some(ColumnPathLink::Leaf(column_id)),
+        access: None,
+        vector_distance_function: None,
+    })
+}
+
 fn new_param(
     name: &str,
     entity_type_name: &str,
@@ -201,13 +388,16 @@ pub fn new_field_param(
     order_by_types: &MappedArena<OrderByParameterType>,
     database: &Database,
 ) -> Option<OrderByParameter> {
-    // If the field has one-to-many relationship, we cannot order by it. For example, it doesn't
-    // make sense to order venues by concert id (assuming venue hosts multiple concerts)
+    // If the field has one-to-many relationship, we cannot order by it directly. For example, it
+    // doesn't make sense to order venues by concert id (assuming venue hosts multiple concerts).
+    // However, we can order by an aggregate such as the number of concerts.
     match &entity_field.typ {
-        FieldType::List(_) => return None,
+        FieldType::List(_) => {
+            return aggregate_relation_param(entity_field, order_by_types, database)
+        }
         FieldType::Optional(inner) => {
             if matches!(inner.as_ref(), FieldType::List(_)) {
-                return None;
+                return aggregate_relation_param(entity_field, order_by_types, database);
             }
         }
         FieldType::Plain(_) => (),
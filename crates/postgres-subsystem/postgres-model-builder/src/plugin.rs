@@ -113,6 +113,36 @@ impl SubsystemBuilder for PostgresSubsystemBuilder {
                     ]),
                 },
             ),
+            (
+                "softDelete",
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Type],
+                    no_params: false,
+                    single_params: false,
+                    mapped_params: Some(&[MappedAnnotationParamSpec {
+                        name: "field",
+                        optional: false,
+                    }]),
+                },
+            ),
+            (
+                "fullTextSearch",
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Type],
+                    no_params: false,
+                    single_params: false,
+                    mapped_params: Some(&[
+                        MappedAnnotationParamSpec {
+                            name: "fields",
+                            optional: false,
+                        },
+                        MappedAnnotationParamSpec {
+                            name: "language",
+                            optional: true,
+                        },
+                    ]),
+                },
+            ),
             (
                 "scale",
                 AnnotationSpec {
@@ -203,6 +233,69 @@ impl SubsystemBuilder for PostgresSubsystemBuilder {
                     ]),
                 },
             ),
+            (
+                "view",
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Type],
+                    no_params: true,
+                    single_params: true,
+                    mapped_params: Some(&[
+                        MappedAnnotationParamSpec {
+                            name: "name",
+                            optional: true,
+                        },
+                        MappedAnnotationParamSpec {
+                            name: "schema",
+                            optional: true,
+                        },
+                    ]),
+                },
+            ),
+            (
+                "materializedView",
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Type],
+                    no_params: true,
+                    single_params: true,
+                    mapped_params: Some(&[
+                        MappedAnnotationParamSpec {
+                            name: "name",
+                            optional: true,
+                        },
+                        MappedAnnotationParamSpec {
+                            name: "schema",
+                            optional: true,
+                        },
+                    ]),
+                },
+            ),
+            (
+                "subscribable",
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Type],
+                    no_params: true,
+                    single_params: false,
+                    mapped_params: None,
+                },
+            ),
+            (
+                "audited",
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Type],
+                    no_params: true,
+                    single_params: false,
+                    mapped_params: Some(&[
+                        MappedAnnotationParamSpec {
+                            name: "user",
+                            optional: true,
+                        },
+                        MappedAnnotationParamSpec {
+                            name: "access",
+                            optional: true,
+                        },
+                    ]),
+                },
+            ),
             (
                 "unique",
                 AnnotationSpec {
@@ -230,6 +323,15 @@ impl SubsystemBuilder for PostgresSubsystemBuilder {
                     mapped_params: None,
                 },
             ),
+            (
+                "version",
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Field],
+                    no_params: true,
+                    single_params: false,
+                    mapped_params: None,
+                },
+            ),
             (
                 "update",
                 AnnotationSpec {
@@ -239,6 +341,27 @@ impl SubsystemBuilder for PostgresSubsystemBuilder {
                     mapped_params: None,
                 },
             ),
+            (
+                "function", // Postgres function backing a query or mutation
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Method],
+                    no_params: false,
+                    single_params: true,
+                    mapped_params: None,
+                },
+            ),
+            (
+                "manyToMany", // linking table for an implicit many-to-many relation
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Field],
+                    no_params: false,
+                    single_params: true,
+                    mapped_params: Some(&[MappedAnnotationParamSpec {
+                        name: "table",
+                        optional: false,
+                    }]),
+                },
+            ),
         ]
     }
 
@@ -267,20 +390,50 @@ impl SubsystemBuilder for PostgresSubsystemBuilder {
                     .iter()
                     .map(|(_, q)| q.name.clone());
 
+                let connection_query_names = subsystem
+                    .connection_queries
+                    .iter()
+                    .map(|(_, q)| q.name.clone());
+
                 let aggregate_query_names = subsystem
                     .aggregate_queries
                     .iter()
                     .map(|(_, q)| q.name.clone());
 
+                let group_by_query_names = subsystem
+                    .group_by_queries
+                    .iter()
+                    .map(|(_, q)| q.name.clone());
+
+                let history_query_names = subsystem
+                    .history_queries
+                    .iter()
+                    .map(|(_, q)| q.name.clone());
+
+                let function_query_names = subsystem
+                    .function_queries
+                    .iter()
+                    .map(|(_, q)| q.name.clone());
+
                 pk_query_names
                     .chain(collection_query_names)
+                    .chain(connection_query_names)
                     .chain(aggregate_query_names)
+                    .chain(group_by_query_names)
+                    .chain(history_query_names)
+                    .chain(function_query_names)
                     .collect()
             },
             mutation_names: subsystem
                 .mutations
                 .iter()
                 .map(|(_, q)| q.name.clone())
+                .chain(
+                    subsystem
+                        .function_mutations
+                        .iter()
+                        .map(|(_, q)| q.name.clone()),
+                )
                 .collect(),
             interceptions: vec![],
         }))
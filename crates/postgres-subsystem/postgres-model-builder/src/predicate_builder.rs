@@ -11,6 +11,7 @@ use core_plugin_interface::core_model::{
     mapped_arena::{MappedArena, SerializableSlabIndex},
     types::{FieldType, Named},
 };
+use exo_sql::ColumnPathLink;
 use postgres_model::{
     predicate::PredicateParameterTypeWrapper,
     relation::PostgresRelation,
@@ -18,7 +19,11 @@ use postgres_model::{
 };
 use std::collections::HashMap;
 
-use crate::{resolved_builder::ResolvedTypeHint, shallow::Shallow, type_builder::ResolvedTypeEnv};
+use crate::{
+    resolved_builder::{ResolvedFieldType, ResolvedTypeHint, SEARCH_VECTOR_COLUMN_NAME},
+    shallow::Shallow,
+    type_builder::ResolvedTypeEnv,
+};
 
 use super::{
     resolved_builder::{ResolvedCompositeType, ResolvedType},
@@ -38,6 +43,7 @@ impl Shallow for PredicateParameter {
             column_path_link: None,
             access: None,
             vector_distance_function: None,
+            foreign_column_path_link: None,
         }
     }
 }
@@ -96,6 +102,36 @@ pub fn build_shallow(types: &MappedArena<ResolvedType>, building: &mut SystemCon
                         .predicate_types
                         .add(&shallow_type.name.clone(), shallow_type);
                 }
+                // Filter types for (single-dimensional) array fields
+                for field in c.fields.iter() {
+                    if let Some(element_type_name) = array_element_type_name(&field.typ) {
+                        let type_name = get_array_filter_type_name(element_type_name);
+                        if building.predicate_types.get_id(&type_name).is_none() {
+                            let shallow_type = PredicateParameterType {
+                                name: type_name,
+                                kind: PredicateParameterTypeKind::ImplicitEqual, // Will be set to the correct value in expand_array_type
+                            };
+                            building
+                                .predicate_types
+                                .add(&shallow_type.name.clone(), shallow_type);
+                        }
+                    }
+                }
+                // Filter types for the other side of many-to-many fields
+                for field in c.fields.iter() {
+                    if field.many_to_many_table.is_some() {
+                        let type_name = get_many_to_many_filter_type_name(field.typ.name());
+                        if building.predicate_types.get_id(&type_name).is_none() {
+                            let shallow_type = PredicateParameterType {
+                                name: type_name,
+                                kind: PredicateParameterTypeKind::ImplicitEqual, // Will be set to the correct value in expand_many_to_many_type
+                            };
+                            building
+                                .predicate_types
+                                .add(&shallow_type.name.clone(), shallow_type);
+                        }
+                    }
+                }
             }
         }
     }
@@ -106,6 +142,30 @@ pub fn build_shallow(types: &MappedArena<ResolvedType>, building: &mut SystemCon
             kind: PredicateParameterTypeKind::Vector,
         },
     );
+
+    let has_full_text_search = types
+        .iter()
+        .any(|(_, typ)| matches!(typ, ResolvedType::Composite(c) if c.full_text_search.is_some()));
+    if has_full_text_search {
+        for (type_name, kind) in [
+            (
+                TEXT_SEARCH_FILTER_TYPE_NAME,
+                PredicateParameterTypeKind::TextSearch,
+            ),
+            (
+                TEXT_SEARCH_MODE_TYPE_NAME,
+                PredicateParameterTypeKind::TextSearchMode,
+            ),
+        ] {
+            building.predicate_types.add(
+                type_name,
+                PredicateParameterType {
+                    name: type_name.to_string(),
+                    kind,
+                },
+            );
+        }
+    }
 }
 
 pub fn build_expanded(resolved_env: &ResolvedTypeEnv, building: &mut SystemContextBuilding) {
@@ -115,6 +175,12 @@ pub fn build_expanded(resolved_env: &ResolvedTypeEnv, building: &mut SystemConte
 
         let new_kind = expand_primitive_type(primitive_type, building);
         building.predicate_types[existing_param_id.unwrap()].kind = new_kind;
+
+        let array_param_type_name = get_array_filter_type_name(&primitive_type.name);
+        if let Some(existing_param_id) = building.predicate_types.get_id(&array_param_type_name) {
+            let new_kind = expand_array_type(primitive_type, building);
+            building.predicate_types[existing_param_id].kind = new_kind;
+        }
     }
 
     for (_, entity_type) in building.entity_types.iter() {
@@ -138,16 +204,70 @@ pub fn build_expanded(resolved_env: &ResolvedTypeEnv, building: &mut SystemConte
             building.predicate_types[existing_param_id.unwrap()].kind = new_kind;
         }
     }
+
+    for (_, entity_type) in building.entity_types.iter() {
+        for field in entity_type.fields.iter() {
+            if let PostgresRelation::ManyToMany { .. } = field.relation {
+                let param_type_name = get_many_to_many_filter_type_name(field.typ.name());
+                let existing_param_id = building.predicate_types.get_id(&param_type_name);
+                let new_kind = expand_many_to_many_type(field.typ.name(), building);
+                building.predicate_types[existing_param_id.unwrap()].kind = new_kind;
+            }
+        }
+    }
 }
 
+pub const TEXT_SEARCH_FILTER_TYPE_NAME: &str = "TextSearchFilter";
+pub const TEXT_SEARCH_MODE_TYPE_NAME: &str = "TextSearchMode";
+
 pub fn get_filter_type_name(type_name: &str) -> String {
     format!("{type_name}Filter")
 }
 
+pub fn get_array_filter_type_name(element_type_name: &str) -> String {
+    format!("{element_type_name}ArrayFilter")
+}
+
 pub fn get_unique_filter_type_name(type_name: &str) -> String {
     format!("{type_name}UniqueFilter")
 }
 
+pub fn get_many_to_many_filter_type_name(type_name: &str) -> String {
+    format!("{type_name}ManyToManyFilter")
+}
+
+/// The `isNull` operator, offered by every filter type with operators (scalar types without
+/// operators, such as `Blob`, use implicit equality such as `{data: "..."}`). An `isNull: null`
+/// argument doesn't filter anything (like omitting any other operator).
+fn is_null_parameter(building: &SystemContextBuilding) -> PredicateParameter {
+    PredicateParameter {
+        name: "isNull".to_string(),
+        typ: FieldType::Optional(Box::new(FieldType::Plain(PredicateParameterTypeWrapper {
+            name: "Boolean".to_string(),
+            type_id: building.predicate_types.get_id("Boolean").unwrap(),
+        }))),
+        column_path_link: None,
+        access: None,
+        vector_distance_function: None,
+        foreign_column_path_link: None,
+    }
+}
+
+/// The element type of a single-dimensional array of a primitive type (such as `String` for
+/// `Array<String>`). Multi-dimensional arrays don't support any filters.
+fn array_element_type_name(field_type: &FieldType<ResolvedFieldType>) -> Option<&str> {
+    match field_type.base_type() {
+        FieldType::List(element_type) => match element_type.as_ref() {
+            FieldType::Plain(ResolvedFieldType {
+                type_name,
+                is_primitive: true,
+            }) => Some(type_name),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
 fn expand_primitive_type(
     typ: &PostgresPrimitiveType,
     building: &SystemContextBuilding,
@@ -165,11 +285,7 @@ fn expand_entity_type(
     let field_params: Vec<PredicateParameter> = entity_type
         .fields
         .iter()
-        .map(|field| {
-            let param_type_name = get_filter_type_name(field.typ.name());
-
-            let column_path_link = Some(field.relation.column_path_link(&building.database));
-
+        .filter_map(|field| {
             let resolved_field = resolved_type
                 .as_composite()
                 .fields
@@ -177,7 +293,27 @@ fn expand_entity_type(
                 .find(|f| f.name == field.name)
                 .unwrap();
 
-            PredicateParameter {
+            let (param_type_name, foreign_column_path_link) = match &field.relation {
+                PostgresRelation::ManyToMany(relation) => (
+                    get_many_to_many_filter_type_name(field.typ.name()),
+                    Some(relation.foreign_column_path_link(&building.database)),
+                ),
+                PostgresRelation::Scalar { .. }
+                    if matches!(resolved_field.typ.base_type(), FieldType::List(_)) =>
+                {
+                    match array_element_type_name(&resolved_field.typ) {
+                        Some(element_type_name) => {
+                            (get_array_filter_type_name(element_type_name), None)
+                        }
+                        None => return None, // Multi-dimensional arrays don't support any filters
+                    }
+                }
+                _ => (get_filter_type_name(field.typ.name()), None),
+            };
+
+            let column_path_link = Some(field.relation.column_path_link(&building.database));
+
+            Some(PredicateParameter {
                 name: field.name.to_string(),
                 typ: FieldType::Optional(Box::new(FieldType::Plain(
                     PredicateParameterTypeWrapper {
@@ -186,6 +322,7 @@ fn expand_entity_type(
                     },
                 ))),
                 column_path_link,
+                foreign_column_path_link,
                 access: Some(field.access.clone()),
                 vector_distance_function: resolved_field.type_hint.as_ref().and_then(|hint| {
                     match hint {
@@ -195,8 +332,13 @@ fn expand_entity_type(
                         _ => None,
                     }
                 }),
-            }
+            })
         })
+        .chain(search_parameter(
+            resolved_type.as_composite(),
+            entity_type,
+            building,
+        ))
         .collect();
 
     #[derive(Debug, PartialEq, Eq)]
@@ -235,6 +377,7 @@ fn expand_entity_type(
                 column_path_link: None,
                 access: None,
                 vector_distance_function: None,
+                foreign_column_path_link: None,
             }
         })
         .collect();
@@ -242,9 +385,110 @@ fn expand_entity_type(
     PredicateParameterTypeKind::Composite {
         field_params,
         logical_op_params,
+        is_null_param: is_null_parameter(building),
     }
 }
 
+/// The `search` parameter (such as `{search: {query: "rust async"}}`) for a type with the
+/// `@fullTextSearch` annotation, which matches against the type's search vector column.
+fn search_parameter(
+    resolved_type: &ResolvedCompositeType,
+    entity_type: &EntityType,
+    building: &SystemContextBuilding,
+) -> Option<PredicateParameter> {
+    resolved_type.full_text_search.as_ref()?;
+
+    let column_id = building
+        .database
+        .get_column_id(entity_type.table_id, SEARCH_VECTOR_COLUMN_NAME)?;
+
+    Some(PredicateParameter {
+        name: "search".to_string(),
+        typ: FieldType::Optional(Box::new(FieldType::Plain(PredicateParameterTypeWrapper {
+            name: TEXT_SEARCH_FILTER_TYPE_NAME.to_string(),
+            type_id: building
+                .predicate_types
+                .get_id(TEXT_SEARCH_FILTER_TYPE_NAME)
+                .unwrap(),
+        }))),
+        column_path_link: Some(ColumnPathLink::Leaf(column_id)),
+        // The searched fields may not have their own access control (see `build_full_text_search`)
+        access: None,
+        vector_distance_function: None,
+        foreign_column_path_link: None,
+    })
+}
+
+/// Expand the filter for an array field such as `Concert.tags` (of type `Array<String>`), which
+/// allows filtering concerts by the elements or the length of their tags (or whether they have
+/// any, through `isNull`).
+fn expand_array_type(
+    element_type: &PostgresPrimitiveType,
+    building: &SystemContextBuilding,
+) -> PredicateParameterTypeKind {
+    let param_type = |type_name: &str| {
+        FieldType::Plain(PredicateParameterTypeWrapper {
+            name: type_name.to_string(),
+            type_id: building
+                .predicate_types
+                .get_id(type_name)
+                .unwrap_or_else(|| panic!("Could not find predicate type '{type_name}'")),
+        })
+    };
+    let elements_type = || FieldType::List(Box::new(param_type(&element_type.name)));
+
+    let parameters = [
+        ("contains", elements_type()),
+        ("containedBy", elements_type()),
+        ("overlaps", elements_type()),
+        ("anyEq", param_type(&element_type.name)),
+        ("length", param_type(&get_filter_type_name("Int"))),
+    ]
+    .into_iter()
+    .map(|(name, typ)| PredicateParameter {
+        name: name.to_string(),
+        typ: FieldType::Optional(Box::new(typ)),
+        column_path_link: None,
+        foreign_column_path_link: None,
+        access: None,
+        vector_distance_function: None,
+    })
+    .chain(std::iter::once(is_null_parameter(building)))
+    .collect();
+
+    PredicateParameterTypeKind::Operator(parameters)
+}
+
+/// Expand the filter for a many-to-many field such as `Concert.artists`, which allows filtering
+/// concerts with `some`, `every`, or `none` of their artists matching the given filter.
+fn expand_many_to_many_type(
+    foreign_type_name: &str,
+    building: &SystemContextBuilding,
+) -> PredicateParameterTypeKind {
+    let param_type_name = get_filter_type_name(foreign_type_name);
+    let param_type_id = building
+        .predicate_types
+        .get_id(&param_type_name)
+        .unwrap_or_else(|| panic!("Could not find predicate type '{param_type_name}'"));
+
+    let parameters = ["some", "every", "none"]
+        .into_iter()
+        .map(|name| PredicateParameter {
+            name: name.to_string(),
+            typ: FieldType::Optional(Box::new(FieldType::Plain(PredicateParameterTypeWrapper {
+                name: param_type_name.clone(),
+                type_id: param_type_id,
+            }))),
+            column_path_link: None,
+            foreign_column_path_link: None,
+            access: None,
+            vector_distance_function: None,
+        })
+        .collect();
+
+    PredicateParameterTypeKind::ManyToMany(parameters)
+}
+
 fn expand_unique_type(
     entity_type: &EntityType,
     building: &SystemContextBuilding,
@@ -271,6 +515,7 @@ fn expand_unique_type(
                     access: Some(field.access.clone()),
                     column_path_link: None,
                     vector_distance_function: None,
+                    foreign_column_path_link: None,
                 })
             }
             _ => None,
@@ -288,7 +533,8 @@ lazy_static! {
 
         let numeric_operators = Some(vec![
             "eq", "neq",
-            "lt", "lte", "gt", "gte"
+            "lt", "lte", "gt", "gte",
+            "in", "notIn"
         ]);
 
         supported_operators.insert("Int", numeric_operators.clone());
@@ -300,6 +546,7 @@ lazy_static! {
             Some(vec![
                 "eq", "neq",
                 "lt", "lte", "gt", "gte",
+                "in", "notIn",
                 "like", "ilike", "startsWith", "endsWith"
             ])
         );
@@ -311,7 +558,8 @@ lazy_static! {
 
         let datetime_operators = Some(vec![
             "eq", "neq",
-            "lt", "lte", "gt", "gte"
+            "lt", "lte", "gt", "gte",
+            "in", "notIn"
         ]);
 
         supported_operators.insert("LocalTime", datetime_operators.clone());
@@ -331,7 +579,7 @@ lazy_static! {
 
         supported_operators.insert(
             "Uuid",
-            Some(vec!["eq", "neq"])
+            Some(vec!["eq", "neq", "in", "notIn"])
         );
 
         supported_operators.insert("Vector", Some(vec!["similar", "eq", "neq"]));
@@ -344,6 +592,9 @@ lazy_static! {
     };
 }
 
+/// The operators allowed for every enum type
+const ENUM_OPERATORS: [&str; 4] = ["eq", "neq", "in", "notIn"];
+
 fn create_operator_filter_type_kind(
     primitive_type: &PostgresPrimitiveType,
     building: &SystemContextBuilding,
@@ -357,18 +608,38 @@ fn create_operator_filter_type_kind(
         };
         let predicate_param_type_id = building.predicate_types.get_id(operand_type).unwrap();
 
+        let operand_field_type = FieldType::Plain(PredicateParameterTypeWrapper {
+            name: operand_type.to_owned(),
+            type_id: predicate_param_type_id,
+        });
+
         PredicateParameter {
             name: operator.to_string(),
-            typ: FieldType::Optional(Box::new(FieldType::Plain(PredicateParameterTypeWrapper {
-                name: operand_type.to_owned(),
-                type_id: predicate_param_type_id,
-            }))),
+            // The `in` and `notIn` operators take a list of values
+            typ: FieldType::Optional(Box::new(if operator == &"in" || operator == &"notIn" {
+                FieldType::List(Box::new(operand_field_type))
+            } else {
+                operand_field_type
+            })),
             column_path_link: None,
             access: None,
             vector_distance_function: None,
+            foreign_column_path_link: None,
         }
     };
 
+    let with_null_check = |mut parameters: Vec<PredicateParameter>| {
+        parameters.push(is_null_parameter(building));
+        parameters
+    };
+
+    // enums are user-declared, so can't be in the (type, operations) table
+    if primitive_type.enum_values.is_some() {
+        return PredicateParameterTypeKind::Operator(with_null_check(
+            ENUM_OPERATORS.iter().map(parameter_constructor).collect(),
+        ));
+    }
+
     // look up type in (type, operations) table
     if let Some(maybe_operators) = TYPE_OPERATORS.get(&primitive_type.name as &str) {
         if let Some(operators) = maybe_operators {
@@ -376,7 +647,7 @@ fn create_operator_filter_type_kind(
             let parameters: Vec<PredicateParameter> =
                 operators.iter().map(parameter_constructor).collect();
 
-            PredicateParameterTypeKind::Operator(parameters)
+            PredicateParameterTypeKind::Operator(with_null_check(parameters))
         } else {
             // type supports no specific operations, assume implicit equals
             PredicateParameterTypeKind::ImplicitEqual
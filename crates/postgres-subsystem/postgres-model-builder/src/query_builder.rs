@@ -14,14 +14,21 @@ use core_plugin_interface::core_model::{
 
 use exo_sql::Database;
 use postgres_model::{
+    audit::{HistoryQuery, HistoryQueryParameters, HistoryType},
+    connection::{ConnectionType, CursorParameter, CursorParameterType},
+    group_by::{GroupByParameter, GroupByParameterType, GroupKeyField, GroupType},
     limit_offset::{LimitParameter, LimitParameterType, OffsetParameter, OffsetParameterType},
     order::{OrderByParameter, OrderByParameterType},
     predicate::{PredicateParameter, PredicateParameterType, PredicateParameterTypeWrapper},
     query::{
         AggregateQuery, AggregateQueryParameters, CollectionQuery, CollectionQueryParameters,
-        PkQuery, PkQueryParameters, UniqueQuery, UniqueQueryParameters,
+        ConnectionQuery, ConnectionQueryParameters, GroupByQuery, GroupByQueryParameters, PkQuery,
+        PkQueryParameters, UniqueQuery, UniqueQueryParameters,
     },
     relation::PostgresRelation,
+    soft_delete::{
+        IncludeDeletedParameter, IncludeDeletedParameterType, INCLUDE_DELETED_PARAM_NAME,
+    },
     types::{EntityType, PostgresField, PostgresPrimitiveType},
 };
 
@@ -31,8 +38,10 @@ use crate::{
 };
 
 use super::{
-    naming::ToPostgresQueryName, order_by_type_builder, predicate_builder,
-    resolved_builder::ResolvedType, system_builder::SystemContextBuilding,
+    naming::{ToPostgresQueryName, ToPostgresTypeNames},
+    order_by_type_builder, predicate_builder,
+    resolved_builder::ResolvedType,
+    system_builder::SystemContextBuilding,
 };
 
 pub fn build_shallow(types: &MappedArena<ResolvedType>, building: &mut SystemContextBuilding) {
@@ -41,6 +50,7 @@ pub fn build_shallow(types: &MappedArena<ResolvedType>, building: &mut SystemCon
             let entity_type_id = building.get_entity_type_id(c.name.as_str()).unwrap();
             let shallow_query = shallow_pk_query(entity_type_id, c);
             let collection_query = shallow_collection_query(entity_type_id, c);
+            let connection_query = shallow_connection_query(entity_type_id, c);
             let aggregate_query = shallow_aggregate_query(entity_type_id, c);
             let unique_queries = shallow_unique_queries(entity_type_id, c);
 
@@ -50,14 +60,54 @@ pub fn build_shallow(types: &MappedArena<ResolvedType>, building: &mut SystemCon
             building
                 .collection_queries
                 .add(&collection_query.name.to_owned(), collection_query);
+            building
+                .connection_queries
+                .add(&connection_query.name.to_owned(), connection_query);
+            building.connection_types.add(
+                &c.connection_type(),
+                ConnectionType {
+                    name: c.connection_type(),
+                    edge_type_name: c.edge_type(),
+                    node_type_id: entity_type_id,
+                },
+            );
             building
                 .aggregate_queries
                 .add(&aggregate_query.name.to_owned(), aggregate_query);
+            let group_type = GroupType {
+                name: c.group_type(),
+                entity_type_id,
+                key_fields: group_key_fields(c),
+                aggregate_type_id: building
+                    .aggregate_types
+                    .get_id(&aggregate_type_name(&c.name))
+                    .unwrap(),
+            };
+            let group_by_query = shallow_group_by_query(entity_type_id, c, &group_type);
+            building
+                .group_by_queries
+                .add(&group_by_query.name.to_owned(), group_by_query);
+            building
+                .group_types
+                .add(&group_type.name.clone(), group_type);
             for unique_query in unique_queries {
                 building
                     .unique_queries
                     .add(&unique_query.name.to_owned(), unique_query);
             }
+            if c.audit.is_some() {
+                let history_query = shallow_history_query(entity_type_id, c);
+                building
+                    .history_queries
+                    .add(&history_query.name.to_owned(), history_query);
+                building.history_types.add(
+                    &c.history_type(),
+                    HistoryType {
+                        name: c.history_type(),
+                        entity_type_id,
+                    },
+                );
+            }
         }
     }
 }
@@ -66,6 +116,7 @@ pub fn build_expanded(resolved_env: &ResolvedTypeEnv, building: &mut SystemConte
     for (_, entity_type) in building.entity_types.iter() {
         expand_pk_query(
             entity_type,
+            &building.primitive_types,
             &building.predicate_types,
             &mut building.pk_queries,
             &building.database,
@@ -77,18 +128,40 @@ pub fn build_expanded(resolved_env: &ResolvedTypeEnv, building: &mut SystemConte
             &building.order_by_types,
             &mut building.collection_queries,
         );
+        expand_connection_query(
+            entity_type,
+            &building.primitive_types,
+            &building.predicate_types,
+            &building.order_by_types,
+            &mut building.connection_queries,
+        );
         expand_aggregate_query(
             entity_type,
             &building.predicate_types,
             &mut building.aggregate_queries,
         );
+        expand_group_by_query(
+            entity_type,
+            &building.primitive_types,
+            &building.predicate_types,
+            &mut building.group_by_queries,
+        );
         expand_unique_queries(
             entity_type,
+            &building.primitive_types,
             &building.predicate_types,
             &mut building.unique_queries,
             resolved_env,
             &building.database,
         );
+        if entity_type.audit.is_some() {
+            expand_history_query(
+                entity_type,
+                &building.predicate_types,
+                &mut building.history_queries,
+                &building.database,
+            );
+        }
     }
 }
 
@@ -100,7 +173,8 @@ fn shallow_pk_query(
     PkQuery {
         name: operation_name,
         parameters: PkQueryParameters {
-            predicate_param: PredicateParameter::shallow(),
+            predicate_params: vec![],
+            include_deleted_param: None,
         },
         return_type: OperationReturnType::Optional(Box::new(OperationReturnType::Plain(
             BaseOperationReturnType {
@@ -113,23 +187,61 @@ fn shallow_pk_query(
 
 fn expand_pk_query(
     entity_type: &EntityType,
+    primitive_types: &MappedArena<PostgresPrimitiveType>,
     predicate_types: &MappedArena<PredicateParameterType>,
     pk_queries: &mut MappedArena<PkQuery>,
     database: &Database,
 ) {
     let operation_name = entity_type.pk_query();
     let existing_query = &mut pk_queries.get_by_key_mut(&operation_name).unwrap();
-    existing_query.parameters.predicate_param =
-        pk_predicate_param(entity_type, predicate_types, database);
+    existing_query.parameters.predicate_params =
+        pk_predicate_params(entity_type, predicate_types, database);
+    existing_query.parameters.include_deleted_param =
+        include_deleted_param(entity_type, primitive_types);
 }
 
-pub fn pk_predicate_param(
+fn shallow_history_query(
+    entity_type_id: SerializableSlabIndex<EntityType>,
+    typ: &ResolvedCompositeType,
+) -> HistoryQuery {
+    HistoryQuery {
+        name: typ.history_query(),
+        parameters: HistoryQueryParameters {
+            predicate_params: vec![],
+        },
+        return_type: OperationReturnType::List(Box::new(OperationReturnType::Plain(
+            BaseOperationReturnType {
+                associated_type_id: entity_type_id,
+                type_name: typ.history_type(),
+            },
+        ))),
+    }
+}
+
+fn expand_history_query(
     entity_type: &EntityType,
     predicate_types: &MappedArena<PredicateParameterType>,
+    history_queries: &mut MappedArena<HistoryQuery>,
     database: &Database,
-) -> PredicateParameter {
-    let pk_field = entity_type.pk_field().unwrap();
-    implicit_equals_predicate_param(pk_field, predicate_types, database)
+) {
+    let operation_name = entity_type.history_query();
+    let existing_query = &mut history_queries.get_by_key_mut(&operation_name).unwrap();
+    existing_query.parameters.predicate_params =
+        pk_predicate_params(entity_type, predicate_types, database);
+}
+
+/// Predicate parameters for the primary key fields (one per field, so multiple for a composite
+/// primary key)
+pub fn pk_predicate_params(
+    entity_type: &EntityType,
+    predicate_types: &MappedArena<PredicateParameterType>,
+    database: &Database,
+) -> Vec<PredicateParameter> {
+    entity_type
+        .pk_fields()
+        .into_iter()
+        .map(|pk_field| implicit_equals_predicate_param(pk_field, predicate_types, database))
+        .collect()
 }
 
 fn implicit_equals_predicate_param(
@@ -149,6 +261,7 @@ fn implicit_equals_predicate_param(
         column_path_link: Some(field.relation.column_path_link(database)),
         access: None,
         vector_distance_function: None,
+        foreign_column_path_link: None,
     }
 }
 
@@ -164,6 +277,7 @@ fn shallow_collection_query(
             order_by_param: OrderByParameter::shallow(),
             limit_param: LimitParameter::shallow(),
             offset_param: OffsetParameter::shallow(),
+            include_deleted_param: None,
         },
         return_type: OperationReturnType::List(Box::new(OperationReturnType::Plain(
             BaseOperationReturnType {
@@ -195,6 +309,54 @@ fn expand_collection_query(
     existing_query.parameters.order_by_param = order_by_param;
     existing_query.parameters.limit_param = limit_param;
     existing_query.parameters.offset_param = offset_param;
+    existing_query.parameters.include_deleted_param =
+        include_deleted_param(entity_type, primitive_types);
+}
+
+fn shallow_connection_query(
+    entity_type_id: SerializableSlabIndex<EntityType>,
+    resolved_entity_type: &ResolvedCompositeType,
+) -> ConnectionQuery {
+    ConnectionQuery {
+        name: resolved_entity_type.connection_query(),
+        parameters: ConnectionQueryParameters {
+            first_param: LimitParameter::shallow(),
+            after_param: CursorParameter::shallow(),
+            last_param: LimitParameter::shallow(),
+            before_param: CursorParameter::shallow(),
+            predicate_param: PredicateParameter::shallow(),
+            order_by_param: OrderByParameter::shallow(),
+        },
+        return_type: OperationReturnType::Plain(BaseOperationReturnType {
+            associated_type_id: entity_type_id,
+            type_name: resolved_entity_type.connection_type(),
+        }),
+    }
+}
+
+fn expand_connection_query(
+    entity_type: &EntityType,
+    primitive_types: &MappedArena<PostgresPrimitiveType>,
+    predicate_types: &MappedArena<PredicateParameterType>,
+    order_by_types: &MappedArena<OrderByParameterType>,
+    connection_queries: &mut MappedArena<ConnectionQuery>,
+) {
+    let operation_name = entity_type.connection_query();
+
+    let existing_query = &mut connection_queries.get_by_key_mut(&operation_name).unwrap();
+
+    existing_query.parameters = ConnectionQueryParameters {
+        first_param: count_param("first", primitive_types),
+        after_param: cursor_param("after", primitive_types),
+        last_param: count_param("last", primitive_types),
+        before_param: cursor_param("before", primitive_types),
+        predicate_param: collection_predicate_param(entity_type, predicate_types),
+        order_by_param: order_by_type_builder::new_root_param(
+            &entity_type.name,
+            false,
+            order_by_types,
+        ),
+    };
 }
 
 fn shallow_aggregate_query(
@@ -226,6 +388,84 @@ fn expand_aggregate_query(
     existing_query.parameters.predicate_param = predicate_param;
 }
 
+fn shallow_group_by_query(
+    entity_type_id: SerializableSlabIndex<EntityType>,
+    resolved_entity_type: &ResolvedCompositeType,
+    group_type: &GroupType,
+) -> GroupByQuery {
+    // The parameters typed with the group's supporting types don't depend on other types, so we
+    // can set them right away
+    let group_by_param_type =
+        |type_name: String| FieldType::Plain(GroupByParameterType { type_name });
+
+    GroupByQuery {
+        name: resolved_entity_type.group_by_query(),
+        parameters: GroupByQueryParameters {
+            by_param: GroupByParameter {
+                name: "by".to_string(),
+                typ: FieldType::List(Box::new(group_by_param_type(
+                    group_type.by_field_type_name(),
+                ))),
+            },
+            predicate_param: PredicateParameter::shallow(),
+            having_param: GroupByParameter {
+                name: "having".to_string(),
+                typ: FieldType::Optional(Box::new(group_by_param_type(
+                    group_type.having_type_name(),
+                ))),
+            },
+            order_by_param: GroupByParameter {
+                name: "orderBy".to_string(),
+                typ: FieldType::Optional(Box::new(FieldType::List(Box::new(group_by_param_type(
+                    group_type.ordering_type_name(),
+                ))))),
+            },
+            limit_param: LimitParameter::shallow(),
+            offset_param: OffsetParameter::shallow(),
+        },
+        return_type: OperationReturnType::List(Box::new(OperationReturnType::Plain(
+            BaseOperationReturnType {
+                associated_type_id: entity_type_id,
+                type_name: group_type.name.clone(),
+            },
+        ))),
+    }
+}
+
+/// The fields rows may be grouped by: the scalar fields (leaving out lists and vectors, which
+/// make for meaningless groups)
+fn group_key_fields(resolved_entity_type: &ResolvedCompositeType) -> Vec<GroupKeyField> {
+    resolved_entity_type
+        .fields
+        .iter()
+        .filter_map(|field| match field.typ.base_type() {
+            FieldType::Plain(typ) if typ.is_primitive && typ.type_name != "Vector" => {
+                Some(GroupKeyField {
+                    name: field.name.clone(),
+                    type_name: typ.type_name.clone(),
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn expand_group_by_query(
+    entity_type: &EntityType,
+    primitive_types: &MappedArena<PostgresPrimitiveType>,
+    predicate_types: &MappedArena<PredicateParameterType>,
+    group_by_queries: &mut MappedArena<GroupByQuery>,
+) {
+    let operation_name = entity_type.group_by_query();
+
+    let existing_query = &mut group_by_queries.get_by_key_mut(&operation_name).unwrap();
+
+    existing_query.parameters.predicate_param =
+        collection_predicate_param(entity_type, predicate_types);
+    existing_query.parameters.limit_param = limit_param(primitive_types);
+    existing_query.parameters.offset_param = offset_param(primitive_types);
+}
+
 fn shallow_unique_queries(
     entity_type_id: SerializableSlabIndex<EntityType>,
     resolved_entity_type: &ResolvedCompositeType,
@@ -237,6 +477,7 @@ fn shallow_unique_queries(
             name: resolved_entity_type.unique_query(name),
             parameters: UniqueQueryParameters {
                 predicate_params: vec![],
+                include_deleted_param: None,
             },
             return_type: OperationReturnType::Optional(Box::new(OperationReturnType::Plain(
                 BaseOperationReturnType {
@@ -250,6 +491,7 @@ fn shallow_unique_queries(
 
 pub fn expand_unique_queries(
     entity_type: &EntityType,
+    primitive_types: &MappedArena<PostgresPrimitiveType>,
     predicate_types: &MappedArena<PredicateParameterType>,
     unique_queries: &mut MappedArena<UniqueQuery>,
     resolved_env: &ResolvedTypeEnv,
@@ -286,10 +528,11 @@ pub fn expand_unique_queries(
                                 ),
                                 access: None,
                                 vector_distance_function: None,
+                                foreign_column_path_link: None,
                             }
                         }
-                        PostgresRelation::OneToMany { .. } => {
-                            panic!("OneToMany relations cannot be used in unique queries")
+                        PostgresRelation::OneToMany { .. } | PostgresRelation::ManyToMany { .. } => {
+                            panic!("OneToMany and ManyToMany relations cannot be used in unique queries")
                         }
                     }
                 })
@@ -297,15 +540,21 @@ pub fn expand_unique_queries(
 
             let existing_query = &mut unique_queries.get_by_key_mut(&operation_name).unwrap();
             existing_query.parameters.predicate_params = predicate_params;
+            existing_query.parameters.include_deleted_param =
+                include_deleted_param(entity_type, primitive_types);
         }
     }
 }
 
 pub fn limit_param(primitive_types: &MappedArena<PostgresPrimitiveType>) -> LimitParameter {
+    count_param("limit", primitive_types)
+}
+
+fn count_param(name: &str, primitive_types: &MappedArena<PostgresPrimitiveType>) -> LimitParameter {
     let param_type_name = "Int".to_string();
 
     LimitParameter {
-        name: "limit".to_string(),
+        name: name.to_string(),
         typ: FieldType::Optional(Box::new(FieldType::Plain(LimitParameterType {
             type_name: param_type_name.clone(),
             type_id: primitive_types.get_id(&param_type_name).unwrap(),
@@ -325,6 +574,39 @@ pub fn offset_param(primitive_types: &MappedArena<PostgresPrimitiveType>) -> Off
     }
 }
 
+/// The `includeDeleted` parameter for a type with the `@softDelete` annotation
+fn include_deleted_param(
+    entity_type: &EntityType,
+    primitive_types: &MappedArena<PostgresPrimitiveType>,
+) -> Option<IncludeDeletedParameter> {
+    entity_type.soft_delete.as_ref()?;
+
+    let param_type_name = "Boolean".to_string();
+
+    Some(IncludeDeletedParameter {
+        name: INCLUDE_DELETED_PARAM_NAME.to_string(),
+        typ: FieldType::Optional(Box::new(FieldType::Plain(IncludeDeletedParameterType {
+            type_name: param_type_name.clone(),
+            type_id: primitive_types.get_id(&param_type_name).unwrap(),
+        }))),
+    })
+}
+
+fn cursor_param(
+    name: &str,
+    primitive_types: &MappedArena<PostgresPrimitiveType>,
+) -> CursorParameter {
+    let param_type_name = "String".to_string();
+
+    CursorParameter {
+        name: name.to_string(),
+        typ: FieldType::Optional(Box::new(FieldType::Plain(CursorParameterType {
+            type_name: param_type_name.clone(),
+            type_id: primitive_types.get_id(&param_type_name).unwrap(),
+        }))),
+    }
+}
+
 pub fn collection_predicate_param(
     entity_type: &EntityType,
     predicate_types: &MappedArena<PredicateParameterType>,
@@ -343,6 +625,7 @@ pub fn collection_predicate_param(
         column_path_link: None,
         access: None,
         vector_distance_function: None,
+        foreign_column_path_link: None,
     }
 }
 
@@ -381,3 +664,21 @@ impl Shallow for OffsetParameterType {
         }
     }
 }
+
+impl Shallow for CursorParameter {
+    fn shallow() -> Self {
+        CursorParameter {
+            name: String::default(),
+            typ: FieldType::Plain(CursorParameterType::shallow()),
+        }
+    }
+}
+
+impl Shallow for CursorParameterType {
+    fn shallow() -> Self {
+        CursorParameterType {
+            type_name: String::default(),
+            type_id: SerializableSlabIndex::shallow(),
+        }
+    }
+}
@@ -0,0 +1,64 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Build refresh mutations (`refresh<Type>s`) for types with the `@materializedView` annotation.
+//!
+//! Refresh mutations take no parameters, so this builder doesn't create any types of its own.
+
+use core_plugin_interface::core_model::{
+    access::AccessPredicateExpression,
+    types::{BaseOperationReturnType, OperationReturnType},
+};
+use exo_sql::TableKind;
+use postgres_model::{
+    mutation::{PostgresMutation, PostgresMutationParameters},
+    types::EntityType,
+};
+
+use super::{naming::ToPostgresMutationNames, system_builder::SystemContextBuilding};
+
+/// Build the refresh mutations for all materialized view types.
+///
+/// Refreshing changes the content of the view, so we offer it only if updates are allowed by the
+/// access rule.
+pub fn build_expanded(building: &mut SystemContextBuilding) {
+    let update_access_is_false = |entity_type: &EntityType| -> bool {
+        matches!(
+            building.input_access_expressions.borrow()[entity_type.access.update.input],
+            AccessPredicateExpression::BooleanLiteral(false)
+        ) || matches!(
+            building.database_access_expressions.borrow()[entity_type.access.update.database],
+            AccessPredicateExpression::BooleanLiteral(false)
+        )
+    };
+
+    let mut mutations = vec![];
+
+    for (entity_type_id, entity_type) in building.entity_types.iter() {
+        let table_kind = building.database.get_table(entity_type.table_id).kind;
+
+        if table_kind == TableKind::MaterializedView && !update_access_is_false(entity_type) {
+            mutations.push(PostgresMutation {
+                name: entity_type.collection_refresh(),
+                parameters: PostgresMutationParameters::Refresh,
+                // The refreshed content of the view
+                return_type: OperationReturnType::List(Box::new(OperationReturnType::Plain(
+                    BaseOperationReturnType {
+                        associated_type_id: entity_type_id,
+                        type_name: entity_type.name.clone(),
+                    },
+                ))),
+            });
+        }
+    }
+
+    for mutation in mutations {
+        building.mutations.add(&mutation.name.to_owned(), mutation);
+    }
+}
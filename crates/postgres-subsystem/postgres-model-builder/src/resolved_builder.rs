@@ -20,6 +20,7 @@ use codemap_diagnostic::{Diagnostic, Level, SpanLabel, SpanStyle};
 
 use core_plugin_interface::{
     core_model::{
+        cache_policy::CachePolicy,
         mapped_arena::MappedArena,
         primitive_type::PrimitiveType,
         types::{FieldType, Named},
@@ -27,9 +28,11 @@ use core_plugin_interface::{
     core_model_builder::{
         ast::ast_types::{
             default_span, AstAnnotationParams, AstExpr, AstField, AstFieldDefault,
-            AstFieldDefaultKind, AstFieldType, AstModel, AstModelKind,
+            AstFieldDefaultKind, AstFieldType, AstMethod, AstMethodType, AstModel, AstModelKind,
+        },
+        builder::{
+            cache_policy_builder::build_cache_policy, resolved_builder::AnnotationMapHelper,
         },
-        builder::resolved_builder::AnnotationMapHelper,
         error::ModelBuildingError,
         typechecker::{
             typ::{Module, Type, TypecheckedSystem},
@@ -37,7 +40,7 @@ use core_plugin_interface::{
         },
     },
 };
-use exo_sql::{PhysicalTableName, VectorDistanceFunction};
+use exo_sql::{PhysicalTableName, TableKind, VectorDistanceFunction, DEFAULT_TEXT_SEARCH_LANGUAGE};
 
 use super::{
     access_builder::{build_access, ResolvedAccess},
@@ -77,6 +80,21 @@ pub fn build(
     }
 }
 
+/// Consume typed-checked methods in `@postgres` modules and build resolved functions
+pub fn build_functions(
+    typechecked_system: &TypecheckedSystem,
+) -> Result<Vec<ResolvedFunction>, ModelBuildingError> {
+    let mut errors = Vec::new();
+
+    let resolved_functions = resolve_functions(typechecked_system, &mut errors);
+
+    if errors.is_empty() {
+        Ok(resolved_functions)
+    } else {
+        Err(ModelBuildingError::Diagnosis(errors))
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[allow(clippy::large_enum_variant)]
 pub enum ResolvedType {
@@ -90,13 +108,74 @@ pub struct ResolvedCompositeType {
     pub plural_name: String,
     pub fields: Vec<ResolvedField>,
     pub table_name: PhysicalTableName,
+    /// Is this type backed by a table or a view (through the `@view` or `@materializedView`
+    /// annotation)
+    pub table_kind: TableKind,
     pub access: ResolvedAccess,
+    /// Should changes to this type be pushed to subscribers (through the `@subscribable` annotation)
+    pub subscribable: bool,
+    /// How long responses including this type may be cached (through the `@cache` annotation)
+    pub cache_policy: Option<CachePolicy>,
+    /// The fields to search through the `@fullTextSearch` annotation
+    pub full_text_search: Option<ResolvedFullTextSearch>,
+    /// The timestamp field marking deleted entities through the `@softDelete` annotation
+    pub soft_delete: Option<String>,
+    /// The field for optimistic concurrency control through the `@version` annotation
+    pub version: Option<String>,
+    /// The recording of changes to this type through the `@audited` annotation
+    pub audit: Option<ResolvedAudit>,
+    #[serde(skip_serializing)]
+    #[serde(skip_deserializing)]
+    #[serde(default = "default_span")]
+    pub span: Span,
+}
+
+/// The name of the generated `tsvector` column backing a type's full-text search
+pub const SEARCH_VECTOR_COLUMN_NAME: &str = "search_vector";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedFullTextSearch {
+    /// The (String) fields whose content is searched
+    pub fields: Vec<String>,
+    /// The text search configuration (such as "english") used to parse the content and queries
+    pub language: String,
+}
+
+/// The parameters of `@audited(user=AuthContext.id, access=AuthContext.role == "admin")`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ResolvedAudit {
+    /// The context field identifying the user making a change (recorded with the change)
+    pub user: Option<AstExpr<Typed>>,
+    /// The access rule for querying the history (no one may query it if not specified)
+    pub access: Option<AstExpr<Typed>>,
+}
+
+/// A query or mutation backed by a Postgres function through the `@function` annotation such as
+/// `@function("reporting.top_concerts") query topConcerts(count: Int): Set<Concert>`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ResolvedFunction {
+    pub name: String,
+    pub typ: AstMethodType,
+    /// The function to call such as `reporting.top_concerts`
+    pub function_name: PhysicalTableName,
+    pub arguments: Vec<ResolvedFunctionArgument>,
+    /// The entity type (or a list of it) whose rows the function returns
+    pub return_type: FieldType<ResolvedFieldType>,
+    /// The access rule for calling the function (no one may call it if not specified)
+    pub access: Option<AstExpr<Typed>>,
     #[serde(skip_serializing)]
     #[serde(skip_deserializing)]
     #[serde(default = "default_span")]
     pub span: Span,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ResolvedFunctionArgument {
+    pub name: String,
+    /// A primitive type (or a list of it)
+    pub typ: FieldType<ResolvedFieldType>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct ResolvedFieldType {
     pub type_name: String, // Should really be Id<ResolvedType>, but using String since the former is not serializable as needed by the insta crate
@@ -144,6 +223,11 @@ pub struct ResolvedField {
     pub default_value: Option<ResolvedFieldDefault>,
     pub update_sync: bool,
     pub readonly: bool,
+    pub cache_policy: Option<CachePolicy>,
+    /// The linking table of an implicit many-to-many relation (through the `@manyToMany`
+    /// annotation). Set for the fields on both sides of the relation, in which case `column_name`
+    /// is the linking table's column that refers to the enclosing type.
+    pub many_to_many_table: Option<PhysicalTableName>,
     #[serde(skip_serializing)]
     #[serde(skip_deserializing)]
     #[serde(default = "default_span")]
@@ -190,12 +274,38 @@ pub enum ResolvedTypeHint {
 }
 
 impl ResolvedCompositeType {
-    pub fn pk_field(&self) -> Option<&ResolvedField> {
-        self.fields.iter().find(|f| f.is_pk)
+    /// The primary key fields (more than one for a composite primary key)
+    pub fn pk_fields(&self) -> Vec<&ResolvedField> {
+        self.fields.iter().filter(|f| f.is_pk).collect()
     }
 
+    /// The names of the columns that refer to this type's primary key for a relation field with
+    /// the given column name.
+    ///
+    /// With a single-column primary key, this is just the given column name (such as `venue_id`).
+    /// With a composite primary key, there is a column for each primary key column, named by
+    /// replacing the `_id` suffix of the given column name with the primary key column's name
+    /// (such as `venue_tenant_id` and `venue_id` for the `tenant_id` and `id` primary key columns).
+    pub fn foreign_key_column_names(&self, column_name: &str) -> Vec<String> {
+        match self.pk_fields().as_slice() {
+            [_] => vec![column_name.to_string()],
+            pk_fields => {
+                let prefix = column_name.strip_suffix("_id").unwrap_or(column_name);
+                pk_fields
+                    .iter()
+                    .map(|pk_field| format!("{prefix}_{}", pk_field.column_name))
+                    .collect()
+            }
+        }
+    }
+
+    /// The field backed by the given column (of this type's table or, for a one-to-many field,
+    /// the other type's table). Many-to-many fields are never matched, since their column lives
+    /// in the linking table.
     pub fn field_by_column_name(&self, column_name: &str) -> Option<&ResolvedField> {
-        self.fields.iter().find(|f| f.column_name == column_name)
+        self.fields
+            .iter()
+            .find(|f| f.many_to_many_table.is_none() && f.column_name == column_name)
     }
 
     pub fn unique_constraints(&self) -> HashMap<String, Vec<&ResolvedField>> {
@@ -292,7 +402,10 @@ fn resolve(
                             name: table_name,
                             schema: schema_name,
                         } = extract_table_annotation(
-                            ct.annotations.get("table"),
+                            ct.annotations
+                                .get("table")
+                                .or_else(|| ct.annotations.get("view"))
+                                .or_else(|| ct.annotations.get("materializedView")),
                             &ct.name,
                             plural_annotation_value.clone(),
                         );
@@ -307,7 +420,8 @@ fn resolve(
                             .iter()
                             .flat_map(|field| {
                                 let update_sync = field.annotations.contains("update");
-                                let readonly = field.annotations.contains("readonly");
+                                let readonly = field.annotations.contains("readonly")
+                                    || field.annotations.contains("version");
 
                                 let column_info =
                                     compute_column_info(ct, field, &typechecked_system.types);
@@ -319,6 +433,7 @@ fn resolve(
                                         access,
                                         unique_constraints,
                                         indices,
+                                        many_to_many_table,
                                     }) => {
                                         let typ = resolve_field_type(
                                             &field.typ.to_typ(&typechecked_system.types),
@@ -347,6 +462,11 @@ fn resolve(
                                             default_value,
                                             update_sync,
                                             readonly,
+                                            cache_policy: build_cache_policy(
+                                                field.annotations.get("cache"),
+                                                errors,
+                                            ),
+                                            many_to_many_table,
                                             span: field.span,
                                         })
                                     }
@@ -368,7 +488,25 @@ fn resolve(
                                     name: table_name,
                                     schema: schema_name,
                                 },
+                                table_kind: build_table_kind(ct, errors),
                                 access: access.clone(),
+                                subscribable: ct.annotations.contains("subscribable"),
+                                cache_policy: build_cache_policy(
+                                    ct.annotations.get("cache"),
+                                    errors,
+                                ),
+                                full_text_search: build_full_text_search(
+                                    ct,
+                                    &typechecked_system.types,
+                                    errors,
+                                ),
+                                soft_delete: build_soft_delete(
+                                    ct,
+                                    &typechecked_system.types,
+                                    errors,
+                                ),
+                                version: build_version(ct, &typechecked_system.types, errors),
+                                audit: build_audit(ct),
                                 span: ct.span,
                             }),
                         );
@@ -760,6 +898,7 @@ struct ColumnInfo {
     unique_constraints: Vec<String>,
     indices: Vec<String>,
     access: ResolvedAccess,
+    many_to_many_table: Option<PhysicalTableName>,
     // // Will this field be auto-updated by the system (through triggers, etc.) to its default value?
     // update_sync: bool,
 }
@@ -827,12 +966,14 @@ fn compute_column_info(
             .unwrap_or_default();
 
         let update_sync = field.annotations.contains("update");
-        let readonly = field.annotations.contains("readonly");
+        let readonly =
+            field.annotations.contains("readonly") || field.annotations.contains("version");
 
         if (update_sync || readonly) && field.default_value.is_none() {
             return Err(Diagnostic {
                 level: Level::Error,
-                message: "Fields with @readonly or @update must have a default value".to_string(),
+                message: "Fields with @readonly, @update, or @version must have a default value"
+                    .to_string(),
                 code: Some("C000".to_string()),
                 spans: vec![SpanLabel {
                     span: field.span,
@@ -913,6 +1054,7 @@ fn compute_column_info(
                                         access,
                                         unique_constraints,
                                         indices,
+                                        many_to_many_table: None,
                                     }),
                                     Cardinality::Unbounded => Ok(ColumnInfo {
                                         name: id_column_name(&field.name),
@@ -920,6 +1062,7 @@ fn compute_column_info(
                                         access,
                                         unique_constraints,
                                         indices,
+                                        many_to_many_table: None,
                                     }),
                                 }
                             }
@@ -938,6 +1081,7 @@ fn compute_column_info(
                                     access,
                                     unique_constraints,
                                     indices,
+                                    many_to_many_table: None,
                                 })
                             }
                         }
@@ -956,27 +1100,21 @@ fn compute_column_info(
                             let matching_field_cardinality = field_cardinality(&matching_field.typ);
 
                             if matching_field_cardinality == Cardinality::Unbounded {
-                                let referring_type_name = &enclosing_type.name;
-                                let referred_type_name = &field_type.name;
-                                let suggested_linking_type_name =
-                                    if referring_type_name < referred_type_name {
-                                        format!("{}{}", referring_type_name, referred_type_name)
-                                    } else {
-                                        format!("{}{}", referred_type_name, referring_type_name)
-                                    };
-
-                                // We don't support direct many-to-many relationships
-                                Err(Diagnostic {
-                                    level: Level::Error,
-                                    message: format!(
-                                        "Many-to-many relationships without a linking type are not supported. Consider adding a type such as '{suggested_linking_type_name}' to connect '{referring_type_name}' and '{referred_type_name}",
-                                    ),
-                                    code: Some("C000".to_string()),
-                                    spans: vec![SpanLabel {
-                                        span: field.span,
-                                        style: SpanStyle::Primary,
-                                        label: None,
-                                    }],
+                                let many_to_many_table = many_to_many_table(
+                                    field,
+                                    enclosing_type,
+                                    matching_field,
+                                    &field_type,
+                                )?;
+
+                                // The column in the linking table that refers to the enclosing type
+                                Ok(ColumnInfo {
+                                    name: format!("{}_id", enclosing_type.name.to_snake_case()),
+                                    self_column: false,
+                                    access,
+                                    unique_constraints,
+                                    indices,
+                                    many_to_many_table: Some(many_to_many_table),
                                 })
                             } else {
                                 Ok(ColumnInfo {
@@ -985,6 +1123,7 @@ fn compute_column_info(
                                     access,
                                     unique_constraints,
                                     indices,
+                                    many_to_many_table: None,
                                 })
                             }
                         } else {
@@ -1015,6 +1154,7 @@ fn compute_column_info(
                                 access,
                                 unique_constraints,
                                 indices,
+                                many_to_many_table: None,
                             })
                         } else {
                             Err(Diagnostic {
@@ -1035,6 +1175,7 @@ fn compute_column_info(
                         access,
                         unique_constraints,
                         indices,
+                        many_to_many_table: None,
                     }),
                 }
             }
@@ -1055,7 +1196,93 @@ fn compute_column_info(
         }
     }
 
-    column_name(enclosing_type, field, types)
+    let column_info = column_name(enclosing_type, field, types)?;
+
+    if field.annotations.contains("manyToMany") && column_info.many_to_many_table.is_none() {
+        return Err(Diagnostic {
+            level: Level::Error,
+            message: "The @manyToMany annotation is only valid on a set field whose matching field is also a set".to_string(),
+            code: Some("C000".to_string()),
+            spans: vec![SpanLabel {
+                span: field.span,
+                style: SpanStyle::Primary,
+                label: None,
+            }],
+        });
+    }
+
+    Ok(column_info)
+}
+
+/// The linking table of a many-to-many relation between `field` (of `enclosing_type`) and its
+/// `matching_field` (of `field_type`).
+///
+/// The table is specified through the `@manyToMany(table="<table-name>")` annotation on either
+/// (or both) of the fields. If both fields specify it, the table names must be the same.
+fn many_to_many_table(
+    field: &AstField<Typed>,
+    enclosing_type: &AstModel<Typed>,
+    matching_field: &AstField<Typed>,
+    field_type: &AstModel<Typed>,
+) -> Result<PhysicalTableName, Diagnostic> {
+    fn annotated_table(field: &AstField<Typed>) -> Option<String> {
+        field.annotations.get("manyToMany").map(|p| match p {
+            AstAnnotationParams::Single(value, _) => value.as_string(),
+            AstAnnotationParams::Map(m, _) => m["table"].as_string(),
+            AstAnnotationParams::None => panic!("@manyToMany requires a table name"),
+        })
+    }
+
+    let error = |message: String| Diagnostic {
+        level: Level::Error,
+        message,
+        code: Some("C000".to_string()),
+        spans: vec![SpanLabel {
+            span: field.span,
+            style: SpanStyle::Primary,
+            label: None,
+        }],
+    };
+
+    let referring_type_name = &enclosing_type.name;
+    let referred_type_name = &field_type.name;
+
+    if referring_type_name == referred_type_name {
+        return Err(error(format!(
+            "Many-to-many relationships of a type with itself are not supported. Consider adding a linking type to connect '{referring_type_name}' to itself"
+        )));
+    }
+
+    let table_name = match (annotated_table(field), annotated_table(matching_field)) {
+        (Some(table_name), None) | (None, Some(table_name)) => table_name,
+        (Some(table_name), Some(matching_table_name)) => {
+            if table_name != matching_table_name {
+                return Err(error(format!(
+                    "The @manyToMany annotations of '{referring_type_name}.{}' and '{referred_type_name}.{}' specify different linking tables ('{table_name}' and '{matching_table_name}')",
+                    field.name, matching_field.name
+                )));
+            }
+            table_name
+        }
+        (None, None) => {
+            let suggested_linking_type_name = if referring_type_name < referred_type_name {
+                format!("{}{}", referring_type_name, referred_type_name)
+            } else {
+                format!("{}{}", referred_type_name, referring_type_name)
+            };
+            let suggested_table_name = suggested_linking_type_name.table_name(None);
+
+            return Err(error(format!(
+                "Many-to-many relationships need a linking table. Consider annotating '{referring_type_name}.{}' with '@manyToMany(table=\"{suggested_table_name}\")' or adding a type such as '{suggested_linking_type_name}' to connect '{referring_type_name}' and '{referred_type_name}'",
+                field.name
+            )));
+        }
+    };
+
+    Ok(PhysicalTableName {
+        name: table_name,
+        schema: None,
+    })
 }
 
 fn get_matching_field<'a>(
@@ -1163,6 +1390,450 @@ struct TableInfo {
     schema: Option<String>,
 }
 
+/// Given parameters for `@fullTextSearch(fields=["title", "body"], language="english")`, extract
+/// the fields to search and the text search configuration (defaulting to "english").
+///
+/// Each field must be a non-list `String` field without its own access control (otherwise
+/// searching would reveal the content of the field to users who may not read it).
+fn build_full_text_search(
+    ct: &AstModel<Typed>,
+    types: &MappedArena<Type>,
+    errors: &mut Vec<Diagnostic>,
+) -> Option<ResolvedFullTextSearch> {
+    let params = ct.annotations.get("fullTextSearch")?;
+    let params = params.as_map();
+
+    let mut report = |message: String, span: Span| {
+        errors.push(Diagnostic {
+            level: Level::Error,
+            message,
+            code: Some("C000".to_string()),
+            spans: vec![SpanLabel {
+                span,
+                style: SpanStyle::Primary,
+                label: None,
+            }],
+        });
+    };
+
+    let fields: Vec<(String, Span)> = match params.get("fields") {
+        Some(AstExpr::StringList(fields, spans)) => {
+            fields.iter().cloned().zip(spans.iter().cloned()).collect()
+        }
+        Some(AstExpr::StringLiteral(field, span)) => vec![(field.clone(), *span)],
+        Some(expr) => {
+            report(
+                "The fields of @fullTextSearch must be a list of field names".to_string(),
+                expr.span(),
+            );
+            return None;
+        }
+        None => return None,
+    };
+
+    for (field_name, span) in &fields {
+        match ct.fields.iter().find(|field| &field.name == field_name) {
+            Some(field) => {
+                let is_string = match field.typ.to_typ(types).deref(types) {
+                    Type::Optional(underlying) => {
+                        matches!(*underlying, Type::Primitive(PrimitiveType::String))
+                    }
+                    typ => matches!(typ, Type::Primitive(PrimitiveType::String)),
+                };
+
+                if !is_string {
+                    report(
+                        format!("Field '{field_name}' of @fullTextSearch must be a String"),
+                        *span,
+                    );
+                } else if field.annotations.contains("access") {
+                    report(
+                        format!(
+                            "Field '{field_name}' of @fullTextSearch must not have its own access control"
+                        ),
+                        *span,
+                    );
+                }
+            }
+            None => report(
+                format!(
+                    "Field '{field_name}' of @fullTextSearch not found in '{}'",
+                    ct.name
+                ),
+                *span,
+            ),
+        }
+    }
+
+    // The `search` filter would otherwise clash with the field's filter
+    if let Some(field) = ct.fields.iter().find(|field| field.name == "search") {
+        report(
+            "A type with @fullTextSearch may not have a field named 'search'".to_string(),
+            field.span,
+        );
+    }
+
+    let language = match params.get("language") {
+        Some(language) => {
+            let value = language.as_string();
+            let is_valid = !value.is_empty()
+                && value
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+            if !is_valid {
+                report(
+                    format!("Invalid text search language '{value}'"),
+                    language.span(),
+                );
+            }
+            value
+        }
+        None => DEFAULT_TEXT_SEARCH_LANGUAGE.to_string(),
+    };
+
+    Some(ResolvedFullTextSearch {
+        fields: fields
+            .into_iter()
+            .map(|(field_name, _)| field_name)
+            .collect(),
+        language,
+    })
+}
+
+/// Given parameters for `@softDelete(field="deletedAt")`, extract the name of the field marking
+/// deleted entities.
+///
+/// The field must be an optional `Instant` or `LocalDateTime` field (`null` for entities that are
+/// not deleted).
+fn build_soft_delete(
+    ct: &AstModel<Typed>,
+    types: &MappedArena<Type>,
+    errors: &mut Vec<Diagnostic>,
+) -> Option<String> {
+    let params = ct.annotations.get("softDelete")?;
+    let field_expr = params.as_map().get("field")?;
+
+    let mut report = |message: String| {
+        errors.push(Diagnostic {
+            level: Level::Error,
+            message,
+            code: Some("C000".to_string()),
+            spans: vec![SpanLabel {
+                span: field_expr.span(),
+                style: SpanStyle::Primary,
+                label: None,
+            }],
+        });
+    };
+
+    let field_name = match field_expr {
+        AstExpr::StringLiteral(field_name, _) => field_name.clone(),
+        _ => {
+            report("The field of @softDelete must be a field name".to_string());
+            return None;
+        }
+    };
+
+    match ct.fields.iter().find(|field| field.name == field_name) {
+        Some(field) => {
+            let is_optional_timestamp = match field.typ.to_typ(types).deref(types) {
+                Type::Optional(underlying) => matches!(
+                    *underlying,
+                    Type::Primitive(PrimitiveType::Instant | PrimitiveType::LocalDateTime)
+                ),
+                _ => false,
+            };
+
+            if !is_optional_timestamp {
+                report(format!(
+                    "Field '{field_name}' of @softDelete must be an optional Instant or LocalDateTime"
+                ));
+                return None;
+            }
+        }
+        None => {
+            report(format!(
+                "Field '{field_name}' of @softDelete not found in '{}'",
+                ct.name
+            ));
+            return None;
+        }
+    }
+
+    Some(field_name)
+}
+
+/// Determine whether the type is backed by a table or a view (through the `@view` or
+/// `@materializedView` annotation).
+///
+/// Exograph doesn't create or change views, so a view type may not use annotations that need
+/// changes to the underlying table (such as triggers or generated columns) or that only apply to
+/// mutations.
+fn build_table_kind(ct: &AstModel<Typed>, errors: &mut Vec<Diagnostic>) -> TableKind {
+    let (kind, view_annotation) = if ct.annotations.contains("materializedView") {
+        (TableKind::MaterializedView, "materializedView")
+    } else if ct.annotations.contains("view") {
+        (TableKind::View, "view")
+    } else {
+        return TableKind::Table;
+    };
+
+    let span = ct.annotations.annotations[view_annotation].span;
+
+    let mut report = |message: String| {
+        errors.push(Diagnostic {
+            level: Level::Error,
+            message,
+            code: Some("C000".to_string()),
+            spans: vec![SpanLabel {
+                span,
+                style: SpanStyle::Primary,
+                label: None,
+            }],
+        });
+    };
+
+    for conflicting_annotation in [
+        "table",
+        "view",
+        "subscribable",
+        "audited",
+        "softDelete",
+        "fullTextSearch",
+    ] {
+        if conflicting_annotation != view_annotation
+            && ct.annotations.contains(conflicting_annotation)
+        {
+            report(format!(
+                "Type '{}' may not have both @{view_annotation} and @{conflicting_annotation}",
+                ct.name
+            ));
+        }
+    }
+
+    if ct
+        .fields
+        .iter()
+        .any(|field| field.annotations.contains("version"))
+    {
+        report(format!(
+            "Type '{}' with @{view_annotation} may not have a @version field",
+            ct.name
+        ));
+    }
+
+    kind
+}
+
+/// Extract the parameters of the `@audited` annotation, if any
+fn build_audit(ct: &AstModel<Typed>) -> Option<ResolvedAudit> {
+    match ct.annotations.get("audited")? {
+        AstAnnotationParams::Map(params, _) => Some(ResolvedAudit {
+            user: params.get("user").cloned(),
+            access: params.get("access").cloned(),
+        }),
+        _ => Some(ResolvedAudit {
+            user: None,
+            access: None,
+        }),
+    }
+}
+
+fn resolve_functions(
+    typechecked_system: &TypecheckedSystem,
+    errors: &mut Vec<Diagnostic>,
+) -> Vec<ResolvedFunction> {
+    typechecked_system
+        .modules
+        .iter()
+        .filter(|(_, Module(module))| module.annotations.contains("postgres"))
+        .flat_map(|(_, Module(module))| module.methods.iter())
+        .filter_map(|method| resolve_function(method, &typechecked_system.types, errors))
+        .collect()
+}
+
+/// Resolve a query or mutation in a `@postgres` module, which must be backed by a function through
+/// the `@function` annotation.
+///
+/// The arguments (passed to the function as bind parameters) must be of primitive types and the
+/// return type must be a list of an entity type (for functions such as `RETURNS SETOF concerts`)
+/// or an optional entity type (for functions returning at most one row).
+fn resolve_function(
+    method: &AstMethod<Typed>,
+    types: &MappedArena<Type>,
+    errors: &mut Vec<Diagnostic>,
+) -> Option<ResolvedFunction> {
+    let mut report = |message: String, span: Span| {
+        errors.push(Diagnostic {
+            level: Level::Error,
+            message,
+            code: Some("C000".to_string()),
+            spans: vec![SpanLabel {
+                span,
+                style: SpanStyle::Primary,
+                label: None,
+            }],
+        });
+    };
+
+    let Some(function_annotation) = method.annotations.get("function") else {
+        report(
+            format!(
+                "Operation '{}' in a @postgres module must have a @function annotation",
+                method.name
+            ),
+            method.span,
+        );
+        return None;
+    };
+
+    // A function name such as `reporting.top_concerts` includes the schema
+    let function_name = function_annotation.as_single().as_string();
+    let function_name = match function_name.split_once('.') {
+        Some((schema, name)) => PhysicalTableName::new(name, Some(schema)),
+        None => PhysicalTableName::new(function_name.clone(), None),
+    };
+
+    let mut valid = true;
+
+    let arguments = method
+        .arguments
+        .iter()
+        .map(|argument| {
+            let typ = resolve_field_type(&argument.typ.to_typ(types), types);
+
+            if argument.annotations.contains("inject") {
+                report(
+                    format!(
+                        "Argument '{}' of operation '{}' may not be injected",
+                        argument.name, method.name
+                    ),
+                    argument.typ.span(),
+                );
+                valid = false;
+            } else if !typ.innermost().is_primitive {
+                report(
+                    format!(
+                        "Argument '{}' of operation '{}' must be of a primitive type",
+                        argument.name, method.name
+                    ),
+                    argument.typ.span(),
+                );
+                valid = false;
+            }
+
+            ResolvedFunctionArgument {
+                name: argument.name.clone(),
+                typ,
+            }
+        })
+        .collect();
+
+    let return_type = resolve_field_type(&method.return_type.to_typ(types), types);
+
+    let is_valid_return_type = match &return_type {
+        FieldType::List(underlying) | FieldType::Optional(underlying) => {
+            matches!(underlying.as_ref(), FieldType::Plain(t) if !t.is_primitive)
+        }
+        FieldType::Plain(_) => false,
+    };
+
+    if !is_valid_return_type {
+        report(
+            format!(
+                "Operation '{}' must return a set or an optional value of a type in a @postgres module",
+                method.name
+            ),
+            method.return_type.span(),
+        );
+        valid = false;
+    }
+
+    let access = match method.annotations.get("access") {
+        Some(AstAnnotationParams::Single(expr, _)) => Some(expr.clone()),
+        Some(_) => {
+            report(
+                format!(
+                    "The @access annotation of operation '{}' must be a single expression",
+                    method.name
+                ),
+                method.annotations.annotations["access"].span,
+            );
+            valid = false;
+            None
+        }
+        None => None,
+    };
+
+    valid.then(|| ResolvedFunction {
+        name: method.name.clone(),
+        typ: method.typ.clone(),
+        function_name,
+        arguments,
+        return_type,
+        access,
+        span: method.span,
+    })
+}
+
+/// Extract the name of the field with the `@version` annotation (used for optimistic concurrency
+/// control).
+///
+/// There may be at most one such field, and it must be a non-optional `Int` field.
+fn build_version(
+    ct: &AstModel<Typed>,
+    types: &MappedArena<Type>,
+    errors: &mut Vec<Diagnostic>,
+) -> Option<String> {
+    let version_fields: Vec<_> = ct
+        .fields
+        .iter()
+        .filter(|field| field.annotations.contains("version"))
+        .collect();
+
+    let mut report = |message: String, span: Span| {
+        errors.push(Diagnostic {
+            level: Level::Error,
+            message,
+            code: Some("C000".to_string()),
+            spans: vec![SpanLabel {
+                span,
+                style: SpanStyle::Primary,
+                label: None,
+            }],
+        });
+    };
+
+    match version_fields.as_slice() {
+        [] => None,
+        [field] => {
+            let is_int = matches!(
+                field.typ.to_typ(types).deref(types),
+                Type::Primitive(PrimitiveType::Int)
+            );
+
+            if is_int {
+                Some(field.name.clone())
+            } else {
+                report(
+                    format!("Field '{}' with @version must be an Int", field.name),
+                    field.span,
+                );
+                None
+            }
+        }
+        [_, rest @ ..] => {
+            for field in rest {
+                report(
+                    format!("Type '{}' may have only one @version field", ct.name),
+                    field.span,
+                );
+            }
+            None
+        }
+    }
+}
+
 /// Given parameters for `@table(name=<table-name>, schema=<schema-name>)` extract table and schema name.
 ///
 /// If a single string is provided (for example, `@table("t_name")), it is assumed to be the table name and the schema name is assumed to be `public`.
@@ -1172,6 +1843,7 @@ struct TableInfo {
 ///
 /// If no parameters are provided, the table name is derived from the type name and the schema name is assumed to be `public`.
 ///
+/// The `@view` and `@materializedView` annotations take the same parameters (naming the view instead of the table).
 fn extract_table_annotation(
     annotation_params: Option<&AstAnnotationParams<Typed>>,
     type_name: &str,
@@ -1619,4 +2291,127 @@ mod tests {
             "Many-to-many relationships (both side optional) without a linking type should be rejected"
         );
     }
+
+    #[multiplatform_test]
+    fn many_to_many_with_linking_table() {
+        assert_resolved!(
+            r#"
+        @postgres
+        module ConcertModule {
+            type Concert {
+                @pk id: Int = autoIncrement()
+                title: String
+                @manyToMany(table="concert_artists") artists: Set<Artist>?
+            }
+
+            type Artist {
+                @pk id: Int = autoIncrement()
+                name: String
+                concerts: Set<Concert>?
+            }
+        }
+        "#,
+            "many_to_many_with_linking_table"
+        );
+    }
+
+    #[multiplatform_test]
+    fn many_to_many_with_invalid_linking_table() {
+        assert_resolved_err!(
+            r#"
+        @postgres
+        module ConcertModule {
+            type Concert {
+                @pk id: Int = autoIncrement()
+                @manyToMany(table="concert_artists") artists: Set<Artist>?
+            }
+
+            type Artist {
+                @pk id: Int = autoIncrement()
+                @manyToMany(table="artist_concerts") concerts: Set<Concert>?
+            }
+        }
+        "#,
+            "Many-to-many relationships with different linking tables on each side should be rejected"
+        );
+
+        assert_resolved_err!(
+            r#"
+        @postgres
+        module ConcertModule {
+            type Concert {
+                @pk id: Int = autoIncrement()
+                @manyToMany(table="concert_venues") venue: Venue
+            }
+
+            type Venue {
+                @pk id: Int = autoIncrement()
+                concerts: Set<Concert>?
+            }
+        }
+        "#,
+            "The @manyToMany annotation on a non-set field should be rejected"
+        );
+
+        assert_resolved_err!(
+            r#"
+        @postgres
+        module UserModule {
+            type User {
+                @pk id: Int = autoIncrement()
+                @manyToMany(table="user_friends") friends: Set<User>?
+            }
+        }
+        "#,
+            "Self-referential many-to-many relationships should be rejected"
+        );
+    }
+
+    #[multiplatform_test]
+    fn view_with_invalid_annotations() {
+        assert_resolved_err!(
+            r#"
+        @postgres
+        module ConcertModule {
+            @view("concert_summaries")
+            @table("concerts")
+            type ConcertSummary {
+                @pk id: Int
+                title: String
+            }
+        }
+        "#,
+            "A view with a table annotation should be rejected"
+        );
+
+        assert_resolved_err!(
+            r#"
+        @postgres
+        module ConcertModule {
+            @materializedView
+            @subscribable
+            type ConcertSummary {
+                @pk id: Int
+                title: String
+            }
+        }
+        "#,
+            "A subscribable view should be rejected"
+        );
+
+        assert_resolved_err!(
+            r#"
+        @postgres
+        module ConcertModule {
+            @view
+            type ConcertSummary {
+                @pk id: Int
+                title: String
+                @version version: Int
+            }
+        }
+        "#,
+            "A view with a version field should be rejected"
+        );
+    }
 }
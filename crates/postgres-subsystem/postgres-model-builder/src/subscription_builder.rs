@@ -0,0 +1,92 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use core_plugin_interface::core_model::{
+    mapped_arena::{MappedArena, SerializableSlabIndex},
+    types::{BaseOperationReturnType, OperationReturnType},
+};
+
+use postgres_model::{
+    predicate::PredicateParameter,
+    subscription::{PostgresSubscription, PostgresSubscriptionParameters, SubscriptionKind},
+    types::EntityType,
+};
+
+use crate::{
+    naming::ToPostgresSubscriptionNames, query_builder::collection_predicate_param,
+    resolved_builder::ResolvedCompositeType, shallow::Shallow,
+};
+
+use super::{resolved_builder::ResolvedType, system_builder::SystemContextBuilding};
+
+const SUBSCRIPTION_KINDS: [SubscriptionKind; 3] = [
+    SubscriptionKind::Created,
+    SubscriptionKind::Updated,
+    SubscriptionKind::Deleted,
+];
+
+pub fn build_shallow(types: &MappedArena<ResolvedType>, building: &mut SystemContextBuilding) {
+    for (_, typ) in types.iter() {
+        if let ResolvedType::Composite(c) = &typ {
+            if !c.subscribable {
+                continue;
+            }
+
+            let entity_type_id = building.get_entity_type_id(c.name.as_str()).unwrap();
+
+            for kind in SUBSCRIPTION_KINDS {
+                let subscription = shallow_subscription(entity_type_id, c, kind);
+                building
+                    .subscriptions
+                    .add(&subscription.name.to_owned(), subscription);
+            }
+        }
+    }
+}
+
+pub fn build_expanded(building: &mut SystemContextBuilding) {
+    for (_, entity_type) in building.entity_types.iter() {
+        for kind in SUBSCRIPTION_KINDS {
+            let operation_name = subscription_name(entity_type, kind);
+
+            if let Some(existing_subscription) =
+                building.subscriptions.get_by_key_mut(&operation_name)
+            {
+                existing_subscription.parameters.predicate_param =
+                    collection_predicate_param(entity_type, &building.predicate_types);
+            }
+        }
+    }
+}
+
+fn shallow_subscription(
+    entity_type_id: SerializableSlabIndex<EntityType>,
+    typ: &ResolvedCompositeType,
+    kind: SubscriptionKind,
+) -> PostgresSubscription {
+    PostgresSubscription {
+        name: subscription_name(typ, kind),
+        parameters: PostgresSubscriptionParameters {
+            kind,
+            predicate_param: PredicateParameter::shallow(),
+        },
+        return_type: OperationReturnType::Plain(BaseOperationReturnType {
+            associated_type_id: entity_type_id,
+            type_name: typ.name.clone(),
+        }),
+    }
+}
+
+fn subscription_name(typ: &impl ToPostgresSubscriptionNames, kind: SubscriptionKind) -> String {
+    match kind {
+        SubscriptionKind::Created => typ.created_subscription(),
+        SubscriptionKind::Updated => typ.updated_subscription(),
+        SubscriptionKind::Deleted => typ.deleted_subscription(),
+    }
+}
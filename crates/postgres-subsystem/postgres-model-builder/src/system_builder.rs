@@ -23,10 +23,15 @@ use core_plugin_interface::{
 use postgres_model::{
     access::{DatabaseAccessPrimitiveExpression, InputAccessPrimitiveExpression},
     aggregate::AggregateType,
+    audit::{HistoryQuery, HistoryType},
+    connection::ConnectionType,
+    function::FunctionOperation,
+    group_by::GroupType,
     mutation::PostgresMutation,
     order::OrderByParameterType,
     predicate::PredicateParameterType,
-    query::{AggregateQuery, CollectionQuery, PkQuery, UniqueQuery},
+    query::{AggregateQuery, CollectionQuery, ConnectionQuery, GroupByQuery, PkQuery, UniqueQuery},
+    subscription::PostgresSubscription,
     subsystem::PostgresSubsystem,
     types::{EntityType, MutationType, PostgresPrimitiveType},
     vector_distance::VectorDistanceType,
@@ -37,8 +42,8 @@ use exo_sql::Database;
 use crate::aggregate_type_builder;
 
 use super::{
-    mutation_builder, order_by_type_builder, predicate_builder, query_builder, resolved_builder,
-    type_builder, type_builder::ResolvedTypeEnv,
+    function_builder, mutation_builder, order_by_type_builder, predicate_builder, query_builder,
+    resolved_builder, subscription_builder, type_builder, type_builder::ResolvedTypeEnv,
 };
 
 pub fn build(
@@ -48,6 +53,7 @@ pub fn build(
     let mut building = SystemContextBuilding::default();
 
     let resolved_types = resolved_builder::build(typechecked_system)?;
+    let resolved_functions = resolved_builder::build_functions(typechecked_system)?;
     let system = {
         let resolved_env = ResolvedTypeEnv {
             contexts: &base_system.contexts,
@@ -56,23 +62,32 @@ pub fn build(
         };
 
         build_shallow(&resolved_env, &mut building);
-        build_expanded(&resolved_env, &mut building)?;
+        build_expanded(&resolved_functions, &resolved_env, &mut building)?;
 
         PostgresSubsystem {
             contexts: base_system.contexts.clone(),
             primitive_types: building.primitive_types.values(),
             entity_types: building.entity_types.values(),
             aggregate_types: building.aggregate_types.values(),
+            connection_types: building.connection_types,
+            group_types: building.group_types,
+            history_types: building.history_types,
 
             order_by_types: building.order_by_types.values(),
             predicate_types: building.predicate_types.values(),
             pk_queries: building.pk_queries,
             collection_queries: building.collection_queries,
+            connection_queries: building.connection_queries,
             aggregate_queries: building.aggregate_queries,
+            group_by_queries: building.group_by_queries,
             unique_queries: building.unique_queries,
+            history_queries: building.history_queries,
+            function_queries: building.function_queries,
             database: building.database,
             mutation_types: building.mutation_types.values(),
             mutations: building.mutations,
+            function_mutations: building.function_mutations,
+            subscriptions: building.subscriptions,
 
             input_access_expressions: building.input_access_expressions.into_inner().elems,
             database_access_expressions: building.database_access_expressions.into_inner().elems,
@@ -84,6 +99,8 @@ pub fn build(
             && system.collection_queries.is_empty()
             && system.aggregate_queries.is_empty()
             && system.mutations.is_empty()
+            && system.function_queries.is_empty()
+            && system.function_mutations.is_empty()
         {
             None
         } else {
@@ -103,14 +120,16 @@ fn build_shallow(resolved_env: &ResolvedTypeEnv, building: &mut SystemContextBui
 
     aggregate_type_builder::build_shallow(resolved_env, building);
 
-    // The next two shallow builders need POSTGRES types build above (the order of the next two is unimportant)
-    // Specifically, the OperationReturn type in Query and Mutation looks for the id for the return type, so requires
+    // The next three shallow builders need POSTGRES types build above (the order of the next three is unimportant)
+    // Specifically, the OperationReturn type in Query, Mutation, and Subscription looks for the id for the return type, so requires
     // type_builder::build_shallow to have run
     query_builder::build_shallow(&resolved_env.resolved_types, building);
     mutation_builder::build_shallow(&resolved_env.resolved_types, building);
+    subscription_builder::build_shallow(&resolved_env.resolved_types, building);
 }
 
 fn build_expanded(
+    resolved_functions: &[resolved_builder::ResolvedFunction],
     resolved_env: &ResolvedTypeEnv,
     building: &mut SystemContextBuilding,
 ) -> Result<(), ModelBuildingError> {
@@ -123,9 +142,11 @@ fn build_expanded(
     predicate_builder::build_expanded(resolved_env, building);
     aggregate_type_builder::build_expanded(resolved_env, building)?;
 
-    // Finally expand queries, mutations, and module methods
+    // Finally expand queries, mutations, subscriptions, and module methods
     query_builder::build_expanded(resolved_env, building);
-    mutation_builder::build_expanded(building)?;
+    mutation_builder::build_expanded(resolved_env, building)?;
+    subscription_builder::build_expanded(building);
+    function_builder::build_expanded(resolved_functions, resolved_env, building)?;
 
     Ok(())
 }
@@ -136,17 +157,27 @@ pub struct SystemContextBuilding {
     pub entity_types: MappedArena<EntityType>,
 
     pub aggregate_types: MappedArena<AggregateType>,
+    pub connection_types: MappedArena<ConnectionType>,
+    pub group_types: MappedArena<GroupType>,
+    pub history_types: MappedArena<HistoryType>,
     pub vector_distance_types: MappedArena<VectorDistanceType>,
     pub order_by_types: MappedArena<OrderByParameterType>,
     pub predicate_types: MappedArena<PredicateParameterType>,
 
     pub pk_queries: MappedArena<PkQuery>,
     pub collection_queries: MappedArena<CollectionQuery>,
+    pub connection_queries: MappedArena<ConnectionQuery>,
     pub aggregate_queries: MappedArena<AggregateQuery>,
+    pub group_by_queries: MappedArena<GroupByQuery>,
     pub unique_queries: MappedArena<UniqueQuery>,
+    pub history_queries: MappedArena<HistoryQuery>,
+    pub function_queries: MappedArena<FunctionOperation>,
 
     pub mutation_types: MappedArena<MutationType>,
     pub mutations: MappedArena<PostgresMutation>,
+    pub function_mutations: MappedArena<FunctionOperation>,
+
+    pub subscriptions: MappedArena<PostgresSubscription>,
 
     pub input_access_expressions:
         RefCell<AccessExpressionsBuilding<InputAccessPrimitiveExpression>>,
@@ -210,7 +241,15 @@ impl SystemContextBuilding {
 mod tests {
     use std::collections::HashSet;
 
-    use exo_sql::{FloatBits, IntBits, PhysicalColumn, PhysicalColumnType, PhysicalTable};
+    use core_plugin_interface::core_model::{
+        type_normalization::{Operation, Parameter},
+        types::{Named, OperationReturnType},
+    };
+    use exo_sql::{
+        FloatBits, IntBits, PhysicalColumn, PhysicalColumnType, PhysicalTable, PhysicalTableName,
+        TableKind,
+    };
+    use postgres_model::{mutation::PostgresMutationParameters, order::OrderByParameterTypeKind};
 
     use super::*;
 
@@ -405,6 +444,797 @@ mod tests {
         assert!(!mutation_type_names.contains("TodoUpdateInput"));
     }
 
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn subscribable() {
+        let src = r#"
+        @postgres
+        module TodoModule {
+            @subscribable
+            type Todo {
+                @pk id: Int = autoIncrement()
+                title: String
+            }
+
+            type Note {
+                @pk id: Int = autoIncrement()
+                content: String
+            }
+        }
+        "#;
+
+        let system = create_system(src).await;
+
+        let subscription_names = system
+            .subscriptions
+            .iter()
+            .map(|(_, s)| s.name.clone())
+            .collect::<HashSet<String>>();
+        assert_eq!(
+            subscription_names,
+            HashSet::from([
+                "onTodoCreated".to_string(),
+                "onTodoUpdated".to_string(),
+                "onTodoDeleted".to_string()
+            ])
+        );
+
+        assert!(get_table_from_arena("todos", &system.database).notify_changes);
+        assert!(!get_table_from_arena("notes", &system.database).notify_changes);
+    }
+
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn connection_queries() {
+        let src = r#"
+        @postgres
+        module ConcertModule {
+            type Concert {
+                @pk id: Int = autoIncrement()
+                title: String
+            }
+        }
+        "#;
+
+        let system = create_system(src).await;
+
+        let query = system
+            .connection_queries
+            .get_by_key("concertsConnection")
+            .unwrap();
+        assert_eq!(query.return_type.type_name(), "ConcertConnection");
+
+        let parameter_names = query
+            .parameters()
+            .iter()
+            .map(|p| p.name().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            parameter_names,
+            vec!["first", "after", "last", "before", "where", "orderBy"]
+        );
+
+        let connection_type = system
+            .connection_types
+            .get_by_key("ConcertConnection")
+            .unwrap();
+        assert_eq!(connection_type.edge_type_name, "ConcertEdge");
+
+        let type_names = system
+            .schema_types()
+            .into_iter()
+            .map(|t| t.name.node.to_string())
+            .collect::<HashSet<_>>();
+        for name in ["ConcertConnection", "ConcertEdge", "PageInfo"] {
+            assert!(type_names.contains(name), "Missing type {name}");
+        }
+    }
+
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn group_by_queries() {
+        let src = r#"
+        @postgres
+        module ConcertModule {
+            type Concert {
+                @pk id: Int = autoIncrement()
+                title: String
+                price: Int
+                tags: Array<String>
+                venue: Venue
+            }
+
+            type Venue {
+                @pk id: Int = autoIncrement()
+                name: String
+                concerts: Set<Concert>?
+            }
+        }
+        "#;
+
+        let system = create_system(src).await;
+
+        let query = system
+            .group_by_queries
+            .get_by_key("concertsGroupBy")
+            .unwrap();
+        assert_eq!(query.return_type.type_name(), "ConcertGroup");
+
+        let parameter_names = query
+            .parameters()
+            .iter()
+            .map(|p| p.name().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            parameter_names,
+            vec!["by", "where", "having", "orderBy", "limit", "offset"]
+        );
+
+        // Only scalar fields may be grouped by
+        let group_type = system.group_types.get_by_key("ConcertGroup").unwrap();
+        let key_field_names = group_type
+            .key_fields
+            .iter()
+            .map(|field| field.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(key_field_names, vec!["id", "title", "price"]);
+
+        let type_names = system
+            .schema_types()
+            .into_iter()
+            .map(|t| t.name.node.to_string())
+            .collect::<HashSet<_>>();
+        for name in [
+            "ConcertGroup",
+            "ConcertGroupKey",
+            "ConcertGroupByField",
+            "ConcertGroupHaving",
+            "ConcertGroupOrdering",
+            "ConcertAggFilter",
+            "ConcertAggOrdering",
+            "IntAggFilter",
+            "StringAggOrdering",
+        ] {
+            assert!(type_names.contains(name), "Missing type {name}");
+        }
+    }
+
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn aggregate_order_by() {
+        let src = r#"
+        @postgres
+        module ConcertModule {
+            type Concert {
+                @pk id: Int = autoIncrement()
+                title: String
+                price: Int
+                tags: Array<String>
+                venue: Venue
+            }
+
+            type Venue {
+                @pk id: Int = autoIncrement()
+                name: String
+                concerts: Set<Concert>?
+            }
+        }
+        "#;
+
+        let system = create_system(src).await;
+
+        let composite_parameters = |type_name: &str| {
+            let (_, order_by_type) = system
+                .order_by_types
+                .iter()
+                .find(|(_, t)| t.name == type_name)
+                .unwrap();
+            match &order_by_type.kind {
+                OrderByParameterTypeKind::Composite { parameters }
+                | OrderByParameterTypeKind::Aggregate { parameters, .. } => parameters
+                    .iter()
+                    .map(|p| (p.name.clone(), p.typ.name().to_string()))
+                    .collect::<Vec<_>>(),
+                _ => panic!("Expected a composite or aggregate type"),
+            }
+        };
+
+        // The one-to-many relation may be used to order by aggregates over the related entities
+        assert!(composite_parameters("VenueOrdering")
+            .contains(&("concertsAgg".to_string(), "ConcertAggOrdering".to_string())));
+
+        // Only scalar fields may be aggregated
+        assert_eq!(
+            composite_parameters("ConcertAggOrdering"),
+            vec![
+                ("id".to_string(), "IntAggOrdering".to_string()),
+                ("title".to_string(), "StringAggOrdering".to_string()),
+                ("price".to_string(), "IntAggOrdering".to_string()),
+            ]
+        );
+
+        // The aggregate ordering types are shared with group-by queries, so must be defined once
+        let aggregate_ordering_count = system
+            .schema_types()
+            .into_iter()
+            .filter(|t| t.name.node.as_str() == "ConcertAggOrdering")
+            .count();
+        assert_eq!(aggregate_ordering_count, 1);
+    }
+
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn upsert_mutations() {
+        let src = r#"
+        @postgres
+        module ConcertModule {
+            @access(true)
+            type Concert {
+                @pk id: Int = autoIncrement()
+                @unique title: String
+            }
+
+            @access(true)
+            type Account {
+                @pk code: String
+                @unique("email") email: String
+                @unique("name") firstName: String
+                @unique("name") lastName: String
+            }
+        }
+        "#;
+
+        let system = create_system(src).await;
+
+        let upsert_names = system
+            .mutations
+            .iter()
+            .map(|(_, m)| m.name.clone())
+            .filter(|name| name.starts_with("upsert"))
+            .collect::<HashSet<String>>();
+
+        // No `upsertConcert`, since the primary key is auto-incremented (and thus never supplied)
+        assert_eq!(
+            upsert_names,
+            HashSet::from(
+                [
+                    "upsertConcertByTitle",
+                    "upsertConcertsByTitle",
+                    "upsertAccount",
+                    "upsertAccounts",
+                    "upsertAccountByEmail",
+                    "upsertAccountsByEmail",
+                    "upsertAccountByName",
+                    "upsertAccountsByName",
+                ]
+                .map(|name| name.to_string())
+            )
+        );
+
+        let conflict_column_names = |mutation_name: &str| match &system
+            .mutations
+            .get_by_key(mutation_name)
+            .unwrap()
+            .parameters
+        {
+            PostgresMutationParameters::Upsert {
+                conflict_columns, ..
+            } => conflict_columns
+                .iter()
+                .map(|column_id| column_id.get_column(&system.database).name.clone())
+                .collect::<Vec<_>>(),
+            _ => panic!("Expected an upsert mutation"),
+        };
+
+        assert_eq!(conflict_column_names("upsertAccount"), vec!["code"]);
+        assert_eq!(
+            conflict_column_names("upsertAccountsByName"),
+            vec!["first_name", "last_name"]
+        );
+        assert_eq!(conflict_column_names("upsertConcertByTitle"), vec!["title"]);
+
+        let data_param_type = system
+            .mutations
+            .get_by_key("upsertAccounts")
+            .unwrap()
+            .parameters()[0]
+            .typ()
+            .to_string();
+        assert_eq!(data_param_type, "[AccountCreationInput!]!");
+    }
+
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn composite_pk() {
+        let src = r#"
+        @postgres
+        module ConcertModule {
+            @access(true)
+            type Concert {
+                @pk id: Int = autoIncrement()
+                title: String
+                venue: Venue
+            }
+
+            @access(true)
+            type Venue {
+                @pk tenantId: Int
+                @pk id: Int
+                name: String
+                concerts: Set<Concert>?
+            }
+        }
+        "#;
+
+        let system = create_system(src).await;
+        let get_table = |n| get_table_from_arena(n, &system.database);
+
+        let venues = get_table("venues");
+        let pk_column_names = venues
+            .get_pk_physical_columns()
+            .into_iter()
+            .map(|column| column.name.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(pk_column_names, vec!["tenant_id", "id"]);
+
+        // One foreign key column per primary key column of the referenced table
+        let concerts = get_table("concerts");
+        let concerts_venue_tenant_id = get_column_from_table("venue_tenant_id", concerts);
+        let concerts_venue_id = get_column_from_table("venue_id", concerts);
+        assert!(!concerts_venue_tenant_id.is_pk);
+        assert!(!concerts_venue_id.is_pk);
+
+        let concerts_table_id = system
+            .database
+            .get_table_id(&PhysicalTableName::new("concerts", None))
+            .unwrap();
+        let relation = system
+            .database
+            .get_column_id(concerts_table_id, "venue_id")
+            .unwrap()
+            .get_mto_relation(&system.database)
+            .unwrap()
+            .deref(&system.database);
+        let self_column_names = relation
+            .self_column_ids()
+            .into_iter()
+            .map(|column_id| column_id.get_column(&system.database).name.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(self_column_names, vec!["venue_tenant_id", "venue_id"]);
+
+        // The pk query and mutations take an argument per primary key field
+        let pk_query = system.pk_queries.get_by_key("venue").unwrap();
+        let pk_param_names = pk_query
+            .parameters
+            .predicate_params
+            .iter()
+            .map(|param| param.name.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(pk_param_names, vec!["tenantId", "id"]);
+
+        let delete_param_names = system
+            .mutations
+            .get_by_key("deleteVenue")
+            .unwrap()
+            .parameters()
+            .iter()
+            .map(|param| param.name().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(delete_param_names, vec!["tenantId", "id"]);
+    }
+
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn soft_delete() {
+        let src = r#"
+        @postgres
+        module ConcertModule {
+            @access(query=true, mutation=AuthContext.role == "admin")
+            @softDelete(field="deletedAt")
+            type Concert {
+                @pk id: Int = autoIncrement()
+                @unique title: String
+                deletedAt: Instant?
+            }
+
+            @access(true)
+            type Venue {
+                @pk id: Int = autoIncrement()
+                name: String
+            }
+        }
+
+        context AuthContext {
+            @jwt role: String
+        }
+        "#;
+
+        let system = create_system(src).await;
+
+        let concert_type = system.entity_types.get_by_key("Concert").unwrap();
+        let soft_delete = concert_type.soft_delete.as_ref().unwrap();
+        assert_eq!(soft_delete.field_name, "deletedAt");
+        assert_eq!(
+            soft_delete.column_id.get_column(&system.database).name,
+            "deleted_at"
+        );
+        assert!(system
+            .entity_types
+            .get_by_key("Venue")
+            .unwrap()
+            .soft_delete
+            .is_none());
+
+        // Queries of the soft-deletable type may ask for the deleted entities as well
+        let param_names = |params: Vec<&dyn Parameter>| {
+            params
+                .iter()
+                .map(|param| param.name().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            param_names(
+                system
+                    .pk_queries
+                    .get_by_key("concert")
+                    .unwrap()
+                    .parameters()
+            ),
+            vec!["id", "includeDeleted"]
+        );
+        assert!(param_names(
+            system
+                .collection_queries
+                .get_by_key("concerts")
+                .unwrap()
+                .parameters()
+        )
+        .contains(&"includeDeleted".to_string()));
+        assert!(param_names(
+            system
+                .unique_queries
+                .get_by_key("concertByTitle")
+                .unwrap()
+                .parameters()
+        )
+        .contains(&"includeDeleted".to_string()));
+        assert!(!param_names(
+            system
+                .collection_queries
+                .get_by_key("venues")
+                .unwrap()
+                .parameters()
+        )
+        .contains(&"includeDeleted".to_string()));
+
+        let mutation_names = |prefix: &str| {
+            system
+                .mutations
+                .iter()
+                .map(|(_, m)| m.name.clone())
+                .filter(|name| name.starts_with(prefix))
+                .collect::<HashSet<String>>()
+        };
+        assert_eq!(
+            mutation_names("restore"),
+            HashSet::from(["restoreConcert", "restoreConcerts"].map(|name| name.to_string()))
+        );
+        assert!(matches!(
+            system
+                .mutations
+                .get_by_key("restoreConcert")
+                .unwrap()
+                .parameters,
+            PostgresMutationParameters::Restore(_)
+        ));
+    }
+
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn version() {
+        let src = r#"
+        @postgres
+        module ConcertModule {
+            @access(true)
+            type Concert {
+                @pk id: Int = autoIncrement()
+                @unique title: String
+                @version version: Int = 1
+            }
+        }
+        "#;
+
+        let system = create_system(src).await;
+
+        let concert_type = system.entity_types.get_by_key("Concert").unwrap();
+        let version = concert_type.version.as_ref().unwrap();
+        assert_eq!(version.field_name, "version");
+        assert_eq!(
+            version.column_id.get_column(&system.database).name,
+            "version"
+        );
+
+        let param_names = |mutation_name: &str| {
+            system
+                .mutations
+                .get_by_key(mutation_name)
+                .unwrap()
+                .parameters()
+                .iter()
+                .map(|param| param.name().to_string())
+                .collect::<Vec<_>>()
+        };
+        // Only the single update requires the expected version
+        assert_eq!(param_names("updateConcert"), vec!["id", "version", "data"]);
+        assert_eq!(param_names("updateConcerts"), vec!["where", "data"]);
+
+        // The version is maintained by updates, so may not be set directly
+        let update_type = system
+            .mutation_types
+            .get_by_key("ConcertUpdateInput")
+            .unwrap();
+        assert!(update_type
+            .fields
+            .iter()
+            .filter(|field| field.name == "version")
+            .all(|field| field.readonly));
+
+        // An upsert couldn't check the expected version, so isn't offered (even with a unique field)
+        assert!(system
+            .mutations
+            .iter()
+            .all(|(_, mutation)| !mutation.name.starts_with("upsert")));
+    }
+
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn audited() {
+        let src = r#"
+        context AuthContext {
+            @jwt id: String
+            @jwt role: String
+        }
+
+        @postgres
+        module ConcertModule {
+            @access(true)
+            @audited(user=AuthContext.id, access=AuthContext.role == "admin")
+            type Concert {
+                @pk id: Int = autoIncrement()
+                title: String
+            }
+
+            @access(true)
+            type Venue {
+                @pk id: Int = autoIncrement()
+                name: String
+            }
+        }
+        "#;
+
+        let system = create_system(src).await;
+
+        let concert_type = system.entity_types.get_by_key("Concert").unwrap();
+        let audit = concert_type.audit.as_ref().unwrap();
+        assert_eq!(audit.user.as_ref().unwrap().context_name, "AuthContext");
+
+        let concerts = get_table_from_arena("concerts", &system.database);
+        assert_eq!(
+            concerts.history_table.as_ref().unwrap().name,
+            "concerts_history"
+        );
+
+        // The history table records the primary key (without a foreign key, so that the history of
+        // deleted entities remains) along with the details of each change
+        let history = system.database.get_table(audit.history_table_id);
+        assert_eq!(
+            history
+                .columns
+                .iter()
+                .map(|column| column.name.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "history_id",
+                "id",
+                "operation",
+                "changed_at",
+                "changed_by",
+                "old_row",
+                "new_row"
+            ]
+        );
+        assert!(!get_column_from_table("id", history).is_pk);
+
+        assert!(system
+            .history_queries
+            .get_by_key("concertHistory")
+            .is_some());
+        assert!(system.history_types.get_by_key("ConcertHistory").is_some());
+
+        // Types without the annotation are not audited
+        let venue_type = system.entity_types.get_by_key("Venue").unwrap();
+        assert!(venue_type.audit.is_none());
+        assert!(system.history_queries.get_by_key("venueHistory").is_none());
+    }
+
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn views() {
+        let src = r#"
+        @postgres
+        module ConcertModule {
+            @access(true)
+            type Venue {
+                @pk id: Int = autoIncrement()
+                name: String
+                summaries: Set<VenueSummary>?
+            }
+
+            @access(true)
+            @view("venue_summaries")
+            type VenueSummary {
+                @pk id: Int
+                venue: Venue
+                concertCount: Int
+            }
+
+            @access(query=true, mutation=AuthContext.role == "admin")
+            @materializedView(name="venue_stats", schema="reporting")
+            type VenueStat {
+                @pk id: Int
+                totalPrice: Float
+            }
+        }
+
+        context AuthContext {
+            @jwt role: String
+        }
+        "#;
+
+        let system = create_system(src).await;
+
+        let venue_summaries = get_table_from_arena("venue_summaries", &system.database);
+        assert_eq!(venue_summaries.kind, TableKind::View);
+        let venue_stats = get_table_from_arena("venue_stats", &system.database);
+        assert_eq!(venue_stats.kind, TableKind::MaterializedView);
+        assert_eq!(venue_stats.name.schema.as_deref(), Some("reporting"));
+
+        // Views may be queried (including through relations)...
+        assert!(system.pk_queries.get_by_key("venueSummary").is_some());
+        assert!(system
+            .collection_queries
+            .get_by_key("venueSummaries")
+            .is_some());
+        assert!(system
+            .aggregate_queries
+            .get_by_key("venueSummariesAgg")
+            .is_some());
+
+        // ...but not changed (other than refreshing a materialized view)
+        let mutation_names = system
+            .mutations
+            .iter()
+            .map(|(_, m)| m.name.clone())
+            .filter(|name| !name.ends_with("Venue") && !name.ends_with("Venues"))
+            .collect::<HashSet<String>>();
+        assert_eq!(
+            mutation_names,
+            HashSet::from(["refreshVenueStats".to_string()])
+        );
+        assert!(matches!(
+            system
+                .mutations
+                .get_by_key("refreshVenueStats")
+                .unwrap()
+                .parameters,
+            PostgresMutationParameters::Refresh
+        ));
+
+        // The input types of other types don't include views
+        let mutation_type_names = get_mutation_type_names(&system);
+        assert!(mutation_type_names
+            .iter()
+            .all(|name| !name.starts_with("VenueSummary") && !name.starts_with("VenueStat")));
+        let venue_creation_type = system
+            .mutation_types
+            .get_by_key("VenueCreationInput")
+            .unwrap();
+        assert!(venue_creation_type
+            .fields
+            .iter()
+            .all(|field| field.name != "summaries"));
+    }
+
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn functions() {
+        let src = r#"
+        @postgres
+        module ConcertModule {
+            @access(true)
+            type Concert {
+                @pk id: Int = autoIncrement()
+                title: String
+            }
+
+            @access(true)
+            @function("reporting.top_concerts")
+            query topConcerts(count: Int, titles: Array<String>?): Set<Concert>
+
+            @access(AuthContext.role == "admin")
+            @function("archive_concert")
+            mutation archiveConcert(id: Int): Concert?
+
+            @function("purge_concerts")
+            mutation purgeConcerts(): Set<Concert>
+        }
+
+        context AuthContext {
+            @jwt role: String
+        }
+        "#;
+
+        let system = create_system(src).await;
+
+        let top_concerts = system.function_queries.get_by_key("topConcerts").unwrap();
+        assert_eq!(
+            top_concerts.parameters.function_name,
+            PhysicalTableName::new("top_concerts", Some("reporting"))
+        );
+        assert!(matches!(
+            top_concerts.return_type,
+            OperationReturnType::List(_)
+        ));
+        assert_eq!(
+            top_concerts
+                .parameters
+                .arguments
+                .iter()
+                .map(|argument| (argument.name.as_str(), argument.column_type.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("count", PhysicalColumnType::Int { bits: IntBits::_32 }),
+                (
+                    "titles",
+                    PhysicalColumnType::Array {
+                        typ: Box::new(PhysicalColumnType::String { max_length: None })
+                    }
+                ),
+            ]
+        );
+        assert!(matches!(
+            system.input_access_expressions[top_concerts.parameters.access],
+            AccessPredicateExpression::BooleanLiteral(true)
+        ));
+
+        let archive_concert = system
+            .function_mutations
+            .get_by_key("archiveConcert")
+            .unwrap();
+        assert_eq!(
+            archive_concert.parameters.function_name,
+            PhysicalTableName::new("archive_concert", None)
+        );
+        assert!(matches!(
+            archive_concert.return_type,
+            OperationReturnType::Optional(_)
+        ));
+        assert!(system
+            .function_queries
+            .get_by_key("archiveConcert")
+            .is_none());
+
+        // Without an access rule, no one may call the function
+        let purge_concerts = system
+            .function_mutations
+            .get_by_key("purgeConcerts")
+            .unwrap();
+        assert!(matches!(
+            system.input_access_expressions[purge_concerts.parameters.access],
+            AccessPredicateExpression::BooleanLiteral(false)
+        ));
+    }
+
     fn get_mutation_type_names(system: &PostgresSubsystem) -> HashSet<String> {
         system
             .mutation_types
@@ -30,9 +30,12 @@ use core_plugin_interface::{
 };
 
 use exo_sql::{
-    schema::index_spec::IndexKind, ColumnId, FloatBits, IntBits, ManyToOne, PhysicalColumn,
-    PhysicalColumnType, PhysicalIndex, PhysicalTable, TableId, VectorDistanceFunction,
-    DEFAULT_VECTOR_SIZE,
+    schema::index_spec::IndexKind, ColumnId, FloatBits, IntBits, ManyToManyId, ManyToOne,
+    PhysicalColumn, PhysicalColumnType, PhysicalEnum, PhysicalIndex, PhysicalTable,
+    PhysicalTableName, RelationColumnPair, TableId, TableKind, VectorDistanceFunction,
+    DEFAULT_VECTOR_SIZE, HISTORY_CHANGED_AT_COLUMN_NAME, HISTORY_CHANGED_BY_COLUMN_NAME,
+    HISTORY_ID_COLUMN_NAME, HISTORY_NEW_ROW_COLUMN_NAME, HISTORY_OLD_ROW_COLUMN_NAME,
+    HISTORY_OPERATION_COLUMN_NAME,
 };
 
 use heck::ToSnakeCase;
@@ -42,18 +45,25 @@ use postgres_model::{
         UpdateAccessExpression,
     },
     aggregate::{AggregateField, AggregateFieldType},
-    relation::{ManyToOneRelation, OneToManyRelation, PostgresRelation, RelationCardinality},
+    audit::Audit,
+    relation::{
+        ManyToManyRelation, ManyToOneRelation, OneToManyRelation, PostgresRelation,
+        RelationCardinality,
+    },
+    soft_delete::SoftDelete,
     types::{
         get_field_id, EntityType, PostgresField, PostgresFieldType, PostgresPrimitiveType,
         TypeIndex,
     },
     vector_distance::{VectorDistanceField, VectorDistanceType},
+    version::Version,
 };
 
 use super::{
     naming::ToPostgresQueryName,
     resolved_builder::{
         ResolvedCompositeType, ResolvedField, ResolvedFieldDefault, ResolvedType, ResolvedTypeHint,
+        SEARCH_VECTOR_COLUMN_NAME,
     },
     system_builder::SystemContextBuilding,
 };
@@ -84,7 +94,7 @@ pub(super) fn build_expanded(
     // Ensure that all types have a primary key
     for (_, resolved_type) in resolved_env.resolved_types.iter() {
         if let ResolvedType::Composite(c) = &resolved_type {
-            if c.pk_field().is_none() {
+            if c.pk_fields().is_empty() {
                 let diagnostic = Diagnostic {
                     level: Level::Error,
                     message: format!(
@@ -140,9 +150,45 @@ pub(super) fn build_expanded(
         }
     }
 
+    for (_, resolved_type) in resolved_env.resolved_types.iter() {
+        if let ResolvedType::Composite(c) = &resolved_type {
+            expand_type_audit(c, resolved_env, building)?;
+        }
+    }
+
+    // Create the database enums for the enum types used by columns (an enum may also be declared
+    // only for use by other subsystems)
+    for (_, resolved_type) in resolved_env.resolved_types.iter() {
+        if let ResolvedType::Primitive(PrimitiveType::Enum(enum_type)) = &resolved_type {
+            let enum_name = enum_type.name.to_snake_case();
+
+            let used = building.database.tables().iter().any(|(_, table)| {
+                table
+                    .columns
+                    .iter()
+                    .any(|column| is_enum_column_type(&column.typ, &enum_name))
+            });
+
+            if used {
+                building.database.enums.push(PhysicalEnum {
+                    name: enum_name,
+                    values: enum_type.values.clone(),
+                });
+            }
+        }
+    }
+
     Ok(())
 }
 
+fn is_enum_column_type(typ: &PhysicalColumnType, enum_name: &str) -> bool {
+    match typ {
+        PhysicalColumnType::Enum { enum_name: name } => name == enum_name,
+        PhysicalColumnType::Array { typ } => is_enum_column_type(typ, enum_name),
+        _ => false,
+    }
+}
+
 fn create_shallow_type(
     resolved_type: &ResolvedType,
     _resolved_env: &ResolvedTypeEnv,
@@ -154,6 +200,10 @@ fn create_shallow_type(
                 &resolved_type.name(),
                 PostgresPrimitiveType {
                     name: resolved_type.name(),
+                    enum_values: match pt {
+                        PrimitiveType::Enum(enum_type) => Some(enum_type.values.clone()),
+                        _ => None,
+                    },
                 },
             );
             if matches!(pt, PrimitiveType::Vector) {
@@ -163,7 +213,7 @@ fn create_shallow_type(
                     .add("VectorDistance", vector_distance_type);
             }
         }
-        ResolvedType::Composite(_) => {
+        ResolvedType::Composite(composite) => {
             let typ = EntityType {
                 name: resolved_type.name(),
                 plural_name: resolved_type.plural_name(),
@@ -175,6 +225,10 @@ fn create_shallow_type(
                 collection_query: SerializableSlabIndex::shallow(),
                 aggregate_query: SerializableSlabIndex::shallow(),
                 access: restrictive_access(),
+                cache_policy: composite.cache_policy,
+                soft_delete: None,
+                version: None,
+                audit: None,
             };
 
             building.entity_types.add(&resolved_type.name(), typ);
@@ -201,6 +255,9 @@ fn expand_type_no_fields(
         name: resolved_type.table_name.clone(),
         columns: vec![],
         indices: vec![],
+        notify_changes: resolved_type.subscribable,
+        history_table: None,
+        kind: resolved_type.table_kind,
     };
 
     let table_id = building.database.insert_table(table);
@@ -209,7 +266,8 @@ fn expand_type_no_fields(
         let columns = resolved_type
             .fields
             .iter()
-            .flat_map(|field| create_column(field, table_id, resolved_type, resolved_env))
+            .flat_map(|field| create_columns(field, table_id, resolved_type, resolved_env))
+            .chain(create_search_vector_column(resolved_type, table_id))
             .collect();
         building.database.get_table_mut(table_id).columns = columns;
     }
@@ -217,16 +275,24 @@ fn expand_type_no_fields(
     {
         let mut indices: Vec<PhysicalIndex> = vec![];
         resolved_type.fields.iter().for_each(|field| {
+            let column_names = field_column_names(field, resolved_env);
+
             field.indices.iter().for_each(|index_name| {
                 let existing_index = indices.iter_mut().find(|i| &i.name == index_name);
 
                 match existing_index {
                     Some(existing_index) => {
-                        existing_index.columns.insert(field.column_name.clone());
+                        existing_index.columns.extend(column_names.iter().cloned());
+                        // A GIN index (without extensions such as `btree_gin`) supports only
+                        // array columns, so a multi-column index with an array field uses the
+                        // default kind (which supports arrays through their comparison operators)
+                        if existing_index.index_kind == IndexKind::Gin {
+                            existing_index.index_kind = IndexKind::default();
+                        }
                     }
                     None => indices.push(PhysicalIndex {
                         name: index_name.clone(),
-                        columns: HashSet::from_iter([field.column_name.clone()]),
+                        columns: HashSet::from_iter(column_names.iter().cloned()),
                         index_kind: if field.typ.innermost().type_name == "Vector" {
                             let distance_function = match field.type_hint {
                                 Some(ResolvedTypeHint::Vector {
@@ -240,6 +306,8 @@ fn expand_type_no_fields(
                                 distance_function,
                                 params: None,
                             }
+                        } else if is_array_field(field) {
+                            IndexKind::Gin
                         } else {
                             IndexKind::default()
                         },
@@ -247,6 +315,16 @@ fn expand_type_no_fields(
                 }
             })
         });
+
+        if resolved_type.full_text_search.is_some() {
+            indices.push(PhysicalIndex {
+                name: format!("{}_{SEARCH_VECTOR_COLUMN_NAME}_idx", resolved_type.name)
+                    .to_ascii_lowercase(),
+                columns: HashSet::from([SEARCH_VECTOR_COLUMN_NAME.to_string()]),
+                index_kind: IndexKind::Gin,
+            });
+        }
+
         building.database.get_table_mut(table_id).indices = indices;
     }
 
@@ -265,12 +343,81 @@ fn expand_type_no_fields(
         .get_id(&resolved_type.aggregate_query())
         .unwrap();
 
+    let soft_delete = resolved_type.soft_delete.as_ref().map(|field_name| {
+        let field = resolved_type
+            .fields
+            .iter()
+            .find(|field| &field.name == field_name)
+            .unwrap();
+
+        SoftDelete {
+            field_name: field_name.clone(),
+            column_id: building
+                .database
+                .get_column_id(table_id, &field.column_name)
+                .unwrap(),
+        }
+    });
+
+    let version = resolved_type.version.as_ref().map(|field_name| {
+        let field = resolved_type
+            .fields
+            .iter()
+            .find(|field| &field.name == field_name)
+            .unwrap();
+
+        Version {
+            field_name: field_name.clone(),
+            column_id: building
+                .database
+                .get_column_id(table_id, &field.column_name)
+                .unwrap(),
+        }
+    });
+
     let existing_type_id = building.get_entity_type_id(&resolved_type.name).unwrap();
     let existing_type = &mut building.entity_types[existing_type_id];
     existing_type.table_id = table_id;
     existing_type.pk_query = pk_query;
     existing_type.collection_query = collection_query;
     existing_type.aggregate_query = aggregate_query;
+    existing_type.soft_delete = soft_delete;
+    existing_type.version = version;
+}
+
+/// The generated `tsvector` column for a type with the `@fullTextSearch` annotation. Its value is
+/// computed by the database from the searched fields' columns.
+fn create_search_vector_column(
+    resolved_type: &ResolvedCompositeType,
+    table_id: TableId,
+) -> Option<PhysicalColumn> {
+    resolved_type
+        .full_text_search
+        .as_ref()
+        .map(|full_text_search| PhysicalColumn {
+            table_id,
+            name: SEARCH_VECTOR_COLUMN_NAME.to_string(),
+            typ: PhysicalColumnType::TextSearchVector {
+                language: full_text_search.language.clone(),
+                columns: full_text_search
+                    .fields
+                    .iter()
+                    .filter_map(|field_name| {
+                        resolved_type
+                            .fields
+                            .iter()
+                            .find(|field| &field.name == field_name)
+                            .map(|field| field.column_name.clone())
+                    })
+                    .collect(),
+            },
+            is_pk: false,
+            is_auto_increment: false,
+            is_nullable: true,
+            unique_constraints: vec![],
+            default_value: None,
+            update_sync: false,
+        })
 }
 
 fn expand_type_relations(
@@ -283,23 +430,88 @@ fn expand_type_relations(
 
     resolved_type.fields.iter().for_each(|field| {
         if field.self_column {
-            let self_column_id = building
-                .database
-                .get_column_id(table_id, &field.column_name)
-                .unwrap();
             if let Some(relation) =
-                compute_many_to_one_relation(field, self_column_id, resolved_env, building)
+                compute_many_to_one_relation(field, table_id, resolved_env, building)
             {
                 // In the earlier phase, we set the type of a many-to-one column to a placeholder value
                 // Now that we have the foreign type, we can set the type of the column to the foreign type's PK
-                let foreign_column_typ = &relation
-                    .foreign_pk_column_id
-                    .get_column(&building.database)
-                    .typ;
-                building.database.get_column_mut(self_column_id).typ = foreign_column_typ.clone();
+                for pair in relation.column_pairs.iter() {
+                    let foreign_column_typ =
+                        &pair.foreign_column_id.get_column(&building.database).typ;
+                    building.database.get_column_mut(pair.self_column_id).typ =
+                        foreign_column_typ.clone();
+                }
                 building.database.relations.push(relation);
             }
         }
+
+        if let Some(linking_table_name) = &field.many_to_many_table {
+            expand_linking_table(linking_table_name, field, resolved_type, table_id, building);
+        }
+    });
+}
+
+/// Create (if not already created by the other side of the relation) the linking table for a
+/// many-to-many field along with the columns (and the relation) that refer to the enclosing type.
+///
+/// For the `Concert.artists` field (assuming Concert <-> Artist through the `concert_artists`
+/// table), this creates the `concert_artists` table with the `concert_id` column (referring to
+/// `concerts.id`). The `artist_id` column is created when processing the `Artist.concerts` field.
+/// Together, the columns form the primary key of the linking table.
+fn expand_linking_table(
+    linking_table_name: &PhysicalTableName,
+    field: &ResolvedField,
+    resolved_type: &ResolvedCompositeType,
+    table_id: TableId,
+    building: &mut SystemContextBuilding,
+) {
+    let linking_table_id = match building.database.get_table_id(linking_table_name) {
+        Some(linking_table_id) => linking_table_id,
+        None => building.database.insert_table(PhysicalTable {
+            name: linking_table_name.clone(),
+            columns: vec![],
+            indices: vec![],
+            notify_changes: false,
+            history_table: None,
+            kind: TableKind::Table,
+        }),
+    };
+
+    let pk_column_ids = building.database.get_pk_column_ids(table_id);
+
+    let column_pairs = resolved_type
+        .foreign_key_column_names(&field.column_name)
+        .into_iter()
+        .zip(pk_column_ids)
+        .map(|(column_name, pk_column_id)| {
+            let pk_column_typ = pk_column_id.get_column(&building.database).typ.clone();
+
+            let linking_table = building.database.get_table_mut(linking_table_id);
+            linking_table.columns.push(PhysicalColumn {
+                table_id: linking_table_id,
+                name: column_name,
+                typ: pk_column_typ,
+                is_pk: true,
+                is_auto_increment: false,
+                is_nullable: false,
+                unique_constraints: vec![],
+                default_value: None,
+                update_sync: false,
+            });
+
+            RelationColumnPair {
+                self_column_id: ColumnId {
+                    table_id: linking_table_id,
+                    column_index: linking_table.columns.len() - 1,
+                },
+                foreign_column_id: pk_column_id,
+            }
+        })
+        .collect();
+
+    building.database.relations.push(ManyToOne {
+        column_pairs,
+        foreign_table_alias: None,
     });
 }
 
@@ -418,14 +630,22 @@ fn expand_dynamic_default_values(
                                     }
                                 }
                                 PostgresRelation::ManyToOne(ManyToOneRelation {
-                                    foreign_pk_field_id: foreign_field_id,
+                                    ref foreign_pk_field_ids,
                                     ..
                                 }) => {
-                                    let foreign_type_pk = &foreign_field_id
-                                        .resolve(building.entity_types.values_ref())
-                                        .typ;
-
-                                    if !matches(foreign_type_pk, context_type) {
+                                    // A context value can supply only a single-column primary key
+                                    let foreign_type_pk = match foreign_pk_field_ids.as_slice() {
+                                        [foreign_field_id] => Some(
+                                            &foreign_field_id
+                                                .resolve(building.entity_types.values_ref())
+                                                .typ,
+                                        ),
+                                        _ => None,
+                                    };
+
+                                    if !foreign_type_pk
+                                        .is_some_and(|typ| matches(typ, context_type))
+                                    {
                                         Err(ModelBuildingError::Generic(
                                             "Type of default value does not match field type"
                                                 .to_string(),
@@ -486,6 +706,129 @@ fn expand_type_access(
     Ok(())
 }
 
+/// Create the history table for a type with the `@audited` annotation (such as `concerts_history`
+/// for the `Concert` type) and set up the recording of changes to the type.
+///
+/// Besides the primary key columns of the type's table (without a foreign key, so that the history
+/// of a deleted entity remains), the history table records the kind of change, its time, the
+/// acting user, and the row before and after the change.
+fn expand_type_audit(
+    resolved_type: &ResolvedCompositeType,
+    resolved_env: &ResolvedTypeEnv,
+    building: &mut SystemContextBuilding,
+) -> Result<(), ModelBuildingError> {
+    let resolved_audit = match &resolved_type.audit {
+        Some(resolved_audit) => resolved_audit,
+        None => return Ok(()),
+    };
+
+    let existing_type_id = building.get_entity_type_id(&resolved_type.name).unwrap();
+    let table_id = building.entity_types[existing_type_id].table_id;
+
+    let user = match &resolved_audit.user {
+        Some(AstExpr::FieldSelection(selection)) => {
+            let (context_selection, _) =
+                selection.get_context(resolved_env.contexts, resolved_env.function_definitions)?;
+            Some(context_selection)
+        }
+        Some(_) => {
+            return Err(ModelBuildingError::Generic(format!(
+                "The user of @audited for type '{}' must be a context field",
+                resolved_type.name
+            )))
+        }
+        None => None,
+    };
+
+    let access = compute_input_access_expr(
+        &[&resolved_audit.access],
+        existing_type_id,
+        resolved_env,
+        building,
+    )?;
+
+    let history_table_name = PhysicalTableName {
+        name: format!("{}_history", resolved_type.table_name.name),
+        schema: resolved_type.table_name.schema.clone(),
+    };
+
+    let history_table_id = building.database.insert_table(PhysicalTable {
+        name: history_table_name.clone(),
+        columns: vec![],
+        indices: vec![],
+        notify_changes: false,
+        history_table: None,
+        kind: TableKind::Table,
+    });
+
+    let history_column = |name: &str, typ: PhysicalColumnType, is_nullable: bool| PhysicalColumn {
+        table_id: history_table_id,
+        name: name.to_string(),
+        typ,
+        is_pk: false,
+        is_auto_increment: false,
+        is_nullable,
+        unique_constraints: vec![],
+        default_value: None,
+        update_sync: false,
+    };
+
+    let columns = std::iter::once(PhysicalColumn {
+        is_pk: true,
+        is_auto_increment: true,
+        ..history_column(
+            HISTORY_ID_COLUMN_NAME,
+            PhysicalColumnType::Int { bits: IntBits::_64 },
+            false,
+        )
+    })
+    .chain(
+        building
+            .database
+            .get_table(table_id)
+            .get_pk_physical_columns()
+            .into_iter()
+            .map(|pk_column| history_column(&pk_column.name, pk_column.typ.clone(), false)),
+    )
+    .chain([
+        history_column(
+            HISTORY_OPERATION_COLUMN_NAME,
+            PhysicalColumnType::String { max_length: None },
+            false,
+        ),
+        PhysicalColumn {
+            default_value: Some("now()".to_string()),
+            ..history_column(
+                HISTORY_CHANGED_AT_COLUMN_NAME,
+                PhysicalColumnType::Timestamp {
+                    timezone: true,
+                    precision: None,
+                },
+                false,
+            )
+        },
+        history_column(
+            HISTORY_CHANGED_BY_COLUMN_NAME,
+            PhysicalColumnType::String { max_length: None },
+            true,
+        ),
+        history_column(HISTORY_OLD_ROW_COLUMN_NAME, PhysicalColumnType::Json, true),
+        history_column(HISTORY_NEW_ROW_COLUMN_NAME, PhysicalColumnType::Json, true),
+    ])
+    .collect();
+
+    building.database.get_table_mut(history_table_id).columns = columns;
+    building.database.get_table_mut(table_id).history_table = Some(history_table_name);
+
+    building.entity_types[existing_type_id].audit = Some(Audit {
+        history_table_id,
+        user,
+        access,
+    });
+
+    Ok(())
+}
+
 /// Compute access expression for database access.
 ///
 /// Goes over the chain of the expressions and maps the first non-optional expression to a database access expression.
@@ -529,7 +872,7 @@ fn compute_database_access_expr(
     })
 }
 
-fn compute_input_access_expr(
+pub(crate) fn compute_input_access_expr(
     ast_exprs: &[&Option<AstExpr<Typed>>],
     entity_id: SerializableSlabIndex<EntityType>,
     resolved_env: &ResolvedTypeEnv,
@@ -640,6 +983,7 @@ fn create_persistent_field(
         has_default_value: field.default_value.is_some(),
         dynamic_default_value: None,
         readonly: field.readonly || field.update_sync,
+        cache_policy: field.cache_policy,
     })
 }
 
@@ -717,14 +1061,20 @@ fn create_vector_distance_field(
     }
 }
 
-fn default_value(field: &ResolvedField) -> Option<String> {
+fn default_value(field: &ResolvedField, env: &ResolvedTypeEnv) -> Option<String> {
+    // A string literal is either a text or (for an enum field) an enum value
+    let string_type = match field.typ.deref(env) {
+        ResolvedType::Primitive(PrimitiveType::Enum(enum_type)) => enum_type.name.to_snake_case(),
+        _ => "text".to_string(),
+    };
+
     field
         .default_value
         .as_ref()
         .and_then(|default_value| match default_value {
             ResolvedFieldDefault::Value(val) => match &**val {
                 AstExpr::StringLiteral(string, _) => {
-                    Some(format!("'{}'::text", string.replace('\'', "''")))
+                    Some(format!("'{}'::{string_type}", string.replace('\'', "''")))
                 }
                 AstExpr::BooleanLiteral(boolean, _) => Some(format!("{boolean}")),
                 AstExpr::NumberLiteral(val, _) => Some(format!("{val}")),
@@ -736,15 +1086,34 @@ fn default_value(field: &ResolvedField) -> Option<String> {
         })
 }
 
-fn create_column(
+/// The names of the columns for a field (multiple columns for a reference to a type with a
+/// composite primary key)
+fn field_column_names(field: &ResolvedField, env: &ResolvedTypeEnv) -> Vec<String> {
+    match field.typ.base_type() {
+        FieldType::Plain(ResolvedFieldType { type_name, .. }) => match env.get_by_key(type_name) {
+            Some(ResolvedType::Composite(foreign_type)) => {
+                foreign_type.foreign_key_column_names(&field.column_name)
+            }
+            _ => vec![field.column_name.clone()],
+        },
+        _ => vec![field.column_name.clone()],
+    }
+}
+
+/// Is the field an array of a primitive type (such as `Array<String>`)?
+fn is_array_field(field: &ResolvedField) -> bool {
+    matches!(field.typ.base_type(), FieldType::List(_)) && field.typ.innermost().is_primitive
+}
+
+fn create_columns(
     field: &ResolvedField,
     table_id: TableId,
     resolved_type: &ResolvedCompositeType,
     env: &ResolvedTypeEnv,
-) -> Option<PhysicalColumn> {
+) -> Vec<PhysicalColumn> {
     // Check that the field holds to a self column
     let unique_constraint_name = if !field.self_column {
-        return None;
+        return vec![];
     } else {
         field
             .unique_constraints
@@ -760,7 +1129,7 @@ fn create_column(
         _ => (&field.typ, false),
     };
 
-    let default_value = default_value(field);
+    let default_value = default_value(field, env);
     let update_sync = field.update_sync;
 
     match typ {
@@ -769,7 +1138,7 @@ fn create_column(
             let field_type = env.get_by_key(type_name).unwrap();
 
             match field_type {
-                ResolvedType::Primitive(pt) => Some(PhysicalColumn {
+                ResolvedType::Primitive(pt) => vec![PhysicalColumn {
                     table_id,
                     name: field.column_name.to_string(),
                     typ: determine_column_type(pt, field),
@@ -787,22 +1156,26 @@ fn create_column(
                     unique_constraints: unique_constraint_name,
                     default_value,
                     update_sync,
-                }),
-                ResolvedType::Composite(_) => {
+                }],
+                ResolvedType::Composite(foreign_type) => {
                     // Many-to-one:
-                    // Column from the current table (but of the type of the pk column of the other table)
-                    // and it refers to the pk column in the other table.
-                    Some(PhysicalColumn {
-                        table_id,
-                        name: field.column_name.to_string(),
-                        typ: PhysicalColumnType::Boolean, // A placeholder value. Will be resolved in the next phase (see expand_type_relations)
-                        is_pk: false,
-                        is_auto_increment: false,
-                        is_nullable: optional,
-                        unique_constraints: unique_constraint_name,
-                        default_value,
-                        update_sync,
-                    })
+                    // Columns from the current table (but of the type of the pk columns of the other table)
+                    // and they refer to the pk columns in the other table.
+                    foreign_type
+                        .foreign_key_column_names(&field.column_name)
+                        .into_iter()
+                        .map(|column_name| PhysicalColumn {
+                            table_id,
+                            name: column_name,
+                            typ: PhysicalColumnType::Boolean, // A placeholder value. Will be resolved in the next phase (see expand_type_relations)
+                            is_pk: false,
+                            is_auto_increment: false,
+                            is_nullable: optional,
+                            unique_constraints: unique_constraint_name.clone(),
+                            default_value: default_value.clone(),
+                            update_sync,
+                        })
+                        .collect()
                 }
             }
         }
@@ -839,7 +1212,7 @@ fn create_column(
                     pt = PrimitiveType::Array(Box::new(pt))
                 }
 
-                Some(PhysicalColumn {
+                vec![PhysicalColumn {
                     table_id,
                     name: field.column_name.to_string(),
                     typ: determine_column_type(&pt, field),
@@ -849,10 +1222,10 @@ fn create_column(
                     unique_constraints: unique_constraint_name,
                     default_value,
                     update_sync,
-                })
+                }]
             } else {
                 // this is a OneToMany relation, so the other side has the associated column
-                None
+                vec![]
             }
         }
         FieldType::Optional(_) => panic!("Optional in an Optional?"),
@@ -861,7 +1234,7 @@ fn create_column(
 
 fn compute_many_to_one_relation(
     field: &ResolvedField,
-    self_column_id: ColumnId,
+    self_table_id: TableId,
     env: &ResolvedTypeEnv,
     building: &SystemContextBuilding,
 ) -> Option<ManyToOne> {
@@ -874,20 +1247,30 @@ fn compute_many_to_one_relation(
             let field_type = env.get_by_key(type_name).unwrap();
             match field_type {
                 ResolvedType::Composite(ct) => {
-                    // Column from the current table (but of the type of the pk column of the other table)
-                    // and it refers to the pk column in the other table.
+                    // Columns from the current table (but of the type of the pk columns of the other table)
+                    // and they refer to the pk columns in the other table.
 
                     let foreign_table_id = building.database.get_table_id(&ct.table_name).unwrap();
-                    let foreign_pk_column_id = building
-                        .database
-                        .get_pk_column_id(foreign_table_id)
-                        .unwrap();
+                    let foreign_pk_column_ids =
+                        building.database.get_pk_column_ids(foreign_table_id);
+
+                    let column_pairs = ct
+                        .foreign_key_column_names(&field.column_name)
+                        .iter()
+                        .zip(foreign_pk_column_ids)
+                        .map(|(self_column_name, foreign_column_id)| RelationColumnPair {
+                            self_column_id: building
+                                .database
+                                .get_column_id(self_table_id, self_column_name)
+                                .unwrap(),
+                            foreign_column_id,
+                        })
+                        .collect();
 
                     let field_alias = field.name.to_snake_case().to_plural();
 
                     Some(ManyToOne {
-                        self_column_id,
-                        foreign_pk_column_id,
+                        column_pairs,
                         foreign_table_alias: Some(field_alias),
                     })
                 }
@@ -1023,40 +1406,48 @@ fn determine_column_type<'a>(
             }
         }
     } else {
-        match pt {
-            // choose a default SQL type
-            PrimitiveType::Int => PhysicalColumnType::Int { bits: IntBits::_32 },
-            PrimitiveType::Float => PhysicalColumnType::Float {
-                bits: FloatBits::_24,
-            },
-            PrimitiveType::Decimal => PhysicalColumnType::Numeric {
-                precision: None,
-                scale: None,
-            },
-            PrimitiveType::String => PhysicalColumnType::String { max_length: None },
-            PrimitiveType::Boolean => PhysicalColumnType::Boolean,
-            PrimitiveType::LocalTime => PhysicalColumnType::Time { precision: None },
-            PrimitiveType::LocalDateTime => PhysicalColumnType::Timestamp {
-                precision: None,
-                timezone: false,
-            },
-            PrimitiveType::LocalDate => PhysicalColumnType::Date,
-            PrimitiveType::Instant => PhysicalColumnType::Timestamp {
-                precision: None,
-                timezone: true,
-            },
-            PrimitiveType::Json => PhysicalColumnType::Json,
-            PrimitiveType::Blob => PhysicalColumnType::Blob,
-            PrimitiveType::Uuid => PhysicalColumnType::Uuid,
-            PrimitiveType::Vector => PhysicalColumnType::Vector {
-                size: DEFAULT_VECTOR_SIZE,
-            },
-            PrimitiveType::Array(_)
-            | PrimitiveType::Exograph
-            | PrimitiveType::ExographPriv
-            | PrimitiveType::Interception(_) => {
-                panic!()
-            }
+        default_column_type(pt)
+    }
+}
+
+/// The column type for a primitive type without any hints (such as `@dbtype` or `@maxLength`)
+pub(crate) fn default_column_type(pt: &PrimitiveType) -> PhysicalColumnType {
+    match pt {
+        // choose a default SQL type
+        PrimitiveType::Int => PhysicalColumnType::Int { bits: IntBits::_32 },
+        PrimitiveType::Float => PhysicalColumnType::Float {
+            bits: FloatBits::_24,
+        },
+        PrimitiveType::Decimal => PhysicalColumnType::Numeric {
+            precision: None,
+            scale: None,
+        },
+        PrimitiveType::String => PhysicalColumnType::String { max_length: None },
+        PrimitiveType::Boolean => PhysicalColumnType::Boolean,
+        PrimitiveType::LocalTime => PhysicalColumnType::Time { precision: None },
+        PrimitiveType::LocalDateTime => PhysicalColumnType::Timestamp {
+            precision: None,
+            timezone: false,
+        },
+        PrimitiveType::LocalDate => PhysicalColumnType::Date,
+        PrimitiveType::Instant => PhysicalColumnType::Timestamp {
+            precision: None,
+            timezone: true,
+        },
+        PrimitiveType::Json => PhysicalColumnType::Json,
+        PrimitiveType::Blob => PhysicalColumnType::Blob,
+        PrimitiveType::Uuid => PhysicalColumnType::Uuid,
+        PrimitiveType::Vector => PhysicalColumnType::Vector {
+            size: DEFAULT_VECTOR_SIZE,
+        },
+        PrimitiveType::Enum(enum_type) => PhysicalColumnType::Enum {
+            enum_name: enum_type.name.to_snake_case(),
+        },
+        PrimitiveType::Array(underlying_pt) => PhysicalColumnType::Array {
+            typ: Box::new(default_column_type(underlying_pt)),
+        },
+        PrimitiveType::Exograph | PrimitiveType::ExographPriv | PrimitiveType::Interception(_) => {
+            panic!()
         }
     }
 }
@@ -1080,6 +1471,10 @@ fn create_relation(
 
     let self_type = &building.entity_types[type_id];
     let self_table_id = &self_type.table_id;
+    let self_resolved_type = resolved_env
+        .get_by_key(&self_type.name)
+        .unwrap()
+        .as_composite();
 
     if field.is_pk {
         let column_id = building
@@ -1092,6 +1487,19 @@ fn create_relation(
         let field_base_typ = &field.typ.base_type();
 
         match field_base_typ {
+            FieldType::List(underlying) if field.many_to_many_table.is_some() => {
+                let foreign_field_type = underlying.deref(resolved_env).as_composite();
+                if expand_foreign_relations {
+                    compute_many_to_many_relation(
+                        field,
+                        self_resolved_type,
+                        foreign_field_type,
+                        building,
+                    )
+                } else {
+                    placeholder_relation()
+                }
+            }
             FieldType::List(underlying) => {
                 // Since the field type is a list, the relation depends on the underlying type.
                 // 1. If it is a primitive, we treat it as a scalar ("List" of a primitive type is still a scalar from the database perspective)
@@ -1107,6 +1515,7 @@ fn create_relation(
                         if expand_foreign_relations {
                             compute_many_to_one(
                                 field,
+                                self_resolved_type,
                                 foreign_field_type,
                                 RelationCardinality::Unbounded,
                                 building,
@@ -1143,6 +1552,7 @@ fn create_relation(
                                 if expand_foreign_relations {
                                     compute_many_to_one(
                                         field,
+                                        self_resolved_type,
                                         foreign_field_type,
                                         RelationCardinality::Optional,
                                         building,
@@ -1198,6 +1608,7 @@ fn create_relation(
 
 fn compute_many_to_one(
     field: &ResolvedField,
+    self_resolved_type: &ResolvedCompositeType,
     foreign_field_type: &ResolvedCompositeType,
     cardinality: RelationCardinality,
     building: &SystemContextBuilding,
@@ -1211,9 +1622,11 @@ fn compute_many_to_one(
     let foreign_type = &building.entity_types[foreign_type_id];
     let foreign_table_id = foreign_type.table_id;
 
+    // With a composite primary key, any of the foreign key columns identifies the relation
+    let foreign_column_name = &self_resolved_type.foreign_key_column_names(&field.column_name)[0];
     let foreign_column_id = building
         .database
-        .get_column_id(foreign_table_id, &field.column_name)
+        .get_column_id(foreign_table_id, foreign_column_name)
         .unwrap();
 
     let foreign_resolved_field = foreign_field_type
@@ -1254,21 +1667,79 @@ fn compute_one_to_many_relation(
         .unwrap();
     let foreign_type = &building.entity_types[foreign_type_id];
 
+    // With a composite primary key, any of the foreign key columns identifies the relation
+    let self_column_name = &foreign_field_type.foreign_key_column_names(&field.column_name)[0];
     let self_column_id = building
         .database
-        .get_column_id(*self_table_id, &field.column_name)
+        .get_column_id(*self_table_id, self_column_name)
         .unwrap();
-    let foreign_pk_field_id = foreign_type.pk_field_id(foreign_type_id).unwrap();
+    let foreign_pk_field_ids = foreign_type.pk_field_ids(foreign_type_id);
 
     let relation_id = self_column_id.get_mto_relation(&building.database).unwrap();
 
     PostgresRelation::ManyToOne(ManyToOneRelation {
         cardinality,
-        foreign_pk_field_id,
+        foreign_pk_field_ids,
         relation_id,
     })
 }
 
+fn compute_many_to_many_relation(
+    field: &ResolvedField,
+    self_resolved_type: &ResolvedCompositeType,
+    foreign_field_type: &ResolvedCompositeType,
+    building: &SystemContextBuilding,
+) -> PostgresRelation {
+    let linking_table_id = building
+        .database
+        .get_table_id(field.many_to_many_table.as_ref().unwrap())
+        .unwrap();
+
+    let foreign_type_id = building
+        .get_entity_type_id(&foreign_field_type.name)
+        .unwrap();
+
+    // The field on the other side of the relation (through the same linking table)
+    let foreign_resolved_field = foreign_field_type
+        .fields
+        .iter()
+        .find(|f| f.many_to_many_table == field.many_to_many_table)
+        .unwrap();
+
+    let foreign_field_id = get_field_id(
+        building.entity_types.values_ref(),
+        foreign_type_id,
+        &foreign_resolved_field.name,
+    )
+    .unwrap();
+
+    // With a composite primary key, any of the linking table's columns (referring to the same
+    // side) identifies the relation
+    let linking_column_id = |resolved_type: &ResolvedCompositeType, column_name: &str| {
+        let linking_column_name = &resolved_type.foreign_key_column_names(column_name)[0];
+        building
+            .database
+            .get_column_id(linking_table_id, linking_column_name)
+            .unwrap()
+    };
+
+    let self_link_id = linking_column_id(self_resolved_type, &field.column_name)
+        .get_otm_relation(&building.database)
+        .unwrap();
+    let foreign_link_id =
+        linking_column_id(foreign_field_type, &foreign_resolved_field.column_name)
+            .get_mto_relation(&building.database)
+            .unwrap();
+
+    PostgresRelation::ManyToMany(ManyToManyRelation {
+        foreign_field_id,
+        relation_id: ManyToManyId {
+            self_link_id,
+            foreign_link_id,
+        },
+    })
+}
+
 fn restrictive_access() -> Access {
     Access {
         creation: SerializableSlabIndex::shallow(),
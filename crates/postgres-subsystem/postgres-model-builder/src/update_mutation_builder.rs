@@ -19,8 +19,12 @@ use core_plugin_interface::{
 };
 use postgres_model::{
     mutation::{DataParameter, DataParameterType, PostgresMutationParameters},
-    relation::PostgresRelation,
-    types::{EntityType, MutationType, PostgresField, PostgresFieldType, TypeIndex},
+    relation::{ManyToManyRelation, PostgresRelation},
+    types::{
+        EntityType, MutationType, PostgresField, PostgresFieldType, PostgresPrimitiveType,
+        TypeIndex,
+    },
+    version::{VersionParameter, VersionParameterType, VERSION_PARAM_NAME},
 };
 
 use crate::{mutation_builder::DataParamRole, shallow::Shallow, utils::to_mutation_type};
@@ -44,11 +48,23 @@ impl Builder for UpdateMutationBuilder {
         types: &MappedArena<ResolvedType>,
     ) -> Vec<String> {
         // TODO: This implementation is the same for CreateMutationBuilder. Fix it when we refactor non-mutations builders
-        if !resolved_composite_type.access.update_allowed() {
+        if !resolved_composite_type.access.update_allowed()
+            || resolved_composite_type.table_kind.is_view()
+        {
             return vec![];
         }
         let mut field_types = self.data_param_field_type_names(resolved_composite_type, types);
         field_types.push(Self::data_param_type_name(resolved_composite_type));
+
+        // Many-to-many relations are symmetric, so if this type has a many-to-many field, some
+        // other type refers to it through a many-to-many field and needs the connect/disconnect type
+        if resolved_composite_type
+            .fields
+            .iter()
+            .any(|field| field.many_to_many_table.is_some())
+        {
+            field_types.push(resolved_composite_type.name.many_to_many_update_type());
+        }
         field_types
     }
 
@@ -57,17 +73,21 @@ impl Builder for UpdateMutationBuilder {
         &self,
         building: &mut SystemContextBuilding,
     ) -> Result<(), ModelBuildingError> {
-        let update_access_is_false = |entity_type: &EntityType| -> bool {
-            matches!(
-                building.input_access_expressions.borrow()[entity_type.access.update.input],
-                AccessPredicateExpression::BooleanLiteral(false)
-            ) || matches!(
-                building.database_access_expressions.borrow()[entity_type.access.update.database],
-                AccessPredicateExpression::BooleanLiteral(false)
-            )
+        // Views are read-only
+        let update_not_allowed = |entity_type: &EntityType| -> bool {
+            entity_type.is_view(&building.database)
+                || matches!(
+                    building.input_access_expressions.borrow()[entity_type.access.update.input],
+                    AccessPredicateExpression::BooleanLiteral(false)
+                )
+                || matches!(
+                    building.database_access_expressions.borrow()
+                        [entity_type.access.update.database],
+                    AccessPredicateExpression::BooleanLiteral(false)
+                )
         };
         for (_, entity_type) in building.entity_types.iter() {
-            if !update_access_is_false(entity_type) {
+            if !update_not_allowed(entity_type) {
                 for (existing_id, expanded_type) in
                     self.expanded_data_type(entity_type, building, Some(entity_type), None, false)?
                 {
@@ -76,8 +96,18 @@ impl Builder for UpdateMutationBuilder {
             }
         }
 
+        for (_, entity_type) in building.entity_types.iter() {
+            if !update_not_allowed(entity_type) {
+                if let Some((existing_id, expanded_type)) =
+                    expanded_many_to_many_type(entity_type, building)
+                {
+                    building.mutation_types[existing_id] = expanded_type;
+                }
+            }
+        }
+
         for (entity_type_id, entity_type) in building.entity_types.iter() {
-            if !update_access_is_false(entity_type) {
+            if !update_not_allowed(entity_type) {
                 for mutation in self.build_mutations(entity_type_id, entity_type, building) {
                     building.mutations.add(&mutation.name.to_owned(), mutation);
                 }
@@ -99,11 +129,12 @@ impl MutationBuilder for UpdateMutationBuilder {
     ) -> PostgresMutationParameters {
         PostgresMutationParameters::Update {
             data_param: Self::data_param(entity_type, building, false),
-            predicate_param: query_builder::pk_predicate_param(
+            predicate_params: query_builder::pk_predicate_params(
                 entity_type,
                 &building.predicate_types,
                 &building.database,
             ),
+            version_param: version_param(entity_type, &building.primitive_types),
         }
     }
 
@@ -124,14 +155,34 @@ impl MutationBuilder for UpdateMutationBuilder {
     ) -> PostgresMutationParameters {
         PostgresMutationParameters::Update {
             data_param: Self::data_param(entity_type, building, true),
-            predicate_param: query_builder::collection_predicate_param(
+            predicate_params: vec![query_builder::collection_predicate_param(
                 entity_type,
                 &building.predicate_types,
-            ),
+            )],
+            // Collection updates increment the version without checking it
+            version_param: None,
         }
     }
 }
 
+/// The required `version` parameter of `update<Type>` for a type with a `@version` field
+fn version_param(
+    entity_type: &EntityType,
+    primitive_types: &MappedArena<PostgresPrimitiveType>,
+) -> Option<VersionParameter> {
+    entity_type.version.as_ref()?;
+
+    let param_type_name = "Int".to_string();
+
+    Some(VersionParameter {
+        name: VERSION_PARAM_NAME.to_string(),
+        typ: FieldType::Plain(VersionParameterType {
+            type_name: param_type_name.clone(),
+            type_id: primitive_types.get_id(&param_type_name).unwrap(),
+        }),
+    })
+}
+
 impl DataParamBuilder<DataParameter> for UpdateMutationBuilder {
     fn mark_fields_optional() -> bool {
         true
@@ -145,6 +196,10 @@ impl DataParamBuilder<DataParameter> for UpdateMutationBuilder {
         entity_type_name.update_type()
     }
 
+    fn many_to_many_data_type_name(field_type_name: &str) -> String {
+        field_type_name.many_to_many_update_type()
+    }
+
     fn data_param_role() -> DataParamRole {
         DataParamRole::Update
     }
@@ -242,6 +297,7 @@ impl DataParamBuilder<DataParameter> for UpdateMutationBuilder {
                         has_default_value: field.has_default_value,
                         dynamic_default_value: None,
                         readonly: field.readonly,
+                        cache_policy: None,
                     }
                 })
                 .collect();
@@ -277,18 +333,25 @@ impl DataParamBuilder<DataParameter> for UpdateMutationBuilder {
                         let base_type = tpe.1.clone();
                         let mut base_type_fields = base_type.fields;
 
-                        let base_type_pk_field = base_type_fields
+                        let base_type_pk_fields: Vec<_> = base_type_fields
                             .iter_mut()
-                            .find(|f| matches!(f.relation, PostgresRelation::Pk { .. }));
+                            .filter(|f| matches!(f.relation, PostgresRelation::Pk { .. }))
+                            .collect();
 
-                        // For a non-nested type ("base type"), we already have the PK field, but it is optional. So here
-                        // we make it required (by not wrapping the entity_pk_field it as optional)
-                        if let Some(base_type_pk_field) = base_type_pk_field {
-                            let entity_pk_field = entity_type.pk_field().unwrap();
-                            base_type_pk_field.typ = to_mutation_type(&entity_pk_field.typ);
-                        } else {
+                        if base_type_pk_fields.is_empty() {
                             panic!("Expected a PK field in the base type")
-                        };
+                        }
+
+                        // For a non-nested type ("base type"), we already have the PK fields, but they are optional. So here
+                        // we make them required (by not wrapping the entity pk fields as optional)
+                        for base_type_pk_field in base_type_pk_fields {
+                            let entity_pk_field = entity_type
+                                .pk_fields()
+                                .into_iter()
+                                .find(|f| f.name == base_type_pk_field.name)
+                                .unwrap();
+                            base_type_pk_field.typ = to_mutation_type(&entity_pk_field.typ);
+                        }
 
                         let type_with_id = MutationType {
                             name: nested_existing_type_name,
@@ -311,3 +374,58 @@ impl DataParamBuilder<DataParameter> for UpdateMutationBuilder {
         }
     }
 }
+
+/// Expand the type to connect and disconnect objects of the given entity type through a
+/// many-to-many relation (`<Type>ManyToManyUpdateInput`). The type has two fields, `connect` and
+/// `disconnect`, each taking a list of references.
+fn expanded_many_to_many_type(
+    entity_type: &EntityType,
+    building: &SystemContextBuilding,
+) -> Option<(SerializableSlabIndex<MutationType>, MutationType)> {
+    let existing_type_name = entity_type.name.many_to_many_update_type();
+    let existing_type_id = building.mutation_types.get_id(&existing_type_name)?;
+
+    // The relation of a field (in another type) that refers to this type
+    let referring_field = entity_type
+        .fields
+        .iter()
+        .find_map(|field| match &field.relation {
+            PostgresRelation::ManyToMany(ManyToManyRelation {
+                foreign_field_id, ..
+            }) => Some(foreign_field_id.resolve(building.entity_types.values_ref())),
+            _ => None,
+        })?;
+
+    let reference_type_name = entity_type.name.reference_type();
+    let reference_type_id = building.mutation_types.get_id(&reference_type_name)?;
+
+    let fields = ["connect", "disconnect"]
+        .into_iter()
+        .map(|name| PostgresField {
+            name: name.to_string(),
+            typ: FieldType::Optional(Box::new(FieldType::List(Box::new(FieldType::Plain(
+                PostgresFieldType {
+                    type_id: TypeIndex::Composite(reference_type_id),
+                    type_name: reference_type_name.clone(),
+                },
+            ))))),
+            access: referring_field.access.clone(),
+            relation: referring_field.relation.clone(),
+            has_default_value: false,
+            dynamic_default_value: None,
+            readonly: false,
+            cache_policy: None,
+        })
+        .collect();
+
+    Some((
+        existing_type_id,
+        MutationType {
+            name: existing_type_name,
+            fields,
+            entity_id: building.entity_types.get_id(&entity_type.name).unwrap(),
+            input_access: None,
+            database_access: None,
+        },
+    ))
+}
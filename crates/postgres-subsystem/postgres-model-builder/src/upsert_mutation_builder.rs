@@ -0,0 +1,208 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Build upsert mutations (`upsert<Type>`, `upsert<Type>s`, and for each unique constraint,
+//! `upsert<Type>By<Constraint>` and `upsert<Type>sBy<Constraint>`).
+//!
+//! Upsert mutations take the same data as the create mutations (`<Type>CreationInput`), so this
+//! builder doesn't create any types of its own.
+
+use std::collections::BTreeMap;
+
+use core_plugin_interface::core_model::{
+    access::AccessPredicateExpression,
+    mapped_arena::SerializableSlabIndex,
+    types::{BaseOperationReturnType, OperationReturnType},
+};
+use exo_sql::{ColumnId, Database};
+use postgres_model::{
+    mutation::{PostgresMutation, PostgresMutationParameters},
+    relation::{ManyToOneRelation, PostgresRelation},
+    types::EntityType,
+};
+
+use super::{
+    create_mutation_builder::CreateMutationBuilder,
+    mutation_builder::DataParamBuilder,
+    naming::{ToPostgresMutationNames, ToPostgresTypeNames},
+    resolved_builder::ResolvedType,
+    system_builder::SystemContextBuilding,
+    type_builder::ResolvedTypeEnv,
+};
+
+/// Build the upsert mutations for all entity types.
+///
+/// An upsert may either create or update a row (depending on the existing data), so we offer it
+/// only if both are allowed. We don't offer it for types with a `@version` field, since the update
+/// would bypass the check of the expected version (as well as the version's increment).
+pub fn build_expanded(resolved_env: &ResolvedTypeEnv, building: &mut SystemContextBuilding) {
+    let upsert_access_is_false = |entity_type: &EntityType| -> bool {
+        let input_access_expressions = building.input_access_expressions.borrow();
+
+        [entity_type.access.creation, entity_type.access.update.input]
+            .into_iter()
+            .any(|access| {
+                matches!(
+                    input_access_expressions[access],
+                    AccessPredicateExpression::BooleanLiteral(false)
+                )
+            })
+            || matches!(
+                building.database_access_expressions.borrow()[entity_type.access.update.database],
+                AccessPredicateExpression::BooleanLiteral(false)
+            )
+    };
+
+    let mut mutations = vec![];
+
+    for (entity_type_id, entity_type) in building.entity_types.iter() {
+        let has_creation_type = building
+            .mutation_types
+            .get_id(&entity_type.name.creation_type())
+            .is_some();
+
+        if has_creation_type
+            && entity_type.version.is_none()
+            && !upsert_access_is_false(entity_type)
+        {
+            mutations.extend(build_mutations(
+                entity_type_id,
+                entity_type,
+                resolved_env,
+                building,
+            ));
+        }
+    }
+
+    for mutation in mutations {
+        building.mutations.add(&mutation.name.to_owned(), mutation);
+    }
+}
+
+fn build_mutations(
+    entity_type_id: SerializableSlabIndex<EntityType>,
+    entity_type: &EntityType,
+    resolved_env: &ResolvedTypeEnv,
+    building: &SystemContextBuilding,
+) -> Vec<PostgresMutation> {
+    let database = &building.database;
+    let column_ids = database.get_column_ids(entity_type.table_id);
+
+    // The creation input doesn't include auto-incremented columns, so a primary key made of such
+    // columns can never conflict with an existing row
+    let pk_columns: Vec<ColumnId> = column_ids
+        .iter()
+        .filter(|column_id| column_id.get_column(database).is_pk)
+        .copied()
+        .collect();
+    let pk_is_supplied = !pk_columns.is_empty()
+        && pk_columns
+            .iter()
+            .all(|column_id| !column_id.get_column(database).is_auto_increment);
+
+    let unique_constraints = match resolved_env.get_by_key(&entity_type.name) {
+        Some(ResolvedType::Composite(resolved_composite_type)) => resolved_composite_type
+            .unique_constraints()
+            .into_iter()
+            .map(|(constraint_name, fields)| {
+                let conflict_columns = fields
+                    .iter()
+                    .flat_map(|field| {
+                        let entity_field = entity_type.field_by_name(&field.name).unwrap();
+                        unique_column_ids(&entity_field.relation, database)
+                    })
+                    .collect::<Vec<_>>();
+                (constraint_name, conflict_columns)
+            })
+            .collect::<BTreeMap<_, _>>(),
+        _ => BTreeMap::new(),
+    };
+
+    let pk_mutations = pk_is_supplied.then(|| {
+        upsert_mutations(
+            entity_type.pk_upsert(),
+            entity_type.collection_upsert(),
+            pk_columns,
+            entity_type_id,
+            entity_type,
+            building,
+        )
+    });
+
+    let unique_mutations =
+        unique_constraints
+            .into_iter()
+            .map(|(constraint_name, conflict_columns)| {
+                upsert_mutations(
+                    entity_type.unique_upsert(&constraint_name),
+                    entity_type.collection_unique_upsert(&constraint_name),
+                    conflict_columns,
+                    entity_type_id,
+                    entity_type,
+                    building,
+                )
+            });
+
+    pk_mutations
+        .into_iter()
+        .chain(unique_mutations)
+        .flatten()
+        .collect()
+}
+
+fn unique_column_ids(relation: &PostgresRelation, database: &Database) -> Vec<ColumnId> {
+    match relation {
+        PostgresRelation::Pk { column_id } | PostgresRelation::Scalar { column_id } => {
+            vec![*column_id]
+        }
+        PostgresRelation::ManyToOne(ManyToOneRelation { relation_id, .. }) => {
+            relation_id.deref(database).self_column_ids()
+        }
+        PostgresRelation::OneToMany { .. } | PostgresRelation::ManyToMany { .. } => {
+            panic!("OneToMany and ManyToMany relations cannot be used in unique constraints")
+        }
+    }
+}
+
+fn upsert_mutations(
+    single_name: String,
+    multi_name: String,
+    conflict_columns: Vec<ColumnId>,
+    entity_type_id: SerializableSlabIndex<EntityType>,
+    entity_type: &EntityType,
+    building: &SystemContextBuilding,
+) -> [PostgresMutation; 2] {
+    let base_type = || BaseOperationReturnType {
+        associated_type_id: entity_type_id,
+        type_name: entity_type.name.clone(),
+    };
+
+    let single_mutation = PostgresMutation {
+        name: single_name,
+        parameters: PostgresMutationParameters::Upsert {
+            data_param: CreateMutationBuilder::data_param(entity_type, building, false),
+            conflict_columns: conflict_columns.clone(),
+        },
+        // We return null if the existing row may not be updated
+        return_type: OperationReturnType::Optional(Box::new(OperationReturnType::Plain(
+            base_type(),
+        ))),
+    };
+
+    let multi_mutation = PostgresMutation {
+        name: multi_name,
+        parameters: PostgresMutationParameters::Upsert {
+            data_param: CreateMutationBuilder::data_param(entity_type, building, true),
+            conflict_columns,
+        },
+        return_type: OperationReturnType::List(Box::new(OperationReturnType::Plain(base_type()))),
+    };
+
+    [single_mutation, multi_mutation]
+}
@@ -14,8 +14,9 @@ use async_graphql_value::Name;
 use serde::{Deserialize, Serialize};
 
 use crate::query::AggregateQueryParameters;
-use crate::relation::{OneToManyRelation, PostgresRelation};
+use crate::relation::{ManyToManyRelation, OneToManyRelation, PostgresRelation};
 use crate::subsystem::PostgresSubsystem;
+use core_plugin_interface::core_model::cache_policy::{cache_policy_directives, CachePolicy};
 use core_plugin_interface::core_model::mapped_arena::SerializableSlabIndex;
 use core_plugin_interface::core_model::type_normalization::{
     default_positioned, default_positioned_name, FieldDefinitionProvider, InputValueProvider,
@@ -26,6 +27,8 @@ use core_plugin_interface::core_model::type_normalization::{
 pub struct AggregateType {
     pub name: String, // Such as IntAgg, ConcertAgg.
     pub fields: Vec<AggregateField>,
+    /// The cache policy of the aggregated entity type (if any)
+    pub cache_policy: Option<CachePolicy>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -86,7 +89,7 @@ impl TypeDefinitionProvider<PostgresSubsystem> for AggregateType {
             extend: false,
             description: None,
             name: default_positioned_name(&self.name),
-            directives: vec![],
+            directives: cache_policy_directives(&self.cache_policy),
             kind,
         }
     }
@@ -103,6 +106,9 @@ impl FieldDefinitionProvider<PostgresSubsystem> for AggregateField {
                 }
                 PostgresRelation::OneToMany(OneToManyRelation {
                     foreign_field_id, ..
+                })
+                | PostgresRelation::ManyToMany(ManyToManyRelation {
+                    foreign_field_id, ..
                 }) => {
                     let foreign_type = &system.entity_types[foreign_field_id.entity_type_id()];
                     let aggregate_query = &system.aggregate_queries[foreign_type.aggregate_query];
@@ -0,0 +1,122 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Types to support recording the changes to types with the `@audited` annotation and querying them
+//! through history queries such as `concertHistory(id: 1)`.
+
+use async_graphql_parser::types::{
+    BaseType, FieldDefinition, ObjectType, Type, TypeDefinition, TypeKind,
+};
+use async_graphql_value::Name;
+use serde::{Deserialize, Serialize};
+
+use core_plugin_interface::core_model::{
+    access::AccessPredicateExpression,
+    context_type::ContextSelection,
+    mapped_arena::SerializableSlabIndex,
+    type_normalization::{
+        default_positioned, default_positioned_name, Parameter, TypeDefinitionProvider,
+    },
+};
+use exo_sql::TableId;
+
+use crate::{
+    access::InputAccessPrimitiveExpression,
+    operation::{OperationParameters, PostgresOperation},
+    predicate::PredicateParameter,
+    subsystem::PostgresSubsystem,
+    types::EntityType,
+};
+
+/// Recording of changes to a type with the `@audited` annotation. Triggers on the type's table
+/// record each insert, update, and delete in a companion history table.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Audit {
+    /// The table recording the changes such as `concerts_history`
+    pub history_table_id: TableId,
+    /// The context field identifying the user making a change such as `AuthContext.id` (the
+    /// changes are recorded without a user if not specified)
+    pub user: Option<ContextSelection>,
+    /// The access rule for querying the history
+    pub access: SerializableSlabIndex<AccessPredicateExpression<InputAccessPrimitiveExpression>>,
+}
+
+/// The name of the field with the kind of change (`INSERT`, `UPDATE`, or `DELETE`)
+pub const HISTORY_OPERATION_FIELD_NAME: &str = "operation";
+/// The name of the field with the time of the change
+pub const HISTORY_CHANGED_AT_FIELD_NAME: &str = "changedAt";
+/// The name of the field with the user who made the change
+pub const HISTORY_CHANGED_BY_FIELD_NAME: &str = "changedBy";
+/// The name of the field with the entity before the change
+pub const HISTORY_BEFORE_FIELD_NAME: &str = "before";
+/// The name of the field with the entity after the change
+pub const HISTORY_AFTER_FIELD_NAME: &str = "after";
+
+/// A history type such as `ConcertHistory` describing a recorded change to an entity
+#[derive(Serialize, Deserialize, Debug)]
+pub struct HistoryType {
+    pub name: String,
+    pub entity_type_id: SerializableSlabIndex<EntityType>,
+}
+
+impl TypeDefinitionProvider<PostgresSubsystem> for HistoryType {
+    fn type_definition(&self, _system: &PostgresSubsystem) -> TypeDefinition {
+        TypeDefinition {
+            extend: false,
+            description: None,
+            name: default_positioned_name(&self.name),
+            directives: vec![],
+            kind: TypeKind::Object(ObjectType {
+                implements: vec![],
+                fields: vec![
+                    field_definition(HISTORY_OPERATION_FIELD_NAME, "String", false),
+                    field_definition(HISTORY_CHANGED_AT_FIELD_NAME, "Instant", false),
+                    field_definition(HISTORY_CHANGED_BY_FIELD_NAME, "String", true),
+                    field_definition(HISTORY_BEFORE_FIELD_NAME, "Json", true),
+                    field_definition(HISTORY_AFTER_FIELD_NAME, "Json", true),
+                ]
+                .into_iter()
+                .map(default_positioned)
+                .collect(),
+            }),
+        }
+    }
+}
+
+fn field_definition(name: &str, type_name: &str, nullable: bool) -> FieldDefinition {
+    FieldDefinition {
+        description: None,
+        name: default_positioned_name(name),
+        arguments: vec![],
+        ty: default_positioned(Type {
+            base: BaseType::Named(Name::new(type_name)),
+            nullable,
+        }),
+        directives: vec![],
+    }
+}
+
+/// Query that returns the recorded changes to an entity (oldest first) such as
+/// `concertHistory(id: 1)`
+pub type HistoryQuery = PostgresOperation<HistoryQueryParameters>;
+
+/// History query parameters such as `id: 1` in `concertHistory(id: 1)` (one per primary key field)
+#[derive(Serialize, Deserialize, Debug)]
+pub struct HistoryQueryParameters {
+    pub predicate_params: Vec<PredicateParameter>,
+}
+
+impl OperationParameters for HistoryQueryParameters {
+    fn introspect(&self) -> Vec<&dyn Parameter> {
+        self.predicate_params
+            .iter()
+            .map(|p| p as &dyn Parameter)
+            .collect()
+    }
+}
@@ -0,0 +1,171 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Types to support Relay-style cursor pagination through connection queries such as
+//! `concertsConnection(first: 10, after: "...")`.
+
+use async_graphql_parser::types::{
+    BaseType, FieldDefinition, ObjectType, Type, TypeDefinition, TypeKind,
+};
+use async_graphql_value::Name;
+use serde::{Deserialize, Serialize};
+
+use core_plugin_interface::core_model::{
+    cache_policy::cache_policy_directives,
+    mapped_arena::SerializableSlabIndex,
+    type_normalization::{
+        default_positioned, default_positioned_name, Parameter, TypeDefinitionProvider,
+    },
+    types::{FieldType, Named},
+};
+
+use crate::{
+    subsystem::PostgresSubsystem,
+    types::{EntityType, PostgresPrimitiveType},
+};
+
+/// The name of the type describing the page returned by a connection query
+pub const PAGE_INFO_TYPE_NAME: &str = "PageInfo";
+
+/// A connection type such as `ConcertConnection` (along with its edge type such as `ConcertEdge`)
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ConnectionType {
+    pub name: String,
+    pub edge_type_name: String,
+    pub node_type_id: SerializableSlabIndex<EntityType>,
+}
+
+impl ConnectionType {
+    /// The definition of the edge type such as `type ConcertEdge { cursor: String!, node: Concert! }`
+    pub fn edge_type_definition(&self, system: &PostgresSubsystem) -> TypeDefinition {
+        let node_type = &system.entity_types[self.node_type_id];
+
+        self.object_type_definition(
+            &self.edge_type_name,
+            vec![
+                field_definition("cursor", "String", false),
+                field_definition("node", &node_type.name, false),
+            ],
+            system,
+        )
+    }
+
+    fn object_type_definition(
+        &self,
+        name: &str,
+        fields: Vec<FieldDefinition>,
+        system: &PostgresSubsystem,
+    ) -> TypeDefinition {
+        // The connection and edge types carry the cache policy of the node type, so that the
+        // responses to connection queries are cached the same way as those to collection queries
+        let node_type = &system.entity_types[self.node_type_id];
+
+        TypeDefinition {
+            extend: false,
+            description: None,
+            name: default_positioned_name(name),
+            directives: cache_policy_directives(&node_type.cache_policy),
+            kind: TypeKind::Object(ObjectType {
+                implements: vec![],
+                fields: fields.into_iter().map(default_positioned).collect(),
+            }),
+        }
+    }
+}
+
+impl TypeDefinitionProvider<PostgresSubsystem> for ConnectionType {
+    fn type_definition(&self, system: &PostgresSubsystem) -> TypeDefinition {
+        let edges_type = Type {
+            base: BaseType::List(Box::new(Type {
+                base: BaseType::Named(Name::new(&self.edge_type_name)),
+                nullable: false,
+            })),
+            nullable: false,
+        };
+
+        self.object_type_definition(
+            &self.name,
+            vec![
+                FieldDefinition {
+                    description: None,
+                    name: default_positioned_name("edges"),
+                    arguments: vec![],
+                    ty: default_positioned(edges_type),
+                    directives: vec![],
+                },
+                field_definition("pageInfo", PAGE_INFO_TYPE_NAME, false),
+            ],
+            system,
+        )
+    }
+}
+
+/// The definition of `type PageInfo { hasNextPage: Boolean!, hasPreviousPage: Boolean!, startCursor: String, endCursor: String }`
+pub fn page_info_type_definition() -> TypeDefinition {
+    TypeDefinition {
+        extend: false,
+        description: None,
+        name: default_positioned_name(PAGE_INFO_TYPE_NAME),
+        directives: vec![],
+        kind: TypeKind::Object(ObjectType {
+            implements: vec![],
+            fields: vec![
+                field_definition("hasNextPage", "Boolean", false),
+                field_definition("hasPreviousPage", "Boolean", false),
+                field_definition("startCursor", "String", true),
+                field_definition("endCursor", "String", true),
+            ]
+            .into_iter()
+            .map(default_positioned)
+            .collect(),
+        }),
+    }
+}
+
+fn field_definition(name: &str, type_name: &str, nullable: bool) -> FieldDefinition {
+    FieldDefinition {
+        description: None,
+        name: default_positioned_name(name),
+        arguments: vec![],
+        ty: default_positioned(Type {
+            base: BaseType::Named(Name::new(type_name)),
+            nullable,
+        }),
+        directives: vec![],
+    }
+}
+
+/// A cursor parameter such as `after: "..."` or `before: "..."`
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CursorParameter {
+    pub name: String,
+    pub typ: FieldType<CursorParameterType>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CursorParameterType {
+    pub type_name: String,
+    pub type_id: SerializableSlabIndex<PostgresPrimitiveType>,
+}
+
+impl Named for CursorParameterType {
+    fn name(&self) -> &str {
+        &self.type_name
+    }
+}
+
+impl Parameter for CursorParameter {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn typ(&self) -> Type {
+        (&self.typ).into()
+    }
+}
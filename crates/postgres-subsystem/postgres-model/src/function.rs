@@ -0,0 +1,83 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Types to support queries and mutations backed by Postgres functions through the `@function`
+//! annotation such as `@function("reporting.top_concerts") query topConcerts(count: Int): Set<Concert>`.
+
+use async_graphql_parser::types::Type;
+use serde::{Deserialize, Serialize};
+
+use core_plugin_interface::core_model::{
+    access::AccessPredicateExpression,
+    mapped_arena::SerializableSlabIndex,
+    type_normalization::Parameter,
+    types::{FieldType, Named},
+};
+use exo_sql::{PhysicalColumnType, PhysicalTableName};
+
+use crate::{
+    access::InputAccessPrimitiveExpression,
+    operation::{OperationParameters, PostgresOperation},
+    types::PostgresPrimitiveType,
+};
+
+/// A query or mutation that returns the entities returned by a Postgres function (which must
+/// return rows of the entity's table such as `RETURNS SETOF concerts`)
+pub type FunctionOperation = PostgresOperation<FunctionParameters>;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FunctionParameters {
+    /// The function to call such as `reporting.top_concerts`
+    pub function_name: PhysicalTableName,
+    /// The arguments passed to the function (in the order of its parameters)
+    pub arguments: Vec<FunctionArgument>,
+    /// The access rule (from the `@access` annotation on the query or mutation) for calling the
+    /// function (the entity's read access rule further filters the returned rows)
+    pub access: SerializableSlabIndex<AccessPredicateExpression<InputAccessPrimitiveExpression>>,
+}
+
+impl OperationParameters for FunctionParameters {
+    fn introspect(&self) -> Vec<&dyn Parameter> {
+        self.arguments
+            .iter()
+            .map(|argument| argument as &dyn Parameter)
+            .collect()
+    }
+}
+
+/// An argument such as `count: Int` passed to the function as a bind parameter
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FunctionArgument {
+    pub name: String,
+    pub typ: FieldType<FunctionArgumentType>,
+    /// The database type to bind the argument's value as
+    pub column_type: PhysicalColumnType,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FunctionArgumentType {
+    pub type_name: String,
+    pub type_id: SerializableSlabIndex<PostgresPrimitiveType>,
+}
+
+impl Named for FunctionArgumentType {
+    fn name(&self) -> &str {
+        &self.type_name
+    }
+}
+
+impl Parameter for FunctionArgument {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn typ(&self) -> Type {
+        (&self.typ).into()
+    }
+}
@@ -0,0 +1,324 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Types to support group-by queries such as
+//! `concertsGroupBy(by: [venueId], having: { count: { gt: 1 } }) { key { venueId } count aggregate { price { avg } } }`.
+
+use std::collections::BTreeMap;
+
+use async_graphql_parser::{
+    types::{
+        BaseType, EnumType, EnumValueDefinition, FieldDefinition, InputObjectType,
+        InputValueDefinition, ObjectType, Type, TypeDefinition, TypeKind,
+    },
+    Pos, Positioned,
+};
+use async_graphql_value::Name;
+use serde::{Deserialize, Serialize};
+
+use core_plugin_interface::core_model::{
+    cache_policy::{cache_policy_directives, CachePolicy},
+    mapped_arena::SerializableSlabIndex,
+    type_normalization::{default_positioned, default_positioned_name, Parameter},
+    types::{FieldType, Named},
+};
+
+use crate::{
+    aggregate::{AggregateFieldType, AggregateType},
+    order::aggregate_ordering_type_name,
+    subsystem::PostgresSubsystem,
+    types::EntityType,
+};
+
+/// The field of a group with the values of the grouped fields
+pub const GROUP_KEY_FIELD: &str = "key";
+/// The field of a group with the number of rows in the group
+pub const GROUP_COUNT_FIELD: &str = "count";
+/// The field of a group with the aggregates over the rows in the group
+pub const GROUP_AGGREGATE_FIELD: &str = "aggregate";
+
+/// A group type such as `ConcertGroup`.
+///
+/// Each group type comes with a few supporting types (named after the group type):
+/// - `ConcertGroupKey`: The values of the grouped fields
+/// - `ConcertGroupByField`: An enum of the fields the rows may be grouped by
+/// - `ConcertGroupHaving`: An input type to filter the groups
+/// - `ConcertGroupOrdering` (and `ConcertGroupKeyOrdering`): Input types to order the groups
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GroupType {
+    pub name: String,
+    pub entity_type_id: SerializableSlabIndex<EntityType>,
+    /// The fields the rows may be grouped by (these are also the fields of the key type)
+    pub key_fields: Vec<GroupKeyField>,
+    /// The aggregate type of the entity type (such as `ConcertAgg`)
+    pub aggregate_type_id: SerializableSlabIndex<AggregateType>,
+}
+
+/// A field the rows may be grouped by such as `venueId`
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GroupKeyField {
+    pub name: String,
+    pub type_name: String,
+}
+
+impl GroupType {
+    pub fn key_type_name(&self) -> String {
+        format!("{}Key", self.name)
+    }
+
+    pub fn by_field_type_name(&self) -> String {
+        format!("{}ByField", self.name)
+    }
+
+    pub fn having_type_name(&self) -> String {
+        format!("{}Having", self.name)
+    }
+
+    pub fn ordering_type_name(&self) -> String {
+        format!("{}Ordering", self.name)
+    }
+
+    fn key_ordering_type_name(&self) -> String {
+        format!("{}KeyOrdering", self.name)
+    }
+
+    /// The definitions of the group type and its supporting types
+    fn type_definitions(&self, system: &PostgresSubsystem) -> Vec<TypeDefinition> {
+        // The group types carry the cache policy of the entity type, so that the responses to
+        // group-by queries are cached the same way as those to aggregate queries
+        let cache_policy = &system.entity_types[self.entity_type_id].cache_policy;
+        let aggregate_type_name = &system.aggregate_types[self.aggregate_type_id].name;
+
+        vec![
+            object_type_definition(
+                &self.name,
+                vec![
+                    field_definition(GROUP_KEY_FIELD, &self.key_type_name(), false),
+                    field_definition(GROUP_COUNT_FIELD, "Int", false),
+                    field_definition(GROUP_AGGREGATE_FIELD, aggregate_type_name, false),
+                ],
+                cache_policy,
+            ),
+            object_type_definition(
+                &self.key_type_name(),
+                self.key_fields
+                    .iter()
+                    .map(|field| field_definition(&field.name, &field.type_name, true))
+                    .collect(),
+                cache_policy,
+            ),
+            TypeDefinition {
+                extend: false,
+                description: None,
+                name: default_positioned_name(&self.by_field_type_name()),
+                directives: vec![],
+                kind: TypeKind::Enum(EnumType {
+                    values: self
+                        .key_fields
+                        .iter()
+                        .map(|field| {
+                            Positioned::new(
+                                EnumValueDefinition {
+                                    description: None,
+                                    value: Positioned::new(Name::new(&field.name), Pos::default()),
+                                    directives: vec![],
+                                },
+                                Pos::default(),
+                            )
+                        })
+                        .collect(),
+                }),
+            },
+            input_type_definition(
+                &self.having_type_name(),
+                vec![
+                    (GROUP_COUNT_FIELD.to_string(), "IntFilter".to_string()),
+                    (
+                        GROUP_AGGREGATE_FIELD.to_string(),
+                        aggregate_filter_type_name(aggregate_type_name),
+                    ),
+                ],
+            ),
+            input_type_definition(
+                &self.ordering_type_name(),
+                vec![
+                    (GROUP_KEY_FIELD.to_string(), self.key_ordering_type_name()),
+                    (GROUP_COUNT_FIELD.to_string(), "Ordering".to_string()),
+                    (
+                        GROUP_AGGREGATE_FIELD.to_string(),
+                        aggregate_ordering_type_name(aggregate_type_name),
+                    ),
+                ],
+            ),
+            input_type_definition(
+                &self.key_ordering_type_name(),
+                self.key_fields
+                    .iter()
+                    .map(|field| (field.name.clone(), "Ordering".to_string()))
+                    .collect(),
+            ),
+        ]
+    }
+}
+
+/// The name of the input type to filter groups by an aggregate (such as `ConcertAggFilter` or `IntAggFilter`)
+pub fn aggregate_filter_type_name(aggregate_type_name: &str) -> String {
+    format!("{aggregate_type_name}Filter")
+}
+
+/// Can the groups be filtered and ordered by the given aggregate field? We leave out the vector
+/// averages, since comparing vectors isn't meaningful.
+pub fn is_comparable_aggregate(typ: &AggregateFieldType) -> bool {
+    !matches!(typ, AggregateFieldType::Scalar { type_name, .. } if type_name == "Vector")
+}
+
+/// The definitions of all group types along with the input types to filter by the aggregates they
+/// use (the input types to order by the aggregates are order-by types such as `ConcertAggOrdering`).
+///
+/// For a composite aggregate type such as `ConcertAgg`, the input type has a field for each
+/// aggregated field (`input ConcertAggFilter { price: IntAggFilter, ... }`), and for a scalar
+/// aggregate type such as `IntAgg`, a field for each aggregate (`input IntAggFilter { min:
+/// IntFilter, avg: FloatFilter, ... }`).
+pub fn group_type_definitions(system: &PostgresSubsystem) -> Vec<TypeDefinition> {
+    // The aggregate types used by the group types (keyed by name to define each only once)
+    let aggregate_types: BTreeMap<_, _> = system
+        .group_types
+        .iter()
+        .flat_map(|(_, group_type)| {
+            let aggregate_type = &system.aggregate_types[group_type.aggregate_type_id];
+            let field_aggregate_types =
+                aggregate_type
+                    .fields
+                    .iter()
+                    .filter_map(|field| match &field.typ {
+                        AggregateFieldType::Composite { type_id, .. } => {
+                            Some(&system.aggregate_types[*type_id])
+                        }
+                        AggregateFieldType::Scalar { .. } => None,
+                    });
+
+            std::iter::once(aggregate_type).chain(field_aggregate_types)
+        })
+        .map(|aggregate_type| (aggregate_type.name.as_str(), aggregate_type))
+        .collect();
+
+    let aggregate_type_definitions = aggregate_types.into_values().map(|aggregate_type| {
+        let filter_fields = aggregate_type
+            .fields
+            .iter()
+            .filter(|field| is_comparable_aggregate(&field.typ))
+            .map(|field| match &field.typ {
+                AggregateFieldType::Scalar { type_name, .. } => {
+                    (field.name.clone(), format!("{type_name}Filter"))
+                }
+                AggregateFieldType::Composite { type_name, .. } => {
+                    (field.name.clone(), aggregate_filter_type_name(type_name))
+                }
+            })
+            .collect();
+
+        input_type_definition(
+            &aggregate_filter_type_name(&aggregate_type.name),
+            filter_fields,
+        )
+    });
+
+    system
+        .group_types
+        .iter()
+        .flat_map(|(_, group_type)| group_type.type_definitions(system))
+        .chain(aggregate_type_definitions)
+        .collect()
+}
+
+fn object_type_definition(
+    name: &str,
+    fields: Vec<FieldDefinition>,
+    cache_policy: &Option<CachePolicy>,
+) -> TypeDefinition {
+    TypeDefinition {
+        extend: false,
+        description: None,
+        name: default_positioned_name(name),
+        directives: cache_policy_directives(cache_policy),
+        kind: TypeKind::Object(ObjectType {
+            implements: vec![],
+            fields: fields.into_iter().map(default_positioned).collect(),
+        }),
+    }
+}
+
+fn field_definition(name: &str, type_name: &str, nullable: bool) -> FieldDefinition {
+    FieldDefinition {
+        description: None,
+        name: default_positioned_name(name),
+        arguments: vec![],
+        ty: default_positioned(Type {
+            base: BaseType::Named(Name::new(type_name)),
+            nullable,
+        }),
+        directives: vec![],
+    }
+}
+
+/// An input type with optional fields given as `(name, type name)` pairs
+fn input_type_definition(name: &str, fields: Vec<(String, String)>) -> TypeDefinition {
+    TypeDefinition {
+        extend: false,
+        description: None,
+        name: default_positioned_name(name),
+        directives: vec![],
+        kind: TypeKind::InputObject(InputObjectType {
+            fields: fields
+                .into_iter()
+                .map(|(field_name, type_name)| {
+                    default_positioned(InputValueDefinition {
+                        description: None,
+                        name: default_positioned_name(&field_name),
+                        directives: vec![],
+                        default_value: None,
+                        ty: default_positioned(Type {
+                            base: BaseType::Named(Name::new(type_name)),
+                            nullable: true,
+                        }),
+                    })
+                })
+                .collect(),
+        }),
+    }
+}
+
+/// A parameter of a group-by query typed with one of the group's supporting types such as
+/// `by: [ConcertGroupByField!]!` or `having: ConcertGroupHaving`
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GroupByParameter {
+    pub name: String,
+    pub typ: FieldType<GroupByParameterType>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GroupByParameterType {
+    pub type_name: String,
+}
+
+impl Named for GroupByParameterType {
+    fn name(&self) -> &str {
+        &self.type_name
+    }
+}
+
+impl Parameter for GroupByParameter {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn typ(&self) -> Type {
+        (&self.typ).into()
+    }
+}
@@ -9,6 +9,10 @@
 
 pub mod access;
 pub mod aggregate;
+pub mod audit;
+pub mod connection;
+pub mod function;
+pub mod group_by;
 pub mod limit_offset;
 pub mod migration;
 pub mod mutation;
@@ -17,6 +21,9 @@ pub mod order;
 pub mod predicate;
 pub mod query;
 pub mod relation;
+pub mod soft_delete;
+pub mod subscription;
 pub mod subsystem;
 pub mod types;
 pub mod vector_distance;
+pub mod version;
@@ -10,10 +10,14 @@
 use std::fmt::Display;
 
 use super::subsystem::PostgresSubsystem;
+use core_plugin_interface::core_model::types::OperationReturnType;
 use exo_sql::{
     database_error::DatabaseError,
-    schema::{database_spec::DatabaseSpec, issue::WithIssues, op::SchemaOp, spec::diff},
-    DatabaseClientManager,
+    schema::{
+        column_spec::ColumnTypeSpec, database_spec::DatabaseSpec, issue::WithIssues, op::SchemaOp,
+        spec::diff,
+    },
+    DatabaseClientManager, TableKind,
 };
 use serde::Serialize;
 
@@ -64,13 +68,26 @@ impl Migration {
 
         for diff in diffs.iter() {
             let is_destructive = match diff {
+                // A generated column (such as the text search vector) can be re-computed from the other columns
+                SchemaOp::DeleteColumn { column, .. }
+                    if matches!(column.typ, ColumnTypeSpec::TextSearchVector { .. }) =>
+                {
+                    false
+                }
+
                 SchemaOp::DeleteSchema { .. }
                 | SchemaOp::DeleteTable { .. }
                 | SchemaOp::DeleteColumn { .. }
-                | SchemaOp::RemoveExtension { .. } => true,
+                | SchemaOp::RemoveExtension { .. }
+                | SchemaOp::DeleteEnum { .. }
+                // Re-creating an enum (to remove or reorder values) fails if any row uses a removed value
+                | SchemaOp::RenameEnum { .. }
+                | SchemaOp::ConvertEnumColumn { .. } => true,
 
                 // Explicitly matching the other cases here to ensure that we have thought about each case
                 SchemaOp::CreateSchema { .. }
+                | SchemaOp::CreateEnum { .. }
+                | SchemaOp::AddEnumValue { .. }
                 | SchemaOp::CreateTable { .. }
                 | SchemaOp::CreateColumn { .. }
                 | SchemaOp::CreateIndex { .. }
@@ -122,6 +139,12 @@ impl Migration {
 
         let database_spec = DatabaseSpec::from_database(&postgres_subsystem.database);
 
+        // Migrations leave views alone, so they can't resolve a view that is a table on the other side
+        let conflicts = table_kind_conflicts(&old_schema.value, &database_spec);
+        if !conflicts.is_empty() {
+            return Err(DatabaseError::Validation(conflicts.join("\n")));
+        }
+
         Ok(Migration::from_schemas(&old_schema.value, &database_spec))
     }
 
@@ -145,7 +168,32 @@ impl Migration {
 
         let diff = diff(&old_schema.value, &new_schema);
 
-        let errors: Vec<_> = diff.iter().flat_map(|op| op.error_string()).collect();
+        let mut errors: Vec<_> = diff.iter().flat_map(|op| op.error_string()).collect();
+
+        errors.extend(table_kind_conflicts(&old_schema.value, &new_schema));
+
+        // Migrations do not create views, so they must already exist in the database
+        errors.extend(
+            new_schema
+                .tables
+                .iter()
+                .filter(|table| table.kind.is_view())
+                .filter(|view| {
+                    !old_schema
+                        .value
+                        .tables
+                        .iter()
+                        .any(|table| table.sql_name() == view.sql_name())
+                })
+                .map(|view| {
+                    format!(
+                        "The view `{}` exists in the model, but does not exist in the database.",
+                        view.sql_name()
+                    )
+                }),
+        );
+
+        errors.extend(function_errors(database, postgres_subsystem).await?);
 
         if !errors.is_empty() {
             Err(VerificationErrors::ModelNotCompatible(errors))
@@ -206,6 +254,91 @@ impl MigrationStatement {
     }
 }
 
+/// Tables and views of the same name but of a different kind (for example, a view in the model but a
+/// table in the database). The `diff` leaves out both sides of such a pair (since migrations don't
+/// touch views), so these must be reported separately.
+fn table_kind_conflicts(old_schema: &DatabaseSpec, new_schema: &DatabaseSpec) -> Vec<String> {
+    fn kind_name(kind: TableKind) -> &'static str {
+        match kind {
+            TableKind::Table => "a table",
+            TableKind::View => "a view",
+            TableKind::MaterializedView => "a materialized view",
+        }
+    }
+
+    new_schema
+        .tables
+        .iter()
+        .flat_map(|new_table| {
+            old_schema
+                .tables
+                .iter()
+                .find(|old_table| old_table.sql_name() == new_table.sql_name())
+                .filter(|old_table| old_table.kind != new_table.kind)
+                .map(|old_table| {
+                    format!(
+                        "`{}` is {} in the model, but {} in the database.",
+                        new_table.sql_name(),
+                        kind_name(new_table.kind),
+                        kind_name(old_table.kind)
+                    )
+                })
+        })
+        .collect()
+}
+
+/// Functions backing `@function` queries and mutations that are missing from the database or
+/// whose result doesn't match the operation's return type. The model is built without access to the
+/// database, so a function such as `RETURNS SETOF concerts` behind an operation returning an optional
+/// value (and thus at most one row) can only be detected here.
+async fn function_errors(
+    database: &DatabaseClientManager,
+    postgres_subsystem: &PostgresSubsystem,
+) -> Result<Vec<String>, DatabaseError> {
+    const RETURNS_SET_QUERY: &str = "SELECT p.proretset FROM pg_proc p JOIN pg_namespace n ON n.oid = p.pronamespace WHERE p.proname = $1 AND n.nspname = $2";
+
+    let client = database.get_client().await?;
+
+    let operations = postgres_subsystem
+        .function_queries
+        .iter()
+        .chain(postgres_subsystem.function_mutations.iter());
+
+    let mut errors = vec![];
+
+    for (_, operation) in operations {
+        let function_name = &operation.parameters.function_name;
+        let schema = function_name.schema.as_deref().unwrap_or("public");
+
+        let rows = client
+            .query(RETURNS_SET_QUERY, &[&function_name.name, &schema])
+            .await?;
+
+        let returns_list = matches!(operation.return_type, OperationReturnType::List(_));
+
+        match rows.first() {
+            None => errors.push(format!(
+                "The function `{}` (for the operation '{}') does not exist in the database.",
+                function_name.fully_qualified_name(),
+                operation.name
+            )),
+            Some(row) => {
+                let returns_set: bool = row.get("proretset");
+
+                if returns_set && !returns_list {
+                    errors.push(format!(
+                        "The function `{}` returns a set of rows, but the operation '{}' returns an optional value. Declare its return type as a `Set`.",
+                        function_name.fully_qualified_name(),
+                        operation.name
+                    ))
+                }
+            }
+        }
+    }
+
+    Ok(errors)
+}
+
 async fn extract_db_schema(
     database: &DatabaseClientManager,
 ) -> Result<WithIssues<DatabaseSpec>, DatabaseError> {
@@ -407,6 +540,99 @@ mod tests {
         ).await
     }
 
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn add_composite_pk_relation() {
+        assert_changes(
+            r#"
+            @postgres
+            module ConcertModule {
+                type Concert {
+                    @pk id: Int = autoIncrement()
+                    title: String
+                }
+            }
+            "#,
+            r#"
+            @postgres
+            module ConcertModule {
+                type Concert {
+                    @pk id: Int = autoIncrement()
+                    title: String
+                    venue: Venue
+                }
+                type Venue {
+                    @pk tenantId: Int
+                    @pk id: Int
+                    name: String
+                    concerts: Set<Concert>?
+                }
+            }
+            "#,
+            vec![
+                (
+                    r#"CREATE TABLE "concerts" (
+                    |    "id" SERIAL PRIMARY KEY,
+                    |    "title" TEXT NOT NULL
+                    |);"#,
+                    false,
+                ),
+            ],
+            vec![
+                (
+                    r#"CREATE TABLE "concerts" (
+                    |    "id" SERIAL PRIMARY KEY,
+                    |    "title" TEXT NOT NULL,
+                    |    "venue_tenant_id" INT NOT NULL,
+                    |    "venue_id" INT NOT NULL
+                    |);"#,
+                    false,
+                ),
+                (
+                    r#"CREATE TABLE "venues" (
+                    |    "tenant_id" INT,
+                    |    "id" INT,
+                    |    "name" TEXT NOT NULL,
+                    |    PRIMARY KEY ("tenant_id", "id")
+                    |);"#,
+                    false,
+                ),
+                (
+                    r#"ALTER TABLE "concerts" ADD CONSTRAINT "concerts_venue_tenant_id_venue_id_fk" FOREIGN KEY ("venue_tenant_id", "venue_id") REFERENCES "venues";"#,
+                    false,
+                ),
+            ],
+            vec![
+                (
+                    r#"ALTER TABLE "concerts" ADD "venue_tenant_id" INT NOT NULL;"#,
+                    false,
+                ),
+                (
+                    r#"ALTER TABLE "concerts" ADD "venue_id" INT NOT NULL;"#,
+                    false,
+                ),
+                (
+                    r#"CREATE TABLE "venues" (
+                    |    "tenant_id" INT,
+                    |    "id" INT,
+                    |    "name" TEXT NOT NULL,
+                    |    PRIMARY KEY ("tenant_id", "id")
+                    |);"#,
+                    false,
+                ),
+                (
+                    r#"ALTER TABLE "concerts" ADD CONSTRAINT "concerts_venue_tenant_id_venue_id_fk" FOREIGN KEY ("venue_tenant_id", "venue_id") REFERENCES "venues";"#,
+                    false,
+                ),
+            ],
+            vec![
+                (r#"ALTER TABLE "concerts" DROP COLUMN "venue_tenant_id";"#, true),
+                (r#"ALTER TABLE "concerts" DROP COLUMN "venue_id";"#, true),
+                (r#"DROP TABLE "venues" CASCADE;"#, true),
+            ],
+        ).await
+    }
+
     #[cfg_attr(not(target_family = "wasm"), tokio::test)]
     #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
     async fn add_relation_field() {
@@ -664,6 +890,179 @@ mod tests {
         ).await
     }
 
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn add_array_index() {
+        assert_changes(
+            r#"
+            @postgres
+            module ConcertModule {
+                type Concert {
+                    @pk id: Int = autoIncrement()
+                    tags: Array<String>
+                }
+            }
+            "#,
+            r#"
+            @postgres
+            module ConcertModule {
+                type Concert {
+                    @pk id: Int = autoIncrement()
+                    @index tags: Array<String>
+                }
+            }
+            "#,
+            vec![(
+                r#"CREATE TABLE "concerts" (
+                    |    "id" SERIAL PRIMARY KEY,
+                    |    "tags" TEXT[] NOT NULL
+                    |);"#,
+                false,
+            )],
+            vec![
+                (
+                    r#"CREATE TABLE "concerts" (
+                    |    "id" SERIAL PRIMARY KEY,
+                    |    "tags" TEXT[] NOT NULL
+                    |);"#,
+                    false,
+                ),
+                (
+                    r#"CREATE INDEX "concert_tags_idx" ON "concerts" USING gin ("tags");"#,
+                    false,
+                ),
+            ],
+            vec![(
+                r#"CREATE INDEX "concert_tags_idx" ON "concerts" USING gin ("tags");"#,
+                false,
+            )],
+            vec![(r#"DROP INDEX "concert_tags_idx";"#, false)],
+        )
+        .await
+    }
+
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn add_multi_column_array_index() {
+        // A multi-column index with an array field uses the default kind (GIN supports only arrays)
+        assert_changes(
+            r#"
+            @postgres
+            module ConcertModule {
+                type Concert {
+                    @pk id: Int = autoIncrement()
+                    tags: Array<String>
+                    title: String
+                }
+            }
+            "#,
+            r#"
+            @postgres
+            module ConcertModule {
+                type Concert {
+                    @pk id: Int = autoIncrement()
+                    @index("tags-title") tags: Array<String>
+                    @index("tags-title") title: String
+                }
+            }
+            "#,
+            vec![(
+                r#"CREATE TABLE "concerts" (
+                    |    "id" SERIAL PRIMARY KEY,
+                    |    "tags" TEXT[] NOT NULL,
+                    |    "title" TEXT NOT NULL
+                    |);"#,
+                false,
+            )],
+            vec![
+                (
+                    r#"CREATE TABLE "concerts" (
+                    |    "id" SERIAL PRIMARY KEY,
+                    |    "tags" TEXT[] NOT NULL,
+                    |    "title" TEXT NOT NULL
+                    |);"#,
+                    false,
+                ),
+                (
+                    r#"CREATE INDEX "tags-title" ON "concerts" ("tags", "title");"#,
+                    false,
+                ),
+            ],
+            vec![(
+                r#"CREATE INDEX "tags-title" ON "concerts" ("tags", "title");"#,
+                false,
+            )],
+            vec![(r#"DROP INDEX "tags-title";"#, false)],
+        )
+        .await
+    }
+
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn add_full_text_search() {
+        assert_changes(
+            r#"
+            @postgres
+            module ArticleModule {
+                type Article {
+                    @pk id: Int = autoIncrement()
+                    title: String
+                    body: String?
+                }
+            }
+            "#,
+            r#"
+            @postgres
+            module ArticleModule {
+                @fullTextSearch(fields=["title", "body"])
+                type Article {
+                    @pk id: Int = autoIncrement()
+                    title: String
+                    body: String?
+                }
+            }
+            "#,
+            vec![(
+                r#"CREATE TABLE "articles" (
+                    |    "id" SERIAL PRIMARY KEY,
+                    |    "title" TEXT NOT NULL,
+                    |    "body" TEXT
+                    |);"#,
+                false,
+            )],
+            vec![
+                (
+                    r#"CREATE TABLE "articles" (
+                    |    "id" SERIAL PRIMARY KEY,
+                    |    "title" TEXT NOT NULL,
+                    |    "body" TEXT,
+                    |    "search_vector" tsvector GENERATED ALWAYS AS (to_tsvector('english', coalesce("title", '') || ' ' || coalesce("body", ''))) STORED
+                    |);"#,
+                    false,
+                ),
+                (
+                    r#"CREATE INDEX "article_search_vector_idx" ON "articles" USING gin ("search_vector");"#,
+                    false,
+                ),
+            ],
+            vec![
+                (
+                    r#"ALTER TABLE "articles" ADD "search_vector" tsvector GENERATED ALWAYS AS (to_tsvector('english', coalesce("title", '') || ' ' || coalesce("body", ''))) STORED;"#,
+                    false,
+                ),
+                (
+                    r#"CREATE INDEX "article_search_vector_idx" ON "articles" USING gin ("search_vector");"#,
+                    false,
+                ),
+            ],
+            vec![(
+                r#"ALTER TABLE "articles" DROP COLUMN "search_vector";"#,
+                false,
+            )],
+        )
+        .await
+    }
+
     #[cfg_attr(not(target_family = "wasm"), tokio::test)]
     #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
     async fn modify_multi_column_indices() {
@@ -1208,6 +1607,96 @@ mod tests {
         .await
     }
 
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn enum_value_change() {
+        assert_changes(
+            r#"
+                @postgres
+                module BlogModule {
+                    enum Status {
+                        DRAFT,
+                        PUBLISHED
+                    }
+
+                    type Post {
+                        @pk id: Int = autoIncrement()
+                        status: Status = "DRAFT"
+                    }
+                }
+            "#,
+            r#"
+                @postgres
+                module BlogModule {
+                    enum Status {
+                        DRAFT,
+                        REVIEW, // Add a value
+                        PUBLISHED
+                    }
+
+                    type Post {
+                        @pk id: Int = autoIncrement()
+                        status: Status = "DRAFT"
+                    }
+                }
+            "#,
+            vec![
+                (
+                    r#"CREATE TYPE "status" AS ENUM ('DRAFT', 'PUBLISHED');"#,
+                    false,
+                ),
+                (
+                    r#"CREATE TABLE "posts" (
+                    |    "id" SERIAL PRIMARY KEY,
+                    |    "status" "status" NOT NULL DEFAULT 'DRAFT'::status
+                    |);"#,
+                    false,
+                ),
+            ],
+            vec![
+                (
+                    r#"CREATE TYPE "status" AS ENUM ('DRAFT', 'REVIEW', 'PUBLISHED');"#,
+                    false,
+                ),
+                (
+                    r#"CREATE TABLE "posts" (
+                    |    "id" SERIAL PRIMARY KEY,
+                    |    "status" "status" NOT NULL DEFAULT 'DRAFT'::status
+                    |);"#,
+                    false,
+                ),
+            ],
+            vec![(
+                r#"ALTER TYPE "status" ADD VALUE 'REVIEW' AFTER 'DRAFT';"#,
+                false,
+            )],
+            vec![
+                (
+                    r#"ALTER TYPE "status" RENAME TO "status_old";"#,
+                    true,
+                ),
+                (
+                    r#"CREATE TYPE "status" AS ENUM ('DRAFT', 'PUBLISHED');"#,
+                    false,
+                ),
+                (
+                    r#"ALTER TABLE "posts" ALTER COLUMN "status" DROP DEFAULT;"#,
+                    false,
+                ),
+                (
+                    r#"ALTER TABLE "posts" ALTER COLUMN "status" TYPE "status" USING "status"::text::"status";"#,
+                    true,
+                ),
+                (
+                    r#"ALTER TABLE "posts" ALTER COLUMN "status" SET DEFAULT 'DRAFT'::status;"#,
+                    false,
+                ),
+                (r#"DROP TYPE "status_old";"#, true),
+            ],
+        )
+        .await
+    }
+
     #[cfg_attr(not(target_family = "wasm"), tokio::test)]
     #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
     async fn not_null() {
@@ -1704,6 +2193,219 @@ mod tests {
         ).await
     }
 
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn add_subscribable_annotation() {
+        assert_changes(
+            r#"
+            @postgres
+            module ConcertModule {
+                type Concert {
+                    @pk id: Int = autoIncrement()
+                    title: String
+                }
+            }
+            "#,
+            r#"
+            @postgres
+            module ConcertModule {
+                @subscribable
+                type Concert {
+                    @pk id: Int = autoIncrement()
+                    title: String
+                }
+            }
+            "#,
+            vec![
+                ("CREATE TABLE \"concerts\" (\n    \"id\" SERIAL PRIMARY KEY,\n    \"title\" TEXT NOT NULL\n);", false)
+            ],
+            vec![
+                ("CREATE TABLE \"concerts\" (\n    \"id\" SERIAL PRIMARY KEY,\n    \"title\" TEXT NOT NULL\n);", false),
+                ("CREATE FUNCTION exograph_notify_concerts() RETURNS TRIGGER AS $$ DECLARE changed_row RECORD; payload TEXT; BEGIN IF TG_OP = 'DELETE' THEN changed_row := OLD; ELSE changed_row := NEW; END IF; payload := json_build_object('op', TG_OP, 'row', row_to_json(changed_row))::text; IF octet_length(payload) >= 8000 THEN payload := json_build_object('op', TG_OP, 'partial', true, 'row', json_build_object('id', changed_row.\"id\"))::text; END IF; PERFORM pg_notify('exograph_changes_concerts', payload); RETURN NULL; END; $$ language 'plpgsql';", false),
+                ("CREATE TRIGGER exograph_notify_on_insert_concerts AFTER INSERT ON concerts FOR EACH ROW EXECUTE FUNCTION exograph_notify_concerts();", false),
+                ("CREATE TRIGGER exograph_notify_on_update_concerts AFTER UPDATE ON concerts FOR EACH ROW EXECUTE FUNCTION exograph_notify_concerts();", false),
+                ("CREATE TRIGGER exograph_notify_on_delete_concerts AFTER DELETE ON concerts FOR EACH ROW EXECUTE FUNCTION exograph_notify_concerts();", false)
+            ],
+            vec![
+                ("CREATE FUNCTION exograph_notify_concerts() RETURNS TRIGGER AS $$ DECLARE changed_row RECORD; payload TEXT; BEGIN IF TG_OP = 'DELETE' THEN changed_row := OLD; ELSE changed_row := NEW; END IF; payload := json_build_object('op', TG_OP, 'row', row_to_json(changed_row))::text; IF octet_length(payload) >= 8000 THEN payload := json_build_object('op', TG_OP, 'partial', true, 'row', json_build_object('id', changed_row.\"id\"))::text; END IF; PERFORM pg_notify('exograph_changes_concerts', payload); RETURN NULL; END; $$ language 'plpgsql';", false),
+                ("CREATE TRIGGER exograph_notify_on_insert_concerts AFTER INSERT ON concerts FOR EACH ROW EXECUTE FUNCTION exograph_notify_concerts();", false),
+                ("CREATE TRIGGER exograph_notify_on_update_concerts AFTER UPDATE ON concerts FOR EACH ROW EXECUTE FUNCTION exograph_notify_concerts();", false),
+                ("CREATE TRIGGER exograph_notify_on_delete_concerts AFTER DELETE ON concerts FOR EACH ROW EXECUTE FUNCTION exograph_notify_concerts();", false)
+            ],
+            vec![
+                ("DROP TRIGGER exograph_notify_on_insert_concerts on \"concerts\";", false),
+                ("DROP TRIGGER exograph_notify_on_update_concerts on \"concerts\";", false),
+                ("DROP TRIGGER exograph_notify_on_delete_concerts on \"concerts\";", false),
+                ("DROP FUNCTION exograph_notify_concerts;", false)
+            ],
+        ).await
+    }
+
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn add_audited_annotation() {
+        assert_changes(
+            r#"
+            @postgres
+            module ConcertModule {
+                type Concert {
+                    @pk id: Int = autoIncrement()
+                    title: String
+                }
+            }
+            "#,
+            r#"
+            @postgres
+            module ConcertModule {
+                @audited
+                type Concert {
+                    @pk id: Int = autoIncrement()
+                    title: String
+                }
+            }
+            "#,
+            vec![
+                ("CREATE TABLE \"concerts\" (\n    \"id\" SERIAL PRIMARY KEY,\n    \"title\" TEXT NOT NULL\n);", false)
+            ],
+            vec![
+                ("CREATE TABLE \"concerts\" (\n    \"id\" SERIAL PRIMARY KEY,\n    \"title\" TEXT NOT NULL\n);", false),
+                ("CREATE TABLE \"concerts_history\" (\n    \"history_id\" BIGSERIAL PRIMARY KEY,\n    \"id\" INT NOT NULL,\n    \"operation\" TEXT NOT NULL,\n    \"changed_at\" TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),\n    \"changed_by\" TEXT,\n    \"old_row\" JSONB,\n    \"new_row\" JSONB\n);", false),
+                ("CREATE FUNCTION exograph_audit_concerts() RETURNS TRIGGER AS $$ DECLARE changed_row RECORD; BEGIN IF TG_OP = 'DELETE' THEN changed_row := OLD; ELSE changed_row := NEW; END IF; INSERT INTO \"concerts_history\" (\"id\", \"operation\", \"changed_by\", \"old_row\", \"new_row\") VALUES (changed_row.\"id\", TG_OP, NULLIF(NULLIF(current_setting('exograph.acting_user', true), '')::jsonb ->> 'concerts', ''), CASE WHEN TG_OP = 'INSERT' THEN NULL ELSE to_jsonb(OLD) END, CASE WHEN TG_OP = 'DELETE' THEN NULL ELSE to_jsonb(NEW) END); RETURN NULL; END; $$ language 'plpgsql';", false),
+                ("CREATE TRIGGER exograph_audit_on_insert_concerts AFTER INSERT ON concerts FOR EACH ROW EXECUTE FUNCTION exograph_audit_concerts();", false),
+                ("CREATE TRIGGER exograph_audit_on_update_concerts AFTER UPDATE ON concerts FOR EACH ROW EXECUTE FUNCTION exograph_audit_concerts();", false),
+                ("CREATE TRIGGER exograph_audit_on_delete_concerts AFTER DELETE ON concerts FOR EACH ROW EXECUTE FUNCTION exograph_audit_concerts();", false)
+            ],
+            vec![
+                ("CREATE TABLE \"concerts_history\" (\n    \"history_id\" BIGSERIAL PRIMARY KEY,\n    \"id\" INT NOT NULL,\n    \"operation\" TEXT NOT NULL,\n    \"changed_at\" TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),\n    \"changed_by\" TEXT,\n    \"old_row\" JSONB,\n    \"new_row\" JSONB\n);", false),
+                ("CREATE FUNCTION exograph_audit_concerts() RETURNS TRIGGER AS $$ DECLARE changed_row RECORD; BEGIN IF TG_OP = 'DELETE' THEN changed_row := OLD; ELSE changed_row := NEW; END IF; INSERT INTO \"concerts_history\" (\"id\", \"operation\", \"changed_by\", \"old_row\", \"new_row\") VALUES (changed_row.\"id\", TG_OP, NULLIF(NULLIF(current_setting('exograph.acting_user', true), '')::jsonb ->> 'concerts', ''), CASE WHEN TG_OP = 'INSERT' THEN NULL ELSE to_jsonb(OLD) END, CASE WHEN TG_OP = 'DELETE' THEN NULL ELSE to_jsonb(NEW) END); RETURN NULL; END; $$ language 'plpgsql';", false),
+                ("CREATE TRIGGER exograph_audit_on_insert_concerts AFTER INSERT ON concerts FOR EACH ROW EXECUTE FUNCTION exograph_audit_concerts();", false),
+                ("CREATE TRIGGER exograph_audit_on_update_concerts AFTER UPDATE ON concerts FOR EACH ROW EXECUTE FUNCTION exograph_audit_concerts();", false),
+                ("CREATE TRIGGER exograph_audit_on_delete_concerts AFTER DELETE ON concerts FOR EACH ROW EXECUTE FUNCTION exograph_audit_concerts();", false)
+            ],
+            vec![
+                ("DROP TRIGGER exograph_audit_on_insert_concerts on \"concerts\";", false),
+                ("DROP TRIGGER exograph_audit_on_update_concerts on \"concerts\";", false),
+                ("DROP TRIGGER exograph_audit_on_delete_concerts on \"concerts\";", false),
+                (r#"DROP TABLE "concerts_history" CASCADE;"#, true),
+                ("DROP FUNCTION exograph_audit_concerts;", false)
+            ],
+        ).await
+    }
+
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn add_view_types() {
+        assert_changes(
+            r#"
+            @postgres
+            module ConcertModule {
+                type Concert {
+                    @pk id: Int = autoIncrement()
+                    title: String
+                }
+            }
+            "#,
+            r#"
+            @postgres
+            module ConcertModule {
+                type Concert {
+                    @pk id: Int = autoIncrement()
+                    title: String
+                }
+
+                @view("concert_summaries")
+                type ConcertSummary {
+                    @pk id: Int
+                    title: String
+                }
+
+                @materializedView(name="concert_stats", schema="reporting")
+                type ConcertStat {
+                    @pk id: Int
+                    ticketCount: Int
+                }
+            }
+            "#,
+            vec![
+                ("CREATE TABLE \"concerts\" (\n    \"id\" SERIAL PRIMARY KEY,\n    \"title\" TEXT NOT NULL\n);", false)
+            ],
+            vec![
+                ("CREATE TABLE \"concerts\" (\n    \"id\" SERIAL PRIMARY KEY,\n    \"title\" TEXT NOT NULL\n);", false)
+            ],
+            vec![],
+            vec![],
+        ).await
+    }
+
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn table_and_view_conflicts() {
+        let table_system = compute_spec(
+            r#"
+            @postgres
+            module ConcertModule {
+                @table("concert_summaries")
+                type ConcertSummary {
+                    @pk id: Int = autoIncrement()
+                    title: String
+                }
+            }
+            "#,
+        )
+        .await;
+
+        let view_system = compute_spec(
+            r#"
+            @postgres
+            module ConcertModule {
+                @view("concert_summaries")
+                type ConcertSummary {
+                    @pk id: Int
+                    title: String
+                }
+            }
+            "#,
+        )
+        .await;
+
+        let materialized_view_system = compute_spec(
+            r#"
+            @postgres
+            module ConcertModule {
+                @materializedView("concert_summaries")
+                type ConcertSummary {
+                    @pk id: Int
+                    title: String
+                }
+            }
+            "#,
+        )
+        .await;
+
+        // Migrations leave both sides of a conflict alone...
+        assert_change(&table_system, &view_system, vec![], "Table to view");
+        assert_change(&view_system, &table_system, vec![], "View to table");
+
+        // ...so the conflict must be reported explicitly
+        assert_eq!(
+            table_kind_conflicts(&table_system, &view_system),
+            vec!["`concert_summaries` is a view in the model, but a table in the database."]
+        );
+        assert_eq!(
+            table_kind_conflicts(&view_system, &table_system),
+            vec!["`concert_summaries` is a table in the model, but a view in the database."]
+        );
+        assert_eq!(
+            table_kind_conflicts(&view_system, &materialized_view_system),
+            vec!["`concert_summaries` is a materialized view in the model, but a view in the database."]
+        );
+
+        assert!(table_kind_conflicts(&view_system, &view_system).is_empty());
+        assert!(table_kind_conflicts(&table_system, &table_system).is_empty());
+        // A missing view isn't a conflict (but `verify` reports it)
+        assert!(table_kind_conflicts(&DatabaseSpec::new(vec![], vec![]), &view_system).is_empty());
+    }
+
     async fn create_postgres_system_from_str(
         model_str: &str,
         file_name: String,
@@ -8,16 +8,18 @@
 // by the Apache License, Version 2.0.
 
 use async_graphql_parser::types::Type;
+use exo_sql::ColumnId;
 use serde::{Deserialize, Serialize};
 
-use crate::{predicate::PredicateParameter, types::MutationType};
+use crate::{predicate::PredicateParameter, types::MutationType, version::VersionParameter};
 use core_plugin_interface::core_model::mapped_arena::SerializableSlabIndex;
 use core_plugin_interface::core_model::type_normalization::Parameter;
 use core_plugin_interface::core_model::types::{FieldType, Named};
 
 use super::operation::{OperationParameters, PostgresOperation};
 
-/// A mutation such as `createTodo`, `updateTodo`, or `deleteTodo`
+/// A mutation such as `createTodo`, `updateTodo`, `deleteTodo`, `upsertTodo`, `restoreTodo`, or
+/// `refreshTodoSummaries`
 pub type PostgresMutation = PostgresOperation<PostgresMutationParameters>;
 
 /// Mutation parameters
@@ -30,30 +32,66 @@ pub enum PostgresMutationParameters {
     Create(DataParameter),
 
     /// Parameters for a delete mutation such as `deleteTodo` or `deleteTodos`
-    /// The only parameter is a predicate such as `id: 1` or `where: {complete: {eq: true}}`
+    /// The parameters are predicates such as `id: 1` (one per primary key field) or
+    /// `where: {complete: {eq: true}}`
     /// `{ deleteTodo(id: 1)` or `{ deleteTodos(where: { complete: {eq: true }}) }`
-    Delete(PredicateParameter),
+    Delete(Vec<PredicateParameter>),
 
     /// Parameters for an update mutation such as `updateTodo` or `updateTodos`
-    /// It takes two parameters: a predicate such as `id: 1` or `where: {complete: {eq: true}}`
+    /// It takes predicates such as `id: 1` (one per primary key field) or `where: {complete: {eq: true}}`
     /// and the data to be updated such as `data: { title: "New title" }`.
     /// This allows mutations such as `{ updateTodo(id: 1, data: { title: "New title" }) }` and
     /// `{ updateTodos(where: { complete: {eq: true }}, data: { title: "New title" }) }`
+    /// For a type with a `@version` field, `updateTodo` also takes the expected version such as
+    /// `version: 3`.
     Update {
         data_param: DataParameter,
-        predicate_param: PredicateParameter,
+        predicate_params: Vec<PredicateParameter>,
+        version_param: Option<VersionParameter>,
     },
+
+    /// Parameters for an upsert mutation such as `upsertTodo`, `upsertTodos`, or `upsertTodoByTitle`
+    /// The only parameter is the data such as `data: { id: 1, title: "New title" }`. A row that
+    /// conflicts with an existing one on the `conflict_columns` (the primary key or a unique
+    /// constraint) updates the existing row instead of creating a new one.
+    Upsert {
+        data_param: DataParameter,
+        conflict_columns: Vec<ColumnId>,
+    },
+
+    /// Parameters for a restore mutation such as `restoreTodo` or `restoreTodos` (only for a type
+    /// with the `@softDelete` annotation)
+    /// The parameters are predicates such as `id: 1` (one per primary key field) or
+    /// `where: {complete: {eq: true}}` to select the soft-deleted entities to restore.
+    Restore(Vec<PredicateParameter>),
+
+    /// Parameters for a refresh mutation such as `refreshTodoSummaries` (only for a type with the
+    /// `@materializedView` annotation)
+    /// There are no parameters: the mutation re-computes the whole view and returns its content.
+    Refresh,
 }
 
 impl OperationParameters for PostgresMutationParameters {
     fn introspect(&self) -> Vec<&dyn Parameter> {
         match &self {
             PostgresMutationParameters::Create(data_param) => vec![data_param],
-            PostgresMutationParameters::Delete(predicate_param) => vec![predicate_param],
+            PostgresMutationParameters::Delete(predicate_params)
+            | PostgresMutationParameters::Restore(predicate_params) => predicate_params
+                .iter()
+                .map(|p| p as &dyn Parameter)
+                .collect(),
             PostgresMutationParameters::Update {
                 data_param,
-                predicate_param,
-            } => vec![predicate_param, data_param],
+                predicate_params,
+                version_param,
+            } => predicate_params
+                .iter()
+                .map(|p| p as &dyn Parameter)
+                .chain(version_param.iter().map(|p| p as &dyn Parameter))
+                .chain(std::iter::once(data_param as &dyn Parameter))
+                .collect(),
+            PostgresMutationParameters::Upsert { data_param, .. } => vec![data_param],
+            PostgresMutationParameters::Refresh => vec![],
         }
     }
 }
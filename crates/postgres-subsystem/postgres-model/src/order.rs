@@ -7,7 +7,7 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use crate::{access::Access, subsystem::PostgresSubsystem};
+use crate::{access::Access, subsystem::PostgresSubsystem, types::EntityType};
 
 use async_graphql_parser::{
     types::{
@@ -64,11 +64,30 @@ pub struct OrderByParameterType {
 pub enum OrderByParameterTypeKind {
     Primitive,
     Vector,
-    Composite { parameters: Vec<OrderByParameter> },
+    TextSearch, // {query: "rust async", mode: WEBSEARCH, order: DESC}, to order by the search rank
+    Composite {
+        parameters: Vec<OrderByParameter>,
+    },
+    /// Aggregates over the entities related through a one-to-many relation such as
+    /// `{price: {max: DESC}}` (when used as `venues(orderBy: {concertsAgg: {price: {max: DESC}}})`).
+    /// There is a parameter for each field that may be aggregated.
+    Aggregate {
+        entity_type_id: SerializableSlabIndex<EntityType>,
+        parameters: Vec<OrderByParameter>,
+    },
+    /// Aggregates of a scalar field such as `{max: DESC}` (the functions are "count", "max", etc.)
+    ScalarAggregate {
+        functions: Vec<String>,
+    },
 }
 
 pub const PRIMITIVE_ORDERING_OPTIONS: [&str; 2] = ["ASC", "DESC"];
 
+/// The name of the input type to order by an aggregate (such as `ConcertAggOrdering` or `IntAggOrdering`)
+pub fn aggregate_ordering_type_name(aggregate_type_name: &str) -> String {
+    format!("{aggregate_type_name}Ordering")
+}
+
 impl Named for OrderByParameterTypeWrapper {
     fn name(&self) -> &str {
         &self.name
@@ -88,7 +107,8 @@ impl Parameter for OrderByParameter {
 impl TypeDefinitionProvider<PostgresSubsystem> for OrderByParameterType {
     fn type_definition(&self, _system: &PostgresSubsystem) -> TypeDefinition {
         match &self.kind {
-            OrderByParameterTypeKind::Composite { parameters } => {
+            OrderByParameterTypeKind::Composite { parameters }
+            | OrderByParameterTypeKind::Aggregate { parameters, .. } => {
                 let fields = parameters
                     .iter()
                     .map(|parameter| default_positioned(parameter.input_value()))
@@ -123,6 +143,31 @@ impl TypeDefinitionProvider<PostgresSubsystem> for OrderByParameterType {
                         .collect(),
                 }),
             },
+            OrderByParameterTypeKind::ScalarAggregate { functions } => {
+                let fields = functions
+                    .iter()
+                    .map(|function| {
+                        default_positioned(InputValueDefinition {
+                            description: None,
+                            name: default_positioned_name(function),
+                            directives: vec![],
+                            default_value: None,
+                            ty: default_positioned(Type {
+                                base: BaseType::Named(Name::new("Ordering")),
+                                nullable: true,
+                            }),
+                        })
+                    })
+                    .collect();
+
+                TypeDefinition {
+                    extend: false,
+                    description: None,
+                    name: default_positioned_name(&self.name),
+                    directives: vec![],
+                    kind: TypeKind::InputObject(InputObjectType { fields }),
+                }
+            }
             OrderByParameterTypeKind::Vector => {
                 let fields = vec![
                     InputValueDefinition {
@@ -147,6 +192,51 @@ impl TypeDefinitionProvider<PostgresSubsystem> for OrderByParameterType {
                 .map(default_positioned)
                 .collect();
 
+                TypeDefinition {
+                    extend: false,
+                    description: None,
+                    name: default_positioned_name(&self.name),
+                    directives: vec![],
+                    kind: TypeKind::InputObject(InputObjectType { fields }),
+                }
+            }
+            OrderByParameterTypeKind::TextSearch => {
+                let fields = vec![
+                    InputValueDefinition {
+                        description: None,
+                        name: default_positioned_name("query"),
+                        directives: vec![],
+                        default_value: None,
+                        ty: default_positioned(Type {
+                            base: BaseType::Named(Name::new("String")),
+                            nullable: false,
+                        }),
+                    },
+                    InputValueDefinition {
+                        description: None,
+                        name: default_positioned_name("mode"),
+                        directives: vec![],
+                        default_value: None,
+                        ty: default_positioned(Type {
+                            base: BaseType::Named(Name::new("TextSearchMode")),
+                            nullable: true,
+                        }),
+                    },
+                    InputValueDefinition {
+                        description: None,
+                        name: default_positioned_name("order"),
+                        directives: vec![],
+                        default_value: None,
+                        ty: default_positioned(Type {
+                            base: BaseType::Named(Name::new("Ordering")),
+                            nullable: true,
+                        }),
+                    },
+                ]
+                .into_iter()
+                .map(default_positioned)
+                .collect();
+
                 TypeDefinition {
                     extend: false,
                     description: None,
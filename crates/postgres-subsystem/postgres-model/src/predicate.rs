@@ -8,9 +8,14 @@
 // by the Apache License, Version 2.0.
 
 use crate::{access::Access, subsystem::PostgresSubsystem};
-use async_graphql_parser::types::{
-    InputObjectType, InputValueDefinition, Type, TypeDefinition, TypeKind,
+use async_graphql_parser::{
+    types::{
+        BaseType, EnumType, EnumValueDefinition, InputObjectType, InputValueDefinition, Type,
+        TypeDefinition, TypeKind,
+    },
+    Pos, Positioned,
 };
+use async_graphql_value::Name;
 use core_plugin_interface::core_model::{
     mapped_arena::SerializableSlabIndex,
     primitive_type::vector_introspection_type,
@@ -40,6 +45,11 @@ pub struct PredicateParameter {
     ///   where: None
     /// ```
     pub column_path_link: Option<ColumnPathLink>,
+    /// For a many-to-many field, the link from the linking table to the foreign table (the
+    /// `column_path_link` is the link from the self table to the linking table). For example, for the
+    /// `artists` parameter used as `{where: {artists: {some: {name: {eq: "A"}}}}}`, it will be
+    /// `(<the concert_artists.artist_id column>, <the artists.id column>)`.
+    pub foreign_column_path_link: Option<ColumnPathLink>,
     pub access: Option<Access>,
     // TODO: Generalize this to support more than just vector distance functions
     pub vector_distance_function: Option<VectorDistanceFunction>,
@@ -73,14 +83,20 @@ impl Named for PredicateParameterTypeWrapper {
 pub enum PredicateParameterTypeKind {
     ImplicitEqual,                     // {id: 3}
     Operator(Vec<PredicateParameter>), // {lt: ..,gt: ..} such as IntFilter
-    Vector, // {similar: <vector-value>, distance: {<operator such as lt/gt>: <value>}}
+    Vector,         // {similar: <vector-value>, distance: {<operator such as lt/gt>: <value>}}
+    TextSearch,     // {search: {query: "rust async", mode: WEBSEARCH}}
+    TextSearchMode, // PLAIN or WEBSEARCH
     Composite {
         field_params: Vec<PredicateParameter>, // {where: {id: .., name: ..}} such as AccountFilter
         logical_op_params: Vec<PredicateParameter>, // logical operator predicates like `and: [{name: ..}, {id: ..}]`
+        is_null_param: PredicateParameter, // `isNull` for a relation such as `{venue: {isNull: true}}`
     },
-    Reference(Vec<PredicateParameter>), // {venue: {id: 3}}
+    Reference(Vec<PredicateParameter>),  // {venue: {id: 3}}
+    ManyToMany(Vec<PredicateParameter>), // {artists: {some: {name: {eq: "A"}}}}, also "every" and "none"
 }
 
+pub const TEXT_SEARCH_MODE_OPTIONS: [&str; 2] = ["PLAIN", "WEBSEARCH"];
+
 impl Parameter for PredicateParameter {
     fn name(&self) -> &str {
         &self.name
@@ -95,7 +111,8 @@ impl TypeDefinitionProvider<PostgresSubsystem> for PredicateParameterType {
     fn type_definition(&self, _system: &PostgresSubsystem) -> TypeDefinition {
         match &self.kind {
             PredicateParameterTypeKind::Operator(parameters)
-            | PredicateParameterTypeKind::Reference(parameters) => {
+            | PredicateParameterTypeKind::Reference(parameters)
+            | PredicateParameterTypeKind::ManyToMany(parameters) => {
                 let fields = parameters
                     .iter()
                     .map(|parameter| default_positioned(parameter.input_value()))
@@ -112,8 +129,12 @@ impl TypeDefinitionProvider<PostgresSubsystem> for PredicateParameterType {
             PredicateParameterTypeKind::Composite {
                 field_params,
                 logical_op_params,
+                is_null_param,
             } => {
-                let parameters = field_params.iter().chain(logical_op_params.iter());
+                let parameters = field_params
+                    .iter()
+                    .chain(logical_op_params.iter())
+                    .chain(std::iter::once(is_null_param));
 
                 let fields = parameters
                     .map(|parameter| default_positioned(parameter.input_value()))
@@ -163,6 +184,62 @@ impl TypeDefinitionProvider<PostgresSubsystem> for PredicateParameterType {
                     kind: TypeKind::InputObject(InputObjectType { fields }),
                 }
             }
+            PredicateParameterTypeKind::TextSearch => {
+                let fields = vec![
+                    InputValueDefinition {
+                        description: None,
+                        name: default_positioned_name("query"),
+                        ty: default_positioned(Type {
+                            base: BaseType::Named(Name::new("String")),
+                            nullable: false,
+                        }),
+                        default_value: None,
+                        directives: vec![],
+                    },
+                    InputValueDefinition {
+                        description: None,
+                        name: default_positioned_name("mode"),
+                        ty: default_positioned(Type {
+                            base: BaseType::Named(Name::new("TextSearchMode")),
+                            nullable: true,
+                        }),
+                        default_value: None,
+                        directives: vec![],
+                    },
+                ]
+                .into_iter()
+                .map(default_positioned)
+                .collect();
+
+                TypeDefinition {
+                    extend: false,
+                    description: None,
+                    name: default_positioned_name(&self.name),
+                    directives: vec![],
+                    kind: TypeKind::InputObject(InputObjectType { fields }),
+                }
+            }
+            PredicateParameterTypeKind::TextSearchMode => TypeDefinition {
+                extend: false,
+                description: None,
+                name: default_positioned_name(&self.name),
+                directives: vec![],
+                kind: TypeKind::Enum(EnumType {
+                    values: TEXT_SEARCH_MODE_OPTIONS
+                        .iter()
+                        .map(|value| {
+                            Positioned::new(
+                                EnumValueDefinition {
+                                    description: None,
+                                    value: Positioned::new(Name::new(value), Pos::default()),
+                                    directives: vec![],
+                                },
+                                Pos::default(),
+                            )
+                        })
+                        .collect(),
+                }),
+            },
         }
     }
 }
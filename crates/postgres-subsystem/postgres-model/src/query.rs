@@ -12,9 +12,12 @@ use serde::{Deserialize, Serialize};
 use core_plugin_interface::core_model::type_normalization::Parameter;
 
 use crate::{
+    connection::CursorParameter,
+    group_by::GroupByParameter,
     limit_offset::{LimitParameter, OffsetParameter},
     order::OrderByParameter,
     predicate::PredicateParameter,
+    soft_delete::IncludeDeletedParameter,
 };
 
 use super::operation::{OperationParameters, PostgresOperation};
@@ -22,15 +25,27 @@ use super::operation::{OperationParameters, PostgresOperation};
 /// Query by primary key such as `todo(id: 1)`
 pub type PkQuery = PostgresOperation<PkQueryParameters>;
 
-/// Primary key query parameter such as `id: 1` in `todo(id: 1)`
+/// Primary key query parameters such as `id: 1` in `todo(id: 1)` (one per primary key field, so
+/// `tenantId: 1, id: 2` in `todo(tenantId: 1, id: 2)` for a composite primary key)
 #[derive(Serialize, Deserialize, Debug)]
 pub struct PkQueryParameters {
-    pub predicate_param: PredicateParameter,
+    pub predicate_params: Vec<PredicateParameter>,
+    /// The parameter to include a soft-deleted entity such as `includeDeleted: true` (only for
+    /// a type with the `@softDelete` annotation)
+    pub include_deleted_param: Option<IncludeDeletedParameter>,
 }
 
 impl OperationParameters for PkQueryParameters {
     fn introspect(&self) -> Vec<&dyn Parameter> {
-        vec![&self.predicate_param]
+        self.predicate_params
+            .iter()
+            .map(|p| p as &dyn Parameter)
+            .chain(
+                self.include_deleted_param
+                    .iter()
+                    .map(|p| p as &dyn Parameter),
+            )
+            .collect()
     }
 }
 
@@ -48,15 +63,55 @@ pub struct CollectionQueryParameters {
     pub limit_param: LimitParameter,
     /// The offset parameter such as `offset: 20`
     pub offset_param: OffsetParameter,
+    /// The parameter to include soft-deleted entities such as `includeDeleted: true` (only for
+    /// a type with the `@softDelete` annotation)
+    pub include_deleted_param: Option<IncludeDeletedParameter>,
 }
 
 impl OperationParameters for CollectionQueryParameters {
     fn introspect(&self) -> Vec<&dyn Parameter> {
-        vec![
+        let mut params: Vec<&dyn Parameter> = vec![
             &self.predicate_param,
             &self.order_by_param,
             &self.limit_param,
             &self.offset_param,
+        ];
+        if let Some(include_deleted_param) = &self.include_deleted_param {
+            params.push(include_deleted_param);
+        }
+        params
+    }
+}
+
+/// Query that returns a page of a collection such as `todosConnection(first: 10, after: "...")`
+pub type ConnectionQuery = PostgresOperation<ConnectionQueryParameters>;
+
+/// Connection query parameters
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ConnectionQueryParameters {
+    /// The number of elements to return after the `after` cursor such as `first: 10`
+    pub first_param: LimitParameter,
+    /// The cursor after which to return elements such as `after: "..."`
+    pub after_param: CursorParameter,
+    /// The number of elements to return before the `before` cursor such as `last: 10`
+    pub last_param: LimitParameter,
+    /// The cursor before which to return elements such as `before: "..."`
+    pub before_param: CursorParameter,
+    /// The predicate parameter such as `where: { title: { eq: "Hello" } }`
+    pub predicate_param: PredicateParameter,
+    /// The order by parameter such as `orderBy: { title: ASC }`
+    pub order_by_param: OrderByParameter,
+}
+
+impl OperationParameters for ConnectionQueryParameters {
+    fn introspect(&self) -> Vec<&dyn Parameter> {
+        vec![
+            &self.first_param,
+            &self.after_param,
+            &self.last_param,
+            &self.before_param,
+            &self.predicate_param,
+            &self.order_by_param,
         ]
     }
 }
@@ -76,12 +131,48 @@ impl OperationParameters for AggregateQueryParameters {
     }
 }
 
+/// Query that returns aggregates for groups of rows such as `todosGroupBy(by: [completed]) { key { completed } count }`
+pub type GroupByQuery = PostgresOperation<GroupByQueryParameters>;
+
+/// Group-by query parameters
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GroupByQueryParameters {
+    /// The fields to group by such as `by: [completed]`
+    pub by_param: GroupByParameter,
+    /// The predicate parameter to filter rows before grouping such as `where: { title: { eq: "Hello" } }`
+    pub predicate_param: PredicateParameter,
+    /// The predicate parameter to filter the groups such as `having: { count: { gt: 1 } }`
+    pub having_param: GroupByParameter,
+    /// The order by parameter for the groups such as `orderBy: { count: DESC }`
+    pub order_by_param: GroupByParameter,
+    /// The limit parameter such as `limit: 10`
+    pub limit_param: LimitParameter,
+    /// The offset parameter such as `offset: 20`
+    pub offset_param: OffsetParameter,
+}
+
+impl OperationParameters for GroupByQueryParameters {
+    fn introspect(&self) -> Vec<&dyn Parameter> {
+        vec![
+            &self.by_param,
+            &self.predicate_param,
+            &self.having_param,
+            &self.order_by_param,
+            &self.limit_param,
+            &self.offset_param,
+        ]
+    }
+}
+
 /// Query by unique constrained parameters such as `userByEmail(email: "hello@example.com")` or `userByFirstAndLastName(firstName: "John", lastName: "Doe")`
 pub type UniqueQuery = PostgresOperation<UniqueQueryParameters>;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct UniqueQueryParameters {
     pub predicate_params: Vec<PredicateParameter>,
+    /// The parameter to include a soft-deleted entity such as `includeDeleted: true` (only for
+    /// a type with the `@softDelete` annotation)
+    pub include_deleted_param: Option<IncludeDeletedParameter>,
 }
 
 impl OperationParameters for UniqueQueryParameters {
@@ -89,6 +180,11 @@ impl OperationParameters for UniqueQueryParameters {
         self.predicate_params
             .iter()
             .map(|p| p as &dyn Parameter)
+            .chain(
+                self.include_deleted_param
+                    .iter()
+                    .map(|p| p as &dyn Parameter),
+            )
             .collect()
     }
 }
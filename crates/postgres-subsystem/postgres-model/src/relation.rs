@@ -9,7 +9,7 @@
 
 use crate::types::EntityFieldId;
 
-use exo_sql::{ColumnId, ColumnPathLink, Database, ManyToOneId, OneToManyId};
+use exo_sql::{ColumnId, ColumnPathLink, Database, ManyToManyId, ManyToOneId, OneToManyId};
 use serde::{Deserialize, Serialize};
 
 // We model one-to-one (more precisely one-to-one_or_zero and one_or_zero-to-one) relations as
@@ -27,17 +27,20 @@ pub enum PostgresRelation {
     Scalar { column_id: ColumnId },
     ManyToOne(ManyToOneRelation),
     OneToMany(OneToManyRelation),
+    ManyToMany(ManyToManyRelation),
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ManyToOneRelation {
     // For the `Concert.venue` field (assuming [Concert] -> Venue), we will have:
     // - cardinality: Unbounded
-    // - foreign_pk_field_id: Venue.id
-    // - relation_id.self_column_id: concerts.venue_id
-    // - relation_id.foreign_pk_column_id: venues.id
+    // - foreign_pk_field_ids: [Venue.id]
+    // - relation_id.column_pairs: [(concerts.venue_id, venues.id)]
+    //
+    // With a composite primary key, there is a field and a column pair for each of its columns (in
+    // the same order).
     pub cardinality: RelationCardinality,
-    pub foreign_pk_field_id: EntityFieldId,
+    pub foreign_pk_field_ids: Vec<EntityFieldId>,
     pub relation_id: ManyToOneId,
 }
 
@@ -54,8 +57,7 @@ pub struct OneToManyRelation {
     // For the `Venue.concerts` field (assuming Venue -> [Concert]), we will have:
     // - cardinality: Unbounded
     // - foreign_field_id: Concert.venue
-    // - relation_id.self_pk_column_id: venues.id
-    // - relation_id.foreign_column_id: concerts.venue_id
+    // - relation_id.column_pairs: [(venues.id, concerts.venue_id)]
     pub cardinality: RelationCardinality,
     pub foreign_field_id: EntityFieldId,
     pub relation_id: OneToManyId,
@@ -68,6 +70,32 @@ impl OneToManyRelation {
     }
 }
 
+/// Model for a many-to-many relation through a linking table (that isn't exposed as a type).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ManyToManyRelation {
+    // For the `Concert.artists` field (assuming Concert <-> Artist through the `concert_artists`
+    // table), we will have:
+    // - foreign_field_id: Artist.concerts
+    // - relation_id.self_link_id.column_pairs: [(concerts.id, concert_artists.concert_id)]
+    // - relation_id.foreign_link_id.column_pairs: [(concert_artists.artist_id, artists.id)]
+    pub foreign_field_id: EntityFieldId,
+    pub relation_id: ManyToManyId,
+}
+
+impl ManyToManyRelation {
+    /// The link from the self table to the linking table
+    pub fn column_path_link(&self, database: &Database) -> ColumnPathLink {
+        let relation = self.relation_id.deref(database);
+        relation.self_link.column_path_link()
+    }
+
+    /// The link from the linking table to the foreign table
+    pub fn foreign_column_path_link(&self, database: &Database) -> ColumnPathLink {
+        let relation = self.relation_id.deref(database);
+        relation.foreign_link.column_path_link()
+    }
+}
+
 impl PostgresRelation {
     pub fn column_path_link(&self, database: &Database) -> ColumnPathLink {
         match &self {
@@ -76,6 +104,7 @@ impl PostgresRelation {
             }
             PostgresRelation::ManyToOne(relation) => relation.column_path_link(database),
             PostgresRelation::OneToMany(relation) => relation.column_path_link(database),
+            PostgresRelation::ManyToMany(relation) => relation.column_path_link(database),
         }
     }
 }
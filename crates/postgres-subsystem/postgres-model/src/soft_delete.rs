@@ -0,0 +1,62 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use crate::types::PostgresPrimitiveType;
+use async_graphql_parser::types::Type;
+use core_plugin_interface::core_model::{
+    mapped_arena::SerializableSlabIndex,
+    type_normalization::Parameter,
+    types::{FieldType, Named},
+};
+use exo_sql::ColumnId;
+use serde::{Deserialize, Serialize};
+
+/// Soft deletion of a type with the `@softDelete(field="deletedAt")` annotation. Deleting an
+/// entity sets its timestamp field to the current time instead of removing the row, and queries
+/// leave out such entities.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SoftDelete {
+    /// The name of the timestamp field such as `deletedAt`
+    pub field_name: String,
+    /// The column of the timestamp field (`NULL` for entities that are not deleted)
+    pub column_id: ColumnId,
+}
+
+/// The name of the parameter to include soft-deleted entities
+pub const INCLUDE_DELETED_PARAM_NAME: &str = "includeDeleted";
+
+/// The parameter to include soft-deleted entities such as `includeDeleted: true` in
+/// `concerts(includeDeleted: true)`
+#[derive(Serialize, Deserialize, Debug)]
+pub struct IncludeDeletedParameter {
+    pub name: String,
+    pub typ: FieldType<IncludeDeletedParameterType>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct IncludeDeletedParameterType {
+    pub type_name: String,
+    pub type_id: SerializableSlabIndex<PostgresPrimitiveType>,
+}
+
+impl Named for IncludeDeletedParameterType {
+    fn name(&self) -> &str {
+        &self.type_name
+    }
+}
+
+impl Parameter for IncludeDeletedParameter {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn typ(&self) -> Type {
+        (&self.typ).into()
+    }
+}
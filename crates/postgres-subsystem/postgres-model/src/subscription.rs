@@ -0,0 +1,53 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use serde::{Deserialize, Serialize};
+
+use core_plugin_interface::core_model::type_normalization::Parameter;
+
+use crate::predicate::PredicateParameter;
+
+use super::operation::{OperationParameters, PostgresOperation};
+
+/// A subscription such as `onTodoCreated`, `onTodoUpdated`, or `onTodoDeleted`
+pub type PostgresSubscription = PostgresOperation<PostgresSubscriptionParameters>;
+
+/// Subscription parameters
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PostgresSubscriptionParameters {
+    /// The kind of change to subscribe to
+    pub kind: SubscriptionKind,
+    /// The predicate parameter such as `where: { title: { eq: "Hello" } }` to filter the pushed rows
+    pub predicate_param: PredicateParameter,
+}
+
+/// The kind of change that triggers a subscription event (maps to the `TG_OP` of the notifying trigger)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubscriptionKind {
+    Created,
+    Updated,
+    Deleted,
+}
+
+impl SubscriptionKind {
+    /// The operation name reported by Postgres triggers (`TG_OP`) for this kind
+    pub fn trigger_operation(&self) -> &'static str {
+        match self {
+            SubscriptionKind::Created => "INSERT",
+            SubscriptionKind::Updated => "UPDATE",
+            SubscriptionKind::Deleted => "DELETE",
+        }
+    }
+}
+
+impl OperationParameters for PostgresSubscriptionParameters {
+    fn introspect(&self) -> Vec<&dyn Parameter> {
+        vec![&self.predicate_param]
+    }
+}
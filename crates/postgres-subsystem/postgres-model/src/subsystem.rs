@@ -18,7 +18,12 @@ use super::{
 use crate::{
     access::{DatabaseAccessPrimitiveExpression, InputAccessPrimitiveExpression},
     aggregate::AggregateType,
-    query::{AggregateQuery, CollectionQuery, UniqueQuery},
+    audit::{HistoryQuery, HistoryType},
+    connection::{page_info_type_definition, ConnectionType},
+    function::FunctionOperation,
+    group_by::{group_type_definitions, GroupType},
+    query::{AggregateQuery, CollectionQuery, ConnectionQuery, GroupByQuery, UniqueQuery},
+    subscription::PostgresSubscription,
     types::{EntityType, MutationType, PostgresPrimitiveType},
 };
 use core_plugin_interface::{
@@ -41,6 +46,9 @@ pub struct PostgresSubsystem {
     pub entity_types: SerializableSlab<EntityType>,
 
     pub aggregate_types: SerializableSlab<AggregateType>,
+    pub connection_types: MappedArena<ConnectionType>,
+    pub group_types: MappedArena<GroupType>,
+    pub history_types: MappedArena<HistoryType>,
 
     // query related
     pub order_by_types: SerializableSlab<OrderByParameterType>,
@@ -48,12 +56,20 @@ pub struct PostgresSubsystem {
 
     pub pk_queries: MappedArena<PkQuery>,
     pub collection_queries: MappedArena<CollectionQuery>,
+    pub connection_queries: MappedArena<ConnectionQuery>,
     pub aggregate_queries: MappedArena<AggregateQuery>,
+    pub group_by_queries: MappedArena<GroupByQuery>,
     pub unique_queries: MappedArena<UniqueQuery>,
+    pub history_queries: MappedArena<HistoryQuery>,
+    pub function_queries: MappedArena<FunctionOperation>,
 
     // mutation related
     pub mutation_types: SerializableSlab<MutationType>, // create, update, delete input types such as `PersonUpdateInput`
     pub mutations: MappedArena<PostgresMutation>,
+    pub function_mutations: MappedArena<FunctionOperation>,
+
+    // subscription related
+    pub subscriptions: MappedArena<PostgresSubscription>,
 
     pub input_access_expressions:
         SerializableSlab<AccessPredicateExpression<InputAccessPrimitiveExpression>>,
@@ -75,27 +91,65 @@ impl PostgresSubsystem {
             .iter()
             .map(|(_, query)| query.field_definition(self));
 
+        let connection_queries_defn = self
+            .connection_queries
+            .iter()
+            .map(|(_, query)| query.field_definition(self));
+
         let aggregate_queries_defn = self
             .aggregate_queries
             .iter()
             .map(|query| query.1.field_definition(self));
 
+        let group_by_queries_defn = self
+            .group_by_queries
+            .iter()
+            .map(|(_, query)| query.field_definition(self));
+
         let unique_queries_defn = self
             .unique_queries
             .iter()
             .map(|(_, query)| query.field_definition(self));
 
+        let history_queries_defn = self
+            .history_queries
+            .iter()
+            .map(|(_, query)| query.field_definition(self));
+
+        let function_queries_defn = self
+            .function_queries
+            .iter()
+            .map(|(_, query)| query.field_definition(self));
+
         pk_queries_defn
             .chain(collection_queries_defn)
+            .chain(connection_queries_defn)
             .chain(aggregate_queries_defn)
+            .chain(group_by_queries_defn)
             .chain(unique_queries_defn)
+            .chain(history_queries_defn)
+            .chain(function_queries_defn)
             .collect()
     }
 
     pub fn schema_mutations(&self) -> Vec<FieldDefinition> {
-        self.mutations
+        let mutations_defn = self
+            .mutations
             .iter()
-            .map(|(_, mutation)| mutation.field_definition(self))
+            .map(|(_, mutation)| mutation.field_definition(self));
+
+        let function_mutations_defn = self
+            .function_mutations
+            .iter()
+            .map(|(_, mutation)| mutation.field_definition(self));
+
+        mutations_defn.chain(function_mutations_defn).collect()
+    }
+
+    pub fn schema_subscriptions(&self) -> Vec<FieldDefinition> {
+        self.subscriptions
+            .iter()
+            .map(|(_, subscription)| subscription.field_definition(self))
             .collect()
     }
 
@@ -114,6 +168,21 @@ impl PostgresSubsystem {
             .iter()
             .for_each(|typ| all_type_definitions.push(typ.1.type_definition(self)));
 
+        self.connection_types.iter().for_each(|(_, typ)| {
+            all_type_definitions.push(typ.type_definition(self));
+            all_type_definitions.push(typ.edge_type_definition(self));
+        });
+
+        if !self.connection_types.is_empty() {
+            all_type_definitions.push(page_info_type_definition());
+        }
+
+        all_type_definitions.extend(group_type_definitions(self));
+
+        self.history_types
+            .iter()
+            .for_each(|(_, typ)| all_type_definitions.push(typ.type_definition(self)));
+
         self.order_by_types.iter().for_each(|parameter_type| {
             all_type_definitions.push(parameter_type.1.type_definition(self))
         });
@@ -137,14 +206,23 @@ impl Default for PostgresSubsystem {
             primitive_types: SerializableSlab::new(),
             entity_types: SerializableSlab::new(),
             aggregate_types: SerializableSlab::new(),
+            connection_types: MappedArena::default(),
+            group_types: MappedArena::default(),
+            history_types: MappedArena::default(),
             order_by_types: SerializableSlab::new(),
             predicate_types: SerializableSlab::new(),
             pk_queries: MappedArena::default(),
             collection_queries: MappedArena::default(),
+            connection_queries: MappedArena::default(),
             aggregate_queries: MappedArena::default(),
+            group_by_queries: MappedArena::default(),
             unique_queries: MappedArena::default(),
+            history_queries: MappedArena::default(),
+            function_queries: MappedArena::default(),
             mutation_types: SerializableSlab::new(),
             mutations: MappedArena::default(),
+            function_mutations: MappedArena::default(),
+            subscriptions: MappedArena::default(),
 
             input_access_expressions: SerializableSlab::new(),
             database_access_expressions: SerializableSlab::new(),
@@ -11,14 +11,20 @@ use super::access::Access;
 use super::relation::PostgresRelation;
 use crate::access::{DatabaseAccessPrimitiveExpression, InputAccessPrimitiveExpression};
 use crate::aggregate::AggregateField;
+use crate::audit::Audit;
 use crate::query::{AggregateQuery, CollectionQuery, CollectionQueryParameters, PkQuery};
-use crate::relation::OneToManyRelation;
+use crate::relation::{ManyToManyRelation, OneToManyRelation};
+use crate::soft_delete::SoftDelete;
 use crate::subsystem::PostgresSubsystem;
 use crate::vector_distance::VectorDistanceField;
+use crate::version::Version;
 use async_graphql_parser::types::{
-    FieldDefinition, InputObjectType, ObjectType, Type, TypeDefinition, TypeKind,
+    EnumType, EnumValueDefinition, FieldDefinition, InputObjectType, ObjectType, Type,
+    TypeDefinition, TypeKind,
 };
+use async_graphql_value::Name;
 use core_plugin_interface::core_model::access::AccessPredicateExpression;
+use core_plugin_interface::core_model::cache_policy::{cache_policy_directives, CachePolicy};
 use core_plugin_interface::core_model::context_type::ContextSelection;
 use core_plugin_interface::core_model::primitive_type::vector_introspection_base_type;
 use core_plugin_interface::core_model::{
@@ -29,7 +35,7 @@ use core_plugin_interface::core_model::{
     },
     types::{FieldType, Named},
 };
-use exo_sql::PhysicalTable;
+use exo_sql::{Database, PhysicalTable};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy)]
@@ -69,6 +75,8 @@ impl<'a, CT: Named> PostgresType<'a, CT> {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct PostgresPrimitiveType {
     pub name: String,
+    /// The values of a user-declared enum (`None` for a scalar)
+    pub enum_values: Option<Vec<String>>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -84,6 +92,13 @@ pub struct EntityType {
     pub collection_query: SerializableSlabIndex<CollectionQuery>,
     pub aggregate_query: SerializableSlabIndex<AggregateQuery>,
     pub access: Access,
+    pub cache_policy: Option<CachePolicy>,
+    /// Soft deletion through the `@softDelete` annotation
+    pub soft_delete: Option<SoftDelete>,
+    /// Optimistic concurrency control through a `@version` field
+    pub version: Option<Version>,
+    /// Recording of changes through the `@audited` annotation
+    pub audit: Option<Audit>,
 }
 
 pub fn get_field_id(
@@ -141,20 +156,22 @@ impl EntityType {
         self.fields.iter().find(|field| field.name == name)
     }
 
-    pub fn pk_field(&self) -> Option<&PostgresField<EntityType>> {
+    /// The primary key fields (more than one for a composite primary key) in the order of their
+    /// columns
+    pub fn pk_fields(&self) -> Vec<&PostgresField<EntityType>> {
         self.fields
             .iter()
-            .find(|field| matches!(&field.relation, PostgresRelation::Pk { .. }))
+            .filter(|field| matches!(&field.relation, PostgresRelation::Pk { .. }))
+            .collect()
     }
 
-    pub fn pk_field_id(
-        &self,
-        entity_id: SerializableSlabIndex<EntityType>,
-    ) -> Option<EntityFieldId> {
+    pub fn pk_field_ids(&self, entity_id: SerializableSlabIndex<EntityType>) -> Vec<EntityFieldId> {
         self.fields
             .iter()
-            .position(|field| matches!(&field.relation, PostgresRelation::Pk { .. }))
-            .map(|field_index| EntityFieldId(field_index, entity_id))
+            .enumerate()
+            .filter(|(_, field)| matches!(&field.relation, PostgresRelation::Pk { .. }))
+            .map(|(field_index, _)| EntityFieldId(field_index, entity_id))
+            .collect()
     }
 
     pub fn aggregate_field_by_name(&self, name: &str) -> Option<&AggregateField> {
@@ -166,6 +183,12 @@ impl EntityType {
             .iter()
             .find(|field| field.name == name)
     }
+
+    /// Is this type backed by a view (through the `@view` or `@materializedView` annotation)?
+    /// Such types are read-only.
+    pub fn is_view(&self, database: &Database) -> bool {
+        database.get_table(self.table_id).kind.is_view()
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -177,6 +200,7 @@ pub struct PostgresField<CT> {
     pub dynamic_default_value: Option<ContextSelection>,
     pub readonly: bool,
     pub access: Access,
+    pub cache_policy: Option<CachePolicy>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -208,7 +232,21 @@ impl TypeDefinitionProvider<PostgresSubsystem> for PostgresPrimitiveType {
             description: None,
             name: default_positioned_name(&self.name),
             directives: vec![],
-            kind: TypeKind::Scalar,
+            kind: match &self.enum_values {
+                Some(values) => TypeKind::Enum(EnumType {
+                    values: values
+                        .iter()
+                        .map(|value| {
+                            default_positioned(EnumValueDefinition {
+                                description: None,
+                                value: default_positioned(Name::new(value)),
+                                directives: vec![],
+                            })
+                        })
+                        .collect(),
+                }),
+                None => TypeKind::Scalar,
+            },
         }
     }
 }
@@ -249,7 +287,7 @@ impl TypeDefinitionProvider<PostgresSubsystem> for EntityType {
             extend: false,
             description: None,
             name: default_positioned_name(&self.name),
-            directives: vec![],
+            directives: cache_policy_directives(&self.cache_policy),
             kind,
         }
     }
@@ -307,6 +345,9 @@ impl<CT> FieldDefinitionProvider<PostgresSubsystem> for PostgresField<CT> {
             }
             PostgresRelation::OneToMany(OneToManyRelation {
                 foreign_field_id, ..
+            })
+            | PostgresRelation::ManyToMany(ManyToManyRelation {
+                foreign_field_id, ..
             }) => {
                 let foreign_type = &system.entity_types[foreign_field_id.entity_type_id()];
                 let collection_query = &system.collection_queries[foreign_type.collection_query];
@@ -316,6 +357,7 @@ impl<CT> FieldDefinitionProvider<PostgresSubsystem> for PostgresField<CT> {
                     order_by_param,
                     limit_param,
                     offset_param,
+                    include_deleted_param,
                 } = &collection_query.parameters;
 
                 [
@@ -325,6 +367,11 @@ impl<CT> FieldDefinitionProvider<PostgresSubsystem> for PostgresField<CT> {
                     offset_param.input_value(),
                 ]
                 .into_iter()
+                .chain(
+                    include_deleted_param
+                        .iter()
+                        .map(|param| param.input_value()),
+                )
                 .map(default_positioned)
                 .collect()
             }
@@ -335,7 +382,7 @@ impl<CT> FieldDefinitionProvider<PostgresSubsystem> for PostgresField<CT> {
             name: default_positioned_name(&self.name),
             arguments,
             ty: field_type,
-            directives: vec![],
+            directives: cache_policy_directives(&self.cache_policy),
         }
     }
 }
@@ -0,0 +1,62 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use crate::types::PostgresPrimitiveType;
+use async_graphql_parser::types::Type;
+use core_plugin_interface::core_model::{
+    mapped_arena::SerializableSlabIndex,
+    type_normalization::Parameter,
+    types::{FieldType, Named},
+};
+use exo_sql::ColumnId;
+use serde::{Deserialize, Serialize};
+
+/// Optimistic concurrency control of a type with a `@version` field. Updating an entity requires
+/// the version last read and increments it, so an update based on stale data fails instead of
+/// overwriting a concurrent change.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Version {
+    /// The name of the version field such as `version`
+    pub field_name: String,
+    /// The column of the version field
+    pub column_id: ColumnId,
+}
+
+/// The name of the parameter with the expected version
+pub const VERSION_PARAM_NAME: &str = "version";
+
+/// The parameter with the expected version such as `version: 3` in
+/// `updateConcert(id: 1, version: 3, data: {...})`
+#[derive(Serialize, Deserialize, Debug)]
+pub struct VersionParameter {
+    pub name: String,
+    pub typ: FieldType<VersionParameterType>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct VersionParameterType {
+    pub type_name: String,
+    pub type_id: SerializableSlabIndex<PostgresPrimitiveType>,
+}
+
+impl Named for VersionParameterType {
+    fn name(&self) -> &str {
+        &self.type_name
+    }
+}
+
+impl Parameter for VersionParameter {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn typ(&self) -> Type {
+        (&self.typ).into()
+    }
+}
@@ -373,12 +373,15 @@ mod tests {
             context_type::ContextSelection,
         },
         interception::InterceptionMap,
+        persisted_queries::PersistedQueries,
         trusted_documents::TrustedDocuments,
     };
 
     use common::http::RequestHead;
     use core_resolver::introspection::definition::schema::Schema;
+    use core_resolver::response_cache::ResponseCache;
     use core_resolver::system_resolver::SystemResolver;
+    use core_resolver::validation::query_cost::QueryCostModel;
     use exo_env::MapEnvironment;
     use exo_sql::PhysicalTableName;
     use serde_json::{json, Value};
@@ -502,11 +505,16 @@ mod tests {
                 map: HashMap::new(),
             },
             TrustedDocuments::all(),
-            Schema::new(vec![], vec![], vec![]),
+            PersistedQueries::new(0),
+            Schema::new(vec![], vec![], vec![], vec![]),
             None.into(),
             Arc::new(MapEnvironment::from(HashMap::new())),
             10,
             10,
+            QueryCostModel::default(),
+            1,
+            10,
+            ResponseCache::new(0),
         );
 
         TestSystem {
@@ -11,10 +11,9 @@ use super::{
     auth_util::check_access, postgres_execution_error::PostgresExecutionError,
     sql_mapper::SQLOperationKind,
 };
-use crate::operation_resolver::OperationSelectionResolver;
+use crate::{operation_resolver::OperationSelectionResolver, soft_delete::not_deleted_predicate};
 use async_recursion::async_recursion;
 use async_trait::async_trait;
-use core_plugin_interface::core_model::types::OperationReturnType;
 use core_plugin_interface::core_resolver::{
     context::RequestContext, validation::field::ValidatedField,
 };
@@ -53,13 +52,21 @@ impl OperationSelectionResolver for AggregateQuery {
             request_context,
         )
         .await?;
-        let predicate = AbstractPredicate::and(query_predicate, access_predicate);
+        let predicate = [
+            query_predicate,
+            access_predicate,
+            not_deleted_predicate(self.return_type.typ(&subsystem.entity_types)),
+        ]
+        .into_iter()
+        .reduce(AbstractPredicate::and)
+        .unwrap();
         let return_postgres_type = &self.return_type.typ(&subsystem.entity_types);
 
         let root_physical_table_id = return_postgres_type.table_id;
 
         let content_object = content_select(
-            &self.return_type,
+            return_postgres_type,
+            self.return_type.type_name(),
             &field.subfields,
             subsystem,
             request_context,
@@ -77,15 +84,26 @@ impl OperationSelectionResolver for AggregateQuery {
     }
 }
 
+/// Compute the selection for the fields of an aggregate type (such as `ConcertAgg`) of the given entity type
 #[async_recursion]
-async fn content_select<'content>(
-    return_type: &OperationReturnType<EntityType>,
+pub(crate) async fn content_select<'content>(
+    entity_type: &EntityType,
+    aggregate_type_name: &str,
     fields: &'content [ValidatedField],
     subsystem: &'content PostgresSubsystem,
     request_context: &'content RequestContext<'content>,
 ) -> Result<Vec<AliasedSelectionElement>, PostgresExecutionError> {
     futures::stream::iter(fields.iter())
-        .then(|field| async { map_field(return_type, field, subsystem, request_context).await })
+        .then(|field| async {
+            map_field(
+                entity_type,
+                aggregate_type_name,
+                field,
+                subsystem,
+                request_context,
+            )
+            .await
+        })
         .collect::<Vec<Result<_, _>>>()
         .await
         .into_iter()
@@ -93,16 +111,15 @@ async fn content_select<'content>(
 }
 
 async fn map_field<'content>(
-    return_type: &OperationReturnType<EntityType>,
+    entity_type: &EntityType,
+    aggregate_type_name: &str,
     field: &'content ValidatedField,
-    subsystem: &'content PostgresSubsystem,
+    _subsystem: &'content PostgresSubsystem,
     _request_context: &'content RequestContext<'content>,
 ) -> Result<AliasedSelectionElement, PostgresExecutionError> {
     let selection_elem = if field.name == "__typename" {
-        SelectionElement::Constant(return_type.type_name().to_string())
+        SelectionElement::Constant(aggregate_type_name.to_string())
     } else {
-        let entity_type = &return_type.typ(&subsystem.entity_types);
-
         let model_field = entity_type.field_by_name(&field.name).unwrap();
         let model_field_type = &model_field.typ.innermost().type_name;
         // This is duplicated from builder.
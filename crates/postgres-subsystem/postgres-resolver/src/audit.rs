@@ -0,0 +1,244 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Support for types with the `@audited` annotation, whose changes are recorded (by triggers) in a
+//! history table and queried through history queries such as `concertHistory(id: 1)`.
+
+use core_plugin_interface::core_resolver::{
+    access_solver::AccessSolver, context::RequestContext, context_extractor::ContextExtractor,
+    validation::field::ValidatedField, value::Val, QueryResponse,
+};
+use exo_sql::{
+    AbstractOperation, AbstractOrderBy, AbstractOrderByExpr, AbstractPredicate, AbstractSelect,
+    AliasedSelectionElement, ColumnId, ColumnPath, Ordering, PhysicalColumnPath, Selection,
+    SelectionCardinality, SelectionElement, ACTING_USER_SETTING, HISTORY_CHANGED_AT_COLUMN_NAME,
+    HISTORY_CHANGED_BY_COLUMN_NAME, HISTORY_ID_COLUMN_NAME, HISTORY_NEW_ROW_COLUMN_NAME,
+    HISTORY_OLD_ROW_COLUMN_NAME, HISTORY_OPERATION_COLUMN_NAME,
+};
+use postgres_model::{
+    audit::{
+        Audit, HistoryQuery, HISTORY_AFTER_FIELD_NAME, HISTORY_BEFORE_FIELD_NAME,
+        HISTORY_CHANGED_AT_FIELD_NAME, HISTORY_CHANGED_BY_FIELD_NAME, HISTORY_OPERATION_FIELD_NAME,
+    },
+    relation::PostgresRelation,
+    subsystem::PostgresSubsystem,
+    types::EntityType,
+};
+
+use crate::{
+    abstract_operation_resolver::resolve_operation, cast::literal_column_path,
+    plugin::subsystem_resolver::PostgresSubsystemResolver,
+    postgres_execution_error::PostgresExecutionError, util::find_arg,
+};
+
+/// Record the acting users (for the history of changes) for the rest of the transaction.
+///
+/// A mutation may write to audited tables other than its type's table (through nested writes or,
+/// for function mutations, the function's body), so we record the user for every audited type
+/// (each of which may identify the user differently). Without a value for the user in the context,
+/// we record an empty string, which also replaces any user recorded by an earlier mutation in the
+/// same transaction.
+pub(crate) async fn set_acting_user<'a>(
+    request_context: &'a RequestContext<'a>,
+    subsystem_resolver: &'a PostgresSubsystemResolver,
+) -> Result<(), PostgresExecutionError> {
+    let subsystem = &subsystem_resolver.subsystem;
+
+    let mut users = serde_json::Map::new();
+
+    for (_, entity_type) in subsystem.entity_types.iter() {
+        let Some(audit) = &entity_type.audit else {
+            continue;
+        };
+
+        let user = match &audit.user {
+            Some(user_selection) => {
+                match subsystem
+                    .extract_context_selection(request_context, user_selection)
+                    .await?
+                {
+                    Some(Val::String(user) | Val::Enum(user)) => user.clone(),
+                    Some(Val::Number(user)) => user.to_string(),
+                    Some(Val::Bool(user)) => user.to_string(),
+                    _ => String::new(),
+                }
+            }
+            None => String::new(),
+        };
+
+        let table_name = &subsystem.database.get_table(entity_type.table_id).name;
+        users.insert(
+            table_name.fully_qualified_name_with_sep("."),
+            serde_json::Value::String(user),
+        );
+    }
+
+    // Without any audited types, no trigger reads the setting
+    if users.is_empty() {
+        return Ok(());
+    }
+
+    let ctx = request_context.get_base_context();
+    let mut tx = ctx.transaction_holder.try_lock().unwrap();
+
+    subsystem_resolver
+        .executor
+        .set_local(
+            ACTING_USER_SETTING,
+            &serde_json::Value::Object(users).to_string(),
+            &mut tx,
+            &subsystem.database,
+        )
+        .await
+        .map_err(PostgresExecutionError::Postgres)
+}
+
+/// Resolve a history query such as `concertHistory(id: 1) { operation changedAt before after }`
+/// by selecting the recorded changes to the entity (oldest first).
+///
+/// The access rule of the `@audited` annotation must fully evaluate to `true` (a history query
+/// either returns all changes to an entity or fails).
+pub(crate) async fn resolve_history_query<'a>(
+    query: &'a HistoryQuery,
+    field: &'a ValidatedField,
+    request_context: &'a RequestContext<'a>,
+    subsystem_resolver: &'a PostgresSubsystemResolver,
+) -> Result<QueryResponse, PostgresExecutionError> {
+    let subsystem = &subsystem_resolver.subsystem;
+    let entity_type = query.return_type.typ(&subsystem.entity_types);
+
+    let audit = entity_type.audit.as_ref().ok_or_else(|| {
+        PostgresExecutionError::Generic(format!("Type '{}' is not audited", entity_type.name))
+    })?;
+
+    let access_predicate = subsystem
+        .solve(
+            request_context,
+            None,
+            &subsystem.input_access_expressions[audit.access],
+        )
+        .await?
+        .map(|p| p.0)
+        .unwrap_or(AbstractPredicate::False);
+
+    if access_predicate != AbstractPredicate::True {
+        return Err(PostgresExecutionError::Authorization);
+    }
+
+    let predicate = history_predicate(entity_type, audit, field, subsystem)?;
+
+    let selection = field
+        .subfields
+        .iter()
+        .map(|subfield| {
+            let selection_elem = if subfield.name == "__typename" {
+                SelectionElement::Constant(query.return_type.type_name().to_owned())
+            } else {
+                SelectionElement::Physical(find_history_column(
+                    audit,
+                    history_column_name(&subfield.name)?,
+                    subsystem,
+                )?)
+            };
+
+            Ok(AliasedSelectionElement::new(
+                subfield.output_name(),
+                selection_elem,
+            ))
+        })
+        .collect::<Result<Vec<_>, PostgresExecutionError>>()?;
+
+    let select = AbstractSelect {
+        table_id: audit.history_table_id,
+        selection: Selection::Json(selection, SelectionCardinality::Many),
+        predicate,
+        order_by: Some(AbstractOrderBy(vec![(
+            AbstractOrderByExpr::Column(PhysicalColumnPath::leaf(find_history_column(
+                audit,
+                HISTORY_ID_COLUMN_NAME,
+                subsystem,
+            )?)),
+            Ordering::Asc,
+        )])),
+        offset: None,
+        limit: None,
+    };
+
+    resolve_operation(
+        &AbstractOperation::Select(select),
+        subsystem_resolver,
+        request_context,
+    )
+    .await
+}
+
+/// The predicate to select the changes to the entity identified by the primary key arguments
+/// such as `"concerts_history"."id" = $1`
+fn history_predicate(
+    entity_type: &EntityType,
+    audit: &Audit,
+    field: &ValidatedField,
+    subsystem: &PostgresSubsystem,
+) -> Result<AbstractPredicate, PostgresExecutionError> {
+    entity_type
+        .pk_fields()
+        .into_iter()
+        .try_fold(AbstractPredicate::True, |predicate, pk_field| {
+            let column_id = match &pk_field.relation {
+                PostgresRelation::Pk { column_id } => column_id,
+                _ => unreachable!("Primary key field must have a primary key relation"),
+            };
+
+            let value = find_arg(&field.arguments, &pk_field.name)
+                .ok_or_else(|| PostgresExecutionError::MissingArgument(pk_field.name.clone()))?;
+
+            let history_column_id = find_history_column(
+                audit,
+                &column_id.get_column(&subsystem.database).name,
+                subsystem,
+            )?;
+            let history_column = history_column_id.get_column(&subsystem.database);
+
+            Ok(AbstractPredicate::and(
+                predicate,
+                AbstractPredicate::eq(
+                    ColumnPath::Physical(PhysicalColumnPath::leaf(history_column_id)),
+                    literal_column_path(value, &history_column.typ)?,
+                ),
+            ))
+        })
+}
+
+fn history_column_name(field_name: &str) -> Result<&'static str, PostgresExecutionError> {
+    match field_name {
+        HISTORY_OPERATION_FIELD_NAME => Ok(HISTORY_OPERATION_COLUMN_NAME),
+        HISTORY_CHANGED_AT_FIELD_NAME => Ok(HISTORY_CHANGED_AT_COLUMN_NAME),
+        HISTORY_CHANGED_BY_FIELD_NAME => Ok(HISTORY_CHANGED_BY_COLUMN_NAME),
+        HISTORY_BEFORE_FIELD_NAME => Ok(HISTORY_OLD_ROW_COLUMN_NAME),
+        HISTORY_AFTER_FIELD_NAME => Ok(HISTORY_NEW_ROW_COLUMN_NAME),
+        _ => Err(PostgresExecutionError::Generic(format!(
+            "Invalid history field '{field_name}'"
+        ))),
+    }
+}
+
+fn find_history_column(
+    audit: &Audit,
+    column_name: &str,
+    subsystem: &PostgresSubsystem,
+) -> Result<ColumnId, PostgresExecutionError> {
+    subsystem
+        .database
+        .get_column_id(audit.history_table_id, column_name)
+        .ok_or_else(|| {
+            PostgresExecutionError::Generic(format!(
+                "Column '{column_name}' not found in the history table"
+            ))
+        })
+}
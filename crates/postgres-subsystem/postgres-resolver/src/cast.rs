@@ -77,7 +77,7 @@ pub(crate) fn cast_value(
         Val::String(v) => cast_string(v, destination_type).map(Some),
         Val::Bool(v) => Ok(Some(SQLParamContainer::bool(*v))),
         Val::Null => Ok(None),
-        Val::Enum(v) => Ok(Some(SQLParamContainer::string(v.to_string()))), // We might need guidance from the database to do a correct translation
+        Val::Enum(v) => cast_string(v, destination_type).map(Some),
         Val::List(elems) => cast_list(elems, destination_type),
         Val::Object(_) => Ok(Some(cast_object(value, destination_type))),
         Val::Binary(bytes) => Ok(Some(SQLParamContainer::bytes(bytes.clone()))),
@@ -316,6 +316,10 @@ fn cast_string(
 
         PhysicalColumnType::Array { typ } => cast_string(string, typ)?,
 
+        PhysicalColumnType::Enum { .. } => {
+            SQLParamContainer::enum_value(string.to_owned(), destination_type.get_pg_type())
+        }
+
         _ => SQLParamContainer::string(string.to_owned()),
     };
 
@@ -0,0 +1,519 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Resolution of connection queries such as
+//! `concertsConnection(first: 10, after: "...") { edges { cursor node { id } } pageInfo { hasNextPage } }`.
+//!
+//! Connection queries paginate using keysets instead of offsets. Each node's cursor encodes the
+//! values of the columns it is ordered by (the columns in `orderBy` followed by the primary key,
+//! which makes the order total). The `after` and `before` cursors then translate into predicates
+//! on those columns, so a page is located through an index rather than by skipping the preceding
+//! rows (and stays stable when rows are inserted or deleted before the cursor).
+
+use base64::Engine;
+use core_plugin_interface::core_resolver::{
+    context::RequestContext,
+    validation::{field::ValidatedField, query_cost::DEFAULT_LIST_SIZE},
+    value::Val,
+    QueryResponse, QueryResponseBody,
+};
+use exo_sql::{
+    AbstractOperation, AbstractOrderBy, AbstractOrderByExpr, AbstractPredicate, AbstractSelect,
+    AliasedSelectionElement, ColumnId, ColumnPath, ColumnPathLink, Limit, Ordering,
+    PhysicalColumnPath, Selection, SelectionCardinality, SelectionElement,
+};
+use postgres_model::{
+    connection::{ConnectionType, CursorParameter, PAGE_INFO_TYPE_NAME},
+    limit_offset::LimitParameter,
+    query::{ConnectionQuery, ConnectionQueryParameters},
+    relation::PostgresRelation,
+    subsystem::PostgresSubsystem,
+    types::EntityType,
+};
+use serde_json::{Map, Value};
+
+use crate::{
+    abstract_operation_resolver::resolve_operation,
+    auth_util::check_access,
+    cast::literal_column_path,
+    plugin::subsystem_resolver::PostgresSubsystemResolver,
+    postgres_execution_error::PostgresExecutionError,
+    postgres_query::{compute_order_by, content_select},
+    predicate_mapper::compute_predicate,
+    soft_delete::not_deleted_predicate,
+    sql_mapper::{extract_and_map, SQLOperationKind},
+    util::{find_arg, Arguments},
+};
+
+/// The key in each selected row for the values that make up the cursor
+const CURSOR_KEY: &str = "cursor";
+
+/// The page size of a connection query without `first` or `last` (matching the list size assumed
+/// by the default query cost model)
+const DEFAULT_PAGE_SIZE: i64 = DEFAULT_LIST_SIZE as i64;
+
+/// A column that makes up the cursor along with its ordering
+struct CursorColumn {
+    column_id: ColumnId,
+    ordering: Ordering,
+}
+
+pub(crate) async fn resolve_connection_query<'a>(
+    query: &'a ConnectionQuery,
+    field: &'a ValidatedField,
+    request_context: &'a RequestContext<'a>,
+    subsystem_resolver: &'a PostgresSubsystemResolver,
+) -> Result<QueryResponse, PostgresExecutionError> {
+    let subsystem = &subsystem_resolver.subsystem;
+
+    let ConnectionQueryParameters {
+        first_param,
+        after_param,
+        last_param,
+        before_param,
+        predicate_param,
+        order_by_param,
+    } = &query.parameters;
+
+    let arguments = &field.arguments;
+
+    let first = page_size(first_param, arguments, subsystem, request_context).await?;
+    let last = page_size(last_param, arguments, subsystem, request_context).await?;
+
+    if first.is_some() && last.is_some() {
+        return Err(PostgresExecutionError::Validation(
+            last_param.name.clone(),
+            format!("Cannot be combined with '{}'", first_param.name),
+        ));
+    }
+
+    // With `last`, we select in the reverse order (and reverse the selected rows afterwards)
+    let backward = last.is_some();
+    let page_size = first.or(last).unwrap_or(DEFAULT_PAGE_SIZE);
+
+    let entity_type = query.return_type.typ(&subsystem.entity_types);
+
+    let order_by = compute_order_by(order_by_param, arguments, subsystem, request_context).await?;
+    let cursor_columns = cursor_columns(order_by, entity_type)?;
+
+    let after_predicate =
+        cursor_predicate(after_param, arguments, &cursor_columns, true, subsystem)?;
+    let before_predicate =
+        cursor_predicate(before_param, arguments, &cursor_columns, false, subsystem)?;
+
+    let node_fields = node_fields(field);
+
+    let mut access_predicate = check_access(
+        entity_type,
+        &[],
+        &SQLOperationKind::Retrieve,
+        subsystem,
+        request_context,
+        None,
+    )
+    .await?;
+    for node_field in &node_fields {
+        let node_access_predicate = check_access(
+            entity_type,
+            &node_field.subfields,
+            &SQLOperationKind::Retrieve,
+            subsystem,
+            request_context,
+            None,
+        )
+        .await?;
+        access_predicate = AbstractPredicate::and(access_predicate, node_access_predicate);
+    }
+
+    let predicate = [
+        compute_predicate(predicate_param, arguments, subsystem, request_context).await?,
+        after_predicate,
+        before_predicate,
+        access_predicate,
+        not_deleted_predicate(entity_type),
+    ]
+    .into_iter()
+    .reduce(AbstractPredicate::and)
+    .unwrap();
+
+    let mut content = vec![AliasedSelectionElement::new(
+        CURSOR_KEY.to_string(),
+        SelectionElement::Object(
+            cursor_columns
+                .iter()
+                .enumerate()
+                .map(|(index, column)| {
+                    (
+                        index.to_string(),
+                        SelectionElement::Physical(column.column_id),
+                    )
+                })
+                .collect(),
+        ),
+    )];
+    for (index, node_field) in node_fields.iter().enumerate() {
+        let node_content = content_select(
+            entity_type,
+            &node_field.subfields,
+            subsystem,
+            request_context,
+        )
+        .await?;
+
+        content.push(AliasedSelectionElement::new(
+            node_key(index),
+            SelectionElement::Object(node_content.into_iter().map(Into::into).collect()),
+        ));
+    }
+
+    let select = AbstractSelect {
+        table_id: entity_type.table_id,
+        selection: Selection::Json(content, SelectionCardinality::Many),
+        predicate,
+        order_by: Some(AbstractOrderBy(
+            cursor_columns
+                .iter()
+                .map(|column| {
+                    let ordering = match (column.ordering, backward) {
+                        (ordering, false) => ordering,
+                        (Ordering::Asc, true) => Ordering::Desc,
+                        (Ordering::Desc, true) => Ordering::Asc,
+                    };
+                    (
+                        AbstractOrderByExpr::Column(PhysicalColumnPath::leaf(column.column_id)),
+                        ordering,
+                    )
+                })
+                .collect(),
+        )),
+        offset: None,
+        // Select an extra row to determine if there is another page
+        limit: Some(Limit(page_size + 1)),
+    };
+
+    let response = resolve_operation(
+        &AbstractOperation::Select(select),
+        subsystem_resolver,
+        request_context,
+    )
+    .await?;
+
+    let mut rows = match response.body.to_json().map_err(|e| {
+        PostgresExecutionError::Generic(format!("Invalid connection query result: {e}"))
+    })? {
+        Value::Array(rows) => rows,
+        _ => vec![],
+    };
+
+    let has_more = rows.len() > page_size as usize;
+    rows.truncate(page_size as usize);
+
+    if backward {
+        rows.reverse();
+    }
+
+    let page = Page {
+        cursors: rows
+            .iter()
+            .map(|row| encode_cursor(row, cursor_columns.len()))
+            .collect(),
+        rows,
+        has_next_page: !backward && has_more,
+        has_previous_page: backward && has_more,
+    };
+
+    let connection_type = subsystem
+        .connection_types
+        .get_by_key(query.return_type.type_name())
+        .ok_or_else(|| {
+            PostgresExecutionError::Generic(format!(
+                "Connection type '{}' not found",
+                query.return_type.type_name()
+            ))
+        })?;
+
+    Ok(QueryResponse {
+        body: QueryResponseBody::Json(connection_value(field, &page, connection_type)),
+        headers: vec![],
+    })
+}
+
+async fn page_size<'a>(
+    param: &'a LimitParameter,
+    arguments: &'a Arguments,
+    subsystem: &'a PostgresSubsystem,
+    request_context: &'a RequestContext<'a>,
+) -> Result<Option<i64>, PostgresExecutionError> {
+    let size: Option<Limit> = extract_and_map(param, arguments, subsystem, request_context).await?;
+
+    match size {
+        Some(Limit(size)) if size < 0 => Err(PostgresExecutionError::Validation(
+            param.name.clone(),
+            "Must not be negative".into(),
+        )),
+        size => Ok(size.map(|Limit(size)| size)),
+    }
+}
+
+/// The columns that make up the cursor: the columns in `orderBy` followed by the primary key columns (unless already included)
+fn cursor_columns(
+    order_by: Option<AbstractOrderBy>,
+    entity_type: &EntityType,
+) -> Result<Vec<CursorColumn>, PostgresExecutionError> {
+    let mut columns = order_by
+        .map(|order_by| order_by.0)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(expr, ordering)| match expr {
+            AbstractOrderByExpr::Column(path) => match path.split_head() {
+                (ColumnPathLink::Leaf(column_id), None) => Ok(CursorColumn {
+                    column_id,
+                    ordering,
+                }),
+                _ => Err(unsupported_order_by()),
+            },
+            AbstractOrderByExpr::VectorDistance(..)
+            | AbstractOrderByExpr::TextSearchRank(..)
+            | AbstractOrderByExpr::Aggregate(..) => Err(unsupported_order_by()),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let pk_column_ids = entity_type
+        .pk_fields()
+        .into_iter()
+        .map(|field| match &field.relation {
+            PostgresRelation::Pk { column_id } => Some(*column_id),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()
+        .filter(|column_ids| !column_ids.is_empty())
+        .ok_or_else(|| {
+            PostgresExecutionError::Generic(format!(
+                "Type '{}' has no primary key",
+                entity_type.name
+            ))
+        })?;
+
+    for pk_column_id in pk_column_ids {
+        if !columns
+            .iter()
+            .any(|column| column.column_id == pk_column_id)
+        {
+            columns.push(CursorColumn {
+                column_id: pk_column_id,
+                ordering: Ordering::Asc,
+            });
+        }
+    }
+
+    Ok(columns)
+}
+
+fn unsupported_order_by() -> PostgresExecutionError {
+    PostgresExecutionError::Validation(
+        "orderBy".into(),
+        "Connection queries may be ordered only by the scalar fields of the queried type".into(),
+    )
+}
+
+/// The predicate to select the rows after (if `forward`) or before the cursor in the argument (if provided)
+fn cursor_predicate(
+    param: &CursorParameter,
+    arguments: &Arguments,
+    cursor_columns: &[CursorColumn],
+    forward: bool,
+    subsystem: &PostgresSubsystem,
+) -> Result<AbstractPredicate, PostgresExecutionError> {
+    let cursor = match find_arg(arguments, &param.name) {
+        None | Some(Val::Null) => return Ok(AbstractPredicate::True),
+        Some(Val::String(cursor)) => cursor,
+        Some(_) => return Err(invalid_cursor(param)),
+    };
+
+    let values = base64::engine::general_purpose::STANDARD
+        .decode(cursor)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<Vec<Value>>(&bytes).ok())
+        .filter(|values| values.len() == cursor_columns.len())
+        .ok_or_else(|| invalid_cursor(param))?;
+
+    // The cursor's row precedes a row if, for some column, the values of the columns before it
+    // are equal and the value of the column follows the cursor's value (in the column's ordering)
+    let mut predicate = AbstractPredicate::False;
+    let mut preceding_equal = AbstractPredicate::True;
+
+    for (column, value) in cursor_columns.iter().zip(values) {
+        let physical_column = column.column_id.get_column(&subsystem.database);
+        let value = literal_column_path(&Val::from(value), &physical_column.typ)
+            .map_err(|_| invalid_cursor(param))?;
+        let column_path = ColumnPath::Physical(PhysicalColumnPath::leaf(column.column_id));
+
+        let follows = follows(column_path.clone(), value.clone(), column.ordering, forward);
+
+        predicate = AbstractPredicate::or(
+            predicate,
+            AbstractPredicate::and(preceding_equal.clone(), follows),
+        );
+        preceding_equal =
+            AbstractPredicate::and(preceding_equal, AbstractPredicate::Eq(column_path, value));
+    }
+
+    Ok(predicate)
+}
+
+/// The predicate for the column's value to come after (if `forward`) or before the given value
+/// in the ordering. Postgres places nulls last in the ascending order (and first in the
+/// descending order).
+fn follows(
+    column: ColumnPath,
+    value: ColumnPath,
+    ordering: Ordering,
+    forward: bool,
+) -> AbstractPredicate {
+    let ascending = (ordering == Ordering::Asc) == forward;
+
+    match (value, ascending) {
+        (ColumnPath::Null, true) => AbstractPredicate::False,
+        (ColumnPath::Null, false) => AbstractPredicate::Neq(column, ColumnPath::Null),
+        (value, true) => AbstractPredicate::or(
+            AbstractPredicate::Gt(column.clone(), value),
+            AbstractPredicate::Eq(column, ColumnPath::Null),
+        ),
+        (value, false) => AbstractPredicate::Lt(column, value),
+    }
+}
+
+fn invalid_cursor(param: &CursorParameter) -> PostgresExecutionError {
+    PostgresExecutionError::Validation(param.name.clone(), "Invalid cursor".into())
+}
+
+fn encode_cursor(row: &Value, column_count: usize) -> String {
+    let values: Vec<&Value> = (0..column_count)
+        .map(|index| &row[CURSOR_KEY][index.to_string()])
+        .collect();
+
+    base64::engine::general_purpose::STANDARD.encode(serde_json::to_vec(&values).unwrap())
+}
+
+/// The `node` fields in all `edges` fields of the connection field (in the order of appearance)
+fn node_fields(field: &ValidatedField) -> Vec<&ValidatedField> {
+    field
+        .subfields
+        .iter()
+        .filter(|subfield| subfield.name == "edges")
+        .flat_map(|edges_field| edges_field.subfields.iter())
+        .filter(|subfield| subfield.name == "node")
+        .collect()
+}
+
+fn node_key(index: usize) -> String {
+    format!("node{index}")
+}
+
+struct Page {
+    rows: Vec<Value>,
+    cursors: Vec<String>,
+    has_next_page: bool,
+    has_previous_page: bool,
+}
+
+fn connection_value(
+    field: &ValidatedField,
+    page: &Page,
+    connection_type: &ConnectionType,
+) -> Value {
+    // The index of the next `node` field (matching the order of `node_fields`)
+    let mut next_node_index = 0;
+
+    let value = field
+        .subfields
+        .iter()
+        .map(|subfield| {
+            let value = match subfield.name.as_str() {
+                "__typename" => Value::String(connection_type.name.clone()),
+                "edges" => {
+                    let first_node_index = next_node_index;
+                    next_node_index += subfield
+                        .subfields
+                        .iter()
+                        .filter(|edge_field| edge_field.name == "node")
+                        .count();
+
+                    Value::Array(
+                        page.rows
+                            .iter()
+                            .zip(&page.cursors)
+                            .map(|(row, cursor)| {
+                                edge_value(subfield, row, cursor, first_node_index, connection_type)
+                            })
+                            .collect(),
+                    )
+                }
+                "pageInfo" => page_info_value(subfield, page),
+                _ => Value::Null,
+            };
+
+            (subfield.output_name(), value)
+        })
+        .collect::<Map<_, _>>();
+
+    Value::Object(value)
+}
+
+fn edge_value(
+    field: &ValidatedField,
+    row: &Value,
+    cursor: &str,
+    first_node_index: usize,
+    connection_type: &ConnectionType,
+) -> Value {
+    let mut next_node_index = first_node_index;
+
+    let value = field
+        .subfields
+        .iter()
+        .map(|subfield| {
+            let value = match subfield.name.as_str() {
+                "__typename" => Value::String(connection_type.edge_type_name.clone()),
+                "cursor" => Value::String(cursor.to_string()),
+                "node" => {
+                    let node = row[node_key(next_node_index)].clone();
+                    next_node_index += 1;
+                    node
+                }
+                _ => Value::Null,
+            };
+
+            (subfield.output_name(), value)
+        })
+        .collect::<Map<_, _>>();
+
+    Value::Object(value)
+}
+
+fn page_info_value(field: &ValidatedField, page: &Page) -> Value {
+    let value = field
+        .subfields
+        .iter()
+        .map(|subfield| {
+            let value = match subfield.name.as_str() {
+                "__typename" => Value::String(PAGE_INFO_TYPE_NAME.to_string()),
+                "hasNextPage" => Value::Bool(page.has_next_page),
+                "hasPreviousPage" => Value::Bool(page.has_previous_page),
+                "startCursor" => page.cursors.first().cloned().into(),
+                "endCursor" => page.cursors.last().cloned().into(),
+                _ => Value::Null,
+            };
+
+            (subfield.output_name(), value)
+        })
+        .collect::<Map<_, _>>();
+
+    Value::Object(value)
+}
@@ -14,12 +14,12 @@ use core_plugin_interface::core_resolver::context_extractor::ContextExtractor;
 use core_plugin_interface::core_resolver::value::Val;
 use exo_sql::{
     AbstractInsert, AbstractSelect, ColumnId, ColumnValuePair, InsertionElement, InsertionRow,
-    ManyToOne, NestedInsertion,
+    ManyToMany, ManyToOne, NestedInsertion,
 };
 use futures::future::{join_all, try_join_all};
 use postgres_model::{
     mutation::DataParameter,
-    relation::{ManyToOneRelation, OneToManyRelation, PostgresRelation},
+    relation::{ManyToManyRelation, ManyToOneRelation, OneToManyRelation, PostgresRelation},
     subsystem::PostgresSubsystem,
     types::{base_type, MutationType, PostgresField, PostgresType},
 };
@@ -56,6 +56,7 @@ impl<'a> SQLMapper<'a, AbstractInsert> for InsertOperation<'a> {
             table_id,
             rows,
             selection: self.select,
+            on_conflict: None,
         };
 
         Ok(abs_insert)
@@ -123,16 +124,40 @@ async fn map_single<'a>(
 
         field_arg.map(|field_arg| async move {
             match &field.relation {
-                PostgresRelation::Pk { column_id } | PostgresRelation::Scalar { column_id } => {
-                    map_self_column(*column_id, field, field_arg, subsystem).await
-                }
+                PostgresRelation::Pk { column_id } | PostgresRelation::Scalar { column_id } => Ok(
+                    vec![map_self_column(*column_id, field, field_arg, subsystem)?],
+                ),
+
+                PostgresRelation::ManyToOne(ManyToOneRelation {
+                    relation_id,
+                    foreign_pk_field_ids,
+                    ..
+                }) => {
+                    let ManyToOne { column_pairs, .. } = relation_id.deref(&subsystem.database);
 
-                PostgresRelation::ManyToOne(ManyToOneRelation { relation_id, .. }) => {
-                    let ManyToOne { self_column_id, .. } = relation_id.deref(&subsystem.database);
-                    map_self_column(self_column_id, field, field_arg, subsystem).await
+                    column_pairs
+                        .iter()
+                        .zip(foreign_pk_field_ids)
+                        .map(|(pair, foreign_pk_field_id)| {
+                            let foreign_type_pk_field_name =
+                                &foreign_pk_field_id.resolve(&subsystem.entity_types).name;
+                            let argument_value = match super::util::get_argument_field(
+                                field_arg,
+                                foreign_type_pk_field_name,
+                            ) {
+                                Some(foreign_type_pk_arg) => foreign_type_pk_arg,
+                                None => {
+                                    // This can happen if we used a context value for a foreign key
+                                    // Instead of getting in the `{id: <value>}` format, we get the value directly
+                                    field_arg
+                                }
+                            };
+                            map_self_column(pair.self_column_id, field, argument_value, subsystem)
+                        })
+                        .collect()
                 }
 
-                PostgresRelation::OneToMany(one_to_many_relation) => {
+                PostgresRelation::OneToMany(one_to_many_relation) => Ok(vec![
                     map_foreign(
                         field,
                         field_arg,
@@ -140,7 +165,16 @@ async fn map_single<'a>(
                         subsystem,
                         request_context,
                     )
-                    .await
+                    .await?,
+                ]),
+
+                PostgresRelation::ManyToMany(many_to_many_relation) => {
+                    Ok(vec![map_many_to_many_connect(
+                        field,
+                        field_arg,
+                        many_to_many_relation,
+                        subsystem,
+                    )?])
                 }
             }
         })
@@ -150,34 +184,18 @@ async fn map_single<'a>(
     let row = row.into_iter().flatten().collect::<Vec<_>>();
     let row = try_join_all(row).await?;
 
-    Ok(InsertionRow { elems: row })
+    Ok(InsertionRow {
+        elems: row.into_iter().flatten().collect(),
+    })
 }
 
-async fn map_self_column<'a>(
+fn map_self_column(
     key_column_id: ColumnId,
-    field: &'a PostgresField<MutationType>,
-    argument: &'a Val,
-    subsystem: &'a PostgresSubsystem,
+    field: &PostgresField<MutationType>,
+    argument_value: &Val,
+    subsystem: &PostgresSubsystem,
 ) -> Result<InsertionElement, PostgresExecutionError> {
     let key_column = key_column_id.get_column(&subsystem.database);
-    let argument_value = match &field.relation {
-        PostgresRelation::ManyToOne(ManyToOneRelation {
-            foreign_pk_field_id,
-            ..
-        }) => {
-            let foreign_type_pk_field_name =
-                &foreign_pk_field_id.resolve(&subsystem.entity_types).name;
-            match super::util::get_argument_field(argument, foreign_type_pk_field_name) {
-                Some(foreign_type_pk_arg) => foreign_type_pk_arg,
-                None => {
-                    // This can happen if we used a context value for a foreign key
-                    // Instead of getting in the `{id: <value>}` format, we get the value directly
-                    argument
-                }
-            }
-        }
-        _ => argument,
-    };
 
     let value_column = cast::literal_column(argument_value, key_column).with_context(format!(
         "trying to convert the '{}' field to the '{}' type",
@@ -219,3 +237,87 @@ async fn map_foreign<'a>(
         insertions: insertion,
     }))
 }
+
+/// Map the references of a many-to-many field to rows in the linking table.
+/// For example, if the data parameter is `data: {title: "concert-title", artists: [{id: 1}, {id: 2}]}`,
+/// this needs to be called for the `artists` part (which is mapped to rows in the `concert_artists` table)
+fn map_many_to_many_connect(
+    field: &PostgresField<MutationType>, // "artists"
+    argument: &Val,                      // [{id: 1}, {id: 2}]
+    many_to_many_relation: &ManyToManyRelation,
+    subsystem: &PostgresSubsystem,
+) -> Result<InsertionElement, PostgresExecutionError> {
+    let references = match argument {
+        Val::List(references) => references.iter().collect(),
+        _ => vec![argument],
+    };
+
+    let insertions = references
+        .into_iter()
+        .map(|reference| {
+            let elems =
+                map_many_to_many_reference(field, reference, many_to_many_relation, subsystem)?
+                    .into_iter()
+                    .map(InsertionElement::SelfInsert)
+                    .collect();
+            Ok(InsertionRow { elems })
+        })
+        .collect::<Result<Vec<_>, PostgresExecutionError>>()?;
+
+    Ok(InsertionElement::NestedInsert(NestedInsertion {
+        relation_id: many_to_many_relation.relation_id.self_link_id,
+        insertions,
+    }))
+}
+
+/// Map a reference to an object on the foreign side of a many-to-many relation (such as `{id: 1}`)
+/// to the values of the linking table's columns that refer to that object (such as `artist_id = 1`)
+pub(crate) fn map_many_to_many_reference(
+    field: &PostgresField<MutationType>,
+    reference: &Val,
+    many_to_many_relation: &ManyToManyRelation,
+    subsystem: &PostgresSubsystem,
+) -> Result<Vec<ColumnValuePair>, PostgresExecutionError> {
+    let foreign_entity_type =
+        &subsystem.entity_types[many_to_many_relation.foreign_field_id.entity_type_id()];
+    let ManyToMany { foreign_link, .. } =
+        many_to_many_relation.relation_id.deref(&subsystem.database);
+
+    foreign_link
+        .column_pairs
+        .iter()
+        .map(|pair| {
+            let foreign_pk_field = foreign_entity_type
+                .pk_fields()
+                .into_iter()
+                .find(|pk_field| {
+                    matches!(pk_field.relation, PostgresRelation::Pk { column_id } if column_id == pair.foreign_column_id)
+                })
+                .ok_or_else(|| {
+                    PostgresExecutionError::Generic(format!(
+                        "Could not find the primary key field of '{}'",
+                        foreign_entity_type.name
+                    ))
+                })?;
+
+            let argument_value = super::util::get_argument_field(reference, &foreign_pk_field.name)
+                .ok_or_else(|| {
+                    PostgresExecutionError::MissingArgument(format!(
+                        "{}.{}",
+                        field.name, foreign_pk_field.name
+                    ))
+                })?;
+
+            let linking_column = pair.self_column_id.get_column(&subsystem.database);
+            let value_column = cast::literal_column(argument_value, linking_column).with_context(
+                format!(
+                    "trying to convert the '{}' field to the '{}' type",
+                    field.name,
+                    linking_column.typ.type_string()
+                ),
+            )?;
+
+            Ok(ColumnValuePair::new(pair.self_column_id, value_column))
+        })
+        .collect()
+}
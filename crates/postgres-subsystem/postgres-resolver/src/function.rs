@@ -0,0 +1,94 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Support for queries and mutations backed by Postgres functions through the `@function`
+//! annotation such as `topConcerts(count: 10) { id title }`.
+
+use core_plugin_interface::core_resolver::{
+    access_solver::AccessSolver, context::RequestContext, validation::field::ValidatedField,
+    QueryResponse,
+};
+use exo_sql::{AbstractFunctionSelect, AbstractOperation, AbstractPredicate};
+use postgres_model::function::FunctionOperation;
+
+use crate::{
+    abstract_operation_resolver::resolve_operation, cast::cast_value,
+    plugin::subsystem_resolver::PostgresSubsystemResolver,
+    postgres_execution_error::PostgresExecutionError, postgres_query::compute_select,
+    soft_delete::not_deleted_predicate, util::find_arg,
+};
+
+/// Resolve an operation by calling its function (with the arguments as bind parameters) and
+/// selecting from the returned rows.
+///
+/// The access rule of the operation must fully evaluate to `true` (since the function may have
+/// effects of its own). The read access rule of the returned type then filters the returned rows
+/// (as does the `@softDelete` annotation).
+pub(crate) async fn resolve_function_operation<'a>(
+    operation: &'a FunctionOperation,
+    field: &'a ValidatedField,
+    request_context: &'a RequestContext<'a>,
+    subsystem_resolver: &'a PostgresSubsystemResolver,
+) -> Result<QueryResponse, PostgresExecutionError> {
+    let subsystem = &subsystem_resolver.subsystem;
+
+    let access_predicate = subsystem
+        .solve(
+            request_context,
+            None,
+            &subsystem.input_access_expressions[operation.parameters.access],
+        )
+        .await?
+        .map(|p| p.0)
+        .unwrap_or(AbstractPredicate::False);
+
+    if access_predicate != AbstractPredicate::True {
+        return Err(PostgresExecutionError::Authorization);
+    }
+
+    // A missing argument is passed as `NULL` (just like an explicit `null`)
+    let args = operation
+        .parameters
+        .arguments
+        .iter()
+        .map(
+            |argument| match find_arg(&field.arguments, &argument.name) {
+                Some(value) => cast_value(value, &argument.column_type),
+                None => Ok(None),
+            },
+        )
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Just like other queries, leave out soft-deleted entities (the function may return them,
+    // since it selects from the table directly)
+    let predicate = not_deleted_predicate(operation.return_type.typ(&subsystem.entity_types));
+
+    let select = compute_select(
+        predicate,
+        None,
+        None,
+        None,
+        &operation.return_type,
+        &field.subfields,
+        subsystem,
+        request_context,
+    )
+    .await?;
+
+    resolve_operation(
+        &AbstractOperation::FunctionSelect(AbstractFunctionSelect {
+            function_name: operation.parameters.function_name.clone(),
+            args,
+            select,
+        }),
+        subsystem_resolver,
+        request_context,
+    )
+    .await
+}
@@ -0,0 +1,566 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Resolution of group-by queries such as:
+//!
+//! ```graphql
+//! concertsGroupBy(by: [venueId], having: { count: { gt: 1 } }, orderBy: { count: DESC }) {
+//!   key { venueId }
+//!   count
+//!   aggregate { price { avg } }
+//! }
+//! ```
+
+use super::{
+    aggregate_query::content_select,
+    auth_util::{check_access, check_retrieve_access},
+    cast::cast_value,
+    order_by_mapper::ordering,
+    postgres_execution_error::PostgresExecutionError,
+    predicate_mapper::compute_predicate,
+    sql_mapper::{extract_and_map, SQLOperationKind},
+    util::{find_arg, Arguments},
+};
+use crate::{operation_resolver::OperationResolver, soft_delete::not_deleted_predicate};
+use async_trait::async_trait;
+use core_plugin_interface::core_resolver::{
+    context::RequestContext, validation::field::ValidatedField, value::Val,
+};
+use exo_sql::{
+    AbstractGroupBySelect, AbstractOperation, AbstractPredicate, AliasedSelectionElement, ColumnId,
+    FloatBits, Function, GroupColumn, GroupPredicate, IntBits, Ordering, PhysicalColumnType,
+    Predicate, SelectionElement,
+};
+use postgres_model::{
+    group_by::{GroupType, GROUP_AGGREGATE_FIELD, GROUP_COUNT_FIELD, GROUP_KEY_FIELD},
+    query::{GroupByQuery, GroupByQueryParameters},
+    relation::PostgresRelation,
+    subsystem::PostgresSubsystem,
+    types::{EntityType, PostgresField},
+};
+
+/// The aggregate functions that may be used in the `having` and `orderBy` arguments (these match the
+/// fields of the aggregate types such as `IntAgg`)
+const AGGREGATE_FUNCTIONS: [&str; 5] = ["count", "sum", "avg", "min", "max"];
+
+#[async_trait]
+impl OperationResolver for GroupByQuery {
+    async fn resolve<'a>(
+        &'a self,
+        field: &'a ValidatedField,
+        request_context: &'a RequestContext<'a>,
+        subsystem: &'a PostgresSubsystem,
+    ) -> Result<AbstractOperation, PostgresExecutionError> {
+        let GroupByQueryParameters {
+            by_param,
+            predicate_param,
+            having_param,
+            order_by_param,
+            limit_param,
+            offset_param,
+        } = &self.parameters;
+        let arguments = &field.arguments;
+
+        let entity_type = self.return_type.typ(&subsystem.entity_types);
+        let group_type = subsystem
+            .group_types
+            .get_by_key(self.return_type.type_name())
+            .ok_or_else(|| {
+                PostgresExecutionError::Generic(format!(
+                    "Group type '{}' not found",
+                    self.return_type.type_name()
+                ))
+            })?;
+
+        let by_fields = group_by_fields(&by_param.name, arguments, entity_type)?;
+        let count = count_function(entity_type)?;
+
+        // Fields used to group, aggregate, filter, or order the groups (each must be readable)
+        let mut used_fields = by_fields
+            .iter()
+            .map(|(field, _)| *field)
+            .collect::<Vec<_>>();
+
+        let having = match find_arg(arguments, &having_param.name) {
+            None | Some(Val::Null) => Predicate::True,
+            Some(having) => having_predicate(
+                &having_param.name,
+                having,
+                entity_type,
+                &count,
+                &mut used_fields,
+                subsystem,
+            )?,
+        };
+
+        let order_by = match find_arg(arguments, &order_by_param.name) {
+            None | Some(Val::Null) => vec![],
+            Some(Val::List(elems)) => elems
+                .iter()
+                .map(|elem| {
+                    group_ordering(
+                        &order_by_param.name,
+                        elem,
+                        entity_type,
+                        &by_fields,
+                        &count,
+                        &mut used_fields,
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()?
+                .concat(),
+            Some(elem) => group_ordering(
+                &order_by_param.name,
+                elem,
+                entity_type,
+                &by_fields,
+                &count,
+                &mut used_fields,
+            )?,
+        };
+
+        let selection = group_selection(
+            field,
+            group_type,
+            entity_type,
+            &by_fields,
+            &count,
+            &mut used_fields,
+            subsystem,
+            request_context,
+        )
+        .await?;
+
+        let entity_access_predicate = check_access(
+            entity_type,
+            &[],
+            &SQLOperationKind::Retrieve,
+            subsystem,
+            request_context,
+            None,
+        )
+        .await?;
+        let field_access_predicate =
+            fields_access_predicate(&used_fields, subsystem, request_context).await?;
+        let query_predicate =
+            compute_predicate(predicate_param, arguments, subsystem, request_context).await?;
+
+        let predicate = [
+            query_predicate,
+            entity_access_predicate,
+            field_access_predicate,
+            not_deleted_predicate(entity_type),
+        ]
+        .into_iter()
+        .reduce(AbstractPredicate::and)
+        .unwrap();
+
+        Ok(AbstractOperation::GroupBySelect(AbstractGroupBySelect {
+            table_id: entity_type.table_id,
+            group_by: by_fields.iter().map(|(_, column_id)| *column_id).collect(),
+            selection,
+            predicate,
+            having,
+            order_by,
+            offset: extract_and_map(offset_param, arguments, subsystem, request_context).await?,
+            limit: extract_and_map(limit_param, arguments, subsystem, request_context).await?,
+        }))
+    }
+}
+
+/// The fields (along with their columns) specified in the `by` argument
+fn group_by_fields<'a>(
+    param_name: &str,
+    arguments: &Arguments,
+    entity_type: &'a EntityType,
+) -> Result<Vec<(&'a PostgresField<EntityType>, ColumnId)>, PostgresExecutionError> {
+    let names = match find_arg(arguments, param_name) {
+        Some(Val::List(names)) => names.iter().collect::<Vec<_>>(),
+        Some(name @ (Val::Enum(_) | Val::String(_))) => vec![name],
+        _ => vec![],
+    };
+
+    if names.is_empty() {
+        return Err(PostgresExecutionError::Validation(
+            param_name.into(),
+            "At least one field to group by must be specified".into(),
+        ));
+    }
+
+    names
+        .into_iter()
+        .map(|name| match name {
+            // Values from variables don't get mapped to the Enum type
+            Val::Enum(name) | Val::String(name) => entity_field(param_name, name, entity_type),
+            _ => Err(PostgresExecutionError::Validation(
+                param_name.into(),
+                format!("Invalid field to group by: {name}"),
+            )),
+        })
+        .collect()
+}
+
+/// The field (along with its column) with the given name. Only scalar fields may be grouped by or
+/// aggregated.
+fn entity_field<'a>(
+    param_name: &str,
+    field_name: &str,
+    entity_type: &'a EntityType,
+) -> Result<(&'a PostgresField<EntityType>, ColumnId), PostgresExecutionError> {
+    let field = entity_type.field_by_name(field_name);
+
+    match field.map(|field| (field, &field.relation)) {
+        Some((
+            field,
+            PostgresRelation::Pk { column_id } | PostgresRelation::Scalar { column_id },
+        )) => Ok((field, *column_id)),
+        _ => Err(PostgresExecutionError::Validation(
+            param_name.into(),
+            format!("Invalid field '{field_name}'"),
+        )),
+    }
+}
+
+/// The function to count the rows in a group. We count the primary key column, which is never null
+/// (so is equivalent to `count(*)`).
+fn count_function(entity_type: &EntityType) -> Result<Function, PostgresExecutionError> {
+    match entity_type.pk_fields().first().map(|field| &field.relation) {
+        Some(PostgresRelation::Pk { column_id }) => Ok(Function::Named {
+            function_name: "count".into(),
+            column_id: *column_id,
+        }),
+        _ => Err(PostgresExecutionError::Generic(format!(
+            "Type '{}' has no primary key to count the rows in a group",
+            entity_type.name
+        ))),
+    }
+}
+
+/// The selection for each group (`key`, `count`, and `aggregate`)
+#[allow(clippy::too_many_arguments)]
+async fn group_selection<'a>(
+    field: &'a ValidatedField,
+    group_type: &GroupType,
+    entity_type: &'a EntityType,
+    by_fields: &[(&'a PostgresField<EntityType>, ColumnId)],
+    count: &Function,
+    used_fields: &mut Vec<&'a PostgresField<EntityType>>,
+    subsystem: &'a PostgresSubsystem,
+    request_context: &'a RequestContext<'a>,
+) -> Result<Vec<AliasedSelectionElement>, PostgresExecutionError> {
+    let aggregate_type_name = &subsystem.aggregate_types[group_type.aggregate_type_id].name;
+
+    let mut selection = vec![];
+
+    for subfield in field.subfields.iter() {
+        let selection_elem = match subfield.name.as_str() {
+            "__typename" => SelectionElement::Constant(group_type.name.clone()),
+            GROUP_KEY_FIELD => SelectionElement::Object(
+                subfield
+                    .subfields
+                    .iter()
+                    .map(|key_field| {
+                        let key_elem = if key_field.name == "__typename" {
+                            SelectionElement::Constant(group_type.key_type_name())
+                        } else {
+                            let (_, column_id) = by_fields
+                                .iter()
+                                .find(|(field, _)| field.name == key_field.name)
+                                .ok_or_else(|| {
+                                    PostgresExecutionError::Validation(
+                                        key_field.output_name(),
+                                        format!(
+                                            "Field '{}' is not one of the fields to group by",
+                                            key_field.name
+                                        ),
+                                    )
+                                })?;
+                            SelectionElement::Physical(*column_id)
+                        };
+                        Ok((key_field.output_name(), key_elem))
+                    })
+                    .collect::<Result<Vec<_>, PostgresExecutionError>>()?,
+            ),
+            GROUP_COUNT_FIELD => SelectionElement::Function(count.clone()),
+            GROUP_AGGREGATE_FIELD => {
+                for aggregate_field in subfield.subfields.iter() {
+                    if aggregate_field.name != "__typename" {
+                        let (field, _) = entity_field(
+                            &aggregate_field.output_name(),
+                            &aggregate_field.name,
+                            entity_type,
+                        )?;
+                        used_fields.push(field);
+                    }
+                }
+
+                let elements = content_select(
+                    entity_type,
+                    aggregate_type_name,
+                    &subfield.subfields,
+                    subsystem,
+                    request_context,
+                )
+                .await?;
+
+                SelectionElement::Object(elements.into_iter().map(Into::into).collect())
+            }
+            _ => {
+                return Err(PostgresExecutionError::Validation(
+                    subfield.output_name(),
+                    format!("Invalid field '{}'", subfield.name),
+                ))
+            }
+        };
+
+        selection.push(AliasedSelectionElement::new(
+            subfield.output_name(),
+            selection_elem,
+        ));
+    }
+
+    Ok(selection)
+}
+
+/// The predicate on the groups from an argument such as `{count: {gt: 1}, aggregate: {price: {avg: {lt: 100}}}}`
+fn having_predicate<'a>(
+    param_name: &str,
+    argument: &Val,
+    entity_type: &'a EntityType,
+    count: &Function,
+    used_fields: &mut Vec<&'a PostgresField<EntityType>>,
+    subsystem: &PostgresSubsystem,
+) -> Result<GroupPredicate, PostgresExecutionError> {
+    let mut predicate = Predicate::True;
+
+    for (name, value) in object_entries(param_name, argument)? {
+        let predicate_elem = match name.as_str() {
+            GROUP_COUNT_FIELD => comparison_predicate(
+                param_name,
+                GroupColumn::Aggregate(count.clone()),
+                &PhysicalColumnType::Int { bits: IntBits::_64 },
+                value,
+            )?,
+            GROUP_AGGREGATE_FIELD => {
+                let mut aggregate_predicate = Predicate::True;
+
+                for (field_name, field_value) in object_entries(param_name, value)? {
+                    let (field, column_id) = entity_field(param_name, field_name, entity_type)?;
+                    used_fields.push(field);
+                    let column_type = &column_id.get_column(&subsystem.database).typ;
+
+                    for (function_name, function_value) in object_entries(param_name, field_value)?
+                    {
+                        let function = aggregate_function(param_name, function_name, column_id)?;
+                        let value_type = aggregate_value_type(function_name, column_type);
+
+                        aggregate_predicate = Predicate::and(
+                            aggregate_predicate,
+                            comparison_predicate(
+                                param_name,
+                                GroupColumn::Aggregate(function),
+                                &value_type,
+                                function_value,
+                            )?,
+                        );
+                    }
+                }
+
+                aggregate_predicate
+            }
+            _ => {
+                return Err(PostgresExecutionError::Validation(
+                    param_name.into(),
+                    format!("Invalid field '{name}'"),
+                ))
+            }
+        };
+
+        predicate = Predicate::and(predicate, predicate_elem);
+    }
+
+    Ok(predicate)
+}
+
+/// The predicate for a filter such as `{gt: 1, lte: 10}` on a group column
+fn comparison_predicate(
+    param_name: &str,
+    column: GroupColumn,
+    value_type: &PhysicalColumnType,
+    argument: &Val,
+) -> Result<GroupPredicate, PostgresExecutionError> {
+    let mut predicate = Predicate::True;
+
+    for (operator, value) in object_entries(param_name, argument)? {
+        // A null value is the same as not specifying the operator
+        let Some(value) = cast_value(value, value_type)? else {
+            continue;
+        };
+        let (lhs, rhs) = (column.clone(), GroupColumn::Param(value));
+
+        let predicate_elem = match operator.as_str() {
+            "eq" => Predicate::eq(lhs, rhs),
+            "neq" => Predicate::neq(lhs, rhs),
+            "lt" => Predicate::Lt(lhs, rhs),
+            "lte" => Predicate::Lte(lhs, rhs),
+            "gt" => Predicate::Gt(lhs, rhs),
+            "gte" => Predicate::Gte(lhs, rhs),
+            _ => {
+                return Err(PostgresExecutionError::Validation(
+                    param_name.into(),
+                    format!("Operator '{operator}' is not supported to filter groups"),
+                ))
+            }
+        };
+
+        predicate = Predicate::and(predicate, predicate_elem);
+    }
+
+    Ok(predicate)
+}
+
+/// The order of the groups from an argument such as `{key: {venueId: ASC}}` or `{aggregate: {price: {avg: DESC}}}`
+fn group_ordering<'a>(
+    param_name: &str,
+    argument: &Val,
+    entity_type: &'a EntityType,
+    by_fields: &[(&'a PostgresField<EntityType>, ColumnId)],
+    count: &Function,
+    used_fields: &mut Vec<&'a PostgresField<EntityType>>,
+) -> Result<Vec<(GroupColumn, Ordering)>, PostgresExecutionError> {
+    let mut order_by = vec![];
+
+    for (name, value) in object_entries(param_name, argument)? {
+        match name.as_str() {
+            GROUP_KEY_FIELD => {
+                for (field_name, field_value) in object_entries(param_name, value)? {
+                    let (_, column_id) = by_fields
+                        .iter()
+                        .find(|(field, _)| &field.name == field_name)
+                        .ok_or_else(|| {
+                            PostgresExecutionError::Validation(
+                                param_name.into(),
+                                format!(
+                                    "Field '{field_name}' is not one of the fields to group by"
+                                ),
+                            )
+                        })?;
+                    order_by.push((GroupColumn::Grouped(*column_id), ordering(field_value)?));
+                }
+            }
+            GROUP_COUNT_FIELD => {
+                order_by.push((GroupColumn::Aggregate(count.clone()), ordering(value)?));
+            }
+            GROUP_AGGREGATE_FIELD => {
+                for (field_name, field_value) in object_entries(param_name, value)? {
+                    let (field, column_id) = entity_field(param_name, field_name, entity_type)?;
+                    used_fields.push(field);
+
+                    for (function_name, function_value) in object_entries(param_name, field_value)?
+                    {
+                        order_by.push((
+                            GroupColumn::Aggregate(aggregate_function(
+                                param_name,
+                                function_name,
+                                column_id,
+                            )?),
+                            ordering(function_value)?,
+                        ));
+                    }
+                }
+            }
+            _ => {
+                return Err(PostgresExecutionError::Validation(
+                    param_name.into(),
+                    format!("Invalid field '{name}'"),
+                ))
+            }
+        }
+    }
+
+    Ok(order_by)
+}
+
+fn aggregate_function(
+    param_name: &str,
+    function_name: &str,
+    column_id: ColumnId,
+) -> Result<Function, PostgresExecutionError> {
+    if AGGREGATE_FUNCTIONS.contains(&function_name) {
+        Ok(Function::Named {
+            function_name: function_name.into(),
+            column_id,
+        })
+    } else {
+        Err(PostgresExecutionError::Validation(
+            param_name.into(),
+            format!("Invalid aggregate '{function_name}'"),
+        ))
+    }
+}
+
+/// The type of the value of an aggregate function over a column of the given type (matching the
+/// types of the fields of the aggregate types such as `IntAgg`)
+fn aggregate_value_type(
+    function_name: &str,
+    column_type: &PhysicalColumnType,
+) -> PhysicalColumnType {
+    match (function_name, column_type) {
+        ("count", _) | ("sum", PhysicalColumnType::Int { .. }) => {
+            PhysicalColumnType::Int { bits: IntBits::_64 }
+        }
+        ("avg", PhysicalColumnType::Numeric { .. }) => column_type.clone(),
+        ("avg", _) => PhysicalColumnType::Float {
+            bits: FloatBits::_53,
+        },
+        _ => column_type.clone(),
+    }
+}
+
+/// Entries in an object argument. Null values are the same as not specifying the entry.
+fn object_entries<'a>(
+    param_name: &str,
+    argument: &'a Val,
+) -> Result<impl Iterator<Item = (&'a String, &'a Val)>, PostgresExecutionError> {
+    match argument {
+        Val::Object(entries) => Ok(entries
+            .iter()
+            .filter(|(_, value)| !matches!(value, Val::Null))),
+        _ => Err(PostgresExecutionError::Validation(
+            param_name.into(),
+            format!("Expected an object, got {argument}"),
+        )),
+    }
+}
+
+/// The access predicate for reading the given fields
+async fn fields_access_predicate<'a>(
+    fields: &[&'a PostgresField<EntityType>],
+    subsystem: &'a PostgresSubsystem,
+    request_context: &'a RequestContext<'a>,
+) -> Result<AbstractPredicate, PostgresExecutionError> {
+    let mut predicate = AbstractPredicate::True;
+
+    for field in fields {
+        let field_predicate = check_retrieve_access(
+            &subsystem.database_access_expressions[field.access.read],
+            subsystem,
+            request_context,
+        )
+        .await?;
+
+        if field_predicate == AbstractPredicate::False {
+            return Err(PostgresExecutionError::Authorization);
+        }
+
+        predicate = AbstractPredicate::and(predicate, field_predicate);
+    }
+
+    Ok(predicate)
+}
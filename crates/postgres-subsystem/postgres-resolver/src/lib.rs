@@ -12,10 +12,14 @@ pub use plugin::PostgresSubsystemLoader;
 mod abstract_operation_resolver;
 mod access_solver;
 mod aggregate_query;
+mod audit;
 mod auth_util;
 mod cast;
 mod column_path_util;
+mod connection_query;
 mod create_data_param_mapper;
+mod function;
+mod group_by_query;
 mod limit_offset_mapper;
 mod operation_resolver;
 mod order_by_mapper;
@@ -23,10 +27,15 @@ mod plugin;
 mod postgres_execution_error;
 mod postgres_mutation;
 mod postgres_query;
+mod postgres_subscription;
 mod predicate_mapper;
+mod refresh;
+mod soft_delete;
 mod sql_mapper;
 mod update_data_param_mapper;
+mod upsert;
 mod util;
+mod version;
 
 #[cfg(test)]
 mod test_utils;
@@ -10,15 +10,17 @@
 use async_trait::async_trait;
 use futures::future::join_all;
 
-use crate::util::to_pg_vector;
+use crate::util::{get_argument_field, to_pg_vector, to_text_search};
 use crate::{
     auth_util::check_retrieve_access, column_path_util::to_column_path,
-    postgres_execution_error::PostgresExecutionError, sql_mapper::SQLMapper,
+    postgres_execution_error::PostgresExecutionError, soft_delete::not_deleted_predicate,
+    sql_mapper::SQLMapper,
 };
 use core_plugin_interface::core_resolver::context::RequestContext;
 use core_plugin_interface::core_resolver::value::Val;
 use exo_sql::{
-    AbstractOrderBy, AbstractOrderByExpr, AbstractPredicate, Ordering, PhysicalColumnPath,
+    AbstractOrderBy, AbstractOrderByExpr, AbstractPredicate, ColumnPathLink, Function, Ordering,
+    PhysicalColumnPath,
 };
 
 use exo_sql::{ColumnPath, SQLParamContainer, VectorDistanceFunction};
@@ -26,6 +28,7 @@ use exo_sql::{ColumnPath, SQLParamContainer, VectorDistanceFunction};
 use postgres_model::{
     order::{OrderByParameter, OrderByParameterType, OrderByParameterTypeKind},
     subsystem::PostgresSubsystem,
+    types::EntityType,
 };
 
 pub(crate) struct OrderByParameterInput<'a> {
@@ -163,6 +166,33 @@ async fn order_by_pair<'a>(
                                 "Invalid vector order by parameter".into(),
                             )),
                         },
+                        OrderByParameterTypeKind::TextSearch => {
+                            let new_column_path = new_column_path.unwrap();
+
+                            // Order the best matches first, unless specified otherwise
+                            let default_order = Val::String("DESC".to_owned());
+                            let order = get_argument_field(parameter_value, "order")
+                                .unwrap_or(&default_order);
+
+                            let (query, language, mode) = to_text_search(
+                                parameter_value,
+                                &new_column_path,
+                                parameter_name,
+                                subsystem,
+                            )?;
+
+                            ordering(order).map(|ordering| {
+                                AbstractOrderBy(vec![(
+                                    AbstractOrderByExpr::TextSearchRank(
+                                        new_column_path,
+                                        query,
+                                        language,
+                                        mode,
+                                    ),
+                                    ordering,
+                                )])
+                            })
+                        }
                         OrderByParameterTypeKind::Composite { .. } => {
                             OrderByParameterInput {
                                 param: parameter,
@@ -171,6 +201,28 @@ async fn order_by_pair<'a>(
                             .to_sql(parameter_value, subsystem, request_context)
                             .await
                         }
+                        OrderByParameterTypeKind::Aggregate {
+                            entity_type_id,
+                            parameters,
+                        } => {
+                            aggregate_order_by(
+                                parameter,
+                                parameter_name,
+                                parameter_value,
+                                &subsystem.entity_types[*entity_type_id],
+                                parameters,
+                                &parent_column_path,
+                                subsystem,
+                                request_context,
+                            )
+                            .await
+                        }
+                        OrderByParameterTypeKind::ScalarAggregate { .. } => {
+                            Err(PostgresExecutionError::Validation(
+                                parameter_name.into(),
+                                "Invalid aggregate order by parameter".into(),
+                            ))
+                        }
                     }
                 }
                 None => Err(PostgresExecutionError::Validation(
@@ -186,7 +238,134 @@ async fn order_by_pair<'a>(
     }
 }
 
-fn ordering(argument: &Val) -> Result<Ordering, PostgresExecutionError> {
+/// Order by aggregates over the entities related through a one-to-many relation such as
+/// `{concertsAgg: {price: {max: DESC}}}`. Each aggregate becomes a subselect over the related rows.
+#[allow(clippy::too_many_arguments)]
+async fn aggregate_order_by<'a>(
+    relation_parameter: &'a OrderByParameter,
+    parameter_name: &str,
+    parameter_value: &'a Val,
+    entity_type: &'a EntityType,
+    parameters: &'a [OrderByParameter],
+    parent_column_path: &Option<PhysicalColumnPath>,
+    subsystem: &'a PostgresSubsystem,
+    request_context: &'a RequestContext<'a>,
+) -> Result<AbstractOrderBy, PostgresExecutionError> {
+    let relation = match &relation_parameter.column_path_link {
+        Some(ColumnPathLink::Relation(relation)) => relation,
+        _ => {
+            return Err(PostgresExecutionError::Generic(format!(
+                "Invalid relation for the aggregate order by parameter '{parameter_name}'"
+            )))
+        }
+    };
+
+    // The aggregates reveal information about the related entities, so (as with fields used in
+    // ordering) the entities must be readable without any restrictions
+    let entity_access = check_retrieve_access(
+        &subsystem.database_access_expressions[entity_type.access.read],
+        subsystem,
+        request_context,
+    )
+    .await?;
+    if entity_access != AbstractPredicate::True {
+        return Err(PostgresExecutionError::Authorization);
+    }
+
+    // The path to the relation's self column (such as `venues.id`) so that the subselect can refer
+    // to the current row
+    let self_column_path = to_column_path(
+        parent_column_path,
+        &Some(ColumnPathLink::Leaf(
+            relation.column_pairs[0].self_column_id,
+        )),
+    )
+    .unwrap();
+
+    let Val::Object(field_values) = parameter_value else {
+        return Err(PostgresExecutionError::Validation(
+            parameter_name.into(),
+            "Invalid aggregate order by parameter".into(),
+        ));
+    };
+
+    let mut order_by = vec![];
+
+    for (field_name, field_value) in field_values {
+        if field_value == &Val::Null {
+            continue;
+        }
+
+        let field_parameter = parameters
+            .iter()
+            .find(|p| &p.name == field_name)
+            .ok_or_else(|| {
+                PostgresExecutionError::Validation(
+                    parameter_name.into(),
+                    format!("Invalid field '{field_name}' to aggregate"),
+                )
+            })?;
+
+        if let Some(access) = &field_parameter.access {
+            let field_access = check_retrieve_access(
+                &subsystem.database_access_expressions[access.read],
+                subsystem,
+                request_context,
+            )
+            .await?;
+            if field_access != AbstractPredicate::True {
+                return Err(PostgresExecutionError::Authorization);
+            }
+        }
+
+        let (
+            Some(ColumnPathLink::Leaf(column_id)),
+            OrderByParameterTypeKind::ScalarAggregate { functions },
+            Val::Object(function_values),
+        ) = (
+            &field_parameter.column_path_link,
+            &subsystem.order_by_types[field_parameter.typ.innermost().type_id].kind,
+            field_value,
+        )
+        else {
+            return Err(PostgresExecutionError::Validation(
+                parameter_name.into(),
+                format!("Invalid aggregate order by parameter for field '{field_name}'"),
+            ));
+        };
+
+        for (function_name, function_value) in function_values {
+            if function_value == &Val::Null {
+                continue;
+            }
+
+            if !functions.contains(function_name) {
+                return Err(PostgresExecutionError::Validation(
+                    parameter_name.into(),
+                    format!("Invalid aggregate '{function_name}'"),
+                ));
+            }
+
+            order_by.push((
+                AbstractOrderByExpr::Aggregate(
+                    self_column_path.clone(),
+                    relation.clone(),
+                    Function::Named {
+                        function_name: function_name.clone(),
+                        column_id: *column_id,
+                    },
+                    // Soft-deleted entities don't count towards the aggregates
+                    not_deleted_predicate(entity_type),
+                ),
+                ordering(function_value)?,
+            ));
+        }
+    }
+
+    Ok(AbstractOrderBy(order_by))
+}
+
+pub(crate) fn ordering(argument: &Val) -> Result<Ordering, PostgresExecutionError> {
     fn str_ordering(value: &str) -> Result<Ordering, PostgresExecutionError> {
         if value == "ASC" {
             Ok(Ordering::Asc)
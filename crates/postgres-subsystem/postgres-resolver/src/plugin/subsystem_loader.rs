@@ -36,6 +36,9 @@ impl SubsystemLoader for PostgresSubsystemLoader {
     ) -> Result<Box<dyn SubsystemResolver + Send + Sync>, SubsystemLoadingError> {
         let subsystem = PostgresSubsystem::deserialize(serialized_subsystem)?;
 
+        #[cfg(feature = "network")]
+        let mut notification_listener = None;
+
         let database_client = if let Some(existing) = self.existing_client.take() {
             existing
         } else {
@@ -57,9 +60,17 @@ impl SubsystemLoader for PostgresSubsystemLoader {
                     .map(|s| s == "true")
                     .unwrap_or(true);
 
-                DatabaseClientManager::from_url(&url, check_connection, pool_size)
-                    .await
-                    .map_err(|e| SubsystemLoadingError::BoxedError(Box::new(e)))?
+                let database_client =
+                    DatabaseClientManager::from_url(&url, check_connection, pool_size)
+                        .await
+                        .map_err(|e| SubsystemLoadingError::BoxedError(Box::new(e)))?;
+
+                // Connects lazily (upon the first subscription), so no cost for systems without subscriptions
+                if !subsystem.subscriptions.is_empty() {
+                    notification_listener = Some(exo_sql::NotificationListener::new(url));
+                }
+
+                database_client
             }
 
             #[cfg(not(feature = "network"))]
@@ -74,6 +85,8 @@ impl SubsystemLoader for PostgresSubsystemLoader {
             id: self.id(),
             subsystem,
             executor,
+            #[cfg(feature = "network")]
+            notification_listener,
         }))
     }
 }
@@ -8,15 +8,25 @@
 // by the Apache License, Version 2.0.
 
 use crate::{
-    abstract_operation_resolver::resolve_operation, operation_resolver::OperationResolver,
+    abstract_operation_resolver::resolve_operation,
+    audit::{resolve_history_query, set_acting_user},
+    auth_util::check_access,
+    connection_query::resolve_connection_query,
+    function::resolve_function_operation,
+    operation_resolver::OperationResolver,
     postgres_execution_error::PostgresExecutionError,
+    postgres_subscription::compute_subscription_event_operation,
+    refresh::refresh_materialized_view,
+    sql_mapper::SQLOperationKind,
+    upsert::resolve_upsert,
+    version::resolve_versioned_update,
 };
 use async_graphql_parser::types::{FieldDefinition, OperationType, TypeDefinition};
 use async_trait::async_trait;
 use core_plugin_interface::{
     core_resolver::{
         context::RequestContext,
-        plugin::{SubsystemResolutionError, SubsystemResolver},
+        plugin::{SubscriptionEventStream, SubsystemResolutionError, SubsystemResolver},
         system_resolver::SystemResolver,
         validation::field::ValidatedField,
         InterceptedOperation, QueryResponse,
@@ -24,12 +34,19 @@ use core_plugin_interface::{
     interception::InterceptorIndex,
 };
 use exo_sql::DatabaseExecutor;
-use postgres_model::subsystem::PostgresSubsystem;
+#[cfg(feature = "network")]
+use exo_sql::NotificationListener;
+use postgres_model::{mutation::PostgresMutationParameters, subsystem::PostgresSubsystem};
+use serde_json::Value;
 
 pub struct PostgresSubsystemResolver {
     pub id: &'static str,
     pub subsystem: PostgresSubsystem,
     pub executor: DatabaseExecutor,
+    /// Listener for the change notifications that drive subscriptions (not available when using
+    /// an existing database client, since notifications require a dedicated connection)
+    #[cfg(feature = "network")]
+    pub notification_listener: Option<NotificationListener>,
 }
 
 #[async_trait]
@@ -47,6 +64,72 @@ impl SubsystemResolver for PostgresSubsystemResolver {
     ) -> Result<Option<QueryResponse>, SubsystemResolutionError> {
         let operation_name = &field.name;
 
+        // Connection queries shape their response from the selected page (instead of returning the
+        // result of a single SQL operation)
+        if operation_type == OperationType::Query {
+            if let Some(query) = self.subsystem.connection_queries.get_by_key(operation_name) {
+                return Ok(Some(
+                    resolve_connection_query(query, field, request_context, self).await?,
+                ));
+            }
+
+            // History queries select from the history table (instead of the type's table)
+            if let Some(query) = self.subsystem.history_queries.get_by_key(operation_name) {
+                return Ok(Some(
+                    resolve_history_query(query, field, request_context, self).await?,
+                ));
+            }
+
+            // Function queries select from the rows returned by the function
+            if let Some(query) = self.subsystem.function_queries.get_by_key(operation_name) {
+                return Ok(Some(
+                    resolve_function_operation(query, field, request_context, self).await?,
+                ));
+            }
+        }
+
+        if operation_type == OperationType::Mutation {
+            if let Some(mutation) = self.subsystem.mutations.get_by_key(operation_name) {
+                // The history triggers of audited types record the acting users set here
+                set_acting_user(request_context, self).await?;
+
+                // Refresh mutations select the content of the materialized view after refreshing it
+                if let PostgresMutationParameters::Refresh = &mutation.parameters {
+                    refresh_materialized_view(mutation, field, request_context, self).await?;
+                }
+
+                // Updates with an expected version report a conflict (instead of returning null)
+                // when the version is stale
+                if let PostgresMutationParameters::Update {
+                    version_param: Some(_),
+                    ..
+                } = &mutation.parameters
+                {
+                    return Ok(Some(
+                        resolve_versioned_update(mutation, field, request_context, self).await?,
+                    ));
+                }
+
+                // Upserts report an authorization error (instead of skipping the entity) when an
+                // existing entity conflicts, but may not be updated
+                if let PostgresMutationParameters::Upsert { .. } = &mutation.parameters {
+                    return Ok(Some(
+                        resolve_upsert(mutation, field, request_context, self).await?,
+                    ));
+                }
+            }
+
+            // Function mutations select from the rows returned by the function (which may change
+            // the database)
+            if let Some(mutation) = self.subsystem.function_mutations.get_by_key(operation_name) {
+                set_acting_user(request_context, self).await?;
+
+                return Ok(Some(
+                    resolve_function_operation(mutation, field, request_context, self).await?,
+                ));
+            }
+        }
+
         let operation = match operation_type {
             OperationType::Query => match self.subsystem.pk_queries.get_by_key(operation_name) {
                 Some(query) => Some(query.resolve(field, request_context, &self.subsystem).await),
@@ -62,7 +145,16 @@ impl SubsystemResolver for PostgresSubsystemResolver {
                             Some(query) => {
                                 Some(query.resolve(field, request_context, &self.subsystem).await)
                             }
-                            None => None,
+                            None => {
+                                match self.subsystem.group_by_queries.get_by_key(operation_name) {
+                                    Some(query) => Some(
+                                        query
+                                            .resolve(field, request_context, &self.subsystem)
+                                            .await,
+                                    ),
+                                    None => None,
+                                }
+                            }
                         },
                     },
                 },
@@ -79,9 +171,8 @@ impl SubsystemResolver for PostgresSubsystemResolver {
                     None => None,
                 }
             }
-            OperationType::Subscription => Some(Err(PostgresExecutionError::Generic(
-                "Subscriptions are not supported".to_string(),
-            ))),
+            // Subscriptions are resolved through `subscribe` and `resolve_subscription_event`
+            OperationType::Subscription => None,
         };
 
         match operation {
@@ -93,6 +184,105 @@ impl SubsystemResolver for PostgresSubsystemResolver {
         }
     }
 
+    async fn subscribe<'a>(
+        &'a self,
+        operation: &'a ValidatedField,
+        request_context: &'a RequestContext<'a>,
+    ) -> Result<Option<SubscriptionEventStream>, SubsystemResolutionError> {
+        let Some(subscription) = self.subsystem.subscriptions.get_by_key(&operation.name) else {
+            return Ok(None);
+        };
+
+        let entity_type = subscription.return_type.typ(&self.subsystem.entity_types);
+
+        // Reject upfront if the user may not read any entity of this type (access to the
+        // individual rows is evaluated for each event)
+        check_access(
+            entity_type,
+            &operation.subfields,
+            &SQLOperationKind::Retrieve,
+            &self.subsystem,
+            request_context,
+            None,
+        )
+        .await?;
+
+        #[cfg(feature = "network")]
+        {
+            use futures::StreamExt;
+
+            let listener = self.notification_listener.as_ref().ok_or_else(|| {
+                PostgresExecutionError::Generic(
+                    "Subscriptions are not supported with the current database connection"
+                        .to_string(),
+                )
+            })?;
+
+            let channel = self
+                .subsystem
+                .database
+                .get_table(entity_type.table_id)
+                .notification_channel();
+
+            let operation_name = subscription.parameters.kind.trigger_operation();
+
+            let payloads = listener
+                .listen(&channel)
+                .await
+                .map_err(PostgresExecutionError::Postgres)?;
+
+            let events = payloads.filter_map(move |payload| async move {
+                match serde_json::from_str::<Value>(&payload) {
+                    Ok(event) => (event.get("op").and_then(Value::as_str) == Some(operation_name))
+                        .then_some(event),
+                    Err(e) => {
+                        tracing::error!("Invalid change notification payload: {}", e);
+                        None
+                    }
+                }
+            });
+
+            Ok(Some(events.boxed()))
+        }
+
+        #[cfg(not(feature = "network"))]
+        {
+            let _ = entity_type;
+            Err(PostgresExecutionError::Generic(
+                "Subscriptions are not supported in this environment".to_string(),
+            )
+            .into())
+        }
+    }
+
+    async fn resolve_subscription_event<'a>(
+        &'a self,
+        operation: &'a ValidatedField,
+        event: &'a Value,
+        request_context: &'a RequestContext<'a>,
+        _system_resolver: &'a SystemResolver,
+    ) -> Result<Option<QueryResponse>, SubsystemResolutionError> {
+        let Some(subscription) = self.subsystem.subscriptions.get_by_key(&operation.name) else {
+            return Ok(None);
+        };
+
+        let operation = compute_subscription_event_operation(
+            subscription,
+            operation,
+            event,
+            request_context,
+            &self.subsystem,
+        )
+        .await?;
+
+        match operation {
+            Some(operation) => Ok(Some(
+                resolve_operation(&operation, self, request_context).await?,
+            )),
+            None => Ok(None),
+        }
+    }
+
     async fn invoke_interceptor<'a>(
         &'a self,
         _interceptor_index: InterceptorIndex,
@@ -111,6 +301,10 @@ impl SubsystemResolver for PostgresSubsystemResolver {
         self.subsystem.schema_mutations()
     }
 
+    fn schema_subscriptions(&self) -> Vec<FieldDefinition> {
+        self.subsystem.schema_subscriptions()
+    }
+
     fn schema_types(&self) -> Vec<TypeDefinition> {
         self.subsystem.schema_types()
     }
@@ -120,7 +314,7 @@ impl From<PostgresExecutionError> for SubsystemResolutionError {
     fn from(e: PostgresExecutionError) -> Self {
         match e {
             PostgresExecutionError::Authorization => SubsystemResolutionError::Authorization,
-            _ => SubsystemResolutionError::UserDisplayError(e.user_error_message()),
+            _ => SubsystemResolutionError::UserDisplayError(e.user_error_message(), e.code()),
         }
     }
 }
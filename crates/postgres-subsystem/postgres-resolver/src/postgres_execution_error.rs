@@ -8,7 +8,7 @@
 // by the Apache License, Version 2.0.
 
 use core_plugin_interface::core_resolver::{
-    access_solver::AccessSolverError, context::ContextExtractionError,
+    access_solver::AccessSolverError, context::ContextExtractionError, ErrorCode,
 };
 use thiserror::Error;
 use tracing::error;
@@ -44,6 +44,9 @@ pub enum PostgresExecutionError {
     #[error("Missing argument '{0}'")]
     MissingArgument(String),
 
+    #[error("{0}")]
+    Conflict(String),
+
     #[error("{0}")]
     ContextExtraction(#[from] ContextExtractionError),
 }
@@ -56,7 +59,9 @@ impl PostgresExecutionError {
     pub fn user_error_message(&self) -> String {
         match self {
             PostgresExecutionError::Authorization => "Not authorized".to_string(),
-            PostgresExecutionError::Validation(_, _) => self.to_string(),
+            PostgresExecutionError::Validation(_, _) | PostgresExecutionError::Conflict(_) => {
+                self.to_string()
+            }
             PostgresExecutionError::CastError(_) => {
                 "Unable to convert input to the expected type".to_string()
             }
@@ -70,6 +75,18 @@ impl PostgresExecutionError {
             }
         }
     }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            PostgresExecutionError::Authorization => ErrorCode::Unauthorized,
+            PostgresExecutionError::Validation(_, _) | PostgresExecutionError::CastError(_) => {
+                ErrorCode::BadUserInput
+            }
+            PostgresExecutionError::Conflict(_) => ErrorCode::Conflict,
+            PostgresExecutionError::WithContext(_, e) => e.code(),
+            _ => ErrorCode::OperationFailed,
+        }
+    }
 }
 
 impl From<AccessSolverError> for PostgresExecutionError {
@@ -14,24 +14,31 @@ use super::{
     util::{find_arg, return_type_info},
 };
 use crate::{
-    create_data_param_mapper::InsertOperation, operation_resolver::OperationResolver,
-    postgres_query::compute_select, predicate_mapper::compute_predicate, sql_mapper::SQLMapper,
+    create_data_param_mapper::InsertOperation,
+    operation_resolver::OperationResolver,
+    postgres_query::compute_select,
+    predicate_mapper::compute_predicates,
+    soft_delete::{deleted_predicate, deletion_time, not_deleted_predicate},
+    sql_mapper::SQLMapper,
     update_data_param_mapper::UpdateOperation,
+    version::version_predicate,
 };
 use async_trait::async_trait;
 use core_plugin_interface::core_model::types::OperationReturnType;
 use core_plugin_interface::core_resolver::{
-    context::RequestContext, validation::field::ValidatedField,
+    context::RequestContext, validation::field::ValidatedField, value::Val,
 };
 use exo_sql::{
     AbstractDelete, AbstractInsert, AbstractOperation, AbstractPredicate, AbstractSelect,
-    AbstractUpdate, Predicate,
+    AbstractUpdate, Column, ColumnId, OnConflictUpdate, Predicate,
 };
+use futures::future::try_join_all;
 use postgres_model::{
     mutation::{DataParameter, PostgresMutation, PostgresMutationParameters},
     predicate::PredicateParameter,
     subsystem::PostgresSubsystem,
     types::EntityType,
+    version::VersionParameter,
 };
 
 #[async_trait]
@@ -71,25 +78,53 @@ impl OperationResolver for PostgresMutation {
                 )
                 .await?,
             ),
-            PostgresMutationParameters::Delete(predicate_param) => AbstractOperation::Delete(
+            PostgresMutationParameters::Delete(predicate_params) => {
                 delete_operation(
                     return_type,
-                    predicate_param,
+                    predicate_params,
                     field,
                     abstract_select,
                     subsystem,
                     request_context,
                 )
-                .await?,
-            ),
+                .await?
+            }
             PostgresMutationParameters::Update {
                 data_param,
-                predicate_param,
+                predicate_params,
+                version_param,
             } => AbstractOperation::Update(
                 update_operation(
                     return_type,
                     data_param,
-                    predicate_param,
+                    predicate_params,
+                    version_param.as_ref(),
+                    field,
+                    abstract_select,
+                    subsystem,
+                    request_context,
+                )
+                .await?,
+            ),
+            PostgresMutationParameters::Upsert {
+                data_param,
+                conflict_columns,
+            } => AbstractOperation::Insert(
+                upsert_operation(
+                    return_type,
+                    data_param,
+                    conflict_columns,
+                    field,
+                    abstract_select,
+                    subsystem,
+                    request_context,
+                )
+                .await?,
+            ),
+            PostgresMutationParameters::Restore(predicate_params) => AbstractOperation::Update(
+                restore_operation(
+                    return_type,
+                    predicate_params,
                     field,
                     abstract_select,
                     subsystem,
@@ -97,6 +132,9 @@ impl OperationResolver for PostgresMutation {
                 )
                 .await?,
             ),
+            // The materialized view has been refreshed (see `refresh_materialized_view`), so we
+            // only need to select its content
+            PostgresMutationParameters::Refresh => AbstractOperation::Select(abstract_select),
         })
     }
 }
@@ -122,18 +160,73 @@ async fn create_operation<'content>(
     }
 }
 
+async fn upsert_operation<'content>(
+    return_type: &'content OperationReturnType<EntityType>,
+    data_param: &'content DataParameter,
+    conflict_columns: &[ColumnId],
+    field: &'content ValidatedField,
+    select: AbstractSelect,
+    subsystem: &'content PostgresSubsystem,
+    request_context: &'content RequestContext<'content>,
+) -> Result<AbstractInsert, PostgresExecutionError> {
+    let data_arg = find_arg(&field.arguments, &data_param.name)
+        .ok_or_else(|| PostgresExecutionError::MissingArgument(data_param.name.clone()))?;
+
+    // Mapping the data to rows checks the creation access, so here we only need to check the
+    // update access (for each row, since input access rules may depend on the data supplied)
+    let rows = match data_arg {
+        Val::List(rows) => rows.iter().collect(),
+        _ => vec![data_arg],
+    };
+    let update_access_predicates = try_join_all(rows.into_iter().map(|row| {
+        check_access(
+            return_type.typ(&subsystem.entity_types),
+            &field.subfields,
+            &SQLOperationKind::Update,
+            subsystem,
+            request_context,
+            Some(row),
+        )
+    }))
+    .await?;
+
+    // The residual predicates come from the database access rules, so are typically the same
+    // for every row
+    let mut update_predicate = AbstractPredicate::True;
+    for (index, predicate) in update_access_predicates.iter().enumerate() {
+        if !update_access_predicates[..index].contains(predicate) {
+            update_predicate = Predicate::and(update_predicate, predicate.clone());
+        }
+    }
+
+    let insert = InsertOperation { data_param, select }
+        .to_sql(data_arg, subsystem, request_context)
+        .await?;
+
+    Ok(AbstractInsert {
+        on_conflict: Some(OnConflictUpdate {
+            conflict_columns: conflict_columns.to_vec(),
+            predicate: update_predicate,
+        }),
+        ..insert
+    })
+}
+
+/// Delete the entities matching the arguments. For a type with the `@softDelete` annotation, this
+/// marks the (not already deleted) entities as deleted instead of removing them.
 async fn delete_operation<'content>(
     return_type: &'content OperationReturnType<EntityType>,
-    predicate_param: &'content PredicateParameter,
+    predicate_params: &'content [PredicateParameter],
     field: &'content ValidatedField,
     select: AbstractSelect,
     subsystem: &'content PostgresSubsystem,
     request_context: &'content RequestContext<'content>,
-) -> Result<AbstractDelete, PostgresExecutionError> {
+) -> Result<AbstractOperation, PostgresExecutionError> {
     let (table_id, _, _) = return_type_info(return_type, subsystem);
+    let entity_type = return_type.typ(&subsystem.entity_types);
 
     let access_predicate = check_access(
-        return_type.typ(&subsystem.entity_types),
+        entity_type,
         &field.subfields,
         &SQLOperationKind::Delete,
         subsystem,
@@ -142,8 +235,8 @@ async fn delete_operation<'content>(
     )
     .await?;
 
-    let arg_predicate = compute_predicate(
-        predicate_param,
+    let arg_predicate = compute_predicates(
+        predicate_params,
         &field.arguments,
         subsystem,
         request_context,
@@ -151,9 +244,77 @@ async fn delete_operation<'content>(
     .await?;
     let predicate = Predicate::and(access_predicate, arg_predicate);
 
-    Ok(AbstractDelete {
+    Ok(match &entity_type.soft_delete {
+        Some(soft_delete) => AbstractOperation::Update(AbstractUpdate {
+            table_id,
+            predicate: Predicate::and(predicate, not_deleted_predicate(entity_type)),
+            column_values: vec![(soft_delete.column_id, deletion_time(soft_delete, subsystem))],
+            nested_updates: vec![],
+            nested_inserts: vec![],
+            nested_deletes: vec![],
+            selection: select,
+        }),
+        None => AbstractOperation::Delete(AbstractDelete {
+            table_id,
+            predicate,
+            selection: select,
+        }),
+    })
+}
+
+/// Restore the soft-deleted entities matching the arguments. Restoring undoes a deletion, so
+/// requires the same access as deleting.
+async fn restore_operation<'content>(
+    return_type: &'content OperationReturnType<EntityType>,
+    predicate_params: &'content [PredicateParameter],
+    field: &'content ValidatedField,
+    select: AbstractSelect,
+    subsystem: &'content PostgresSubsystem,
+    request_context: &'content RequestContext<'content>,
+) -> Result<AbstractUpdate, PostgresExecutionError> {
+    let (table_id, _, _) = return_type_info(return_type, subsystem);
+    let entity_type = return_type.typ(&subsystem.entity_types);
+
+    let soft_delete = entity_type.soft_delete.as_ref().ok_or_else(|| {
+        PostgresExecutionError::Generic(format!(
+            "Type '{}' does not support soft deletion",
+            entity_type.name
+        ))
+    })?;
+
+    let access_predicate = check_access(
+        entity_type,
+        &field.subfields,
+        &SQLOperationKind::Delete,
+        subsystem,
+        request_context,
+        None,
+    )
+    .await?;
+
+    let arg_predicate = compute_predicates(
+        predicate_params,
+        &field.arguments,
+        subsystem,
+        request_context,
+    )
+    .await?;
+    let predicate = [
+        access_predicate,
+        arg_predicate,
+        deleted_predicate(soft_delete),
+    ]
+    .into_iter()
+    .reduce(Predicate::and)
+    .unwrap();
+
+    Ok(AbstractUpdate {
         table_id,
         predicate,
+        column_values: vec![(soft_delete.column_id, Column::Null)],
+        nested_updates: vec![],
+        nested_inserts: vec![],
+        nested_deletes: vec![],
         selection: select,
     })
 }
@@ -161,15 +322,17 @@ async fn delete_operation<'content>(
 async fn update_operation<'content>(
     return_type: &'content OperationReturnType<EntityType>,
     data_param: &'content DataParameter,
-    predicate_param: &'content PredicateParameter,
+    predicate_params: &'content [PredicateParameter],
+    version_param: Option<&'content VersionParameter>,
     field: &'content ValidatedField,
     select: AbstractSelect,
     subsystem: &'content PostgresSubsystem,
     request_context: &'content RequestContext<'content>,
 ) -> Result<AbstractUpdate, PostgresExecutionError> {
+    let entity_type = return_type.typ(&subsystem.entity_types);
     let data_arg = find_arg(&field.arguments, &data_param.name);
     let access_predicate = check_access(
-        return_type.typ(&subsystem.entity_types),
+        entity_type,
         &field.subfields,
         &SQLOperationKind::Update,
         subsystem,
@@ -178,14 +341,23 @@ async fn update_operation<'content>(
     )
     .await?;
 
-    let arg_predicate = compute_predicate(
-        predicate_param,
+    let arg_predicate = compute_predicates(
+        predicate_params,
         &field.arguments,
         subsystem,
         request_context,
     )
     .await?;
-    let predicate = Predicate::and(access_predicate, arg_predicate);
+    // Soft-deleted entities may only be restored, not updated
+    let predicate = [
+        access_predicate,
+        arg_predicate,
+        not_deleted_predicate(entity_type),
+        version_predicate(entity_type, version_param, &field.arguments, subsystem)?,
+    ]
+    .into_iter()
+    .reduce(Predicate::and)
+    .unwrap();
 
     match data_arg {
         Some(argument) => {
@@ -7,11 +7,12 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use super::predicate_mapper::compute_predicate;
+use super::predicate_mapper::{compute_predicate, compute_predicates};
 use super::{
     auth_util::check_access, postgres_execution_error::PostgresExecutionError,
     sql_mapper::SQLOperationKind, util::Arguments,
 };
+use crate::soft_delete::soft_delete_predicate;
 use crate::util::to_pg_vector;
 use crate::{
     operation_resolver::OperationSelectionResolver, order_by_mapper::OrderByParameterInput,
@@ -28,7 +29,6 @@ use exo_sql::{
     RelationId, SelectionCardinality, SelectionElement,
 };
 use exo_sql::{Function, SQLParamContainer};
-use futures::stream::TryStreamExt;
 use futures::StreamExt;
 use postgres_model::query::UniqueQuery;
 use postgres_model::vector_distance::VectorDistanceField;
@@ -36,7 +36,10 @@ use postgres_model::{
     aggregate::AggregateField,
     order::OrderByParameter,
     query::{CollectionQuery, CollectionQueryParameters, PkQuery},
-    relation::{ManyToOneRelation, OneToManyRelation, PostgresRelation, RelationCardinality},
+    relation::{
+        ManyToManyRelation, ManyToOneRelation, OneToManyRelation, PostgresRelation,
+        RelationCardinality,
+    },
     subsystem::PostgresSubsystem,
     types::{EntityType, PostgresField},
 };
@@ -49,8 +52,16 @@ impl OperationSelectionResolver for PkQuery {
         request_context: &'a RequestContext<'a>,
         subsystem: &'a PostgresSubsystem,
     ) -> Result<AbstractSelect, PostgresExecutionError> {
-        let predicate = compute_predicate(
-            &self.parameters.predicate_param,
+        let predicate = compute_predicates(
+            &self.parameters.predicate_params,
+            &field.arguments,
+            subsystem,
+            request_context,
+        )
+        .await?;
+        let deletion_predicate = soft_delete_predicate(
+            self.return_type.typ(&subsystem.entity_types),
+            self.parameters.include_deleted_param.as_ref(),
             &field.arguments,
             subsystem,
             request_context,
@@ -58,7 +69,7 @@ impl OperationSelectionResolver for PkQuery {
         .await?;
 
         compute_select(
-            predicate,
+            AbstractPredicate::and(predicate, deletion_predicate),
             None,
             None,
             None,
@@ -79,21 +90,24 @@ impl OperationSelectionResolver for UniqueQuery {
         request_context: &'a RequestContext<'a>,
         subsystem: &'a PostgresSubsystem,
     ) -> Result<AbstractSelect, PostgresExecutionError> {
-        let predicate = futures::stream::iter(
-            self.parameters
-                .predicate_params
-                .iter()
-                .map(Ok::<_, PostgresExecutionError>),
+        let predicate = compute_predicates(
+            &self.parameters.predicate_params,
+            &field.arguments,
+            subsystem,
+            request_context,
+        )
+        .await?;
+        let deletion_predicate = soft_delete_predicate(
+            self.return_type.typ(&subsystem.entity_types),
+            self.parameters.include_deleted_param.as_ref(),
+            &field.arguments,
+            subsystem,
+            request_context,
         )
-        .try_fold(AbstractPredicate::True, |acc, p| async {
-            let predicate =
-                compute_predicate(p, &field.arguments, subsystem, request_context).await?;
-            Ok(AbstractPredicate::and(acc, predicate))
-        })
         .await?;
 
         compute_select(
-            predicate,
+            AbstractPredicate::and(predicate, deletion_predicate),
             None,
             None,
             None,
@@ -119,12 +133,24 @@ impl OperationSelectionResolver for CollectionQuery {
             order_by_param,
             limit_param,
             offset_param,
+            include_deleted_param,
         } = &self.parameters;
 
         let arguments = &field.arguments;
 
+        let predicate =
+            compute_predicate(predicate_param, arguments, subsystem, request_context).await?;
+        let deletion_predicate = soft_delete_predicate(
+            self.return_type.typ(&subsystem.entity_types),
+            include_deleted_param.as_ref(),
+            arguments,
+            subsystem,
+            request_context,
+        )
+        .await?;
+
         compute_select(
-            compute_predicate(predicate_param, arguments, subsystem, request_context).await?,
+            AbstractPredicate::and(predicate, deletion_predicate),
             compute_order_by(order_by_param, arguments, subsystem, request_context).await?,
             extract_and_map(limit_param, arguments, subsystem, request_context).await?,
             extract_and_map(offset_param, arguments, subsystem, request_context).await?,
@@ -179,7 +205,7 @@ pub(super) async fn compute_select<'content>(
     })
 }
 
-async fn compute_order_by<'content>(
+pub(super) async fn compute_order_by<'content>(
     param: &'content OrderByParameter,
     arguments: &'content Arguments,
     subsystem: &'content PostgresSubsystem,
@@ -198,7 +224,7 @@ async fn compute_order_by<'content>(
 }
 
 #[async_recursion]
-async fn content_select<'content>(
+pub(super) async fn content_select<'content>(
     return_type: &EntityType,
     fields: &'content [ValidatedField],
     subsystem: &'content PostgresSubsystem,
@@ -263,11 +289,11 @@ async fn map_persistent_field<'content>(
         }
         PostgresRelation::ManyToOne(relation) => {
             let ManyToOneRelation {
-                foreign_pk_field_id,
+                foreign_pk_field_ids,
                 ..
             } = relation;
 
-            let foreign_type = &subsystem.entity_types[foreign_pk_field_id.entity_type_id()];
+            let foreign_type = &subsystem.entity_types[foreign_pk_field_ids[0].entity_type_id()];
 
             let foreign_table_pk_query = &subsystem.pk_queries[foreign_type.pk_query];
 
@@ -312,6 +338,24 @@ async fn map_persistent_field<'content>(
                 nested_abstract_select,
             ))
         }
+        PostgresRelation::ManyToMany(relation) => {
+            let ManyToManyRelation {
+                foreign_field_id,
+                relation_id,
+            } = relation;
+
+            let foreign_type = &subsystem.entity_types[foreign_field_id.entity_type_id()];
+            let collection_query = &subsystem.collection_queries[foreign_type.collection_query];
+
+            let nested_abstract_select = collection_query
+                .resolve_select(field, request_context, subsystem)
+                .await?;
+
+            Ok(SelectionElement::SubSelect(
+                RelationId::ManyToMany(*relation_id),
+                nested_abstract_select,
+            ))
+        }
     }
 }
 
@@ -321,41 +365,63 @@ async fn map_aggregate_field<'content>(
     subsystem: &'content PostgresSubsystem,
     request_context: &'content RequestContext<'content>,
 ) -> Result<SelectionElement, PostgresExecutionError> {
-    if let Some(PostgresRelation::OneToMany(relation)) = &agg_field.relation {
-        let OneToManyRelation {
-            foreign_field_id,
-            cardinality,
-            relation_id,
-        } = relation;
-        // TODO: Avoid code duplication with map_persistent_field
-        let foreign_type = &subsystem.entity_types[foreign_field_id.entity_type_id()];
-
-        let nested_abstract_select = {
-            // Aggregate is supported only for unbounded relations (i.e. not supported for one-to-one)
-            if cardinality == &RelationCardinality::Unbounded {
-                let aggregate_query = &subsystem.aggregate_queries[foreign_type.aggregate_query];
-
-                aggregate_query
-                    .resolve_select(field, request_context, subsystem)
-                    .await
-            } else {
-                // Reaching this point means our validation logic failed
-                Err(PostgresExecutionError::Generic(
-                    "Validation error: Aggregate is supported only for unbounded relations"
-                        .to_string(),
-                ))
-            }
-        }?;
+    match &agg_field.relation {
+        Some(PostgresRelation::OneToMany(relation)) => {
+            let OneToManyRelation {
+                foreign_field_id,
+                cardinality,
+                relation_id,
+            } = relation;
+            // TODO: Avoid code duplication with map_persistent_field
+            let foreign_type = &subsystem.entity_types[foreign_field_id.entity_type_id()];
 
-        Ok(SelectionElement::SubSelect(
-            RelationId::OneToMany(*relation_id),
-            nested_abstract_select,
-        ))
-    } else {
-        // Reaching this point means our validation logic failed
-        Err(PostgresExecutionError::Generic(
-            "Validation error: Aggregate is supported only for one-to-many".to_string(),
-        ))
+            let nested_abstract_select = {
+                // Aggregate is supported only for unbounded relations (i.e. not supported for one-to-one)
+                if cardinality == &RelationCardinality::Unbounded {
+                    let aggregate_query =
+                        &subsystem.aggregate_queries[foreign_type.aggregate_query];
+
+                    aggregate_query
+                        .resolve_select(field, request_context, subsystem)
+                        .await
+                } else {
+                    // Reaching this point means our validation logic failed
+                    Err(PostgresExecutionError::Generic(
+                        "Validation error: Aggregate is supported only for unbounded relations"
+                            .to_string(),
+                    ))
+                }
+            }?;
+
+            Ok(SelectionElement::SubSelect(
+                RelationId::OneToMany(*relation_id),
+                nested_abstract_select,
+            ))
+        }
+        Some(PostgresRelation::ManyToMany(relation)) => {
+            let ManyToManyRelation {
+                foreign_field_id,
+                relation_id,
+            } = relation;
+            let foreign_type = &subsystem.entity_types[foreign_field_id.entity_type_id()];
+            let aggregate_query = &subsystem.aggregate_queries[foreign_type.aggregate_query];
+
+            let nested_abstract_select = aggregate_query
+                .resolve_select(field, request_context, subsystem)
+                .await?;
+
+            Ok(SelectionElement::SubSelect(
+                RelationId::ManyToMany(*relation_id),
+                nested_abstract_select,
+            ))
+        }
+        _ => {
+            // Reaching this point means our validation logic failed
+            Err(PostgresExecutionError::Generic(
+                "Validation error: Aggregate is supported only for one-to-many and many-to-many"
+                    .to_string(),
+            ))
+        }
     }
 }
 
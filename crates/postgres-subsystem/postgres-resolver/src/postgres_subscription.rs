@@ -0,0 +1,430 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use core_plugin_interface::core_resolver::{
+    context::RequestContext, validation::field::ValidatedField, value::Val,
+};
+use exo_sql::{
+    AbstractOperation, AbstractPredicate, AbstractRecordSelect, AbstractSelect, ColumnPath,
+    PhysicalColumnPath, TableId,
+};
+use postgres_model::{
+    subscription::{PostgresSubscription, SubscriptionKind},
+    subsystem::PostgresSubsystem,
+};
+use serde_json::Value;
+
+use crate::{
+    cast::literal_column_path, postgres_execution_error::PostgresExecutionError,
+    postgres_query::compute_select, predicate_mapper::compute_predicate,
+    soft_delete::not_deleted_predicate,
+};
+
+/// Compute the operation to resolve a subscription for a change event published by the table's
+/// notification trigger.
+///
+/// The event is of the form `{"op": "INSERT", "row": {...}}` (with an additional `"partial": true`
+/// if the row was too large for a notification and only its primary key is included). Returns
+/// `None` if the event is for a different kind of change than the one subscribed to or if the
+/// subscriber may not read the changed entity at all.
+///
+/// The selection, the `where` argument, and the access rules are all evaluated against the
+/// changed row. For complete rows, we evaluate against the row in the event (which is the only
+/// option for deleted rows, since they no longer exist in the table). For partial rows, we query
+/// the table by the primary key (for partial deleted rows, only the primary key columns are
+/// available to evaluate against).
+pub(crate) async fn compute_subscription_event_operation<'a>(
+    subscription: &'a PostgresSubscription,
+    field: &'a ValidatedField,
+    event: &'a Value,
+    request_context: &'a RequestContext<'a>,
+    subsystem: &'a PostgresSubsystem,
+) -> Result<Option<AbstractOperation>, PostgresExecutionError> {
+    let kind = subscription.parameters.kind;
+
+    if event.get("op").and_then(Value::as_str) != Some(kind.trigger_operation()) {
+        return Ok(None);
+    }
+
+    let row = event.get("row").ok_or_else(|| {
+        PostgresExecutionError::Generic("Invalid change event: missing row".to_string())
+    })?;
+    let partial = event.get("partial").and_then(Value::as_bool) == Some(true);
+
+    let predicate = compute_predicate(
+        &subscription.parameters.predicate_param,
+        &field.arguments,
+        subsystem,
+        request_context,
+    )
+    .await?;
+    // A soft deletion shows up as an update, which shouldn't reach subscribers as one
+    let predicate = if kind == SubscriptionKind::Deleted {
+        predicate
+    } else {
+        AbstractPredicate::and(
+            predicate,
+            not_deleted_predicate(subscription.return_type.typ(&subsystem.entity_types)),
+        )
+    };
+
+    let select = compute_select(
+        predicate,
+        None,
+        None,
+        None,
+        &subscription.return_type,
+        &field.subfields,
+        subsystem,
+        request_context,
+    )
+    .await;
+
+    // An event the subscriber may not read is not delivered (rather than reported as an error)
+    let select = match select {
+        Ok(select) => select,
+        Err(PostgresExecutionError::Authorization) => return Ok(None),
+        Err(e) => return Err(e),
+    };
+
+    if partial && kind != SubscriptionKind::Deleted {
+        let pk_predicate = pk_predicate(select.table_id, row, subsystem)?;
+
+        Ok(Some(AbstractOperation::Select(AbstractSelect {
+            predicate: AbstractPredicate::and(select.predicate, pk_predicate),
+            ..select
+        })))
+    } else {
+        Ok(Some(AbstractOperation::RecordSelect(
+            AbstractRecordSelect {
+                record: row.clone(),
+                select,
+            },
+        )))
+    }
+}
+
+fn pk_predicate(
+    table_id: TableId,
+    row: &Value,
+    subsystem: &PostgresSubsystem,
+) -> Result<AbstractPredicate, PostgresExecutionError> {
+    let pk_column_ids = subsystem.database.get_pk_column_ids(table_id);
+    if pk_column_ids.is_empty() {
+        return Err(PostgresExecutionError::Generic(
+            "Missing primary key".to_string(),
+        ));
+    }
+
+    pk_column_ids
+        .into_iter()
+        .try_fold(AbstractPredicate::True, |acc, pk_column_id| {
+            let pk_column = pk_column_id.get_column(&subsystem.database);
+
+            let pk_value = row.get(&pk_column.name).cloned().ok_or_else(|| {
+                PostgresExecutionError::Generic(
+                    "Invalid change event: missing primary key".to_string(),
+                )
+            })?;
+
+            Ok(AbstractPredicate::and(
+                acc,
+                AbstractPredicate::eq(
+                    ColumnPath::Physical(PhysicalColumnPath::leaf(pk_column_id)),
+                    literal_column_path(&Val::from(pk_value), &pk_column.typ)?,
+                ),
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Arc};
+
+    use common::http::RequestHead;
+    use core_plugin_interface::{
+        async_graphql_value::{indexmap::IndexMap, Name},
+        interception::InterceptionMap,
+        persisted_queries::PersistedQueries,
+        trusted_documents::TrustedDocuments,
+    };
+    use core_resolver::{
+        introspection::definition::schema::Schema, response_cache::ResponseCache,
+        system_resolver::SystemResolver, validation::query_cost::QueryCostModel,
+    };
+    use exo_env::MapEnvironment;
+    use exo_sql::{PhysicalTableName, SQLParamContainer};
+    use serde_json::json;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn event_of_other_kind() {
+        let subsystem = test_subsystem().await;
+        let system_resolver = test_system_resolver();
+        let request_context = test_request_context(json!({"role": "admin"}), &system_resolver);
+
+        let operation = event_operation(
+            &subsystem,
+            "onConcertCreated",
+            &json!({"op": "UPDATE", "row": {"id": 1, "title": "C1", "published": true}}),
+            &request_context,
+        )
+        .await;
+
+        assert!(operation.is_none());
+    }
+
+    #[tokio::test]
+    async fn event_for_unreadable_row() {
+        let subsystem = test_subsystem().await;
+        let system_resolver = test_system_resolver();
+        let event = json!({"op": "INSERT", "row": {"id": 1, "content": "S1"}});
+
+        // Only admins may read secrets, so the event is dropped for others
+        let request_context = test_request_context(json!({"role": "user"}), &system_resolver);
+        let operation =
+            event_operation(&subsystem, "onSecretCreated", &event, &request_context).await;
+        assert!(operation.is_none());
+
+        let request_context = test_request_context(json!({"role": "admin"}), &system_resolver);
+        let operation =
+            event_operation(&subsystem, "onSecretCreated", &event, &request_context).await;
+        assert!(matches!(
+            operation,
+            Some(AbstractOperation::RecordSelect(AbstractRecordSelect { record, .. })) if record == event["row"]
+        ));
+    }
+
+    #[tokio::test]
+    async fn event_for_row_with_row_level_access() {
+        let subsystem = test_subsystem().await;
+        let system_resolver = test_system_resolver();
+        let event = json!({"op": "INSERT", "row": {"id": 1, "title": "C1", "published": false}});
+
+        // Others may read only published concerts, which is evaluated against the row in the event
+        // (so the select returns no entity for an unpublished concert and the event is dropped)
+        let request_context = test_request_context(json!({"role": "user"}), &system_resolver);
+        let Some(AbstractOperation::RecordSelect(AbstractRecordSelect { record, select })) =
+            event_operation(&subsystem, "onConcertCreated", &event, &request_context).await
+        else {
+            panic!("Expected a record select");
+        };
+
+        assert_eq!(record, event["row"]);
+        assert_eq!(
+            select.predicate,
+            AbstractPredicate::eq(
+                concert_column(&subsystem, "published"),
+                ColumnPath::Param(SQLParamContainer::bool(true))
+            )
+        );
+
+        let request_context = test_request_context(json!({"role": "admin"}), &system_resolver);
+        let Some(AbstractOperation::RecordSelect(AbstractRecordSelect { select, .. })) =
+            event_operation(&subsystem, "onConcertCreated", &event, &request_context).await
+        else {
+            panic!("Expected a record select");
+        };
+
+        assert_eq!(select.predicate, AbstractPredicate::True);
+    }
+
+    #[tokio::test]
+    async fn partial_event() {
+        let subsystem = test_subsystem().await;
+        let system_resolver = test_system_resolver();
+        let request_context = test_request_context(json!({"role": "admin"}), &system_resolver);
+
+        let id_predicate = AbstractPredicate::eq(
+            concert_column(&subsystem, "id"),
+            ColumnPath::Param(SQLParamContainer::i32(5)),
+        );
+
+        // A row too large for a notification includes only the primary key, so the row is queried
+        // from the table instead
+        let event = json!({"op": "UPDATE", "partial": true, "row": {"id": 5}});
+        let Some(AbstractOperation::Select(select)) =
+            event_operation(&subsystem, "onConcertUpdated", &event, &request_context).await
+        else {
+            panic!("Expected a select");
+        };
+        assert_eq!(select.predicate, id_predicate);
+
+        // A deleted row is no longer in the table, so only the primary key is available
+        let event = json!({"op": "DELETE", "partial": true, "row": {"id": 5}});
+        let Some(AbstractOperation::RecordSelect(AbstractRecordSelect { record, .. })) =
+            event_operation(&subsystem, "onConcertDeleted", &event, &request_context).await
+        else {
+            panic!("Expected a record select");
+        };
+        assert_eq!(record, json!({"id": 5}));
+
+        // The primary key is required
+        let field = subscription_field("onConcertUpdated");
+        let event = json!({"op": "UPDATE", "partial": true, "row": {}});
+        let operation = compute_subscription_event_operation(
+            subsystem
+                .subscriptions
+                .get_by_key("onConcertUpdated")
+                .unwrap(),
+            &field,
+            &event,
+            &request_context,
+            &subsystem,
+        )
+        .await;
+        assert!(operation.is_err());
+    }
+
+    async fn event_operation(
+        subsystem: &PostgresSubsystem,
+        subscription_name: &str,
+        event: &Value,
+        request_context: &RequestContext<'_>,
+    ) -> Option<AbstractOperation> {
+        let field = subscription_field(subscription_name);
+
+        compute_subscription_event_operation(
+            subsystem
+                .subscriptions
+                .get_by_key(subscription_name)
+                .unwrap(),
+            &field,
+            event,
+            request_context,
+            subsystem,
+        )
+        .await
+        .unwrap()
+    }
+
+    /// A subscription field selecting `id`
+    fn subscription_field(name: &str) -> ValidatedField {
+        ValidatedField {
+            alias: None,
+            name: Name::new(name),
+            arguments: IndexMap::new(),
+            subfields: vec![ValidatedField {
+                alias: None,
+                name: Name::new("id"),
+                arguments: IndexMap::new(),
+                subfields: vec![],
+            }],
+        }
+    }
+
+    fn concert_column(subsystem: &PostgresSubsystem, column_name: &str) -> ColumnPath {
+        let table_id = subsystem
+            .database
+            .get_table_id(&PhysicalTableName::new("concerts", None))
+            .unwrap();
+
+        ColumnPath::Physical(PhysicalColumnPath::leaf(
+            subsystem
+                .database
+                .get_column_id(table_id, column_name)
+                .unwrap(),
+        ))
+    }
+
+    async fn test_subsystem() -> PostgresSubsystem {
+        crate::test_utils::create_postgres_system_from_str(
+            r#"
+                context AuthContext {
+                    @test("role") role: String
+                }
+
+                @postgres
+                module ConcertModule {
+                    @subscribable
+                    @access(query=self.published || AuthContext.role == "admin", mutation=true)
+                    type Concert {
+                        @pk id: Int = autoIncrement()
+                        title: String
+                        published: Boolean
+                    }
+
+                    @subscribable
+                    @access(query=AuthContext.role == "admin", mutation=true)
+                    type Secret {
+                        @pk id: Int = autoIncrement()
+                        content: String
+                    }
+                }
+            "#,
+            "test.exo".to_string(),
+        )
+        .await
+        .unwrap()
+    }
+
+    // The resolver is never invoked (since there is no @query context)
+    fn test_system_resolver() -> SystemResolver {
+        SystemResolver::new(
+            vec![],
+            InterceptionMap {
+                map: HashMap::new(),
+            },
+            InterceptionMap {
+                map: HashMap::new(),
+            },
+            TrustedDocuments::all(),
+            PersistedQueries::new(0),
+            Schema::new(vec![], vec![], vec![], vec![]),
+            None.into(),
+            Arc::new(MapEnvironment::from(HashMap::new())),
+            10,
+            10,
+            QueryCostModel::default(),
+            1,
+            10,
+            ResponseCache::new(0),
+        )
+    }
+
+    fn test_request_context(
+        test_values: Value,
+        system_resolver: &SystemResolver,
+    ) -> RequestContext {
+        RequestContext::new(
+            &REQUEST,
+            vec![Box::new(core_resolver::context::TestRequestContext {
+                test_values,
+            })],
+            system_resolver,
+        )
+    }
+
+    struct TestRequest {}
+
+    impl RequestHead for TestRequest {
+        fn get_headers(&self, _key: &str) -> Vec<String> {
+            vec![]
+        }
+
+        fn get_ip(&self) -> Option<std::net::IpAddr> {
+            None
+        }
+
+        fn get_method(&self) -> &http::Method {
+            &http::Method::POST
+        }
+
+        fn get_path(&self) -> &str {
+            ""
+        }
+
+        fn get_query(&self) -> serde_json::Value {
+            Default::default()
+        }
+    }
+
+    const REQUEST: TestRequest = TestRequest {};
+}
@@ -13,8 +13,8 @@ use futures::{StreamExt, TryStreamExt};
 use core_plugin_interface::core_resolver::context::RequestContext;
 use core_plugin_interface::core_resolver::value::Val;
 use exo_sql::{
-    AbstractPredicate, CaseSensitivity, ColumnPath, ParamEquality, PhysicalColumnPath,
-    PhysicalColumnType, Predicate,
+    AbstractPredicate, CaseSensitivity, ColumnPath, ColumnPathLink, ParamEquality,
+    PhysicalColumnPath, PhysicalColumnType, Predicate,
 };
 
 use exo_sql::{NumericComparator, SQLParamContainer};
@@ -29,11 +29,15 @@ use crate::{
     auth_util::check_retrieve_access,
     cast::literal_column_path,
     column_path_util::to_column_path,
+    soft_delete::not_deleted_linked_predicate,
     sql_mapper::{extract_and_map, SQLMapper},
     util::{get_argument_field, Arguments},
 };
 
-use crate::{cast::cast_value, util::to_pg_vector};
+use crate::{
+    cast::cast_value,
+    util::{to_pg_vector, to_text_search},
+};
 
 use super::postgres_execution_error::PostgresExecutionError;
 
@@ -66,24 +70,24 @@ impl<'a> SQLMapper<'a, AbstractPredicate> for PredicateParamInput<'a> {
                 Ok(AbstractPredicate::eq(op_key_path, op_value_path))
             }
             PredicateParameterTypeKind::Reference(parameters) => {
-                parameters
-                    .iter()
-                    .try_fold(AbstractPredicate::True, |acc, parameter| {
+                // The parameters are the primary key fields of the referenced type, which are in
+                // the same order as the columns of the link
+                let param_column_ids = self
+                    .param
+                    .column_path_link
+                    .as_ref()
+                    .unwrap()
+                    .self_column_ids();
+
+                parameters.iter().zip(param_column_ids).try_fold(
+                    AbstractPredicate::True,
+                    |acc, (parameter, param_column_id)| {
                         let arg = get_argument_field(argument, &parameter.name);
 
                         match arg {
                             Some(arg) => {
-                                let param_column_id = &self
-                                    .param
-                                    .column_path_link
-                                    .as_ref()
-                                    .unwrap()
-                                    .self_column_id()
-                                    .clone();
-
-                                let param_column_path = ColumnPath::Physical(
-                                    PhysicalColumnPath::leaf(*param_column_id),
-                                );
+                                let param_column_path =
+                                    ColumnPath::Physical(PhysicalColumnPath::leaf(param_column_id));
 
                                 let param_physical_column =
                                     param_column_id.get_column(&subsystem.database);
@@ -101,7 +105,8 @@ impl<'a> SQLMapper<'a, AbstractPredicate> for PredicateParamInput<'a> {
                                 format!("Reference parameter {} is missing", parameter.name),
                             ))?,
                         }
-                    })
+                    },
+                )
             }
             PredicateParameterTypeKind::Operator(parameters) => {
                 parameters
@@ -127,18 +132,13 @@ impl<'a> SQLMapper<'a, AbstractPredicate> for PredicateParamInput<'a> {
                                             let operator = map.keys().next().unwrap();
                                             let threshold = map.values().next().unwrap();
 
-                                            let distance_comparator = match operator.as_str() {
-                                                "eq" => Ok(NumericComparator::Eq),
-                                                "neq" => Ok(NumericComparator::Neq),
-                                                "lt" => Ok(NumericComparator::Lt),
-                                                "lte" => Ok(NumericComparator::Lte),
-                                                "gt" => Ok(NumericComparator::Gt),
-                                                "gte" => Ok(NumericComparator::Gte),
-                                                _ => Err(PostgresExecutionError::Validation(
-                                                    "distance".into(),
-                                                    "Invalid distance operator".into(),
-                                                )),
-                                            }?;
+                                            let distance_comparator = numeric_comparator(operator)
+                                                .ok_or_else(|| {
+                                                    PostgresExecutionError::Validation(
+                                                        "distance".into(),
+                                                        "Invalid distance operator".into(),
+                                                    )
+                                                })?;
 
                                             let threshold = cast_value(
                                                 threshold,
@@ -171,6 +171,24 @@ impl<'a> SQLMapper<'a, AbstractPredicate> for PredicateParamInput<'a> {
                                             "Invalid distance parameter".into(),
                                         )),
                                     }
+                                } else if parameter.name == "isNull" {
+                                    is_null_predicate(
+                                        self.param,
+                                        &self.parent_column_path,
+                                        op_value,
+                                        subsystem,
+                                    )
+                                } else if let PhysicalColumnType::Array { typ: element_type } =
+                                    &param_column_type(self.param, subsystem)
+                                {
+                                    array_predicate(
+                                        &parameter.name,
+                                        self.param,
+                                        op_value,
+                                        element_type,
+                                        &self.parent_column_path,
+                                        subsystem,
+                                    )
                                 } else {
                                     let override_op_value_type = match parameter.name.as_str() {
                                         "matchAllKeys" | "matchAnyKey" => {
@@ -180,6 +198,12 @@ impl<'a> SQLMapper<'a, AbstractPredicate> for PredicateParamInput<'a> {
                                                 }),
                                             })
                                         }
+                                        // The list of values is bound as an array of the column's type
+                                        "in" | "notIn" => Some(PhysicalColumnType::Array {
+                                            typ: Box::new(
+                                                param_column_type(self.param, subsystem).clone(),
+                                            ),
+                                        }),
                                         _ => None,
                                     };
 
@@ -209,7 +233,15 @@ impl<'a> SQLMapper<'a, AbstractPredicate> for PredicateParamInput<'a> {
             PredicateParameterTypeKind::Composite {
                 field_params,
                 logical_op_params,
+                is_null_param,
             } => {
+                let null_check_predicate = match get_argument_field(argument, &is_null_param.name) {
+                    Some(is_null) => {
+                        is_null_predicate(self.param, &self.parent_column_path, is_null, subsystem)?
+                    }
+                    None => AbstractPredicate::True,
+                };
+
                 // first, match any logical op predicates the argument_value might contain
                 // logical_op_argument_value is of the form operation and value pair. For example,
                 // `and: [{name: {eq: "foo"}}, {id: {lt: 1}}]` will be mapped to `("and", Some([{name: {eq: "foo"}}, {id: {lt: 1}}]))`
@@ -236,7 +268,7 @@ impl<'a> SQLMapper<'a, AbstractPredicate> for PredicateParamInput<'a> {
                     })?;
 
                 // do we have a match?
-                match logical_op_argument_value {
+                let predicate = match logical_op_argument_value {
                     (logical_op_name, Some(logical_op_argument_value)) => {
                         // we have a single logical op predicate argument
                         // e.g. and: [..], or: [..], not: {..}
@@ -317,7 +349,7 @@ impl<'a> SQLMapper<'a, AbstractPredicate> for PredicateParamInput<'a> {
                             arg.map(|arg| (arg, parameter))
                         });
 
-                        futures::stream::iter(provided_field_params)
+                        let field_predicate = futures::stream::iter(provided_field_params)
                             .map(Ok)
                             .try_fold(AbstractPredicate::True, |acc, (arg, parameter)| async {
                                 let new_column_path = to_column_path(
@@ -350,14 +382,110 @@ impl<'a> SQLMapper<'a, AbstractPredicate> for PredicateParamInput<'a> {
                                     Ok(AbstractPredicate::and(acc, param_predicate))
                                 }
                             })
-                            .await
+                            .await?;
+
+                        // The fields of a related entity (such as `{concerts: {title: ...}}`) may
+                        // match only the entities that aren't soft-deleted
+                        if field_predicate == AbstractPredicate::True {
+                            Ok(field_predicate)
+                        } else {
+                            Ok(AbstractPredicate::and(
+                                field_predicate,
+                                not_deleted_linked_predicate(
+                                    &self.parent_column_path,
+                                    &self.param.column_path_link,
+                                    subsystem,
+                                ),
+                            ))
+                        }
                     }
-                }
+                }?;
+
+                Ok(AbstractPredicate::and(null_check_predicate, predicate))
+            }
+            PredicateParameterTypeKind::ManyToMany(parameters) => {
+                // The path to the linking table (`concerts -> concert_artists`) followed by the path
+                // to the foreign table (`concert_artists -> artists`). For "every" and "none", we
+                // negate the link to the linking table, so that the predicate holds when there is
+                // no linked row that matches the inner predicate (negated, in case of "every").
+                let param = self.param;
+                let parent_column_path = &self.parent_column_path;
+                let self_link = param.column_path_link.as_ref().unwrap();
+
+                futures::stream::iter(parameters.iter().flat_map(|parameter| {
+                    get_argument_field(argument, &parameter.name).map(|arg| (arg, parameter))
+                }))
+                .map(Ok)
+                .try_fold(
+                    AbstractPredicate::True,
+                    |acc, (arg, parameter)| async move {
+                        let (link, negate_inner) = match parameter.name.as_str() {
+                            "some" => (self_link.clone(), false),
+                            "none" => (self_link.clone().negated(), false),
+                            "every" => (self_link.clone().negated(), true),
+                            _ => {
+                                return Err(PostgresExecutionError::Validation(
+                                    param.name.clone(),
+                                    format!("Unknown many-to-many operator '{}'", parameter.name),
+                                ))
+                            }
+                        };
+
+                        let linking_column_path = to_column_path(parent_column_path, &Some(link));
+                        let foreign_column_path =
+                            to_column_path(&linking_column_path, &param.foreign_column_path_link);
+
+                        let inner_predicate = PredicateParamInput {
+                            param: parameter,
+                            parent_column_path: foreign_column_path,
+                        }
+                        .to_sql(arg, subsystem, request_context)
+                        .await?;
+
+                        let inner_predicate = if negate_inner {
+                            !inner_predicate
+                        } else {
+                            inner_predicate
+                        };
+
+                        // Soft-deleted foreign entities don't count (for "every" as well, so that
+                        // they don't need to match)
+                        let inner_predicate = AbstractPredicate::and(
+                            inner_predicate,
+                            not_deleted_linked_predicate(
+                                &linking_column_path,
+                                &param.foreign_column_path_link,
+                                subsystem,
+                            ),
+                        );
+
+                        Ok(AbstractPredicate::and(acc, inner_predicate))
+                    },
+                )
+                .await
+            }
+            PredicateParameterTypeKind::TextSearch => {
+                let column_path =
+                    to_column_path(&self.parent_column_path, &self.param.column_path_link).unwrap();
+
+                let (query, language, mode) =
+                    to_text_search(argument, &column_path, &self.param.name, subsystem)?;
+
+                Ok(AbstractPredicate::TextSearch(
+                    ColumnPath::Physical(column_path),
+                    ColumnPath::Param(query),
+                    language,
+                    mode,
+                ))
             }
             PredicateParameterTypeKind::Vector => Err(PostgresExecutionError::Validation(
                 self.param.name.clone(),
                 "Vector argument not expected in this context".into(),
             )),
+            PredicateParameterTypeKind::TextSearchMode => Err(PostgresExecutionError::Validation(
+                self.param.name.clone(),
+                "Text search mode not expected in this context".into(),
+            )),
         }
     }
 
@@ -379,6 +507,8 @@ pub fn predicate_from_name<C: PartialEq + ParamEquality>(
         "lte" => Predicate::Lte(lhs, rhs),
         "gt" => Predicate::Gt(lhs, rhs),
         "gte" => Predicate::Gte(lhs, rhs),
+        "in" => Predicate::In(lhs, rhs),
+        "notIn" => !Predicate::In(lhs, rhs),
         "like" => Predicate::StringLike(lhs, rhs, CaseSensitivity::Sensitive),
         "ilike" => Predicate::StringLike(lhs, rhs, CaseSensitivity::Insensitive),
         "startsWith" => Predicate::StringStartsWith(lhs, rhs),
@@ -392,6 +522,222 @@ pub fn predicate_from_name<C: PartialEq + ParamEquality>(
     }
 }
 
+fn numeric_comparator(operator: &str) -> Option<NumericComparator> {
+    match operator {
+        "eq" => Some(NumericComparator::Eq),
+        "neq" => Some(NumericComparator::Neq),
+        "lt" => Some(NumericComparator::Lt),
+        "lte" => Some(NumericComparator::Lte),
+        "gt" => Some(NumericComparator::Gt),
+        "gte" => Some(NumericComparator::Gte),
+        _ => None,
+    }
+}
+
+/// The type of the column that the (scalar field) parameter refers to
+fn param_column_type<'a>(
+    param: &PredicateParameter,
+    subsystem: &'a PostgresSubsystem,
+) -> &'a PhysicalColumnType {
+    &param
+        .column_path_link
+        .as_ref()
+        .expect("Could not find column path link")
+        .self_column_ids()[0]
+        .get_column(&subsystem.database)
+        .typ
+}
+
+/// Predicate for the length of an array field such as `{tags: {length: {gte: 2, notIn: [5]}}}`.
+/// The argument is an `IntFilter`, so it supports all its operators (the length of a null array is
+/// null, so `isNull` checks the array itself).
+fn array_length_predicate(
+    param: &PredicateParameter,
+    op_value: &Val,
+    parent_column_path: &Option<PhysicalColumnPath>,
+    subsystem: &PostgresSubsystem,
+) -> Result<AbstractPredicate, PostgresExecutionError> {
+    let column_path =
+        ColumnPath::Physical(to_column_path(parent_column_path, &param.column_path_link).unwrap());
+
+    let length_comparison = |comparator: NumericComparator,
+                             length: &Val|
+     -> Result<AbstractPredicate, PostgresExecutionError> {
+        let length = literal_column_path(
+            length,
+            &PhysicalColumnType::Int {
+                bits: exo_sql::IntBits::_32,
+            },
+        )?;
+
+        Ok(AbstractPredicate::ArrayLength(
+            column_path.clone(),
+            comparator,
+            length,
+        ))
+    };
+
+    // The predicate for `in` (the negation of which is the predicate for `notIn`)
+    let length_in = |lengths: &Val| match lengths {
+        Val::List(lengths) => lengths
+            .iter()
+            .try_fold(AbstractPredicate::False, |acc, length| {
+                Ok(AbstractPredicate::or(
+                    acc,
+                    length_comparison(NumericComparator::Eq, length)?,
+                ))
+            }),
+        _ => Err(PostgresExecutionError::Validation(
+            param.name.clone(),
+            "Invalid length parameter (expected a list)".into(),
+        )),
+    };
+
+    match op_value {
+        Val::Object(map) => {
+            map.iter()
+                .try_fold(AbstractPredicate::True, |acc, (operator, length)| {
+                    let new_predicate = match (operator.as_str(), length) {
+                        // Like other operators, a null argument doesn't filter anything
+                        (_, Val::Null) => AbstractPredicate::True,
+                        ("isNull", _) => {
+                            is_null_predicate(param, parent_column_path, length, subsystem)?
+                        }
+                        ("in", _) => length_in(length)?,
+                        ("notIn", _) => !length_in(length)?,
+                        (operator, _) => {
+                            let comparator = numeric_comparator(operator).ok_or_else(|| {
+                                PostgresExecutionError::Validation(
+                                    param.name.clone(),
+                                    format!("Invalid length operator '{operator}'"),
+                                )
+                            })?;
+                            length_comparison(comparator, length)?
+                        }
+                    };
+
+                    Ok(AbstractPredicate::and(acc, new_predicate))
+                })
+        }
+        _ => Err(PostgresExecutionError::Validation(
+            param.name.clone(),
+            "Invalid length parameter".into(),
+        )),
+    }
+}
+
+/// Predicate for an operator on an array field such as `{tags: {overlaps: ["a", "b"]}}`
+fn array_predicate(
+    op_name: &str,
+    param: &PredicateParameter,
+    op_value: &Val,
+    element_type: &PhysicalColumnType,
+    parent_column_path: &Option<PhysicalColumnPath>,
+    subsystem: &PostgresSubsystem,
+) -> Result<AbstractPredicate, PostgresExecutionError> {
+    if op_name == "length" {
+        return array_length_predicate(param, op_value, parent_column_path, subsystem);
+    }
+
+    // `anyEq` takes a single element, the other operators take an array
+    let op_value_type = (op_name == "anyEq").then_some(element_type);
+
+    let (op_key_column, op_value_column) = operands(
+        param,
+        op_value,
+        op_value_type,
+        parent_column_path,
+        subsystem,
+    )?;
+
+    match op_name {
+        "contains" => Ok(AbstractPredicate::ArrayContains(
+            op_key_column,
+            op_value_column,
+        )),
+        "containedBy" => Ok(AbstractPredicate::ArrayContainedBy(
+            op_key_column,
+            op_value_column,
+        )),
+        "overlaps" => Ok(AbstractPredicate::ArrayOverlaps(
+            op_key_column,
+            op_value_column,
+        )),
+        "anyEq" => Ok(AbstractPredicate::ArrayAnyEq(
+            op_key_column,
+            op_value_column,
+        )),
+        _ => Err(PostgresExecutionError::Validation(
+            param.name.clone(),
+            format!("Unknown array operator '{op_name}'"),
+        )),
+    }
+}
+
+/// Predicate for the `isNull` operator of a field or relation (`isNull: null` doesn't filter anything)
+fn is_null_predicate(
+    param: &PredicateParameter,
+    parent_column_path: &Option<PhysicalColumnPath>,
+    is_null: &Val,
+    subsystem: &PostgresSubsystem,
+) -> Result<AbstractPredicate, PostgresExecutionError> {
+    let is_null = match is_null {
+        Val::Bool(is_null) => *is_null,
+        Val::Null => return Ok(AbstractPredicate::True),
+        _ => {
+            return Err(PostgresExecutionError::Validation(
+                param.name.clone(),
+                "The isNull operator requires a boolean value".into(),
+            ))
+        }
+    };
+
+    let link = match &param.column_path_link {
+        Some(link) => link,
+        // The top-level filter (such as `concerts(where: {isNull: true})`) applies to the rows
+        // themselves, which are never null
+        None if is_null => return Ok(AbstractPredicate::False),
+        None => return Ok(AbstractPredicate::True),
+    };
+
+    match link {
+        // The foreign key is in the other table (such as for `User.membership`, where the
+        // `memberships` table refers to the `users` table), so check if a linked row exists
+        ColumnPathLink::Relation(relation) if link.is_one_to_many(&subsystem.database) => {
+            let foreign_column_id = relation.column_pairs[0].foreign_column_id;
+            let link = if is_null {
+                link.clone().negated()
+            } else {
+                link.clone()
+            };
+            let column_path = to_column_path(parent_column_path, &Some(link))
+                .unwrap()
+                .push(ColumnPathLink::Leaf(foreign_column_id));
+
+            Ok(AbstractPredicate::neq(
+                ColumnPath::Physical(column_path),
+                ColumnPath::Null,
+            ))
+        }
+        // The column (or the foreign key columns) are in the self table
+        _ => Ok(link.self_column_ids().into_iter().fold(
+            AbstractPredicate::True,
+            |acc, column_id| {
+                let column_path = ColumnPath::Physical(
+                    to_column_path(parent_column_path, &Some(ColumnPathLink::Leaf(column_id)))
+                        .unwrap(),
+                );
+                let predicate = if is_null {
+                    AbstractPredicate::eq(column_path, ColumnPath::Null)
+                } else {
+                    AbstractPredicate::neq(column_path, ColumnPath::Null)
+                };
+                AbstractPredicate::and(acc, predicate)
+            },
+        )),
+    }
+}
+
 fn operands<'a>(
     param: &'a PredicateParameter,
     op_value: &'a Val,
@@ -403,7 +749,7 @@ fn operands<'a>(
         .column_path_link
         .as_ref()
         .expect("Could not find column path link while forming operands")
-        .self_column_id();
+        .self_column_ids()[0];
     let op_physical_column = op_physical_column_id.get_column(&subsystem.database);
 
     let op_value = literal_column_path(op_value, op_value_type.unwrap_or(&op_physical_column.typ))?;
@@ -432,3 +778,19 @@ pub async fn compute_predicate<'a>(
     .await
     .map(|predicate| predicate.unwrap_or(AbstractPredicate::True))
 }
+
+/// Compute the conjunction of the predicates for the given parameters (such as the parameters for
+/// each field of a composite primary key)
+pub async fn compute_predicates<'a>(
+    params: &'a [PredicateParameter],
+    arguments: &'a Arguments,
+    subsystem: &'a PostgresSubsystem,
+    request_context: &'a RequestContext<'a>,
+) -> Result<AbstractPredicate, PostgresExecutionError> {
+    futures::stream::iter(params.iter().map(Ok::<_, PostgresExecutionError>))
+        .try_fold(AbstractPredicate::True, |acc, param| async {
+            let predicate = compute_predicate(param, arguments, subsystem, request_context).await?;
+            Ok(AbstractPredicate::and(acc, predicate))
+        })
+        .await
+}
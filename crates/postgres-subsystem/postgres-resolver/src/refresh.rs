@@ -0,0 +1,60 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Support for refreshing types with the `@materializedView` annotation through mutations such as
+//! `refreshConcertSummaries`.
+
+use core_plugin_interface::core_resolver::{
+    context::RequestContext, validation::field::ValidatedField,
+};
+use exo_sql::AbstractPredicate;
+use postgres_model::mutation::PostgresMutation;
+
+use crate::{
+    auth_util::check_access, plugin::subsystem_resolver::PostgresSubsystemResolver,
+    postgres_execution_error::PostgresExecutionError, sql_mapper::SQLOperationKind,
+};
+
+/// Re-compute the content of the materialized view of a refresh mutation (the mutation then
+/// selects the refreshed content).
+///
+/// Refreshing replaces the content of the view as a whole, so the update access rule must fully
+/// evaluate to `true`.
+pub(crate) async fn refresh_materialized_view<'a>(
+    mutation: &'a PostgresMutation,
+    field: &'a ValidatedField,
+    request_context: &'a RequestContext<'a>,
+    subsystem_resolver: &'a PostgresSubsystemResolver,
+) -> Result<(), PostgresExecutionError> {
+    let subsystem = &subsystem_resolver.subsystem;
+    let entity_type = mutation.return_type.typ(&subsystem.entity_types);
+
+    let access_predicate = check_access(
+        entity_type,
+        &field.subfields,
+        &SQLOperationKind::Update,
+        subsystem,
+        request_context,
+        None,
+    )
+    .await?;
+
+    if access_predicate != AbstractPredicate::True {
+        return Err(PostgresExecutionError::Authorization);
+    }
+
+    let ctx = request_context.get_base_context();
+    let mut tx = ctx.transaction_holder.try_lock().unwrap();
+
+    subsystem_resolver
+        .executor
+        .refresh_materialized_view(entity_type.table_id, &mut tx, &subsystem.database)
+        .await
+        .map_err(PostgresExecutionError::Postgres)
+}
@@ -0,0 +1,157 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Support for types with the `@softDelete` annotation, whose entities are marked as deleted (by
+//! setting a timestamp field) instead of being removed.
+
+use async_trait::async_trait;
+use chrono::Utc;
+use core_plugin_interface::core_resolver::{context::RequestContext, value::Val};
+use exo_sql::{
+    AbstractPredicate, Column, ColumnPath, ColumnPathLink, PhysicalColumnPath, PhysicalColumnType,
+    SQLParamContainer,
+};
+use postgres_model::{
+    soft_delete::{IncludeDeletedParameter, SoftDelete},
+    subsystem::PostgresSubsystem,
+    types::EntityType,
+};
+
+use crate::{
+    auth_util::check_access,
+    column_path_util::to_column_path,
+    postgres_execution_error::PostgresExecutionError,
+    sql_mapper::{extract_and_map, SQLMapper, SQLOperationKind},
+    util::Arguments,
+};
+
+/// The predicate to leave out soft-deleted entities of a type with the `@softDelete` annotation
+/// (`True` for other types).
+///
+/// With `includeDeleted: true`, the soft-deleted entities that the user may delete (and so
+/// restore) are included as well. A user who may not delete any entity gets an authorization
+/// error.
+pub(crate) async fn soft_delete_predicate<'a>(
+    entity_type: &'a EntityType,
+    include_deleted_param: Option<&'a IncludeDeletedParameter>,
+    arguments: &'a Arguments,
+    subsystem: &'a PostgresSubsystem,
+    request_context: &'a RequestContext<'a>,
+) -> Result<AbstractPredicate, PostgresExecutionError> {
+    let include_deleted = match include_deleted_param {
+        Some(param) => extract_and_map(param, arguments, subsystem, request_context)
+            .await?
+            .unwrap_or(false),
+        None => false,
+    };
+
+    if include_deleted {
+        let delete_access = check_access(
+            entity_type,
+            &[],
+            &SQLOperationKind::Delete,
+            subsystem,
+            request_context,
+            None,
+        )
+        .await?;
+
+        Ok(AbstractPredicate::or(
+            not_deleted_predicate(entity_type),
+            delete_access,
+        ))
+    } else {
+        Ok(not_deleted_predicate(entity_type))
+    }
+}
+
+/// The predicate to leave out soft-deleted entities such as `"concerts"."deleted_at" IS NULL`
+/// (`True` for a type without the `@softDelete` annotation)
+pub(crate) fn not_deleted_predicate(entity_type: &EntityType) -> AbstractPredicate {
+    match &entity_type.soft_delete {
+        Some(soft_delete) => AbstractPredicate::eq(deleted_at_path(soft_delete), ColumnPath::Null),
+        None => AbstractPredicate::True,
+    }
+}
+
+/// The predicate to leave out soft-deleted entities linked through a relation in a `where`
+/// argument such as `{concerts: {title: {eq: "C1"}}}`, so that only the entities that queries
+/// return may match. The link leads from the parent path to the linked table (such as `venues ->
+/// concerts`). It is `True` if the linked type doesn't have the `@softDelete` annotation (or the
+/// link isn't a relation).
+pub(crate) fn not_deleted_linked_predicate(
+    parent_column_path: &Option<PhysicalColumnPath>,
+    link: &Option<ColumnPathLink>,
+    subsystem: &PostgresSubsystem,
+) -> AbstractPredicate {
+    let Some(ColumnPathLink::Relation(relation)) = link else {
+        return AbstractPredicate::True;
+    };
+
+    let soft_delete = subsystem
+        .entity_types
+        .iter()
+        .find(|(_, entity_type)| entity_type.table_id == relation.linked_table_id())
+        .and_then(|(_, entity_type)| entity_type.soft_delete.as_ref());
+
+    match (soft_delete, to_column_path(parent_column_path, link)) {
+        (Some(soft_delete), Some(relation_path)) => AbstractPredicate::eq(
+            ColumnPath::Physical(relation_path.push(ColumnPathLink::Leaf(soft_delete.column_id))),
+            ColumnPath::Null,
+        ),
+        _ => AbstractPredicate::True,
+    }
+}
+
+/// The predicate to select only soft-deleted entities such as `"concerts"."deleted_at" IS NOT
+/// NULL`
+pub(crate) fn deleted_predicate(soft_delete: &SoftDelete) -> AbstractPredicate {
+    AbstractPredicate::neq(deleted_at_path(soft_delete), ColumnPath::Null)
+}
+
+/// The current time to mark an entity as deleted (matching the type of the timestamp column)
+pub(crate) fn deletion_time(soft_delete: &SoftDelete, subsystem: &PostgresSubsystem) -> Column {
+    let now = Utc::now();
+
+    let param = match &soft_delete.column_id.get_column(&subsystem.database).typ {
+        PhysicalColumnType::Timestamp { timezone: true, .. } => {
+            SQLParamContainer::timestamp_utc(now)
+        }
+        _ => SQLParamContainer::timestamp(now.naive_utc()),
+    };
+
+    Column::Param(param)
+}
+
+fn deleted_at_path(soft_delete: &SoftDelete) -> ColumnPath {
+    ColumnPath::Physical(PhysicalColumnPath::leaf(soft_delete.column_id))
+}
+
+#[async_trait]
+impl<'a> SQLMapper<'a, bool> for &IncludeDeletedParameter {
+    async fn to_sql(
+        self,
+        argument: &'a Val,
+        _subsystem: &'a PostgresSubsystem,
+        _request_context: &'a RequestContext<'a>,
+    ) -> Result<bool, PostgresExecutionError> {
+        match argument {
+            Val::Bool(include_deleted) => Ok(*include_deleted),
+            Val::Null => Ok(false),
+            _ => Err(PostgresExecutionError::Validation(
+                self.name.clone(),
+                "Expected a boolean value".into(),
+            )),
+        }
+    }
+
+    fn param_name(&self) -> &str {
+        &self.name
+    }
+}
@@ -14,19 +14,21 @@ use core_plugin_interface::core_resolver::context::RequestContext;
 use core_plugin_interface::core_resolver::value::Val;
 use exo_sql::{
     AbstractDelete, AbstractInsert, AbstractPredicate, AbstractSelect, AbstractUpdate, Column,
-    ColumnId, ColumnPath, ManyToOne, NestedAbstractDelete, NestedAbstractInsert,
-    NestedAbstractInsertSet, NestedAbstractUpdate, OneToMany, PhysicalColumnPath, Selection,
+    ColumnId, ColumnPath, InsertionElement, InsertionRow, ManyToMany, ManyToOne,
+    NestedAbstractDelete, NestedAbstractInsert, NestedAbstractInsertSet, NestedAbstractUpdate,
+    OneToMany, PhysicalColumnPath, Selection,
 };
 use futures::StreamExt;
 use postgres_model::{
     mutation::DataParameter,
-    relation::{ManyToOneRelation, OneToManyRelation, PostgresRelation},
+    relation::{ManyToManyRelation, ManyToOneRelation, OneToManyRelation, PostgresRelation},
     subsystem::PostgresSubsystem,
-    types::{base_type, EntityType, MutationType, PostgresType, TypeIndex},
+    types::{base_type, EntityType, MutationType, PostgresField, PostgresType, TypeIndex},
 };
 
 use crate::{
     auth_util::check_access,
+    create_data_param_mapper::map_many_to_many_reference,
     sql_mapper::{SQLMapper, SQLOperationKind},
     util::{get_argument_field, return_type_info},
 };
@@ -74,51 +76,74 @@ impl<'a> SQLMapper<'a, AbstractUpdate> for UpdateOperation<'a> {
     }
 }
 
+/// The columns to set for an update. For a type with a `@version` field, this includes
+/// incrementing the version.
 fn compute_update_columns<'a>(
     data_type: &'a MutationType,
     argument: &'a Val,
     subsystem: &'a PostgresSubsystem,
 ) -> Vec<(ColumnId, Column)> {
+    let version_update = subsystem.entity_types[data_type.entity_id]
+        .version
+        .as_ref()
+        .map(|version| (version.column_id, Column::Increment(version.column_id)));
+
     data_type
         .fields
         .iter()
         .flat_map(|field| match &field.relation {
             PostgresRelation::Pk { column_id } | PostgresRelation::Scalar { column_id } => {
-                get_argument_field(argument, &field.name).map(|argument_value| {
-                    let column = column_id.get_column(&subsystem.database);
-                    let value_column = cast::literal_column(argument_value, column);
-                    (*column_id, value_column.unwrap())
-                })
+                get_argument_field(argument, &field.name)
+                    .map(|argument_value| {
+                        let column = column_id.get_column(&subsystem.database);
+                        let value_column = cast::literal_column(argument_value, column);
+                        (*column_id, value_column.unwrap())
+                    })
+                    .into_iter()
+                    .collect()
             }
             PostgresRelation::ManyToOne(ManyToOneRelation {
-                foreign_pk_field_id,
+                foreign_pk_field_ids,
                 relation_id,
                 ..
             }) => {
-                let ManyToOne { self_column_id, .. } = relation_id.deref(&subsystem.database);
-
-                let self_column = self_column_id.get_column(&subsystem.database);
-                let foreign_type_pk_field_name =
-                    &foreign_pk_field_id.resolve(&subsystem.entity_types).name;
+                let ManyToOne { column_pairs, .. } = relation_id.deref(&subsystem.database);
 
                 match get_argument_field(argument, &field.name) {
-                    Some(Val::Null) => Some((self_column_id, Column::Null)), // `{..., foreign_field: null}` means set the column to null
+                    // `{..., foreign_field: null}` means set the columns to null
+                    Some(Val::Null) => column_pairs
+                        .iter()
+                        .map(|pair| (pair.self_column_id, Column::Null))
+                        .collect(),
                     Some(argument_value) => {
-                        // `{..., foreign_field: { id: 1 }}` means set the column to the id of the nested object
-                        match get_argument_field(argument_value, foreign_type_pk_field_name) {
-                            Some(foreign_type_pk_arg) => {
-                                let value_column =
-                                    cast::literal_column(foreign_type_pk_arg, self_column);
-                                Some((self_column_id, value_column.unwrap()))
-                            }
-                            None => unreachable!("Expected pk argument"), // Validation should have caught this
-                        }
+                        // `{..., foreign_field: { id: 1 }}` means set the columns to the primary key of the nested object
+                        column_pairs
+                            .iter()
+                            .zip(foreign_pk_field_ids)
+                            .map(|(pair, foreign_pk_field_id)| {
+                                let self_column =
+                                    pair.self_column_id.get_column(&subsystem.database);
+                                let foreign_type_pk_field_name =
+                                    &foreign_pk_field_id.resolve(&subsystem.entity_types).name;
+
+                                match get_argument_field(argument_value, foreign_type_pk_field_name)
+                                {
+                                    Some(foreign_type_pk_arg) => {
+                                        let value_column =
+                                            cast::literal_column(foreign_type_pk_arg, self_column);
+                                        (pair.self_column_id, value_column.unwrap())
+                                    }
+                                    None => unreachable!("Expected pk argument"), // Validation should have caught this
+                                }
+                            })
+                            .collect()
                     }
-                    None => None,
+                    None => vec![],
                 }
             }
-            PostgresRelation::OneToMany { .. } => None,
+            PostgresRelation::OneToMany { .. } | PostgresRelation::ManyToMany { .. } => vec![],
         })
+        .chain(version_update)
         .collect()
 }
 
@@ -191,6 +216,27 @@ async fn compute_nested_ops<'a>(
                     .await?,
                 );
             }
+        } else if let PostgresRelation::ManyToMany(many_to_many_relation) = &field.relation {
+            if let Some(argument) = get_argument_field(arg, &field.name) {
+                let ManyToMany { self_link, .. } =
+                    many_to_many_relation.relation_id.deref(&subsystem.database);
+
+                nested_insert_sets.push(compute_many_to_many_connect(
+                    field,
+                    argument,
+                    many_to_many_relation,
+                    &self_link,
+                    subsystem,
+                )?);
+
+                nested_deletes.extend(compute_many_to_many_disconnect(
+                    field,
+                    argument,
+                    many_to_many_relation,
+                    &self_link,
+                    subsystem,
+                )?);
+            }
         }
     }
 
@@ -292,7 +338,7 @@ async fn compute_nested_update_object_arg<'a>(
     let predicate = AbstractPredicate::and(arg_predicate, access_predicate);
 
     Ok(NestedAbstractUpdate {
-        nesting_relation: *nesting_relation,
+        nesting_relation: nesting_relation.clone(),
         update: AbstractUpdate {
             table_id,
             predicate,
@@ -338,7 +384,7 @@ async fn compute_nested_inserts<'a>(
         .await?;
 
         Ok(NestedAbstractInsert {
-            relation_column_id: nesting_relation.foreign_column_id,
+            relation_column_ids: nesting_relation.foreign_column_ids(),
             insert: AbstractInsert {
                 table_id,
                 rows,
@@ -350,6 +396,7 @@ async fn compute_nested_inserts<'a>(
                     offset: None,
                     limit: None,
                 },
+                on_conflict: None,
             },
         })
     }
@@ -502,7 +549,7 @@ async fn compute_nested_delete_object_arg<'a>(
     let table_id = subsystem.entity_types[field_mutation_type.entity_id].table_id;
 
     Ok(NestedAbstractDelete {
-        nesting_relation: *nesting_relation,
+        nesting_relation: nesting_relation.clone(),
         delete: AbstractDelete {
             table_id,
             predicate,
@@ -518,6 +565,107 @@ async fn compute_nested_delete_object_arg<'a>(
     })
 }
 
+// Looks for the "connect" field in the argument. If it exists, compute the rows to insert into the linking table.
+fn compute_many_to_many_connect(
+    field: &PostgresField<MutationType>,
+    argument: &Val,
+    many_to_many_relation: &ManyToManyRelation,
+    nesting_relation: &OneToMany,
+    subsystem: &PostgresSubsystem,
+) -> Result<NestedAbstractInsertSet, PostgresExecutionError> {
+    let linking_table_id = nesting_relation.foreign_table_id();
+
+    let inserts = many_to_many_references(argument, "connect")
+        .into_iter()
+        .map(|reference| {
+            let elems =
+                map_many_to_many_reference(field, reference, many_to_many_relation, subsystem)?
+                    .into_iter()
+                    .map(InsertionElement::SelfInsert)
+                    .collect();
+
+            Ok(NestedAbstractInsert {
+                relation_column_ids: nesting_relation.foreign_column_ids(),
+                insert: AbstractInsert {
+                    table_id: linking_table_id,
+                    rows: vec![InsertionRow { elems }],
+                    selection: AbstractSelect {
+                        table_id: linking_table_id,
+                        selection: Selection::Seq(vec![]),
+                        predicate: AbstractPredicate::True,
+                        order_by: None,
+                        offset: None,
+                        limit: None,
+                    },
+                    on_conflict: None,
+                },
+            })
+        })
+        .collect::<Result<Vec<_>, PostgresExecutionError>>()?;
+
+    Ok(NestedAbstractInsertSet::new(
+        inserts,
+        AbstractPredicate::True,
+    ))
+}
+
+// Looks for the "disconnect" field in the argument. If it exists, compute the deletion of the matching rows in the linking table.
+fn compute_many_to_many_disconnect(
+    field: &PostgresField<MutationType>,
+    argument: &Val,
+    many_to_many_relation: &ManyToManyRelation,
+    nesting_relation: &OneToMany,
+    subsystem: &PostgresSubsystem,
+) -> Result<Vec<NestedAbstractDelete>, PostgresExecutionError> {
+    let linking_table_id = nesting_relation.foreign_table_id();
+
+    many_to_many_references(argument, "disconnect")
+        .into_iter()
+        .map(|reference| {
+            let predicate =
+                map_many_to_many_reference(field, reference, many_to_many_relation, subsystem)?
+                    .into_iter()
+                    .fold(AbstractPredicate::True, |acc, pair| {
+                        let value = match pair.value {
+                            Column::Param(value) => ColumnPath::Param(value),
+                            _ => panic!("Expected literal"),
+                        };
+                        AbstractPredicate::and(
+                            acc,
+                            AbstractPredicate::eq(
+                                ColumnPath::Physical(PhysicalColumnPath::leaf(pair.column)),
+                                value,
+                            ),
+                        )
+                    });
+
+            Ok(NestedAbstractDelete {
+                nesting_relation: nesting_relation.clone(),
+                delete: AbstractDelete {
+                    table_id: linking_table_id,
+                    predicate,
+                    selection: AbstractSelect {
+                        table_id: linking_table_id,
+                        selection: Selection::Seq(vec![]),
+                        predicate: AbstractPredicate::True,
+                        order_by: None,
+                        offset: None,
+                        limit: None,
+                    },
+                },
+            })
+        })
+        .collect()
+}
+
+fn many_to_many_references<'a>(argument: &'a Val, arg_name: &str) -> Vec<&'a Val> {
+    match get_argument_field(argument, arg_name) {
+        Some(Val::List(references)) => references.iter().collect(),
+        Some(Val::Null) | None => vec![],
+        Some(reference) => vec![reference],
+    }
+}
+
 fn extract_argument<'a>(
     argument: &'a Val,
     arg_type: &'a MutationType,
@@ -0,0 +1,126 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Support for upsert mutations such as `upsertConcert`, which insert an entity or update the
+//! existing one with the same primary key (or unique fields).
+
+use core_plugin_interface::core_resolver::{
+    context::RequestContext, validation::field::ValidatedField, QueryResponse,
+};
+use exo_sql::{
+    AbstractInsert, AbstractOperation, AbstractPredicate, AbstractSelect, AliasedSelectionElement,
+    Column, ColumnPath, InsertionElement, InsertionRow, Limit, OnConflictUpdate,
+    PhysicalColumnPath, Selection, SelectionCardinality, SelectionElement,
+};
+use postgres_model::mutation::PostgresMutation;
+use serde_json::Value;
+
+use crate::{
+    abstract_operation_resolver::resolve_operation, operation_resolver::OperationResolver,
+    plugin::subsystem_resolver::PostgresSubsystemResolver,
+    postgres_execution_error::PostgresExecutionError,
+};
+
+/// Resolve an upsert mutation.
+///
+/// The upsert updates a conflicting entity only if it satisfies the update access rule (through
+/// `ON CONFLICT ... DO UPDATE ... WHERE`), and otherwise silently skips it. To report that as an
+/// authorization error (as an update mutation does), we first check for conflicting entities
+/// that the user may not update.
+pub(crate) async fn resolve_upsert<'a>(
+    mutation: &'a PostgresMutation,
+    field: &'a ValidatedField,
+    request_context: &'a RequestContext<'a>,
+    subsystem_resolver: &'a PostgresSubsystemResolver,
+) -> Result<QueryResponse, PostgresExecutionError> {
+    let operation = mutation
+        .resolve(field, request_context, &subsystem_resolver.subsystem)
+        .await?;
+
+    if let AbstractOperation::Insert(AbstractInsert {
+        table_id,
+        rows,
+        on_conflict: Some(on_conflict),
+        ..
+    }) = &operation
+    {
+        if on_conflict.predicate != AbstractPredicate::True {
+            let select = AbstractSelect {
+                table_id: *table_id,
+                selection: Selection::Json(
+                    vec![AliasedSelectionElement::new(
+                        "conflict".to_string(),
+                        SelectionElement::Constant("true".to_string()),
+                    )],
+                    SelectionCardinality::Many,
+                ),
+                predicate: AbstractPredicate::and(
+                    conflict_predicate(rows, on_conflict),
+                    !on_conflict.predicate.clone(),
+                ),
+                order_by: None,
+                offset: None,
+                limit: Some(Limit(1)),
+            };
+
+            let conflicts = resolve_operation(
+                &AbstractOperation::Select(select),
+                subsystem_resolver,
+                request_context,
+            )
+            .await?;
+
+            let conflicts = conflicts.body.to_json().map_err(|e| {
+                PostgresExecutionError::Generic(format!("Invalid upsert conflict result: {e}"))
+            })?;
+
+            if matches!(conflicts, Value::Array(conflicts) if !conflicts.is_empty()) {
+                return Err(PostgresExecutionError::Authorization);
+            }
+        }
+    }
+
+    resolve_operation(&operation, subsystem_resolver, request_context).await
+}
+
+/// The predicate to select the existing rows that conflict with any of the rows to be inserted
+/// such as `("concerts"."id" = $1) OR ("concerts"."id" = $2)`.
+///
+/// A row without a value (or with a null value) for a conflict column can't conflict.
+fn conflict_predicate(rows: &[InsertionRow], on_conflict: &OnConflictUpdate) -> AbstractPredicate {
+    rows.iter()
+        .filter_map(|row| {
+            on_conflict
+                .conflict_columns
+                .iter()
+                .map(|conflict_column| {
+                    let value = row.elems.iter().find_map(|elem| match elem {
+                        InsertionElement::SelfInsert(pair) if pair.column == *conflict_column => {
+                            match &pair.value {
+                                Column::Param(param) => Some(ColumnPath::Param(param.clone())),
+                                _ => None,
+                            }
+                        }
+                        _ => None,
+                    })?;
+
+                    Some(AbstractPredicate::eq(
+                        ColumnPath::Physical(PhysicalColumnPath::leaf(*conflict_column)),
+                        value,
+                    ))
+                })
+                .collect::<Option<Vec<_>>>()
+        })
+        .map(|column_predicates| {
+            column_predicates
+                .into_iter()
+                .fold(AbstractPredicate::True, AbstractPredicate::and)
+        })
+        .fold(AbstractPredicate::False, AbstractPredicate::or)
+}
@@ -13,7 +13,7 @@ use postgres_model::types::EntityType;
 
 use core_plugin_interface::core_model::types::OperationReturnType;
 use core_plugin_interface::core_resolver::value::Val;
-use exo_sql::TableId;
+use exo_sql::{PhysicalColumnPath, PhysicalColumnType, SQLParamContainer, TableId, TextSearchMode};
 use postgres_model::{
     query::{CollectionQuery, PkQuery},
     subsystem::PostgresSubsystem,
@@ -65,6 +65,60 @@ pub(super) fn to_pg_vector(
     Ok(vec_value)
 }
 
+/// Map a text search argument such as `{query: "rust async", mode: WEBSEARCH}` for the given
+/// (`tsvector`) column to the query text, the text search configuration (such as "english"), and
+/// the mode.
+pub(super) fn to_text_search(
+    value: &Val,
+    column_path: &PhysicalColumnPath,
+    parameter_name: &str,
+    subsystem: &PostgresSubsystem,
+) -> Result<(SQLParamContainer, String, TextSearchMode), PostgresExecutionError> {
+    let query = match get_argument_field(value, "query") {
+        Some(Val::String(query)) => query.clone(),
+        _ => {
+            return Err(PostgresExecutionError::Validation(
+                parameter_name.into(),
+                "Invalid text search parameter: query must be a string".into(),
+            ))
+        }
+    };
+
+    let mode = match get_argument_field(value, "mode") {
+        None | Some(Val::Null) => TextSearchMode::default(),
+        // A string is needed when processing values from variables (that don't get mapped to the Enum type)
+        Some(Val::Enum(mode)) | Some(Val::String(mode)) => match mode.as_str() {
+            "PLAIN" => TextSearchMode::Plain,
+            "WEBSEARCH" => TextSearchMode::WebSearch,
+            _ => {
+                return Err(PostgresExecutionError::Validation(
+                    parameter_name.into(),
+                    format!("Invalid text search mode {mode}"),
+                ))
+            }
+        },
+        Some(_) => {
+            return Err(PostgresExecutionError::Validation(
+                parameter_name.into(),
+                "Invalid text search parameter: mode must be PLAIN or WEBSEARCH".into(),
+            ))
+        }
+    };
+
+    let column = column_path.leaf_column().get_column(&subsystem.database);
+    let language = match &column.typ {
+        PhysicalColumnType::TextSearchVector { language, .. } => language.clone(),
+        _ => {
+            return Err(PostgresExecutionError::Generic(format!(
+                "Column {} is not a text search column",
+                column.name
+            )))
+        }
+    };
+
+    Ok((SQLParamContainer::string(query), language, mode))
+}
+
 ///
 /// # Returns
 /// - A (table associated with the return type, pk query, collection query) tuple.
@@ -0,0 +1,163 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Support for types with a `@version` field, whose updates require the version last read (to
+//! avoid overwriting a concurrent change).
+
+use core_plugin_interface::core_resolver::{
+    context::RequestContext, validation::field::ValidatedField, QueryResponse,
+};
+use exo_sql::{
+    AbstractOperation, AbstractPredicate, AbstractSelect, AliasedSelectionElement, ColumnPath,
+    PhysicalColumnPath, Selection, SelectionCardinality, SelectionElement,
+};
+use postgres_model::{
+    mutation::{PostgresMutation, PostgresMutationParameters},
+    subsystem::PostgresSubsystem,
+    types::EntityType,
+    version::VersionParameter,
+};
+use serde_json::Value;
+
+use crate::{
+    abstract_operation_resolver::resolve_operation,
+    auth_util::check_access,
+    cast::literal_column_path,
+    operation_resolver::OperationResolver,
+    plugin::subsystem_resolver::PostgresSubsystemResolver,
+    postgres_execution_error::PostgresExecutionError,
+    predicate_mapper::compute_predicates,
+    soft_delete::not_deleted_predicate,
+    sql_mapper::SQLOperationKind,
+    util::{find_arg, Arguments},
+};
+
+/// The predicate to update an entity only if it still has the expected version such as
+/// `"concerts"."version" = $1` (`True` for a type without a `@version` field)
+pub(crate) fn version_predicate(
+    entity_type: &EntityType,
+    version_param: Option<&VersionParameter>,
+    arguments: &Arguments,
+    subsystem: &PostgresSubsystem,
+) -> Result<AbstractPredicate, PostgresExecutionError> {
+    let (version, version_param) = match (&entity_type.version, version_param) {
+        (Some(version), Some(version_param)) => (version, version_param),
+        _ => return Ok(AbstractPredicate::True),
+    };
+
+    let expected_version = find_arg(arguments, &version_param.name)
+        .ok_or_else(|| PostgresExecutionError::MissingArgument(version_param.name.clone()))?;
+
+    let version_column = version.column_id.get_column(&subsystem.database);
+
+    Ok(AbstractPredicate::eq(
+        ColumnPath::Physical(PhysicalColumnPath::leaf(version.column_id)),
+        literal_column_path(expected_version, &version_column.typ)?,
+    ))
+}
+
+/// Resolve an update of a type with a `@version` field.
+///
+/// An update that matches no entity returns `null`, which may be because no (accessible) entity
+/// matches the arguments or because the expected version is stale. To tell these apart, we check
+/// for an entity matching the arguments (ignoring the version) and report a conflict if one exists.
+pub(crate) async fn resolve_versioned_update<'a>(
+    mutation: &'a PostgresMutation,
+    field: &'a ValidatedField,
+    request_context: &'a RequestContext<'a>,
+    subsystem_resolver: &'a PostgresSubsystemResolver,
+) -> Result<QueryResponse, PostgresExecutionError> {
+    let subsystem = &subsystem_resolver.subsystem;
+
+    let operation = mutation.resolve(field, request_context, subsystem).await?;
+    let response = resolve_operation(&operation, subsystem_resolver, request_context).await?;
+
+    if !is_null(&response)? {
+        return Ok(response);
+    }
+
+    let (data_param, predicate_params) = match &mutation.parameters {
+        PostgresMutationParameters::Update {
+            data_param,
+            predicate_params,
+            ..
+        } => (data_param, predicate_params),
+        _ => return Ok(response),
+    };
+
+    let entity_type = mutation.return_type.typ(&subsystem.entity_types);
+    let version = match &entity_type.version {
+        Some(version) => version,
+        None => return Ok(response),
+    };
+
+    let access_predicate = check_access(
+        entity_type,
+        &[],
+        &SQLOperationKind::Update,
+        subsystem,
+        request_context,
+        find_arg(&field.arguments, &data_param.name),
+    )
+    .await?;
+    let arg_predicate = compute_predicates(
+        predicate_params,
+        &field.arguments,
+        subsystem,
+        request_context,
+    )
+    .await?;
+
+    let select = AbstractSelect {
+        table_id: entity_type.table_id,
+        selection: Selection::Json(
+            vec![AliasedSelectionElement::new(
+                version.field_name.clone(),
+                SelectionElement::Physical(version.column_id),
+            )],
+            SelectionCardinality::One,
+        ),
+        predicate: [
+            access_predicate,
+            arg_predicate,
+            not_deleted_predicate(entity_type),
+        ]
+        .into_iter()
+        .reduce(AbstractPredicate::and)
+        .unwrap(),
+        order_by: None,
+        offset: None,
+        limit: None,
+    };
+
+    let existing = resolve_operation(
+        &AbstractOperation::Select(select),
+        subsystem_resolver,
+        request_context,
+    )
+    .await?;
+
+    if is_null(&existing)? {
+        Ok(response)
+    } else {
+        Err(PostgresExecutionError::Conflict(format!(
+            "{} has been updated since the expected version",
+            entity_type.name
+        )))
+    }
+}
+
+fn is_null(response: &QueryResponse) -> Result<bool, PostgresExecutionError> {
+    let value = response
+        .body
+        .to_json()
+        .map_err(|e| PostgresExecutionError::Generic(format!("Invalid update result: {e}")))?;
+
+    Ok(value == Value::Null)
+}
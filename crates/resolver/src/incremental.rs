@@ -0,0 +1,238 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Incremental delivery of `@defer` and `@stream` selections as `multipart/mixed` responses
+//! (following the [incremental delivery](https://github.com/graphql/graphql-over-http/blob/main/rfcs/IncrementalDelivery.md)
+//! RFC).
+//!
+//! The immediate fields are resolved before the response starts (so that their headers may be
+//! included), and each deferred fragment is resolved (in its own transaction) while the response
+//! is being streamed.
+
+use std::sync::Arc;
+
+use async_graphql_parser::types::OperationType;
+use async_stream::try_stream;
+use bytes::Bytes;
+use common::http::RequestHead;
+use core_resolver::system_resolver::{FieldResponses, SystemResolver};
+use core_resolver::validation::operation::{StreamedField, ValidatedOperation};
+use core_resolver::{GraphQLError, QueryResponseBody};
+use futures::Stream;
+use serde_json::{json, Map, Value};
+
+use crate::root_resolver::resolve_operation;
+
+pub(crate) const MULTIPART_CONTENT_TYPE: &str =
+    "multipart/mixed; boundary=\"-\"; deferSpec=20220824";
+
+const PART_HEADER: &[u8] = b"\r\n---\r\nContent-Type: application/json; charset=utf-8\r\n\r\n";
+const TERMINATOR: &[u8] = b"\r\n-----\r\n";
+
+/// Should the operation be delivered incrementally?
+///
+/// Only queries with `@defer` or `@stream` root selections qualify, and only if the client accepts
+/// a `multipart/mixed` response (otherwise, everything is delivered in a single response).
+pub(crate) fn is_incremental(
+    operation: &ValidatedOperation,
+    request_head: &(dyn RequestHead + Send + Sync),
+) -> bool {
+    operation.typ == OperationType::Query
+        && !(operation.deferred.is_empty() && operation.streamed.is_empty())
+        && request_head
+            .get_headers("accept")
+            .iter()
+            .any(|accept| accept.contains("multipart/mixed"))
+}
+
+/// A deferred fragment along with the operation to resolve its fields
+pub(crate) struct DeferredOperation {
+    label: Option<String>,
+    operation: ValidatedOperation,
+}
+
+/// Split the operation into the fields to be resolved immediately and the deferred fragments
+pub(crate) fn split_deferred(
+    mut operation: ValidatedOperation,
+) -> (ValidatedOperation, Vec<DeferredOperation>) {
+    let deferred = std::mem::take(&mut operation.deferred)
+        .into_iter()
+        .map(|deferred_fields| {
+            let (fields, remaining) = std::mem::take(&mut operation.fields)
+                .into_iter()
+                .partition(|field| deferred_fields.output_names.contains(&field.output_name()));
+            operation.fields = remaining;
+
+            DeferredOperation {
+                label: deferred_fields.label,
+                operation: ValidatedOperation {
+                    name: operation.name.clone(),
+                    typ: operation.typ,
+                    fields,
+                    deferred: vec![],
+                    streamed: vec![],
+                    cost: 0,
+                    cache_policy: None,
+                },
+            }
+        })
+        .collect();
+
+    (operation, deferred)
+}
+
+/// Stream the initial response, followed by the remaining items of each streamed field and the
+/// response to each deferred fragment
+pub(crate) fn incremental_stream(
+    initial_responses: FieldResponses,
+    streamed: Vec<StreamedField>,
+    deferred: Vec<DeferredOperation>,
+    extensions: Option<Value>,
+    request_head: Box<dyn RequestHead + Send + Sync>,
+    system_resolver: Arc<SystemResolver>,
+) -> impl Stream<Item = Result<Bytes, std::io::Error>> {
+    try_stream! {
+        let (mut data, errors) = collect_responses(initial_responses);
+        let streamed_items = split_streamed_items(&mut data, &streamed);
+
+        let remaining = streamed_items.len() + deferred.len();
+        yield part(initial_payload(data, errors, extensions, remaining > 0))?;
+
+        let mut remaining = remaining;
+        for (field, start_index, items) in streamed_items {
+            remaining -= 1;
+            yield part(streamed_payload(field, start_index, items, remaining > 0))?;
+        }
+
+        for DeferredOperation { label, operation } in deferred {
+            remaining -= 1;
+            let response = resolve_operation(operation, request_head.as_ref(), &system_resolver).await;
+            let payload = match response {
+                Ok(responses) => {
+                    let (data, errors) = collect_responses(responses);
+                    deferred_payload(label, Value::Object(data), errors, remaining > 0)
+                }
+                Err(err) => {
+                    deferred_payload(label, Value::Null, vec![GraphQLError::from(&err)], remaining > 0)
+                }
+            };
+            yield part(payload)?;
+        }
+
+        yield Bytes::from_static(TERMINATOR);
+    }
+}
+
+fn part(payload: Value) -> Result<Bytes, std::io::Error> {
+    let mut bytes = PART_HEADER.to_vec();
+    serde_json::to_writer(&mut bytes, &payload)?;
+    Ok(Bytes::from(bytes))
+}
+
+/// Collect the field responses into the data object and the errors of the failed fields
+fn collect_responses(responses: FieldResponses) -> (Map<String, Value>, Vec<GraphQLError>) {
+    let mut errors = vec![];
+
+    let data = responses
+        .into_iter()
+        .map(|(name, response)| {
+            let value = match response.map(|response| response.body) {
+                Ok(QueryResponseBody::Json(value)) => value,
+                Ok(QueryResponseBody::Raw(Some(value))) => {
+                    serde_json::from_str(&value).unwrap_or(Value::Null)
+                }
+                Ok(QueryResponseBody::Raw(None)) => Value::Null,
+                Err(err) => {
+                    errors.push(GraphQLError::from(&err));
+                    Value::Null
+                }
+            };
+            (name, value)
+        })
+        .collect();
+
+    (data, errors)
+}
+
+/// Truncate each streamed field to its initial count, returning the remaining items (fields
+/// without any remaining items, including those that failed to resolve, are left out).
+///
+/// Each streamed field is resolved in full along with the initial response (subsystems resolve a
+/// root field as a unit), so only the delivery of its items is incremental (which is why the schema
+/// doesn't advertise `@stream`). Validation honors `@stream` only on root fields (ignoring it
+/// elsewhere), where this split applies.
+fn split_streamed_items<'a>(
+    data: &mut Map<String, Value>,
+    streamed: &'a [StreamedField],
+) -> Vec<(&'a StreamedField, usize, Vec<Value>)> {
+    streamed
+        .iter()
+        .filter_map(|field| match data.get_mut(&field.output_name) {
+            Some(Value::Array(items)) if items.len() > field.initial_count => Some((
+                field,
+                field.initial_count,
+                items.split_off(field.initial_count),
+            )),
+            _ => None,
+        })
+        .collect()
+}
+
+fn initial_payload(
+    data: Map<String, Value>,
+    errors: Vec<GraphQLError>,
+    extensions: Option<Value>,
+    has_next: bool,
+) -> Value {
+    let mut payload = Map::new();
+    if !errors.is_empty() {
+        payload.insert("errors".into(), json!(errors));
+    }
+    payload.insert("data".into(), Value::Object(data));
+    if let Some(extensions) = extensions {
+        payload.insert("extensions".into(), extensions);
+    }
+    payload.insert("hasNext".into(), Value::Bool(has_next));
+    Value::Object(payload)
+}
+
+fn streamed_payload(
+    field: &StreamedField,
+    start_index: usize,
+    items: Vec<Value>,
+    has_next: bool,
+) -> Value {
+    let mut incremental = Map::new();
+    incremental.insert("items".into(), Value::Array(items));
+    incremental.insert("path".into(), json!([field.output_name, start_index]));
+    if let Some(label) = &field.label {
+        incremental.insert("label".into(), Value::String(label.clone()));
+    }
+
+    json!({ "incremental": [incremental], "hasNext": has_next })
+}
+
+fn deferred_payload(
+    label: Option<String>,
+    data: Value,
+    errors: Vec<GraphQLError>,
+    has_next: bool,
+) -> Value {
+    let mut incremental = Map::new();
+    incremental.insert("data".into(), data);
+    incremental.insert("path".into(), json!([]));
+    if let Some(label) = label {
+        incremental.insert("label".into(), Value::String(label));
+    }
+    if !errors.is_empty() {
+        incremental.insert("errors".into(), json!(errors));
+    }
+
+    json!({ "incremental": [incremental], "hasNext": has_next })
+}
@@ -12,10 +12,13 @@
 ///
 /// The `resolve` function is responsible for doing the work, using information
 /// extracted from an incoming request, and returning the response as a stream.
+mod incremental;
 mod root_resolver;
+mod subscription_router;
 mod system_loader;
 
 pub use root_resolver::{
     create_system_resolver, create_system_resolver_from_system, resolve_in_memory, GraphQLRouter,
 };
+pub use subscription_router::{SubscriptionMessage, SubscriptionProtocol, SubscriptionRouter};
 pub use system_loader::{StaticLoaders, SystemLoader, SystemLoadingError};
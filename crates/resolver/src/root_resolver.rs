@@ -21,18 +21,28 @@ use common::http::{Headers, RequestHead, RequestPayload, ResponseBody, ResponseP
 use common::router::Router;
 use core_plugin_shared::serializable_system::SerializableSystem;
 use core_plugin_shared::trusted_documents::TrustedDocumentEnforcement;
-use core_resolver::QueryResponse;
+use core_resolver::GraphQLError;
 use http::StatusCode;
 
 use super::system_loader::SystemLoader;
+use crate::incremental::{
+    incremental_stream, is_incremental, split_deferred, MULTIPART_CONTENT_TYPE,
+};
 use ::tracing::instrument;
-use async_graphql_parser::Pos;
+use async_graphql_parser::types::OperationType;
 use async_stream::try_stream;
 use bytes::Bytes;
-use core_resolver::system_resolver::SystemResolver;
+use core_model::cache_policy::{CachePolicy, CacheScope};
+use core_resolver::response_cache::{
+    context_cache_key, response_cache_key, CacheDependencies, CachedResponse,
+};
+use core_resolver::system_resolver::{FieldResponses, SystemResolver};
 use core_resolver::system_resolver::{RequestError, SystemResolutionError};
+use core_resolver::validation::operation::ValidatedOperation;
+use core_resolver::BatchedOperationsPayload;
 pub use core_resolver::OperationsPayload;
 use core_resolver::{context::RequestContext, QueryResponseBody};
+use futures::{Stream, TryStreamExt};
 
 use exo_env::Environment;
 
@@ -44,41 +54,265 @@ pub async fn resolve_in_memory<'a>(
     request: &mut (dyn RequestPayload + Send),
     system_resolver: &SystemResolver,
     trusted_document_enforcement: TrustedDocumentEnforcement,
-) -> Result<Vec<(String, QueryResponse)>, SystemResolutionError> {
+) -> Result<FieldResponses, SystemResolutionError> {
     let body = request.take_body();
-    let request_head = request.get_head();
 
     let operations_payload = OperationsPayload::from_json(body.clone())
         .map_err(|e| SystemResolutionError::RequestError(RequestError::InvalidBodyJson(e)))?;
+
+    resolve_operations_payload(
+        operations_payload,
+        request.get_head(),
+        system_resolver,
+        trusted_document_enforcement,
+        true,
+    )
+    .await
+}
+
+/// Resolve a single operation with its own request context (and thus its own transaction).
+///
+/// Mutations are rejected unless `allow_mutations` is set (for example, when the operation
+/// arrived through an HTTP GET request).
+async fn resolve_operations_payload(
+    operations_payload: OperationsPayload,
+    request_head: &(dyn RequestHead + Send + Sync),
+    system_resolver: &SystemResolver,
+    trusted_document_enforcement: TrustedDocumentEnforcement,
+    allow_mutations: bool,
+) -> Result<FieldResponses, SystemResolutionError> {
+    let operation = validate_operations_payload(
+        operations_payload,
+        system_resolver,
+        trusted_document_enforcement,
+        allow_mutations,
+    )?;
+
+    resolve_operation(operation, request_head, system_resolver).await
+}
+
+fn validate_operations_payload(
+    operations_payload: OperationsPayload,
+    system_resolver: &SystemResolver,
+    trusted_document_enforcement: TrustedDocumentEnforcement,
+    allow_mutations: bool,
+) -> Result<ValidatedOperation, SystemResolutionError> {
+    let operation = system_resolver
+        .validate_operations_payload(operations_payload, trusted_document_enforcement)?;
+
+    if !allow_mutations && operation.typ == OperationType::Mutation {
+        return Err(SystemResolutionError::UnsupportedOperationType(
+            operation.typ,
+        ));
+    }
+
+    Ok(operation)
+}
+
+/// Resolve a validated operation with its own request context (and thus its own transaction)
+pub(crate) async fn resolve_operation(
+    operation: ValidatedOperation,
+    request_head: &(dyn RequestHead + Send + Sync),
+    system_resolver: &SystemResolver,
+) -> Result<FieldResponses, SystemResolutionError> {
     let request_context = RequestContext::new(request_head, vec![], system_resolver);
 
     let response = system_resolver
-        .resolve_operations(
-            operations_payload,
-            &request_context,
-            trusted_document_enforcement,
-        )
+        .resolve_validated_operation(operation, &request_context)
         .await;
 
-    let ctx = request_context.get_base_context();
-    let mut tx_holder = ctx.transaction_holder.try_lock().unwrap();
-
-    tx_holder
-        .finalize(response.is_ok())
+    system_resolver
+        .finalize_transaction(&request_context, response.is_ok())
         .await
-        .map_err(|e| {
-            SystemResolutionError::Generic(format!("Error while finalizing transaction: {e}"))
-        })
         .and(response)
 }
 
+/// The outcome of [resolve_cacheable_operation]
+enum CacheableResolution {
+    /// The response from the response cache
+    Cached(Arc<CachedResponse>),
+    Resolved {
+        response: Result<FieldResponses, SystemResolutionError>,
+        /// Whether the response depends on the request's context (such as the authenticated user)
+        context_dependent: bool,
+        /// Set if the response may be stored in the response cache
+        cache_entry: Option<CacheEntry>,
+    },
+}
+
+/// What we need to store a response in the response cache
+struct CacheEntry {
+    key: String,
+    dependencies: CacheDependencies,
+    /// The invalidation count before resolving the operation (see
+    /// [core_resolver::response_cache::ResponseCache::insert])
+    invalidation_count: u64,
+}
+
+/// Resolve a query with a cache policy with its own request context (and thus its own
+/// transaction).
+///
+/// If the response cache is enabled (`operation_key` is the key for the operation; see
+/// [response_cache_key]), serves the response from the cache if possible. Since the response may
+/// depend on the request's context (for example, through an access rule that uses the
+/// authenticated user), the key for the response includes the values of the context fields that
+/// the operation's responses have used.
+async fn resolve_cacheable_operation(
+    operation: ValidatedOperation,
+    operation_key: Option<String>,
+    request_head: &(dyn RequestHead + Send + Sync),
+    system_resolver: &SystemResolver,
+) -> CacheableResolution {
+    let response_cache = system_resolver.response_cache();
+    let invalidation_count = response_cache.invalidation_count();
+
+    let request_context = RequestContext::new(request_head, vec![], system_resolver);
+
+    if let Some(operation_key) = &operation_key {
+        let context_fields = response_cache.context_fields(operation_key);
+
+        // If a context value can't be extracted (for example, due to an invalid JWT), resolving
+        // the operation will report the error
+        let cached = match request_context.context_field_values(&context_fields).await {
+            Ok(context_values) => {
+                response_cache.get(&context_cache_key(operation_key, &context_values))
+            }
+            Err(_) => None,
+        };
+
+        if let Some(cached) = cached {
+            // Extracting the context values may have used the database (for `@query` contexts)
+            return match system_resolver
+                .finalize_transaction(&request_context, true)
+                .await
+            {
+                Ok(()) => CacheableResolution::Cached(cached),
+                Err(err) => CacheableResolution::Resolved {
+                    response: Err(err),
+                    context_dependent: true,
+                    cache_entry: None,
+                },
+            };
+        }
+    }
+
+    let types = system_resolver
+        .operation_types(&operation)
+        .unwrap_or_default();
+
+    let response = system_resolver
+        .resolve_validated_operation(operation, &request_context)
+        .await;
+
+    let accessed_context_fields = request_context.get_base_context().accessed_context_fields();
+    let context_dependent = !accessed_context_fields.is_empty();
+
+    // Computed before finalizing the transaction, which `@query` contexts may need
+    let key = match (&operation_key, &response) {
+        (Some(operation_key), Ok(_)) => {
+            let context_fields =
+                response_cache.record_context_fields(operation_key, accessed_context_fields);
+
+            request_context
+                .context_field_values(&context_fields)
+                .await
+                .ok()
+                .map(|context_values| context_cache_key(operation_key, &context_values))
+        }
+        _ => None,
+    };
+
+    let response = system_resolver
+        .finalize_transaction(&request_context, response.is_ok())
+        .await
+        .and(response);
+
+    let cache_entry = match key {
+        Some(key) => {
+            let tx_holder = request_context
+                .get_base_context()
+                .transaction_holder
+                .lock()
+                .await;
+
+            Some(CacheEntry {
+                key,
+                dependencies: CacheDependencies {
+                    types,
+                    tables: tx_holder.table_usage().referenced.clone(),
+                },
+                invalidation_count,
+            })
+        }
+        None => None,
+    };
+
+    CacheableResolution::Resolved {
+        response,
+        context_dependent,
+        cache_entry,
+    }
+}
+
+/// Serialize the response to an operation as `{"data": ..., "errors": ..., "extensions": ...}`
+fn response_stream(
+    response: Result<FieldResponses, SystemResolutionError>,
+    extensions: Option<serde_json::Value>,
+) -> impl Stream<Item = Result<Bytes, std::io::Error>> {
+    try_stream! {
+        match response {
+            Ok(parts) => {
+                // Report errors of the failed fields (if any) first (as recommended by the spec)
+                let errors: Vec<_> = parts
+                    .iter()
+                    .filter_map(|(_, part)| part.as_ref().err().map(GraphQLError::from))
+                    .collect();
+
+                if errors.is_empty() {
+                    yield Bytes::from_static(br#"{"data": {"#);
+                } else {
+                    yield Bytes::from_static(br#"{"errors": "#);
+                    yield Bytes::from(serde_json::to_string(&errors)?);
+                    yield Bytes::from_static(br#", "data": {"#);
+                }
+
+                let parts_len = parts.len();
+                for (index, part) in parts.into_iter().enumerate() {
+                    yield Bytes::from_static(b"\"");
+                    yield Bytes::from(part.0);
+                    yield Bytes::from_static(br#"":"#);
+                    match part.1.map(|response| response.body) {
+                        Ok(QueryResponseBody::Json(value)) => yield Bytes::from(value.to_string()),
+                        Ok(QueryResponseBody::Raw(Some(value))) => yield Bytes::from(value),
+                        // A failed field is reported as null (with the error in `errors`)
+                        Ok(QueryResponseBody::Raw(None)) | Err(_) => yield Bytes::from_static(b"null"),
+                    };
+                    if index != parts_len - 1 {
+                        yield Bytes::from_static(b", ");
+                    }
+                };
+                yield Bytes::from_static(b"}");
+                if let Some(extensions) = extensions {
+                    yield Bytes::from_static(br#", "extensions": "#);
+                    yield Bytes::from(extensions.to_string());
+                }
+                yield Bytes::from_static(b"}");
+            },
+            Err(err) => {
+                let errors = serde_json::json!({ "errors": [GraphQLError::from(&err)] });
+                yield Bytes::from(errors.to_string());
+            },
+        }
+    }
+}
+
 pub struct GraphQLRouter {
-    system_resolver: SystemResolver,
+    system_resolver: Arc<SystemResolver>,
     env: Arc<dyn Environment>,
 }
 
 impl GraphQLRouter {
-    pub fn new(system_resolver: SystemResolver, env: Arc<dyn Environment>) -> Self {
+    pub fn new(system_resolver: Arc<SystemResolver>, env: Arc<dyn Environment>) -> Self {
         Self {
             system_resolver,
             env,
@@ -87,7 +321,63 @@ impl GraphQLRouter {
 
     fn suitable(&self, request_head: &(dyn RequestHead + Sync)) -> bool {
         request_head.get_path() == get_graphql_http_path(self.env.as_ref())
-            && request_head.get_method() == http::Method::POST
+            && (request_head.get_method() == http::Method::POST
+                || request_head.get_method() == http::Method::GET)
+    }
+}
+
+impl GraphQLRouter {
+    /// Resolve the immediate fields and respond with a `multipart/mixed` stream that delivers the
+    /// deferred and streamed parts as they become available
+    async fn resolve_incrementally(
+        &self,
+        mut operation: ValidatedOperation,
+        request_head: Box<dyn RequestHead + Send + Sync>,
+    ) -> ResponsePayload {
+        let streamed = std::mem::take(&mut operation.streamed);
+        let extensions = self
+            .system_resolver
+            .query_cost_model()
+            .cost_extensions(operation.cost);
+        let (operation, deferred) = split_deferred(operation);
+
+        let initial_responses =
+            resolve_operation(operation, request_head.as_ref(), &self.system_resolver).await;
+
+        let initial_responses = match initial_responses {
+            Ok(initial_responses) => initial_responses,
+            Err(err) => {
+                let mut headers = Headers::new();
+                headers.insert("content-type".into(), "application/json".into());
+                return ResponsePayload {
+                    body: ResponseBody::Stream(Box::pin(response_stream(Err(err), None))),
+                    headers,
+                    status_code: StatusCode::OK,
+                };
+            }
+        };
+
+        // Headers set by the deferred fields cannot be delivered (the response will have started by then)
+        let mut headers = Headers::from_vec(
+            initial_responses
+                .iter()
+                .flat_map(|(_, qr)| qr.iter().flat_map(|qr| qr.headers.clone()))
+                .collect(),
+        );
+        headers.insert("content-type".into(), MULTIPART_CONTENT_TYPE.into());
+
+        ResponsePayload {
+            body: ResponseBody::Stream(Box::pin(incremental_stream(
+                initial_responses,
+                streamed,
+                deferred,
+                extensions,
+                request_head,
+                self.system_resolver.clone(),
+            ))),
+            headers,
+            status_code: StatusCode::OK,
+        }
     }
 }
 
@@ -97,6 +387,20 @@ impl Router for GraphQLRouter {
     /// of HTTP headers. The JSON may be either the data returned by the query, or a list of errors
     /// if something went wrong.
     ///
+    /// Operations may be sent either through a POST request (with a JSON body containing a single
+    /// operation or an array of operations) or through a GET request (with the operation in the query
+    /// parameters). A GET request may only execute queries. For a batch, each operation is resolved
+    /// independently (in its own transaction) and the response is an array of individual responses.
+    /// A batch with more operations than the configured maximum is rejected as a bad request.
+    ///
+    /// A (non-batched) query with `@defer` or `@stream` root selections is delivered incrementally
+    /// as a `multipart/mixed` response if the client accepts it.
+    ///
+    /// A successful (non-batched) query with a cache policy gets the `Cache-Control` header (with
+    /// the private scope if the response depends on the request's context, such as the
+    /// authenticated user), and its response is served from the response cache (if enabled) until
+    /// the policy's max age elapses or a committed mutation modifies its data.
+    ///
     /// In a typical use case (for example server-actix), the caller will
     /// first call `create_system_resolver_or_exit` to create a [SystemResolver] object, and
     /// then call `resolve` with that object.
@@ -113,116 +417,273 @@ impl Router for GraphQLRouter {
             return None;
         }
 
-        #[cfg(not(target_family = "wasm"))]
-        let is_production = is_production(self.env.as_ref());
-        #[cfg(target_family = "wasm")]
-        let is_production = !playground_request;
-
-        let response = resolve_in_memory(
-            request,
-            &self.system_resolver,
-            if playground_request && !is_production {
-                TrustedDocumentEnforcement::DoNotEnforce
-            } else {
-                TrustedDocumentEnforcement::Enforce
-            },
-        )
-        .await;
+        let trusted_document_enforcement =
+            trusted_document_enforcement(self.env.as_ref(), playground_request);
+
+        let is_get = request.get_head().get_method() == http::Method::GET;
+
+        let payload = if is_get {
+            OperationsPayload::from_query_params(request.get_head().get_query())
+                .map(BatchedOperationsPayload::Single)
+        } else {
+            BatchedOperationsPayload::from_json(request.take_body())
+        };
+
+        let payload = match payload {
+            Ok(payload) => payload,
+            Err(e) => {
+                tracing::error!("Error while resolving request: {:?}", e);
+                return Some(ResponsePayload {
+                    body: ResponseBody::None,
+                    headers: Headers::new(),
+                    status_code: StatusCode::BAD_REQUEST,
+                });
+            }
+        };
+
+        let (batched, payloads) = match payload {
+            BatchedOperationsPayload::Single(payload) => (false, vec![payload]),
+            BatchedOperationsPayload::Batch(payloads) => (true, payloads),
+        };
+
+        // Each operation of a batch is resolved independently, so limit how many a single request
+        // may fan out into
+        let max_batch_size = self.system_resolver.max_batch_size();
+        if payloads.len() > max_batch_size {
+            let error = SystemResolutionError::RequestError(RequestError::BatchTooLarge(
+                payloads.len(),
+                max_batch_size,
+            ));
+
+            let mut headers = Headers::new();
+            headers.insert("content-type".into(), "application/json".into());
 
-        if let Err(SystemResolutionError::RequestError(e)) = response {
-            tracing::error!("Error while resolving request: {:?}", e);
             return Some(ResponsePayload {
-                body: ResponseBody::None,
-                headers: Headers::new(),
+                body: ResponseBody::Stream(Box::pin(response_stream(Err(error), None))),
+                headers,
                 status_code: StatusCode::BAD_REQUEST,
             });
         }
 
-        let mut headers = if let Ok(ref response) = response {
-            Headers::from_vec(
-                response
-                    .iter()
-                    .flat_map(|(_, qr)| qr.headers.clone())
-                    .collect(),
-            )
-        } else {
-            Headers::new()
-        };
+        let mut responses = vec![];
+        let mut extensions = vec![];
+        // The cache policy of a non-batched query (if any), whether its response depends on the
+        // request's context, and what we need to store its response in the response cache (if enabled)
+        let mut cache_policy = None;
+        let mut context_dependent = false;
+        let mut cache_entry = None;
+        for payload in payloads {
+            let cacheable_payload = (!batched
+                && self.system_resolver.response_cache().is_enabled())
+            .then(|| payload.clone());
 
-        headers.insert("content-type".into(), "application/json".into());
+            let operation = validate_operations_payload(
+                payload,
+                &self.system_resolver,
+                trusted_document_enforcement,
+                !is_get,
+            );
 
-        let stream = try_stream! {
-            macro_rules! report_position {
-                ($position:expr) => {
-                    let p: Pos = $position;
+            extensions.push(operation.as_ref().ok().and_then(|operation| {
+                self.system_resolver
+                    .query_cost_model()
+                    .cost_extensions(operation.cost)
+            }));
 
-                    yield Bytes::from_static(br#"{"line": "#);
-                    yield Bytes::from(p.line.to_string());
-                    yield Bytes::from_static(br#", "column": "#);
-                    yield Bytes::from(p.column.to_string());
-                    yield Bytes::from_static(br#"}"#);
-                };
-            }
+            let response = match operation {
+                Ok(operation) if !batched && is_incremental(&operation, request.get_head()) => {
+                    // Deferred fragments are resolved while streaming the response, so we need a
+                    // request head that outlives this request (without which, we deliver everything
+                    // at once)
+                    match request.clone_head() {
+                        Some(request_head) => {
+                            return Some(self.resolve_incrementally(operation, request_head).await);
+                        }
+                        None => {
+                            resolve_operation(operation, request.get_head(), &self.system_resolver)
+                                .await
+                        }
+                    }
+                }
+                Ok(operation) => {
+                    if !batched {
+                        cache_policy = operation.cache_policy;
+                    }
+
+                    match cache_policy {
+                        Some(policy) => {
+                            let operation_key = cacheable_payload.map(|payload| {
+                                response_cache_key(&payload, policy.scope, request.get_head())
+                            });
+
+                            match resolve_cacheable_operation(
+                                operation,
+                                operation_key,
+                                request.get_head(),
+                                &self.system_resolver,
+                            )
+                            .await
+                            {
+                                CacheableResolution::Cached(cached) => {
+                                    let mut headers = Headers::from_vec(cached.headers.clone());
+                                    headers.insert("cache-control".into(), cached.cache_control());
 
-            macro_rules! report_positions {
-                ($positions:expr) => {
-                    let mut first = true;
-                    for p in $positions {
-                        if !first {
-                            yield Bytes::from_static(b", ");
+                                    return Some(ResponsePayload {
+                                        body: ResponseBody::Bytes(cached.body.clone()),
+                                        headers,
+                                        status_code: StatusCode::OK,
+                                    });
+                                }
+                                CacheableResolution::Resolved {
+                                    response,
+                                    context_dependent: resolved_context_dependent,
+                                    cache_entry: resolved_cache_entry,
+                                } => {
+                                    context_dependent = resolved_context_dependent;
+                                    cache_entry = resolved_cache_entry;
+                                    response
+                                }
+                            }
+                        }
+                        None => {
+                            resolve_operation(operation, request.get_head(), &self.system_resolver)
+                                .await
                         }
-                        first = false;
-                        report_position!(p);
                     }
-                };
+                }
+                Err(err) => Err(err),
+            };
+            responses.push(response);
+        }
+
+        let mut headers = Headers::from_vec(
+            responses
+                .iter()
+                .flat_map(|response| response.iter().flatten())
+                .flat_map(|(_, qr)| qr.iter().flat_map(|qr| qr.headers.clone()))
+                .collect(),
+        );
+
+        headers.insert("content-type".into(), "application/json".into());
+
+        // Only a successful response may be cached (an error may be transient)
+        let cache_policy = cache_policy
+            .filter(|_| match &responses[..] {
+                [Ok(fields)] => fields.iter().all(|(_, response)| response.is_ok()),
+                _ => false,
+            })
+            // A response that depends on the request's context must not be shared with other users
+            .map(|cache_policy| CachePolicy {
+                scope: if context_dependent {
+                    CacheScope::Private
+                } else {
+                    cache_policy.scope
+                },
+                ..cache_policy
+            });
+        if let Some(cache_policy) = cache_policy {
+            headers.insert("cache-control".into(), cache_policy.header_value());
+        }
+
+        // As required by the GraphQL over HTTP spec, a mutation over GET is rejected with 405
+        let status_code = match &responses[..] {
+            [Err(SystemResolutionError::UnsupportedOperationType(OperationType::Mutation))]
+                if is_get =>
+            {
+                headers.insert("allow".into(), "POST".into());
+                StatusCode::METHOD_NOT_ALLOWED
             }
+            _ => StatusCode::OK,
+        };
 
-            match response {
-                Ok(parts) => {
-                    let parts_len = parts.len();
-                    yield Bytes::from_static(br#"{"data": {"#);
-                    for (index, part) in parts.into_iter().enumerate() {
-                        yield Bytes::from_static(b"\"");
-                        yield Bytes::from(part.0);
-                        yield Bytes::from_static(br#"":"#);
-                        match part.1.body {
-                            QueryResponseBody::Json(value) => yield Bytes::from(value.to_string()),
-                            QueryResponseBody::Raw(Some(value)) => yield Bytes::from(value),
-                            QueryResponseBody::Raw(None) => yield Bytes::from_static(b"null"),
-                        };
-                        if index != parts_len - 1 {
-                            yield Bytes::from_static(b", ");
+        let body = if batched {
+            ResponseBody::Stream(Box::pin(try_stream! {
+                yield Bytes::from_static(b"[");
+                let responses_len = responses.len();
+                for (index, (response, extensions)) in responses.into_iter().zip(extensions).enumerate() {
+                    for await bytes in response_stream(response, extensions) {
+                        yield bytes?;
+                    }
+                    if index != responses_len - 1 {
+                        yield Bytes::from_static(b", ");
+                    }
+                }
+                yield Bytes::from_static(b"]");
+            }))
+        } else {
+            let stream = response_stream(responses.remove(0), extensions.remove(0));
+
+            match cache_entry.zip(cache_policy) {
+                // Buffer the response to store it in the cache
+                Some((cache_entry, cache_policy)) => {
+                    let body = stream
+                        .try_fold(vec![], |mut body, bytes| async move {
+                            body.extend_from_slice(&bytes);
+                            Ok(body)
+                        })
+                        .await;
+
+                    match body {
+                        Ok(body) => {
+                            self.system_resolver.response_cache().insert(
+                                cache_entry.key,
+                                body.clone(),
+                                // Cookies set for this request must not leak to other clients (and
+                                // the `cache-control` header reflects the remaining max age)
+                                headers
+                                    .clone()
+                                    .into_iter()
+                                    .filter(|(name, _)| {
+                                        name != "set-cookie" && name != "cache-control"
+                                    })
+                                    .collect(),
+                                cache_entry.dependencies,
+                                cache_policy,
+                                cache_entry.invalidation_count,
+                            );
+                            ResponseBody::Bytes(body)
                         }
-                    };
-                    yield Bytes::from_static(b"}}");
-                },
-                Err(err) => {
-                    yield Bytes::from_static(br#"{"errors": [{"message":""#);
-                    yield Bytes::from(
-                        err.user_error_message().to_string()
-                            .replace('\"', "")
-                            .replace('\n', "; ")
-                    );
-                    yield Bytes::from_static(br#"""#);
-                    if let SystemResolutionError::Validation(err) = err {
-                        yield Bytes::from_static(br#", "locations": ["#);
-                        report_positions!(err.positions());
-                        yield Bytes::from_static(br#"]"#);
-                    };
-                    yield Bytes::from_static(br#"}"#);
-                    yield Bytes::from_static(b"]}");
-                },
+                        Err(e) => {
+                            tracing::error!("Error while serializing response: {:?}", e);
+                            return Some(ResponsePayload {
+                                body: ResponseBody::None,
+                                headers: Headers::new(),
+                                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+                            });
+                        }
+                    }
+                }
+                None => ResponseBody::Stream(Box::pin(stream)),
             }
         };
 
         Some(ResponsePayload {
-            body: ResponseBody::Stream(Box::pin(stream)),
+            body,
             headers,
-            status_code: StatusCode::OK,
+            status_code,
         })
     }
 }
 
+/// Determine whether a request may only use trusted documents. Requests from the playground may
+/// use any document (except in production).
+#[cfg_attr(target_family = "wasm", allow(unused_variables))]
+pub(crate) fn trusted_document_enforcement(
+    env: &dyn Environment,
+    playground_request: bool,
+) -> TrustedDocumentEnforcement {
+    #[cfg(not(target_family = "wasm"))]
+    let is_production = is_production(env);
+    #[cfg(target_family = "wasm")]
+    let is_production = !playground_request;
+
+    if playground_request && !is_production {
+        TrustedDocumentEnforcement::DoNotEnforce
+    } else {
+        TrustedDocumentEnforcement::Enforce
+    }
+}
+
 pub async fn create_system_resolver(
     exo_ir_file: &str,
     static_loaders: StaticLoaders,
@@ -0,0 +1,938 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Support for GraphQL subscriptions over WebSocket.
+//!
+//! Implements both the current [graphql-transport-ws](https://github.com/enisdenjo/graphql-ws/blob/master/PROTOCOL.md)
+//! protocol and the legacy [graphql-ws](https://github.com/apollographql/subscriptions-transport-ws/blob/master/PROTOCOL.md)
+//! protocol. The router doesn't depend on any specific web framework: the server is expected to
+//! upgrade the connection, feed the incoming text messages and forward the outgoing messages.
+
+use std::{collections::HashMap, sync::Arc};
+
+use async_stream::stream;
+use common::{env_const::get_graphql_http_path, http::RequestHead};
+use core_plugin_shared::trusted_documents::TrustedDocumentEnforcement;
+use core_resolver::{
+    context::RequestContext,
+    system_resolver::{Subscription, SystemResolver},
+    ErrorCode, GraphQLError, OperationsPayload,
+};
+use exo_env::Environment;
+use futures::{
+    stream::{self, AbortHandle, Abortable, BoxStream, SelectAll},
+    StreamExt,
+};
+use serde_json::{json, Map, Value};
+
+use crate::root_resolver::trusted_document_enforcement;
+
+/// The WebSocket subprotocol spoken by the client
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubscriptionProtocol {
+    /// The `graphql-transport-ws` protocol (used by the `graphql-ws` library)
+    GraphQLTransportWS,
+    /// The legacy `graphql-ws` protocol (used by the `subscriptions-transport-ws` library)
+    GraphQLWS,
+}
+
+impl SubscriptionProtocol {
+    /// The name to be used in the `Sec-WebSocket-Protocol` header
+    pub fn name(&self) -> &'static str {
+        match self {
+            SubscriptionProtocol::GraphQLTransportWS => "graphql-transport-ws",
+            SubscriptionProtocol::GraphQLWS => "graphql-ws",
+        }
+    }
+
+    /// Pick a protocol from the (comma-separated) `Sec-WebSocket-Protocol` header value
+    pub fn negotiate(requested: &str) -> Option<Self> {
+        let requested: Vec<_> = requested.split(',').map(|p| p.trim()).collect();
+
+        [
+            SubscriptionProtocol::GraphQLTransportWS,
+            SubscriptionProtocol::GraphQLWS,
+        ]
+        .into_iter()
+        .find(|protocol| requested.contains(&protocol.name()))
+    }
+}
+
+/// A message to be sent to the client
+#[derive(Debug, PartialEq)]
+pub enum SubscriptionMessage {
+    Text(String),
+    /// Close the connection with the given code and reason (the last message of the stream)
+    Close {
+        code: u16,
+        reason: String,
+    },
+}
+
+pub struct SubscriptionRouter {
+    system_resolver: Arc<SystemResolver>,
+    env: Arc<dyn Environment>,
+}
+
+impl SubscriptionRouter {
+    pub fn new(system_resolver: Arc<SystemResolver>, env: Arc<dyn Environment>) -> Self {
+        Self {
+            system_resolver,
+            env,
+        }
+    }
+
+    /// Determine the protocol to use for a WebSocket upgrade request (`None` if the request is not
+    /// for this router)
+    pub fn protocol(
+        &self,
+        request_head: &(dyn RequestHead + Sync),
+    ) -> Option<SubscriptionProtocol> {
+        if request_head.get_path() != get_graphql_http_path(self.env.as_ref()) {
+            return None;
+        }
+
+        request_head
+            .get_header("sec-websocket-protocol")
+            .and_then(|requested| SubscriptionProtocol::negotiate(&requested))
+    }
+
+    /// Serve a WebSocket connection.
+    ///
+    /// Takes the stream of text messages received from the client and returns the stream of
+    /// messages to be sent to the client. The returned stream ends when the connection should be
+    /// closed (either because the client ended the incoming stream or due to a protocol error, in
+    /// which case the last message is [SubscriptionMessage::Close]).
+    ///
+    /// Subscriptions are subject to the same trusted document enforcement as other operations
+    /// (so only a playground request outside production may use any document).
+    pub fn serve(
+        &self,
+        protocol: SubscriptionProtocol,
+        request_head: Box<dyn RequestHead + Send + Sync>,
+        incoming: BoxStream<'static, String>,
+        playground_request: bool,
+    ) -> BoxStream<'static, SubscriptionMessage> {
+        let system_resolver = self.system_resolver.clone();
+        let trusted_document_enforcement =
+            trusted_document_enforcement(self.env.as_ref(), playground_request);
+
+        let output = stream! {
+            let mut connection = Connection {
+                protocol,
+                system_resolver,
+                trusted_document_enforcement,
+                upgrade_request_head: Some(request_head),
+                request_head: None,
+                active: HashMap::new(),
+                next_sequence: 0,
+            };
+
+            let mut inputs: SelectAll<BoxStream<'static, Input>> = SelectAll::new();
+            inputs.push(
+                incoming
+                    .map(Input::Client)
+                    .chain(stream::once(async { Input::ClientClosed }))
+                    .boxed(),
+            );
+
+            while let Some(input) = inputs.next().await {
+                let action = match input {
+                    Input::Client(message) => connection.handle_message(&message).await,
+                    Input::Event { id, payload } => Action::Send(vec![connection.data(&id, payload)]),
+                    Input::Complete { id, sequence } => {
+                        Action::Send(connection.complete_subscription(&id, sequence))
+                    }
+                    Input::ClientClosed => Action::End,
+                };
+
+                match action {
+                    Action::Send(messages) => {
+                        for message in messages {
+                            yield SubscriptionMessage::Text(message.to_string());
+                        }
+                    }
+                    Action::Subscribe(events) => inputs.push(events),
+                    Action::Close { code, reason } => {
+                        yield SubscriptionMessage::Close { code, reason };
+                        break;
+                    }
+                    Action::End => break,
+                }
+            }
+
+            // Stop listening to events for any remaining subscriptions
+            for (_, (_, abort_handle)) in connection.active.drain() {
+                abort_handle.abort();
+            }
+        };
+
+        output.boxed()
+    }
+}
+
+enum Input {
+    Client(String),
+    ClientClosed,
+    Event { id: String, payload: Value },
+    Complete { id: String, sequence: u64 },
+}
+
+enum Action {
+    Send(Vec<Value>),
+    Subscribe(BoxStream<'static, Input>),
+    Close { code: u16, reason: String },
+    End,
+}
+
+struct Connection {
+    protocol: SubscriptionProtocol,
+    system_resolver: Arc<SystemResolver>,
+    trusted_document_enforcement: TrustedDocumentEnforcement,
+    /// The head of the upgrade request (until the connection is initialized)
+    upgrade_request_head: Option<Box<dyn RequestHead + Send + Sync>>,
+    /// The head to use for operations (set once the connection is initialized)
+    request_head: Option<Arc<SubscriptionRequestHead>>,
+    /// Active subscriptions by their client-provided id. The sequence number distinguishes
+    /// between subscriptions that reuse an id after the earlier one completed.
+    active: HashMap<String, (u64, AbortHandle)>,
+    next_sequence: u64,
+}
+
+impl Connection {
+    async fn handle_message(&mut self, message: &str) -> Action {
+        let Ok(Value::Object(mut message)) = serde_json::from_str::<Value>(message) else {
+            return self.invalid_message("Invalid message");
+        };
+
+        let id = message
+            .get("id")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let payload = message.remove("payload");
+
+        match (
+            self.protocol,
+            message.get("type").and_then(Value::as_str).unwrap_or(""),
+        ) {
+            (_, "connection_init") => {
+                let Some(upgrade_request_head) = self.upgrade_request_head.take() else {
+                    return Action::Close {
+                        code: 4429,
+                        reason: "Too many initialisation requests".to_string(),
+                    };
+                };
+
+                // Expose the connection parameters (such as `Authorization`) as headers to the
+                // subsystems
+                let connection_params = match payload {
+                    Some(Value::Object(params)) => params,
+                    _ => Map::new(),
+                };
+                self.request_head = Some(Arc::new(SubscriptionRequestHead {
+                    head: upgrade_request_head,
+                    connection_params,
+                }));
+
+                let mut messages = vec![json!({"type": "connection_ack"})];
+                if self.protocol == SubscriptionProtocol::GraphQLWS {
+                    messages.push(json!({"type": "ka"}));
+                }
+                Action::Send(messages)
+            }
+            (SubscriptionProtocol::GraphQLTransportWS, "ping") => {
+                Action::Send(vec![json!({"type": "pong"})])
+            }
+            (SubscriptionProtocol::GraphQLTransportWS, "pong") => Action::Send(vec![]),
+            (SubscriptionProtocol::GraphQLTransportWS, "subscribe")
+            | (SubscriptionProtocol::GraphQLWS, "start") => {
+                let Some(id) = id else {
+                    return self.invalid_message("Missing subscription id");
+                };
+                let Some(request_head) = self.request_head.clone() else {
+                    return Action::Close {
+                        code: 4401,
+                        reason: "Unauthorized".to_string(),
+                    };
+                };
+                if self.active.contains_key(&id) {
+                    return match self.protocol {
+                        SubscriptionProtocol::GraphQLTransportWS => Action::Close {
+                            code: 4409,
+                            reason: format!("Subscriber for {id} already exists"),
+                        },
+                        SubscriptionProtocol::GraphQLWS => Action::Send(vec![self.error(
+                            &id,
+                            GraphQLError::new(
+                                "Subscription id is already in use".to_string(),
+                                ErrorCode::BadUserInput,
+                            ),
+                        )]),
+                    };
+                }
+
+                self.subscribe(id, payload.unwrap_or(Value::Null), request_head)
+                    .await
+            }
+            (SubscriptionProtocol::GraphQLTransportWS, "complete")
+            | (SubscriptionProtocol::GraphQLWS, "stop") => {
+                let Some(id) = id else {
+                    return self.invalid_message("Missing subscription id");
+                };
+                let stopped = match self.active.remove(&id) {
+                    Some((_, abort_handle)) => {
+                        abort_handle.abort();
+                        true
+                    }
+                    None => false,
+                };
+
+                // The legacy protocol expects a confirmation (in the current protocol, the client
+                // has already moved on)
+                if stopped && self.protocol == SubscriptionProtocol::GraphQLWS {
+                    Action::Send(vec![json!({"id": id, "type": "complete"})])
+                } else {
+                    Action::Send(vec![])
+                }
+            }
+            (SubscriptionProtocol::GraphQLWS, "connection_terminate") => Action::End,
+            (_, typ) => self.invalid_message(&format!("Unsupported message type '{typ}'")),
+        }
+    }
+
+    async fn subscribe(
+        &mut self,
+        id: String,
+        payload: Value,
+        request_head: Arc<SubscriptionRequestHead>,
+    ) -> Action {
+        let operations_payload = match OperationsPayload::from_json(payload) {
+            Ok(operations_payload) => operations_payload,
+            Err(_) => {
+                return Action::Send(vec![self.error(
+                    &id,
+                    GraphQLError::new("Invalid payload".to_string(), ErrorCode::BadUserInput),
+                )])
+            }
+        };
+
+        let subscribed = {
+            let request_context =
+                RequestContext::new(request_head.as_ref(), vec![], &self.system_resolver);
+
+            let subscribed = self
+                .system_resolver
+                .subscribe(
+                    operations_payload,
+                    &request_context,
+                    self.trusted_document_enforcement,
+                )
+                .await;
+
+            self.system_resolver
+                .finalize_transaction(&request_context, subscribed.is_ok())
+                .await
+                .and(subscribed)
+        };
+
+        let (subscription, events) = match subscribed {
+            Ok(subscribed) => subscribed,
+            Err(err) => {
+                tracing::error!("Error while subscribing: {:?}", err);
+                return Action::Send(vec![self.error(&id, GraphQLError::from(&err))]);
+            }
+        };
+
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+
+        let (abort_handle, abort_registration) = AbortHandle::new_pair();
+        self.active.insert(id.clone(), (sequence, abort_handle));
+
+        let system_resolver = self.system_resolver.clone();
+        let subscription = Arc::new(subscription);
+
+        let event_id = id.clone();
+        let payloads =
+            events
+                .then(move |event| {
+                    let system_resolver = system_resolver.clone();
+                    let request_head = request_head.clone();
+                    let subscription = subscription.clone();
+
+                    async move {
+                        resolve_event(&system_resolver, &request_head, &subscription, event).await
+                    }
+                })
+                .filter_map(|payload| async move { payload })
+                .map(move |payload| Input::Event {
+                    id: event_id.clone(),
+                    payload,
+                });
+
+        let events = Abortable::new(payloads, abort_registration)
+            .chain(stream::once(
+                async move { Input::Complete { id, sequence } },
+            ))
+            .boxed();
+
+        Action::Subscribe(events)
+    }
+
+    /// Complete a subscription whose event stream ended (unless the client already stopped it)
+    fn complete_subscription(&mut self, id: &str, sequence: u64) -> Vec<Value> {
+        match self.active.get(id) {
+            Some((active_sequence, _)) if *active_sequence == sequence => {
+                self.active.remove(id);
+                vec![json!({"id": id, "type": "complete"})]
+            }
+            _ => vec![],
+        }
+    }
+
+    fn data(&self, id: &str, payload: Value) -> Value {
+        let typ = match self.protocol {
+            SubscriptionProtocol::GraphQLTransportWS => "next",
+            SubscriptionProtocol::GraphQLWS => "data",
+        };
+
+        json!({"id": id, "type": typ, "payload": payload})
+    }
+
+    fn error(&self, id: &str, error: GraphQLError) -> Value {
+        let payload = match self.protocol {
+            SubscriptionProtocol::GraphQLTransportWS => json!([error]),
+            SubscriptionProtocol::GraphQLWS => json!(error),
+        };
+
+        json!({"id": id, "type": "error", "payload": payload})
+    }
+
+    fn invalid_message(&self, reason: &str) -> Action {
+        match self.protocol {
+            SubscriptionProtocol::GraphQLTransportWS => Action::Close {
+                code: 4400,
+                reason: reason.to_string(),
+            },
+            SubscriptionProtocol::GraphQLWS => Action::Send(vec![json!({
+                "type": "connection_error",
+                "payload": { "message": reason }
+            })]),
+        }
+    }
+}
+
+/// Resolve an event to the payload to be sent to the client (`None` if the event should not be
+/// sent, for example, if the subscriber has no access to the changed entity)
+async fn resolve_event(
+    system_resolver: &SystemResolver,
+    request_head: &SubscriptionRequestHead,
+    subscription: &Subscription,
+    event: Value,
+) -> Option<Value> {
+    // A fresh context for each event, so that access rules are evaluated anew
+    let request_context = RequestContext::new(request_head, vec![], system_resolver);
+
+    let response = system_resolver
+        .resolve_subscription_event(subscription, &event, &request_context)
+        .await;
+
+    let response = system_resolver
+        .finalize_transaction(&request_context, response.is_ok())
+        .await
+        .and(response);
+
+    let output_name = subscription.field.output_name();
+
+    match response {
+        Ok(Some(response)) => match response.body.to_json() {
+            Ok(Value::Null) => None,
+            Ok(value) => Some(json!({ "data": { output_name: value } })),
+            Err(err) => {
+                tracing::error!("Invalid subscription event response: {:?}", err);
+                None
+            }
+        },
+        Ok(None) => None,
+        Err(err) => {
+            tracing::error!("Error while resolving subscription event: {:?}", err);
+            Some(json!({ "errors": [GraphQLError::from(&err)] }))
+        }
+    }
+}
+
+/// A request head that exposes the connection parameters sent with `connection_init` as headers
+/// (falling back to the headers of the upgrade request)
+struct SubscriptionRequestHead {
+    head: Box<dyn RequestHead + Send + Sync>,
+    connection_params: Map<String, Value>,
+}
+
+impl RequestHead for SubscriptionRequestHead {
+    fn get_headers(&self, key: &str) -> Vec<String> {
+        let param = self
+            .connection_params
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(key))
+            .and_then(|(_, value)| value.as_str());
+
+        match param {
+            Some(value) => vec![value.to_string()],
+            None => self.head.get_headers(key),
+        }
+    }
+
+    fn get_ip(&self) -> Option<std::net::IpAddr> {
+        self.head.get_ip()
+    }
+
+    fn get_path(&self) -> &str {
+        self.head.get_path()
+    }
+
+    fn get_query(&self) -> Value {
+        self.head.get_query()
+    }
+
+    fn get_method(&self) -> &http::Method {
+        self.head.get_method()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use async_graphql_parser::{
+        parse_schema,
+        types::{FieldDefinition, OperationType, TypeDefinition, TypeKind, TypeSystemDefinition},
+    };
+    use async_trait::async_trait;
+    use core_plugin_shared::{
+        interception::{InterceptionMap, InterceptorIndex},
+        persisted_queries::PersistedQueries,
+        trusted_documents::TrustedDocuments,
+    };
+    use core_resolver::{
+        introspection::definition::schema::Schema,
+        plugin::{SubscriptionEventStream, SubsystemResolutionError, SubsystemResolver},
+        response_cache::ResponseCache,
+        validation::{field::ValidatedField, query_cost::QueryCostModel},
+        InterceptedOperation, QueryResponse, QueryResponseBody,
+    };
+    use exo_env::MapEnvironment;
+    use futures::channel::mpsc;
+
+    #[test]
+    fn negotiate() {
+        assert_eq!(
+            SubscriptionProtocol::negotiate("graphql-transport-ws"),
+            Some(SubscriptionProtocol::GraphQLTransportWS)
+        );
+        assert_eq!(
+            SubscriptionProtocol::negotiate("graphql-ws"),
+            Some(SubscriptionProtocol::GraphQLWS)
+        );
+        // The current protocol is preferred
+        assert_eq!(
+            SubscriptionProtocol::negotiate("graphql-ws, graphql-transport-ws"),
+            Some(SubscriptionProtocol::GraphQLTransportWS)
+        );
+        assert_eq!(SubscriptionProtocol::negotiate("mqtt"), None);
+    }
+
+    #[tokio::test]
+    async fn transport_ws_init_and_ping() {
+        let mut connection = TestConnection::new(SubscriptionProtocol::GraphQLTransportWS);
+
+        connection.send(json!({"type": "connection_init"}));
+        assert_eq!(
+            connection.receive().await,
+            json!({"type": "connection_ack"})
+        );
+
+        connection.send(json!({"type": "ping"}));
+        assert_eq!(connection.receive().await, json!({"type": "pong"}));
+
+        // A second initialization closes the connection
+        connection.send(json!({"type": "connection_init"}));
+        connection
+            .expect_close(4429, "Too many initialisation requests")
+            .await;
+    }
+
+    #[tokio::test]
+    async fn transport_ws_subscribe_before_init() {
+        let mut connection = TestConnection::new(SubscriptionProtocol::GraphQLTransportWS);
+
+        connection.send(subscribe_message("1", "ticks"));
+        connection.expect_close(4401, "Unauthorized").await;
+    }
+
+    #[tokio::test]
+    async fn transport_ws_invalid_message() {
+        let mut connection = TestConnection::new(SubscriptionProtocol::GraphQLTransportWS);
+
+        connection
+            .sender
+            .unbounded_send("not json".to_string())
+            .unwrap();
+        connection.expect_close(4400, "Invalid message").await;
+    }
+
+    #[tokio::test]
+    async fn transport_ws_events() {
+        let mut connection =
+            TestConnection::initialized(SubscriptionProtocol::GraphQLTransportWS).await;
+
+        connection.send(subscribe_message("1", "ticks"));
+
+        // The second event is dropped by the subsystem (see `TestSubsystemResolver`)
+        assert_eq!(
+            connection.receive().await,
+            json!({"id": "1", "type": "next", "payload": {"data": {"ticks": 1}}})
+        );
+        assert_eq!(
+            connection.receive().await,
+            json!({"id": "1", "type": "next", "payload": {"data": {"ticks": 3}}})
+        );
+        assert_eq!(
+            connection.receive().await,
+            json!({"id": "1", "type": "complete"})
+        );
+    }
+
+    #[tokio::test]
+    async fn transport_ws_subscription_error() {
+        let mut connection =
+            TestConnection::initialized(SubscriptionProtocol::GraphQLTransportWS).await;
+
+        connection.send(subscribe_message("1", "failing"));
+
+        let message = connection.receive().await;
+        assert_eq!(message["id"], json!("1"));
+        assert_eq!(message["type"], json!("error"));
+        assert_eq!(
+            message["payload"][0]["extensions"]["code"],
+            json!("UNAUTHORIZED")
+        );
+    }
+
+    #[tokio::test]
+    async fn transport_ws_duplicate_id() {
+        let mut connection =
+            TestConnection::initialized(SubscriptionProtocol::GraphQLTransportWS).await;
+
+        connection.send(subscribe_message("1", "forever"));
+        connection.send(subscribe_message("1", "forever"));
+        connection
+            .expect_close(4409, "Subscriber for 1 already exists")
+            .await;
+    }
+
+    #[tokio::test]
+    async fn transport_ws_complete() {
+        let mut connection =
+            TestConnection::initialized(SubscriptionProtocol::GraphQLTransportWS).await;
+
+        connection.send(subscribe_message("1", "forever"));
+        connection.send(json!({"id": "1", "type": "complete"}));
+
+        // No confirmation (the pong is the next message)
+        connection.send(json!({"type": "ping"}));
+        assert_eq!(connection.receive().await, json!({"type": "pong"}));
+
+        // The id may be reused once completed
+        connection.send(subscribe_message("1", "ticks"));
+        assert_eq!(
+            connection.receive().await,
+            json!({"id": "1", "type": "next", "payload": {"data": {"ticks": 1}}})
+        );
+    }
+
+    #[tokio::test]
+    async fn graphql_ws_init() {
+        let mut connection = TestConnection::new(SubscriptionProtocol::GraphQLWS);
+
+        connection.send(
+            json!({"type": "start", "id": "1", "payload": {"query": "subscription { ticks }"}}),
+        );
+        connection.expect_close(4401, "Unauthorized").await;
+
+        let mut connection = TestConnection::new(SubscriptionProtocol::GraphQLWS);
+
+        connection.send(json!({"type": "connection_init"}));
+        assert_eq!(
+            connection.receive().await,
+            json!({"type": "connection_ack"})
+        );
+        assert_eq!(connection.receive().await, json!({"type": "ka"}));
+
+        // The legacy protocol reports invalid messages without closing the connection
+        connection.send(json!({"type": "ping"}));
+        assert_eq!(
+            connection.receive().await,
+            json!({"type": "connection_error", "payload": {"message": "Unsupported message type 'ping'"}})
+        );
+
+        connection.send(json!({"type": "connection_terminate"}));
+        assert_eq!(connection.output.next().await, None);
+    }
+
+    #[tokio::test]
+    async fn graphql_ws_events() {
+        let mut connection = TestConnection::initialized(SubscriptionProtocol::GraphQLWS).await;
+
+        connection.send(start_message("1", "ticks"));
+
+        assert_eq!(
+            connection.receive().await,
+            json!({"id": "1", "type": "data", "payload": {"data": {"ticks": 1}}})
+        );
+        assert_eq!(
+            connection.receive().await,
+            json!({"id": "1", "type": "data", "payload": {"data": {"ticks": 3}}})
+        );
+        assert_eq!(
+            connection.receive().await,
+            json!({"id": "1", "type": "complete"})
+        );
+    }
+
+    #[tokio::test]
+    async fn graphql_ws_duplicate_id_and_stop() {
+        let mut connection = TestConnection::initialized(SubscriptionProtocol::GraphQLWS).await;
+
+        connection.send(start_message("1", "forever"));
+
+        // The legacy protocol reports a duplicate id as an error of the subscription
+        connection.send(start_message("1", "forever"));
+        let message = connection.receive().await;
+        assert_eq!(message["id"], json!("1"));
+        assert_eq!(message["type"], json!("error"));
+        assert_eq!(
+            message["payload"]["message"],
+            json!("Subscription id is already in use")
+        );
+
+        // Stopping is confirmed
+        connection.send(json!({"id": "1", "type": "stop"}));
+        assert_eq!(
+            connection.receive().await,
+            json!({"id": "1", "type": "complete"})
+        );
+
+        // Stopping an unknown subscription is ignored
+        connection.send(json!({"id": "2", "type": "stop"}));
+        connection.send(start_message("2", "ticks"));
+        assert_eq!(
+            connection.receive().await,
+            json!({"id": "2", "type": "data", "payload": {"data": {"ticks": 1}}})
+        );
+    }
+
+    fn subscribe_message(id: &str, field_name: &str) -> Value {
+        json!({"id": id, "type": "subscribe", "payload": {"query": format!("subscription {{ {field_name} }}")}})
+    }
+
+    fn start_message(id: &str, field_name: &str) -> Value {
+        json!({"id": id, "type": "start", "payload": {"query": format!("subscription {{ {field_name} }}")}})
+    }
+
+    /// A connection served by a router with [TestSubsystemResolver]
+    struct TestConnection {
+        sender: mpsc::UnboundedSender<String>,
+        output: BoxStream<'static, SubscriptionMessage>,
+    }
+
+    impl TestConnection {
+        fn new(protocol: SubscriptionProtocol) -> Self {
+            let (sender, receiver) = mpsc::unbounded();
+
+            let router = SubscriptionRouter::new(
+                Arc::new(test_system_resolver()),
+                Arc::new(MapEnvironment::from(HashMap::new())),
+            );
+            let output = router.serve(protocol, Box::new(TestRequest {}), receiver.boxed(), false);
+
+            Self { sender, output }
+        }
+
+        async fn initialized(protocol: SubscriptionProtocol) -> Self {
+            let mut connection = Self::new(protocol);
+
+            connection.send(json!({"type": "connection_init"}));
+            assert_eq!(
+                connection.receive().await,
+                json!({"type": "connection_ack"})
+            );
+            if protocol == SubscriptionProtocol::GraphQLWS {
+                assert_eq!(connection.receive().await, json!({"type": "ka"}));
+            }
+
+            connection
+        }
+
+        fn send(&self, message: Value) {
+            self.sender.unbounded_send(message.to_string()).unwrap();
+        }
+
+        async fn receive(&mut self) -> Value {
+            match self.output.next().await {
+                Some(SubscriptionMessage::Text(text)) => serde_json::from_str(&text).unwrap(),
+                message => panic!("Expected a text message, got {message:?}"),
+            }
+        }
+
+        async fn expect_close(&mut self, code: u16, reason: &str) {
+            assert_eq!(
+                self.output.next().await,
+                Some(SubscriptionMessage::Close {
+                    code,
+                    reason: reason.to_string()
+                })
+            );
+            assert_eq!(self.output.next().await, None);
+        }
+    }
+
+    fn test_system_resolver() -> SystemResolver {
+        let document =
+            parse_schema("scalar Int type Subscription { ticks: Int forever: Int failing: Int }")
+                .unwrap();
+
+        let mut type_definitions: Vec<TypeDefinition> = vec![];
+        let mut subscriptions: Vec<FieldDefinition> = vec![];
+        for definition in document.definitions {
+            if let TypeSystemDefinition::Type(typ) = definition {
+                match typ.node.kind {
+                    TypeKind::Object(object) => {
+                        subscriptions.extend(object.fields.into_iter().map(|field| field.node))
+                    }
+                    _ => type_definitions.push(typ.node),
+                }
+            }
+        }
+
+        SystemResolver::new(
+            vec![Box::new(TestSubsystemResolver {})],
+            InterceptionMap {
+                map: HashMap::new(),
+            },
+            InterceptionMap {
+                map: HashMap::new(),
+            },
+            TrustedDocuments::all(),
+            PersistedQueries::new(0),
+            Schema::new(type_definitions, vec![], vec![], subscriptions),
+            None.into(),
+            Arc::new(MapEnvironment::from(HashMap::new())),
+            10,
+            10,
+            QueryCostModel::default(),
+            1,
+            10,
+            ResponseCache::new(0),
+        )
+    }
+
+    /// A subsystem with the `ticks` subscription (producing the events 1, 2, and 3, of which it
+    /// drops the second), the `forever` subscription (that never produces an event), and the
+    /// `failing` subscription (that the subscriber has no access to)
+    struct TestSubsystemResolver {}
+
+    #[async_trait]
+    impl SubsystemResolver for TestSubsystemResolver {
+        fn id(&self) -> &'static str {
+            "test"
+        }
+
+        async fn resolve<'a>(
+            &'a self,
+            _operation: &'a ValidatedField,
+            _operation_type: OperationType,
+            _request_context: &'a RequestContext,
+            _system_resolver: &'a SystemResolver,
+        ) -> Result<Option<QueryResponse>, SubsystemResolutionError> {
+            Ok(None)
+        }
+
+        async fn subscribe<'a>(
+            &'a self,
+            operation: &'a ValidatedField,
+            _request_context: &'a RequestContext<'a>,
+        ) -> Result<Option<SubscriptionEventStream>, SubsystemResolutionError> {
+            match operation.name.as_str() {
+                "ticks" => Ok(Some(stream::iter([json!(1), json!(2), json!(3)]).boxed())),
+                "forever" => Ok(Some(stream::pending().boxed())),
+                "failing" => Err(SubsystemResolutionError::Authorization),
+                _ => Ok(None),
+            }
+        }
+
+        async fn resolve_subscription_event<'a>(
+            &'a self,
+            _operation: &'a ValidatedField,
+            event: &'a Value,
+            _request_context: &'a RequestContext<'a>,
+            _system_resolver: &'a SystemResolver,
+        ) -> Result<Option<QueryResponse>, SubsystemResolutionError> {
+            Ok((event != &json!(2)).then(|| QueryResponse {
+                body: QueryResponseBody::Json(event.clone()),
+                headers: vec![],
+            }))
+        }
+
+        async fn invoke_interceptor<'a>(
+            &'a self,
+            _interceptor_index: InterceptorIndex,
+            _intercepted_operation: &'a InterceptedOperation,
+            _request_context: &'a RequestContext<'a>,
+            _system_resolver: &'a SystemResolver,
+        ) -> Result<Option<QueryResponse>, SubsystemResolutionError> {
+            Err(SubsystemResolutionError::NoInterceptorFound)
+        }
+
+        fn schema_queries(&self) -> Vec<FieldDefinition> {
+            vec![]
+        }
+
+        fn schema_mutations(&self) -> Vec<FieldDefinition> {
+            vec![]
+        }
+
+        fn schema_types(&self) -> Vec<TypeDefinition> {
+            vec![]
+        }
+    }
+
+    struct TestRequest {}
+
+    impl RequestHead for TestRequest {
+        fn get_headers(&self, _key: &str) -> Vec<String> {
+            vec![]
+        }
+
+        fn get_ip(&self) -> Option<std::net::IpAddr> {
+            None
+        }
+
+        fn get_method(&self) -> &http::Method {
+            &http::Method::GET
+        }
+
+        fn get_path(&self) -> &str {
+            "/graphql"
+        }
+
+        fn get_query(&self) -> Value {
+            Default::default()
+        }
+    }
+}
@@ -7,6 +7,7 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use common::introspection::{introspection_mode, IntrospectionMode};
@@ -18,11 +19,13 @@ use thiserror::Error;
 use core_plugin_interface::interface::SubsystemLoader;
 use core_plugin_interface::interface::{LibraryLoadingError, SubsystemLoadingError};
 use core_plugin_shared::{
-    error::ModelSerializationError, serializable_system::SerializableSystem,
-    system_serializer::SystemSerializer,
+    error::ModelSerializationError, persisted_queries::PersistedQueries,
+    serializable_system::SerializableSystem, system_serializer::SystemSerializer,
 };
 
 use core_resolver::plugin::SubsystemResolver;
+use core_resolver::response_cache::ResponseCache;
+use core_resolver::validation::query_cost::{QueryCostModel, DEFAULT_LIST_SIZE};
 use core_resolver::{introspection::definition::schema::Schema, system_resolver::SystemResolver};
 use exo_env::Environment;
 
@@ -33,6 +36,14 @@ pub type StaticLoaders = Vec<Box<dyn SubsystemLoader>>;
 pub struct SystemLoader;
 
 const EXO_MAX_SELECTION_DEPTH: &str = "EXO_MAX_SELECTION_DEPTH";
+const EXO_PERSISTED_QUERY_CACHE_SIZE: &str = "EXO_PERSISTED_QUERY_CACHE_SIZE";
+const EXO_MAX_QUERY_COST: &str = "EXO_MAX_QUERY_COST";
+const EXO_QUERY_COST_DEFAULT_LIST_SIZE: &str = "EXO_QUERY_COST_DEFAULT_LIST_SIZE";
+const EXO_QUERY_FIELD_COSTS: &str = "EXO_QUERY_FIELD_COSTS";
+const EXO_REPORT_QUERY_COST: &str = "EXO_REPORT_QUERY_COST";
+const EXO_MAX_CONCURRENT_ROOT_FIELDS: &str = "EXO_MAX_CONCURRENT_ROOT_FIELDS";
+const EXO_MAX_BATCH_SIZE: &str = "EXO_MAX_BATCH_SIZE";
+const EXO_RESPONSE_CACHE_SIZE: &str = "EXO_RESPONSE_CACHE_SIZE";
 
 impl SystemLoader {
     pub async fn load(
@@ -115,6 +126,16 @@ impl SystemLoader {
         let (normal_query_depth_limit, introspection_query_depth_limit) =
             query_depth_limits(env.as_ref())?;
 
+        let persisted_queries = PersistedQueries::new(persisted_query_cache_size(env.as_ref())?);
+
+        let query_cost_model = query_cost_model(env.as_ref())?;
+
+        let max_concurrent_root_fields = max_concurrent_root_fields(env.as_ref())?;
+
+        let max_batch_size = max_batch_size(env.as_ref())?;
+
+        let response_cache = ResponseCache::new(response_cache_size(env.as_ref())?);
+
         let authenticator = JwtAuthenticator::new_from_env(env.as_ref())
             .await
             .map_err(|e| SystemLoadingError::Config(e.to_string()))?;
@@ -124,11 +145,16 @@ impl SystemLoader {
             query_interception_map,
             mutation_interception_map,
             trusted_documents,
+            persisted_queries,
             schema,
             Arc::new(authenticator),
             env,
             normal_query_depth_limit,
             introspection_query_depth_limit,
+            query_cost_model,
+            max_concurrent_root_fields,
+            max_batch_size,
+            response_cache,
         ))
     }
 
@@ -174,6 +200,122 @@ pub fn query_depth_limits(env: &dyn Environment) -> Result<(usize, usize), Syste
     Ok((query_depth, DEFAULT_INTROSPECTION_QUERY_DEPTH))
 }
 
+/// Returns the maximum number of documents registered through the automatic persisted queries
+/// protocol to keep in memory
+fn persisted_query_cache_size(env: &dyn Environment) -> Result<usize, SystemLoadingError> {
+    const DEFAULT_PERSISTED_QUERY_CACHE_SIZE: usize = 1000;
+
+    match env.get(EXO_PERSISTED_QUERY_CACHE_SIZE) {
+        Some(e) => e.parse::<usize>().map_err(|_| {
+            SystemLoadingError::Config(format!(
+                "{EXO_PERSISTED_QUERY_CACHE_SIZE} env var must be set to a non-negative integer"
+            ))
+        }),
+        None => Ok(DEFAULT_PERSISTED_QUERY_CACHE_SIZE),
+    }
+}
+
+/// Returns the maximum number of root fields of a query to resolve concurrently. By default, the
+/// fields are resolved one after another in a single transaction (and thus see a consistent view of
+/// the database).
+fn max_concurrent_root_fields(env: &dyn Environment) -> Result<usize, SystemLoadingError> {
+    match env.get(EXO_MAX_CONCURRENT_ROOT_FIELDS) {
+        Some(e) => match e.parse::<usize>() {
+            Ok(v) if v > 0 => Ok(v),
+            _ => Err(SystemLoadingError::Config(format!(
+                "{EXO_MAX_CONCURRENT_ROOT_FIELDS} env var must be set to a positive integer"
+            ))),
+        },
+        None => Ok(1),
+    }
+}
+
+/// Returns the maximum number of operations in a batched request (so that a single request can't
+/// fan out into an unbounded number of operations)
+fn max_batch_size(env: &dyn Environment) -> Result<usize, SystemLoadingError> {
+    const DEFAULT_MAX_BATCH_SIZE: usize = 10;
+
+    match env.get(EXO_MAX_BATCH_SIZE) {
+        Some(e) => match e.parse::<usize>() {
+            Ok(v) if v > 0 => Ok(v),
+            _ => Err(SystemLoadingError::Config(format!(
+                "{EXO_MAX_BATCH_SIZE} env var must be set to a positive integer"
+            ))),
+        },
+        None => Ok(DEFAULT_MAX_BATCH_SIZE),
+    }
+}
+
+/// Returns the maximum number of query responses to keep in the in-process response cache (by
+/// default, responses aren't cached in-process, but `Cache-Control` headers are still emitted)
+fn response_cache_size(env: &dyn Environment) -> Result<usize, SystemLoadingError> {
+    // The cache relies on `std::time::Instant` to expire responses, which isn't available on WASM
+    if cfg!(target_family = "wasm") {
+        return Ok(0);
+    }
+
+    match env.get(EXO_RESPONSE_CACHE_SIZE) {
+        Some(e) => e.parse::<usize>().map_err(|_| {
+            SystemLoadingError::Config(format!(
+                "{EXO_RESPONSE_CACHE_SIZE} env var must be set to a non-negative integer"
+            ))
+        }),
+        None => Ok(0),
+    }
+}
+
+/// Returns the cost model used to reject expensive operations.
+///
+/// Field costs are specified as a comma-separated list of `<type>.<field>=<cost>` entries (for
+/// example, `Query.concerts=10,Concert.venue=2`).
+fn query_cost_model(env: &dyn Environment) -> Result<QueryCostModel, SystemLoadingError> {
+    fn parse_usize(env: &dyn Environment, key: &str) -> Result<Option<usize>, SystemLoadingError> {
+        env.get(key)
+            .map(|value| {
+                value.parse::<usize>().map_err(|_| {
+                    SystemLoadingError::Config(format!(
+                        "{key} env var must be set to a non-negative integer"
+                    ))
+                })
+            })
+            .transpose()
+    }
+
+    let field_costs = match env.get(EXO_QUERY_FIELD_COSTS) {
+        Some(field_costs) => field_costs
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                entry
+                    .split_once('=')
+                    .and_then(|(field, cost)| {
+                        let field = field.trim();
+                        let cost = cost.trim().parse::<usize>().ok()?;
+                        field.contains('.').then(|| (field.to_string(), cost))
+                    })
+                    .ok_or_else(|| {
+                        SystemLoadingError::Config(format!(
+                            "{EXO_QUERY_FIELD_COSTS} env var entries must be of the form <type>.<field>=<cost>, found '{entry}'"
+                        ))
+                    })
+            })
+            .collect::<Result<HashMap<_, _>, _>>()?,
+        None => HashMap::new(),
+    };
+
+    Ok(QueryCostModel {
+        max_cost: parse_usize(env, EXO_MAX_QUERY_COST)?,
+        default_list_size: parse_usize(env, EXO_QUERY_COST_DEFAULT_LIST_SIZE)?
+            .unwrap_or(DEFAULT_LIST_SIZE),
+        field_costs,
+        report_cost: env
+            .get(EXO_REPORT_QUERY_COST)
+            .map(|value| value == "true")
+            .unwrap_or(false),
+    })
+}
+
 #[derive(Error, Debug)]
 pub enum SystemLoadingError {
     #[error("System serialization error: {0}")]
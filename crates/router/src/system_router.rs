@@ -15,7 +15,7 @@ use exo_env::Environment;
 use playground_router::PlaygroundRouter;
 use resolver::{
     create_system_resolver, create_system_resolver_from_system, GraphQLRouter, StaticLoaders,
-    SystemLoadingError,
+    SubscriptionRouter, SystemLoadingError,
 };
 
 pub async fn create_system_router_from_file(
@@ -42,22 +42,30 @@ fn create_system_router_from_resolver(
     resolver: SystemResolver,
     env: Arc<dyn Environment>,
 ) -> Result<SystemRouter, SystemLoadingError> {
+    let resolver = Arc::new(resolver);
+
     Ok(SystemRouter::new(
         vec![
-            Box::new(GraphQLRouter::new(resolver, env.clone())),
+            Box::new(GraphQLRouter::new(resolver.clone(), env.clone())),
             #[cfg(not(target_family = "wasm"))]
             Box::new(PlaygroundRouter::new(env.clone())),
         ],
+        SubscriptionRouter::new(resolver, env.clone()),
         env.as_ref(),
     ))
 }
 
 pub struct SystemRouter {
     underlying: CorsRouter,
+    subscription_router: SubscriptionRouter,
 }
 
 impl SystemRouter {
-    pub fn new(routers: Vec<Box<dyn Router + Send>>, env: &dyn Environment) -> Self {
+    pub fn new(
+        routers: Vec<Box<dyn Router + Send>>,
+        subscription_router: SubscriptionRouter,
+        env: &dyn Environment,
+    ) -> Self {
         let cors_domains = env.get(EXO_CORS_DOMAINS);
 
         Self {
@@ -65,8 +73,14 @@ impl SystemRouter {
                 Arc::new(CompositeRouter::new(routers)),
                 CorsConfig::from_env(cors_domains),
             ),
+            subscription_router,
         }
     }
+
+    /// The router for subscriptions (served over WebSocket connections)
+    pub fn subscription_router(&self) -> &SubscriptionRouter {
+        &self.subscription_router
+    }
 }
 
 #[async_trait::async_trait]
@@ -12,17 +12,22 @@ mod request;
 use std::sync::Arc;
 
 use actix_web::{
+    guard,
+    http::header::{HeaderValue, SEC_WEBSOCKET_PROTOCOL, UPGRADE},
     web::{self, ServiceConfig},
     HttpRequest, HttpResponse, HttpResponseBuilder, Responder,
 };
+use actix_ws::{CloseCode, CloseReason, Message};
 use exo_env::Environment;
+use futures::{channel::mpsc, StreamExt};
 use reqwest::StatusCode;
+use resolver::SubscriptionMessage;
 use router::system_router::SystemRouter;
 use url::Url;
 
 use common::http::{RequestHead, RequestPayload, ResponseBody, ResponsePayload};
 use common::{
-    env_const::{get_deployment_mode, DeploymentMode},
+    env_const::{get_deployment_mode, get_graphql_http_path, DeploymentMode},
     router::Router,
 };
 use request::ActixRequestHead;
@@ -43,13 +48,105 @@ pub fn configure_router(
         _ => None,
     };
 
+    let graphql_http_path = get_graphql_http_path(env.as_ref());
+
     move |app| {
         app.app_data(system_router)
             .app_data(web::Data::new(endpoint_url))
+            .route(
+                &graphql_http_path,
+                web::get()
+                    .guard(guard::fn_guard(|ctx| {
+                        ctx.head()
+                            .headers()
+                            .get(UPGRADE)
+                            .map(|value| value.as_bytes().eq_ignore_ascii_case(b"websocket"))
+                            .unwrap_or(false)
+                    }))
+                    .to(subscribe),
+            )
             .default_service(web::to(resolve));
     }
 }
 
+/// Serve GraphQL subscriptions over a WebSocket connection
+async fn subscribe(
+    http_request: HttpRequest,
+    body: web::Payload,
+    system_router: web::Data<SystemRouter>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let subscription_router = system_router.subscription_router();
+
+    let playground_request = is_playground_request(&http_request);
+
+    let request_head = ActixRequestHead::from_request(http_request.clone(), Value::Null);
+
+    let Some(protocol) = subscription_router.protocol(&request_head) else {
+        return Ok(HttpResponse::BadRequest().body(error_msg!("Unsupported WebSocket subprotocol")));
+    };
+
+    let (mut response, session, mut messages) = actix_ws::handle(&http_request, body)?;
+    response.headers_mut().insert(
+        SEC_WEBSOCKET_PROTOCOL,
+        HeaderValue::from_static(protocol.name()),
+    );
+
+    // The actix message stream isn't `Send`, so forward text messages through a channel
+    let (incoming_sender, incoming) = mpsc::unbounded();
+    let mut outgoing = subscription_router.serve(
+        protocol,
+        Box::new(request_head),
+        incoming.boxed(),
+        playground_request,
+    );
+
+    let mut reader_session = session.clone();
+    actix_web::rt::spawn(async move {
+        while let Some(Ok(message)) = messages.next().await {
+            match message {
+                Message::Text(text) => {
+                    if incoming_sender.unbounded_send(text.to_string()).is_err() {
+                        break;
+                    }
+                }
+                Message::Ping(bytes) => {
+                    if reader_session.pong(&bytes).await.is_err() {
+                        break;
+                    }
+                }
+                Message::Close(_) => break,
+                _ => {}
+            }
+        }
+    });
+
+    let mut writer_session = session;
+    actix_web::rt::spawn(async move {
+        while let Some(message) = outgoing.next().await {
+            match message {
+                SubscriptionMessage::Text(text) => {
+                    if writer_session.text(text).await.is_err() {
+                        return;
+                    }
+                }
+                SubscriptionMessage::Close { code, reason } => {
+                    let _ = writer_session
+                        .close(Some(CloseReason {
+                            code: CloseCode::Other(code),
+                            description: Some(reason),
+                        }))
+                        .await;
+                    return;
+                }
+            }
+        }
+
+        let _ = writer_session.close(None).await;
+    });
+
+    Ok(response)
+}
+
 /// Resolve a GraphQL request
 ///
 /// # Arguments
@@ -89,6 +186,17 @@ impl RequestPayload for ActixRequestPayload {
     fn take_body(&mut self) -> Value {
         self.body.take()
     }
+
+    fn clone_head(&self) -> Option<Box<dyn RequestHead + Send + Sync>> {
+        Some(Box::new(self.head.clone()))
+    }
+}
+
+fn is_playground_request(req: &HttpRequest) -> bool {
+    req.headers()
+        .get("_exo_playground")
+        .map(|value| value == "true")
+        .unwrap_or(false)
 }
 
 async fn resolve_locally(
@@ -97,11 +205,7 @@ async fn resolve_locally(
     query: Value,
     system_router: web::Data<SystemRouter>,
 ) -> HttpResponse {
-    let playground_request = req
-        .headers()
-        .get("_exo_playground")
-        .map(|value| value == "true")
-        .unwrap_or(false);
+    let playground_request = is_playground_request(&req);
 
     let mut request = ActixRequestPayload {
         head: ActixRequestHead::from_request(req, query),
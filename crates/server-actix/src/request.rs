@@ -10,6 +10,7 @@
 use actix_web::{dev::ConnectionInfo, http::header::HeaderMap, HttpRequest};
 use common::http::RequestHead;
 
+#[derive(Clone)]
 pub struct ActixRequestHead {
     // we cannot refer to HttpRequest directly, as it holds an Rc (and therefore does
     // not impl Send or Sync)
@@ -23,7 +23,7 @@ async fn test_basic_query() {
             "statusCode": 200,
             "headers": {},
             "multiValueHeaders": {"content-type": ["application/json"]},
-            "body": "{\"errors\": [{\"message\":\"Operation failed\"}]}"
+            "body": "{\"errors\":[{\"message\":\"Operation failed\",\"path\":[\"log\"],\"extensions\":{\"code\":\"OPERATION_FAILED\"}}]}"
         }),
     )
     .await
@@ -159,6 +159,7 @@ fn shallow_module_query(
         method_id: None,
         argument_param: argument_param(method, building),
         return_type: compute_shallow_return_type(&method.return_type, module_types),
+        cache_policy: method.cache_policy,
     }
 }
 
@@ -13,16 +13,19 @@ use std::path::Path;
 use codemap::Span;
 use codemap_diagnostic::{Diagnostic, Level, SpanLabel, SpanStyle};
 
+use core_model::cache_policy::CachePolicy;
 use core_model::types::{FieldType, Named};
 use core_model::{mapped_arena::MappedArena, primitive_type::PrimitiveType};
 use core_model_builder::ast::ast_types::AstFieldType;
+use core_model_builder::builder::cache_policy_builder::build_cache_policy;
 use core_model_builder::builder::resolved_builder::AnnotationMapHelper;
 use core_model_builder::builder::system_builder::BaseModelSystem;
 use core_model_builder::typechecker::typ::{Module, TypecheckedSystem};
 use core_model_builder::typechecker::AnnotationMap;
 use core_model_builder::{
     ast::ast_types::{
-        AstAnnotationParams, AstArgument, AstExpr, AstMethodType, AstModelKind, AstModule,
+        AstAnnotationParams, AstArgument, AstExpr, AstMethod, AstMethodType, AstModelKind,
+        AstModule,
     },
     error::ModelBuildingError,
     typechecker::{typ::Type, Typed},
@@ -98,6 +101,7 @@ pub struct ResolvedMethod {
     pub access: ResolvedAccess,
     pub arguments: Vec<ResolvedArgument>,
     pub return_type: FieldType<ResolvedFieldType>,
+    pub cache_policy: Option<CachePolicy>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -307,6 +311,7 @@ async fn resolve_module(
                             .map(|a| resolve_argument(a, types))
                             .collect(),
                         return_type: resolve_field_type(&m.return_type.to_typ(types), m.return_type.module_name(), types),
+                        cache_policy: resolve_method_cache_policy(m, errors),
                     }
                 })
                 .collect(),
@@ -369,6 +374,31 @@ async fn resolve_module(
     Ok(())
 }
 
+/// Build the cache policy of a method, which only applies to queries (since mutations must not be
+/// served from a cache)
+fn resolve_method_cache_policy(
+    method: &AstMethod<Typed>,
+    errors: &mut Vec<Diagnostic>,
+) -> Option<CachePolicy> {
+    let annotation = method.annotations.get("cache")?;
+
+    if method.typ == AstMethodType::Mutation {
+        errors.push(Diagnostic {
+            level: Level::Error,
+            message: "@cache may only be specified on queries".to_string(),
+            code: Some("C000".to_string()),
+            spans: vec![SpanLabel {
+                span: method.span,
+                style: SpanStyle::Primary,
+                label: None,
+            }],
+        });
+        return None;
+    }
+
+    build_cache_policy(Some(annotation), errors)
+}
+
 fn resolve_argument(arg: &AstArgument<Typed>, types: &MappedArena<Type>) -> ResolvedArgument {
     ResolvedArgument {
         name: arg.name.clone(),
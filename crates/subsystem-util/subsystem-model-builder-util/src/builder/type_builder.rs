@@ -9,7 +9,7 @@
 
 use core_model::{
     context_type::ContextType, function_defn::FunctionDefinition, mapped_arena::MappedArena,
-    types::FieldType,
+    primitive_type::PrimitiveType, types::FieldType,
 };
 use core_model_builder::{ast::ast_types::AstExpr, error::ModelBuildingError, typechecker::Typed};
 use subsystem_model_util::{
@@ -203,11 +203,18 @@ fn expand_type_access(
 fn create_shallow_type(resolved_type: &ResolvedType, building: &mut SystemContextBuilding) {
     let type_name = resolved_type.name();
 
-    // Mark every type as Primitive, since other types that may be referred haven't been processed yet
-    // and we haven't build query and mutation types either
+    // Mark every type (other than an enum, which is complete as is) as Primitive, since other types
+    // that may be referred haven't been processed yet and we haven't build query and mutation types either
+    let kind = match resolved_type {
+        ResolvedType::Primitive(PrimitiveType::Enum(enum_type)) => {
+            ModuleTypeKind::Enum(enum_type.values.clone())
+        }
+        _ => ModuleTypeKind::Primitive,
+    };
+
     let typ = ModuleType {
         name: type_name.to_string(),
-        kind: ModuleTypeKind::Primitive,
+        kind,
         is_input: false,
     };
 
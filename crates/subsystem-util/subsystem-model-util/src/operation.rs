@@ -10,6 +10,7 @@
 use std::fmt::Debug;
 
 use async_graphql_parser::types::Type;
+use core_model::cache_policy::CachePolicy;
 use core_model::mapped_arena::SerializableSlabIndex;
 use core_model::type_normalization::{Operation, Parameter};
 use serde::{Deserialize, Serialize};
@@ -24,6 +25,7 @@ pub struct ModuleQuery {
     pub method_id: Option<SerializableSlabIndex<ModuleMethod>>,
     pub argument_param: Vec<ArgumentParameter>,
     pub return_type: ModuleOperationReturnType,
+    pub cache_policy: Option<CachePolicy>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -52,6 +54,10 @@ impl Operation for ModuleQuery {
     fn return_type(&self) -> Type {
         return_type(&self.return_type)
     }
+
+    fn cache_policy(&self) -> Option<CachePolicy> {
+        self.cache_policy
+    }
 }
 
 impl Operation for ModuleMutation {
@@ -8,8 +8,10 @@
 // by the Apache License, Version 2.0.
 
 use async_graphql_parser::types::{
-    FieldDefinition, InputObjectType, ObjectType, Type, TypeDefinition, TypeKind,
+    EnumType, EnumValueDefinition, FieldDefinition, InputObjectType, ObjectType, Type,
+    TypeDefinition, TypeKind,
 };
+use async_graphql_value::Name;
 
 use core_model::{
     mapped_arena::{SerializableSlab, SerializableSlabIndex},
@@ -35,6 +37,8 @@ pub struct ModuleType {
 #[allow(clippy::large_enum_variant)]
 pub enum ModuleTypeKind {
     Primitive,
+    /// A user-declared enum with its values
+    Enum(Vec<String>),
     Composite(ModuleCompositeType),
 }
 
@@ -74,6 +78,24 @@ impl TypeDefinitionProvider<SerializableSlab<ModuleType>> for ModuleType {
                 directives: vec![],
                 kind: TypeKind::Scalar,
             },
+            ModuleTypeKind::Enum(values) => TypeDefinition {
+                extend: false,
+                description: None,
+                name: default_positioned_name(&self.name),
+                directives: vec![],
+                kind: TypeKind::Enum(EnumType {
+                    values: values
+                        .iter()
+                        .map(|value| {
+                            default_positioned(EnumValueDefinition {
+                                description: None,
+                                value: default_positioned(Name::new(value)),
+                                directives: vec![],
+                            })
+                        })
+                        .collect(),
+                }),
+            },
             ModuleTypeKind::Composite(ModuleCompositeType {
                 fields: model_fields,
                 ..
@@ -8,6 +8,7 @@
 // by the Apache License, Version 2.0.
 
 use anyhow::{anyhow, bail, Context, Result};
+use async_graphql_parser::{parse_query, types::OperationType};
 use colored::Colorize;
 
 use common::env_const::{
@@ -18,14 +19,19 @@ use common::http::{RequestHead, RequestPayload, ResponseBody};
 use common::router::Router;
 use core_resolver::OperationsPayload;
 use exo_sql::testing::db::EphemeralDatabaseServer;
+use exo_sql::DatabaseClientManager;
+use futures::channel::mpsc;
 use futures::future::OptionFuture;
-use futures::FutureExt;
+use futures::stream::BoxStream;
+use futures::{FutureExt, StreamExt};
 use jsonwebtoken::{encode, EncodingKey, Header};
 use rand::{distributions::Alphanumeric, Rng};
 use regex::Regex;
+use resolver::{SubscriptionMessage, SubscriptionProtocol};
 use router::system_router::{create_system_router_from_file, SystemRouter};
 use serde_json::{json, Map, Value};
 
+use std::collections::HashSet;
 use std::net::{IpAddr, Ipv4Addr};
 use std::path::PathBuf;
 use std::sync::mpsc::Sender;
@@ -47,6 +53,21 @@ struct TestfileContext {
     jwtsecret: String,
     cookies: HashMap<String, String>,
     testvariables: HashMap<String, serde_json::Value>,
+    /// Subscriptions opened by earlier operations, whose events are asserted once all operations
+    /// have run
+    subscriptions: Vec<OpenSubscription>,
+}
+
+/// How long to wait for the next event of a subscription before concluding that no more events
+/// will be delivered
+const SUBSCRIPTION_EVENT_TIMEOUT: Duration = Duration::from_secs(2);
+
+struct OpenSubscription {
+    /// Kept to keep the connection open
+    _client_messages: mpsc::UnboundedSender<String>,
+    server_messages: BoxStream<'static, SubscriptionMessage>,
+    expected_payload: Option<String>,
+    deno_prelude: String,
 }
 
 impl IntegrationTest {
@@ -131,6 +152,20 @@ impl IntegrationTest {
                 bail!("Could not build schema for {}", self.name());
             }
 
+            // Run the SQL init files (for example, to create views, which migrations leave alone)
+            if !self.init_sql.is_empty() {
+                let client = DatabaseClientManager::from_url_direct(&db_instance.url(), false)
+                    .await?
+                    .get_client()
+                    .await?;
+
+                for sql in self.init_sql.iter() {
+                    client.batch_execute(sql).await.with_context(|| {
+                        format!("While executing SQL init file for testfile {}", self.name())
+                    })?;
+                }
+            }
+
             // Verify the schema to exercise the verification logic (which in-turn exercises the database introspection logic)
             let verify_child = cmd("exo")
                 .args(["schema", "verify", "--database", &db_instance.url()])
@@ -184,6 +219,7 @@ impl IntegrationTest {
                 jwtsecret,
                 cookies: HashMap::new(),
                 testvariables: HashMap::new(),
+                subscriptions: vec![],
             }
         };
 
@@ -227,6 +263,18 @@ impl IntegrationTest {
             };
         }
 
+        if fail.is_none() {
+            let result = assert_subscription_events(&mut ctx)
+                .await
+                .with_context(|| anyhow!("While running tests for {}", self.name()));
+
+            fail = match result {
+                Ok(OperationResult::AssertFailed(e)) => Some(TestResultKind::Fail(e)),
+                Ok(_) => None,
+                Err(e) => Some(TestResultKind::SetupFail(e)),
+            };
+        }
+
         let success = fail.unwrap_or(TestResultKind::Success);
 
         Ok(TestResult {
@@ -342,6 +390,9 @@ async fn run_operation(
         auth,
         headers,
         deno_prelude,
+        method,
+        batch,
+        expected_status,
     } = gql;
 
     let deno_prelude = deno_prelude.clone().unwrap_or_default();
@@ -369,11 +420,30 @@ async fn run_operation(
     // similarly, remove @unordered directives
     let query = query.replace("@unordered", "");
 
+    let method = match method.as_deref() {
+        None => http::Method::POST,
+        Some(method) => method
+            .to_ascii_uppercase()
+            .parse::<http::Method>()
+            .with_context(|| format!("Invalid method {method}"))?,
+    };
+
+    // A GET request carries the operation in the query parameters (with the variables encoded as
+    // JSON), and a POST request in its body
+    let query_params = if method == http::Method::GET {
+        json!({
+            "query": query,
+            "variables": serde_json::to_string(&variables_map)?,
+        })
+    } else {
+        Value::Null
+    };
+
     let mut request_head = MemoryRequestHead::new(
         ctx.cookies.clone(),
-        http::Method::POST,
+        method.clone(),
         "/graphql".to_string(),
-        Default::default(),
+        query_params,
     );
 
     // add JWT token if specified in testfile
@@ -420,9 +490,39 @@ async fn run_operation(
         query_hash: None,
     };
 
-    let request = MemoryRequestPayload::new(operations_payload.to_json()?, request_head);
+    if is_subscription(&operations_payload) {
+        let subscription = subscribe(
+            request_head,
+            operations_payload,
+            &ctx.router,
+            expected_payload.clone(),
+            deno_prelude,
+        )
+        .await?;
+        ctx.subscriptions.push(subscription);
+
+        return Ok(OperationResult::Finished);
+    }
+
+    let body = if method == http::Method::GET {
+        Value::Null
+    } else if *batch {
+        batch_payloads(operations_payload)?
+    } else {
+        operations_payload.to_json()?
+    };
+    let request = MemoryRequestPayload::new(body, request_head);
     // run the operation
-    let body = run_query(request, &ctx.router, &mut ctx.cookies).await;
+    let (status, body) = run_request(request, &ctx.router, &mut ctx.cookies).await;
+
+    if let Some(expected_status) = expected_status {
+        if status.as_u16() != *expected_status {
+            return Ok(OperationResult::AssertFailed(anyhow!(
+                "Expected status {expected_status}, got {status}: {}",
+                serde_json::to_string_pretty(&body)?
+            )));
+        }
+    }
 
     // resolve testvariables from the result of our current operation
     // and extend our collection with them
@@ -468,11 +568,184 @@ async fn run_operation(
     }
 }
 
+/// The payload of a batch with each operation of the document (in the order of appearance)
+fn batch_payloads(operations_payload: OperationsPayload) -> Result<Value> {
+    let query = operations_payload.query.as_deref().unwrap_or_default();
+    let document = parse_query(query)?;
+
+    let mut operations = document
+        .operations
+        .iter()
+        .map(|(name, operation)| (operation.pos, name.map(|name| name.to_string())))
+        .collect::<Vec<_>>();
+    operations.sort_by_key(|(pos, _)| (pos.line, pos.column));
+
+    let payloads = operations
+        .into_iter()
+        .map(|(_, operation_name)| {
+            OperationsPayload {
+                operation_name,
+                ..operations_payload.clone()
+            }
+            .to_json()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Value::Array(payloads))
+}
+
+fn is_subscription(operations_payload: &OperationsPayload) -> bool {
+    let Some(Ok(document)) = operations_payload.query.as_deref().map(parse_query) else {
+        return false;
+    };
+
+    document
+        .operations
+        .iter()
+        .any(|(_, operation)| operation.node.ty == OperationType::Subscription)
+}
+
+/// Open a subscription (over an in-memory `graphql-transport-ws` connection). Its events are
+/// asserted once all operations of the testfile have run (see [assert_subscription_events]).
+async fn subscribe(
+    request_head: MemoryRequestHead,
+    operations_payload: OperationsPayload,
+    router: &SystemRouter,
+    expected_payload: Option<String>,
+    deno_prelude: String,
+) -> Result<OpenSubscription> {
+    let (client_messages, incoming) = mpsc::unbounded();
+
+    let mut server_messages = router.subscription_router().serve(
+        SubscriptionProtocol::GraphQLTransportWS,
+        Box::new(request_head),
+        incoming.boxed(),
+        true,
+    );
+
+    // The connection parameters fall back to the headers of the request (such as `Authorization`)
+    let messages = [
+        json!({"type": "connection_init"}),
+        json!({"id": "1", "type": "subscribe", "payload": operations_payload.to_json()?}),
+        // The server handles messages in order, so the pong confirms that the subscription is in
+        // place (and won't miss events of the following operations)
+        json!({"type": "ping"}),
+    ];
+
+    for message in messages {
+        client_messages.unbounded_send(message.to_string())?;
+    }
+
+    loop {
+        let message = next_subscription_message(&mut server_messages)
+            .await?
+            .ok_or_else(|| anyhow!("Subscription connection closed before subscribing"))?;
+
+        match message["type"].as_str() {
+            Some("connection_ack") => {}
+            Some("pong") => break,
+            _ => bail!("Unexpected message while subscribing: {message}"),
+        }
+    }
+
+    Ok(OpenSubscription {
+        _client_messages: client_messages,
+        server_messages,
+        expected_payload,
+        deno_prelude,
+    })
+}
+
+/// Assert the events delivered to each open subscription (as an array of the payloads, in the
+/// order of delivery) against the subscription's expected response
+async fn assert_subscription_events(ctx: &mut TestfileContext) -> Result<OperationResult> {
+    for mut subscription in std::mem::take(&mut ctx.subscriptions) {
+        let mut payloads = vec![];
+
+        // An error ends the subscription, so the response to assert against may end with it
+        while let Ok(message) = tokio::time::timeout(
+            SUBSCRIPTION_EVENT_TIMEOUT,
+            next_subscription_message(&mut subscription.server_messages),
+        )
+        .await
+        {
+            let Some(mut message) = message? else {
+                break;
+            };
+
+            match message["type"].as_str() {
+                Some("next") => payloads.push(message["payload"].take()),
+                Some("error") => {
+                    payloads.push(json!({"errors": message["payload"].take()}));
+                    break;
+                }
+                Some("complete") => break,
+                _ => bail!("Unexpected subscription message: {message}"),
+            }
+        }
+
+        let actual = Value::Array(payloads);
+
+        match &subscription.expected_payload {
+            Some(expected_payload) => {
+                if let Err(e) = dynamic_assert_using_deno(
+                    expected_payload,
+                    actual,
+                    &subscription.deno_prelude,
+                    &ctx.testvariables,
+                    &HashSet::new(),
+                )
+                .await
+                {
+                    return Ok(OperationResult::AssertFailed(e));
+                }
+            }
+            None => {
+                if actual
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .any(|p| p.get("errors").is_some())
+                {
+                    return Ok(OperationResult::AssertFailed(anyhow!(
+                        "Unexpected error in subscription events: {}",
+                        serde_json::to_string_pretty(&actual)?
+                    )));
+                }
+            }
+        }
+    }
+
+    Ok(OperationResult::AssertPassed)
+}
+
+/// The next text message of the connection parsed as JSON (`None` once the connection is closed)
+async fn next_subscription_message(
+    server_messages: &mut BoxStream<'static, SubscriptionMessage>,
+) -> Result<Option<Value>> {
+    match server_messages.next().await {
+        Some(SubscriptionMessage::Text(text)) => Ok(Some(serde_json::from_str(&text)?)),
+        Some(SubscriptionMessage::Close { code, reason }) => {
+            bail!("Subscription connection closed ({code}): {reason}")
+        }
+        None => Ok(None),
+    }
+}
+
 pub async fn run_query(
     request: impl RequestPayload + Send + Sync,
     router: &SystemRouter,
     cookies: &mut HashMap<String, String>,
 ) -> Value {
+    run_request(request, router, cookies).await.1
+}
+
+/// Route the request, returning the response's status code along with its body
+async fn run_request(
+    request: impl RequestPayload + Send + Sync,
+    router: &SystemRouter,
+    cookies: &mut HashMap<String, String>,
+) -> (http::StatusCode, Value) {
     let mut request = request;
     let res = router.route(&mut request, true).await.unwrap();
 
@@ -486,9 +759,7 @@ pub async fn run_query(
         }
     });
 
-    use futures::StreamExt;
-
-    match res.body {
+    let body = match res.body {
         ResponseBody::Stream(stream) => {
             let bytes = stream
                 .map(|chunks| chunks.unwrap())
@@ -512,7 +783,9 @@ pub async fn run_query(
         }
         ResponseBody::Redirect(..) => Value::String("Unexpected redirect".to_string()),
         ResponseBody::None => Value::String("".to_string()),
-    }
+    };
+
+    (res.status_code, body)
 }
 
 use std::process::Command;
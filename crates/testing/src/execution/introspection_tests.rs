@@ -198,10 +198,17 @@ pub async fn get_introspection_result(serialized_system: SerializableSystem) ->
     })
     .await??;
 
+    let data = result
+        .into_iter()
+        .map(|(name, result)| {
+            result
+                .map(|result| (name, result.body.to_json().unwrap()))
+                .map_err(|e| anyhow!("Error getting introspection result: {:?}", e))
+        })
+        .collect::<Result<HashMap<String, Value>>>()?;
+
     Ok(serde_json::json!({
-        "data": result.iter().map(|(name, result)| {
-            (name.clone(), result.body.to_json().unwrap())
-        }).collect::<HashMap<String, Value>>(),
+        "data": data,
     }))
 }
 
@@ -31,6 +31,10 @@ struct TestfileStage {
     pub variable: Option<String>,
     pub auth: Option<String>,
     pub response: Option<String>,
+    pub method: Option<String>,
+    #[serde(default)]
+    pub batch: bool,
+    pub status: Option<u16>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -82,6 +86,7 @@ impl IntegrationTest {
     pub fn load(
         testfile_path: &PathBuf,
         init_ops: Vec<IntegrationTestOperation>,
+        init_sql: Vec<String>,
     ) -> Result<IntegrationTest> {
         let mut file = File::open(testfile_path).context("Could not open test file")?;
         let mut contents = String::new();
@@ -135,6 +140,9 @@ impl IntegrationTest {
                     expected_payload: stage.response,
                     headers: stage.headers,
                     deno_prelude: stage.deno,
+                    method: stage.method,
+                    batch: stage.batch,
+                    expected_status: stage.status,
                 })
             })
             .collect::<Result<Vec<_>>>()?;
@@ -146,6 +154,7 @@ impl IntegrationTest {
             retries: common.retries,
             extra_envs: common.envs.unwrap_or_default(),
             init_operations: init_ops,
+            init_sql,
             test_operations: test_operation_sequence,
         })
     }
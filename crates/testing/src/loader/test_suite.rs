@@ -11,7 +11,7 @@ use std::path::Path;
 use std::path::PathBuf;
 use wildmatch::WildMatch;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 
 use crate::model::{IntegrationTest, IntegrationTestOperation, TestSuite};
 
@@ -30,7 +30,7 @@ impl TestSuite {
         exo_project_dirs
             .into_iter()
             .map(|exo_project_dir| {
-                let tests = load_tests_dir(&exo_project_dir, &[], pattern)?;
+                let tests = load_tests_dir(&exo_project_dir, &[], &[], pattern)?;
                 Ok(TestSuite {
                     project_dir: exo_project_dir,
                     tests,
@@ -43,6 +43,7 @@ impl TestSuite {
 fn load_tests_dir(
     test_directory: &Path, // directory that contains "src/index.exo"
     init_ops: &[IntegrationTestOperation],
+    init_sql: &[String],
     pattern: &Option<String>,
 ) -> Result<Vec<IntegrationTest>> {
     // Begin directory traversal
@@ -77,26 +78,36 @@ fn load_tests_dir(
     // sort init files lexicographically
     init_files.sort();
 
-    // Parse init files and populate init_ops
+    // Parse init files and populate init_ops (and init_sql for SQL files, which run right after
+    // migrating the schema)
     let mut init_ops = init_ops.to_owned();
+    let mut init_sql = init_sql.to_owned();
 
     for initfile_path in init_files.iter() {
-        let init_op = IntegrationTest::load(initfile_path, vec![])?;
-        init_ops.extend(init_op.test_operations);
+        if initfile_path.extension().unwrap() == "sql" {
+            init_sql.push(
+                std::fs::read_to_string(initfile_path).context("Could not read SQL init file")?,
+            );
+        } else {
+            let init_op = IntegrationTest::load(initfile_path, vec![], vec![])?;
+            init_ops.extend(init_op.test_operations);
+        }
     }
 
     // Parse test files
     let mut testfiles = vec![];
 
     for testfile_path in exotest_files.iter() {
-        let testfile = IntegrationTest::load(testfile_path, init_ops.clone())?;
+        let testfile = IntegrationTest::load(testfile_path, init_ops.clone(), init_sql.clone())?;
         testfiles.push(testfile);
     }
 
     // Recursively parse test files
     for sub_directory in sub_directories.iter() {
         let child_init_ops = init_ops.clone();
-        let child_testfiles = load_tests_dir(sub_directory, &child_init_ops, pattern)?;
+        let child_init_sql = init_sql.clone();
+        let child_testfiles =
+            load_tests_dir(sub_directory, &child_init_ops, &child_init_sql, pattern)?;
         testfiles.extend(child_testfiles)
     }
 
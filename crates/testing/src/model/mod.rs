@@ -27,6 +27,7 @@ pub struct IntegrationTest {
     pub testfile_path: PathBuf,
     pub retries: usize,
     pub init_operations: Vec<IntegrationTestOperation>,
+    pub init_sql: Vec<String>, // SQL to execute after migrating the schema (for example, to create views)
     pub test_operations: Vec<IntegrationTestOperation>,
     pub extra_envs: HashMap<String, String>, // extra envvars to be set when starting the exo server
 }
@@ -40,4 +41,7 @@ pub struct IntegrationTestOperation {
     pub deno_prelude: Option<String>,
     pub auth: Option<String>,    // stringified
     pub headers: Option<String>, // stringified
+    pub method: Option<String>,  // "GET" or "POST" (the default)
+    pub batch: bool,             // send each operation of the document as part of a batch
+    pub expected_status: Option<u16>,
 }
@@ -105,9 +105,8 @@ impl SubsystemResolver for WasmSubsystemResolver {
                     )
                 })
             }
-            OperationType::Subscription => Some(Err(WasmExecutionError::Generic(
-                "Subscriptions are not supported".to_string(),
-            ))),
+            // This subsystem doesn't offer any subscriptions
+            OperationType::Subscription => None,
         };
 
         match operation {
@@ -164,7 +163,7 @@ impl From<WasmExecutionError> for SubsystemResolutionError {
     fn from(e: WasmExecutionError) -> Self {
         match e {
             WasmExecutionError::Authorization => SubsystemResolutionError::Authorization,
-            _ => SubsystemResolutionError::UserDisplayError(e.user_error_message()),
+            _ => SubsystemResolutionError::UserDisplayError(e.user_error_message(), e.code()),
         }
     }
 }
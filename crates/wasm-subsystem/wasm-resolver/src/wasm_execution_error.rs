@@ -7,6 +7,7 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use core_plugin_interface::core_resolver::ErrorCode;
 use thiserror::Error;
 
 use exo_wasm::WasmError;
@@ -44,6 +45,19 @@ impl WasmExecutionError {
         }
     }
 
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            WasmExecutionError::Authorization => ErrorCode::Unauthorized,
+            WasmExecutionError::Wasm(WasmError::Explicit(_)) => ErrorCode::OperationFailed,
+            WasmExecutionError::Delegate(error) => match error.downcast_ref::<WasmExecutionError>()
+            {
+                Some(error) => error.code(),
+                None => ErrorCode::InternalServerError,
+            },
+            _ => ErrorCode::InternalServerError,
+        }
+    }
+
     pub fn explicit_message(&self) -> Option<String> {
         fn root_error<'a>(
             error: &'a (dyn std::error::Error + 'static),
@@ -0,0 +1,10 @@
+CREATE SCHEMA reporting;
+
+-- Selects from the table directly, so returns soft-deleted concerts as well (a `NULL` count means no limit)
+CREATE FUNCTION reporting.top_concerts(count integer) RETURNS SETOF concerts AS $$
+  SELECT * FROM concerts ORDER BY price DESC LIMIT count
+$$ LANGUAGE sql STABLE;
+
+CREATE FUNCTION discount_concerts(percent integer) RETURNS SETOF concerts AS $$
+  UPDATE concerts SET price = price - price * percent / 100 RETURNING *
+$$ LANGUAGE sql;
@@ -0,0 +1,19 @@
+import type { Exograph } from '../generated/exograph.d.ts';
+
+interface ImportReceipt {
+    title: string,
+}
+
+export async function importConcert(exograph: Exograph, title: string): Promise<ImportReceipt> {
+    await exograph.executeQuery(`
+        mutation($title: String!) {
+            createConcert(data: {title: $title}) {
+                id
+            }
+        }
+    `, {
+        "title": title
+    });
+
+    return { title };
+}
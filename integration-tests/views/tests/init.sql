@@ -0,0 +1,11 @@
+CREATE VIEW venue_summaries AS
+  SELECT venues.id AS id, venues.id AS venue_id, COUNT(concerts.id)::INT AS concert_count, COALESCE(SUM(concerts.price), 0)::INT AS total_price
+  FROM venues LEFT JOIN concerts ON concerts.venue_id = venues.id
+  GROUP BY venues.id;
+
+-- Created before `init.gql` adds any data, so empty until refreshed
+CREATE SCHEMA reporting;
+CREATE MATERIALIZED VIEW reporting.venue_stats AS
+  SELECT venues.id AS id, venues.name AS name, MAX(concerts.price)::INT AS max_price
+  FROM venues JOIN concerts ON concerts.venue_id = venues.id
+  GROUP BY venues.id;
@@ -8,13 +8,18 @@
 // by the Apache License, Version 2.0.
 
 use super::{
-    delete::AbstractDelete, insert::AbstractInsert, select::AbstractSelect, update::AbstractUpdate,
+    delete::AbstractDelete, function_select::AbstractFunctionSelect,
+    group_by_select::AbstractGroupBySelect, insert::AbstractInsert,
+    record_select::AbstractRecordSelect, select::AbstractSelect, update::AbstractUpdate,
 };
 
 /// Top-level abstract operation. A transformed version of this can be submitted to a database.
 #[derive(Debug)]
 pub enum AbstractOperation {
     Select(AbstractSelect),
+    RecordSelect(AbstractRecordSelect),
+    FunctionSelect(AbstractFunctionSelect),
+    GroupBySelect(AbstractGroupBySelect),
     Delete(AbstractDelete),
     Insert(AbstractInsert),
     Update(AbstractUpdate),
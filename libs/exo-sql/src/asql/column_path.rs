@@ -13,7 +13,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     sql::{predicate::ParamEquality, SQLParamContainer},
-    ColumnId, Database, TableId,
+    ColumnId, Database, RelationColumnPair, TableId,
 };
 
 /// A link in `ColumnPath` to a column starting at a root table and ending at a leaf column. This
@@ -74,36 +74,72 @@ pub enum ColumnPathLink {
 
 impl ColumnPathLink {
     pub fn relation(
-        self_column_id: ColumnId,
-        linked_column_id: ColumnId,
+        column_pairs: Vec<RelationColumnPair>,
         linked_table_alias: Option<String>,
     ) -> Self {
         Self::Relation(RelationLink {
-            self_column_id,
-            foreign_column_id: linked_column_id,
+            column_pairs,
             linked_table_alias,
+            negated: false,
         })
     }
 
-    pub fn self_column_id(&self) -> ColumnId {
+    /// The same link, but negated (see [`RelationLink::negated`])
+    pub fn negated(self) -> Self {
         match self {
-            ColumnPathLink::Relation(relation) => relation.self_column_id,
-            ColumnPathLink::Leaf(column_id) => *column_id,
+            ColumnPathLink::Relation(relation) => Self::Relation(RelationLink {
+                negated: true,
+                ..relation
+            }),
+            ColumnPathLink::Leaf(_) => panic!("Cannot negate a leaf link"),
+        }
+    }
+
+    pub fn self_table_id(&self) -> TableId {
+        match self {
+            ColumnPathLink::Relation(relation) => relation.self_table_id(),
+            ColumnPathLink::Leaf(column_id) => column_id.table_id,
+        }
+    }
+
+    /// The columns in the current table (one for a leaf, one per column pair for a relation)
+    pub fn self_column_ids(&self) -> Vec<ColumnId> {
+        match self {
+            ColumnPathLink::Relation(relation) => relation
+                .column_pairs
+                .iter()
+                .map(|pair| pair.self_column_id)
+                .collect(),
+            ColumnPathLink::Leaf(column_id) => vec![*column_id],
         }
     }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
 pub struct RelationLink {
-    /// The column in the current table that is linked to the next table.
-    pub self_column_id: ColumnId,
-    /// The column in the next table that is linked to the current table.
-    pub foreign_column_id: ColumnId,
+    /// The columns in the current table paired with the columns in the next table (more than one
+    /// pair if the relation uses a composite primary key).
+    pub column_pairs: Vec<RelationColumnPair>,
     /// Alias that could be used when joining the table, etc. Useful when multiple columns in the self table refers to the same linked column
     /// For example, if "concerts" has "main_venue_id" and "alternative_venue_id" (both link to the venues.id column), we can set linked_table_alias
     /// to "main_venue_id_table" and "alternative_venue_id_table" respectively. Then we can join the venues table twice with different aliases.
     /// The alias name should not matter as long as it is unique within the self table
     pub linked_table_alias: Option<String>,
+    /// Should a predicate through this link select the rows that *don't* have a linked row
+    /// satisfying the rest of the predicate? For example, to pick concerts without any artist named
+    /// "A" (`concerts.id NOT IN (SELECT concert_id FROM concert_artists WHERE ...)`). Such a link
+    /// can be used only in a subselect (and never in a join).
+    pub negated: bool,
+}
+
+impl RelationLink {
+    pub fn self_table_id(&self) -> TableId {
+        self.column_pairs[0].self_column_id.table_id
+    }
+
+    pub fn linked_table_id(&self) -> TableId {
+        self.column_pairs[0].foreign_column_id.table_id
+    }
 }
 
 impl PartialOrd for RelationLink {
@@ -114,21 +150,29 @@ impl PartialOrd for RelationLink {
 
 impl Ord for RelationLink {
     fn cmp(&self, other: &Self) -> Ordering {
-        (self.self_column_id, self.foreign_column_id)
-            .cmp(&(other.self_column_id, other.foreign_column_id))
+        self.column_pairs.cmp(&other.column_pairs)
     }
 }
 
 impl ColumnPathLink {
     /// Determines if this link is a one-to-many link.
     ///
-    /// If the self column is a primary key and the linked column links to a table, then this is a
+    /// If the linked columns aren't the primary key of the linked table, then this is a
     /// one-to-many link. For example, when referring from a venue to concerts, the `venue.id` would
-    /// be the self column and `concert.venue_id` would be the linked column.
+    /// be the self column and `concert.venue_id` would be the linked column. Checking the linked
+    /// columns (instead of the self columns) keeps this correct when a foreign key is also a part
+    /// of a composite primary key (such as `concert_artist.concert_id`).
     pub fn is_one_to_many(&self, database: &Database) -> bool {
         match self {
-            ColumnPathLink::Relation(RelationLink { self_column_id, .. }) => {
-                self_column_id.get_column(database).is_pk
+            ColumnPathLink::Relation(relation) => {
+                let linked_pk_column_ids = database.get_pk_column_ids(relation.linked_table_id());
+
+                relation.column_pairs.len() != linked_pk_column_ids.len()
+                    || relation
+                        .column_pairs
+                        .iter()
+                        .zip(linked_pk_column_ids.iter())
+                        .any(|(pair, pk_column_id)| &pair.foreign_column_id != pk_column_id)
             }
             ColumnPathLink::Leaf(_) => false,
         }
@@ -185,8 +229,17 @@ impl PhysicalColumnPath {
         self.0.iter().any(|link| link.is_one_to_many(database))
     }
 
+    pub fn has_negated(&self) -> bool {
+        self.0.iter().any(|link| {
+            matches!(
+                link,
+                ColumnPathLink::Relation(RelationLink { negated: true, .. })
+            )
+        })
+    }
+
     pub fn lead_table_id(&self) -> TableId {
-        self.0[0].self_column_id().table_id
+        self.0[0].self_table_id()
     }
 
     pub fn push(mut self, link: ColumnPathLink) -> Self {
@@ -196,10 +249,7 @@ impl PhysicalColumnPath {
         assert!(
             matches!(
                 self.0.last().unwrap(),
-                ColumnPathLink::Relation(RelationLink {
-                    foreign_column_id,
-                    ..
-                }) if foreign_column_id.table_id == link.self_column_id().table_id
+                ColumnPathLink::Relation(relation) if relation.linked_table_id() == link.self_table_id()
             ),
             "Expected link to point to next table"
         );
@@ -215,10 +265,7 @@ impl PhysicalColumnPath {
         // the last link must be a relation and its table must be the same as the new link's self table
         assert!(matches!(
             self.0.last().unwrap(),
-            ColumnPathLink::Relation(RelationLink {
-                foreign_column_id,
-                ..
-            }) if foreign_column_id.table_id == tail.0[0].self_column_id().table_id
+            ColumnPathLink::Relation(relation) if relation.linked_table_id() == tail.0[0].self_table_id()
         ));
 
         self.0.extend(tail.0);
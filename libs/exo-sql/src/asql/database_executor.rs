@@ -16,10 +16,16 @@ use crate::{
             database_client::{DatabaseClient, TransactionWrapper},
             database_client_manager::DatabaseClientManager,
         },
-        transaction::{TransactionScript, TransactionStepResult},
+        refresh::RefreshMaterializedView,
+        set_config::SetConfig,
+        sql_operation::SQLOperation,
+        transaction::{
+            ConcreteTransactionStep, TableUsage, TransactionScript, TransactionStep,
+            TransactionStepResult,
+        },
     },
     transform::{pg::Postgres, transformer::OperationTransformer},
-    Database,
+    Database, TableId,
 };
 
 use super::abstract_operation::AbstractOperation;
@@ -45,6 +51,53 @@ impl DatabaseExecutor {
             .with_tx(database, &self.database_client, transaction_script)
             .await
     }
+
+    /// Set a configuration parameter for the rest of the transaction (such as the acting user
+    /// recorded by the history triggers of audited tables).
+    ///
+    /// Since the parameter is local to the transaction, this also ensures that subsequent
+    /// operations execute in the same transaction.
+    pub async fn set_local(
+        &self,
+        name: &str,
+        value: &str,
+        tx_holder: &mut TransactionHolder,
+        database: &Database,
+    ) -> Result<(), DatabaseError> {
+        tx_holder.ensure_transaction();
+
+        let mut transaction_script = TransactionScript::default();
+        transaction_script.add_step(TransactionStep::Concrete(ConcreteTransactionStep::new(
+            SQLOperation::SetConfig(SetConfig {
+                name: name.to_string(),
+                value: value.to_string(),
+            }),
+        )));
+
+        tx_holder
+            .with_tx(database, &self.database_client, transaction_script)
+            .await
+            .map(|_| ())
+    }
+
+    /// Re-compute the content of a materialized view (in the current transaction, if any, so that
+    /// subsequent operations see the refreshed content).
+    pub async fn refresh_materialized_view(
+        &self,
+        table_id: TableId,
+        tx_holder: &mut TransactionHolder,
+        database: &Database,
+    ) -> Result<(), DatabaseError> {
+        let mut transaction_script = TransactionScript::default();
+        transaction_script.add_step(TransactionStep::Concrete(ConcreteTransactionStep::new(
+            SQLOperation::RefreshMaterializedView(RefreshMaterializedView { table_id }),
+        )));
+
+        tx_holder
+            .with_tx(database, &self.database_client, transaction_script)
+            .await
+            .map(|_| ())
+    }
 }
 
 // TransactionHolder holds raw pointers to two objects: `client` and `transaction`.
@@ -62,8 +115,16 @@ pub struct TransactionHolder {
     transaction: Option<*mut TransactionWrapper<'static>>,
     finalized: AtomicBool,
     needs_transaction: AtomicBool,
+    /// Savepoints (innermost last) started through [TransactionHolder::begin_savepoint] along
+    /// with whether they have been created (since the transaction is started lazily, they are
+    /// created along with the next work)
+    savepoints: Vec<bool>,
+    /// The tables accessed by the work done so far (whether committed or not)
+    table_usage: TableUsage,
 }
 
+const SAVEPOINT_NAME: &str = "exo_savepoint";
+
 /// # Safety
 ///
 /// This is needed to mark mut pointers in TransactionHolder as Send
@@ -109,7 +170,10 @@ impl TransactionHolder {
         };
 
         match tx {
-            Some(tx) => work.execute(database, tx).await,
+            Some(tx) => {
+                self.create_requested_savepoints(tx).await?;
+                work.execute(database, tx, &mut self.table_usage).await
+            }
 
             None => {
                 // first, grab a client if none are available
@@ -137,13 +201,18 @@ impl TransactionHolder {
                             .load(std::sync::atomic::Ordering::SeqCst)
                     {
                         let mut tx = Box::new(client.transaction().await?);
-                        let res = work.execute(database, tx.deref_mut().deref_mut()).await;
+                        self.create_requested_savepoints(tx.deref_mut().deref_mut())
+                            .await?;
+                        let res = work
+                            .execute(database, tx.deref_mut().deref_mut(), &mut self.table_usage)
+                            .await;
 
                         self.transaction = Some(Box::leak(tx));
 
                         res
                     } else {
-                        work.execute(database, client.deref_mut()).await
+                        work.execute(database, client.deref_mut(), &mut self.table_usage)
+                            .await
                     }
                 }
             }
@@ -175,8 +244,59 @@ impl TransactionHolder {
         Ok(())
     }
 
+    pub fn table_usage(&self) -> &TableUsage {
+        &self.table_usage
+    }
+
     pub fn ensure_transaction(&self) {
         self.needs_transaction
             .store(true, std::sync::atomic::Ordering::SeqCst);
     }
+
+    /// Mark the start of work that [TransactionHolder::end_savepoint] may undo without aborting the
+    /// rest of the transaction (for example, after a failed statement, Postgres rejects any further
+    /// statements in the same transaction unless we roll back to a savepoint).
+    pub fn begin_savepoint(&mut self) {
+        self.savepoints.push(false);
+    }
+
+    /// Release the innermost savepoint started through [TransactionHolder::begin_savepoint] or, if
+    /// `rollback` is true, undo the work done since then.
+    pub async fn end_savepoint(&mut self, rollback: bool) -> Result<(), DatabaseError> {
+        let created = self.savepoints.pop().unwrap_or(false);
+
+        // SAFETY: this should always be de-referenceable when it is a Some(_)
+        let tx = unsafe {
+            self.transaction
+                .map(|ptr| ptr.as_mut().unwrap().deref_mut())
+        };
+
+        match tx {
+            Some(tx) if created => {
+                // Savepoints of the same name nest, and these statements apply to the innermost one
+                let statement = if rollback {
+                    format!(
+                        "ROLLBACK TO SAVEPOINT {SAVEPOINT_NAME}; RELEASE SAVEPOINT {SAVEPOINT_NAME}"
+                    )
+                } else {
+                    format!("RELEASE SAVEPOINT {SAVEPOINT_NAME}")
+                };
+                tx.batch_execute(&statement).await?;
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    async fn create_requested_savepoints(
+        &mut self,
+        tx: &mut tokio_postgres::Transaction<'_>,
+    ) -> Result<(), DatabaseError> {
+        for created in self.savepoints.iter_mut().filter(|created| !**created) {
+            tx.batch_execute(&format!("SAVEPOINT {SAVEPOINT_NAME}"))
+                .await?;
+            *created = true;
+        }
+        Ok(())
+    }
 }
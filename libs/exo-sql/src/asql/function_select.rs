@@ -0,0 +1,28 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use crate::{PhysicalTableName, SQLParamContainer};
+
+use super::select::AbstractSelect;
+
+/// A select evaluated against the rows returned by a function such as
+/// `"reporting"."top_concerts"($1)` (instead of the rows currently in the table).
+///
+/// The function must return rows of the table's type (for example, `RETURNS SETOF concerts`), so
+/// that the selection (along with its predicate) applies to the returned rows. Relations of the
+/// rows are still resolved through the database.
+#[derive(Debug)]
+pub struct AbstractFunctionSelect {
+    /// The name of the function, which may be qualified by a schema just like a table name
+    pub function_name: PhysicalTableName,
+    /// The arguments in the order of the function's parameters (`None` for a `NULL` argument)
+    pub args: Vec<Option<SQLParamContainer>>,
+    /// The select to evaluate. Its table stands for the returned rows.
+    pub select: AbstractSelect,
+}
@@ -0,0 +1,69 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use crate::{
+    sql::predicate::{ParamEquality, Predicate},
+    ColumnId, Function, Limit, Offset, Ordering, SQLParamContainer, TableId,
+};
+
+use super::{predicate::AbstractPredicate, selection::AliasedSelectionElement};
+
+/// A value available once the rows have been grouped. Used in the predicate on the groups and the
+/// order by clause.
+#[derive(Debug, PartialEq, Clone)]
+pub enum GroupColumn {
+    /// One of the columns the rows are grouped by
+    Grouped(ColumnId),
+    /// An aggregate over the rows of the group such as `count("concerts"."id")`
+    Aggregate(Function),
+    /// A literal value
+    Param(SQLParamContainer),
+}
+
+impl ParamEquality for GroupColumn {
+    fn param_eq(&self, other: &Self) -> Option<bool> {
+        match (self, other) {
+            (Self::Param(v1), Self::Param(v2)) => Some(v1 == v2),
+            _ => None,
+        }
+    }
+}
+
+/// A predicate over the groups (that is, the `HAVING` clause)
+pub type GroupPredicate = Predicate<GroupColumn>;
+
+/// Represents an abstract select that groups the rows of a table and selects a row for each group.
+///
+/// For example, to compute the number of concerts and the average price for each venue with at
+/// least two concerts:
+///
+/// ```sql
+/// SELECT "venue_id", count("id"), avg("price") FROM "concerts" GROUP BY "venue_id" HAVING count("id") >= 2
+/// ```
+#[derive(Debug)]
+pub struct AbstractGroupBySelect {
+    /// The table to select from
+    pub table_id: TableId,
+    /// The columns to group the rows by
+    pub group_by: Vec<ColumnId>,
+    /// The elements to select for each group. These may refer only to the grouped columns (through
+    /// `SelectionElement::Physical`) and aggregates over the rows of the group (through
+    /// `SelectionElement::Function`) and cannot include sub-selects.
+    pub selection: Vec<AliasedSelectionElement>,
+    /// The predicate to filter rows before grouping them
+    pub predicate: AbstractPredicate,
+    /// The predicate to filter the groups
+    pub having: GroupPredicate,
+    /// The order of the groups
+    pub order_by: Vec<(GroupColumn, Ordering)>,
+    /// The offset
+    pub offset: Option<Offset>,
+    /// The limit
+    pub limit: Option<Limit>,
+}
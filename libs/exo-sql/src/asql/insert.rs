@@ -22,7 +22,11 @@
 //! ```
 //!
 //! Here, concerts created will have their `venue_id` set to the id of the venue being created.
+//!
+//! An insert may also specify what to do when a row conflicts with an existing one (see
+//! [`OnConflictUpdate`]), which allows us to execute upsert mutations such as `upsertVenue`.
 
+use super::predicate::AbstractPredicate;
 use super::select::AbstractSelect;
 use crate::sql::column::Column;
 use crate::{ColumnId, OneToManyId, TableId};
@@ -35,6 +39,22 @@ pub struct AbstractInsert {
     pub rows: Vec<InsertionRow>,
     /// The selection to return
     pub selection: AbstractSelect,
+    /// Update the existing row (instead of failing) when a row to be inserted conflicts with it
+    pub on_conflict: Option<OnConflictUpdate>,
+}
+
+/// Update an existing row that conflicts with the row being inserted
+/// (`INSERT ... ON CONFLICT (<conflict_columns>) DO UPDATE SET ... WHERE <predicate>`).
+///
+/// The columns supplied for the inserted row (other than the conflict columns) are updated with
+/// the values supplied.
+#[derive(Debug)]
+pub struct OnConflictUpdate {
+    /// Columns with a unique constraint (or the primary key) to detect the conflict
+    pub conflict_columns: Vec<ColumnId>,
+    /// The predicate the existing row must satisfy to be updated (typically the update access
+    /// predicate). A row that doesn't satisfy it is neither inserted nor updated.
+    pub predicate: AbstractPredicate,
 }
 
 /// A logical row to be inserted (see `InsertionElement` for more details).
@@ -61,7 +81,7 @@ impl InsertionRow {
 
 #[derive(Debug)]
 pub struct NestedInsertion {
-    /// The relation with the parent element (the self_pk_column_id is the parent table's pk column and the self_column_id is the column in the table being inserted that refers to the the parent table)
+    /// The relation with the parent element (the self columns are the parent table's pk columns and the foreign columns are the columns in the table being inserted that refer to the the parent table)
     pub relation_id: OneToManyId,
     pub insertions: Vec<InsertionRow>,
 }
@@ -11,10 +11,13 @@ pub mod abstract_operation;
 pub mod column_path;
 pub mod database_executor;
 pub mod delete;
+pub mod function_select;
+pub mod group_by_select;
 pub mod insert;
 pub mod order_by;
 
 pub mod predicate;
+pub mod record_select;
 pub mod select;
 pub mod selection;
 
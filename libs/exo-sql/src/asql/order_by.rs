@@ -7,9 +7,12 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use crate::{sql::order::Ordering, ColumnPath, VectorDistanceFunction};
+use crate::{
+    sql::{order::Ordering, text_search::TextSearchMode},
+    AbstractPredicate, ColumnPath, Function, SQLParamContainer, VectorDistanceFunction,
+};
 
-use super::column_path::PhysicalColumnPath;
+use super::column_path::{PhysicalColumnPath, RelationLink};
 
 /// Represents an abstract order-by clause
 #[derive(Debug)]
@@ -19,6 +22,25 @@ pub struct AbstractOrderBy(pub Vec<(AbstractOrderByExpr, Ordering)>);
 pub enum AbstractOrderByExpr {
     Column(PhysicalColumnPath),
     VectorDistance(ColumnPath, ColumnPath, VectorDistanceFunction),
+    /// The rank of the document (a `tsvector` column) for the query text parsed using the text
+    /// search configuration (such as "english")
+    TextSearchRank(
+        PhysicalColumnPath,
+        SQLParamContainer,
+        String,
+        TextSearchMode,
+    ),
+    /// An aggregate (such as `max("concerts"."price")`) over the rows linked through a one-to-many
+    /// relation (such as `venues -> concerts`). The path leads to the relation's self column (such
+    /// as `venues.id`), so any table along the way gets joined as usual. The predicate (over the
+    /// linked table's columns) limits the aggregated rows, for example, to leave out soft-deleted
+    /// ones.
+    Aggregate(
+        PhysicalColumnPath,
+        RelationLink,
+        Function,
+        AbstractPredicate,
+    ),
 }
 
 impl AbstractOrderBy {
@@ -34,6 +56,8 @@ impl AbstractOrderBy {
                         _ => None,
                     })
                     .collect(),
+                AbstractOrderByExpr::TextSearchRank(path, _, _, _) => vec![path],
+                AbstractOrderByExpr::Aggregate(path, _, _, _) => vec![path],
             })
             .collect()
     }
@@ -35,7 +35,13 @@ impl AbstractPredicate {
             | AbstractPredicate::JsonContainedBy(l, r)
             | AbstractPredicate::JsonMatchKey(l, r)
             | AbstractPredicate::JsonMatchAnyKey(l, r)
-            | AbstractPredicate::JsonMatchAllKeys(l, r) => vec![l, r],
+            | AbstractPredicate::JsonMatchAllKeys(l, r)
+            | AbstractPredicate::ArrayContains(l, r)
+            | AbstractPredicate::ArrayContainedBy(l, r)
+            | AbstractPredicate::ArrayOverlaps(l, r)
+            | AbstractPredicate::ArrayAnyEq(l, r)
+            | AbstractPredicate::ArrayLength(l, _, r)
+            | AbstractPredicate::TextSearch(l, r, _, _) => vec![l, r],
 
             AbstractPredicate::VectorDistance(c1, c2, _, _, c3) => vec![c1, c2, c3],
 
@@ -0,0 +1,27 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use serde_json::Value;
+
+use super::select::AbstractSelect;
+
+/// A select evaluated against a single row supplied as a JSON object (instead of the rows
+/// currently in the table).
+///
+/// This allows evaluating a selection (along with its predicate) against a row that may no longer
+/// be in the database (for example, a row that has just been deleted). Relations of the row are
+/// still resolved through the database.
+#[derive(Debug)]
+pub struct AbstractRecordSelect {
+    /// The row as a JSON object keyed by column names. Columns missing from the object are
+    /// treated as `NULL`.
+    pub record: Value,
+    /// The select to evaluate. Its table stands for the supplied row.
+    pub select: AbstractSelect,
+}
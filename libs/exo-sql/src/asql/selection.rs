@@ -26,6 +26,13 @@ impl AliasedSelectionElement {
     }
 }
 
+/// Allows nesting aliased elements inside a [`SelectionElement::Object`]
+impl From<AliasedSelectionElement> for (String, SelectionElement) {
+    fn from(element: AliasedSelectionElement) -> Self {
+        (element.alias, element.column)
+    }
+}
+
 /// The cardinality of a json aggregate
 #[derive(Debug)]
 pub enum SelectionCardinality {
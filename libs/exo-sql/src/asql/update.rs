@@ -80,7 +80,7 @@ pub struct AbstractUpdate {
 /// In our example, the `update: [{id: 100, artist: {id: 10}, rank: 2}, {id: 101, artist: {id: 10}, role: "accompanying"}]` part
 #[derive(Debug)]
 pub struct NestedAbstractUpdate {
-    /// The relation with the parent table. In our example, this would be `OneToMany { self_pk_column_id: concert.id, foreign_column_id: concert_artist.concert_id}`
+    /// The relation with the parent table. In our example, this would be `OneToMany { column_pairs: [(concert.id, concert_artist.concert_id)] }`
     pub nesting_relation: OneToMany,
     /// The update to apply to the nested table
     pub update: AbstractUpdate,
@@ -110,7 +110,7 @@ impl NestedAbstractInsertSet {
         }
 
         assert!(
-            all_same(ops.iter().map(|op| op.relation_column_id)),
+            all_same(ops.iter().map(|op| &op.relation_column_ids)),
             "All nested inserts must be for the same relation"
         );
         assert!(
@@ -131,8 +131,9 @@ impl NestedAbstractInsertSet {
 
 #[derive(Debug)]
 pub struct NestedAbstractInsert {
-    /// Same as `NestedAbstractUpdate::relation_column_id`
-    pub relation_column_id: ColumnId,
+    /// The columns in the nested table that refer to the parent table (in the order of the parent
+    /// table's primary key columns). In our example, this would be `[concert_artist.concert_id]`
+    pub relation_column_ids: Vec<ColumnId>,
     /// The insert to apply to the nested table
     pub insert: AbstractInsert,
 }
@@ -47,12 +47,18 @@ pub mod database_error;
 /// Public types at the root level of this crate
 pub use asql::{
     abstract_operation::AbstractOperation,
-    column_path::{ColumnPath, ColumnPathLink, PhysicalColumnPath},
+    column_path::{ColumnPath, ColumnPathLink, PhysicalColumnPath, RelationLink},
     database_executor::{DatabaseExecutor, TransactionHolder},
     delete::AbstractDelete,
-    insert::{AbstractInsert, ColumnValuePair, InsertionElement, InsertionRow, NestedInsertion},
+    function_select::AbstractFunctionSelect,
+    group_by_select::{AbstractGroupBySelect, GroupColumn, GroupPredicate},
+    insert::{
+        AbstractInsert, ColumnValuePair, InsertionElement, InsertionRow, NestedInsertion,
+        OnConflictUpdate,
+    },
     order_by::{AbstractOrderBy, AbstractOrderByExpr},
     predicate::AbstractPredicate,
+    record_select::AbstractRecordSelect,
     select::AbstractSelect,
     selection::{AliasedSelectionElement, Selection, SelectionCardinality, SelectionElement},
     update::{
@@ -72,12 +78,25 @@ pub use sql::{
     offset::Offset,
     order::Ordering,
     physical_column::{ColumnId, FloatBits, IntBits, PhysicalColumn, PhysicalColumnType},
-    physical_table::{PhysicalIndex, PhysicalTable, PhysicalTableName},
+    physical_enum::PhysicalEnum,
+    physical_table::{
+        PhysicalIndex, PhysicalTable, PhysicalTableName, TableKind, ACTING_USER_SETTING,
+        HISTORY_CHANGED_AT_COLUMN_NAME, HISTORY_CHANGED_BY_COLUMN_NAME, HISTORY_ID_COLUMN_NAME,
+        HISTORY_NEW_ROW_COLUMN_NAME, HISTORY_OLD_ROW_COLUMN_NAME, HISTORY_OPERATION_COLUMN_NAME,
+    },
     predicate::{CaseSensitivity, NumericComparator, ParamEquality, Predicate},
-    relation::{ManyToOne, ManyToOneId, OneToMany, OneToManyId, RelationId},
+    relation::{
+        ManyToMany, ManyToManyId, ManyToOne, ManyToOneId, OneToMany, OneToManyId,
+        RelationColumnPair, RelationId,
+    },
+    text_search::{TextSearchMode, DEFAULT_TEXT_SEARCH_LANGUAGE},
+    transaction::TableUsage,
     vector::{VectorDistanceFunction, DEFAULT_VECTOR_SIZE},
     SQLBytes, SQLParam, SQLParamContainer,
 };
 
+#[cfg(feature = "postgres-url")]
+pub use sql::connect::notification_listener::NotificationListener;
+
 #[cfg(feature = "bigdecimal")]
 pub use pg_bigdecimal::BigDecimal;
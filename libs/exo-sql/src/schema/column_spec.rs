@@ -11,6 +11,7 @@ use std::fmt::Write;
 
 use crate::database_error::DatabaseError;
 use crate::sql::connect::database_client::DatabaseClient;
+use crate::sql::text_search::text_search_vector_expression;
 use crate::{
     Database, FloatBits, IntBits, ManyToOne, PhysicalColumn, PhysicalColumnType, PhysicalTableName,
 };
@@ -63,6 +64,9 @@ pub enum ColumnTypeSpec {
         foreign_table_name: PhysicalTableName,
         foreign_pk_column_name: String,
         foreign_pk_type: Box<ColumnTypeSpec>,
+        /// The name shared by all columns of a composite foreign key (`None` for a single-column
+        /// foreign key). The foreign key constraint is created for the group as a whole.
+        group_name: Option<String>,
     },
     Float {
         bits: FloatBits,
@@ -71,6 +75,14 @@ pub enum ColumnTypeSpec {
         precision: Option<usize>,
         scale: Option<usize>,
     },
+    Enum {
+        enum_name: String,
+    },
+    /// A generated `tsvector` column (see [`PhysicalColumnType::TextSearchVector`])
+    TextSearchVector {
+        language: String,
+        columns: Vec<String>,
+    },
 }
 
 impl ColumnSpec {
@@ -92,10 +104,15 @@ impl ColumnSpec {
             Some(t) => Some(t),
             None => {
                 // Query to find the type of the column and the # of dimensions if the type is an array
+                // Enum types (and arrays of them) are user-defined, so we also query the name of the
+                // enum (if any) instead of parsing the formatted type
                 let db_type_query = format!(
                     "
-                    SELECT format_type(atttypid, atttypmod), attndims
+                    SELECT format_type(atttypid, atttypmod), attndims, enum_type.typname AS enum_name
                     FROM pg_attribute
+                    JOIN pg_type column_type ON column_type.oid = atttypid
+                    LEFT JOIN pg_type enum_type ON enum_type.typtype = 'e' AND enum_type.oid =
+                        (CASE WHEN column_type.typcategory = 'A' THEN column_type.typelem ELSE column_type.oid END)
                     WHERE attrelid = '{}'::regclass AND attname = '{column_name}'",
                     table_name.fully_qualified_name()
                 );
@@ -104,6 +121,7 @@ impl ColumnSpec {
                 let row = rows.first().unwrap();
 
                 let mut sql_type: String = row.get("format_type");
+                let enum_name: Option<String> = row.get("enum_name");
 
                 let dims = {
                     // depending on the version of postgres, the type of `attndims` is either `i16`
@@ -124,15 +142,51 @@ impl ColumnSpec {
                 // So we manually query how many dimensions the column has and append `[]` to
                 // the type
                 sql_type += &"[]".repeat(if dims == 0 { 0 } else { (dims - 1) as usize });
-                match ColumnTypeSpec::from_string(&sql_type) {
-                    Ok(t) => Some(t),
-                    Err(e) => {
-                        issues.push(Issue::Warning(format!(
-                            "skipped column `{}.{column_name}` ({e})",
+                match enum_name {
+                    Some(enum_name) => Some(
+                        (0..dims).fold(ColumnTypeSpec::Enum { enum_name }, |typ, _| {
+                            ColumnTypeSpec::Array { typ: Box::new(typ) }
+                        }),
+                    ),
+                    // A text search column is described by its generation expression
+                    None if sql_type == "tsvector" => {
+                        let generation_expression_query = format!(
+                            "
+                            SELECT pg_get_expr(adbin, adrelid) AS generation_expression
+                            FROM pg_attrdef
+                            JOIN pg_attribute ON attrelid = adrelid AND attnum = adnum
+                            WHERE attrelid = '{}'::regclass AND attname = '{column_name}' AND attgenerated = 's'",
                             table_name.fully_qualified_name()
-                        )));
-                        None
+                        );
+
+                        let generation_expression: Option<String> = client
+                            .query(generation_expression_query.as_str(), &[])
+                            .await?
+                            .first()
+                            .map(|row| row.get("generation_expression"));
+
+                        let typ = generation_expression
+                            .as_deref()
+                            .and_then(ColumnTypeSpec::from_text_search_expression);
+
+                        if typ.is_none() {
+                            issues.push(Issue::Warning(format!(
+                                "skipped column `{}.{column_name}` (only generated text search columns are supported)",
+                                table_name.fully_qualified_name()
+                            )));
+                        }
+                        typ
                     }
+                    None => match ColumnTypeSpec::from_string(&sql_type) {
+                        Ok(t) => Some(t),
+                        Err(e) => {
+                            issues.push(Issue::Warning(format!(
+                                "skipped column `{}.{column_name}` ({e})",
+                                table_name.fully_qualified_name()
+                            )));
+                            None
+                        }
+                    },
                 }
             }
         };
@@ -223,7 +277,12 @@ impl ColumnSpec {
         } = self
             .typ
             .to_sql(table_spec, &self.name, self.is_auto_increment);
-        let pk_str = if self.is_pk { " PRIMARY KEY" } else { "" };
+        // A composite primary key is declared at the table level (see `TableSpec::creation_sql`)
+        let pk_str = if self.is_pk && table_spec.pk_column_names().len() == 1 {
+            " PRIMARY KEY"
+        } else {
+            ""
+        };
         let not_null_str = if !self.is_nullable && !self.is_pk {
             // primary keys are implied to be not null
             " NOT NULL"
@@ -323,19 +382,30 @@ impl ColumnSpec {
                 .map(|relation_id| relation_id.deref(database));
 
             match relation {
-                Some(ManyToOne {
-                    foreign_pk_column_id,
-                    ..
-                }) => {
+                Some(ManyToOne { column_pairs, .. }) => {
+                    let foreign_pk_column_id = column_pairs
+                        .iter()
+                        .find(|pair| pair.self_column_id == column_id)
+                        .unwrap()
+                        .foreign_column_id;
                     let foreign_pk_column = foreign_pk_column_id.get_column(database);
                     let foreign_table = database.get_table(foreign_pk_column.table_id);
 
+                    let group_name = (column_pairs.len() > 1).then(|| {
+                        foreign_key_group_name(
+                            column_pairs
+                                .iter()
+                                .map(|pair| pair.self_column_id.get_column(database).name.as_str()),
+                        )
+                    });
+
                     ColumnTypeSpec::ColumnReference {
                         foreign_table_name: foreign_table.name.clone(),
                         foreign_pk_column_name: foreign_pk_column.name.clone(),
                         foreign_pk_type: Box::new(ColumnTypeSpec::from_physical(
                             foreign_pk_column.typ.clone(),
                         )),
+                        group_name,
                     }
                 }
                 None => ColumnTypeSpec::from_physical(column.typ),
@@ -490,6 +560,35 @@ impl ColumnTypeSpec {
         }
     }
 
+    /// Create a text search column type given the generation expression of a `tsvector` column.
+    ///
+    /// Postgres normalizes the expression we generate (see [`ColumnTypeSpec::to_sql`]), so for
+    /// example, `to_tsvector('english', coalesce("title", '') || ' ' || coalesce("body", ''))`
+    /// is reported as `to_tsvector('english'::regconfig, ((COALESCE(title, ''::text) || ' '::text)
+    /// || COALESCE(body, ''::text)))`.
+    fn from_text_search_expression(expression: &str) -> Option<ColumnTypeSpec> {
+        let language_re = Regex::new(r"^to_tsvector\('([^']+)'::regconfig,").unwrap();
+        let column_re = Regex::new(r#"COALESCE\(("(?:[^"]|"")+"|[^,\s]+),"#).unwrap();
+
+        let language = language_re
+            .captures(expression)?
+            .get(1)?
+            .as_str()
+            .to_string();
+        let columns: Vec<_> = column_re
+            .captures_iter(expression)
+            .map(|captures| {
+                let column = &captures[1];
+                match column.strip_prefix('"').and_then(|c| c.strip_suffix('"')) {
+                    Some(quoted) => quoted.replace("\"\"", "\""),
+                    None => column.to_string(),
+                }
+            })
+            .collect();
+
+        (!columns.is_empty()).then_some(ColumnTypeSpec::TextSearchVector { language, columns })
+    }
+
     pub fn to_database_type(&self) -> PhysicalColumnType {
         match self {
             ColumnTypeSpec::Int { bits } => PhysicalColumnType::Int { bits: *bits },
@@ -523,6 +622,15 @@ impl ColumnTypeSpec {
                 precision: *precision,
                 scale: *scale,
             },
+            ColumnTypeSpec::Enum { enum_name } => PhysicalColumnType::Enum {
+                enum_name: enum_name.clone(),
+            },
+            ColumnTypeSpec::TextSearchVector { language, columns } => {
+                PhysicalColumnType::TextSearchVector {
+                    language: language.clone(),
+                    columns: columns.clone(),
+                }
+            }
         }
     }
 
@@ -606,6 +714,13 @@ impl ColumnTypeSpec {
             ColumnTypeSpec::ColumnReference {
                 foreign_table_name, ..
             } => (foreign_table_name.name.clone(), "".to_string()),
+
+            ColumnTypeSpec::Enum { enum_name } => (enum_name.clone(), "".to_string()),
+
+            // Not a field by itself, but represented by the type's `@fullTextSearch` annotation
+            ColumnTypeSpec::TextSearchVector { .. } => {
+                ("TextSearchVector".to_string(), "".to_string())
+            }
         }
     }
 
@@ -749,6 +864,21 @@ impl ColumnTypeSpec {
                 post_statements: vec![],
             },
 
+            Self::Enum { enum_name } => SchemaStatement {
+                statement: format!("\"{enum_name}\""),
+                pre_statements: vec![],
+                post_statements: vec![],
+            },
+
+            Self::TextSearchVector { language, columns } => SchemaStatement {
+                statement: format!(
+                    "tsvector GENERATED ALWAYS AS ({}) STORED",
+                    text_search_vector_expression(language, columns)
+                ),
+                pre_statements: vec![],
+                post_statements: vec![],
+            },
+
             Self::Array { typ } => {
                 // 'unwrap' nested arrays all the way to the underlying primitive type
 
@@ -777,11 +907,31 @@ impl ColumnTypeSpec {
             Self::ColumnReference {
                 foreign_table_name,
                 foreign_pk_type,
+                group_name,
                 ..
             } => {
                 let mut sql_statement =
                     foreign_pk_type.to_sql(table_spec, column_name, is_auto_increment);
 
+                // The columns of a composite foreign key share a single constraint, which we
+                // create along with the last column of the group
+                let (constraint_base_name, fk_columns) = match group_name {
+                    Some(group_name) => {
+                        let group_column_names = table_spec.foreign_key_group_columns(group_name);
+                        if group_column_names.last().map(|name| name.as_str()) != Some(column_name)
+                        {
+                            return sql_statement;
+                        }
+                        (group_name.as_str(), group_column_names)
+                    }
+                    None => (column_name, vec![column_name.to_string()]),
+                };
+                let fk_columns_str = fk_columns
+                    .iter()
+                    .map(|name| format!("\"{name}\""))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
                 let foreign_table_str = match &foreign_table_name.schema {
                     Some(schema_name) => {
                         format!("\"{}\".\"{}\"", schema_name, foreign_table_name.name)
@@ -792,11 +942,11 @@ impl ColumnTypeSpec {
                 let constraint_name = format!(
                     "{}_{}_fk",
                     table_spec.name.fully_qualified_name_with_sep("_"),
-                    column_name
+                    constraint_base_name
                 );
 
                 let foreign_constraint = format!(
-                    r#"ALTER TABLE {} ADD CONSTRAINT "{constraint_name}" FOREIGN KEY ("{column_name}") REFERENCES {foreign_table_str};"#,
+                    r#"ALTER TABLE {} ADD CONSTRAINT "{constraint_name}" FOREIGN KEY ({fk_columns_str}) REFERENCES {foreign_table_str};"#,
                     table_spec.sql_name()
                 );
 
@@ -831,6 +981,16 @@ impl ColumnTypeSpec {
             PhysicalColumnType::Numeric { precision, scale } => {
                 ColumnTypeSpec::Numeric { precision, scale }
             }
+            PhysicalColumnType::Enum { enum_name } => ColumnTypeSpec::Enum { enum_name },
+            PhysicalColumnType::TextSearchVector { language, columns } => {
+                ColumnTypeSpec::TextSearchVector { language, columns }
+            }
         }
     }
 }
+
+/// The group name for the columns of a composite foreign key (for example,
+/// `venue_tenant_id_venue_id` for the `venue_tenant_id` and `venue_id` columns)
+pub(crate) fn foreign_key_group_name<'a>(column_names: impl Iterator<Item = &'a str>) -> String {
+    column_names.collect::<Vec<_>>().join("_")
+}
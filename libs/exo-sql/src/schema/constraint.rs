@@ -23,9 +23,10 @@ pub(super) struct PrimaryKeyConstraint {
 
 pub(super) struct ForeignKeyConstraint {
     pub(super) _constraint_name: String,
-    pub(super) self_columns: HashSet<String>,
+    /// The columns in this table (paired by position with `foreign_columns`)
+    pub(super) self_columns: Vec<String>,
     pub(super) foreign_table: PhysicalTableName,
-    pub(super) foreign_columns: HashSet<String>,
+    pub(super) foreign_columns: Vec<String>,
 }
 
 #[derive(Debug)]
@@ -97,9 +98,9 @@ impl Constraints {
             .filter(|(contype, _, _)| *contype == 'f')
             .map(|(_, conname, condef)| {
                 let matches = FOREIGN_KEY_RE.captures_iter(condef).next().unwrap();
-                let self_columns = Self::parse_column_list(&matches[1]); // name of the column
-                let foreign_table = matches[2].to_owned(); // name of the table the column refers to
-                let foreign_columns = Self::parse_column_list(&matches[3]); // name of the column in the referenced table
+                let self_columns = Self::parse_ordered_column_list(&matches[1]); // names of the columns
+                let foreign_table = matches[2].to_owned(); // name of the table the columns refer to
+                let foreign_columns = Self::parse_ordered_column_list(&matches[3]); // names of the columns in the referenced table
 
                 ForeignKeyConstraint {
                     _constraint_name: conname.to_string(),
@@ -134,6 +135,12 @@ impl Constraints {
     }
 
     fn parse_column_list(column_list: &str) -> HashSet<String> {
+        Self::parse_ordered_column_list(column_list)
+            .into_iter()
+            .collect()
+    }
+
+    fn parse_ordered_column_list(column_list: &str) -> Vec<String> {
         // Basically just split the string on commas and remove the quotes (the regex takes care of the quotes)
         LIST_RE
             .captures_iter(column_list)
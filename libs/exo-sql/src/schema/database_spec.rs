@@ -12,11 +12,14 @@ use std::collections::HashSet;
 use crate::{
     database_error::DatabaseError, schema::column_spec::ColumnSpec,
     sql::connect::database_client::DatabaseClient, Database, ManyToOne, PhysicalColumn,
-    PhysicalIndex, PhysicalTable, PhysicalTableName, TableId,
+    PhysicalEnum, PhysicalIndex, PhysicalTable, PhysicalTableName, RelationColumnPair, TableId,
+    TableKind, ACTING_USER_SETTING, HISTORY_CHANGED_BY_COLUMN_NAME, HISTORY_NEW_ROW_COLUMN_NAME,
+    HISTORY_OLD_ROW_COLUMN_NAME, HISTORY_OPERATION_COLUMN_NAME,
 };
 
 use super::{
     column_spec::ColumnTypeSpec,
+    enum_spec::EnumSpec,
     function_spec::FunctionSpec,
     index_spec::IndexSpec,
     issue::WithIssues,
@@ -28,17 +31,24 @@ use super::{
 pub struct DatabaseSpec {
     pub tables: Vec<TableSpec>,
     pub functions: Vec<FunctionSpec>,
+    pub enums: Vec<EnumSpec>,
 }
 
 impl DatabaseSpec {
     pub fn new(tables: Vec<TableSpec>, functions: Vec<FunctionSpec>) -> Self {
-        Self { tables, functions }
+        Self {
+            tables,
+            functions,
+            enums: vec![],
+        }
     }
 
-    /// Non-public schemas required by this database spec.
+    /// Non-public schemas required by this database spec (a view's schema is created along with the
+    /// view outside of Exograph).
     pub fn required_schemas(&self) -> HashSet<String> {
         self.tables
             .iter()
+            .filter(|table| !table.kind.is_view())
             .flat_map(|table| table.name.schema.clone())
             .collect()
     }
@@ -54,6 +64,15 @@ impl DatabaseSpec {
     pub fn to_database(self) -> Database {
         let mut database = Database::default();
 
+        database.enums = self
+            .enums
+            .into_iter()
+            .map(|enum_spec| PhysicalEnum {
+                name: enum_spec.name,
+                values: enum_spec.values,
+            })
+            .collect();
+
         // Step 1: Create tables (without columns)
         let tables: Vec<(TableId, Vec<ColumnSpec>, Vec<IndexSpec>)> = self
             .tables
@@ -102,47 +121,68 @@ impl DatabaseSpec {
 
                 let column_ids = database.get_column_ids(*table_id);
 
-                column_ids.into_iter().flat_map(|self_column_id| {
+                // The columns of a composite foreign key form a single relation, so we collect
+                // the pairs by the group name (a single-column foreign key is a group by itself)
+                let mut groups: Vec<(Option<&String>, Vec<RelationColumnPair>, String)> = vec![];
+
+                for self_column_id in column_ids {
                     let column = &table.columns[self_column_id.column_index];
                     let column_spec = column_specs
                         .iter()
                         .find(|column_spec| column_spec.name == column.name)
                         .unwrap();
 
-                    match &column_spec.typ {
-                        ColumnTypeSpec::ColumnReference {
-                            foreign_table_name,
-                            foreign_pk_column_name,
-                            ..
-                        } => {
-                            let foreign_table_id =
-                                database.get_table_id(foreign_table_name).unwrap();
-                            let foreign_pk_column_id = database
-                                .get_column_id(foreign_table_id, foreign_pk_column_name)
-                                .unwrap();
-                            // Roughly match the behavior in type_builder.rs, where we set up the
-                            // alias to the pluralized field name, which in typical setup matches
-                            // the table name.
-
-                            // TODO: Make unit tests compare statements semantically, not lexically
-                            // so setting up aliases consistently is same as not setting them up in
-                            // case aliases are unnecessary.
-                            let foreign_table_alias = Some(if column.name.ends_with("_id") {
-                                let base_name = &column.name[..column.name.len() - 3];
-                                let plural_suffix =
-                                    if base_name.ends_with('s') { "es" } else { "s" };
-                                format!("{base_name}{plural_suffix}")
-                            } else {
-                                column.name.clone()
-                            });
-
-                            Some(ManyToOne {
-                                self_column_id,
-                                foreign_pk_column_id,
-                                foreign_table_alias,
-                            })
+                    if let ColumnTypeSpec::ColumnReference {
+                        foreign_table_name,
+                        foreign_pk_column_name,
+                        group_name,
+                        ..
+                    } = &column_spec.typ
+                    {
+                        let foreign_table_id = database.get_table_id(foreign_table_name).unwrap();
+                        let foreign_pk_column_id = database
+                            .get_column_id(foreign_table_id, foreign_pk_column_name)
+                            .unwrap();
+                        let pair = RelationColumnPair {
+                            self_column_id,
+                            foreign_column_id: foreign_pk_column_id,
+                        };
+
+                        match groups.iter_mut().find(|(existing_group_name, _, _)| {
+                            group_name.is_some() && existing_group_name == &group_name.as_ref()
+                        }) {
+                            Some((_, pairs, _)) => pairs.push(pair),
+                            None => groups.push((
+                                group_name.as_ref(),
+                                vec![pair],
+                                group_name.clone().unwrap_or_else(|| column.name.clone()),
+                            )),
                         }
-                        _ => None,
+                    }
+                }
+
+                groups.into_iter().map(|(_, mut column_pairs, name)| {
+                    // Keep the pairs in the order of the foreign table's primary key columns
+                    column_pairs.sort_by_key(|pair| pair.foreign_column_id);
+
+                    // Roughly match the behavior in type_builder.rs, where we set up the
+                    // alias to the pluralized field name, which in typical setup matches
+                    // the table name.
+
+                    // TODO: Make unit tests compare statements semantically, not lexically
+                    // so setting up aliases consistently is same as not setting them up in
+                    // case aliases are unnecessary.
+                    let foreign_table_alias = Some(if name.ends_with("_id") {
+                        let base_name = &name[..name.len() - 3];
+                        let plural_suffix = if base_name.ends_with('s') { "es" } else { "s" };
+                        format!("{base_name}{plural_suffix}")
+                    } else {
+                        name
+                    });
+
+                    ManyToOne {
+                        column_pairs,
+                        foreign_table_alias,
                     }
                 })
             })
@@ -160,14 +200,24 @@ impl DatabaseSpec {
             .tables()
             .into_iter()
             .map(|(_, table)| {
-                let (trigger_specs, function_specs) = match Self::update_trigger(table) {
+                let (mut trigger_specs, mut function_specs) = match Self::update_trigger(table) {
                     Some((trigger, function)) => (vec![trigger], vec![function]),
                     None => (vec![], vec![]),
                 };
 
+                if let Some((triggers, function)) = Self::notify_triggers(table) {
+                    trigger_specs.extend(triggers);
+                    function_specs.push(function);
+                }
+
+                if let Some((triggers, function)) = Self::audit_triggers(table) {
+                    trigger_specs.extend(triggers);
+                    function_specs.push(function);
+                }
+
                 all_function_specs.extend(function_specs);
 
-                TableSpec::new(
+                let table_spec = TableSpec::new(
                     table.name.clone(),
                     table
                         .columns
@@ -186,11 +236,28 @@ impl DatabaseSpec {
                         })
                         .collect(),
                     trigger_specs,
-                )
+                );
+
+                TableSpec {
+                    kind: table.kind,
+                    ..table_spec
+                }
             })
             .collect();
 
-        DatabaseSpec::new(tables, all_function_specs)
+        let enums = database
+            .enums
+            .iter()
+            .map(|physical_enum| {
+                EnumSpec::new(physical_enum.name.clone(), physical_enum.values.clone())
+            })
+            .collect();
+
+        DatabaseSpec {
+            tables,
+            functions: all_function_specs,
+            enums,
+        }
     }
 
     /// Creates a new schema specification from an SQL database.
@@ -198,11 +265,16 @@ impl DatabaseSpec {
         client: &DatabaseClient,
     ) -> Result<WithIssues<DatabaseSpec>, DatabaseError> {
         const SCHEMAS_QUERY: &str =
-            "SELECT DISTINCT table_schema FROM information_schema.tables WHERE table_schema != 'information_schema' AND table_schema NOT LIKE 'pg_%' AND table_type <> 'SYSTEM VIEW'";
+            "SELECT DISTINCT table_schema FROM information_schema.tables WHERE table_schema != 'information_schema' AND table_schema NOT LIKE 'pg_%' AND table_type <> 'SYSTEM VIEW' UNION SELECT schemaname FROM pg_matviews";
 
-        // Query to get a list of all the tables in the database
+        // Query to get a list of all the tables (and views) in the database
         const TABLE_NAMES_QUERY: &str =
-            "SELECT table_name FROM information_schema.tables WHERE table_schema = $1";
+            "SELECT table_name, table_type FROM information_schema.tables WHERE table_schema = $1";
+
+        // Query to get a list of all the materialized views in the database (which are not part of
+        // `information_schema.tables`)
+        const MATERIALIZED_VIEW_NAMES_QUERY: &str =
+            "SELECT matviewname FROM pg_matviews WHERE schemaname = $1";
 
         let mut issues = Vec::new();
         let mut tables = Vec::new();
@@ -219,17 +291,37 @@ impl DatabaseSpec {
                 Some(raw_schema_name.clone())
             };
 
+            let mut table_names = vec![];
+
             for table_row in client
                 .query(TABLE_NAMES_QUERY, &[&raw_schema_name])
                 .await
                 .map_err(DatabaseError::Delegate)?
             {
+                let table_type: String = table_row.get("table_type");
+                let kind = if table_type == "VIEW" {
+                    TableKind::View
+                } else {
+                    TableKind::Table
+                };
+                table_names.push((table_row.get("table_name"), kind));
+            }
+
+            for view_row in client
+                .query(MATERIALIZED_VIEW_NAMES_QUERY, &[&raw_schema_name])
+                .await
+                .map_err(DatabaseError::Delegate)?
+            {
+                table_names.push((view_row.get("matviewname"), TableKind::MaterializedView));
+            }
+
+            for (name, kind) in table_names {
                 let table_name = PhysicalTableName {
-                    name: table_row.get("table_name"),
+                    name,
                     schema: schema_name.clone(),
                 };
 
-                let mut table = TableSpec::from_live_db(client, table_name).await?;
+                let mut table = TableSpec::from_live_db(client, table_name, kind).await?;
                 issues.append(&mut table.issues);
                 tables.push(table.value);
             }
@@ -241,8 +333,18 @@ impl DatabaseSpec {
         } = FunctionSpec::from_live_db(client).await?;
         issues.extend(functions_issues);
 
+        let WithIssues {
+            value: enums,
+            issues: enums_issues,
+        } = EnumSpec::from_live_db(client).await?;
+        issues.extend(enums_issues);
+
         Ok(WithIssues {
-            value: DatabaseSpec { tables, functions },
+            value: DatabaseSpec {
+                tables,
+                functions,
+                enums,
+            },
             issues,
         })
     }
@@ -293,4 +395,133 @@ impl DatabaseSpec {
             None
         }
     }
+
+    /// Triggers (one each for insert, update, and delete) to publish changes to rows of the table
+    /// on its notification channel.
+    ///
+    /// The payload is a JSON object of the form `{"op": "INSERT", "row": {...}}`. Since Postgres
+    /// limits the payload size (to 8000 bytes by default), if the row is too large, we send
+    /// only its primary key columns and mark the payload as partial: `{"op": "INSERT",
+    /// "partial": true, "row": {"id": 1}}`.
+    fn notify_triggers(table: &PhysicalTable) -> Option<(Vec<TriggerSpec>, FunctionSpec)> {
+        if !table.notify_changes {
+            return None;
+        }
+
+        let table_name = table.name.fully_qualified_name_with_sep("_");
+
+        let pk_object = table
+            .columns
+            .iter()
+            .filter(|column| column.is_pk)
+            .map(|column| format!("'{0}', changed_row.\"{0}\"", column.name))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let channel = table.notification_channel();
+
+        let function_name = format!("exograph_notify_{table_name}");
+        let function_body = format!(
+            "DECLARE changed_row RECORD; payload TEXT; \
+            BEGIN \
+            IF TG_OP = 'DELETE' THEN changed_row := OLD; ELSE changed_row := NEW; END IF; \
+            payload := json_build_object('op', TG_OP, 'row', row_to_json(changed_row))::text; \
+            IF octet_length(payload) >= 8000 THEN \
+            payload := json_build_object('op', TG_OP, 'partial', true, 'row', json_build_object({pk_object}))::text; \
+            END IF; \
+            PERFORM pg_notify('{channel}', payload); \
+            RETURN NULL; \
+            END;"
+        );
+
+        let triggers = [
+            (TriggerEvent::Insert, "insert"),
+            (TriggerEvent::Update, "update"),
+            (TriggerEvent::Delete, "delete"),
+        ]
+        .into_iter()
+        .map(|(event, event_name)| TriggerSpec {
+            name: format!("exograph_notify_on_{event_name}_{table_name}"),
+            function: function_name.clone(),
+            timing: TriggerTiming::After,
+            orientation: TriggerOrientation::Row,
+            event,
+            table: table.name.clone(),
+        })
+        .collect();
+
+        Some((
+            triggers,
+            FunctionSpec {
+                name: function_name,
+                body: function_body,
+                language: "plpgsql".into(),
+            },
+        ))
+    }
+
+    /// Triggers (one each for insert, update, and delete) to record changes to rows of an audited
+    /// table in its history table.
+    ///
+    /// Each change records the primary key of the row, the operation, the acting user (the entry
+    /// for the table in the transaction-local [`ACTING_USER_SETTING`] parameter), and the row
+    /// before and after the change as JSON (`NULL` before an insert and after a delete).
+    fn audit_triggers(table: &PhysicalTable) -> Option<(Vec<TriggerSpec>, FunctionSpec)> {
+        let history_table = table.history_table.as_ref()?;
+
+        let table_name = table.name.fully_qualified_name_with_sep("_");
+
+        let pk_columns = table.get_pk_physical_columns();
+        let pk_column_names = pk_columns
+            .iter()
+            .map(|column| format!("\"{}\"", column.name))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let pk_values = pk_columns
+            .iter()
+            .map(|column| format!("changed_row.\"{}\"", column.name))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let acting_user_key = table.name.fully_qualified_name_with_sep(".");
+
+        let function_name = format!("exograph_audit_{table_name}");
+        let function_body = format!(
+            "DECLARE changed_row RECORD; \
+            BEGIN \
+            IF TG_OP = 'DELETE' THEN changed_row := OLD; ELSE changed_row := NEW; END IF; \
+            INSERT INTO {history_table_name} ({pk_column_names}, \"{HISTORY_OPERATION_COLUMN_NAME}\", \"{HISTORY_CHANGED_BY_COLUMN_NAME}\", \"{HISTORY_OLD_ROW_COLUMN_NAME}\", \"{HISTORY_NEW_ROW_COLUMN_NAME}\") \
+            VALUES ({pk_values}, TG_OP, NULLIF(NULLIF(current_setting('{ACTING_USER_SETTING}', true), '')::jsonb ->> '{acting_user_key}', ''), \
+            CASE WHEN TG_OP = 'INSERT' THEN NULL ELSE to_jsonb(OLD) END, \
+            CASE WHEN TG_OP = 'DELETE' THEN NULL ELSE to_jsonb(NEW) END); \
+            RETURN NULL; \
+            END;",
+            history_table_name = history_table.sql_name()
+        );
+
+        let triggers = [
+            (TriggerEvent::Insert, "insert"),
+            (TriggerEvent::Update, "update"),
+            (TriggerEvent::Delete, "delete"),
+        ]
+        .into_iter()
+        .map(|(event, event_name)| TriggerSpec {
+            name: format!("exograph_audit_on_{event_name}_{table_name}"),
+            function: function_name.clone(),
+            timing: TriggerTiming::After,
+            orientation: TriggerOrientation::Row,
+            event,
+            table: table.name.clone(),
+        })
+        .collect();
+
+        Some((
+            triggers,
+            FunctionSpec {
+                name: function_name,
+                body: function_body,
+                language: "plpgsql".into(),
+            },
+        ))
+    }
 }
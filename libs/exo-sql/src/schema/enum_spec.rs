@@ -0,0 +1,153 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use crate::{database_error::DatabaseError, sql::connect::database_client::DatabaseClient};
+
+use super::{
+    column_spec::{ColumnSpec, ColumnTypeSpec},
+    issue::WithIssues,
+    op::SchemaOp,
+    table_spec::TableSpec,
+};
+
+/// An enum type in the database (`CREATE TYPE <name> AS ENUM (<values>)`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumSpec {
+    pub name: String,
+    pub values: Vec<String>,
+}
+
+const ENUMS_QUERY: &str = r#"
+SELECT t.typname, e.enumlabel
+FROM pg_type t
+JOIN pg_enum e ON e.enumtypid = t.oid
+JOIN pg_namespace n ON n.oid = t.typnamespace
+WHERE n.nspname = 'public'
+ORDER BY t.typname, e.enumsortorder
+"#;
+
+impl EnumSpec {
+    pub fn new(name: String, values: Vec<String>) -> Self {
+        Self { name, values }
+    }
+
+    pub async fn from_live_db(
+        client: &DatabaseClient,
+    ) -> Result<WithIssues<Vec<EnumSpec>>, DatabaseError> {
+        let mut enums: Vec<EnumSpec> = vec![];
+
+        for row in client.query(ENUMS_QUERY, &[]).await?.iter() {
+            let name: String = row.get("typname");
+            let value: String = row.get("enumlabel");
+
+            match enums.last_mut() {
+                Some(last) if last.name == name => last.values.push(value),
+                _ => enums.push(EnumSpec::new(name, vec![value])),
+            }
+        }
+
+        Ok(WithIssues {
+            value: enums,
+            issues: vec![],
+        })
+    }
+
+    /// Compute the changes to go from `self` to `new` (which must have the same name).
+    ///
+    /// Postgres can add values to an enum, but not remove or reorder them. In those cases, we
+    /// replace the type: rename the existing one, create the new one, convert the existing columns
+    /// (in `old_tables`) that use it, and drop the old one. The conversion will fail if any row
+    /// holds a value that no longer exists.
+    pub fn diff<'a>(&'a self, new: &'a Self, old_tables: &'a [TableSpec]) -> Vec<SchemaOp<'a>> {
+        if self.values == new.values {
+            return vec![];
+        }
+
+        if is_subsequence(&self.values, &new.values) {
+            return new
+                .values
+                .iter()
+                .enumerate()
+                .filter(|(_, value)| !self.values.contains(value))
+                .map(|(index, value)| SchemaOp::AddEnumValue {
+                    enum_spec: self,
+                    value,
+                    after: index.checked_sub(1).map(|prev| new.values[prev].as_str()),
+                })
+                .collect();
+        }
+
+        let old_name = format!("{}_old", self.name);
+        let mut changes = vec![
+            SchemaOp::RenameEnum {
+                name: &self.name,
+                new_name: old_name.clone(),
+            },
+            SchemaOp::CreateEnum { enum_spec: new },
+        ];
+
+        for table in old_tables.iter() {
+            for column in table.columns.iter() {
+                if column.typ.enum_name() == Some(&new.name) {
+                    changes.extend(Self::convert_column(table, column));
+                }
+            }
+        }
+
+        changes.push(SchemaOp::DeleteEnum { name: old_name });
+
+        changes
+    }
+
+    /// Convert a column to the (re-created) enum type. Since Postgres can't convert the default
+    /// value along with the type, we drop and restore it.
+    fn convert_column<'a>(table: &'a TableSpec, column: &'a ColumnSpec) -> Vec<SchemaOp<'a>> {
+        match &column.default_value {
+            Some(default_value) => vec![
+                SchemaOp::UnsetColumnDefaultValue { table, column },
+                SchemaOp::ConvertEnumColumn { table, column },
+                SchemaOp::SetColumnDefaultValue {
+                    table,
+                    column,
+                    default_value: default_value.clone(),
+                },
+            ],
+            None => vec![SchemaOp::ConvertEnumColumn { table, column }],
+        }
+    }
+
+    pub fn creation_sql(&self) -> String {
+        let values = self
+            .values
+            .iter()
+            .map(|value| format!("'{}'", value.replace('\'', "''")))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        format!("CREATE TYPE \"{}\" AS ENUM ({values});", self.name)
+    }
+}
+
+impl ColumnTypeSpec {
+    /// The name of the enum type, if this is an enum (or an array of enums) column
+    pub fn enum_name(&self) -> Option<&String> {
+        match self {
+            ColumnTypeSpec::Enum { enum_name } => Some(enum_name),
+            ColumnTypeSpec::Array { typ } => typ.enum_name(),
+            _ => None,
+        }
+    }
+}
+
+/// Are all elements of `sub` present in `sup` in the same relative order?
+fn is_subsequence(sub: &[String], sup: &[String]) -> bool {
+    let mut sup_iter = sup.iter();
+    sub.iter()
+        .all(|value| sup_iter.any(|sup_value| sup_value == value))
+}
@@ -12,8 +12,12 @@ use std::collections::HashSet;
 use crate::schema::{constraint::sorted_comma_list, index_spec::IndexSpec};
 
 use super::{
-    column_spec::ColumnSpec, function_spec::FunctionSpec, statement::SchemaStatement,
-    table_spec::TableSpec, trigger_spec::TriggerSpec,
+    column_spec::{ColumnSpec, ColumnTypeSpec},
+    enum_spec::EnumSpec,
+    function_spec::FunctionSpec,
+    statement::SchemaStatement,
+    table_spec::TableSpec,
+    trigger_spec::TriggerSpec,
 };
 
 /// An execution unit of SQL, representing an operation that can create or destroy resources.
@@ -26,6 +30,29 @@ pub enum SchemaOp<'a> {
         schema: String,
     },
 
+    CreateEnum {
+        enum_spec: &'a EnumSpec,
+    },
+    DeleteEnum {
+        name: String,
+    },
+    RenameEnum {
+        name: &'a str,
+        new_name: String,
+    },
+    AddEnumValue {
+        /// The existing enum (before adding the value)
+        enum_spec: &'a EnumSpec,
+        value: &'a str,
+        /// The value to add the new value after (`None` to add it as the first value)
+        after: Option<&'a str>,
+    },
+    /// Convert an enum column to a re-created enum type (of the same name)
+    ConvertEnumColumn {
+        table: &'a TableSpec,
+        column: &'a ColumnSpec,
+    },
+
     CreateTable {
         table: &'a TableSpec,
     },
@@ -115,6 +142,54 @@ impl SchemaOp<'_> {
                 ..Default::default()
             },
 
+            SchemaOp::CreateEnum { enum_spec } => SchemaStatement {
+                statement: enum_spec.creation_sql(),
+                ..Default::default()
+            },
+            SchemaOp::DeleteEnum { name } => SchemaStatement {
+                statement: format!("DROP TYPE \"{name}\";"),
+                ..Default::default()
+            },
+            SchemaOp::RenameEnum { name, new_name } => SchemaStatement {
+                statement: format!("ALTER TYPE \"{name}\" RENAME TO \"{new_name}\";"),
+                ..Default::default()
+            },
+            SchemaOp::AddEnumValue {
+                enum_spec,
+                value,
+                after,
+            } => {
+                let position = match after {
+                    Some(after) => format!("AFTER '{after}'"),
+                    // Place the value ahead of all existing values
+                    None => format!("BEFORE '{}'", enum_spec.values[0]),
+                };
+                SchemaStatement {
+                    statement: format!(
+                        "ALTER TYPE \"{}\" ADD VALUE '{value}' {position};",
+                        enum_spec.name
+                    ),
+                    ..Default::default()
+                }
+            }
+            SchemaOp::ConvertEnumColumn { table, column } => {
+                let type_name = column.typ.to_sql(table, &column.name, false).statement;
+                // Go through text (or text[] for an array column) to convert between enum types
+                let text_type_name = if matches!(column.typ, ColumnTypeSpec::Array { .. }) {
+                    "text[]"
+                } else {
+                    "text"
+                };
+                SchemaStatement {
+                    statement: format!(
+                        "ALTER TABLE {} ALTER COLUMN \"{name}\" TYPE {type_name} USING \"{name}\"::{text_type_name}::{type_name};",
+                        table.sql_name(),
+                        name = column.name,
+                    ),
+                    ..Default::default()
+                }
+            }
+
             SchemaOp::CreateTable { table } => table.creation_sql(),
             SchemaOp::DeleteTable { table } => table.deletion_sql(),
 
@@ -252,6 +327,12 @@ impl SchemaOp<'_> {
             SchemaOp::CreateSchema { schema } => Some(format!("The schema `{schema}` exists in the model, but does not exist in the database.")),
             SchemaOp::DeleteSchema { .. } => None, // An extra schema in the database is not a problem
 
+            SchemaOp::CreateEnum { enum_spec } => Some(format!("The enum `{}` exists in the model, but does not exist in the database.", enum_spec.name)),
+            SchemaOp::DeleteEnum { .. } => None, // An extra enum in the database is not a problem
+            SchemaOp::RenameEnum { name, .. } => Some(format!("The values of the enum `{name}` in the database do not match the model.")),
+            SchemaOp::AddEnumValue { enum_spec, value, .. } => Some(format!("The enum `{}` in the model has the value `{value}`, which does not exist in the database.", enum_spec.name)),
+            SchemaOp::ConvertEnumColumn { .. } => None, // Reported by `RenameEnum`
+
             SchemaOp::CreateTable { table } => Some(format!("The table `{}` exists in the model, but does not exist in the database.", table.sql_name())),
             SchemaOp::DeleteTable { .. } => None, // An extra table in the database is not a problem
 
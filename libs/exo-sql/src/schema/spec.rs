@@ -7,7 +7,7 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use std::collections::{hash_map::RandomState, hash_set::Difference};
+use std::collections::{hash_map::RandomState, hash_set::Difference, HashSet};
 
 use super::{database_spec::DatabaseSpec, op::SchemaOp};
 
@@ -37,11 +37,48 @@ pub fn diff<'a>(old: &'a DatabaseSpec, new: &'a DatabaseSpec) -> Vec<SchemaOp<'a
         })
     }
 
-    for old_table in old.tables.iter() {
+    // enums must exist before the tables that use them
+    for new_enum in new.enums.iter() {
+        match old
+            .enums
+            .iter()
+            .find(|old_enum| old_enum.name == new_enum.name)
+        {
+            // enum exists, compare values
+            Some(old_enum) => changes.extend(old_enum.diff(new_enum, &old.tables)),
+
+            // new enum
+            None => changes.push(SchemaOp::CreateEnum {
+                enum_spec: new_enum,
+            }),
+        }
+    }
+
+    // Views are defined outside of Exograph, so we neither create, alter, nor drop them (nor a
+    // table with the same name, since that would conflict with the view)
+    let view_names: HashSet<_> = old
+        .tables
+        .iter()
+        .chain(new.tables.iter())
+        .filter(|table| table.kind.is_view())
+        .map(|table| table.sql_name())
+        .collect();
+    let old_tables: Vec<_> = old
+        .tables
+        .iter()
+        .filter(|table| !view_names.contains(&table.sql_name()))
+        .collect();
+    let new_tables: Vec<_> = new
+        .tables
+        .iter()
+        .filter(|table| !view_names.contains(&table.sql_name()))
+        .collect();
+
+    for old_table in old_tables.iter().copied() {
         // try to find a table with the same name in the new spec
-        match new
-            .tables
+        match new_tables
             .iter()
+            .copied()
             .find(|new_table| old_table.sql_name() == new_table.sql_name())
         {
             // table exists, compare columns
@@ -53,9 +90,8 @@ pub fn diff<'a>(old: &'a DatabaseSpec, new: &'a DatabaseSpec) -> Vec<SchemaOp<'a
     }
 
     // try to find a table that needs to be created
-    for new_table in new.tables.iter() {
-        if !old
-            .tables
+    for new_table in new_tables.iter().copied() {
+        if !old_tables
             .iter()
             .any(|old_table| new_table.sql_name() == old_table.sql_name())
         {
@@ -95,6 +131,19 @@ pub fn diff<'a>(old: &'a DatabaseSpec, new: &'a DatabaseSpec) -> Vec<SchemaOp<'a
         }
     }
 
+    // enum removal (after the tables that may use them have been deleted)
+    for old_enum in old.enums.iter() {
+        if !new
+            .enums
+            .iter()
+            .any(|new_enum| new_enum.name == old_enum.name)
+        {
+            changes.push(SchemaOp::DeleteEnum {
+                name: old_enum.name.clone(),
+            })
+        }
+    }
+
     // extension removal
     let extensions_to_drop =
         sorted_strings(old_required_extensions.difference(&new_required_extensions));
@@ -112,16 +161,9 @@ pub fn diff<'a>(old: &'a DatabaseSpec, new: &'a DatabaseSpec) -> Vec<SchemaOp<'a
         })
     }
 
-    // sort changes so that triggers are created after its functions
-    changes.sort_by(|a, b| match (a, b) {
-        (SchemaOp::CreateTrigger { .. }, SchemaOp::CreateFunction { .. }) => {
-            std::cmp::Ordering::Greater
-        }
-        (SchemaOp::CreateFunction { .. }, SchemaOp::CreateTrigger { .. }) => {
-            std::cmp::Ordering::Less
-        }
-        _ => std::cmp::Ordering::Equal,
-    });
+    // move trigger creation to the end (keeping the order otherwise), so that triggers are created
+    // after their functions (and after any tables their functions use)
+    changes.sort_by_key(|change| matches!(change, SchemaOp::CreateTrigger { .. }));
 
     changes
 }
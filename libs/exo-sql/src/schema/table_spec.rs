@@ -11,9 +11,9 @@ use std::collections::{HashMap, HashSet};
 
 use crate::database_error::DatabaseError;
 use crate::sql::connect::database_client::DatabaseClient;
-use crate::{PhysicalTable, PhysicalTableName};
+use crate::{PhysicalTable, PhysicalTableName, TableKind};
 
-use super::column_spec::{ColumnSpec, ColumnTypeSpec};
+use super::column_spec::{foreign_key_group_name, ColumnSpec, ColumnTypeSpec};
 use super::constraint::{sorted_comma_list, Constraints};
 use super::index_spec::IndexSpec;
 use super::issue::WithIssues;
@@ -27,6 +27,7 @@ pub struct TableSpec {
     pub columns: Vec<ColumnSpec>,
     pub indices: Vec<IndexSpec>,
     pub triggers: Vec<TriggerSpec>,
+    pub kind: TableKind,
 }
 
 impl TableSpec {
@@ -41,6 +42,7 @@ impl TableSpec {
             columns,
             indices,
             triggers,
+            kind: TableKind::Table,
         }
     }
 
@@ -49,6 +51,9 @@ impl TableSpec {
             name: self.name.clone(),
             columns: vec![],
             indices: vec![],
+            notify_changes: false,
+            history_table: None,
+            kind: self.kind,
         }
     }
 
@@ -56,6 +61,27 @@ impl TableSpec {
         self.name.sql_name()
     }
 
+    /// The names of the primary key columns (more than one for a composite primary key)
+    pub fn pk_column_names(&self) -> Vec<&str> {
+        self.columns
+            .iter()
+            .filter(|c| c.is_pk)
+            .map(|c| c.name.as_str())
+            .collect()
+    }
+
+    /// The names of the columns in a composite foreign key group (in the order of the referenced
+    /// primary key columns)
+    pub fn foreign_key_group_columns(&self, group_name: &str) -> Vec<String> {
+        self.columns
+            .iter()
+            .filter(|c| {
+                matches!(&c.typ, ColumnTypeSpec::ColumnReference { group_name: Some(name), .. } if name == group_name)
+            })
+            .map(|c| c.name.clone())
+            .collect()
+    }
+
     fn named_unique_constraints(&self) -> HashMap<&String, HashSet<String>> {
         self.columns.iter().fold(HashMap::new(), |mut map, c| {
             {
@@ -68,15 +94,17 @@ impl TableSpec {
         })
     }
 
-    /// Creates a new table specification from an SQL table.
+    /// Creates a new table specification from an SQL table (or view).
     pub(super) async fn from_live_db(
         client: &DatabaseClient,
         table_name: PhysicalTableName,
+        kind: TableKind,
     ) -> Result<WithIssues<TableSpec>, DatabaseError> {
-        // Query to get a list of columns in the table
+        // Query to get a list of columns in the table (`information_schema.columns` doesn't
+        // include the columns of materialized views, so we query `pg_attribute` instead)
         let columns_query = format!(
-            "SELECT column_name FROM information_schema.columns WHERE table_name = '{}' AND table_schema = '{}'",
-            table_name.name, table_name.schema.as_ref().unwrap_or(&"public".to_string())
+            "SELECT attname AS column_name FROM pg_attribute WHERE attrelid = '{}'::regclass AND attnum > 0 AND NOT attisdropped ORDER BY attnum",
+            table_name.fully_qualified_name()
         );
 
         let mut issues = Vec::new();
@@ -86,30 +114,45 @@ impl TableSpec {
         let mut column_type_mapping = HashMap::new();
 
         for foreign_constraint in constraints.foreign_constraints.iter() {
-            // Assumption that there is only one column in the foreign key (for now a correct assumption since we don't support composite keys)
-            let self_column_name = foreign_constraint.self_columns.iter().next().unwrap();
-            let foreign_pk_column_name = foreign_constraint.foreign_columns.iter().next().unwrap();
-
-            let mut column = ColumnSpec::from_live_db(
-                client,
-                &table_name,
-                foreign_pk_column_name,
-                true,
-                None,
-                vec![],
-            )
-            .await?;
-            issues.append(&mut column.issues);
-
-            if let Some(spec) = column.value {
-                column_type_mapping.insert(
-                    self_column_name.clone(),
-                    ColumnTypeSpec::ColumnReference {
-                        foreign_table_name: foreign_constraint.foreign_table.clone(),
-                        foreign_pk_column_name: foreign_pk_column_name.clone(),
-                        foreign_pk_type: Box::new(spec.typ),
-                    },
-                );
+            // A composite foreign key has multiple columns, which are paired (in order) with the
+            // primary key columns of the foreign table
+            let group_name = (foreign_constraint.self_columns.len() > 1).then(|| {
+                foreign_key_group_name(
+                    foreign_constraint
+                        .self_columns
+                        .iter()
+                        .map(|name| name.as_str()),
+                )
+            });
+
+            for (self_column_name, foreign_pk_column_name) in foreign_constraint
+                .self_columns
+                .iter()
+                .zip(foreign_constraint.foreign_columns.iter())
+            {
+                // The foreign key column has the same type as the primary key column it refers to
+                let mut column = ColumnSpec::from_live_db(
+                    client,
+                    &table_name,
+                    self_column_name,
+                    false,
+                    None,
+                    vec![],
+                )
+                .await?;
+                issues.append(&mut column.issues);
+
+                if let Some(spec) = column.value {
+                    column_type_mapping.insert(
+                        self_column_name.clone(),
+                        ColumnTypeSpec::ColumnReference {
+                            foreign_table_name: foreign_constraint.foreign_table.clone(),
+                            foreign_pk_column_name: foreign_pk_column_name.clone(),
+                            foreign_pk_type: Box::new(spec.typ),
+                            group_name: group_name.clone(),
+                        },
+                    );
+                }
             }
         }
 
@@ -163,6 +206,7 @@ impl TableSpec {
                 columns,
                 indices,
                 triggers,
+                kind,
             },
             issues,
         })
@@ -226,18 +270,58 @@ impl TableSpec {
             }
         }
 
+        // Dropping a column also drops its indices
+        let dropped_columns: HashSet<_> = changes
+            .iter()
+            .filter_map(|change| match change {
+                SchemaOp::DeleteColumn { column, .. } => Some(column.name.clone()),
+                _ => None,
+            })
+            .collect();
+
+        // Columns that are dropped and created again (for example, when their type changes)
+        let recreated_columns: HashSet<_> = changes
+            .iter()
+            .filter_map(|change| match change {
+                SchemaOp::CreateColumn { column, .. }
+                    if existing_column_map.contains_key(&column.name) =>
+                {
+                    Some(column.name.clone())
+                }
+                _ => None,
+            })
+            .collect();
+
         for existing_index in self.indices.iter() {
             let new_index = new.indices.iter().find(|i| i.name == existing_index.name);
 
             match new_index {
                 Some(new_index) => {
-                    changes.extend(existing_index.diff(new_index, self, new));
+                    if existing_index
+                        .columns
+                        .iter()
+                        .any(|column| recreated_columns.contains(column))
+                    {
+                        // Dropping a column also drops its indices, so we need to create them again
+                        changes.push(SchemaOp::CreateIndex {
+                            table: new,
+                            index: new_index,
+                        });
+                    } else {
+                        changes.extend(existing_index.diff(new_index, self, new));
+                    }
                 }
                 None => {
-                    changes.push(SchemaOp::DeleteIndex {
-                        table: self,
-                        index: existing_index,
-                    });
+                    if !existing_index
+                        .columns
+                        .iter()
+                        .any(|column| dropped_columns.contains(column))
+                    {
+                        changes.push(SchemaOp::DeleteIndex {
+                            table: self,
+                            index: existing_index,
+                        });
+                    }
                 }
             }
         }
@@ -330,6 +414,18 @@ impl TableSpec {
 
         let table_name = self.sql_name();
 
+        let pk_column_names = self.pk_column_names();
+        let column_stmts = if pk_column_names.len() > 1 {
+            let pk_columns_part = pk_column_names
+                .iter()
+                .map(|name| format!("\"{name}\""))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("{column_stmts},\n\tPRIMARY KEY ({pk_columns_part})")
+        } else {
+            column_stmts
+        };
+
         for (unique_constraint_name, columns) in self.named_unique_constraints().iter() {
             let columns_part = sorted_comma_list(columns, true);
 
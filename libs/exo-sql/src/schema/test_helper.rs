@@ -34,6 +34,7 @@ pub fn pk_reference_column(
             foreign_pk_type: Box::new(ColumnTypeSpec::Int {
                 bits: crate::IntBits::_16,
             }),
+            group_name: None,
         },
         is_pk: false,
         is_auto_increment: false,
@@ -80,3 +81,34 @@ pub fn json_column(name: impl Into<String>) -> ColumnSpec {
         default_value: None,
     }
 }
+
+pub fn int_array_column(name: impl Into<String>) -> ColumnSpec {
+    ColumnSpec {
+        name: name.into(),
+        typ: ColumnTypeSpec::Array {
+            typ: Box::new(ColumnTypeSpec::Int {
+                bits: crate::IntBits::_32,
+            }),
+        },
+        is_pk: false,
+        is_auto_increment: false,
+        is_nullable: false,
+        unique_constraints: vec![],
+        default_value: None,
+    }
+}
+
+pub fn text_search_column(name: impl Into<String>, columns: &[&str]) -> ColumnSpec {
+    ColumnSpec {
+        name: name.into(),
+        typ: ColumnTypeSpec::TextSearchVector {
+            language: "english".to_string(),
+            columns: columns.iter().map(|column| column.to_string()).collect(),
+        },
+        is_pk: false,
+        is_auto_increment: false,
+        is_nullable: true,
+        unique_constraints: vec![],
+        default_value: None,
+    }
+}
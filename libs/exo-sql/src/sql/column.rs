@@ -56,6 +56,16 @@ pub enum Column {
     Null,
     /// A function applied to a column. For example, `count(id)` or `lower(first_name)`.
     Function(Function),
+    /// A row of columns such as `("concerts"."venue_tenant_id", "concerts"."venue_id")`. Used to
+    /// compare multiple columns at once (for example, for a composite foreign key in an `IN`
+    /// predicate).
+    Row(Vec<Column>),
+    /// A column named in the list of selected columns such as `count("concerts"."id") AS "count"`.
+    /// Needed so that an enclosing select can refer to the columns of a sub-select by name.
+    Aliased(Box<Column>, String),
+    /// A column incremented by one such as `"concerts"."version" + 1`. Used to bump a version
+    /// column in an `update <table> set <column> = <column> + 1`.
+    Increment(ColumnId),
 }
 
 #[derive(Debug, PartialEq)]
@@ -133,6 +143,20 @@ impl ExpressionBuilder for Column {
             Column::Null => {
                 builder.push_str("NULL");
             }
+            Column::Row(columns) => {
+                builder.push('(');
+                builder.push_elems(database, columns, ", ");
+                builder.push(')');
+            }
+            Column::Aliased(column, alias) => {
+                column.build(database, builder);
+                builder.push_str(" AS ");
+                builder.push_identifier(alias);
+            }
+            Column::Increment(column_id) => {
+                column_id.get_column(database).build(database, builder);
+                builder.push_str(" + 1");
+            }
         }
     }
 }
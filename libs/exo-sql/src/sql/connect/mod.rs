@@ -11,4 +11,5 @@ pub mod creation;
 pub mod database_client;
 pub mod database_client_manager;
 pub mod database_pool;
+pub mod notification_listener;
 pub mod ssl_config;
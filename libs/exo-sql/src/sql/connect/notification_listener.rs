@@ -0,0 +1,245 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+#![cfg(feature = "postgres-url")]
+
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+use futures::{
+    stream::{self, BoxStream},
+    StreamExt,
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    sync::broadcast,
+};
+use tokio_postgres::{AsyncMessage, Client, Config, Connection};
+
+use crate::database_error::DatabaseError;
+
+use super::ssl_config::SslConfig;
+
+/// Number of notifications buffered per channel before slow subscribers start missing events
+const CHANNEL_CAPACITY: usize = 256;
+
+type Channels = Arc<Mutex<HashMap<String, broadcast::Sender<String>>>>;
+type SharedClient = Arc<tokio::sync::Mutex<Option<Client>>>;
+
+/// Listens to Postgres notifications (`LISTEN`/`NOTIFY`) over a dedicated connection.
+///
+/// A pooled connection cannot be used for this purpose, since notifications are delivered only
+/// to the connection that issued the `LISTEN` command. The listener, therefore, maintains its own
+/// connection (re-established lazily if it drops) and fans out each notification to all
+/// subscribers of the channel. Once the last subscriber of a channel leaves, the listener stops
+/// listening to it.
+pub struct NotificationListener {
+    url: String,
+    client: SharedClient,
+    channels: Channels,
+}
+
+impl NotificationListener {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            client: Arc::new(tokio::sync::Mutex::new(None)),
+            channels: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Subscribe to the payloads sent to the given channel.
+    ///
+    /// The stream ends if the underlying connection is lost (any subsequent call to `listen` will
+    /// reconnect).
+    pub async fn listen(&self, channel: &str) -> Result<BoxStream<'static, String>, DatabaseError> {
+        let mut client = self.client.lock().await;
+
+        if client.as_ref().map(|c| c.is_closed()).unwrap_or(true) {
+            // Any existing senders belong to the lost connection
+            self.channels.lock().unwrap().clear();
+            *client = Some(self.connect().await?);
+        }
+
+        let existing_receiver = self
+            .channels
+            .lock()
+            .unwrap()
+            .get(channel)
+            .map(|sender| sender.subscribe());
+
+        let receiver = match existing_receiver {
+            Some(receiver) => receiver,
+            None => {
+                let (sender, receiver) = broadcast::channel(CHANNEL_CAPACITY);
+                self.channels
+                    .lock()
+                    .unwrap()
+                    .insert(channel.to_string(), sender);
+
+                let listened = client
+                    .as_ref()
+                    .unwrap()
+                    .batch_execute(&format!("LISTEN \"{channel}\""))
+                    .await;
+
+                if let Err(e) = listened {
+                    self.channels.lock().unwrap().remove(channel);
+                    return Err(DatabaseError::Delegate(e)
+                        .with_context(format!("Failed to listen to channel '{channel}'")));
+                }
+
+                receiver
+            }
+        };
+
+        let receiver = ChannelReceiver {
+            channel: channel.to_string(),
+            receiver: Some(receiver),
+            channels: self.channels.clone(),
+            client: self.client.clone(),
+        };
+
+        let stream = stream::unfold(receiver, |mut receiver| async move {
+            let payload = receiver.recv().await?;
+            Some((payload, receiver))
+        });
+
+        Ok(stream.boxed())
+    }
+
+    async fn connect(&self) -> Result<Client, DatabaseError> {
+        let (url, ssl_config) = SslConfig::from_url(&self.url)?;
+
+        let config = Config::from_str(&url).map_err(|e| {
+            DatabaseError::Delegate(e)
+                .with_context("Failed to parse PostgreSQL connection string".into())
+        })?;
+
+        match ssl_config {
+            Some(ssl_config) => {
+                let (config, tls) = ssl_config.updated_config(config)?;
+                let (client, connection) = config.connect(tls).await?;
+                spawn_connection(connection, self.channels.clone());
+                Ok(client)
+            }
+            None => {
+                let (client, connection) = config.connect(tokio_postgres::NoTls).await?;
+                spawn_connection(connection, self.channels.clone());
+                Ok(client)
+            }
+        }
+    }
+}
+
+/// A subscriber's receiver of a channel's notifications. Dropping the last one for a channel
+/// removes the channel and issues `UNLISTEN` for it.
+struct ChannelReceiver {
+    channel: String,
+    /// Always present (until dropped)
+    receiver: Option<broadcast::Receiver<String>>,
+    channels: Channels,
+    client: SharedClient,
+}
+
+impl ChannelReceiver {
+    /// The next payload (`None` once the sender is gone)
+    async fn recv(&mut self) -> Option<String> {
+        let receiver = self.receiver.as_mut()?;
+
+        loop {
+            match receiver.recv().await {
+                Ok(payload) => return Some(payload),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!("Notification subscriber lagged, skipped {skipped} events");
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+impl Drop for ChannelReceiver {
+    fn drop(&mut self) {
+        // Drop our own receiver first, so that it doesn't count as a remaining subscriber
+        self.receiver.take();
+
+        {
+            let mut channels = self.channels.lock().unwrap();
+            match channels.get(&self.channel) {
+                Some(sender) if sender.receiver_count() == 0 => {
+                    channels.remove(&self.channel);
+                }
+                _ => return,
+            }
+        }
+
+        // `UNLISTEN` needs the runtime (which may be shutting down, in which case the connection
+        // is going away anyway)
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+
+        let channel = self.channel.clone();
+        let channels = self.channels.clone();
+        let client = self.client.clone();
+
+        runtime.spawn(async move {
+            // Holding the client lock orders this with any `listen` call for the same channel
+            let client = client.lock().await;
+
+            // A new subscriber started listening to the channel in the meantime
+            if channels.lock().unwrap().contains_key(&channel) {
+                return;
+            }
+
+            if let Some(client) = client.as_ref().filter(|client| !client.is_closed()) {
+                if let Err(e) = client
+                    .batch_execute(&format!("UNLISTEN \"{channel}\""))
+                    .await
+                {
+                    tracing::warn!("Failed to stop listening to channel '{channel}': {e}");
+                }
+            }
+        });
+    }
+}
+
+/// Drive the connection, dispatching notifications to the subscribers of their channel
+fn spawn_connection<S, T>(mut connection: Connection<S, T>, channels: Channels)
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    T: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut messages = stream::poll_fn(move |cx| connection.poll_message(cx));
+
+        while let Some(message) = messages.next().await {
+            match message {
+                Ok(AsyncMessage::Notification(notification)) => {
+                    if let Some(sender) = channels.lock().unwrap().get(notification.channel()) {
+                        // An error here only means that there are no active subscribers
+                        let _ = sender.send(notification.payload().to_string());
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    tracing::error!("Notification connection error: {}", e);
+                    break;
+                }
+            }
+        }
+
+        // Dropping the senders ends all subscriber streams
+        channels.lock().unwrap().clear();
+    });
+}
@@ -9,7 +9,7 @@
 
 use std::fmt::{Debug, Formatter};
 
-use crate::{ColumnId, ManyToOne, PhysicalColumn, PhysicalTable, PhysicalTableName};
+use crate::{ColumnId, ManyToOne, PhysicalColumn, PhysicalEnum, PhysicalTable, PhysicalTableName};
 
 use serde::{Deserialize, Serialize};
 use typed_generational_arena::{Arena, IgnoreGeneration, Index};
@@ -21,6 +21,7 @@ pub type TableId = Index<PhysicalTable, usize, IgnoreGeneration>;
 pub struct Database {
     tables: SerializableSlab<PhysicalTable>,
     pub relations: Vec<ManyToOne>,
+    pub enums: Vec<PhysicalEnum>,
 }
 
 impl Database {
@@ -56,11 +57,14 @@ impl Database {
         })
     }
 
-    pub fn get_pk_column_id(&self, table_id: TableId) -> Option<ColumnId> {
+    /// The primary key columns of the table (more than one for a composite primary key)
+    pub fn get_pk_column_ids(&self, table_id: TableId) -> Vec<ColumnId> {
         let table = self.get_table(table_id);
         table
-            .get_pk_column_index()
+            .get_pk_column_indices()
+            .into_iter()
             .map(|column_index| new_column_id(table_id, column_index))
+            .collect()
     }
 
     pub fn get_column_id(&self, table_id: TableId, column_name: &str) -> Option<ColumnId> {
@@ -87,6 +91,7 @@ impl Default for Database {
         Database {
             tables: SerializableSlab::new(),
             relations: vec![],
+            enums: vec![],
         }
     }
 }
@@ -9,7 +9,7 @@
 
 use maybe_owned::MaybeOwned;
 
-use crate::{Database, OneToMany, SQLParamContainer};
+use crate::{Database, OneToMany};
 
 use super::{
     column::Column,
@@ -89,14 +89,10 @@ impl<'a> TemplateDelete<'a> {
         // Go over all the rows in the previous step and create a concrete update for each row.
         (0..rows)
             .map(|row_index| {
-                let relation_predicate = ConcretePredicate::Eq(
-                    Column::Physical {
-                        column_id: nesting_relation.foreign_column_id,
-                        table_alias: None,
-                    },
-                    Column::Param(SQLParamContainer::from_sql_value(
-                        transaction_context.resolve_value(prev_step_id, row_index, 0),
-                    )),
+                let relation_predicate = transaction_context.row_predicate(
+                    prev_step_id,
+                    row_index,
+                    &nesting_relation.foreign_column_ids(),
                 );
 
                 Delete {
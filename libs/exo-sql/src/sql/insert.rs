@@ -14,6 +14,7 @@ use crate::{Database, PhysicalTable};
 use super::{
     column::{Column, ProxyColumn},
     physical_column::PhysicalColumn,
+    predicate::ConcretePredicate,
     transaction::{TransactionContext, TransactionStepId},
     ExpressionBuilder, SQLBuilder, SQLParamContainer,
 };
@@ -29,12 +30,49 @@ pub struct Insert<'a> {
     pub values_seq: Vec<Vec<MaybeOwned<'a, Column>>>,
     /// The columns to return.
     pub returning: Vec<MaybeOwned<'a, Column>>,
+    /// What to do if a row conflicts with an existing one
+    pub on_conflict: Option<OnConflict<'a>>,
+}
+
+/// The `ON CONFLICT (<conflict_columns>) DO UPDATE SET <column> = EXCLUDED.<column>, ... WHERE
+/// <predicate>` clause of an insert statement.
+#[derive(Debug)]
+pub struct OnConflict<'a> {
+    /// The columns (of the primary key or a unique constraint) to detect the conflict.
+    pub conflict_columns: Vec<&'a PhysicalColumn>,
+    /// The columns to update with the values from the conflicting row.
+    pub update_columns: Vec<&'a PhysicalColumn>,
+    /// The predicate the existing row must satisfy to be updated.
+    pub predicate: ConcretePredicate,
+}
+
+impl<'a> ExpressionBuilder for OnConflict<'a> {
+    fn build(&self, database: &Database, builder: &mut SQLBuilder) {
+        builder.push_str("ON CONFLICT (");
+        builder.without_fully_qualified_column_names(|builder| {
+            builder.push_elems(database, &self.conflict_columns, ", ");
+        });
+        builder.push_str(") DO UPDATE SET ");
+
+        builder.without_fully_qualified_column_names(|builder| {
+            builder.push_iter(self.update_columns.iter(), ", ", |builder, column| {
+                column.build(database, builder);
+                builder.push_str(" = EXCLUDED.");
+                column.build(database, builder);
+            });
+        });
+
+        if self.predicate != ConcretePredicate::True {
+            builder.push_str(" WHERE ");
+            self.predicate.build(database, builder);
+        }
+    }
 }
 
 impl<'a> ExpressionBuilder for Insert<'a> {
     /// Build the insert statement for the form `INSERT INTO <table> (<columns>) VALUES (<values>)
-    /// RETURNING <returning-columns>`. The `RETURNING` clause is omitted if the list of columns to
-    /// return is empty.
+    /// [ON CONFLICT ...] RETURNING <returning-columns>`. The `RETURNING` clause is omitted if the
+    /// list of columns to return is empty.
     fn build(&self, database: &Database, builder: &mut SQLBuilder) {
         builder.push_str("INSERT INTO ");
         self.table.build(database, builder);
@@ -51,6 +89,11 @@ impl<'a> ExpressionBuilder for Insert<'a> {
         });
         builder.push(')');
 
+        if let Some(on_conflict) = &self.on_conflict {
+            builder.push(' ');
+            on_conflict.build(database, builder);
+        }
+
         if !self.returning.is_empty() {
             builder.push_str(" RETURNING ");
             builder.push_elems(database, &self.returning, ", ")
@@ -128,7 +171,77 @@ impl<'a> TemplateInsert<'a> {
                 columns: columns.clone(),
                 values_seq: resolved_cols,
                 returning: returning.iter().map(|ret| ret.into()).collect(),
+                on_conflict: None,
             })
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        schema::{
+            database_spec::DatabaseSpec,
+            table_spec::TableSpec,
+            test_helper::{int_column, pk_column, string_column},
+        },
+        sql::predicate::Predicate,
+        PhysicalTableName,
+    };
+
+    use multiplatform_test::multiplatform_test;
+
+    use super::*;
+
+    #[multiplatform_test]
+    fn insert_on_conflict() {
+        let database = DatabaseSpec::new(
+            vec![TableSpec::new(
+                PhysicalTableName::new("people", None),
+                vec![pk_column("id"), string_column("name"), int_column("age")],
+                vec![],
+                vec![],
+            )],
+            vec![],
+        )
+        .to_database();
+
+        let table_id = database
+            .get_table_id(&PhysicalTableName::new("people", None))
+            .unwrap();
+        let table = database.get_table(table_id);
+        let id_col_id = database.get_column_id(table_id, "id").unwrap();
+        let name_col_id = database.get_column_id(table_id, "name").unwrap();
+        let age_col_id = database.get_column_id(table_id, "age").unwrap();
+
+        let name_col = name_col_id.get_column(&database);
+        let age_col = age_col_id.get_column(&database);
+
+        let insert = Insert {
+            on_conflict: Some(OnConflict {
+                conflict_columns: vec![name_col],
+                update_columns: vec![age_col],
+                predicate: Predicate::Lt(
+                    Column::physical(age_col_id, None),
+                    Column::Param(SQLParamContainer::i32(100)),
+                ),
+            }),
+            ..table.insert(
+                vec![name_col, age_col],
+                vec![vec![
+                    Column::Param(SQLParamContainer::string("Jane".to_string())),
+                    Column::Param(SQLParamContainer::i32(30)),
+                ]],
+                vec![Column::physical(id_col_id, None).into()],
+            )
+        };
+
+        assert_binding!(
+            insert.to_sql(&database),
+            r#"INSERT INTO "people" ("name", "age") VALUES ($1, $2) ON CONFLICT ("name") DO UPDATE SET "age" = EXCLUDED."age" WHERE "people"."age" < $3 RETURNING "people"."id""#,
+            "Jane".to_string(),
+            30,
+            100
+        );
+    }
+}
@@ -34,6 +34,10 @@ impl LeftJoin {
     pub fn left(&self) -> &Table {
         &self.left
     }
+
+    pub fn left_mut(&mut self) -> &mut Table {
+        &mut self.left
+    }
 }
 
 impl ExpressionBuilder for LeftJoin {
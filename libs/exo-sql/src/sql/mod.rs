@@ -25,6 +25,7 @@ pub mod order;
 pub mod physical_column;
 pub mod predicate;
 pub mod relation;
+pub mod text_search;
 pub mod vector;
 
 pub use sql_bytes::SQLBytes;
@@ -40,8 +41,11 @@ pub(crate) mod json_agg;
 pub(crate) mod json_object;
 pub(crate) mod limit;
 pub(crate) mod offset;
+pub(crate) mod physical_enum;
 pub(crate) mod physical_table;
+pub(crate) mod refresh;
 pub(crate) mod select;
+pub(crate) mod set_config;
 pub(crate) mod sql_operation;
 pub(crate) mod table;
 pub(crate) mod transaction;
@@ -7,8 +7,14 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use super::vector::VectorDistanceFunction;
-use crate::{sql::vector::VectorDistance, ColumnId, Database, SQLParamContainer};
+use super::{
+    text_search::{TextSearchMode, TextSearchQuery, TextSearchRank},
+    vector::VectorDistanceFunction,
+};
+use crate::{
+    sql::{select::Select, vector::VectorDistance},
+    Column, ColumnId, Database, Function, SQLParamContainer,
+};
 
 use super::{ExpressionBuilder, SQLBuilder};
 #[derive(Debug, Clone, PartialEq, Eq, Copy)]
@@ -34,6 +40,11 @@ pub enum OrderByElementExpr {
         VectorDistanceOperand,
         VectorDistanceFunction,
     ),
+    TextSearchRank(ColumnId, SQLParamContainer, String, TextSearchMode),
+    /// An aggregate over the rows of a group such as `count("concerts"."id")`
+    Function(Function),
+    /// A subselect that computes a single value such as `(SELECT max("concerts"."price") FROM "concerts" WHERE ...)`
+    SubSelect(Box<Select>),
 }
 
 #[derive(Debug, PartialEq)]
@@ -49,20 +60,27 @@ impl ExpressionBuilder for OrderByElement {
     fn build(&self, database: &Database, builder: &mut SQLBuilder) {
         match &self.0 {
             OrderByElementExpr::Column(column_id) => {
-                let column = column_id.get_column(database);
-                match self.2 {
-                    Some(ref table_alias) => {
-                        builder.push_column_with_table_alias(&column.name, table_alias);
-                    }
-                    None => {
-                        column.build(database, builder);
-                    }
-                }
+                (column_id, self.2.as_ref()).build(database, builder);
             }
             OrderByElementExpr::VectorDistance(lhs, rhs, function) => {
                 VectorDistance::new((lhs, self.2.as_ref()), (rhs, self.2.as_ref()), *function)
                     .build(database, builder);
             }
+            OrderByElementExpr::TextSearchRank(document, text, language, mode) => {
+                TextSearchRank::new(
+                    (document, self.2.as_ref()),
+                    TextSearchQuery::new(Column::Param(text.clone()), language, *mode),
+                )
+                .build(database, builder);
+            }
+            OrderByElementExpr::Function(function) => {
+                function.build(database, builder);
+            }
+            OrderByElementExpr::SubSelect(select) => {
+                builder.push('(');
+                select.build(database, builder);
+                builder.push(')');
+            }
         }
         builder.push_space();
 
@@ -81,19 +99,25 @@ impl ExpressionBuilder for OrderBy {
     }
 }
 
+impl ExpressionBuilder for (&ColumnId, Option<&String>) {
+    fn build(&self, database: &Database, builder: &mut SQLBuilder) {
+        let column = self.0.get_column(database);
+        match &self.1 {
+            Some(table_alias) => {
+                builder.push_column_with_table_alias(&column.name, table_alias);
+            }
+            None => {
+                column.build(database, builder);
+            }
+        }
+    }
+}
+
 impl ExpressionBuilder for (&VectorDistanceOperand, Option<&String>) {
     fn build(&self, database: &Database, builder: &mut SQLBuilder) {
         match &self.0 {
             VectorDistanceOperand::PhysicalColumn(column_id) => {
-                let column = column_id.get_column(database);
-                match &self.1 {
-                    Some(table_alias) => {
-                        builder.push_column_with_table_alias(&column.name, table_alias);
-                    }
-                    None => {
-                        column.build(database, builder);
-                    }
-                }
+                (column_id, self.1).build(database, builder);
             }
             VectorDistanceOperand::Param(param) => {
                 builder.push_param(param.param());
@@ -107,7 +131,7 @@ mod test {
     use std::vec;
 
     use super::*;
-    use crate::schema::test_helper::{int_column, pk_column, string_column};
+    use crate::schema::test_helper::{int_column, pk_column, string_column, text_search_column};
     use crate::schema::{database_spec::DatabaseSpec, table_spec::TableSpec};
     use crate::PhysicalTableName;
 
@@ -185,4 +209,45 @@ mod test {
             );
         }
     }
+
+    #[multiplatform_test]
+    fn text_search_rank() {
+        let database = DatabaseSpec::new(
+            vec![TableSpec::new(
+                PhysicalTableName::new("articles", None),
+                vec![
+                    pk_column("id"),
+                    string_column("title"),
+                    text_search_column("search_vector", &["title"]),
+                ],
+                vec![],
+                vec![],
+            )],
+            vec![],
+        )
+        .to_database();
+
+        let table_id = database
+            .get_table_id(&PhysicalTableName::new("articles", None))
+            .unwrap();
+
+        let search_col = database.get_column_id(table_id, "search_vector").unwrap();
+
+        let order_by = OrderBy(vec![OrderByElement(
+            OrderByElementExpr::TextSearchRank(
+                search_col,
+                SQLParamContainer::str("rust"),
+                "english".to_string(),
+                TextSearchMode::Plain,
+            ),
+            Ordering::Desc,
+            None,
+        )]);
+
+        assert_binding!(
+            order_by.to_sql(&database),
+            r#"ORDER BY ts_rank("articles"."search_vector", plainto_tsquery('english', $1)) DESC"#,
+            "rust"
+        );
+    }
 }
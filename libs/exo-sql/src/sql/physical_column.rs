@@ -14,7 +14,7 @@ use crate::{
 use super::{ExpressionBuilder, SQLBuilder};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use tokio_postgres::types::Type;
+use tokio_postgres::types::{Kind, Type};
 
 /// A column in a physical table
 #[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
@@ -104,6 +104,17 @@ pub enum PhysicalColumnType {
         precision: Option<usize>,
         scale: Option<usize>,
     },
+    /// A Postgres enum type (created with `CREATE TYPE ... AS ENUM`). The values are described by
+    /// the corresponding [`PhysicalEnum`](crate::PhysicalEnum) in the database.
+    Enum {
+        enum_name: String,
+    },
+    /// A `tsvector` column generated from the given (text) columns using the text search
+    /// configuration (such as "english"). Used for full-text search.
+    TextSearchVector {
+        language: String,
+        columns: Vec<String>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -147,6 +158,10 @@ impl PhysicalColumnType {
             PhysicalColumnType::Numeric { precision, scale } => {
                 format!("Numeric with precision: {precision:?}, scale: {scale:?}")
             }
+            PhysicalColumnType::Enum { enum_name } => format!("Enum {enum_name}"),
+            PhysicalColumnType::TextSearchVector { language, columns } => {
+                format!("Text search vector of {columns:?} with language {language}")
+            }
         }
     }
     /// Create a new physical column type given the SQL type string. This is used to reverse-engineer
@@ -294,6 +309,15 @@ impl PhysicalColumnType {
             },
             PhysicalColumnType::Numeric { .. } => Type::NUMERIC,
             PhysicalColumnType::Vector { .. } => Type::FLOAT4_ARRAY,
+            // We don't know the oid of the enum type (nor need it, since the driver infers
+            // parameter types from the statement), so this type serves only to mark enum values
+            PhysicalColumnType::Enum { enum_name } => Type::new(
+                enum_name.clone(),
+                0,
+                Kind::Enum(vec![]),
+                "public".to_string(),
+            ),
+            PhysicalColumnType::TextSearchVector { .. } => Type::TS_VECTOR,
         }
     }
 }
@@ -308,12 +332,21 @@ pub(crate) fn to_pg_array_type(pg_type: &Type) -> Type {
         Type::FLOAT4 => Type::FLOAT4_ARRAY,
         Type::FLOAT8 => Type::FLOAT8_ARRAY,
         Type::BOOL => Type::BOOL_ARRAY,
+        Type::TIMESTAMP => Type::TIMESTAMP_ARRAY,
         Type::TIMESTAMPTZ => Type::TIMESTAMPTZ_ARRAY,
+        Type::DATE => Type::DATE_ARRAY,
+        Type::TIME => Type::TIME_ARRAY,
         Type::TEXT_ARRAY => Type::TEXT_ARRAY,
         Type::VARCHAR => Type::VARCHAR_ARRAY,
         Type::BYTEA => Type::BYTEA_ARRAY,
         Type::UUID => Type::UUID_ARRAY,
         Type::NUMERIC => Type::NUMERIC_ARRAY,
+        _ if matches!(pg_type.kind(), Kind::Enum(_)) => Type::new(
+            format!("_{}", pg_type.name()),
+            0,
+            Kind::Array(pg_type.clone()),
+            pg_type.schema().to_string(),
+        ),
         _ => unimplemented!("Unsupported array type: {:?}", pg_type),
     }
 }
@@ -335,7 +368,12 @@ impl ColumnId {
         database
             .relations
             .iter()
-            .position(|relation| &relation.self_column_id == self)
+            .position(|relation| {
+                relation
+                    .column_pairs
+                    .iter()
+                    .any(|pair| &pair.self_column_id == self)
+            })
             .map(ManyToOneId)
     }
 
@@ -0,0 +1,20 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use serde::{Deserialize, Serialize};
+
+/// An enum type in the database (created with `CREATE TYPE <name> AS ENUM (<values>)`). Columns of
+/// this type use [`PhysicalColumnType::Enum`](crate::PhysicalColumnType::Enum) with the same name.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct PhysicalEnum {
+    /// The name of the enum type.
+    pub name: String,
+    /// The values of the enum in the declared order (which is also the sort order in Postgres).
+    pub values: Vec<String>,
+}
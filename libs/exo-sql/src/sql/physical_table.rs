@@ -70,8 +70,63 @@ pub struct PhysicalTable {
     pub columns: Vec<PhysicalColumn>,
 
     pub indices: Vec<PhysicalIndex>,
+
+    /// Whether changes to rows of this table should be published (through `NOTIFY`) on the
+    /// [notification channel](PhysicalTable::notification_channel).
+    pub notify_changes: bool,
+
+    /// The table recording the history of changes to rows of this table (through triggers), if
+    /// this table is audited. Besides the primary key columns of this table, the history table has
+    /// the [`HISTORY_ID_COLUMN_NAME`], [`HISTORY_OPERATION_COLUMN_NAME`],
+    /// [`HISTORY_CHANGED_AT_COLUMN_NAME`], [`HISTORY_CHANGED_BY_COLUMN_NAME`],
+    /// [`HISTORY_OLD_ROW_COLUMN_NAME`], and [`HISTORY_NEW_ROW_COLUMN_NAME`] columns.
+    pub history_table: Option<PhysicalTableName>,
+
+    /// Whether this is a regular table or a view (which migrations leave alone).
+    pub kind: TableKind,
 }
 
+/// The kind of relation backing a [`PhysicalTable`]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum TableKind {
+    /// A regular table (created and altered by migrations)
+    #[default]
+    Table,
+    /// A view such as one created with `CREATE VIEW` (read-only)
+    View,
+    /// A materialized view such as one created with `CREATE MATERIALIZED VIEW` (read-only, but
+    /// may be refreshed)
+    MaterializedView,
+}
+
+impl TableKind {
+    /// Is this a view (materialized or not)? Views are defined by a query outside of Exograph, so
+    /// migrations neither create nor alter them.
+    pub fn is_view(&self) -> bool {
+        !matches!(self, TableKind::Table)
+    }
+}
+
+/// The configuration parameter holding the users making changes in the current transaction, which
+/// history tables record (see [`PhysicalTable::history_table`]). Its value is a JSON object with
+/// the user for each audited table (keyed by the table's name qualified with its schema such as
+/// `{"concerts": "alice", "auth.users": "alice@example.com"}`), since each audited type may
+/// identify the user differently. An empty string or a missing entry records an unknown user.
+pub const ACTING_USER_SETTING: &str = "exograph.acting_user";
+
+/// The primary key of a history table (ordering the recorded changes)
+pub const HISTORY_ID_COLUMN_NAME: &str = "history_id";
+/// The kind of change recorded in a history table (`INSERT`, `UPDATE`, or `DELETE`)
+pub const HISTORY_OPERATION_COLUMN_NAME: &str = "operation";
+/// The time of the change recorded in a history table
+pub const HISTORY_CHANGED_AT_COLUMN_NAME: &str = "changed_at";
+/// The user who made the change recorded in a history table (`NULL` if not known)
+pub const HISTORY_CHANGED_BY_COLUMN_NAME: &str = "changed_by";
+/// The row before the change recorded in a history table (`NULL` for an insert)
+pub const HISTORY_OLD_ROW_COLUMN_NAME: &str = "old_row";
+/// The row after the change recorded in a history table (`NULL` for a delete)
+pub const HISTORY_NEW_ROW_COLUMN_NAME: &str = "new_row";
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 pub struct PhysicalIndex {
     pub name: String,
@@ -93,8 +148,16 @@ impl std::fmt::Debug for PhysicalTable {
 }
 
 impl PhysicalTable {
-    pub fn get_pk_physical_column(&self) -> Option<&PhysicalColumn> {
-        self.columns.iter().find(|column| column.is_pk)
+    /// The channel on which changes to rows of this table are published (if `notify_changes` is set).
+    pub fn notification_channel(&self) -> String {
+        format!(
+            "exograph_changes_{}",
+            self.name.fully_qualified_name_with_sep("_")
+        )
+    }
+
+    pub fn get_pk_physical_columns(&self) -> Vec<&PhysicalColumn> {
+        self.columns.iter().filter(|column| column.is_pk).collect()
     }
 
     pub fn insert<'a, C>(
@@ -114,6 +177,7 @@ impl PhysicalTable {
                 .map(|rows| rows.into_iter().map(|col| col.into()).collect())
                 .collect(),
             returning,
+            on_conflict: None,
         }
     }
 
@@ -151,8 +215,12 @@ impl PhysicalTable {
         self.columns.iter().position(|c| c.name == name)
     }
 
-    pub(crate) fn get_pk_column_index(&self) -> Option<usize> {
-        self.columns.iter().position(|c| c.is_pk)
+    pub(crate) fn get_pk_column_indices(&self) -> Vec<usize> {
+        self.columns
+            .iter()
+            .enumerate()
+            .filter_map(|(index, c)| c.is_pk.then_some(index))
+            .collect()
     }
 }
 
@@ -9,7 +9,12 @@
 
 use crate::{Database, VectorDistanceFunction};
 
-use super::{column::Column, vector::VectorDistance, ExpressionBuilder, SQLBuilder};
+use super::{
+    column::Column,
+    text_search::{TextSearchMode, TextSearchQuery},
+    vector::VectorDistance,
+    ExpressionBuilder, SQLBuilder,
+};
 
 /// Case sensitivity for string predicates.
 #[derive(Debug, Clone, PartialEq, Eq, Copy)]
@@ -28,6 +33,19 @@ pub enum NumericComparator {
     Gte,
 }
 
+impl NumericComparator {
+    fn sql_operator(&self) -> &'static str {
+        match self {
+            NumericComparator::Eq => "=",
+            NumericComparator::Neq => "<>",
+            NumericComparator::Lt => "<",
+            NumericComparator::Lte => "<=",
+            NumericComparator::Gt => ">",
+            NumericComparator::Gte => ">=",
+        }
+    }
+}
+
 /// A predicate is a boolean expression that can be used in a WHERE clause.
 #[derive(Debug, PartialEq, Clone)]
 pub enum Predicate<C>
@@ -56,8 +74,21 @@ where
     JsonMatchAnyKey(C, C),
     JsonMatchAllKeys(C, C),
 
+    // array predicates
+    ArrayContains(C, C),
+    ArrayContainedBy(C, C),
+    ArrayOverlaps(C, C),
+    // The array (lhs) has an element equal to the value (rhs)
+    ArrayAnyEq(C, C),
+    // Compare the number of elements in the array (lhs) with the value (rhs)
+    ArrayLength(C, NumericComparator, C),
+
     VectorDistance(C, C, VectorDistanceFunction, NumericComparator, C),
 
+    // The document (lhs, a `tsvector` column) matches the query text (rhs) parsed using the text
+    // search configuration (such as "english")
+    TextSearch(C, C, String, TextSearchMode),
+
     // Prefer Predicate::and(), which simplifies the clause
     And(Box<Predicate<C>>, Box<Predicate<C>>),
     // Prefer Predicate::or(), which simplifies the clause
@@ -111,6 +142,52 @@ where
             (lhs, rhs) => Predicate::Or(Box::new(lhs), Box::new(rhs)),
         }
     }
+
+    /// Transform each column in the predicate (keeping the structure of the predicate intact).
+    pub fn map_columns<D>(&self, f: &impl Fn(&C) -> D) -> Predicate<D>
+    where
+        D: PartialEq + ParamEquality,
+    {
+        match self {
+            Predicate::True => Predicate::True,
+            Predicate::False => Predicate::False,
+            Predicate::Eq(l, r) => Predicate::Eq(f(l), f(r)),
+            Predicate::Neq(l, r) => Predicate::Neq(f(l), f(r)),
+            Predicate::Lt(l, r) => Predicate::Lt(f(l), f(r)),
+            Predicate::Lte(l, r) => Predicate::Lte(f(l), f(r)),
+            Predicate::Gt(l, r) => Predicate::Gt(f(l), f(r)),
+            Predicate::Gte(l, r) => Predicate::Gte(f(l), f(r)),
+            Predicate::In(l, r) => Predicate::In(f(l), f(r)),
+            Predicate::StringLike(l, r, cs) => Predicate::StringLike(f(l), f(r), *cs),
+            Predicate::StringStartsWith(l, r) => Predicate::StringStartsWith(f(l), f(r)),
+            Predicate::StringEndsWith(l, r) => Predicate::StringEndsWith(f(l), f(r)),
+            Predicate::JsonContains(l, r) => Predicate::JsonContains(f(l), f(r)),
+            Predicate::JsonContainedBy(l, r) => Predicate::JsonContainedBy(f(l), f(r)),
+            Predicate::JsonMatchKey(l, r) => Predicate::JsonMatchKey(f(l), f(r)),
+            Predicate::JsonMatchAnyKey(l, r) => Predicate::JsonMatchAnyKey(f(l), f(r)),
+            Predicate::JsonMatchAllKeys(l, r) => Predicate::JsonMatchAllKeys(f(l), f(r)),
+            Predicate::ArrayContains(l, r) => Predicate::ArrayContains(f(l), f(r)),
+            Predicate::ArrayContainedBy(l, r) => Predicate::ArrayContainedBy(f(l), f(r)),
+            Predicate::ArrayOverlaps(l, r) => Predicate::ArrayOverlaps(f(l), f(r)),
+            Predicate::ArrayAnyEq(l, r) => Predicate::ArrayAnyEq(f(l), f(r)),
+            Predicate::ArrayLength(l, comparator, r) => {
+                Predicate::ArrayLength(f(l), *comparator, f(r))
+            }
+            Predicate::VectorDistance(l, r, function, comparator, threshold) => {
+                Predicate::VectorDistance(f(l), f(r), *function, *comparator, f(threshold))
+            }
+            Predicate::TextSearch(l, r, language, mode) => {
+                Predicate::TextSearch(f(l), f(r), language.clone(), *mode)
+            }
+            Predicate::And(l, r) => {
+                Predicate::And(Box::new(l.map_columns(f)), Box::new(r.map_columns(f)))
+            }
+            Predicate::Or(l, r) => {
+                Predicate::Or(Box::new(l.map_columns(f)), Box::new(r.map_columns(f)))
+            }
+            Predicate::Not(p) => Predicate::Not(Box::new(p.map_columns(f))),
+        }
+    }
 }
 
 impl<C> From<bool> for Predicate<C>
@@ -201,9 +278,16 @@ impl ExpressionBuilder for ConcretePredicate {
             ConcretePredicate::Gte(column1, column2) => {
                 relational_combine(column1, column2, ">=", database, builder)
             }
-            ConcretePredicate::In(column1, column2) => {
-                relational_combine(column1, column2, "IN", database, builder)
-            }
+            ConcretePredicate::In(column1, column2) => match column2 {
+                // A list of values is bound as a single array parameter
+                Column::Param(_) => {
+                    column1.build(database, builder);
+                    builder.push_str(" = ANY(");
+                    column2.build(database, builder);
+                    builder.push(')');
+                }
+                _ => relational_combine(column1, column2, "IN", database, builder),
+            },
 
             ConcretePredicate::StringLike(column1, column2, case_sensitivity) => {
                 relational_combine(
@@ -246,6 +330,30 @@ impl ExpressionBuilder for ConcretePredicate {
                 relational_combine(column1, column2, "?&", database, builder)
             }
 
+            ConcretePredicate::ArrayContains(column1, column2) => {
+                relational_combine(column1, column2, "@>", database, builder)
+            }
+            ConcretePredicate::ArrayContainedBy(column1, column2) => {
+                relational_combine(column1, column2, "<@", database, builder)
+            }
+            ConcretePredicate::ArrayOverlaps(column1, column2) => {
+                relational_combine(column1, column2, "&&", database, builder)
+            }
+            ConcretePredicate::ArrayAnyEq(column1, column2) => {
+                column2.build(database, builder);
+                builder.push_str(" = ANY(");
+                column1.build(database, builder);
+                builder.push(')');
+            }
+            ConcretePredicate::ArrayLength(column1, numeric_comp_op, column2) => {
+                builder.push_str("cardinality(");
+                column1.build(database, builder);
+                builder.push_str(") ");
+                builder.push_str(numeric_comp_op.sql_operator());
+                builder.push_space();
+                column2.build(database, builder);
+            }
+
             ConcretePredicate::VectorDistance(
                 column1,
                 column2,
@@ -255,17 +363,15 @@ impl ExpressionBuilder for ConcretePredicate {
             ) => {
                 VectorDistance::new(column1, column2, *distance_op).build(database, builder);
                 builder.push_space();
-                match numeric_comp_op {
-                    NumericComparator::Eq => builder.push_str("="),
-                    NumericComparator::Neq => builder.push_str("<>"),
-                    NumericComparator::Lt => builder.push_str("<"),
-                    NumericComparator::Lte => builder.push_str("<="),
-                    NumericComparator::Gt => builder.push_str(">"),
-                    NumericComparator::Gte => builder.push_str(">="),
-                }
+                builder.push_str(numeric_comp_op.sql_operator());
                 builder.push_space();
                 numeric_value.build(database, builder);
             }
+            ConcretePredicate::TextSearch(document, text, language, mode) => {
+                document.build(database, builder);
+                builder.push_str(" @@ ");
+                TextSearchQuery::new(text, language, *mode).build(database, builder);
+            }
 
             ConcretePredicate::And(predicate1, predicate2) => {
                 logical_combine(predicate1, predicate2, "AND", database, builder)
@@ -320,7 +426,9 @@ mod tests {
     use std::sync::Arc;
 
     use crate::schema::table_spec::TableSpec;
-    use crate::schema::test_helper::{int_column, json_column, pk_column, string_column};
+    use crate::schema::test_helper::{
+        int_array_column, int_column, json_column, pk_column, string_column, text_search_column,
+    };
     use crate::{schema::database_spec::DatabaseSpec, sql::SQLParamContainer};
     use crate::{ColumnId, PhysicalTableName};
     use multiplatform_test::multiplatform_test;
@@ -365,6 +473,39 @@ mod tests {
         assert_binding!(predicate.to_sql(&database), r#""people"."age" = $1"#, 5);
     }
 
+    #[multiplatform_test]
+    fn in_predicate() {
+        let database = DatabaseSpec::new(
+            vec![TableSpec::new(
+                PhysicalTableName::new("people", None),
+                vec![pk_column("id"), int_column("age")],
+                vec![],
+                vec![],
+            )],
+            vec![],
+        )
+        .to_database();
+
+        let people_table_id = database
+            .get_table_id(&PhysicalTableName::new("people", None))
+            .unwrap();
+        let age_column_id = database.get_column_id(people_table_id, "age").unwrap();
+
+        let age_col = Column::physical(age_column_id, None);
+        let ages_value_col = Column::Param(SQLParamContainer::new(
+            vec![5, 10],
+            tokio_postgres::types::Type::INT4_ARRAY,
+        ));
+
+        let predicate = Predicate::In(age_col, ages_value_col);
+
+        assert_binding!(
+            predicate.to_sql(&database),
+            r#""people"."age" = ANY($1)"#,
+            vec![5, 10]
+        );
+    }
+
     #[multiplatform_test]
     fn and_predicate() {
         let database = DatabaseSpec::new(
@@ -571,4 +712,123 @@ mod tests {
             json_key_list
         );
     }
+
+    #[multiplatform_test]
+    fn array_predicates() {
+        let database = DatabaseSpec::new(
+            vec![TableSpec::new(
+                PhysicalTableName::new("games", None),
+                vec![pk_column("id"), int_array_column("scores")],
+                vec![],
+                vec![],
+            )],
+            vec![],
+        )
+        .to_database();
+
+        let table_id = database
+            .get_table_id(&PhysicalTableName::new("games", None))
+            .unwrap();
+        let scores_col_id = database.get_column_id(table_id, "scores").unwrap();
+
+        fn array_test_data(scores_col_id: ColumnId) -> (Column, Column) {
+            let scores_col = Column::physical(scores_col_id, None);
+            let scores_value_col = Column::Param(SQLParamContainer::new(
+                vec![5, 10],
+                tokio_postgres::types::Type::INT4_ARRAY,
+            ));
+
+            (scores_col, scores_value_col)
+        }
+
+        // contains
+        let (scores_col, scores_value_col) = array_test_data(scores_col_id);
+        assert_binding!(
+            ConcretePredicate::ArrayContains(scores_col, scores_value_col).to_sql(&database),
+            r#""games"."scores" @> $1"#,
+            vec![5, 10]
+        );
+
+        // containedBy
+        let (scores_col, scores_value_col) = array_test_data(scores_col_id);
+        assert_binding!(
+            ConcretePredicate::ArrayContainedBy(scores_col, scores_value_col).to_sql(&database),
+            r#""games"."scores" <@ $1"#,
+            vec![5, 10]
+        );
+
+        // overlaps
+        let (scores_col, scores_value_col) = array_test_data(scores_col_id);
+        assert_binding!(
+            ConcretePredicate::ArrayOverlaps(scores_col, scores_value_col).to_sql(&database),
+            r#""games"."scores" && $1"#,
+            vec![5, 10]
+        );
+
+        // anyEq
+        let (scores_col, _) = array_test_data(scores_col_id);
+        let score_value_col = Column::Param(SQLParamContainer::i32(5));
+        assert_binding!(
+            ConcretePredicate::ArrayAnyEq(scores_col, score_value_col).to_sql(&database),
+            r#"$1 = ANY("games"."scores")"#,
+            5
+        );
+
+        // length
+        let (scores_col, _) = array_test_data(scores_col_id);
+        let length_value_col = Column::Param(SQLParamContainer::i32(2));
+        assert_binding!(
+            ConcretePredicate::ArrayLength(scores_col, NumericComparator::Gte, length_value_col)
+                .to_sql(&database),
+            r#"cardinality("games"."scores") >= $1"#,
+            2
+        );
+    }
+
+    #[multiplatform_test]
+    fn text_search_predicates() {
+        let database = DatabaseSpec::new(
+            vec![TableSpec::new(
+                PhysicalTableName::new("articles", None),
+                vec![
+                    pk_column("id"),
+                    string_column("title"),
+                    text_search_column("search_vector", &["title"]),
+                ],
+                vec![],
+                vec![],
+            )],
+            vec![],
+        )
+        .to_database();
+
+        let table_id = database
+            .get_table_id(&PhysicalTableName::new("articles", None))
+            .unwrap();
+        let search_col_id = database.get_column_id(table_id, "search_vector").unwrap();
+
+        let plain_predicate = ConcretePredicate::TextSearch(
+            Column::physical(search_col_id, None),
+            Column::Param(SQLParamContainer::str("rust async")),
+            "english".to_string(),
+            TextSearchMode::Plain,
+        );
+        assert_binding!(
+            plain_predicate.to_sql(&database),
+            r#""articles"."search_vector" @@ plainto_tsquery('english', $1)"#,
+            "rust async"
+        );
+
+        let web_search_predicate = ConcretePredicate::TextSearch(
+            Column::physical(search_col_id, None),
+            Column::Param(SQLParamContainer::str("rust -async")),
+            "english".to_string(),
+            TextSearchMode::WebSearch,
+        );
+        assert_binding!(
+            web_search_predicate.to_sql(&database),
+            r#""articles"."search_vector" @@ websearch_to_tsquery('english', $1)"#,
+            "rust -async"
+        );
+    }
 }
@@ -0,0 +1,64 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use crate::{Database, TableId};
+
+use super::{ExpressionBuilder, SQLBuilder};
+
+/// Re-computation of the content of a materialized view such as
+/// `REFRESH MATERIALIZED VIEW "concert_summaries"`.
+#[derive(Debug)]
+pub struct RefreshMaterializedView {
+    pub table_id: TableId,
+}
+
+impl ExpressionBuilder for RefreshMaterializedView {
+    fn build(&self, database: &Database, builder: &mut SQLBuilder) {
+        builder.push_str("REFRESH MATERIALIZED VIEW ");
+        builder.push_str(&database.get_table(self.table_id).name.sql_name());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        schema::{database_spec::DatabaseSpec, table_spec::TableSpec, test_helper::pk_column},
+        PhysicalTableName,
+    };
+
+    use multiplatform_test::multiplatform_test;
+
+    use super::*;
+
+    #[multiplatform_test]
+    fn refresh_materialized_view() {
+        let database = DatabaseSpec::new(
+            vec![TableSpec::new(
+                PhysicalTableName::new("concert_summaries", Some("reporting")),
+                vec![pk_column("id")],
+                vec![],
+                vec![],
+            )],
+            vec![],
+        )
+        .to_database();
+
+        let table_id = database
+            .get_table_id(&PhysicalTableName::new(
+                "concert_summaries",
+                Some("reporting"),
+            ))
+            .unwrap();
+
+        assert_binding!(
+            RefreshMaterializedView { table_id }.to_sql(&database),
+            r#"REFRESH MATERIALIZED VIEW "reporting"."concert_summaries""#
+        );
+    }
+}
@@ -1,43 +1,132 @@
 use serde::{Deserialize, Serialize};
 
-use crate::{ColumnId, ColumnPathLink, Database};
+use crate::{ColumnId, ColumnPathLink, Database, TableId};
 
-#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
-pub struct OneToMany {
-    pub self_pk_column_id: ColumnId,
+/// A pair of columns that link two tables. A relation links tables through one pair per column of
+/// the primary key of the referenced table (so there are multiple pairs if the primary key is
+/// composite).
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug, PartialOrd, Ord)]
+pub struct RelationColumnPair {
+    pub self_column_id: ColumnId,
     pub foreign_column_id: ColumnId,
 }
 
+impl RelationColumnPair {
+    fn flipped(&self) -> Self {
+        Self {
+            self_column_id: self.foreign_column_id,
+            foreign_column_id: self.self_column_id,
+        }
+    }
+}
+
+/// A one-to-many relation such as `venues -> concerts`. The self columns are the primary key
+/// columns of the `venues` table and the foreign columns are the `concerts.venue_id` (or, with a
+/// composite key, `concerts.venue_tenant_id` and `concerts.venue_id`) columns.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct OneToMany {
+    /// The column pairs in the order of the primary key columns of the self table
+    pub column_pairs: Vec<RelationColumnPair>,
+}
+
+/// A many-to-one relation such as `concerts -> venues`. The self columns are the
+/// `concerts.venue_id` (or, with a composite key, `concerts.venue_tenant_id` and
+/// `concerts.venue_id`) columns and the foreign columns are the primary key columns of the
+/// `venues` table.
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Hash, Debug)]
 pub struct ManyToOne {
-    pub self_column_id: ColumnId,
-    pub foreign_pk_column_id: ColumnId,
+    /// The column pairs in the order of the primary key columns of the foreign table
+    pub column_pairs: Vec<RelationColumnPair>,
     /// A name that may be used to alias the foreign table. This is useful when
     /// multiple columns in a table refer to the same foreign table. For example,
     /// `concerts` may have a `main_venue_id` and a `alt_venue_id`.
     pub foreign_table_alias: Option<String>,
 }
 
+/// A many-to-many relation such as `concerts <-> artists` through a linking table such as
+/// `concert_artists`. It is made of the one-to-many relation from the self table to the linking
+/// table (`concerts -> concert_artists`) and the many-to-one relation from the linking table to
+/// the foreign table (`concert_artists -> artists`).
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct ManyToMany {
+    /// The relation from the self table to the linking table (`concerts -> concert_artists`)
+    pub self_link: OneToMany,
+    /// The relation from the linking table to the foreign table (`concert_artists -> artists`)
+    pub foreign_link: ManyToOne,
+}
+
 impl OneToMany {
     pub fn column_path_link(&self) -> ColumnPathLink {
-        ColumnPathLink::relation(self.self_pk_column_id, self.foreign_column_id, None)
+        ColumnPathLink::relation(self.column_pairs.clone(), None)
+    }
+
+    /// The table with the primary key (`venues` in `venues -> concerts`)
+    pub fn self_table_id(&self) -> TableId {
+        self.column_pairs[0].self_column_id.table_id
+    }
+
+    /// The table with the foreign key (`concerts` in `venues -> concerts`)
+    pub fn foreign_table_id(&self) -> TableId {
+        self.column_pairs[0].foreign_column_id.table_id
+    }
+
+    /// The foreign key columns (in the order of the primary key columns they refer to)
+    pub fn foreign_column_ids(&self) -> Vec<ColumnId> {
+        self.column_pairs
+            .iter()
+            .map(|pair| pair.foreign_column_id)
+            .collect()
     }
 }
 
 impl ManyToOne {
     fn flipped(&self) -> OneToMany {
         OneToMany {
-            self_pk_column_id: self.foreign_pk_column_id,
-            foreign_column_id: self.self_column_id,
+            column_pairs: self
+                .column_pairs
+                .iter()
+                .map(RelationColumnPair::flipped)
+                .collect(),
         }
     }
 
     pub fn column_path_link(&self) -> ColumnPathLink {
-        ColumnPathLink::relation(
-            self.self_column_id,
-            self.foreign_pk_column_id,
-            self.foreign_table_alias.clone(),
-        )
+        ColumnPathLink::relation(self.column_pairs.clone(), self.foreign_table_alias.clone())
+    }
+
+    /// The table with the foreign key (`concerts` in `concerts -> venues`)
+    pub fn self_table_id(&self) -> TableId {
+        self.column_pairs[0].self_column_id.table_id
+    }
+
+    /// The table with the primary key (`venues` in `concerts -> venues`)
+    pub fn foreign_table_id(&self) -> TableId {
+        self.column_pairs[0].foreign_column_id.table_id
+    }
+
+    /// The foreign key columns (in the order of the primary key columns they refer to)
+    pub fn self_column_ids(&self) -> Vec<ColumnId> {
+        self.column_pairs
+            .iter()
+            .map(|pair| pair.self_column_id)
+            .collect()
+    }
+}
+
+impl ManyToMany {
+    /// The table on the self side (`concerts` in `concerts <-> artists`)
+    pub fn self_table_id(&self) -> TableId {
+        self.self_link.self_table_id()
+    }
+
+    /// The linking table (`concert_artists` in `concerts <-> artists`)
+    pub fn linking_table_id(&self) -> TableId {
+        self.self_link.foreign_table_id()
+    }
+
+    /// The table on the foreign side (`artists` in `concerts <-> artists`)
+    pub fn foreign_table_id(&self) -> TableId {
+        self.foreign_link.foreign_table_id()
     }
 }
 
@@ -61,8 +150,36 @@ impl OneToManyId {
     }
 }
 
+/// Many to many id, which refers to the many to one relations from the linking table to the tables
+/// on each side (`Database` keeps track of only those relations)
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ManyToManyId {
+    /// The relation from the self table to the linking table (`concerts -> concert_artists`)
+    pub self_link_id: OneToManyId,
+    /// The relation from the linking table to the foreign table (`concert_artists -> artists`)
+    pub foreign_link_id: ManyToOneId,
+}
+
+impl ManyToManyId {
+    pub fn deref(&self, database: &Database) -> ManyToMany {
+        ManyToMany {
+            self_link: self.self_link_id.deref(database),
+            foreign_link: self.foreign_link_id.deref(database),
+        }
+    }
+
+    /// The same relation seen from the other side (`artists <-> concerts` for `concerts <-> artists`)
+    pub fn flipped(&self) -> Self {
+        Self {
+            self_link_id: OneToManyId(self.foreign_link_id),
+            foreign_link_id: self.self_link_id.0,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum RelationId {
     ManyToOne(ManyToOneId),
     OneToMany(OneToManyId),
+    ManyToMany(ManyToManyId),
 }
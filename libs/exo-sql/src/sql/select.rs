@@ -9,7 +9,7 @@
 
 use std::collections::HashMap;
 
-use crate::{Database, Limit, Offset};
+use crate::{Database, Limit, Offset, PhysicalTableName};
 
 use super::{
     column::Column, group_by::GroupBy, order::OrderBy, predicate::ConcretePredicate, table::Table,
@@ -41,20 +41,51 @@ pub struct Select {
 
 impl ExpressionBuilder for Select {
     fn build(&self, database: &Database, builder: &mut SQLBuilder) {
-        let table_alias_map = match &self.table {
-            // If the underlying table is a subselect, we need to add it to the table alias map so
-            // tha the other parts (select, where, etc.) can use the alias instead of the subselect's table name
+        let from_alias_map = match self.table.base() {
+            // If the underlying table is a subselect (or a function), we need to add it to the alias
+            // map so that the other parts (select, where, etc.) can use the alias instead of the
+            // subselect's table name
             Table::SubSelect {
                 alias: Some((alias_name, table_name)),
                 ..
+            }
+            | Table::Function {
+                alias: (alias_name, table_name),
+                ..
             } => HashMap::from([(table_name.clone(), alias_name.clone())]),
             _ => HashMap::new(),
         };
 
-        builder.push_str("SELECT ");
+        // A physical table in the `FROM` clause refers to the table itself, even if an enclosing
+        // select refers to the same table through an alias
+        let own_table_name = match self.table.base() {
+            Table::Physical {
+                table_id,
+                alias: None,
+            } => Some(database.get_table(*table_id).name.clone()),
+            _ => None,
+        };
 
-        // Columns
-        builder.with_table_alias_map(table_alias_map.clone(), |builder| {
+        match own_table_name {
+            Some(own_table_name) => builder.without_from_alias(&own_table_name, |builder| {
+                self.build_with_from_alias_map(database, builder, from_alias_map)
+            }),
+            None => self.build_with_from_alias_map(database, builder, from_alias_map),
+        }
+    }
+}
+
+impl Select {
+    fn build_with_from_alias_map(
+        &self,
+        database: &Database,
+        builder: &mut SQLBuilder,
+        from_alias_map: HashMap<PhysicalTableName, String>,
+    ) {
+        builder.with_from_alias_map(from_alias_map, |builder| {
+            builder.push_str("SELECT ");
+
+            // Columns
             builder.push_iter(self.columns.iter(), ", ", |builder, col| {
                 col.build(database, builder);
 
@@ -65,12 +96,10 @@ impl ExpressionBuilder for Select {
                     builder.push_str("::text");
                 }
             });
-        });
 
-        builder.push_str(" FROM ");
-        self.table.build(database, builder);
+            builder.push_str(" FROM ");
+            self.table.build(database, builder);
 
-        builder.with_table_alias_map(table_alias_map, |builder| {
             // Avoid correct, but inelegant "WHERE TRUE" clause
             if self.predicate != ConcretePredicate::True {
                 builder.push_str(" WHERE ");
@@ -0,0 +1,55 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use crate::Database;
+
+use super::{ExpressionBuilder, SQLBuilder, SQLParamContainer};
+
+/// Setting of a configuration parameter for the rest of the current transaction such as
+/// `SELECT set_config($1, $2, true)`. This is equivalent to `SET LOCAL <name> = <value>`, but
+/// allows supplying the value as a parameter.
+#[derive(Debug)]
+pub struct SetConfig {
+    pub name: String,
+    pub value: String,
+}
+
+impl ExpressionBuilder for SetConfig {
+    fn build(&self, _database: &Database, builder: &mut SQLBuilder) {
+        builder.push_str("SELECT set_config(");
+        builder.push_param(SQLParamContainer::string(self.name.clone()).param());
+        builder.push_str(", ");
+        builder.push_param(SQLParamContainer::string(self.value.clone()).param());
+        builder.push_str(", true)");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use multiplatform_test::multiplatform_test;
+
+    use super::*;
+
+    #[multiplatform_test]
+    fn set_config() {
+        let database = Database::default();
+
+        let set_config = SetConfig {
+            name: "exograph.acting_user".to_string(),
+            value: "alice".to_string(),
+        };
+
+        assert_binding!(
+            set_config.to_sql(&database),
+            "SELECT set_config($1, $2, true)",
+            "exograph.acting_user".to_string(),
+            "alice".to_string()
+        );
+    }
+}
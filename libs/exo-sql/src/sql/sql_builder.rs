@@ -7,7 +7,7 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::Database;
 
@@ -26,6 +26,14 @@ pub struct SQLBuilder {
     /// (...table...)"). Similarly, for a sub-select, the alias would be the name of the sub-select.
     /// This is used to render alias in lieu of table names for the related expressions.
     table_alias_map: HashMap<PhysicalTableName, String>,
+    /// Map from physical table to the alias of an item in the `FROM` clause standing for it (such as
+    /// `(SELECT ...) AS "concerts"` or `"top_concerts"($1) AS "concerts$fn"`). Unlike the name of a
+    /// CTE, such an alias is not visible as a table to a nested select, so a nested select of the
+    /// same table refers to the table itself (see [`Self::without_from_alias`]). Takes precedence
+    /// over [`Self::table_alias_map`].
+    from_alias_map: HashMap<PhysicalTableName, String>,
+    /// The tables referenced by the SQL (including those rendered through an alias)
+    tables: HashSet<PhysicalTableName>,
 }
 
 impl SQLBuilder {
@@ -35,6 +43,8 @@ impl SQLBuilder {
             params: Vec::new(),
             fully_qualify_column_names: true,
             table_alias_map: HashMap::new(),
+            from_alias_map: HashMap::new(),
+            tables: HashSet::new(),
         }
     }
 
@@ -69,7 +79,16 @@ impl SQLBuilder {
 
     /// Push a table name. If the table name has an alias, push the alias instead.
     pub fn push_table(&mut self, table_name: &PhysicalTableName) {
-        match &self.table_alias_map.get(table_name).cloned() {
+        if !self.tables.contains(table_name) {
+            self.tables.insert(table_name.clone());
+        }
+
+        match &self
+            .from_alias_map
+            .get(table_name)
+            .or_else(|| self.table_alias_map.get(table_name))
+            .cloned()
+        {
             Some(alias) => {
                 self.push_identifier(alias);
             }
@@ -144,6 +163,11 @@ impl SQLBuilder {
         (self.sql, self.params)
     }
 
+    /// The tables referenced so far
+    pub fn tables(&self) -> &HashSet<PhysicalTableName> {
+        &self.tables
+    }
+
     /// Execute the given function with the [`Self::fully_qualify_column_names`] flag set to false.
     /// This is useful for building SQL expressions that need to be rendered without the table name,
     /// e.g. for INSERT and UPDATE statements. This takes a closure, so that we can restore the
@@ -177,4 +201,41 @@ impl SQLBuilder {
         self.table_alias_map = cur_table_alias_map;
         ret
     }
+
+    /// Execute the given function with the tables in the given map rendered through the alias of
+    /// an item in the `FROM` clause (see [`Self::from_alias_map`]).
+    pub fn with_from_alias_map<F, R>(
+        &mut self,
+        from_alias_map: HashMap<PhysicalTableName, String>,
+        func: F,
+    ) -> R
+    where
+        F: FnOnce(&mut Self) -> R,
+    {
+        if from_alias_map.is_empty() {
+            return func(self);
+        }
+
+        let cur_from_alias_map = self.from_alias_map.clone();
+        self.from_alias_map.extend(from_alias_map);
+        let ret = func(self);
+        self.from_alias_map = cur_from_alias_map;
+        ret
+    }
+
+    /// Execute the given function with the table no longer rendered through the alias of an item in
+    /// the `FROM` clause of an enclosing select (for a nested select of the table itself).
+    pub fn without_from_alias<F, R>(&mut self, table_name: &PhysicalTableName, func: F) -> R
+    where
+        F: FnOnce(&mut Self) -> R,
+    {
+        match self.from_alias_map.remove(table_name) {
+            Some(alias) => {
+                let ret = func(self);
+                self.from_alias_map.insert(table_name.clone(), alias);
+                ret
+            }
+            None => func(self),
+        }
+    }
 }
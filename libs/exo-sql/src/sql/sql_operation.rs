@@ -7,14 +7,16 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use crate::Database;
+use crate::{Database, PhysicalTableName};
 
 use super::{
     cte::WithQuery,
     delete::Delete,
     delete::TemplateDelete,
     insert::{Insert, TemplateInsert},
+    refresh::RefreshMaterializedView,
     select::Select,
+    set_config::SetConfig,
     transaction::{TransactionContext, TransactionStepId},
     update::{TemplateUpdate, Update},
     ExpressionBuilder, SQLBuilder,
@@ -28,6 +30,28 @@ pub enum SQLOperation<'a> {
     Delete(Delete<'a>),
     Update(Update<'a>),
     WithQuery(WithQuery<'a>),
+    SetConfig(SetConfig),
+    RefreshMaterializedView(RefreshMaterializedView),
+}
+
+impl SQLOperation<'_> {
+    /// The tables this operation writes to
+    pub fn written_tables(&self, database: &Database) -> Vec<PhysicalTableName> {
+        match self {
+            SQLOperation::Insert(insert) => vec![insert.table.name.clone()],
+            SQLOperation::Delete(delete) => vec![delete.table.name.clone()],
+            SQLOperation::Update(update) => vec![update.table.name.clone()],
+            SQLOperation::WithQuery(cte) => cte
+                .expressions
+                .iter()
+                .flat_map(|expression| expression.operation.written_tables(database))
+                .collect(),
+            SQLOperation::RefreshMaterializedView(refresh) => {
+                vec![database.get_table(refresh.table_id).name.clone()]
+            }
+            SQLOperation::Select(_) | SQLOperation::SetConfig(_) => vec![],
+        }
+    }
 }
 
 impl<'a> ExpressionBuilder for SQLOperation<'a> {
@@ -38,6 +62,8 @@ impl<'a> ExpressionBuilder for SQLOperation<'a> {
             SQLOperation::Delete(delete) => delete.build(database, builder),
             SQLOperation::Update(update) => update.build(database, builder),
             SQLOperation::WithQuery(cte) => cte.build(database, builder),
+            SQLOperation::SetConfig(set_config) => set_config.build(database, builder),
+            SQLOperation::RefreshMaterializedView(refresh) => refresh.build(database, builder),
         }
     }
 }
@@ -14,7 +14,7 @@ use std::{
     fmt::{Debug, Display},
     sync::Arc,
 };
-use tokio_postgres::types::{to_sql_checked, ToSql, Type};
+use tokio_postgres::types::{to_sql_checked, Kind, ToSql, Type};
 
 use crate::{SQLBytes, SQLParam};
 
@@ -127,6 +127,10 @@ impl SQLParamContainer {
         Self::new(value, Type::JSONB)
     }
 
+    pub fn enum_value(value: String, enum_type: Type) -> Self {
+        Self::new(SQLEnumValue(value), enum_type)
+    }
+
     pub fn from_sql_values(params: Vec<SQLValue>, elem_type: Type) -> Self {
         let collection_type = to_pg_array_type(&elem_type);
 
@@ -139,6 +143,29 @@ impl SQLParamContainer {
     }
 }
 
+/// A value of a Postgres enum type.
+///
+/// Enum values have the same wire representation as text, but `String` doesn't accept enum types
+/// (and Postgres doesn't implicitly cast text parameters to enums), so we need a separate type.
+#[derive(Debug, Clone, PartialEq)]
+struct SQLEnumValue(String);
+
+impl ToSql for SQLEnumValue {
+    fn to_sql(
+        &self,
+        _ty: &Type,
+        out: &mut bytes::BytesMut,
+    ) -> Result<tokio_postgres::types::IsNull, Box<dyn std::error::Error + Sync + Send>> {
+        self.0.as_str().to_sql(&Type::TEXT, out)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        matches!(ty.kind(), Kind::Enum(_)) || <&str as ToSql>::accepts(ty)
+    }
+
+    to_sql_checked!();
+}
+
 impl PartialEq for SQLParamContainer {
     fn eq(&self, other: &Self) -> bool {
         self.0.eq(&other.0)
@@ -10,8 +10,8 @@
 use crate::{Database, TableId};
 
 use super::{
-    join::LeftJoin, physical_table::PhysicalTableName, select::Select, ExpressionBuilder,
-    SQLBuilder,
+    column::Column, join::LeftJoin, physical_table::PhysicalTableName, select::Select,
+    ExpressionBuilder, SQLBuilder, SQLParamContainer,
 };
 
 /// A table-like concept that can be used in in place of `SELECT FROM <table-query> ...`.
@@ -30,12 +30,41 @@ pub enum Table {
         /// The alias of the sub-select (optional, since we need to alias the sub-select when used in a FROM clause)
         alias: Option<(String, PhysicalTableName)>,
     },
+    /// A single row of a physical table supplied as a JSON object such as
+    /// `jsonb_populate_record(NULL::"concerts", $1)`.
+    Record {
+        table_id: TableId,
+        record: SQLParamContainer,
+    },
+    /// The rows returned by a function such as `"reporting"."top_concerts"($1, $2) AS "concerts$fn"`.
+    Function {
+        name: PhysicalTableName,
+        args: Vec<Column>,
+        /// The alias of the returned rows along with the table whose rows they stand for
+        alias: (String, PhysicalTableName),
+    },
 }
 
 impl Table {
     pub fn physical(table_id: TableId, alias: Option<String>) -> Self {
         Table::Physical { table_id, alias }
     }
+
+    /// The table that the rows come from (the left-most table of a join)
+    pub fn base(&self) -> &Table {
+        match self {
+            Table::Join(join) => join.left().base(),
+            _ => self,
+        }
+    }
+
+    /// Mutable version of [`Self::base`]
+    pub fn base_mut(&mut self) -> &mut Table {
+        match self {
+            Table::Join(join) => join.left_mut().base_mut(),
+            _ => self,
+        }
+    }
 }
 
 impl ExpressionBuilder for Table {
@@ -65,6 +94,28 @@ impl ExpressionBuilder for Table {
                     builder.push_identifier(alias);
                 }
             }
+            Table::Record { table_id, record } => {
+                // The table name here refers to the row type (and not the table itself), so we
+                // must not substitute it with an alias
+                let physical_table = database.get_table(*table_id);
+                builder.push_str("jsonb_populate_record(NULL::");
+                builder.push_str(physical_table.name.sql_name());
+                builder.push_str(", ");
+                builder.push_param(record.param());
+                builder.push(')');
+            }
+            Table::Function {
+                name,
+                args,
+                alias: (alias, _),
+            } => {
+                builder.push_str(name.sql_name());
+                builder.push('(');
+                builder.push_elems(database, args, ", ");
+                builder.push(')');
+                builder.push_str(" AS ");
+                builder.push_identifier(alias);
+            }
         }
     }
 }
@@ -0,0 +1,112 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use serde::{Deserialize, Serialize};
+
+use crate::Database;
+
+use super::{ExpressionBuilder, SQLBuilder};
+
+/// The default text search configuration (the language used to parse documents and queries)
+pub const DEFAULT_TEXT_SEARCH_LANGUAGE: &str = "english";
+
+/// How the text supplied by the user is converted into a `tsquery`
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize, Hash, Eq, Default)]
+pub enum TextSearchMode {
+    /// Match documents containing all the words in the text (`plainto_tsquery`)
+    #[default]
+    Plain,
+    /// Interpret the text using the syntax of web search engines such as quoted phrases, `or`,
+    /// and `-` (`websearch_to_tsquery`)
+    WebSearch,
+}
+
+impl TextSearchMode {
+    fn query_function(&self) -> &'static str {
+        match self {
+            TextSearchMode::Plain => "plainto_tsquery",
+            TextSearchMode::WebSearch => "websearch_to_tsquery",
+        }
+    }
+}
+
+/// A `tsquery` built from the query text. For example, `websearch_to_tsquery('english', $1)`.
+pub struct TextSearchQuery<'a, C>
+where
+    C: ExpressionBuilder,
+{
+    text: C,
+    language: &'a str,
+    mode: TextSearchMode,
+}
+
+impl<'a, C: ExpressionBuilder> TextSearchQuery<'a, C> {
+    pub fn new(text: C, language: &'a str, mode: TextSearchMode) -> Self {
+        Self {
+            text,
+            language,
+            mode,
+        }
+    }
+}
+
+impl<C: ExpressionBuilder> ExpressionBuilder for TextSearchQuery<'_, C> {
+    fn build(&self, database: &Database, builder: &mut SQLBuilder) {
+        builder.push_str(self.mode.query_function());
+        builder.push('(');
+        builder.push_str(quoted_language(self.language));
+        builder.push_str(", ");
+        self.text.build(database, builder);
+        builder.push(')');
+    }
+}
+
+/// The rank of a document (a `tsvector` column) for a query. For example,
+/// `ts_rank("documents"."search_vector", plainto_tsquery('english', $1))`.
+pub struct TextSearchRank<'a, D, C>
+where
+    D: ExpressionBuilder,
+    C: ExpressionBuilder,
+{
+    document: D,
+    query: TextSearchQuery<'a, C>,
+}
+
+impl<'a, D: ExpressionBuilder, C: ExpressionBuilder> TextSearchRank<'a, D, C> {
+    pub fn new(document: D, query: TextSearchQuery<'a, C>) -> Self {
+        Self { document, query }
+    }
+}
+
+impl<D: ExpressionBuilder, C: ExpressionBuilder> ExpressionBuilder for TextSearchRank<'_, D, C> {
+    fn build(&self, database: &Database, builder: &mut SQLBuilder) {
+        builder.push_str("ts_rank(");
+        self.document.build(database, builder);
+        builder.push_str(", ");
+        self.query.build(database, builder);
+        builder.push(')');
+    }
+}
+
+/// The expression that computes the document of a generated `tsvector` column from the given
+/// (text) columns. For example, `to_tsvector('english', coalesce("title", '') || ' ' ||
+/// coalesce("body", ''))`.
+pub(crate) fn text_search_vector_expression(language: &str, columns: &[String]) -> String {
+    let document = columns
+        .iter()
+        .map(|column| format!("coalesce(\"{column}\", '')"))
+        .collect::<Vec<_>>()
+        .join(" || ' ' || ");
+
+    format!("to_tsvector({}, {document})", quoted_language(language))
+}
+
+fn quoted_language(language: &str) -> String {
+    format!("'{}'", language.replace('\'', "''"))
+}
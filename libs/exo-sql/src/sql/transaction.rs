@@ -7,7 +7,7 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use std::fmt::Debug;
+use std::{collections::HashSet, fmt::Debug};
 
 use tokio_postgres::{GenericClient, Row};
 use tracing::{error, info, instrument};
@@ -15,7 +15,7 @@ use tracing::{error, info, instrument};
 use crate::{
     database_error::DatabaseError,
     sql::{select::Select, table::Table, SQLBuilder},
-    Column, Database, Predicate, SQLParamContainer, TableId,
+    Column, ColumnId, Database, PhysicalTableName, Predicate, SQLParamContainer, TableId,
 };
 
 use super::{
@@ -39,6 +39,15 @@ pub struct TransactionContext {
     results: Vec<TransactionStepResult>,
 }
 
+/// The tables accessed by the executed SQL operations
+#[derive(Default, Debug, Clone)]
+pub struct TableUsage {
+    /// Tables referenced by any operation (read or written)
+    pub referenced: HashSet<PhysicalTableName>,
+    /// Tables written by an insert, update, delete, or a materialized view refresh
+    pub written: HashSet<PhysicalTableName>,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct TransactionStepId(pub usize);
 
@@ -52,6 +61,70 @@ impl TransactionContext {
     pub fn row_count(&self, step_id: TransactionStepId) -> usize {
         self.results[step_id.0].len()
     }
+
+    /// Returns a predicate matching the rows of the given table whose primary key values were
+    /// returned by the given steps. Each of those steps must return the primary key columns (in
+    /// order) as its leading columns.
+    ///
+    /// Forms `pk = ANY($1)` for a single-column primary key and `(pk1 = $1 AND pk2 = $2) OR ...`
+    /// for a composite one.
+    pub fn pk_predicate(
+        &self,
+        step_ids: &[TransactionStepId],
+        table_id: TableId,
+        database: &Database,
+    ) -> ConcretePredicate {
+        let pk_column_ids = database.get_pk_column_ids(table_id);
+
+        let rows = step_ids
+            .iter()
+            .flat_map(|step_id| (0..self.row_count(*step_id)).map(move |row| (*step_id, row)));
+
+        match pk_column_ids.as_slice() {
+            [pk_column_id] => {
+                let pk_column_type = pk_column_id.get_column(database).typ.get_pg_type();
+
+                Predicate::Eq(
+                    Column::physical(*pk_column_id, None),
+                    Column::ArrayParam {
+                        param: SQLParamContainer::from_sql_values(
+                            rows.map(|(step_id, row)| self.resolve_value(step_id, row, 0))
+                                .collect(),
+                            pk_column_type,
+                        ),
+                        wrapper: ArrayParamWrapper::Any,
+                    },
+                )
+            }
+            _ => rows.fold(Predicate::False, |acc, (step_id, row)| {
+                Predicate::or(acc, self.row_predicate(step_id, row, &pk_column_ids))
+            }),
+        }
+    }
+
+    /// Returns a predicate matching the given columns with the leading values of a row from the
+    /// given step (`col1 = $1 AND col2 = $2 ...`)
+    pub fn row_predicate(
+        &self,
+        step_id: TransactionStepId,
+        row: usize,
+        column_ids: &[ColumnId],
+    ) -> ConcretePredicate {
+        column_ids
+            .iter()
+            .enumerate()
+            .fold(Predicate::True, |acc, (index, column_id)| {
+                Predicate::and(
+                    acc,
+                    Predicate::Eq(
+                        Column::physical(*column_id, None),
+                        Column::Param(SQLParamContainer::from_sql_value(
+                            self.resolve_value(step_id, row, index),
+                        )),
+                    ),
+                )
+            })
+    }
 }
 
 impl<'a> TransactionScript<'a> {
@@ -64,12 +137,15 @@ impl<'a> TransactionScript<'a> {
         self,
         database: &Database,
         tx: &mut T,
+        table_usage: &mut TableUsage,
     ) -> Result<TransactionStepResult, DatabaseError> {
         let mut transaction_context = TransactionContext { results: vec![] };
 
         // Execute each step in the transaction and store the result in the transaction_context
         for step in self.steps.into_iter() {
-            let result = step.execute(database, tx, &transaction_context).await?;
+            let result = step
+                .execute(database, tx, &transaction_context, table_usage)
+                .await?;
             transaction_context.results.push(result)
         }
 
@@ -112,9 +188,10 @@ impl<'a> TransactionStep<'a> {
         database: &Database,
         client: &mut impl GenericClient,
         transaction_context: &TransactionContext,
+        table_usage: &mut TableUsage,
     ) -> Result<TransactionStepResult, DatabaseError> {
         match self {
-            Self::Concrete(step) => step.execute(database, client).await,
+            Self::Concrete(step) => step.execute(database, client, table_usage).await,
             Self::Template(step) => {
                 let concrete = step.resolve(transaction_context);
 
@@ -125,10 +202,10 @@ impl<'a> TransactionStep<'a> {
                 for (index, substep) in concrete.into_iter().enumerate() {
                     if index == substep_count - 1 {
                         // Execute the last step and return the result
-                        res = substep.execute(database, client).await;
+                        res = substep.execute(database, client, table_usage).await;
                     } else {
                         // Execute all but the last step
-                        substep.execute(database, client).await?;
+                        substep.execute(database, client, table_usage).await?;
                     }
                 }
 
@@ -136,11 +213,11 @@ impl<'a> TransactionStep<'a> {
             }
             Self::Filter(step) => {
                 let concrete = step.resolve(transaction_context, database);
-                concrete.execute(database, client).await
+                concrete.execute(database, client, table_usage).await
             }
             Self::Dynamic(step) => {
                 step.resolve(transaction_context)
-                    .execute(database, client)
+                    .execute(database, client, table_usage)
                     .await
             }
         }
@@ -169,17 +246,27 @@ impl<'a> ConcreteTransactionStep<'a> {
         self,
         database: &Database,
         client: &mut impl GenericClient,
+        table_usage: &mut TableUsage,
     ) -> Result<TransactionStepResult, DatabaseError> {
-        self.run_query(database, client).await
+        self.run_query(database, client, table_usage).await
     }
 
     async fn run_query(
         &'a self,
         database: &Database,
         client: &mut impl GenericClient,
+        table_usage: &mut TableUsage,
     ) -> Result<TransactionStepResult, DatabaseError> {
         let mut sql_builder = SQLBuilder::new();
         self.operation.build(database, &mut sql_builder);
+
+        table_usage
+            .referenced
+            .extend(sql_builder.tables().iter().cloned());
+        table_usage
+            .written
+            .extend(self.operation.written_tables(database));
+
         let (stmt, params) = sql_builder.into_sql();
 
         let params: Vec<_> = params.iter().map(|p| (p.0.as_pg(), p.1.clone())).collect();
@@ -225,43 +312,23 @@ impl TemplateFilterOperation {
         transaction_context: &TransactionContext,
         database: &Database,
     ) -> ConcreteTransactionStep<'a> {
-        let rows = transaction_context.row_count(self.prev_step_id);
-
-        let pk_column_id = database
-            .get_pk_column_id(self.table_id)
-            .expect("No primary key column");
-        let pk_column_type = database
-            .get_table(self.table_id)
-            .get_pk_physical_column()
-            .unwrap()
-            .typ
-            .get_pg_type();
+        let pk_column_ids = database.get_pk_column_ids(self.table_id);
 
         let op = ConcreteTransactionStep {
             operation: SQLOperation::Select(Select {
                 table: Table::physical(self.table_id, None),
                 predicate: Predicate::and(
-                    Predicate::Eq(
-                        Column::physical(pk_column_id, None),
-                        Column::ArrayParam {
-                            param: SQLParamContainer::from_sql_values(
-                                (0..rows)
-                                    .map(|row| {
-                                        transaction_context.resolve_value(self.prev_step_id, row, 0)
-                                    })
-                                    .collect::<Vec<_>>(),
-                                pk_column_type,
-                            ),
-                            wrapper: ArrayParamWrapper::Any,
-                        },
-                    ),
+                    transaction_context.pk_predicate(&[self.prev_step_id], self.table_id, database),
                     self.predicate,
                 ),
                 order_by: None,
                 offset: None,
                 limit: None,
                 top_level_selection: false,
-                columns: vec![Column::physical(pk_column_id, None)],
+                columns: pk_column_ids
+                    .into_iter()
+                    .map(|pk_column_id| Column::physical(pk_column_id, None))
+                    .collect(),
                 group_by: None,
             }),
         };
@@ -16,7 +16,7 @@ use super::{
     physical_column::PhysicalColumn,
     predicate::ConcretePredicate,
     transaction::{TransactionContext, TransactionStepId},
-    ExpressionBuilder, SQLBuilder, SQLParamContainer,
+    ExpressionBuilder, SQLBuilder,
 };
 
 /// An update operation.
@@ -111,14 +111,10 @@ impl<'a> TemplateUpdate<'a> {
                     })
                     .collect();
 
-                let relation_predicate = ConcretePredicate::Eq(
-                    Column::Physical {
-                        column_id: self.nesting_relation.foreign_column_id,
-                        table_alias: None,
-                    },
-                    Column::Param(SQLParamContainer::from_sql_value(
-                        transaction_context.resolve_value(prev_step_id, row_index, 0),
-                    )),
+                let relation_predicate = transaction_context.row_predicate(
+                    prev_step_id,
+                    row_index,
+                    &self.nesting_relation.foreign_column_ids(),
                 );
 
                 Update {
@@ -132,3 +128,80 @@ impl<'a> TemplateUpdate<'a> {
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        schema::{
+            database_spec::DatabaseSpec,
+            table_spec::TableSpec,
+            test_helper::{int_column, pk_column, string_column},
+        },
+        sql::{predicate::Predicate, SQLParamContainer},
+        PhysicalTableName,
+    };
+
+    use multiplatform_test::multiplatform_test;
+
+    use super::*;
+
+    #[multiplatform_test]
+    fn update_with_version_increment() {
+        let database = DatabaseSpec::new(
+            vec![TableSpec::new(
+                PhysicalTableName::new("people", None),
+                vec![
+                    pk_column("id"),
+                    string_column("name"),
+                    int_column("version"),
+                ],
+                vec![],
+                vec![],
+            )],
+            vec![],
+        )
+        .to_database();
+
+        let table_id = database
+            .get_table_id(&PhysicalTableName::new("people", None))
+            .unwrap();
+        let table = database.get_table(table_id);
+        let id_col_id = database.get_column_id(table_id, "id").unwrap();
+        let name_col_id = database.get_column_id(table_id, "name").unwrap();
+        let version_col_id = database.get_column_id(table_id, "version").unwrap();
+
+        let predicate = Predicate::and(
+            Predicate::Eq(
+                Column::physical(id_col_id, None),
+                Column::Param(SQLParamContainer::i32(1)),
+            ),
+            Predicate::Eq(
+                Column::physical(version_col_id, None),
+                Column::Param(SQLParamContainer::i32(3)),
+            ),
+        );
+
+        let update = table.update(
+            vec![
+                (
+                    name_col_id.get_column(&database),
+                    Column::Param(SQLParamContainer::string("Jane".to_string())),
+                ),
+                (
+                    version_col_id.get_column(&database),
+                    Column::Increment(version_col_id),
+                ),
+            ],
+            predicate.into(),
+            vec![Column::physical(id_col_id, None).into()],
+        );
+
+        assert_binding!(
+            update.to_sql(&database),
+            r#"UPDATE "people" SET "name" = $1, "version" = "people"."version" + 1 WHERE ("people"."id" = $2 AND "people"."version" = $3) RETURNING "people"."id""#,
+            "Jane".to_string(),
+            1,
+            3
+        );
+    }
+}
@@ -48,20 +48,31 @@ pub fn compute_join(
             |acc, DependencyLink { link, dependency }| {
                 let (join_predicate, linked_table_alias) = match link {
                     ColumnPathLink::Relation(RelationLink {
-                        self_column_id,
-                        foreign_column_id: linked_column_id,
+                        column_pairs,
                         linked_table_alias,
+                        ..
                     }) => {
-                        let new_alias = selection_level
-                            .alias((linked_column_id.table_id, linked_table_alias), database);
-
-                        (
-                            ConcretePredicate::Eq(
-                                Column::physical(self_column_id, None),
-                                Column::physical(linked_column_id, Some(new_alias.clone())),
-                            ),
-                            new_alias,
-                        )
+                        let linked_table_id = column_pairs[0].foreign_column_id.table_id;
+                        let new_alias =
+                            selection_level.alias((linked_table_id, linked_table_alias), database);
+
+                        let join_predicate =
+                            column_pairs
+                                .into_iter()
+                                .fold(ConcretePredicate::True, |acc, pair| {
+                                    ConcretePredicate::and(
+                                        acc,
+                                        ConcretePredicate::Eq(
+                                            Column::physical(pair.self_column_id, None),
+                                            Column::physical(
+                                                pair.foreign_column_id,
+                                                Some(new_alias.clone()),
+                                            ),
+                                        ),
+                                    )
+                                });
+
+                        (join_predicate, new_alias)
                     }
                     ColumnPathLink::Leaf(_) => {
                         panic!("Unexpected leaf in dependency link")
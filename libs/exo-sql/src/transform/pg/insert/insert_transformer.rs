@@ -33,7 +33,7 @@ impl InsertTransformer for Postgres {
     fn update_transaction_script<'a>(
         &self,
         abstract_insert: &'a AbstractInsert,
-        parent_step: Option<(TransactionStepId, ColumnId)>,
+        parent_step: Option<(TransactionStepId, Vec<ColumnId>)>,
         database: &'a Database,
         transaction_script: &mut TransactionScript<'a>,
     ) {
@@ -24,7 +24,7 @@ pub(crate) trait InsertionStrategy {
     fn update_transaction_script<'a>(
         &self,
         abstract_insert: &'a AbstractInsert,
-        parent_step: Option<(TransactionStepId, ColumnId)>,
+        parent_step: Option<(TransactionStepId, Vec<ColumnId>)>,
         database: &'a Database,
         transformer: &Postgres,
         transaction_script: &mut TransactionScript<'a>,
@@ -11,8 +11,8 @@ use maybe_owned::MaybeOwned;
 
 use crate::{
     sql::{
-        column::{ArrayParamWrapper, ProxyColumn},
-        insert::TemplateInsert,
+        column::ProxyColumn,
+        insert::{Insert, OnConflict, TemplateInsert},
         select::Select,
         sql_operation::{SQLOperation, TemplateSQLOperation},
         transaction::{
@@ -20,9 +20,12 @@ use crate::{
             TransactionContext, TransactionScript, TransactionStep, TransactionStepId,
         },
     },
-    transform::{pg::Postgres, transformer::SelectTransformer},
+    transform::{
+        pg::{selection_level::SelectionLevel, Postgres},
+        transformer::{PredicateTransformer, SelectTransformer},
+    },
     AbstractInsert, Column, ColumnId, ColumnValuePair, Database, InsertionRow, NestedInsertion,
-    OneToMany, Predicate, SQLParamContainer, TableId,
+    OnConflictUpdate, PhysicalColumn, Predicate, TableId,
 };
 
 use super::insertion_strategy::InsertionStrategy;
@@ -44,7 +47,7 @@ impl InsertionStrategy for MultiStatementStrategy {
     fn update_transaction_script<'a>(
         &self,
         abstract_insert: &'a AbstractInsert,
-        parent_step: Option<(TransactionStepId, ColumnId)>,
+        parent_step: Option<(TransactionStepId, Vec<ColumnId>)>,
         database: &'a Database,
         transformer: &Postgres,
         transaction_script: &mut TransactionScript<'a>,
@@ -53,46 +56,35 @@ impl InsertionStrategy for MultiStatementStrategy {
             table_id,
             rows,
             selection,
+            on_conflict,
         } = abstract_insert;
 
         let insert_step_ids: Vec<_> = rows
             .iter()
-            .map(|row| insert_row(*table_id, row, parent_step, transaction_script, database))
+            .map(|row| {
+                insert_row(
+                    *table_id,
+                    row,
+                    parent_step
+                        .as_ref()
+                        .map(|(step_id, column_ids)| (*step_id, column_ids.as_slice())),
+                    on_conflict.as_ref(),
+                    transaction_script,
+                    database,
+                    transformer,
+                )
+            })
             .collect();
 
         let select = transformer.to_select(selection, database);
 
-        let pk_column_type = database
-            .get_table(*table_id)
-            .get_pk_physical_column()
-            .unwrap()
-            .typ
-            .get_pg_type();
-
         // Take the previous insert steps and use them as the input to the select
-        // statement to form a predicate `pk IN (insert_step_1_pk, insert_step_2_pk, ...)`
+        // statement to form a predicate `pk IN (insert_step_1_pk, insert_step_2_pk, ...)`. An
+        // insert step may not return a row (if it had a conflict, but the existing row did not
+        // satisfy the predicate to update it), so we consider only the rows returned.
         let select_transformation = Box::new(move |transaction_context: &TransactionContext| {
-            let in_values = SQLParamContainer::from_sql_values(
-                insert_step_ids
-                    .into_iter()
-                    .map(|insert_step_id| transaction_context.resolve_value(insert_step_id, 0, 0))
-                    .collect::<Vec<_>>(),
-                pk_column_type,
-            );
-
             let predicate = Predicate::and(
-                Predicate::Eq(
-                    Column::physical(
-                        database
-                            .get_pk_column_id(*table_id)
-                            .expect("No primary key column"),
-                        None,
-                    ),
-                    Column::ArrayParam {
-                        param: in_values,
-                        wrapper: ArrayParamWrapper::Any,
-                    },
-                ),
+                transaction_context.pk_predicate(&insert_step_ids, *table_id, database),
                 select.predicate,
             );
             ConcreteTransactionStep::new(SQLOperation::Select(Select {
@@ -110,9 +102,11 @@ impl InsertionStrategy for MultiStatementStrategy {
 fn insert_row<'a>(
     table_id: TableId,
     row: &'a InsertionRow,
-    parent_step: Option<(TransactionStepId, ColumnId)>,
+    parent_step: Option<(TransactionStepId, &[ColumnId])>,
+    on_conflict: Option<&'a OnConflictUpdate>,
     transaction_script: &mut TransactionScript<'a>,
     database: &'a Database,
+    transformer: &Postgres,
 ) -> TransactionStepId {
     let (self_row, nested_rows) = row.partition_self_and_nested();
 
@@ -120,12 +114,20 @@ fn insert_row<'a>(
         table_id,
         self_row,
         parent_step,
+        on_conflict,
         transaction_script,
         database,
+        transformer,
     );
 
     for nested_row in nested_rows {
-        insert_nested_row(nested_row, self_insert_id, transaction_script, database);
+        insert_nested_row(
+            nested_row,
+            self_insert_id,
+            transaction_script,
+            database,
+            transformer,
+        );
     }
 
     self_insert_id
@@ -134,16 +136,18 @@ fn insert_row<'a>(
 fn insert_self_row<'a>(
     table_id: TableId,
     row: Vec<&'a ColumnValuePair>,
-    parent_step: Option<(TransactionStepId, ColumnId)>,
+    parent_step: Option<(TransactionStepId, &[ColumnId])>,
+    on_conflict: Option<&'a OnConflictUpdate>,
     transaction_script: &mut TransactionScript<'a>,
     database: &'a Database,
+    transformer: &Postgres,
 ) -> TransactionStepId {
-    let pk_column = Column::physical(
+    let pk_columns = || {
         database
-            .get_pk_column_id(table_id)
-            .expect("No primary key column"),
-        None,
-    );
+            .get_pk_column_ids(table_id)
+            .into_iter()
+            .map(|pk_column_id| Column::physical(pk_column_id, None))
+    };
 
     let table = database.get_table(table_id);
 
@@ -155,22 +159,30 @@ fn insert_self_row<'a>(
         .unzip();
 
     match parent_step {
-        Some((parent_step_id, parent_column_id)) => {
-            columns.push(parent_column_id.get_column(database));
+        Some((parent_step_id, parent_column_ids)) => {
+            // The parent step returns its primary key columns in order, which are the values of
+            // the columns that refer to the parent table
+            columns.extend(
+                parent_column_ids
+                    .iter()
+                    .map(|column_id| column_id.get_column(database)),
+            );
             let mut proxy_values = values
                 .into_iter()
                 .map(ProxyColumn::Concrete)
                 .collect::<Vec<_>>();
-            proxy_values.push(ProxyColumn::Template {
-                col_index: 0,
-                step_id: parent_step_id,
-            });
+            proxy_values.extend((0..parent_column_ids.len()).map(|col_index| {
+                ProxyColumn::Template {
+                    col_index,
+                    step_id: parent_step_id,
+                }
+            }));
 
             let insert = TemplateSQLOperation::Insert(TemplateInsert {
                 table,
                 columns,
                 column_values_seq: vec![proxy_values],
-                returning: vec![pk_column],
+                returning: pk_columns().collect(),
             });
             transaction_script.add_step(TransactionStep::Template(TemplateTransactionStep {
                 operation: insert,
@@ -178,8 +190,18 @@ fn insert_self_row<'a>(
             }))
         }
         None => {
-            let insert =
-                SQLOperation::Insert(table.insert(columns, vec![values], vec![pk_column.into()]));
+            let on_conflict = on_conflict.map(|on_conflict| {
+                compute_on_conflict(on_conflict, &columns, database, transformer)
+            });
+
+            let insert = SQLOperation::Insert(Insert {
+                on_conflict,
+                ..table.insert(
+                    columns,
+                    vec![values],
+                    pk_columns().map(MaybeOwned::Owned).collect(),
+                )
+            });
             transaction_script.add_step(TransactionStep::Concrete(ConcreteTransactionStep::new(
                 insert,
             )))
@@ -187,28 +209,71 @@ fn insert_self_row<'a>(
     }
 }
 
+/// Compute the `ON CONFLICT` clause for a row being inserted into the given columns.
+///
+/// We update all supplied columns except the conflict columns (which must have the same values as
+/// in the existing row). However, if there are no such columns, we still update the conflict
+/// columns (to the same values), since otherwise Postgres would not return the existing row.
+fn compute_on_conflict<'a>(
+    on_conflict: &'a OnConflictUpdate,
+    columns: &[&'a PhysicalColumn],
+    database: &'a Database,
+    transformer: &Postgres,
+) -> OnConflict<'a> {
+    let conflict_columns: Vec<_> = on_conflict
+        .conflict_columns
+        .iter()
+        .map(|column_id| column_id.get_column(database))
+        .collect();
+
+    let update_columns: Vec<_> = columns
+        .iter()
+        .filter(|column| !conflict_columns.contains(column))
+        .copied()
+        .collect();
+
+    let update_columns = if update_columns.is_empty() {
+        conflict_columns.clone()
+    } else {
+        update_columns
+    };
+
+    OnConflict {
+        conflict_columns,
+        update_columns,
+        predicate: transformer.to_predicate(
+            &on_conflict.predicate,
+            &SelectionLevel::TopLevel,
+            false,
+            database,
+        ),
+    }
+}
+
 fn insert_nested_row<'a>(
     nested_row: &'a NestedInsertion,
     parent_step_id: TransactionStepId,
     transaction_script: &mut TransactionScript<'a>,
     database: &'a Database,
+    transformer: &Postgres,
 ) {
     let NestedInsertion {
         relation_id,
         insertions,
     } = nested_row;
 
-    let OneToMany {
-        foreign_column_id, ..
-    } = relation_id.deref(database);
+    let relation = relation_id.deref(database);
+    let foreign_column_ids = relation.foreign_column_ids();
 
     for insertion in insertions {
         insert_row(
-            foreign_column_id.table_id,
+            relation.foreign_table_id(),
             insertion,
-            Some((parent_step_id, foreign_column_id)),
+            Some((parent_step_id, &foreign_column_ids)),
+            None,
             transaction_script,
             database,
+            transformer,
         );
     }
 }
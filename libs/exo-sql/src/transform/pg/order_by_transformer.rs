@@ -9,9 +9,14 @@
 
 use crate::{
     asql::order_by::AbstractOrderByExpr,
-    sql::order::{OrderBy, OrderByElement, OrderByElementExpr, VectorDistanceOperand},
-    transform::transformer::OrderByTransformer,
-    AbstractOrderBy, ColumnPath, Database,
+    sql::{
+        order::{OrderBy, OrderByElement, OrderByElementExpr, VectorDistanceOperand},
+        predicate::ConcretePredicate,
+        select::Select,
+        table::Table,
+    },
+    transform::transformer::{OrderByTransformer, PredicateTransformer},
+    AbstractOrderBy, Column, ColumnPath, Database, Function, PhysicalColumnPath, RelationLink,
 };
 
 use super::{selection_level::SelectionLevel, Postgres};
@@ -36,11 +41,7 @@ impl OrderByTransformer for Postgres {
                 .iter()
                 .map(|(expr, ordering)| match expr {
                     AbstractOrderByExpr::Column(path) => {
-                        let table_alias = match (selection_level.prefix(database), path.alias()) {
-                            (Some(prefix), Some(alias)) => Some(format!("{}${}", prefix, alias)),
-                            (None, Some(alias)) => Some(alias),
-                            _ => None,
-                        };
+                        let table_alias = table_alias(path, selection_level, database);
 
                         let column_id = path.leaf_column();
                         OrderByElement::new(column_id, *ordering, table_alias)
@@ -61,6 +62,31 @@ impl OrderByTransformer for Postgres {
                         let rhs_column = to_column(rhs);
                         let expr = OrderByElementExpr::VectorDistance(lhs_column, rhs_column, *op);
 
+                        OrderByElement(expr, *ordering, None)
+                    }
+                    AbstractOrderByExpr::TextSearchRank(path, text, language, mode) => {
+                        let table_alias = table_alias(path, selection_level, database);
+
+                        let expr = OrderByElementExpr::TextSearchRank(
+                            path.leaf_column(),
+                            text.clone(),
+                            language.clone(),
+                            *mode,
+                        );
+                        OrderByElement(expr, *ordering, table_alias)
+                    }
+                    AbstractOrderByExpr::Aggregate(path, relation, function, predicate) => {
+                        let table_alias = table_alias(path, selection_level, database);
+                        // The subselect refers to the linked table without an alias
+                        let predicate =
+                            self.to_predicate(predicate, &SelectionLevel::TopLevel, true, database);
+                        let expr = OrderByElementExpr::SubSelect(Box::new(aggregate_select(
+                            relation,
+                            function,
+                            predicate,
+                            table_alias,
+                        )));
+
                         OrderByElement(expr, *ordering, None)
                     }
                 })
@@ -68,3 +94,59 @@ impl OrderByTransformer for Postgres {
         )
     }
 }
+
+fn table_alias(
+    path: &PhysicalColumnPath,
+    selection_level: &SelectionLevel,
+    database: &Database,
+) -> Option<String> {
+    match (selection_level.prefix(database), path.alias()) {
+        (Some(prefix), Some(alias)) => Some(format!("{}${}", prefix, alias)),
+        (None, Some(alias)) => Some(alias),
+        _ => None,
+    }
+}
+
+/// A subselect to compute an aggregate over the rows linked to the current row through a
+/// one-to-many relation. For example, to order venues by the maximum price of their concerts:
+///
+/// ```sql
+/// (SELECT max("concerts"."price") FROM "concerts" WHERE "concerts"."venue_id" = "venues"."id")
+/// ```
+///
+/// The predicate (such as `"concerts"."deleted_at" IS NULL`) further limits the linked rows.
+///
+/// The linked table must differ from the self table (the builder doesn't offer such ordering for
+/// self-referencing relations), since the subselect refers to the linked table without an alias.
+fn aggregate_select(
+    relation: &RelationLink,
+    function: &Function,
+    predicate: ConcretePredicate,
+    self_table_alias: Option<String>,
+) -> Select {
+    let relation_predicate =
+        relation
+            .column_pairs
+            .iter()
+            .fold(ConcretePredicate::True, |predicate, pair| {
+                ConcretePredicate::and(
+                    predicate,
+                    ConcretePredicate::eq(
+                        Column::physical(pair.foreign_column_id, None),
+                        Column::physical(pair.self_column_id, self_table_alias.clone()),
+                    ),
+                )
+            });
+    let predicate = ConcretePredicate::and(relation_predicate, predicate);
+
+    Select {
+        table: Table::physical(relation.linked_table_id(), None),
+        columns: vec![Column::Function(function.clone())],
+        predicate,
+        order_by: None,
+        offset: None,
+        limit: None,
+        group_by: None,
+        top_level_selection: false,
+    }
+}
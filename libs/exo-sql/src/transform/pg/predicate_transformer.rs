@@ -97,6 +97,24 @@ fn to_join_predicate(
             ConcretePredicate::JsonMatchAllKeys(compute_leaf_column(l), compute_leaf_column(r))
         }
 
+        AbstractPredicate::ArrayContains(l, r) => {
+            ConcretePredicate::ArrayContains(compute_leaf_column(l), compute_leaf_column(r))
+        }
+        AbstractPredicate::ArrayContainedBy(l, r) => {
+            ConcretePredicate::ArrayContainedBy(compute_leaf_column(l), compute_leaf_column(r))
+        }
+        AbstractPredicate::ArrayOverlaps(l, r) => {
+            ConcretePredicate::ArrayOverlaps(compute_leaf_column(l), compute_leaf_column(r))
+        }
+        AbstractPredicate::ArrayAnyEq(l, r) => {
+            ConcretePredicate::ArrayAnyEq(compute_leaf_column(l), compute_leaf_column(r))
+        }
+        AbstractPredicate::ArrayLength(l, comparator, r) => ConcretePredicate::ArrayLength(
+            compute_leaf_column(l),
+            *comparator,
+            compute_leaf_column(r),
+        ),
+
         AbstractPredicate::VectorDistance(
             c1,
             c2,
@@ -111,6 +129,13 @@ fn to_join_predicate(
             compute_leaf_column(threshold),
         ),
 
+        AbstractPredicate::TextSearch(l, r, language, mode) => ConcretePredicate::TextSearch(
+            compute_leaf_column(l),
+            compute_leaf_column(r),
+            language.clone(),
+            *mode,
+        ),
+
         AbstractPredicate::And(l, r) => ConcretePredicate::and(
             to_join_predicate(l, selection_level, database),
             to_join_predicate(r, selection_level, database),
@@ -180,19 +205,22 @@ fn form_subselect(
     database: &Database,
     select_transformer: &Postgres,
 ) -> ConcretePredicate {
-    let RelationLink {
-        self_column_id,
-        foreign_column_id,
-        ..
-    } = relation_link;
+    let self_table_id = relation_link.self_table_id();
 
-    let foreign_column = foreign_column_id.get_column(database);
     let abstract_select = AbstractSelect {
-        table_id: self_column_id.table_id,
-        selection: Selection::Seq(vec![AliasedSelectionElement::new(
-            foreign_column.name.clone(),
-            SelectionElement::Physical(foreign_column_id),
-        )]),
+        table_id: self_table_id,
+        selection: Selection::Seq(
+            relation_link
+                .column_pairs
+                .iter()
+                .map(|pair| {
+                    AliasedSelectionElement::new(
+                        pair.foreign_column_id.get_column(database).name.clone(),
+                        SelectionElement::Physical(pair.foreign_column_id),
+                    )
+                })
+                .collect(),
+        ),
         predicate,
         order_by: None,
         offset: None,
@@ -208,7 +236,24 @@ fn form_subselect(
 
     let select_column = Column::SubSelect(Box::new(select));
 
-    ConcretePredicate::In(Column::physical(self_column_id, None), select_column)
+    // For a composite key, compare the row of self columns, i.e. `(a, b) IN (SELECT x, y ...)`
+    let self_column = match relation_link.column_pairs.as_slice() {
+        [pair] => Column::physical(pair.self_column_id, None),
+        pairs => Column::Row(
+            pairs
+                .iter()
+                .map(|pair| Column::physical(pair.self_column_id, None))
+                .collect(),
+        ),
+    };
+
+    let predicate = ConcretePredicate::In(self_column, select_column);
+
+    if relation_link.negated {
+        ConcretePredicate::Not(Box::new(predicate))
+    } else {
+        predicate
+    }
 }
 
 fn leaf_column(
@@ -405,6 +450,20 @@ fn attempt_subselect_predicate(
             binary_operator(l, r, AbstractPredicate::JsonMatchAllKeys)
         }
 
+        AbstractPredicate::ArrayContains(l, r) => {
+            binary_operator(l, r, AbstractPredicate::ArrayContains)
+        }
+        AbstractPredicate::ArrayContainedBy(l, r) => {
+            binary_operator(l, r, AbstractPredicate::ArrayContainedBy)
+        }
+        AbstractPredicate::ArrayOverlaps(l, r) => {
+            binary_operator(l, r, AbstractPredicate::ArrayOverlaps)
+        }
+        AbstractPredicate::ArrayAnyEq(l, r) => binary_operator(l, r, AbstractPredicate::ArrayAnyEq),
+        AbstractPredicate::ArrayLength(l, comparator, r) => binary_operator(l, r, |l, r| {
+            AbstractPredicate::ArrayLength(l, *comparator, r)
+        }),
+
         AbstractPredicate::VectorDistance(l, r, distance_function, comparator, comparator_path) => {
             vector_distance_subselect_predicate(
                 l,
@@ -415,6 +474,10 @@ fn attempt_subselect_predicate(
             )
         }
 
+        AbstractPredicate::TextSearch(l, r, language, mode) => binary_operator(l, r, |l, r| {
+            AbstractPredicate::TextSearch(l, r, language.clone(), *mode)
+        }),
+
         AbstractPredicate::And(l, r) => logical_binary_op(l, r, AbstractPredicate::And),
         AbstractPredicate::Or(l, r) => logical_binary_op(l, r, AbstractPredicate::Or),
         AbstractPredicate::Not(p) => attempt_subselect_predicate(p)
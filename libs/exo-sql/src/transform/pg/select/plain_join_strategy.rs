@@ -87,6 +87,9 @@ impl SelectionStrategy for PlainJoinStrategy {
                 && selection_context.abstract_select.limit.is_none();
 
             no_non_predicate_clauses && !selection_context.has_a_one_to_many_predicate
+        } else if selection_context.has_a_negated_predicate {
+            // A negated predicate can't be expressed through a join
+            false
         } else {
             // The expected output is non-aggregate, so if duplicated rows are allowed, this
             // is a suitable strategy (typically useful as a column in `IN` predicate such as that used by delete)
@@ -65,7 +65,10 @@ impl SelectionStrategy for PlainSubqueryStrategy {
     }
 
     fn suitable(&self, selection_context: &SelectionContext) -> bool {
-        !selection_context.has_a_one_to_many_predicate || selection_context.allow_duplicate_rows
+        // A negated predicate can't be expressed through a join
+        !selection_context.has_a_negated_predicate
+            && (!selection_context.has_a_one_to_many_predicate
+                || selection_context.allow_duplicate_rows)
     }
 
     fn to_select(&self, selection_context: SelectionContext<'_>, database: &Database) -> Select {
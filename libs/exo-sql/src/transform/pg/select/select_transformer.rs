@@ -10,16 +10,35 @@
 use tracing::instrument;
 
 use crate::{
-    asql::select::AbstractSelect,
+    asql::{
+        function_select::AbstractFunctionSelect,
+        group_by_select::{AbstractGroupBySelect, GroupColumn},
+        record_select::AbstractRecordSelect,
+        select::AbstractSelect,
+    },
     sql::{
+        cte::{CteExpression, WithQuery},
+        group_by::GroupBy,
+        json_agg::JsonAgg,
+        order::{OrderBy, OrderByElement, OrderByElementExpr},
+        predicate::ConcretePredicate,
         select::Select,
         sql_operation::SQLOperation,
+        table::Table,
         transaction::{ConcreteTransactionStep, TransactionScript, TransactionStep},
     },
-    transform::{pg::selection_level::SelectionLevel, transformer::SelectTransformer},
-    Database,
+    transform::{
+        pg::selection_level::SelectionLevel,
+        transformer::{PredicateTransformer, SelectTransformer},
+    },
+    AliasedSelectionElement, Column, Database, PhysicalTableName, SQLParamContainer, TableId,
 };
 
+/// The alias of the sub-select that computes the groups in a group-by select
+const GROUPS_ALIAS: &str = "groups";
+/// The suffix of the alias of the rows returned by a function (such as `concerts$fn`)
+const FUNCTION_ALIAS_SUFFIX: &str = "$fn";
+
 use super::{
     selection_context::SelectionContext, selection_strategy_chain::SelectionStrategyChain,
 };
@@ -127,9 +146,233 @@ impl SelectTransformer for Postgres {
         )));
         transaction_script
     }
+
+    fn to_record_transaction_script<'a>(
+        &self,
+        abstract_record_select: &'a AbstractRecordSelect,
+        database: &'a Database,
+    ) -> TransactionScript<'a> {
+        let with_query = self.to_record_select(abstract_record_select, database);
+
+        let mut transaction_script = TransactionScript::default();
+        transaction_script.add_step(TransactionStep::Concrete(ConcreteTransactionStep::new(
+            SQLOperation::WithQuery(with_query),
+        )));
+        transaction_script
+    }
+
+    fn to_function_transaction_script<'a>(
+        &self,
+        abstract_function_select: &'a AbstractFunctionSelect,
+        database: &'a Database,
+    ) -> TransactionScript<'a> {
+        let select = self.to_function_select(abstract_function_select, database);
+
+        let mut transaction_script = TransactionScript::default();
+        transaction_script.add_step(TransactionStep::Concrete(ConcreteTransactionStep::new(
+            SQLOperation::Select(select),
+        )));
+        transaction_script
+    }
+
+    fn to_group_by_transaction_script<'a>(
+        &self,
+        abstract_group_by_select: &'a AbstractGroupBySelect,
+        database: &'a Database,
+    ) -> TransactionScript<'a> {
+        let select = self.to_group_by_select(abstract_group_by_select, database);
+
+        let mut transaction_script = TransactionScript::default();
+        transaction_script.add_step(TransactionStep::Concrete(ConcreteTransactionStep::new(
+            SQLOperation::Select(select),
+        )));
+        transaction_script
+    }
 }
 
 impl Postgres {
+    /// Form a select that uses the supplied record in place of the table being selected from.
+    ///
+    /// We use a CTE to shadow the table with the record, so the rest of the select (including
+    /// joins to related tables) remains the same as for a usual select:
+    ///
+    /// ```sql
+    /// WITH "concerts" AS (
+    ///    SELECT * FROM jsonb_populate_record(NULL::"concerts", $1)
+    /// ) SELECT COALESCE(...)::text FROM "concerts" WHERE ...
+    /// ```
+    fn to_record_select<'a>(
+        &self,
+        abstract_record_select: &AbstractRecordSelect,
+        database: &Database,
+    ) -> WithQuery<'a> {
+        let abstract_select = &abstract_record_select.select;
+
+        let record_select = Select {
+            table: Table::Record {
+                table_id: abstract_select.table_id,
+                record: SQLParamContainer::json(abstract_record_select.record.clone()),
+            },
+            columns: vec![Column::Star(None)],
+            predicate: ConcretePredicate::True,
+            order_by: None,
+            offset: None,
+            limit: None,
+            group_by: None,
+            top_level_selection: false,
+        };
+
+        let select = self.to_select(abstract_select, database);
+
+        let table_name = &database.get_table(abstract_select.table_id).name;
+        WithQuery {
+            expressions: vec![CteExpression::new_auto_name(
+                table_name,
+                SQLOperation::Select(record_select),
+            )],
+            select,
+        }
+    }
+
+    /// Form a select that uses the rows returned by the function in place of the table being
+    /// selected from.
+    ///
+    /// Unlike [`Self::to_record_select`], we don't shadow the table with a CTE (which would make
+    /// nested selects of the same table, such as the concerts of the venue of a returned concert,
+    /// see only the returned rows). Instead, the function call gets an alias of its own, so only
+    /// the references to the returned rows use it:
+    ///
+    /// ```sql
+    /// SELECT COALESCE(...)::text FROM "reporting"."top_concerts"($1) AS "concerts$fn" WHERE ...
+    /// ```
+    fn to_function_select(
+        &self,
+        abstract_function_select: &AbstractFunctionSelect,
+        database: &Database,
+    ) -> Select {
+        let abstract_select = &abstract_function_select.select;
+        let table_name = &database.get_table(abstract_select.table_id).name;
+
+        let function = Table::Function {
+            name: abstract_function_select.function_name.clone(),
+            args: abstract_function_select
+                .args
+                .iter()
+                .map(|arg| match arg {
+                    Some(arg) => Column::Param(arg.clone()),
+                    None => Column::Null,
+                })
+                .collect(),
+            alias: (
+                format!("{}{FUNCTION_ALIAS_SUFFIX}", table_name.synthetic_name()),
+                table_name.clone(),
+            ),
+        };
+
+        let mut select = self.to_select(abstract_select, database);
+        replace_base_table(&mut select, abstract_select.table_id, function);
+        select
+    }
+
+    /// Form a select that groups the rows and returns a JSON array with an element for each group.
+    ///
+    /// Since aggregates cannot be nested (we cannot `json_agg` a row with a `count`), we compute the
+    /// groups in a sub-select (each element of the selection becomes a column aliased to its key)
+    /// and aggregate its rows in the enclosing select:
+    ///
+    /// ```sql
+    /// SELECT COALESCE(json_agg("groups".*), '[]'::json)::text FROM (
+    ///    SELECT "concerts"."venue_id" AS "venueId", count("concerts"."id") AS "count" FROM "concerts"
+    ///        WHERE ... GROUP BY "concerts"."venue_id" HAVING count("concerts"."id") > $1
+    ///        ORDER BY count("concerts"."id") DESC LIMIT $2 OFFSET $3
+    /// ) AS "groups"
+    /// ```
+    fn to_group_by_select(
+        &self,
+        abstract_group_by_select: &AbstractGroupBySelect,
+        database: &Database,
+    ) -> Select {
+        let AbstractGroupBySelect {
+            table_id,
+            group_by,
+            selection,
+            predicate,
+            having,
+            order_by,
+            offset,
+            limit,
+        } = abstract_group_by_select;
+
+        let selection_level = SelectionLevel::TopLevel;
+
+        let columns = selection
+            .iter()
+            .map(|AliasedSelectionElement { alias, column }| {
+                Column::Aliased(
+                    Box::new(column.to_sql(&selection_level, self, database)),
+                    alias.clone(),
+                )
+            })
+            .collect();
+
+        // The predicate may refer to related tables, so we let the predicate transformer bring
+        // them in (through subselects)
+        let predicate = self.to_predicate(predicate, &selection_level, false, database);
+
+        let having = having.map_columns(&|group_column| match group_column {
+            GroupColumn::Grouped(column_id) => Column::physical(*column_id, None),
+            GroupColumn::Aggregate(function) => Column::Function(function.clone()),
+            GroupColumn::Param(param) => Column::Param(param.clone()),
+        });
+
+        let order_by: Vec<_> = order_by
+            .iter()
+            .filter_map(|(group_column, ordering)| {
+                let expr = match group_column {
+                    GroupColumn::Grouped(column_id) => OrderByElementExpr::Column(*column_id),
+                    GroupColumn::Aggregate(function) => {
+                        OrderByElementExpr::Function(function.clone())
+                    }
+                    // Ordering by a literal has no effect
+                    GroupColumn::Param(_) => return None,
+                };
+                Some(OrderByElement(expr, *ordering, None))
+            })
+            .collect();
+
+        let groups_select = Select {
+            table: Table::physical(*table_id, None),
+            columns,
+            predicate,
+            order_by: (!order_by.is_empty()).then_some(OrderBy(order_by)),
+            offset: offset.clone(),
+            limit: limit.clone(),
+            group_by: Some(GroupBy {
+                columns: group_by.clone(),
+                having,
+            }),
+            top_level_selection: false,
+        };
+
+        let groups_table_name = PhysicalTableName::new(GROUPS_ALIAS, None);
+
+        Select {
+            table: Table::SubSelect {
+                select: Box::new(groups_select),
+                alias: Some((GROUPS_ALIAS.to_string(), groups_table_name.clone())),
+            },
+            columns: vec![Column::JsonAgg(JsonAgg(Box::new(Column::Star(Some(
+                groups_table_name,
+            )))))],
+            predicate: ConcretePredicate::True,
+            order_by: None,
+            offset: None,
+            limit: None,
+            group_by: None,
+            top_level_selection: true,
+        }
+    }
+
     /// A lower-level version of [`to_select`] that allows for additional predicates and
     /// control over whether duplicate rows are allowed.
     pub fn compute_select(
@@ -151,11 +394,26 @@ impl Postgres {
     }
 }
 
+/// Replace the table that the rows of the select come from (possibly through a join or a sub-select
+/// in the `FROM` clause) with the given source
+fn replace_base_table(select: &mut Select, table_id: TableId, source: Table) {
+    let base = select.table.base_mut();
+
+    match base {
+        Table::SubSelect { select, .. } => replace_base_table(select, table_id, source),
+        Table::Physical {
+            table_id: base_table_id,
+            alias: None,
+        } if *base_table_id == table_id => *base = source,
+        _ => {}
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
         asql::{
-            column_path::{ColumnPath, PhysicalColumnPath},
+            column_path::{ColumnPath, ColumnPathLink, PhysicalColumnPath},
             order_by::AbstractOrderByExpr,
             predicate::AbstractPredicate,
             selection::{
@@ -164,10 +422,13 @@ mod tests {
         },
         sql::{predicate::Predicate, SQLParamContainer},
         transform::{pg::Postgres, test_util::TestSetup, transformer::SelectTransformer},
-        AbstractOrderBy, Limit, Offset, Ordering, RelationId,
+        AbstractOrderBy, Function, Limit, Offset, Ordering, PhysicalTableName, RelationId,
     };
 
-    use super::AbstractSelect;
+    use super::{
+        AbstractFunctionSelect, AbstractGroupBySelect, AbstractRecordSelect, AbstractSelect,
+        GroupColumn,
+    };
     use crate::sql::ExpressionBuilder;
 
     use multiplatform_test::multiplatform_test;
@@ -202,6 +463,252 @@ mod tests {
         );
     }
 
+    #[multiplatform_test]
+    fn record_selection() {
+        TestSetup::with_setup(
+            |TestSetup {
+                 database,
+                 concerts_table,
+                 concerts_id_column,
+                 concerts_name_column,
+                 ..
+             }| {
+                let aselect = AbstractSelect {
+                    table_id: concerts_table,
+                    selection: Selection::Seq(vec![AliasedSelectionElement::new(
+                        "id".to_string(),
+                        SelectionElement::Physical(concerts_id_column),
+                    )]),
+                    predicate: AbstractPredicate::Eq(
+                        ColumnPath::Physical(PhysicalColumnPath::leaf(concerts_name_column)),
+                        ColumnPath::Param(SQLParamContainer::string("v1".to_string())),
+                    ),
+                    order_by: None,
+                    offset: None,
+                    limit: None,
+                };
+
+                let record = serde_json::json!({"id": 1, "name": "v1"});
+                let record_select = AbstractRecordSelect {
+                    record: record.clone(),
+                    select: aselect,
+                };
+
+                let select = Postgres {}.to_record_select(&record_select, &database);
+                assert_binding!(
+                    select.to_sql(&database),
+                    r#"WITH "concerts" AS (SELECT * FROM jsonb_populate_record(NULL::"concerts", $1)) SELECT "concerts"."id" FROM "concerts" WHERE "concerts"."name" = $2"#,
+                    record,
+                    "v1".to_string()
+                );
+            },
+        );
+    }
+
+    #[multiplatform_test]
+    fn function_selection() {
+        TestSetup::with_setup(
+            |TestSetup {
+                 database,
+                 concerts_table,
+                 concerts_id_column,
+                 concerts_name_column,
+                 ..
+             }| {
+                let aselect = AbstractSelect {
+                    table_id: concerts_table,
+                    selection: Selection::Seq(vec![AliasedSelectionElement::new(
+                        "id".to_string(),
+                        SelectionElement::Physical(concerts_id_column),
+                    )]),
+                    predicate: AbstractPredicate::Eq(
+                        ColumnPath::Physical(PhysicalColumnPath::leaf(concerts_name_column)),
+                        ColumnPath::Param(SQLParamContainer::string("v1".to_string())),
+                    ),
+                    order_by: None,
+                    offset: None,
+                    limit: None,
+                };
+
+                let function_select = AbstractFunctionSelect {
+                    function_name: PhysicalTableName::new("top_concerts", Some("reporting")),
+                    args: vec![Some(SQLParamContainer::i32(10)), None],
+                    select: aselect,
+                };
+
+                let select = Postgres {}.to_function_select(&function_select, &database);
+                assert_binding!(
+                    select.to_sql(&database),
+                    r#"SELECT "concerts$fn"."id" FROM "reporting"."top_concerts"($1, NULL) AS "concerts$fn" WHERE "concerts$fn"."name" = $2"#,
+                    10,
+                    "v1".to_string()
+                );
+            },
+        );
+    }
+
+    #[multiplatform_test]
+    fn function_selection_with_nested_back_relation() {
+        // topConcerts(count: 10) { id, venue { id, concerts { id } } }
+        TestSetup::with_setup(
+            |TestSetup {
+                 database,
+                 concerts_table,
+                 venues_table,
+                 concerts_id_column,
+                 venues_id_column,
+                 concerts_venue_id_column,
+                 ..
+             }| {
+                let venue_concerts_select = AbstractSelect {
+                    table_id: concerts_table,
+                    selection: Selection::Json(
+                        vec![AliasedSelectionElement::new(
+                            "id".to_string(),
+                            SelectionElement::Physical(concerts_id_column),
+                        )],
+                        SelectionCardinality::Many,
+                    ),
+                    predicate: Predicate::True,
+                    order_by: None,
+                    offset: None,
+                    limit: None,
+                };
+
+                let venue_select = AbstractSelect {
+                    table_id: venues_table,
+                    selection: Selection::Json(
+                        vec![
+                            AliasedSelectionElement::new(
+                                "id".to_string(),
+                                SelectionElement::Physical(venues_id_column),
+                            ),
+                            AliasedSelectionElement::new(
+                                "concerts".to_string(),
+                                SelectionElement::SubSelect(
+                                    RelationId::OneToMany(
+                                        concerts_venue_id_column
+                                            .get_otm_relation(&database)
+                                            .unwrap(),
+                                    ),
+                                    venue_concerts_select,
+                                ),
+                            ),
+                        ],
+                        SelectionCardinality::One,
+                    ),
+                    predicate: Predicate::True,
+                    order_by: None,
+                    offset: None,
+                    limit: None,
+                };
+
+                let aselect = AbstractSelect {
+                    table_id: concerts_table,
+                    selection: Selection::Json(
+                        vec![
+                            AliasedSelectionElement::new(
+                                "id".to_string(),
+                                SelectionElement::Physical(concerts_id_column),
+                            ),
+                            AliasedSelectionElement::new(
+                                "venue".to_string(),
+                                SelectionElement::SubSelect(
+                                    RelationId::ManyToOne(
+                                        concerts_venue_id_column
+                                            .get_mto_relation(&database)
+                                            .unwrap(),
+                                    ),
+                                    venue_select,
+                                ),
+                            ),
+                        ],
+                        SelectionCardinality::Many,
+                    ),
+                    predicate: Predicate::True,
+                    order_by: None,
+                    offset: None,
+                    limit: None,
+                };
+
+                let function_select = AbstractFunctionSelect {
+                    function_name: PhysicalTableName::new("top_concerts", Some("reporting")),
+                    args: vec![Some(SQLParamContainer::i32(10))],
+                    select: aselect,
+                };
+
+                // The venue refers to the returned concert, but the concerts of the venue come
+                // from the table
+                let select = Postgres {}.to_function_select(&function_select, &database);
+                assert_binding!(
+                    select.to_sql(&database),
+                    r#"SELECT COALESCE(json_agg(json_build_object('id', "concerts$fn"."id", 'venue', (SELECT json_build_object('id', "venues"."id", 'concerts', (SELECT COALESCE(json_agg(json_build_object('id', "concerts"."id")), '[]'::json) FROM "concerts" WHERE "venues"."id" = "concerts"."venue_id")) FROM "venues" WHERE "concerts$fn"."venue_id" = "venues"."id"))), '[]'::json)::text FROM "reporting"."top_concerts"($1) AS "concerts$fn""#,
+                    10
+                );
+            },
+        );
+    }
+
+    #[multiplatform_test]
+    fn group_by_selection() {
+        TestSetup::with_setup(
+            |TestSetup {
+                 database,
+                 concerts_table,
+                 concerts_id_column,
+                 concerts_name_column,
+                 concerts_venue_id_column,
+                 ..
+             }| {
+                let count = Function::Named {
+                    function_name: "count".to_string(),
+                    column_id: concerts_id_column,
+                };
+
+                let group_by_select = AbstractGroupBySelect {
+                    table_id: concerts_table,
+                    group_by: vec![concerts_venue_id_column],
+                    selection: vec![
+                        AliasedSelectionElement::new(
+                            "venueId".to_string(),
+                            SelectionElement::Physical(concerts_venue_id_column),
+                        ),
+                        AliasedSelectionElement::new(
+                            "count".to_string(),
+                            SelectionElement::Function(count.clone()),
+                        ),
+                    ],
+                    predicate: AbstractPredicate::Neq(
+                        ColumnPath::Physical(PhysicalColumnPath::leaf(concerts_name_column)),
+                        ColumnPath::Param(SQLParamContainer::string("c1".to_string())),
+                    ),
+                    having: Predicate::Gt(
+                        GroupColumn::Aggregate(count.clone()),
+                        GroupColumn::Param(SQLParamContainer::i64(1)),
+                    ),
+                    order_by: vec![
+                        (GroupColumn::Aggregate(count), Ordering::Desc),
+                        (
+                            GroupColumn::Grouped(concerts_venue_id_column),
+                            Ordering::Asc,
+                        ),
+                    ],
+                    offset: None,
+                    limit: Some(Limit(5)),
+                };
+
+                let select = Postgres {}.to_group_by_select(&group_by_select, &database);
+                assert_binding!(
+                    select.to_sql(&database),
+                    r#"SELECT COALESCE(json_agg("groups".*), '[]'::json)::text FROM (SELECT "concerts"."venue_id" AS "venueId", count("concerts"."id") AS "count" FROM "concerts" WHERE "concerts"."name" <> $1 GROUP BY "concerts"."venue_id" HAVING count("concerts"."id") > $2 ORDER BY count("concerts"."id") DESC, "concerts"."venue_id" ASC LIMIT $3) AS "groups""#,
+                    "c1".to_string(),
+                    1i64,
+                    5i64
+                );
+            },
+        );
+    }
+
     #[multiplatform_test]
     fn simple_predicate() {
         TestSetup::with_setup(
@@ -556,6 +1063,116 @@ mod tests {
         );
     }
 
+    #[multiplatform_test]
+    fn aggregate_order_by() {
+        TestSetup::with_setup(
+            |TestSetup {
+                 database,
+                 venues_table,
+                 venues_id_column,
+                 concerts_id_column,
+                 concerts_venue_id_column,
+                 ..
+             }| {
+                let ColumnPathLink::Relation(venue_concerts_link) = concerts_venue_id_column
+                    .get_otm_relation(&database)
+                    .unwrap()
+                    .deref(&database)
+                    .column_path_link()
+                else {
+                    panic!("Expected a relation link")
+                };
+
+                let aselect = AbstractSelect {
+                    table_id: venues_table,
+                    selection: Selection::Seq(vec![AliasedSelectionElement::new(
+                        "id".to_string(),
+                        SelectionElement::Physical(venues_id_column),
+                    )]),
+                    predicate: Predicate::True,
+                    order_by: Some(AbstractOrderBy(vec![(
+                        AbstractOrderByExpr::Aggregate(
+                            PhysicalColumnPath::leaf(venues_id_column),
+                            venue_concerts_link,
+                            Function::Named {
+                                function_name: "count".to_string(),
+                                column_id: concerts_id_column,
+                            },
+                            Predicate::True,
+                        ),
+                        Ordering::Desc,
+                    )])),
+                    offset: None,
+                    limit: None,
+                };
+
+                let select = Postgres {}.to_select(&aselect, &database);
+                assert_binding!(
+                    select.to_sql(&database),
+                    r#"SELECT "venues"."id" FROM "venues" ORDER BY (SELECT count("concerts"."id") FROM "concerts" WHERE "concerts"."venue_id" = "venues"."id") DESC"#
+                );
+            },
+        );
+    }
+
+    #[multiplatform_test]
+    fn aggregate_order_by_with_predicate() {
+        TestSetup::with_setup(
+            |TestSetup {
+                 database,
+                 venues_table,
+                 venues_id_column,
+                 concerts_id_column,
+                 concerts_name_column,
+                 concerts_venue_id_column,
+                 ..
+             }| {
+                let ColumnPathLink::Relation(venue_concerts_link) = concerts_venue_id_column
+                    .get_otm_relation(&database)
+                    .unwrap()
+                    .deref(&database)
+                    .column_path_link()
+                else {
+                    panic!("Expected a relation link")
+                };
+
+                let aselect = AbstractSelect {
+                    table_id: venues_table,
+                    selection: Selection::Seq(vec![AliasedSelectionElement::new(
+                        "id".to_string(),
+                        SelectionElement::Physical(venues_id_column),
+                    )]),
+                    predicate: Predicate::True,
+                    order_by: Some(AbstractOrderBy(vec![(
+                        AbstractOrderByExpr::Aggregate(
+                            PhysicalColumnPath::leaf(venues_id_column),
+                            venue_concerts_link,
+                            Function::Named {
+                                function_name: "count".to_string(),
+                                column_id: concerts_id_column,
+                            },
+                            AbstractPredicate::neq(
+                                ColumnPath::Physical(PhysicalColumnPath::leaf(
+                                    concerts_name_column,
+                                )),
+                                ColumnPath::Null,
+                            ),
+                        ),
+                        Ordering::Desc,
+                    )])),
+                    offset: None,
+                    limit: None,
+                };
+
+                let select = Postgres {}.to_select(&aselect, &database);
+                assert_binding!(
+                    select.to_sql(&database),
+                    r#"SELECT "venues"."id" FROM "venues" ORDER BY (SELECT count("concerts"."id") FROM "concerts" WHERE "concerts"."venue_id" = "venues"."id" AND "concerts"."name" IS NOT NULL) DESC"#
+                );
+            },
+        );
+    }
+
     #[multiplatform_test]
     fn with_predicate_limit_and_offset() {
         TestSetup::with_setup(
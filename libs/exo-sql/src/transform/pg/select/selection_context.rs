@@ -17,6 +17,8 @@ use crate::{
 pub(crate) struct SelectionContext<'c> {
     pub abstract_select: &'c AbstractSelect,
     pub has_a_one_to_many_predicate: bool,
+    /// Does the predicate go through a negated link (which can be expressed only through a subselect)?
+    pub has_a_negated_predicate: bool,
     pub predicate_column_paths: Vec<PhysicalColumnPath>,
     pub order_by_column_paths: Vec<PhysicalColumnPath>,
     pub selection_level: &'c SelectionLevel,
@@ -63,9 +65,12 @@ impl<'c> SelectionContext<'c> {
             .iter()
             .any(|path| path.has_one_to_many(database));
 
+        let has_a_negated_predicate = predicate_column_paths.iter().any(|path| path.has_negated());
+
         Self {
             abstract_select,
             has_a_one_to_many_predicate,
+            has_a_negated_predicate,
             predicate_column_paths,
             order_by_column_paths,
             selection_level,
@@ -17,7 +17,7 @@ use crate::{
         pg::{selection_level::SelectionLevel, Postgres},
         transformer::{OrderByTransformer, PredicateTransformer},
     },
-    AbstractOrderBy, AbstractPredicate, Column, Database, Limit, ManyToOne, Offset, OneToMany,
+    AbstractOrderBy, AbstractPredicate, Column, Database, Limit, ManyToMany, ManyToManyId, Offset,
     PhysicalColumnPath, RelationId, Selection, TableId,
 };
 
@@ -151,35 +151,106 @@ pub(super) fn compute_relation_predicate(
 
     subselect_relation
         .map(|relation_id| {
-            let (self_column_id, foreign_column_id) = match relation_id {
-                RelationId::OneToMany(relation_id) => {
-                    let OneToMany {
-                        self_pk_column_id,
-                        foreign_column_id,
-                    } = relation_id.deref(database);
-
-                    (self_pk_column_id, foreign_column_id)
-                }
-                RelationId::ManyToOne(relation_id) => {
-                    let ManyToOne {
-                        self_column_id,
-                        foreign_pk_column_id,
-                        ..
-                    } = relation_id.deref(database);
-                    (self_column_id, foreign_pk_column_id)
+            let column_pairs = match relation_id {
+                RelationId::OneToMany(relation_id) => relation_id.deref(database).column_pairs,
+                RelationId::ManyToOne(relation_id) => relation_id.deref(database).column_pairs,
+                RelationId::ManyToMany(relation_id) => {
+                    return compute_many_to_many_predicate(
+                        relation_id,
+                        selection_level,
+                        use_alias,
+                        database,
+                    )
                 }
             };
 
             let alias = if use_alias {
-                Some(selection_level.alias((self_column_id.table_id, None), database))
+                Some(
+                    selection_level
+                        .alias((column_pairs[0].self_column_id.table_id, None), database),
+                )
             } else {
                 None
             };
 
-            ConcretePredicate::Eq(
-                Column::physical(self_column_id, alias),
-                Column::physical(foreign_column_id, None),
-            )
+            column_pairs
+                .into_iter()
+                .fold(ConcretePredicate::True, |acc, pair| {
+                    ConcretePredicate::and(
+                        acc,
+                        ConcretePredicate::Eq(
+                            Column::physical(pair.self_column_id, alias.clone()),
+                            Column::physical(pair.foreign_column_id, None),
+                        ),
+                    )
+                })
         })
         .unwrap_or(ConcretePredicate::True)
 }
+
+/// Compute the predicate to pick the foreign rows linked to the self row through the linking table.
+///
+/// For the `concerts <-> artists` relation through the `concert_artists` table, the predicate
+/// will be:
+/// ```sql
+/// "artists"."id" IN (SELECT "concert_artists"."artist_id" FROM "concert_artists" WHERE "concert_artists"."concert_id" = "concerts"."id")
+/// ```
+fn compute_many_to_many_predicate(
+    relation_id: ManyToManyId,
+    selection_level: &SelectionLevel,
+    use_alias: bool,
+    database: &Database,
+) -> ConcretePredicate {
+    let ManyToMany {
+        self_link,
+        foreign_link,
+    } = relation_id.deref(database);
+
+    let alias = if use_alias {
+        Some(selection_level.alias((self_link.self_table_id(), None), database))
+    } else {
+        None
+    };
+
+    let linking_predicate =
+        self_link
+            .column_pairs
+            .iter()
+            .fold(ConcretePredicate::True, |acc, pair| {
+                ConcretePredicate::and(
+                    acc,
+                    ConcretePredicate::Eq(
+                        Column::physical(pair.self_column_id, alias.clone()),
+                        Column::physical(pair.foreign_column_id, None),
+                    ),
+                )
+            });
+
+    let linking_select = Select {
+        table: Table::physical(self_link.foreign_table_id(), None),
+        columns: foreign_link
+            .column_pairs
+            .iter()
+            .map(|pair| Column::physical(pair.self_column_id, None))
+            .collect(),
+        predicate: linking_predicate,
+        order_by: None,
+        offset: None,
+        limit: None,
+        group_by: None,
+        top_level_selection: false,
+    };
+
+    // For a composite key, compare the row of foreign columns, i.e. `(a, b) IN (SELECT x, y ...)`
+    let foreign_column = match foreign_link.column_pairs.as_slice() {
+        [pair] => Column::physical(pair.foreign_column_id, None),
+        pairs => Column::Row(
+            pairs
+                .iter()
+                .map(|pair| Column::physical(pair.foreign_column_id, None))
+                .collect(),
+        ),
+    };
+
+    ConcretePredicate::In(foreign_column, Column::SubSelect(Box::new(linking_select)))
+}
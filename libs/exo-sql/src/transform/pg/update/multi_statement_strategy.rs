@@ -11,7 +11,6 @@ use maybe_owned::MaybeOwned;
 
 use crate::{
     sql::{
-        column::ArrayParamWrapper,
         delete::TemplateDelete,
         select::Select,
         sql_operation::TemplateSQLOperation,
@@ -23,7 +22,7 @@ use crate::{
     },
     transform::transformer::{InsertTransformer, PredicateTransformer},
     ColumnId, NestedAbstractDelete, NestedAbstractInsert, NestedAbstractInsertSet,
-    NestedAbstractUpdate, PhysicalColumn, Predicate, SQLParamContainer,
+    NestedAbstractUpdate, PhysicalColumn, Predicate,
 };
 
 use crate::{
@@ -87,25 +86,21 @@ impl UpdateStrategy for MultiStatementStrategy {
             database,
         );
 
-        // Select only the primary key column, so that we can use that
-        // as the proxy column in the nested updates added to the transaction script.
-        let return_col = Column::physical(
-            database
-                .get_pk_column_id(abstract_update.table_id)
-                .expect("No primary key column"),
-            None,
-        );
+        // Select only the primary key columns, so that we can use them
+        // as the proxy columns in the nested updates added to the transaction script.
+        let return_cols = database
+            .get_pk_column_ids(abstract_update.table_id)
+            .into_iter()
+            .map(|pk_column_id| Column::physical(pk_column_id, None).into())
+            .collect();
 
         let table = database.get_table(abstract_update.table_id);
         let column_values = column_id_values
             .into_iter()
             .map(|(col_id, col)| (col_id.get_column(database), col))
             .collect();
-        let root_update = SQLOperation::Update(table.update(
-            column_values,
-            predicate.into(),
-            vec![return_col.into()],
-        ));
+        let root_update =
+            SQLOperation::Update(table.update(column_values, predicate.into(), return_cols));
 
         let root_step_id = transaction_script.add_step(TransactionStep::Concrete(
             ConcreteTransactionStep::new(root_update),
@@ -169,31 +164,14 @@ impl UpdateStrategy for MultiStatementStrategy {
 
         let select = transformer.to_select(&abstract_update.selection, database);
 
-        let pk_column_type = table.get_pk_physical_column().unwrap().typ.get_pg_type();
-
         // Take the root step and use ids returned by it as the input to the select
         // statement to form a predicate `pk IN (update_pk1, update_pk2, ...)`
         let select_transformation = Box::new(move |transaction_context: &TransactionContext| {
-            let update_count = transaction_context.row_count(root_step_id);
-            let update_ids = SQLParamContainer::from_sql_values(
-                (0..update_count)
-                    .map(|i| transaction_context.resolve_value(root_step_id, i, 0))
-                    .collect::<Vec<_>>(),
-                pk_column_type,
-            );
-
             let predicate = Predicate::and(
-                Predicate::Eq(
-                    Column::physical(
-                        database
-                            .get_pk_column_id(abstract_update.table_id)
-                            .expect("No primary key column"),
-                        None,
-                    ),
-                    Column::ArrayParam {
-                        param: update_ids,
-                        wrapper: ArrayParamWrapper::Any,
-                    },
+                transaction_context.pk_predicate(
+                    &[root_step_id],
+                    abstract_update.table_id,
+                    database,
                 ),
                 select.predicate,
             );
@@ -229,7 +207,7 @@ fn update_op<'a>(
             false,
             database,
         ),
-        nesting_relation: nested_update.nesting_relation,
+        nesting_relation: nested_update.nesting_relation.clone(),
         column_values,
         returning: vec![],
     })
@@ -244,12 +222,12 @@ fn add_insert_steps<'a>(
 ) {
     let NestedAbstractInsert {
         insert,
-        relation_column_id,
+        relation_column_ids,
     } = nested_insert;
 
     transformer.update_transaction_script(
         insert,
-        Some((parent_step_id, *relation_column_id)),
+        Some((parent_step_id, relation_column_ids.clone())),
         database,
         transaction_script,
     );
@@ -270,7 +248,7 @@ fn delete_op<'a>(
     TemplateSQLOperation::Delete(TemplateDelete {
         table: database.get_table(nested_delete.delete.table_id),
         predicate,
-        nesting_relation: nested_delete.nesting_relation,
+        nesting_relation: nested_delete.nesting_relation.clone(),
         returning: vec![],
     })
 }
@@ -11,8 +11,10 @@ use tracing::instrument;
 
 use crate::{
     asql::{
-        abstract_operation::AbstractOperation, delete::AbstractDelete, insert::AbstractInsert,
-        select::AbstractSelect, update::AbstractUpdate,
+        abstract_operation::AbstractOperation, delete::AbstractDelete,
+        function_select::AbstractFunctionSelect, group_by_select::AbstractGroupBySelect,
+        insert::AbstractInsert, record_select::AbstractRecordSelect, select::AbstractSelect,
+        update::AbstractUpdate,
     },
     sql::{
         order::OrderBy,
@@ -44,6 +46,15 @@ impl OperationTransformer for Postgres {
             AbstractOperation::Select(select) => {
                 SelectTransformer::to_transaction_script(self, select, database)
             }
+            AbstractOperation::RecordSelect(record_select) => {
+                SelectTransformer::to_record_transaction_script(self, record_select, database)
+            }
+            AbstractOperation::FunctionSelect(function_select) => {
+                SelectTransformer::to_function_transaction_script(self, function_select, database)
+            }
+            AbstractOperation::GroupBySelect(group_by_select) => {
+                SelectTransformer::to_group_by_transaction_script(self, group_by_select, database)
+            }
             AbstractOperation::Delete(delete) => {
                 DeleteTransformer::to_transaction_script(self, delete, database)
             }
@@ -65,6 +76,24 @@ pub trait SelectTransformer {
         abstract_select: &'a AbstractSelect,
         database: &'a Database,
     ) -> TransactionScript<'a>;
+
+    fn to_record_transaction_script<'a>(
+        &self,
+        abstract_record_select: &'a AbstractRecordSelect,
+        database: &'a Database,
+    ) -> TransactionScript<'a>;
+
+    fn to_function_transaction_script<'a>(
+        &self,
+        abstract_function_select: &'a AbstractFunctionSelect,
+        database: &'a Database,
+    ) -> TransactionScript<'a>;
+
+    fn to_group_by_transaction_script<'a>(
+        &self,
+        abstract_group_by_select: &'a AbstractGroupBySelect,
+        database: &'a Database,
+    ) -> TransactionScript<'a>;
 }
 
 pub trait DeleteTransformer {
@@ -100,7 +129,7 @@ pub trait InsertTransformer {
     fn to_transaction_script<'a>(
         &self,
         abstract_insert: &'a AbstractInsert,
-        parent_step: Option<(TransactionStepId, ColumnId)>,
+        parent_step: Option<(TransactionStepId, Vec<ColumnId>)>,
         database: &'a Database,
     ) -> TransactionScript<'a> {
         let mut transaction_script = TransactionScript::default();
@@ -118,7 +147,7 @@ pub trait InsertTransformer {
     fn update_transaction_script<'a>(
         &self,
         abstract_insert: &'a AbstractInsert,
-        parent_step: Option<(TransactionStepId, ColumnId)>,
+        parent_step: Option<(TransactionStepId, Vec<ColumnId>)>,
         database: &'a Database,
         transaction_script: &mut TransactionScript<'a>,
     );